@@ -0,0 +1,214 @@
+//! FHIR R4 resources for the user directory and legacy patients
+//!
+//! Only the elements we hold are emitted; absent data is omitted rather
+//! than serialized as `null`, as FHIR requires.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use shared::domain::entities::{AdministrativeSex, LegacyPatient, User};
+
+/// Media type for FHIR JSON responses
+pub const FHIR_JSON: &str = "application/fhir+json";
+
+/// Identifier system for UUIDs, whose values are `urn:uuid:<id>`
+pub const URI_SYSTEM: &str = "urn:ietf:rfc:3986";
+/// Identifier system for user names in this directory
+pub const USERNAME_SYSTEM: &str = "urn:health-v1:sid:username";
+/// Identifier system for legacy patient internal entry numbers (DFN)
+pub const LEGACY_DFN_SYSTEM: &str = "urn:health-v1:sid:legacy-dfn";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Meta {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Identifier {
+    #[serde(rename = "use", skip_serializing_if = "Option::is_none")]
+    pub use_: Option<String>,
+    pub system: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HumanName {
+    #[serde(rename = "use", skip_serializing_if = "Option::is_none")]
+    pub use_: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub family: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub given: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactPoint {
+    pub system: String,
+    pub value: String,
+    #[serde(rename = "use", skip_serializing_if = "Option::is_none")]
+    pub use_: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "resourceType")]
+pub struct Practitioner {
+    pub id: String,
+    pub meta: Meta,
+    pub identifier: Vec<Identifier>,
+    pub active: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub telecom: Vec<ContactPoint>,
+}
+
+impl From<&User> for Practitioner {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id.to_string(),
+            meta: Meta {
+                version_id: Some(user.version.to_string()),
+                last_updated: Some(user.updated_at),
+            },
+            identifier: vec![
+                Identifier {
+                    use_: Some("official".to_string()),
+                    system: URI_SYSTEM.to_string(),
+                    value: format!("urn:uuid:{}", user.id),
+                },
+                Identifier {
+                    use_: Some("usual".to_string()),
+                    system: USERNAME_SYSTEM.to_string(),
+                    value: user.username.clone(),
+                },
+            ],
+            active: user.is_active,
+            telecom: vec![ContactPoint {
+                system: "email".to_string(),
+                value: user.email.clone(),
+                use_: Some("work".to_string()),
+            }],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "resourceType")]
+pub struct Patient {
+    pub id: String,
+    pub identifier: Vec<Identifier>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub name: Vec<HumanName>,
+    pub gender: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub birth_date: Option<NaiveDate>,
+}
+
+impl From<&LegacyPatient> for Patient {
+    fn from(patient: &LegacyPatient) -> Self {
+        let gender = match patient.sex {
+            AdministrativeSex::Male => "male",
+            AdministrativeSex::Female => "female",
+            AdministrativeSex::Unknown => "unknown",
+        };
+        Self {
+            id: patient.dfn.clone(),
+            identifier: vec![Identifier {
+                use_: Some("secondary".to_string()),
+                system: LEGACY_DFN_SYSTEM.to_string(),
+                value: patient.dfn.clone(),
+            }],
+            name: vec![HumanName {
+                use_: Some("official".to_string()),
+                family: Some(patient.family_name.clone()),
+                given: patient.given_names.clone(),
+            }],
+            gender: gender.to_string(),
+            birth_date: patient.date_of_birth,
+        }
+    }
+}
+
+/// Error details returned in place of a resource
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "resourceType")]
+pub struct OperationOutcome {
+    pub issue: Vec<OperationOutcomeIssue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperationOutcomeIssue {
+    pub severity: String,
+    /// FHIR issue type, e.g. `not-found`, `invalid`, `exception`
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<String>,
+}
+
+impl OperationOutcome {
+    pub fn error(code: &str, diagnostics: impl Into<String>) -> Self {
+        Self {
+            issue: vec![OperationOutcomeIssue {
+                severity: "error".to_string(),
+                code: code.to_string(),
+                diagnostics: Some(diagnostics.into()),
+            }],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_practitioner_from_user() {
+        let user = User::new("jane@example.com".to_string(), "jdoe".to_string(), "hash".to_string());
+        let json = serde_json::to_value(Practitioner::from(&user)).unwrap();
+
+        assert_eq!(json["resourceType"], "Practitioner");
+        assert_eq!(json["id"], user.id.to_string());
+        assert_eq!(json["identifier"][0]["value"], format!("urn:uuid:{}", user.id));
+        assert_eq!(json["identifier"][1]["system"], USERNAME_SYSTEM);
+        assert_eq!(json["telecom"][0]["system"], "email");
+        assert_eq!(json["meta"]["versionId"], user.version.to_string());
+        assert!(json.get("password_hash").is_none());
+    }
+
+    #[test]
+    fn test_patient_omits_missing_fields() {
+        let patient = LegacyPatient {
+            dfn: "42".to_string(),
+            family_name: "DOE".to_string(),
+            given_names: vec![],
+            sex: AdministrativeSex::Unknown,
+            date_of_birth: None,
+            ssn_last4: Some("6789".to_string()),
+        };
+        let json = serde_json::to_value(Patient::from(&patient)).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "resourceType": "Patient",
+                "id": "42",
+                "identifier": [{ "use": "secondary", "system": LEGACY_DFN_SYSTEM, "value": "42" }],
+                "name": [{ "use": "official", "family": "DOE" }],
+                "gender": "unknown",
+            })
+        );
+
+        let patient = LegacyPatient {
+            given_names: vec!["JANE".to_string()],
+            date_of_birth: NaiveDate::from_ymd_opt(1945, 3, 12),
+            ..patient
+        };
+        let json = serde_json::to_value(Patient::from(&patient)).unwrap();
+        assert_eq!(json["birthDate"], "1945-03-12");
+        assert_eq!(json["name"][0]["given"], serde_json::json!(["JANE"]));
+    }
+}
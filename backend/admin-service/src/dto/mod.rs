@@ -1,6 +1,10 @@
 pub mod user_dto;
 pub mod service_dto;
+pub mod fhir_dto;
+pub mod scim_dto;
 
 pub use user_dto::*;
 pub use service_dto::*;
+pub use fhir_dto::*;
+pub use scim_dto::*;
 
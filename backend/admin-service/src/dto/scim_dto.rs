@@ -0,0 +1,553 @@
+//! SCIM 2.0 (RFC 7643/7644) resources for user and group provisioning
+//!
+//! Identity providers provision users and groups through `/scim/v2`. Only
+//! the attributes we store are mapped; other attributes an IdP sends (names,
+//! phone numbers, ...) are accepted and ignored so provisioning does not fail.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use shared::domain::entities::{Group, User};
+use shared::shared::pagination::LIST_LIMIT_MAX;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Media type for SCIM responses
+pub const SCIM_JSON: &str = "application/scim+json";
+
+pub const USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+pub const GROUP_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+pub const LIST_RESPONSE_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+pub const PATCH_OP_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+pub const ERROR_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+
+/// Page size when `count` is not given
+pub const SCIM_DEFAULT_COUNT: u32 = 100;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimMeta {
+    pub resource_type: String,
+    pub created: DateTime<Utc>,
+    pub last_modified: DateTime<Utc>,
+    /// Weak ETag of the row version, e.g. `W/"3"`
+    pub version: String,
+    pub location: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScimEmail {
+    pub value: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimUser {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub user_name: String,
+    /// The IdP's own identifier; re-provisioning with it updates the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    #[serde(default)]
+    pub emails: Vec<ScimEmail>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+    /// Write-only; IdPs usually omit it for SSO users
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<ScimMeta>,
+}
+
+impl ScimUser {
+    pub fn from_user(user: &User) -> Self {
+        Self {
+            schemas: vec![USER_SCHEMA.to_string()],
+            id: Some(user.id.to_string()),
+            user_name: user.username.clone(),
+            external_id: user.external_id.clone(),
+            emails: vec![ScimEmail {
+                value: user.email.clone(),
+                kind: Some("work".to_string()),
+                primary: Some(true),
+            }],
+            active: Some(user.is_active),
+            password: None,
+            meta: Some(ScimMeta {
+                resource_type: "User".to_string(),
+                created: user.created_at,
+                last_modified: user.updated_at,
+                version: etag(user.version),
+                location: format!("/scim/v2/Users/{}", user.id),
+            }),
+        }
+    }
+
+    /// The primary email, else the first one
+    pub fn primary_email(&self) -> Option<&str> {
+        self.emails
+            .iter()
+            .find(|e| e.primary == Some(true))
+            .or_else(|| self.emails.first())
+            .map(|e| e.value.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScimMember {
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimGroup {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub display_name: String,
+    #[serde(default)]
+    pub members: Vec<ScimMember>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<ScimMeta>,
+}
+
+impl ScimGroup {
+    pub fn from_group(group: &Group, members: &[Uuid]) -> Self {
+        Self {
+            schemas: vec![GROUP_SCHEMA.to_string()],
+            id: Some(group.id.to_string()),
+            display_name: group.name.clone(),
+            members: members
+                .iter()
+                .map(|id| ScimMember { value: id.to_string(), display: None })
+                .collect(),
+            meta: Some(ScimMeta {
+                resource_type: "Group".to_string(),
+                created: group.created_at,
+                last_modified: group.updated_at,
+                version: etag(group.version),
+                location: format!("/scim/v2/Groups/{}", group.id),
+            }),
+        }
+    }
+
+    /// Member ids; non-UUID values are rejected
+    pub fn member_ids(&self) -> Result<Vec<Uuid>, ScimError> {
+        self.members.iter().map(|m| parse_member(&m.value)).collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimListResponse<T> {
+    pub schemas: Vec<String>,
+    pub total_results: i64,
+    pub start_index: u64,
+    pub items_per_page: usize,
+    #[serde(rename = "Resources")]
+    pub resources: Vec<T>,
+}
+
+impl<T> ScimListResponse<T> {
+    pub fn new(resources: Vec<T>, total_results: i64, start_index: u64) -> Self {
+        Self {
+            schemas: vec![LIST_RESPONSE_SCHEMA.to_string()],
+            total_results,
+            start_index,
+            items_per_page: resources.len(),
+            resources,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimError {
+    pub schemas: Vec<String>,
+    /// HTTP status as a string, per RFC 7644 §3.12
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scim_type: Option<String>,
+    pub detail: String,
+}
+
+impl ScimError {
+    pub fn new(status: u16, scim_type: Option<&str>, detail: impl Into<String>) -> Self {
+        Self {
+            schemas: vec![ERROR_SCHEMA.to_string()],
+            status: status.to_string(),
+            scim_type: scim_type.map(str::to_string),
+            detail: detail.into(),
+        }
+    }
+
+    pub fn invalid_value(detail: impl Into<String>) -> Self {
+        Self::new(400, Some("invalidValue"), detail)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScimPatchRequest {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    #[serde(rename = "Operations")]
+    pub operations: Vec<ScimPatchOperation>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScimPatchOperation {
+    pub op: String,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub value: Option<Value>,
+}
+
+/// `startIndex` (1-based), `count` and an optional `attr eq "value"` filter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScimListParams {
+    pub start_index: u64,
+    pub count: u32,
+    pub filter: Option<(String, String)>,
+}
+
+impl ScimListParams {
+    pub fn from_params(params: &HashMap<String, String>) -> Result<Self, ScimError> {
+        // Out-of-range values are clamped, as RFC 7644 §3.4.2.4 asks
+        let start_index = params
+            .get("startIndex")
+            .map(|v| v.parse::<i64>().map_err(|_| ScimError::invalid_value("startIndex must be an integer")))
+            .transpose()?
+            .unwrap_or(1)
+            .max(1) as u64;
+        let count = params
+            .get("count")
+            .map(|v| v.parse::<i64>().map_err(|_| ScimError::invalid_value("count must be an integer")))
+            .transpose()?
+            .unwrap_or(SCIM_DEFAULT_COUNT as i64)
+            .clamp(0, LIST_LIMIT_MAX as i64) as u32;
+        let filter = params.get("filter").map(|f| parse_filter(f)).transpose()?;
+        Ok(Self { start_index, count, filter })
+    }
+
+    /// Rows to skip
+    pub fn offset(&self) -> u64 {
+        self.start_index - 1
+    }
+}
+
+/// Parse `attribute eq "value"`, the only filter we support
+pub fn parse_filter(raw: &str) -> Result<(String, String), ScimError> {
+    let invalid = || {
+        ScimError::new(
+            400,
+            Some("invalidFilter"),
+            format!("unsupported filter '{}'; use attribute eq \"value\"", raw),
+        )
+    };
+    let mut parts = raw.trim().splitn(3, ' ');
+    let (Some(attribute), Some(operator), Some(value)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    if !operator.eq_ignore_ascii_case("eq") {
+        return Err(invalid());
+    }
+    let value = value.trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or_else(invalid)?
+        .replace("\\\"", "\"");
+    Ok((attribute.to_string(), value))
+}
+
+/// Changes requested by a user PATCH
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserPatch {
+    pub user_name: Option<String>,
+    pub email: Option<String>,
+    pub active: Option<bool>,
+    pub password: Option<String>,
+}
+
+impl UserPatch {
+    pub fn from_operations(operations: &[ScimPatchOperation]) -> Result<Self, ScimError> {
+        let mut patch = Self::default();
+        for operation in operations {
+            let op = operation.op.to_ascii_lowercase();
+            match (op.as_str(), operation.path.as_deref()) {
+                ("add" | "replace", None) => {
+                    let Some(Value::Object(attributes)) = &operation.value else {
+                        return Err(ScimError::invalid_value("a patch without path needs an object value"));
+                    };
+                    for (name, value) in attributes {
+                        patch.set(name, value)?;
+                    }
+                }
+                ("add" | "replace", Some(path)) => {
+                    let value = operation
+                        .value
+                        .as_ref()
+                        .ok_or_else(|| ScimError::invalid_value(format!("no value for '{}'", path)))?;
+                    patch.set(path, value)?;
+                }
+                ("remove", Some(path)) if is_user_attribute(path) => {
+                    return Err(ScimError::new(400, Some("mutability"), format!("'{}' cannot be removed", path)));
+                }
+                // Attributes we do not store
+                ("remove", _) => {}
+                _ => return Err(ScimError::invalid_value(format!("unsupported patch op '{}'", operation.op))),
+            }
+        }
+        Ok(patch)
+    }
+
+    fn set(&mut self, path: &str, value: &Value) -> Result<(), ScimError> {
+        let attribute = path.to_ascii_lowercase();
+        if attribute == "username" {
+            self.user_name = Some(string_value(path, value)?);
+        } else if attribute == "active" {
+            self.active = Some(bool_value(path, value)?);
+        } else if attribute == "password" {
+            self.password = Some(string_value(path, value)?);
+        } else if attribute == "emails" {
+            let emails: Vec<ScimEmail> = serde_json::from_value(value.clone())
+                .map_err(|_| ScimError::invalid_value("emails must be a list of {value} objects"))?;
+            let primary = emails.iter().find(|e| e.primary == Some(true)).or(emails.first());
+            self.email = primary.map(|e| e.value.clone());
+        } else if attribute.starts_with("emails[") && attribute.ends_with("].value") {
+            // e.g. emails[type eq "work"].value; we hold a single email
+            self.email = Some(string_value(path, value)?);
+        }
+        Ok(())
+    }
+}
+
+fn is_user_attribute(path: &str) -> bool {
+    let attribute = path.to_ascii_lowercase();
+    matches!(attribute.as_str(), "username" | "active" | "emails") || attribute.starts_with("emails[")
+}
+
+/// Changes requested by a group PATCH
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GroupPatch {
+    pub display_name: Option<String>,
+    /// Replaces the member list before `add_members`/`remove_members` apply
+    pub replace_members: Option<Vec<Uuid>>,
+    pub add_members: Vec<Uuid>,
+    pub remove_members: Vec<Uuid>,
+}
+
+impl GroupPatch {
+    pub fn from_operations(operations: &[ScimPatchOperation]) -> Result<Self, ScimError> {
+        let mut patch = Self::default();
+        for operation in operations {
+            let op = operation.op.to_ascii_lowercase();
+            let path = operation.path.as_deref().map(str::to_ascii_lowercase);
+            match (op.as_str(), path.as_deref()) {
+                ("add" | "replace", None) => {
+                    let Some(Value::Object(attributes)) = &operation.value else {
+                        return Err(ScimError::invalid_value("a patch without path needs an object value"));
+                    };
+                    for (name, value) in attributes {
+                        match name.to_ascii_lowercase().as_str() {
+                            "displayname" => patch.display_name = Some(string_value(name, value)?),
+                            "members" if op == "add" => patch.add_members.extend(member_values(value)?),
+                            "members" => patch.replace_members = Some(member_values(value)?),
+                            _ => {}
+                        }
+                    }
+                }
+                ("add" | "replace", Some("displayname")) => {
+                    let value = operation
+                        .value
+                        .as_ref()
+                        .ok_or_else(|| ScimError::invalid_value("no value for displayName"))?;
+                    patch.display_name = Some(string_value("displayName", value)?);
+                }
+                ("add", Some("members")) => {
+                    patch.add_members.extend(member_values(operation.value.as_ref().unwrap_or(&Value::Null))?)
+                }
+                ("replace", Some("members")) => {
+                    patch.replace_members = Some(member_values(operation.value.as_ref().unwrap_or(&Value::Null))?)
+                }
+                // Azure AD sends the members to remove as the value
+                ("remove", Some("members")) => match &operation.value {
+                    Some(value) => patch.remove_members.extend(member_values(value)?),
+                    None => patch.replace_members = Some(Vec::new()),
+                },
+                ("remove", Some(path)) if path.starts_with("members[") && path.ends_with(']') => {
+                    let (attribute, value) = parse_filter(&path["members[".len()..path.len() - 1])?;
+                    if attribute != "value" {
+                        return Err(ScimError::new(400, Some("invalidPath"), "members can only be selected by value"));
+                    }
+                    patch.remove_members.push(parse_member(&value)?);
+                }
+                ("remove", Some("displayname")) => {
+                    return Err(ScimError::new(400, Some("mutability"), "displayName cannot be removed"));
+                }
+                ("remove", _) => {}
+                _ => return Err(ScimError::invalid_value(format!("unsupported patch op '{}'", operation.op))),
+            }
+        }
+        Ok(patch)
+    }
+}
+
+fn member_values(value: &Value) -> Result<Vec<Uuid>, ScimError> {
+    let members: Vec<ScimMember> = serde_json::from_value(value.clone())
+        .map_err(|_| ScimError::invalid_value("members must be a list of {value} objects"))?;
+    members.iter().map(|m| parse_member(&m.value)).collect()
+}
+
+fn parse_member(value: &str) -> Result<Uuid, ScimError> {
+    Uuid::parse_str(value).map_err(|_| ScimError::invalid_value(format!("member '{}' is not a user id", value)))
+}
+
+fn string_value(path: &str, value: &Value) -> Result<String, ScimError> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| ScimError::invalid_value(format!("'{}' must be a string", path)))
+}
+
+/// Booleans, or the strings `"True"`/`"False"` some IdPs send
+fn bool_value(path: &str, value: &Value) -> Result<bool, ScimError> {
+    match value {
+        Value::Bool(b) => Ok(*b),
+        Value::String(s) if s.eq_ignore_ascii_case("true") => Ok(true),
+        Value::String(s) if s.eq_ignore_ascii_case("false") => Ok(false),
+        _ => Err(ScimError::invalid_value(format!("'{}' must be a boolean", path))),
+    }
+}
+
+/// Weak ETag for a row version
+pub fn etag(version: i64) -> String {
+    format!("W/\"{}\"", version)
+}
+
+/// Row version from an `If-Match` ETag
+pub fn version_from_etag(etag: &str) -> Option<i64> {
+    etag.trim().trim_start_matches("W/").trim_matches('"').parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn operations(value: Value) -> Vec<ScimPatchOperation> {
+        serde_json::from_value::<ScimPatchRequest>(value).unwrap().operations
+    }
+
+    #[test]
+    fn test_user_resource_shape() {
+        let user = User::new("jane@example.com".to_string(), "jdoe".to_string(), "hash".to_string());
+        let json = serde_json::to_value(ScimUser::from_user(&user)).unwrap();
+
+        assert_eq!(json["schemas"], json!([USER_SCHEMA]));
+        assert_eq!(json["userName"], "jdoe");
+        assert_eq!(json["emails"][0], json!({ "value": "jane@example.com", "type": "work", "primary": true }));
+        assert_eq!(json["meta"]["resourceType"], "User");
+        assert_eq!(json["meta"]["version"], format!("W/\"{}\"", user.version));
+        assert!(json.get("password").is_none());
+        assert!(json.get("externalId").is_none());
+
+        let mut provisioned = user.clone();
+        provisioned.external_id = Some("00u1abcd".to_string());
+        let json = serde_json::to_value(ScimUser::from_user(&provisioned)).unwrap();
+        assert_eq!(json["externalId"], "00u1abcd");
+    }
+
+    #[test]
+    fn test_list_params() {
+        let params = |pairs: &[(&str, &str)]| pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+
+        let parsed = ScimListParams::from_params(&params(&[
+            ("startIndex", "11"),
+            ("count", "10"),
+            ("filter", "userName eq \"jdoe@example.com\""),
+        ]))
+        .unwrap();
+        assert_eq!(parsed.offset(), 10);
+        assert_eq!(parsed.count, 10);
+        assert_eq!(parsed.filter, Some(("userName".to_string(), "jdoe@example.com".to_string())));
+
+        let clamped = ScimListParams::from_params(&params(&[("startIndex", "0"), ("count", "100000")])).unwrap();
+        assert_eq!((clamped.start_index, clamped.count), (1, LIST_LIMIT_MAX));
+
+        let error = ScimListParams::from_params(&params(&[("filter", "userName co \"j\"")])).unwrap_err();
+        assert_eq!(error.scim_type.as_deref(), Some("invalidFilter"));
+    }
+
+    #[test]
+    fn test_user_patch_okta_and_azure_styles() {
+        let okta = UserPatch::from_operations(&operations(json!({
+            "schemas": [PATCH_OP_SCHEMA],
+            "Operations": [{ "op": "replace", "value": { "active": false, "name": { "givenName": "J" } } }]
+        })))
+        .unwrap();
+        assert_eq!(okta, UserPatch { active: Some(false), ..Default::default() });
+
+        let azure = UserPatch::from_operations(&operations(json!({
+            "Operations": [
+                { "op": "Replace", "path": "active", "value": "False" },
+                { "op": "Replace", "path": "emails[type eq \"work\"].value", "value": "new@example.com" },
+                { "op": "Add", "path": "userName", "value": "jane" }
+            ]
+        })))
+        .unwrap();
+        assert_eq!(azure.active, Some(false));
+        assert_eq!(azure.email.as_deref(), Some("new@example.com"));
+        assert_eq!(azure.user_name.as_deref(), Some("jane"));
+
+        assert!(UserPatch::from_operations(&operations(json!({
+            "Operations": [{ "op": "remove", "path": "userName" }]
+        })))
+        .is_err());
+    }
+
+    #[test]
+    fn test_group_patch_members() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let patch = GroupPatch::from_operations(&operations(json!({
+            "Operations": [
+                { "op": "add", "path": "members", "value": [{ "value": a.to_string() }] },
+                { "op": "remove", "path": format!("members[value eq \"{}\"]", b) },
+                { "op": "replace", "path": "displayName", "value": "Nurses" }
+            ]
+        })))
+        .unwrap();
+        assert_eq!(patch.add_members, vec![a]);
+        assert_eq!(patch.remove_members, vec![b]);
+        assert_eq!(patch.display_name.as_deref(), Some("Nurses"));
+
+        let cleared = GroupPatch::from_operations(&operations(json!({
+            "Operations": [{ "op": "remove", "path": "members" }]
+        })))
+        .unwrap();
+        assert_eq!(cleared.replace_members, Some(vec![]));
+
+        assert!(GroupPatch::from_operations(&operations(json!({
+            "Operations": [{ "op": "add", "path": "members", "value": [{ "value": "not-a-uuid" }] }]
+        })))
+        .is_err());
+    }
+
+    #[test]
+    fn test_etag_round_trip() {
+        assert_eq!(version_from_etag(&etag(7)), Some(7));
+        assert_eq!(version_from_etag("\"3\""), Some(3));
+        assert_eq!(version_from_etag("*"), None);
+    }
+}
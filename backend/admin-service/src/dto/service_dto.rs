@@ -1,4 +1,12 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+use shared::infrastructure::api::concurrency::ConcurrencySnapshot;
+use shared::shared::validated_json::not_blank;
+
+/// Longest lifetime an API key can be created with
+pub const MAX_API_KEY_LIFETIME_DAYS: u32 = 3650;
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -6,6 +14,9 @@ pub struct ServiceStatusResponse {
     pub services: Vec<ServiceInfo>,
     pub overall_status: String, // "operational", "degraded", "down"
     pub checked_at: String,
+    /// In-flight requests of the reporting service against its limits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<ConcurrencySnapshot>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,3 +33,61 @@ pub struct ServiceInfo {
     pub error: Option<String>,
 }
 
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiKeyRequest {
+    #[validate(custom(function = "not_blank"), length(max = 255, message = "must be at most 255 characters"))]
+    pub name: String,
+    /// Role the key acts as; must be one of the service account's roles
+    #[validate(custom(function = "not_blank"))]
+    pub role: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1, max = MAX_API_KEY_LIFETIME_DAYS, message = "must be between 1 and 3650"))]
+    pub expires_in_days: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub key_prefix: String,
+    pub role_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<shared::domain::entities::ApiKey> for ApiKeyResponse {
+    fn from(api_key: shared::domain::entities::ApiKey) -> Self {
+        Self {
+            id: api_key.id,
+            name: api_key.name,
+            key_prefix: api_key.key_prefix,
+            role_id: api_key.role_id,
+            expires_at: api_key.expires_at,
+            last_used_at: api_key.last_used_at,
+            revoked_at: api_key.revoked_at,
+            created_at: api_key.created_at,
+        }
+    }
+}
+
+/// Returned once on creation; `key` cannot be retrieved again
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiKeyResponse {
+    pub key: String,
+    pub api_key: ApiKeyResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyListResponse {
+    pub api_keys: Vec<ApiKeyResponse>,
+}
@@ -1,18 +1,35 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use validator::Validate;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreateUserRequest {
+    #[validate(email)]
     pub email: String,
+    #[validate(length(min = 1, max = 255, message = "must be 1-255 characters"))]
     pub username: String,
+    #[validate(length(min = 8, message = "must be at least 8 characters"))]
     pub password: String,
+    /// Identity in a provisioning source; makes the create idempotent
+    #[serde(default)]
+    #[validate(length(min = 1, max = 255, message = "must be 1-255 characters"))]
+    pub external_id: Option<String>,
+    /// Create a non-human account that can be issued API keys
+    #[serde(default)]
+    pub is_service_account: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct UpdateUserRequest {
+    #[validate(email)]
     pub email: Option<String>,
+    #[validate(length(min = 1, max = 255, message = "must be 1-255 characters"))]
     pub username: Option<String>,
+    #[validate(length(min = 8, message = "must be at least 8 characters"))]
     pub password: Option<String>,
+    /// Version the client last read; the update is rejected with a
+    /// conflict if the user has changed since
+    pub version: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,7 +39,10 @@ pub struct UserResponse {
     pub username: String,
     pub is_active: bool,
     pub is_verified: bool,
+    pub external_id: Option<String>,
+    pub is_service_account: bool,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    pub version: i64,
 }
 
 impl From<shared::domain::entities::User> for UserResponse {
@@ -33,7 +53,10 @@ impl From<shared::domain::entities::User> for UserResponse {
             username: user.username,
             is_active: user.is_active,
             is_verified: user.is_verified,
+            external_id: user.external_id,
+            is_service_account: user.is_service_account,
             created_at: user.created_at,
+            version: user.version,
         }
     }
 }
@@ -0,0 +1,68 @@
+//! Shared-secret authentication for gRPC calls
+//!
+//! Callers send `authorization: Bearer <token>` metadata matching
+//! `GRPC_AUTH_TOKEN`.
+
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+#[derive(Clone)]
+pub struct BearerAuth {
+    token: Option<String>,
+}
+
+impl BearerAuth {
+    /// Require `token` on every call; `None` admits every caller, for a
+    /// port only reachable from loopback
+    pub fn new(token: Option<String>) -> Self {
+        Self { token }
+    }
+}
+
+impl Interceptor for BearerAuth {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let Some(expected) = &self.token else {
+            return Ok(request);
+        };
+        let presented = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match presented {
+            Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => Ok(request),
+            _ => Err(Status::unauthenticated("missing or invalid gRPC credentials")),
+        }
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(authorization: Option<&str>) -> Request<()> {
+        let mut request = Request::new(());
+        if let Some(value) = authorization {
+            request.metadata_mut().insert("authorization", value.parse().unwrap());
+        }
+        request
+    }
+
+    #[test]
+    fn test_calls_need_the_configured_token() {
+        let mut auth = BearerAuth::new(Some("s3cret".to_string()));
+        assert!(auth.call(request(Some("Bearer s3cret"))).is_ok());
+        assert!(auth.call(request(Some("Bearer wrong"))).is_err());
+        assert!(auth.call(request(Some("s3cret"))).is_err());
+        assert!(auth.call(request(None)).is_err());
+    }
+
+    #[test]
+    fn test_no_token_admits_every_call() {
+        assert!(BearerAuth::new(None).call(request(None)).is_ok());
+    }
+}
@@ -0,0 +1,16 @@
+//! gRPC transport for internal service-to-service calls
+//!
+//! Served next to the axum HTTP API on its own port and backed by the same
+//! `AppState`. Calls carry a shared bearer token (see [`BearerAuth`]); the
+//! port binds to loopback unless configured otherwise.
+
+pub mod auth;
+pub mod permission_service;
+
+pub use auth::BearerAuth;
+pub use permission_service::PermissionGrpcService;
+
+/// Generated protobuf types and service stubs for `authz.v1`
+pub mod proto {
+    tonic::include_proto!("authz.v1");
+}
@@ -0,0 +1,187 @@
+use super::proto::{
+    permission_service_server::{PermissionService, PermissionServiceServer},
+    BatchCheckRequest, BatchCheckResponse, CheckRequest, CheckResponse, ExpandRequest,
+    ExpandResponse, Relationship, UsersetNode, WatchRequest, WatchResponse,
+};
+use shared::infrastructure::repositories::RelationshipRepositoryImpl;
+use shared::infrastructure::zanzibar::{self, GraphPermissionChecker};
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio_stream::Stream;
+use tonic::service::interceptor::InterceptedService;
+use tonic::{Request, Response, Status};
+use super::BearerAuth;
+
+// Type aliases for convenience
+type ConcreteAppState = shared::AppState<
+    authz_core::auth::LoginUseCase,
+    authz_core::auth::RefreshTokenUseCase,
+    authz_core::auth::LogoutUseCase,
+    authz_core::auth::UserInfoUseCase,
+    crate::use_cases::setup::SetupOrganizationUseCase,
+    crate::use_cases::setup::CreateSuperAdminUseCase,
+>;
+
+/// Upper bound on relationships in one `BatchCheck`
+const MAX_BATCH_CHECKS: usize = 1000;
+
+/// `authz.v1.PermissionService` backed by the authorization graph
+///
+/// Checks use the cached `GraphPermissionChecker` when the graph cache is
+/// enabled and fall back to the database-backed `PermissionChecker`
+/// otherwise. `Expand` needs the graph and is unavailable without it.
+pub struct PermissionGrpcService {
+    state: Arc<ConcreteAppState>,
+}
+
+impl PermissionGrpcService {
+    pub fn new(state: Arc<ConcreteAppState>) -> Self {
+        Self { state }
+    }
+
+    /// Wrap in the tonic server type for `tonic::transport::Server::add_service`,
+    /// checking every call with `auth`
+    pub fn into_server(self, auth: BearerAuth) -> InterceptedService<PermissionServiceServer<Self>, BearerAuth> {
+        PermissionServiceServer::with_interceptor(self, auth)
+    }
+
+    async fn graph_checker(&self) -> Result<Option<GraphPermissionChecker>, Status> {
+        let Some(cache) = &self.state.graph_cache else {
+            return Ok(None);
+        };
+        let repository = RelationshipRepositoryImpl::new(self.state.database_pool.as_ref().clone());
+        let graph = cache
+            .get_or_build(&repository)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to build graph: {}", e)))?;
+        Ok(Some(GraphPermissionChecker::new(graph).with_schema(self.state.permission_checker.schema())))
+    }
+}
+
+fn validate(relationship: Option<Relationship>) -> Result<(String, String, String), Status> {
+    let relationship = relationship.ok_or_else(|| Status::invalid_argument("relationship is required"))?;
+    for (field, value) in [
+        ("subject", &relationship.subject),
+        ("relation", &relationship.relation),
+        ("object", &relationship.object),
+    ] {
+        if value.trim().is_empty() {
+            return Err(Status::invalid_argument(format!("{} is required", field)));
+        }
+    }
+    Ok((relationship.subject, relationship.relation, relationship.object))
+}
+
+fn to_proto(node: zanzibar::UsersetNode) -> UsersetNode {
+    UsersetNode {
+        subject: node.subject,
+        relation: node.relation,
+        children: node.children.into_iter().map(to_proto).collect(),
+    }
+}
+
+#[tonic::async_trait]
+impl PermissionService for PermissionGrpcService {
+    async fn check(&self, request: Request<CheckRequest>) -> Result<Response<CheckResponse>, Status> {
+        let (subject, relation, object) = validate(request.into_inner().relationship)?;
+
+        let allowed = match self.graph_checker().await? {
+            Some(checker) => checker.check(&subject, &relation, &object),
+            None => self.state.permission_checker.check(&subject, &relation, &object).await,
+        }
+        .map_err(|e| Status::internal(format!("Failed to check permission: {}", e)))?;
+
+        Ok(Response::new(CheckResponse { allowed }))
+    }
+
+    async fn batch_check(
+        &self,
+        request: Request<BatchCheckRequest>,
+    ) -> Result<Response<BatchCheckResponse>, Status> {
+        let relationships = request.into_inner().relationships;
+        if relationships.len() > MAX_BATCH_CHECKS {
+            return Err(Status::invalid_argument(format!(
+                "at most {} relationships per batch",
+                MAX_BATCH_CHECKS
+            )));
+        }
+        let checks = relationships
+            .into_iter()
+            .map(|r| validate(Some(r)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let allowed = match self.graph_checker().await? {
+            Some(checker) => checker.check_batch(checks),
+            None => self.state.permission_checker.check_batch(checks).await,
+        }
+        .map_err(|e| Status::internal(format!("Failed to check permissions: {}", e)))?;
+
+        Ok(Response::new(BatchCheckResponse { allowed }))
+    }
+
+    async fn expand(&self, request: Request<ExpandRequest>) -> Result<Response<ExpandResponse>, Status> {
+        let request = request.into_inner();
+        if request.relation.trim().is_empty() || request.object.trim().is_empty() {
+            return Err(Status::invalid_argument("relation and object are required"));
+        }
+
+        let checker = self
+            .graph_checker()
+            .await?
+            .ok_or_else(|| Status::unavailable("Graph cache not enabled"))?;
+        let root = checker
+            .expand(&request.relation, &request.object)
+            .map_err(|e| Status::internal(format!("Failed to expand: {}", e)))?;
+
+        Ok(Response::new(ExpandResponse { root: Some(to_proto(root)) }))
+    }
+
+    type WatchStream = Pin<Box<dyn Stream<Item = Result<WatchResponse, Status>> + Send + 'static>>;
+
+    async fn watch(&self, _request: Request<WatchRequest>) -> Result<Response<Self::WatchStream>, Status> {
+        Err(Status::unimplemented("Watch is not implemented yet"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relationship(subject: &str, relation: &str, object: &str) -> Relationship {
+        Relationship {
+            subject: subject.to_string(),
+            relation: relation.to_string(),
+            object: object.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_validate_requires_every_field() {
+        assert!(validate(None).is_err());
+        let err = validate(Some(relationship("user:1", " ", "app:admin"))).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        assert_eq!(
+            validate(Some(relationship("user:1", "view", "app:admin"))).unwrap(),
+            ("user:1".to_string(), "view".to_string(), "app:admin".to_string())
+        );
+    }
+
+    #[test]
+    fn test_userset_tree_converts_recursively() {
+        let node = zanzibar::UsersetNode {
+            subject: "document:1".to_string(),
+            relation: "view".to_string(),
+            children: vec![zanzibar::UsersetNode {
+                subject: "group:doctors".to_string(),
+                relation: "view".to_string(),
+                children: vec![zanzibar::UsersetNode {
+                    subject: "user:bob".to_string(),
+                    relation: "member".to_string(),
+                    children: Vec::new(),
+                }],
+            }],
+        };
+        let proto = to_proto(node);
+        assert_eq!(proto.children[0].children[0].subject, "user:bob");
+    }
+}
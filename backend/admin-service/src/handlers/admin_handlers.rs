@@ -1,13 +1,261 @@
-use axum::Json;
-use shared::AppResult;
+use axum::{Json, extract::{Path, Query, State}, http::StatusCode, response::{IntoResponse, Response}};
+use serde::{Deserialize, Serialize};
+use shared::domain::repositories::{
+    LoginEventRepository, PermissionRepository, RequestLogRepository, RoleRepository, UserRepository,
+};
+use shared::infrastructure::repositories::{
+    LoginEventRepositoryImpl, PermissionRepositoryImpl, RequestLogRepositoryImpl, RoleRepositoryImpl,
+    UserRepositoryImpl,
+};
+use shared::{AppError, AppResult, ListQuery, Page, RequestContext};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use crate::dto::UserResponse;
 
-pub async fn list_users() -> AppResult<Json<serde_json::Value>> {
-    // TODO: Implement admin user list
-    Ok(Json(serde_json::json!([])))
+// Type aliases for convenience
+type ConcreteAppState = shared::AppState<
+    authz_core::auth::LoginUseCase,
+    authz_core::auth::RefreshTokenUseCase,
+    authz_core::auth::LogoutUseCase,
+    authz_core::auth::UserInfoUseCase,
+    crate::use_cases::setup::SetupOrganizationUseCase,
+    crate::use_cases::setup::CreateSuperAdminUseCase,
+>;
+
+/// Body of role and permission updates
+#[derive(Debug, Deserialize)]
+pub struct UpdateDescriptionRequest {
+    pub description: Option<String>,
+    /// Version the client last read
+    pub version: i64,
+}
+
+/// Body of role creation
+#[derive(Debug, Deserialize)]
+pub struct CreateRoleRequest {
+    pub name: String,
+    pub description: Option<String>,
+    /// Permission ids granted to the role
+    #[serde(default)]
+    pub permissions: Vec<Uuid>,
+}
+
+/// Render a use-case error with the status the client should act on
+///
+/// A `VersionConflict` becomes 409 with the current version, so the client
+/// can refetch and reapply its edit instead of overwriting someone else's.
+pub(crate) fn error_response(error: AppError, location: &str, operation: &str) -> Response {
+    let status = match &error {
+        AppError::Validation(_) => StatusCode::BAD_REQUEST,
+        AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        AppError::VersionConflict { .. } => StatusCode::CONFLICT,
+        AppError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        AppError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let body = match &error {
+        AppError::Validation(message) | AppError::NotFound(message) => {
+            serde_json::json!({ "error": message })
+        }
+        AppError::VersionConflict { entity, id, expected_version, current_version } => {
+            serde_json::json!({
+                "error": format!("{} was modified by someone else; refetch and try again", entity),
+                "code": "version_conflict",
+                "id": id,
+                "expected_version": expected_version,
+                "current_version": current_version,
+            })
+        }
+        other => {
+            other.log_with_operation(location, operation);
+            serde_json::json!({
+                "error": format!("Failed to {}: {}", operation.replace('_', " "), other)
+            })
+        }
+    };
+    (status, Json(body)).into_response()
+}
+
+/// Render a list result as a [`Page`] envelope
+pub(crate) fn page_response<T: Serialize>(
+    result: AppResult<Page<T>>,
+    location: &str,
+    operation: &str,
+) -> Response {
+    match result {
+        Ok(page) => (StatusCode::OK, Json(page)).into_response(),
+        Err(e) => error_response(e, location, operation),
+    }
+}
+
+/// List users
+pub async fn list_users(
+    State(state): State<Arc<ConcreteAppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let user_repository = UserRepositoryImpl::new(state.database_service.clone());
+    let location = concat!(file!(), ":", line!());
+    let result = match ListQuery::from_params(&params) {
+        Ok(query) => user_repository.list_page(&query).await,
+        Err(e) => Err(e),
+    };
+    page_response(result.map(|page| page.map(UserResponse::from)), location, "list_users")
+}
+
+/// List roles with their permission ids
+pub async fn list_roles(
+    State(state): State<Arc<ConcreteAppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let permission_repo = Arc::new(PermissionRepositoryImpl::new(state.database_pool.as_ref().clone()));
+    let role_repository = RoleRepositoryImpl::new(
+        state.database_service.clone(),
+        state.relationship_store.clone(),
+        permission_repo,
+    );
+    let location = concat!(file!(), ":", line!());
+    let result = match ListQuery::from_params(&params) {
+        Ok(query) => role_repository.list_page(&query).await,
+        Err(e) => Err(e),
+    };
+    page_response(result, location, "list_roles")
+}
+
+fn role_repository(state: &ConcreteAppState) -> Box<dyn RoleRepository> {
+    let permission_repo = Arc::new(PermissionRepositoryImpl::new(state.database_pool.as_ref().clone()));
+    Box::new(RoleRepositoryImpl::new(
+        state.database_service.clone(),
+        state.relationship_store.clone(),
+        permission_repo,
+    ))
+}
+
+/// Create a role, with its vault policy when role policy sync is enabled
+pub async fn create_role(
+    State(state): State<Arc<ConcreteAppState>>,
+    context: RequestContext,
+    Json(request): Json<CreateRoleRequest>,
+) -> impl IntoResponse {
+    use crate::use_cases::role::{CreateRoleUseCase, RolePolicySync};
+
+    let location = concat!(file!(), ":", line!());
+    let policy_sync = match RolePolicySync::from_env() {
+        Ok(policy_sync) => policy_sync.map(Arc::new),
+        Err(e) => return error_response(e, location, "create_role"),
+    };
+    let use_case = CreateRoleUseCase::new(role_repository(&state), policy_sync);
+    match use_case.execute(&request.name, request.description, request.permissions, &context).await {
+        Ok(role) => (StatusCode::CREATED, Json(role)).into_response(),
+        Err(e) => error_response(e, location, "create_role"),
+    }
+}
+
+/// Delete a role, with its vault policy when role policy sync is enabled
+pub async fn delete_role(
+    State(state): State<Arc<ConcreteAppState>>,
+    context: RequestContext,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    use crate::use_cases::role::{DeleteRoleUseCase, RolePolicySync};
+
+    let location = concat!(file!(), ":", line!());
+    let policy_sync = match RolePolicySync::from_env() {
+        Ok(policy_sync) => policy_sync.map(Arc::new),
+        Err(e) => return error_response(e, location, "delete_role"),
+    };
+    let use_case = DeleteRoleUseCase::new(role_repository(&state), policy_sync);
+    match use_case.execute(id, &context).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => error_response(e, location, "delete_role"),
+    }
+}
+
+/// Update a role's description
+pub async fn update_role(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateDescriptionRequest>,
+) -> impl IntoResponse {
+    use crate::use_cases::role::UpdateRoleUseCase;
+
+    let permission_repo = Arc::new(PermissionRepositoryImpl::new(state.database_pool.as_ref().clone()));
+    let role_repository = Box::new(RoleRepositoryImpl::new(
+        state.database_service.clone(),
+        state.relationship_store.clone(),
+        permission_repo,
+    ));
+    let use_case = UpdateRoleUseCase::new(role_repository);
+
+    let location = concat!(file!(), ":", line!());
+    match use_case.execute(id, request.description, request.version).await {
+        Ok(role) => (StatusCode::OK, Json(role)).into_response(),
+        Err(e) => error_response(e, location, "update_role"),
+    }
+}
+
+/// List permission definitions
+pub async fn list_permissions(
+    State(state): State<Arc<ConcreteAppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let permission_repository = PermissionRepositoryImpl::new(state.database_pool.as_ref().clone());
+    let location = concat!(file!(), ":", line!());
+    let result = match ListQuery::from_params(&params) {
+        Ok(query) => permission_repository.list_page(&query).await,
+        Err(e) => Err(e),
+    };
+    page_response(result, location, "list_permissions")
+}
+
+/// Update a permission definition's description
+pub async fn update_permission(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateDescriptionRequest>,
+) -> impl IntoResponse {
+    use crate::use_cases::permission::UpdatePermissionUseCase;
+
+    let permission_repository = Box::new(PermissionRepositoryImpl::new(state.database_pool.as_ref().clone()));
+    let use_case = UpdatePermissionUseCase::new(permission_repository);
+
+    let location = concat!(file!(), ":", line!());
+    match use_case.execute(id, request.description, request.version).await {
+        Ok(permission) => (StatusCode::OK, Json(permission)).into_response(),
+        Err(e) => error_response(e, location, "update_permission"),
+    }
+}
+
+/// List logged requests, newest first by default
+pub async fn list_request_logs(
+    State(state): State<Arc<ConcreteAppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let request_log_repository = RequestLogRepositoryImpl::new(state.database_service.clone());
+    let location = concat!(file!(), ":", line!());
+    let result = match ListQuery::from_params(&params) {
+        Ok(query) => request_log_repository.list_page(&query).await,
+        Err(e) => Err(e),
+    };
+    page_response(result, location, "list_request_logs")
+}
+
+/// List login attempts, newest first by default; filter by `succeeded`,
+/// `failure_reason`, `email`, `ip_address` and more
+pub async fn list_login_events(
+    State(state): State<Arc<ConcreteAppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let login_event_repository = LoginEventRepositoryImpl::new(state.database_service.clone());
+    let location = concat!(file!(), ":", line!());
+    let result = match ListQuery::from_params(&params) {
+        Ok(query) => login_event_repository.list_page(&query).await,
+        Err(e) => Err(e),
+    };
+    page_response(result, location, "list_login_events")
 }
 
 pub async fn get_audit_logs() -> AppResult<Json<serde_json::Value>> {
     // TODO: Implement audit log retrieval
     Ok(Json(serde_json::json!([])))
 }
-
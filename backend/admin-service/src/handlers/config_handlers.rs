@@ -0,0 +1,54 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use shared::config::ReloadError;
+use std::sync::Arc;
+
+// Type aliases for convenience
+type ConcreteAppState = shared::AppState<
+    authz_core::auth::LoginUseCase,
+    authz_core::auth::RefreshTokenUseCase,
+    authz_core::auth::LogoutUseCase,
+    authz_core::auth::UserInfoUseCase,
+    crate::use_cases::setup::SetupOrganizationUseCase,
+    crate::use_cases::setup::CreateSuperAdminUseCase,
+>;
+
+/// Re-read configuration and apply the reloadable (runtime) settings
+///
+/// Same as sending SIGHUP. Rejected with 409 when a fixed setting changed,
+/// since those need a restart.
+pub async fn reload_config(
+    State(state): State<Arc<ConcreteAppState>>,
+    context: shared::RequestContext,
+) -> impl IntoResponse {
+    match state.settings.reload() {
+        Ok(report) => {
+            tracing::info!(user_id = %context.user_id, changed = ?report.changed, "Configuration reloaded");
+            (StatusCode::OK, Json(serde_json::json!({ "changed": report.changed }))).into_response()
+        }
+        Err(ReloadError::Invalid(errors)) => {
+            let issues: Vec<_> = errors
+                .0
+                .iter()
+                .map(|issue| serde_json::json!({ "var": issue.var, "message": issue.message }))
+                .collect();
+            (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "Invalid configuration", "issues": issues })),
+            )
+                .into_response()
+        }
+        Err(ReloadError::RestartRequired(sections)) => (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": "These settings cannot be reloaded; restart the service to change them",
+                "code": "restart_required",
+                "sections": sections,
+            })),
+        )
+            .into_response(),
+        Err(e @ ReloadError::Load(_)) => {
+            tracing::error!(user_id = %context.user_id, "Configuration reload failed: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ "error": e.to_string() }))).into_response()
+        }
+    }
+}
@@ -89,3 +89,50 @@ pub async fn get_dek_status(
         .into_response()
 }
 
+
+/// Key rotation schedule: last and next rotation per key type
+pub async fn get_key_rotation_schedule(
+    State(state): State<Arc<ConcreteAppState>>,
+) -> impl IntoResponse {
+    use shared::infrastructure::encryption::KeyRotationStore;
+
+    let store = KeyRotationStore::new(state.database_pool.as_ref().clone());
+    let location = concat!(file!(), ":", line!());
+    match store.statuses().await {
+        Ok(statuses) => {
+            let now = chrono::Utc::now();
+            let key_types: Vec<_> = statuses
+                .into_iter()
+                .map(|status| {
+                    let overdue = status.next_due_at <= now;
+                    let mut value = serde_json::to_value(&status).unwrap_or_default();
+                    value["overdue"] = serde_json::Value::Bool(overdue);
+                    value
+                })
+                .collect();
+            (StatusCode::OK, Json(serde_json::json!({ "key_types": key_types }))).into_response()
+        }
+        Err(e) => super::admin_handlers::error_response(e, location, "get_key_rotation_schedule"),
+    }
+}
+
+/// Verify every relationship's encrypted metadata and report failures
+pub async fn scan_relationship_integrity(
+    State(state): State<Arc<ConcreteAppState>>,
+) -> impl IntoResponse {
+    use crate::use_cases::encryption::ScanRelationshipIntegrityUseCase;
+    use shared::infrastructure::encryption::RelationshipEncryption;
+    use shared::infrastructure::repositories::RelationshipRepositoryImpl;
+
+    let relationship_repository = Box::new(RelationshipRepositoryImpl::new(state.database_pool.as_ref().clone()));
+    let use_case = ScanRelationshipIntegrityUseCase::new(
+        relationship_repository,
+        RelationshipEncryption::new(state.dek_manager.clone()),
+    );
+
+    let location = concat!(file!(), ":", line!());
+    match use_case.execute().await {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => super::admin_handlers::error_response(e, location, "scan_relationship_integrity"),
+    }
+}
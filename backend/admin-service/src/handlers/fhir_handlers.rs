@@ -0,0 +1,81 @@
+use axum::{Json, extract::{Path, State}, http::{StatusCode, header}, response::{IntoResponse, Response}};
+use serde::Serialize;
+use shared::AppError;
+use crate::dto::{OperationOutcome, Patient, Practitioner, FHIR_JSON};
+use std::sync::Arc;
+use uuid::Uuid;
+
+// Type aliases for convenience
+type ConcreteAppState = shared::AppState<
+    authz_core::auth::LoginUseCase,
+    authz_core::auth::RefreshTokenUseCase,
+    authz_core::auth::LogoutUseCase,
+    authz_core::auth::UserInfoUseCase,
+    crate::use_cases::setup::SetupOrganizationUseCase,
+    crate::use_cases::setup::CreateSuperAdminUseCase,
+>;
+
+fn fhir_response<T: Serialize>(status: StatusCode, resource: T) -> Response {
+    (status, [(header::CONTENT_TYPE, FHIR_JSON)], Json(resource)).into_response()
+}
+
+fn not_found(diagnostics: String) -> Response {
+    fhir_response(StatusCode::NOT_FOUND, OperationOutcome::error("not-found", diagnostics))
+}
+
+/// Errors as an `OperationOutcome`, as FHIR clients expect
+fn outcome_response(error: AppError, location: &str, operation: &str) -> Response {
+    let (status, code) = match &error {
+        AppError::Validation(_) => (StatusCode::BAD_REQUEST, "invalid"),
+        AppError::NotFound(_) => (StatusCode::NOT_FOUND, "not-found"),
+        AppError::Mumps(_) => (StatusCode::BAD_GATEWAY, "transient"),
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, "exception"),
+    };
+    let diagnostics = match &error {
+        AppError::Validation(message) | AppError::NotFound(message) => message.clone(),
+        other => {
+            other.log_with_operation(location, operation);
+            format!("Failed to {}", operation.replace('_', " "))
+        }
+    };
+    fhir_response(status, OperationOutcome::error(code, diagnostics))
+}
+
+/// A directory user as a FHIR `Practitioner`
+///
+/// Service accounts are not practitioners and are reported as not found.
+pub async fn get_fhir_practitioner(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    use shared::domain::repositories::UserRepository;
+    use shared::infrastructure::repositories::UserRepositoryImpl;
+
+    let location = concat!(file!(), ":", line!());
+    let Ok(user_id) = Uuid::parse_str(&id) else {
+        return not_found(format!("Practitioner/{} is not known", id));
+    };
+    let user_repository = UserRepositoryImpl::new(state.database_service.clone());
+    match user_repository.find_by_id(user_id).await {
+        Ok(Some(user)) if !user.is_service_account => fhir_response(StatusCode::OK, Practitioner::from(&user)),
+        Ok(_) => not_found(format!("Practitioner/{} is not known", id)),
+        Err(e) => outcome_response(e, location, "get_fhir_practitioner"),
+    }
+}
+
+/// A legacy patient as a FHIR `Patient`, read from the MUMPS gateway
+pub async fn get_fhir_patient(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let Some(legacy_patients) = state.legacy_patients.as_ref() else {
+        return not_found("Patient resources are not available on this server".to_string());
+    };
+    match legacy_patients.find_by_dfn(&id).await {
+        Ok(Some(patient)) => fhir_response(StatusCode::OK, Patient::from(&patient)),
+        // Malformed ids cannot name a patient
+        Ok(None) | Err(AppError::Validation(_)) => not_found(format!("Patient/{} is not known", id)),
+        Err(e) => outcome_response(e, location, "get_fhir_patient"),
+    }
+}
@@ -149,7 +149,7 @@ pub async fn find_permission_paths(
         
         match cache.get_or_build(&relationship_repository).await {
             Ok(graph) => {
-                let graph_checker = GraphPermissionChecker::new(graph);
+                let graph_checker = GraphPermissionChecker::new(graph).with_schema(state.permission_checker.schema());
                 match graph_checker.find_permission_paths(user.unwrap(), relation.unwrap(), object.unwrap()) {
                     Ok(paths) => (
                         StatusCode::OK,
@@ -190,3 +190,88 @@ pub async fn find_permission_paths(
     }
 }
 
+/// Check a permission and report why it was granted or denied
+pub async fn explain_permission_check(
+    State(state): State<Arc<ConcreteAppState>>,
+    axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
+) -> impl IntoResponse {
+    let (Some(user), Some(relation), Some(object)) = (params.get("user"), params.get("relation"), params.get("object")) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "Missing required parameters: user, relation, object"
+            })),
+        )
+            .into_response();
+    };
+
+    let Some(cache) = &state.graph_cache else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({
+                "error": "Graph cache not enabled"
+            })),
+        )
+            .into_response();
+    };
+
+    use shared::infrastructure::repositories::RelationshipRepositoryImpl;
+    use shared::infrastructure::zanzibar::GraphPermissionChecker;
+    let location = concat!(file!(), ":", line!());
+    let relationship_repository = RelationshipRepositoryImpl::new(state.database_pool.as_ref().clone());
+    let result = match cache.get_or_build(&relationship_repository).await {
+        Ok(graph) => GraphPermissionChecker::new(graph)
+            .with_schema(state.permission_checker.schema())
+            .check_explain(user, relation, object),
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(explanation) => (StatusCode::OK, Json(explanation)).into_response(),
+        Err(e) => super::admin_handlers::error_response(e, location, "explain_permission_check"),
+    }
+}
+
+/// What access a subject gained or lost between two points in time
+///
+/// `from` and `to` are RFC 3339 times or consistency tokens; `to` defaults
+/// to now. `format=text` returns the plain-text report alone.
+pub async fn diff_authorization(
+    State(state): State<Arc<ConcreteAppState>>,
+    axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
+) -> impl IntoResponse {
+    use shared::domain::repositories::SnapshotPoint;
+
+    let location = concat!(file!(), ":", line!());
+    let (Some(subject), Some(from)) = (params.get("subject"), params.get("from")) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "Missing required parameters: subject, from"
+            })),
+        )
+            .into_response();
+    };
+    let from = SnapshotPoint::parse(from);
+    let to = params
+        .get("to")
+        .map(|to| SnapshotPoint::parse(to))
+        .unwrap_or_else(|| SnapshotPoint::At(chrono::Utc::now()));
+
+    match state.relationship_store.diff_subject(subject, &from, &to).await {
+        Ok(diff) if params.get("format").is_some_and(|f| f == "text") => {
+            ([(axum::http::header::CONTENT_TYPE, "text/plain; charset=utf-8")], diff.report()).into_response()
+        }
+        Ok(diff) => {
+            let report = diff.report();
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "diff": diff,
+                    "report": report
+                })),
+            )
+                .into_response()
+        }
+        Err(e) => super::admin_handlers::error_response(e, location, "diff_authorization"),
+    }
+}
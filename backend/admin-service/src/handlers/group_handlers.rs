@@ -1,6 +1,8 @@
-use axum::{Json, extract::{State, Path}, http::StatusCode, response::IntoResponse};
+use axum::{Json, extract::{Path, Query, State}, http::StatusCode, response::IntoResponse};
 use serde::{Deserialize, Serialize};
 use shared::domain::repositories::GroupRepository;
+use shared::infrastructure::encryption::GroupKeyring;
+use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -21,12 +23,21 @@ pub struct CreateGroupRequest {
     pub organization_id: Option<Uuid>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UpdateGroupRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Version the client last read
+    pub version: i64,
+}
+
 #[derive(Debug, Serialize)]
 pub struct GroupResponse {
     pub id: Uuid,
     pub name: String,
     pub description: Option<String>,
     pub organization_id: Option<Uuid>,
+    pub version: i64,
 }
 
 impl From<shared::domain::entities::Group> for GroupResponse {
@@ -36,6 +47,7 @@ impl From<shared::domain::entities::Group> for GroupResponse {
             name: group.name,
             description: group.description,
             organization_id: group.organization_id,
+            version: group.version,
         }
     }
 }
@@ -110,34 +122,46 @@ pub async fn get_group(
     }
 }
 
-/// List all groups
+/// Update a group's name or description
+///
+/// Answers 409 when the group changed since the client read `version`.
+pub async fn update_group(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateGroupRequest>,
+) -> impl IntoResponse {
+    use crate::use_cases::group::UpdateGroupUseCase;
+    use shared::infrastructure::repositories::GroupRepositoryImpl;
+
+    let group_repository = Box::new(GroupRepositoryImpl::new(state.database_pool.as_ref().clone()));
+    let use_case = UpdateGroupUseCase::new(group_repository);
+
+    let location = concat!(file!(), ":", line!());
+    match use_case.execute(id, request.name, request.description, request.version).await {
+        Ok(group) => (StatusCode::OK, Json(GroupResponse::from(group))).into_response(),
+        Err(e) => super::admin_handlers::error_response(e, location, "update_group"),
+    }
+}
+
+/// List groups, paginated
 pub async fn list_groups(
     State(state): State<Arc<ConcreteAppState>>,
+    Query(params): Query<HashMap<String, String>>,
 ) -> impl IntoResponse {
     use shared::infrastructure::repositories::GroupRepositoryImpl;
     
     let group_repository = GroupRepositoryImpl::new(state.database_pool.as_ref().clone());
     
     let location = concat!(file!(), ":", line!());
-    match group_repository.find_all().await {
-        Ok(groups) => (
-            StatusCode::OK,
-            Json(serde_json::json!({
-                "groups": groups.into_iter().map(GroupResponse::from).collect::<Vec<_>>()
-            })),
-        )
-            .into_response(),
-        Err(e) => {
-            e.log_with_operation(location, "list_groups");
-            (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({
-                "error": format!("Failed to list groups: {}", e)
-            })),
-        )
-                .into_response()
-        }
-    }
+    let result = match shared::ListQuery::from_params(&params) {
+        Ok(query) => group_repository.list_page(&query).await,
+        Err(e) => Err(e),
+    };
+    super::admin_handlers::page_response(
+        result.map(|page| page.map(GroupResponse::from)),
+        location,
+        "list_groups",
+    )
 }
 
 /// Add user to group
@@ -149,9 +173,14 @@ pub async fn add_user_to_group(
     use shared::infrastructure::repositories::UserRepositoryImpl;
     
     let user_repository = Box::new(UserRepositoryImpl::new(state.database_service.clone()));
+    let group_keyring = Arc::new(GroupKeyring::new(
+        state.dek_manager.clone(),
+        state.database_pool.as_ref().clone(),
+    ));
     let use_case = AddUserToGroupUseCase::new(
         user_repository,
         state.relationship_store.clone(),
+        group_keyring,
     );
     
     let location = concat!(file!(), ":", line!());
@@ -178,20 +207,30 @@ pub async fn add_user_to_group(
 }
 
 /// Remove user from group (soft delete relationship)
+///
+/// Also revokes the user's group DEK shares, rotating the group DEK for the
+/// remaining members.
 pub async fn remove_user_from_group(
     State(state): State<Arc<ConcreteAppState>>,
+    context: shared::RequestContext,
     Path((group_id, user_id)): Path<(Uuid, Uuid)>,
 ) -> impl IntoResponse {
-    let user_str = format!("user:{}", user_id);
-    let group_str = format!("group:{}", group_id);
-    
+    use crate::use_cases::group::RemoveUserFromGroupUseCase;
+
+    let group_keyring = Arc::new(GroupKeyring::new(
+        state.dek_manager.clone(),
+        state.database_pool.as_ref().clone(),
+    ));
+    let use_case = RemoveUserFromGroupUseCase::new(state.relationship_store.clone(), group_keyring);
+
     let location = concat!(file!(), ":", line!());
-    match state.relationship_store.soft_delete(&user_str, "member", &group_str, None).await {
-        Ok(_) => (
+    match use_case.execute(user_id, group_id, Some(context.user_id)).await {
+        Ok(rotation) => (
             StatusCode::OK,
             Json(serde_json::json!({
                 "success": true,
-                "message": "User removed from group"
+                "message": "User removed from group",
+                "key_rotation": rotation,
             })),
         )
             .into_response(),
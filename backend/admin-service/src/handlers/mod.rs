@@ -8,6 +8,9 @@ pub mod encryption_handlers;
 pub mod graph_handlers;
 pub mod ui_entity_handlers;
 pub mod dashboard_handlers;
+pub mod config_handlers;
+pub mod fhir_handlers;
+pub mod scim_handlers;
 
 pub use admin_handlers::*;
 pub use setup_handlers::*;
@@ -19,4 +22,7 @@ pub use encryption_handlers::*;
 pub use graph_handlers::*;
 pub use ui_entity_handlers::*;
 pub use dashboard_handlers::*;
+pub use config_handlers::*;
+pub use fhir_handlers::*;
+pub use scim_handlers::*;
 
@@ -1,5 +1,9 @@
-use axum::{Json, extract::{State, Path}, http::StatusCode, response::IntoResponse};
+use axum::{Json, extract::{State, Path}, http::{HeaderMap, StatusCode}, response::IntoResponse};
+use shared::infrastructure::api::etag::conditional_json_by_body;
+use shared::infrastructure::repositories::UiEntityRepositoryImpl;
+use crate::use_cases::ui::ResolveUiVisibilityUseCase;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -109,28 +113,35 @@ pub async fn check_permissions_batch(
     }
 }
 
+fn sorted_permissions(permissions: HashSet<(String, String)>) -> Vec<(String, String)> {
+    let mut permissions: Vec<_> = permissions.into_iter().collect();
+    permissions.sort();
+    permissions
+}
+
 /// Get all permissions for a user
+///
+/// Honors `If-None-Match`, returning 304 while the permissions are unchanged.
 pub async fn get_user_permissions(
     State(state): State<Arc<ConcreteAppState>>,
     Path(user_id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let user_str = format!("user:{}", user_id);
     
     match state.permission_checker.get_all_permissions(&user_str).await {
         Ok(permissions) => {
+            // Sorted so identical permission sets produce identical ETags
+            let permissions = sorted_permissions(permissions);
             let permissions_info: Vec<PermissionInfo> = permissions
                 .into_iter()
                 .map(|(relation, object)| PermissionInfo { relation, object })
                 .collect();
             
-            (
-                StatusCode::OK,
-                Json(UserPermissionsResponse {
-                    user_id,
-                    permissions: permissions_info,
-                }),
-            )
-                .into_response()
+            conditional_json_by_body(&headers, UserPermissionsResponse {
+                user_id,
+                permissions: permissions_info,
+            })
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -143,15 +154,20 @@ pub async fn get_user_permissions(
 }
 
 /// Get user's accessible pages
+///
+/// Honors `If-None-Match`, returning 304 while the permissions are unchanged.
 pub async fn get_user_pages(
     State(state): State<Arc<ConcreteAppState>>,
     Path(user_id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let user_str = format!("user:{}", user_id);
     
     // Get all permissions and filter for pages
     match state.permission_checker.get_all_permissions(&user_str).await {
         Ok(permissions) => {
+            // Sorted so identical permission sets produce identical ETags
+            let permissions = sorted_permissions(permissions);
             let pages: Vec<String> = permissions
                 .iter()
                 .filter(|(relation, object)| {
@@ -160,14 +176,10 @@ pub async fn get_user_pages(
                 .map(|(_, object)| object.strip_prefix("page:").unwrap_or(object).to_string())
                 .collect();
             
-            (
-                StatusCode::OK,
-                Json(serde_json::json!({
-                    "user_id": user_id,
-                    "pages": pages
-                })),
-            )
-                .into_response()
+            conditional_json_by_body(&headers, serde_json::json!({
+                "user_id": user_id,
+                "pages": pages
+            }))
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -180,15 +192,20 @@ pub async fn get_user_pages(
 }
 
 /// Get user's accessible buttons for a page
+///
+/// Honors `If-None-Match`, returning 304 while the permissions are unchanged.
 pub async fn get_user_buttons(
     State(state): State<Arc<ConcreteAppState>>,
     Path((user_id, page_name)): Path<(Uuid, String)>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let user_str = format!("user:{}", user_id);
     
     // Get all permissions and filter for buttons
     match state.permission_checker.get_all_permissions(&user_str).await {
         Ok(permissions) => {
+            // Sorted so identical permission sets produce identical ETags
+            let permissions = sorted_permissions(permissions);
             let buttons: Vec<String> = permissions
                 .iter()
                 .filter(|(relation, object)| {
@@ -197,15 +214,11 @@ pub async fn get_user_buttons(
                 .map(|(_, object)| object.strip_prefix("button:").unwrap_or(object).to_string())
                 .collect();
             
-            (
-                StatusCode::OK,
-                Json(serde_json::json!({
-                    "user_id": user_id,
-                    "page": page_name,
-                    "buttons": buttons
-                })),
-            )
-                .into_response()
+            conditional_json_by_body(&headers, serde_json::json!({
+                "user_id": user_id,
+                "page": page_name,
+                "buttons": buttons
+            }))
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -218,15 +231,20 @@ pub async fn get_user_buttons(
 }
 
 /// Get user's accessible fields for a page
+///
+/// Honors `If-None-Match`, returning 304 while the permissions are unchanged.
 pub async fn get_user_fields(
     State(state): State<Arc<ConcreteAppState>>,
     Path((user_id, page_name)): Path<(Uuid, String)>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let user_str = format!("user:{}", user_id);
     
     // Get all permissions and filter for fields
     match state.permission_checker.get_all_permissions(&user_str).await {
         Ok(permissions) => {
+            // Sorted so identical permission sets produce identical ETags
+            let permissions = sorted_permissions(permissions);
             let view_fields: Vec<String> = permissions
                 .iter()
                 .filter(|(relation, object)| {
@@ -243,16 +261,12 @@ pub async fn get_user_fields(
                 .map(|(_, object)| object.strip_prefix("field:").unwrap_or(object).to_string())
                 .collect();
             
-            (
-                StatusCode::OK,
-                Json(serde_json::json!({
-                    "user_id": user_id,
-                    "page": page_name,
-                    "view_fields": view_fields,
-                    "edit_fields": edit_fields
-                })),
-            )
-                .into_response()
+            conditional_json_by_body(&headers, serde_json::json!({
+                "user_id": user_id,
+                "page": page_name,
+                "view_fields": view_fields,
+                "edit_fields": edit_fields
+            }))
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -264,3 +278,30 @@ pub async fn get_user_fields(
     }
 }
 
+
+/// Get every page, button and field the user may see, as one tree
+///
+/// Honors `If-None-Match`, returning 304 while the visible UI is unchanged.
+pub async fn get_user_ui(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path(user_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let ui_entity_repository = Box::new(UiEntityRepositoryImpl::new(state.database_pool.as_ref().clone()));
+    let use_case = ResolveUiVisibilityUseCase::new(
+        ui_entity_repository,
+        state.permission_checker.clone(),
+        state.ui_visibility_cache.clone(),
+    );
+
+    match use_case.execute(user_id).await {
+        Ok(ui) => conditional_json_by_body(&headers, ui.as_ref()),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": format!("Failed to resolve user UI: {}", e)
+            })),
+        )
+            .into_response(),
+    }
+}
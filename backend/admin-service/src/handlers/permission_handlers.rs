@@ -1,4 +1,4 @@
-use axum::{Json, extract::{State, Path}, http::StatusCode, response::IntoResponse};
+use axum::{Json, extract::{State, Path, Query}, http::StatusCode, response::IntoResponse};
 use serde::Deserialize;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -20,6 +20,7 @@ pub struct CreatePermissionRequest {
     pub user_id: Uuid,
     pub relation: String,
     pub object: String,
+    #[serde(alias = "valid_until")]
     pub expires_at: Option<DateTime<Utc>>,
     pub valid_from: Option<DateTime<Utc>>,
     pub metadata: Option<Value>,
@@ -28,7 +29,17 @@ pub struct CreatePermissionRequest {
 
 #[derive(Debug, Deserialize)]
 pub struct ExtendPermissionRequest {
+    #[serde(alias = "valid_until")]
     pub new_expires_at: DateTime<Utc>,
+    /// Shift the start as well; omitted keeps the current start
+    #[serde(alias = "valid_from")]
+    pub new_valid_from: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduledPermissionsQuery {
+    pub within_hours: Option<i64>,
+    pub limit: Option<u32>,
 }
 
 /// Create individual permission
@@ -62,6 +73,7 @@ pub async fn create_permission(
                 "user": relationship.user,
                 "relation": relationship.relation,
                 "object": relationship.object,
+                "valid_from": relationship.valid_from,
                 "expires_at": relationship.expires_at,
             })),
         )
@@ -76,7 +88,7 @@ pub async fn create_permission(
     }
 }
 
-/// Extend permission expiration
+/// Extend or shift a permission's validity window
 pub async fn extend_permission(
     State(state): State<Arc<ConcreteAppState>>,
     Path(id): Path<Uuid>,
@@ -127,22 +139,47 @@ pub async fn extend_permission(
     
     let use_case = ExtendPermissionUseCase::new(state.relationship_store.clone());
     
-    match use_case.execute(user_id, &relationship.relation, &relationship.object, request.new_expires_at).await {
-        Ok(_) => (
+    let location = concat!(file!(), ":", line!());
+    match use_case
+        .execute(
+            user_id,
+            &relationship.relation,
+            &relationship.object,
+            request.new_valid_from,
+            request.new_expires_at,
+        )
+        .await
+    {
+        Ok(updated) => (
             StatusCode::OK,
             Json(serde_json::json!({
                 "success": true,
-                "message": "Permission expiration extended"
-            })),
-        )
-            .into_response(),
-        Err(e) => (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({
-                "error": format!("Failed to extend permission: {}", e)
+                "id": updated.id,
+                "valid_from": updated.valid_from,
+                "expires_at": updated.expires_at,
+                "version": updated.version,
             })),
         )
             .into_response(),
+        Err(e) => super::admin_handlers::error_response(e, location, "extend_permission"),
+    }
+}
+
+/// List grants starting or expiring within `within_hours` (default one week)
+pub async fn list_scheduled_permissions(
+    State(state): State<Arc<ConcreteAppState>>,
+    Query(query): Query<ScheduledPermissionsQuery>,
+) -> impl IntoResponse {
+    use crate::use_cases::permission::ListScheduledPermissionsUseCase;
+    use shared::infrastructure::repositories::RelationshipRepositoryImpl;
+
+    let relationship_repository = Box::new(RelationshipRepositoryImpl::new(state.database_pool.as_ref().clone()));
+    let use_case = ListScheduledPermissionsUseCase::new(relationship_repository);
+
+    let location = concat!(file!(), ":", line!());
+    match use_case.execute(query.within_hours, query.limit).await {
+        Ok(scheduled) => (StatusCode::OK, Json(scheduled)).into_response(),
+        Err(e) => super::admin_handlers::error_response(e, location, "list_scheduled_permissions"),
     }
 }
 
@@ -256,6 +293,7 @@ pub struct AssignPermissionRequest {
     pub subject: String,
     pub relation: String,
     pub object: String,
+    #[serde(alias = "valid_until")]
     pub expires_at: Option<DateTime<Utc>>,
     pub valid_from: Option<DateTime<Utc>>,
     pub metadata: Option<Value>,
@@ -282,6 +320,7 @@ pub async fn assign_permission(
         &request.relation,
         &request.object,
         request.metadata,
+        request.valid_from,
         request.expires_at,
     ).await {
         Ok(_) => (
@@ -302,6 +341,43 @@ pub async fn assign_permission(
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct BulkPermissionRequest {
+    pub subject: crate::use_cases::permission::PermissionSubject,
+    pub action: crate::use_cases::permission::BulkAction,
+    pub permissions: Vec<crate::use_cases::permission::PermissionSpec>,
+}
+
+/// Grant or revoke a set of permissions for one user or group atomically
+///
+/// Either every permission is applied or none is. Already-granted (or
+/// already-revoked) permissions are reported as unchanged rather than
+/// failing the batch.
+pub async fn bulk_permissions(
+    State(state): State<Arc<ConcreteAppState>>,
+    context: shared::RequestContext,
+    Json(request): Json<BulkPermissionRequest>,
+) -> impl IntoResponse {
+    use crate::use_cases::permission::BulkPermissionUseCase;
+
+    let use_case = BulkPermissionUseCase::new(
+        state.database_pool.as_ref().clone(),
+        state.relationship_store.clone(),
+        state.outbox_relay.clone(),
+    );
+
+    let location = concat!(file!(), ":", line!());
+    match use_case.execute(
+        request.subject,
+        request.action,
+        request.permissions,
+        Some(context.user_id),
+    ).await {
+        Ok(result) => (StatusCode::OK, Json(result)).into_response(),
+        Err(e) => super::admin_handlers::error_response(e, location, "apply_permission_batch"),
+    }
+}
+
 pub async fn assign_permissions_batch(
     State(state): State<Arc<ConcreteAppState>>,
     Json(request): Json<BatchAssignPermissionRequest>,
@@ -315,6 +391,7 @@ pub async fn assign_permissions_batch(
             &assignment.relation,
             &assignment.object,
             assignment.metadata,
+            assignment.valid_from,
             assignment.expires_at,
         ).await {
             Ok(_) => results.push(true),
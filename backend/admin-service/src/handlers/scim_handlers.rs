@@ -0,0 +1,602 @@
+use axum::{Json, extract::{Path, Query, State}, http::{HeaderMap, StatusCode, header}, response::{IntoResponse, Response}};
+use serde::Serialize;
+use shared::domain::entities::User;
+use shared::domain::repositories::relationship_repository::RELATIONSHIP_PAGE_MAX;
+use shared::domain::repositories::{GroupRepository, RelationshipFilter, UserRepository};
+use shared::infrastructure::encryption::GroupKeyring;
+use shared::infrastructure::repositories::{GroupRepositoryImpl, UserRepositoryImpl};
+use shared::{AppError, AppResult, ListQuery, RequestContext, SortDirection};
+use crate::dto::{
+    CreateUserRequest, GroupPatch, ScimError, ScimGroup, ScimListParams, ScimListResponse, ScimPatchRequest,
+    ScimUser, UpdateUserRequest, UserPatch, SCIM_JSON, version_from_etag,
+};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+// Type aliases for convenience
+type ConcreteAppState = shared::AppState<
+    authz_core::auth::LoginUseCase,
+    authz_core::auth::RefreshTokenUseCase,
+    authz_core::auth::LogoutUseCase,
+    authz_core::auth::UserInfoUseCase,
+    crate::use_cases::setup::SetupOrganizationUseCase,
+    crate::use_cases::setup::CreateSuperAdminUseCase,
+>;
+
+fn scim_response<T: Serialize>(status: StatusCode, body: T) -> Response {
+    (status, [(header::CONTENT_TYPE, SCIM_JSON)], Json(body)).into_response()
+}
+
+fn created_response<T: Serialize>(location: String, body: T) -> Response {
+    (
+        StatusCode::CREATED,
+        [(header::CONTENT_TYPE, SCIM_JSON.to_string()), (header::LOCATION, location)],
+        Json(body),
+    )
+        .into_response()
+}
+
+fn scim_error_response(error: ScimError) -> Response {
+    let status = error.status.parse().ok().and_then(|s| StatusCode::from_u16(s).ok()).unwrap_or(StatusCode::BAD_REQUEST);
+    scim_response(status, error)
+}
+
+fn not_found(resource: &str, id: Uuid) -> Response {
+    scim_error_response(ScimError::new(404, None, format!("{} {} not found", resource, id)))
+}
+
+/// Errors in the SCIM error format; version conflicts come from `If-Match`
+fn error_response(error: AppError, location: &str, operation: &str) -> Response {
+    let scim_error = match &error {
+        AppError::Validation(message) => ScimError::invalid_value(message.clone()),
+        AppError::NotFound(message) => ScimError::new(404, None, message.clone()),
+        AppError::VersionConflict { .. } => ScimError::new(412, None, error.to_string()),
+        other => {
+            other.log_with_operation(location, operation);
+            ScimError::new(500, None, format!("Failed to {}", operation.replace('_', " ")))
+        }
+    };
+    scim_error_response(scim_error)
+}
+
+/// Version the client expects from `If-Match`, else the current one
+fn expected_version(headers: &HeaderMap, current: i64) -> i64 {
+    headers
+        .get(header::IF_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(version_from_etag)
+        .unwrap_or(current)
+}
+
+fn user_repository(state: &ConcreteAppState) -> UserRepositoryImpl {
+    UserRepositoryImpl::new(state.database_service.clone())
+}
+
+fn group_repository(state: &ConcreteAppState) -> GroupRepositoryImpl {
+    GroupRepositoryImpl::new(state.database_pool.as_ref().clone())
+}
+
+/// A provisioned user; service accounts are not exposed over SCIM
+async fn find_user(state: &ConcreteAppState, id: Uuid) -> AppResult<Option<User>> {
+    Ok(user_repository(state).find_by_id(id).await?.filter(|user| !user.is_service_account))
+}
+
+/// Apply attribute and `active` changes through the user use cases
+async fn apply_user_patch(
+    state: &ConcreteAppState,
+    context: &RequestContext,
+    user: User,
+    patch: UserPatch,
+    version: i64,
+) -> AppResult<User> {
+    use crate::use_cases::user::{DeactivateUserUseCase, UpdateUserUseCase};
+
+    if patch.user_name.is_some() || patch.email.is_some() || patch.password.is_some() {
+        let request = UpdateUserRequest {
+            email: patch.email.filter(|email| *email != user.email),
+            username: patch.user_name.filter(|name| *name != user.username),
+            password: patch.password,
+            version,
+        };
+        request.validate()?;
+        UpdateUserUseCase::new(Box::new(user_repository(state))).execute(user.id, request).await?;
+    } else {
+        AppError::check_version("User", user.id, version, user.version)?;
+    }
+
+    match patch.active {
+        Some(false) if user.is_active => {
+            let use_case = DeactivateUserUseCase::new(state.database_pool.as_ref().clone(), state.outbox_relay.clone());
+            use_case.execute(user.id, Some(context.user_id)).await?;
+        }
+        Some(true) if !user.is_active => {
+            let repository = user_repository(state);
+            let mut current = repository
+                .find_by_id(user.id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+            current.is_active = true;
+            current.touch(Some(context.request_id.clone()), Some(context.user_id));
+            repository.update(current).await?;
+        }
+        _ => {}
+    }
+
+    user_repository(state)
+        .find_by_id(user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+}
+
+/// List users; supports `filter=userName eq "..."`, `emails eq "..."` and
+/// `externalId eq "..."`
+pub async fn list_scim_users(
+    State(state): State<Arc<ConcreteAppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let params = match ScimListParams::from_params(&params) {
+        Ok(params) => params,
+        Err(e) => return scim_error_response(e),
+    };
+
+    let mut query = ListQuery {
+        limit: params.count.max(1),
+        sort: Some("created_at".to_string()),
+        direction: Some(SortDirection::Asc),
+        ..Default::default()
+    }
+    .with_offset(params.offset());
+    query.filters.insert("is_service_account".to_string(), "false".to_string());
+    if let Some((attribute, value)) = &params.filter {
+        let field = match attribute.to_ascii_lowercase().as_str() {
+            "username" => "username",
+            "emails" | "emails.value" => "email",
+            "externalid" => "external_id",
+            _ => {
+                return scim_error_response(ScimError::new(
+                    400,
+                    Some("invalidFilter"),
+                    format!("cannot filter users by '{}'", attribute),
+                ))
+            }
+        };
+        query.filters.insert(field.to_string(), value.clone());
+    }
+
+    match user_repository(&state).list_page(&query).await {
+        Ok(page) => {
+            let resources = match params.count {
+                0 => Vec::new(),
+                _ => page.items.iter().map(ScimUser::from_user).collect(),
+            };
+            scim_response(StatusCode::OK, ScimListResponse::new(resources, page.total, params.start_index))
+        }
+        Err(e) => error_response(e, location, "list_scim_users"),
+    }
+}
+
+/// Provision a user
+///
+/// Users created without a password get an unusable random one and sign in
+/// through SSO. With an `externalId` the request is idempotent: a user the
+/// IdP already provisioned, or a manually created one with the same email,
+/// is updated and returned with 200 instead of being duplicated (see
+/// [`crate::use_cases::user::create_user`]).
+pub async fn create_scim_user(
+    State(state): State<Arc<ConcreteAppState>>,
+    context: RequestContext,
+    Json(body): Json<ScimUser>,
+) -> impl IntoResponse {
+    use crate::use_cases::user::{CreateUserOutcome, CreateUserUseCase};
+
+    let location = concat!(file!(), ":", line!());
+    let Some(email) = body.primary_email().map(str::to_string) else {
+        return scim_error_response(ScimError::invalid_value("emails must contain at least one address"));
+    };
+    // Without an externalId there is nothing to match a re-send on
+    if body.external_id.is_none() {
+        let repository = user_repository(&state);
+        let existing = match (repository.find_by_username(&body.user_name).await, repository.find_by_email(&email).await) {
+            (Ok(by_name), Ok(by_email)) => by_name.or(by_email),
+            (Err(e), _) | (_, Err(e)) => return error_response(e, location, "create_scim_user"),
+        };
+        if existing.is_some() {
+            return scim_error_response(ScimError::new(409, Some("uniqueness"), "userName or email is already in use"));
+        }
+    }
+
+    let request = CreateUserRequest {
+        email,
+        username: body.user_name.clone(),
+        password: body
+            .password
+            .clone()
+            .unwrap_or_else(|| format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())),
+        external_id: body.external_id.clone(),
+        is_service_account: false,
+    };
+    if let Err(e) = request.validate() {
+        return error_response(e.into(), location, "create_scim_user");
+    }
+    let use_case = CreateUserUseCase::new(
+        Box::new(user_repository(&state)),
+        state.dek_manager.clone(),
+        state.relationship_store.clone(),
+    );
+    let result = match use_case.execute(request).await {
+        Ok((created, outcome)) => match find_user(&state, created.id).await {
+            Ok(Some(user)) if body.active.is_some_and(|active| active != user.is_active) => {
+                let patch = UserPatch { active: body.active, ..Default::default() };
+                let version = user.version;
+                apply_user_patch(&state, &context, user, patch, version).await.map(|user| (user, outcome))
+            }
+            Ok(Some(user)) => Ok((user, outcome)),
+            Ok(None) => Err(AppError::Internal("Created user not found".to_string())),
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok((user, CreateUserOutcome::Created)) => {
+            tracing::info!("SCIM provisioned user {} (by {})", user.id, context.user_id);
+            created_response(format!("/scim/v2/Users/{}", user.id), ScimUser::from_user(&user))
+        }
+        Ok((user, outcome)) => {
+            tracing::info!("SCIM re-provisioned user {} ({:?}, by {})", user.id, outcome, context.user_id);
+            scim_response(StatusCode::OK, ScimUser::from_user(&user))
+        }
+        Err(e) => error_response(e, location, "create_scim_user"),
+    }
+}
+
+pub async fn get_scim_user(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    match find_user(&state, id).await {
+        Ok(Some(user)) => scim_response(StatusCode::OK, ScimUser::from_user(&user)),
+        Ok(None) => not_found("User", id),
+        Err(e) => error_response(e, location, "get_scim_user"),
+    }
+}
+
+/// Replace a user's userName, email and active flag
+pub async fn replace_scim_user(
+    State(state): State<Arc<ConcreteAppState>>,
+    context: RequestContext,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+    Json(body): Json<ScimUser>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let user = match find_user(&state, id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return not_found("User", id),
+        Err(e) => return error_response(e, location, "replace_scim_user"),
+    };
+    let Some(email) = body.primary_email().map(str::to_string) else {
+        return scim_error_response(ScimError::invalid_value("emails must contain at least one address"));
+    };
+    let patch = UserPatch {
+        user_name: Some(body.user_name),
+        email: Some(email),
+        active: body.active,
+        password: body.password,
+    };
+    let version = expected_version(&headers, user.version);
+    match apply_user_patch(&state, &context, user, patch, version).await {
+        Ok(user) => scim_response(StatusCode::OK, ScimUser::from_user(&user)),
+        Err(e) => error_response(e, location, "replace_scim_user"),
+    }
+}
+
+/// Apply a SCIM PatchOp (e.g. `active` = false to deprovision)
+pub async fn patch_scim_user(
+    State(state): State<Arc<ConcreteAppState>>,
+    context: RequestContext,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+    Json(body): Json<ScimPatchRequest>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let patch = match UserPatch::from_operations(&body.operations) {
+        Ok(patch) => patch,
+        Err(e) => return scim_error_response(e),
+    };
+    let user = match find_user(&state, id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return not_found("User", id),
+        Err(e) => return error_response(e, location, "patch_scim_user"),
+    };
+    let version = expected_version(&headers, user.version);
+    match apply_user_patch(&state, &context, user, patch, version).await {
+        Ok(user) => scim_response(StatusCode::OK, ScimUser::from_user(&user)),
+        Err(e) => error_response(e, location, "patch_scim_user"),
+    }
+}
+
+pub async fn delete_scim_user(
+    State(state): State<Arc<ConcreteAppState>>,
+    context: RequestContext,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    use crate::use_cases::user::DeleteUserUseCase;
+
+    let location = concat!(file!(), ":", line!());
+    match find_user(&state, id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return not_found("User", id),
+        Err(e) => return error_response(e, location, "delete_scim_user"),
+    }
+    match DeleteUserUseCase::new(Box::new(user_repository(&state))).execute(id).await {
+        Ok(()) => {
+            tracing::info!("SCIM deprovisioned user {} (by {})", id, context.user_id);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(e) => error_response(e, location, "delete_scim_user"),
+    }
+}
+
+/// Current members of a group (`user:{id}#member@group:{id}`)
+async fn group_members(state: &ConcreteAppState, group_id: Uuid) -> AppResult<Vec<Uuid>> {
+    let mut members = Vec::new();
+    let mut offset = 0;
+    loop {
+        let filter = RelationshipFilter::for_object(format!("group:{}", group_id))
+            .with_relation("member")
+            .page(RELATIONSHIP_PAGE_MAX, offset);
+        let relationships = state.relationship_store.read_relationships(&filter).await?;
+        members.extend(
+            relationships
+                .iter()
+                .filter_map(|r| r.user.strip_prefix("user:").and_then(|id| Uuid::parse_str(id).ok())),
+        );
+        if relationships.len() < RELATIONSHIP_PAGE_MAX as usize {
+            break;
+        }
+        offset += RELATIONSHIP_PAGE_MAX;
+    }
+    members.sort();
+    members.dedup();
+    Ok(members)
+}
+
+/// Add and remove members so the group ends up as `patch` describes
+async fn sync_members(
+    state: &ConcreteAppState,
+    context: &RequestContext,
+    group_id: Uuid,
+    current: &[Uuid],
+    patch: &GroupPatch,
+) -> AppResult<()> {
+    use crate::use_cases::group::{AddUserToGroupUseCase, RemoveUserFromGroupUseCase};
+
+    let current: BTreeSet<Uuid> = current.iter().copied().collect();
+    let mut target = patch
+        .replace_members
+        .as_ref()
+        .map(|members| members.iter().copied().collect())
+        .unwrap_or_else(|| current.clone());
+    target.extend(patch.add_members.iter().copied());
+    for member in &patch.remove_members {
+        target.remove(member);
+    }
+    if target == current {
+        return Ok(());
+    }
+
+    let group_keyring = Arc::new(GroupKeyring::new(state.dek_manager.clone(), state.database_pool.as_ref().clone()));
+    let add = AddUserToGroupUseCase::new(
+        Box::new(user_repository(state)),
+        state.relationship_store.clone(),
+        group_keyring.clone(),
+    );
+    for user_id in target.difference(&current) {
+        add.execute(*user_id, group_id).await?;
+    }
+    let remove = RemoveUserFromGroupUseCase::new(state.relationship_store.clone(), group_keyring);
+    for user_id in current.difference(&target) {
+        remove.execute(*user_id, group_id, Some(context.user_id)).await?;
+    }
+    Ok(())
+}
+
+/// Rename and/or change the members of a group, then return it
+async fn apply_group_patch(
+    state: &ConcreteAppState,
+    context: &RequestContext,
+    group_id: Uuid,
+    patch: GroupPatch,
+    headers: &HeaderMap,
+) -> AppResult<Option<ScimGroup>> {
+    use crate::use_cases::group::UpdateGroupUseCase;
+
+    let Some(group) = group_repository(state).find_by_id(group_id).await? else {
+        return Ok(None);
+    };
+    let version = expected_version(headers, group.version);
+    match &patch.display_name {
+        Some(name) if *name != group.name => {
+            UpdateGroupUseCase::new(Box::new(group_repository(state)))
+                .execute(group_id, Some(name.clone()), None, version)
+                .await?;
+        }
+        _ => AppError::check_version("Group", group_id, version, group.version)?,
+    }
+
+    let members = group_members(state, group_id).await?;
+    sync_members(state, context, group_id, &members, &patch).await?;
+
+    let Some(group) = group_repository(state).find_by_id(group_id).await? else {
+        return Ok(None);
+    };
+    let members = group_members(state, group_id).await?;
+    Ok(Some(ScimGroup::from_group(&group, &members)))
+}
+
+/// List groups; supports `filter=displayName eq "..."`
+pub async fn list_scim_groups(
+    State(state): State<Arc<ConcreteAppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let params = match ScimListParams::from_params(&params) {
+        Ok(params) => params,
+        Err(e) => return scim_error_response(e),
+    };
+
+    let mut query = ListQuery {
+        limit: params.count.max(1),
+        sort: Some("created_at".to_string()),
+        direction: Some(SortDirection::Asc),
+        ..Default::default()
+    }
+    .with_offset(params.offset());
+    if let Some((attribute, value)) = &params.filter {
+        if !attribute.eq_ignore_ascii_case("displayName") {
+            return scim_error_response(ScimError::new(
+                400,
+                Some("invalidFilter"),
+                format!("cannot filter groups by '{}'", attribute),
+            ));
+        }
+        query.filters.insert("name".to_string(), value.clone());
+    }
+
+    let page = match group_repository(&state).list_page(&query).await {
+        Ok(page) => page,
+        Err(e) => return error_response(e, location, "list_scim_groups"),
+    };
+    let mut resources = Vec::new();
+    if params.count > 0 {
+        for group in &page.items {
+            match group_members(&state, group.id).await {
+                Ok(members) => resources.push(ScimGroup::from_group(group, &members)),
+                Err(e) => return error_response(e, location, "list_scim_groups"),
+            }
+        }
+    }
+    scim_response(StatusCode::OK, ScimListResponse::new(resources, page.total, params.start_index))
+}
+
+pub async fn create_scim_group(
+    State(state): State<Arc<ConcreteAppState>>,
+    context: RequestContext,
+    Json(body): Json<ScimGroup>,
+) -> impl IntoResponse {
+    use crate::use_cases::group::CreateGroupUseCase;
+
+    let location = concat!(file!(), ":", line!());
+    let members = match body.member_ids() {
+        Ok(members) => members,
+        Err(e) => return scim_error_response(e),
+    };
+    match group_repository(&state).find_by_name(&body.display_name, context.organization_id).await {
+        Ok(Some(_)) => {
+            return scim_error_response(ScimError::new(409, Some("uniqueness"), "displayName is already in use"))
+        }
+        Ok(None) => {}
+        Err(e) => return error_response(e, location, "create_scim_group"),
+    }
+
+    let use_case = CreateGroupUseCase::new(Box::new(group_repository(&state)), state.relationship_store.clone());
+    let group = match use_case.execute(&body.display_name, None, context.organization_id).await {
+        Ok(group) => group,
+        Err(e) => return error_response(e, location, "create_scim_group"),
+    };
+    let patch = GroupPatch { add_members: members, ..Default::default() };
+    if let Err(e) = sync_members(&state, &context, group.id, &[], &patch).await {
+        return error_response(e, location, "create_scim_group");
+    }
+    match group_members(&state, group.id).await {
+        Ok(members) => {
+            tracing::info!("SCIM provisioned group {} (by {})", group.id, context.user_id);
+            created_response(format!("/scim/v2/Groups/{}", group.id), ScimGroup::from_group(&group, &members))
+        }
+        Err(e) => error_response(e, location, "create_scim_group"),
+    }
+}
+
+pub async fn get_scim_group(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let group = match group_repository(&state).find_by_id(id).await {
+        Ok(Some(group)) => group,
+        Ok(None) => return not_found("Group", id),
+        Err(e) => return error_response(e, location, "get_scim_group"),
+    };
+    match group_members(&state, id).await {
+        Ok(members) => scim_response(StatusCode::OK, ScimGroup::from_group(&group, &members)),
+        Err(e) => error_response(e, location, "get_scim_group"),
+    }
+}
+
+/// Replace a group's displayName and member list
+pub async fn replace_scim_group(
+    State(state): State<Arc<ConcreteAppState>>,
+    context: RequestContext,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+    Json(body): Json<ScimGroup>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let members = match body.member_ids() {
+        Ok(members) => members,
+        Err(e) => return scim_error_response(e),
+    };
+    let patch = GroupPatch {
+        display_name: Some(body.display_name),
+        replace_members: Some(members),
+        ..Default::default()
+    };
+    match apply_group_patch(&state, &context, id, patch, &headers).await {
+        Ok(Some(group)) => scim_response(StatusCode::OK, group),
+        Ok(None) => not_found("Group", id),
+        Err(e) => error_response(e, location, "replace_scim_group"),
+    }
+}
+
+/// Apply a SCIM PatchOp (rename, add or remove members)
+pub async fn patch_scim_group(
+    State(state): State<Arc<ConcreteAppState>>,
+    context: RequestContext,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+    Json(body): Json<ScimPatchRequest>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let patch = match GroupPatch::from_operations(&body.operations) {
+        Ok(patch) => patch,
+        Err(e) => return scim_error_response(e),
+    };
+    match apply_group_patch(&state, &context, id, patch, &headers).await {
+        Ok(Some(group)) => scim_response(StatusCode::OK, group),
+        Ok(None) => not_found("Group", id),
+        Err(e) => error_response(e, location, "patch_scim_group"),
+    }
+}
+
+pub async fn delete_scim_group(
+    State(state): State<Arc<ConcreteAppState>>,
+    context: RequestContext,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let repository = group_repository(&state);
+    match repository.find_by_id(id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return not_found("Group", id),
+        Err(e) => return error_response(e, location, "delete_scim_group"),
+    }
+    match repository.soft_delete(id, Some(context.user_id)).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => error_response(e, location, "delete_scim_group"),
+    }
+}
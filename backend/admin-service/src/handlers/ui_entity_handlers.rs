@@ -1,6 +1,7 @@
-use axum::{Json, extract::{State, Path}, http::StatusCode, response::IntoResponse};
+use axum::{Json, extract::{State, Path}, http::{HeaderMap, StatusCode}, response::IntoResponse};
 use serde::{Deserialize, Serialize};
 use shared::domain::repositories::UiEntityRepository;
+use shared::infrastructure::api::etag::{conditional_json, weak_etag_for_rows};
 use shared::infrastructure::repositories::UiEntityRepositoryImpl;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -163,21 +164,21 @@ pub async fn register_page(
 }
 
 /// List all registered pages
+///
+/// Honors `If-None-Match`, returning 304 while no page has changed.
 pub async fn list_pages(
     State(state): State<Arc<ConcreteAppState>>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let ui_entity_repository = UiEntityRepositoryImpl::new(state.database_pool.as_ref().clone());
     
     match ui_entity_repository.list_pages().await {
         Ok(pages) => {
             let pages_response: Vec<PageResponse> = pages.iter().map(PageResponse::from).collect();
-            (
-                StatusCode::OK,
-                Json(serde_json::json!({
-                    "pages": pages_response
-                })),
-            )
-                .into_response()
+            let etag = weak_etag_for_rows(pages.iter().map(|row| (row.id, row.version, row.updated_at)));
+            conditional_json(&headers, etag, serde_json::json!({
+                "pages": pages_response
+            }))
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -194,13 +195,14 @@ pub async fn register_button(
     State(state): State<Arc<ConcreteAppState>>,
     Json(request): Json<RegisterButtonRequest>,
 ) -> impl IntoResponse {
-    use crate::use_cases::ui::RegisterButtonUseCase;
+    use crate::use_cases::ui::{MarkupPolicy, RegisterButtonUseCase};
     
     let ui_entity_repository = Box::new(UiEntityRepositoryImpl::new(state.database_pool.as_ref().clone()));
     let use_case = RegisterButtonUseCase::new(
         ui_entity_repository,
         state.relationship_store.clone(),
-    );
+    )
+    .with_markup_policy(MarkupPolicy::from_env());
     
     match use_case.execute(request.page_id, &request.button_id, &request.label, request.action).await {
         Ok(button) => (
@@ -219,22 +221,22 @@ pub async fn register_button(
 }
 
 /// List buttons for a page
+///
+/// Honors `If-None-Match`, returning 304 while no button has changed.
 pub async fn list_buttons_for_page(
     State(state): State<Arc<ConcreteAppState>>,
     Path(page_id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let ui_entity_repository = UiEntityRepositoryImpl::new(state.database_pool.as_ref().clone());
     
     match ui_entity_repository.list_buttons_for_page(page_id).await {
         Ok(buttons) => {
             let buttons_response: Vec<ButtonResponse> = buttons.iter().map(ButtonResponse::from).collect();
-            (
-                StatusCode::OK,
-                Json(serde_json::json!({
-                    "buttons": buttons_response
-                })),
-            )
-                .into_response()
+            let etag = weak_etag_for_rows(buttons.iter().map(|row| (row.id, row.version, row.updated_at)));
+            conditional_json(&headers, etag, serde_json::json!({
+                "buttons": buttons_response
+            }))
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -251,13 +253,14 @@ pub async fn register_field(
     State(state): State<Arc<ConcreteAppState>>,
     Json(request): Json<RegisterFieldRequest>,
 ) -> impl IntoResponse {
-    use crate::use_cases::ui::RegisterFieldUseCase;
+    use crate::use_cases::ui::{MarkupPolicy, RegisterFieldUseCase};
     
     let ui_entity_repository = Box::new(UiEntityRepositoryImpl::new(state.database_pool.as_ref().clone()));
     let use_case = RegisterFieldUseCase::new(
         ui_entity_repository,
         state.relationship_store.clone(),
-    );
+    )
+    .with_markup_policy(MarkupPolicy::from_env());
     
     match use_case.execute(request.page_id, &request.field_id, &request.label, &request.field_type).await {
         Ok(field) => (
@@ -276,22 +279,22 @@ pub async fn register_field(
 }
 
 /// List fields for a page
+///
+/// Honors `If-None-Match`, returning 304 while no field has changed.
 pub async fn list_fields_for_page(
     State(state): State<Arc<ConcreteAppState>>,
     Path(page_id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let ui_entity_repository = UiEntityRepositoryImpl::new(state.database_pool.as_ref().clone());
     
     match ui_entity_repository.list_fields_for_page(page_id).await {
         Ok(fields) => {
             let fields_response: Vec<FieldResponse> = fields.iter().map(FieldResponse::from).collect();
-            (
-                StatusCode::OK,
-                Json(serde_json::json!({
-                    "fields": fields_response
-                })),
-            )
-                .into_response()
+            let etag = weak_etag_for_rows(fields.iter().map(|row| (row.id, row.version, row.updated_at)));
+            conditional_json(&headers, etag, serde_json::json!({
+                "fields": fields_response
+            }))
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -333,21 +336,21 @@ pub async fn register_api(
 }
 
 /// List all registered API endpoints
+///
+/// Honors `If-None-Match`, returning 304 while no api has changed.
 pub async fn list_apis(
     State(state): State<Arc<ConcreteAppState>>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let ui_entity_repository = UiEntityRepositoryImpl::new(state.database_pool.as_ref().clone());
     
     match ui_entity_repository.list_apis().await {
         Ok(apis) => {
             let apis_response: Vec<ApiResponse> = apis.iter().map(ApiResponse::from).collect();
-            (
-                StatusCode::OK,
-                Json(serde_json::json!({
-                    "apis": apis_response
-                })),
-            )
-                .into_response()
+            let etag = weak_etag_for_rows(apis.iter().map(|row| (row.id, row.version, row.updated_at)));
+            conditional_json(&headers, etag, serde_json::json!({
+                "apis": apis_response
+            }))
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -359,3 +362,52 @@ pub async fn list_apis(
     }
 }
 
+
+fn entity_kind(kind: &str) -> Result<shared::domain::entities::UiEntityKind, (StatusCode, Json<serde_json::Value>)> {
+    kind.parse().map_err(|e: String| (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": e }))))
+}
+
+/// Superseded versions of a page, button, field or API, newest first
+///
+/// `kind` is the plural used by the other UI routes (`pages`, `buttons`,
+/// `fields`, `apis`).
+pub async fn ui_entity_history(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path((kind, id)): Path<(String, Uuid)>,
+) -> impl IntoResponse {
+    use crate::use_cases::ui::UiEntityHistoryUseCase;
+
+    let location = concat!(file!(), ":", line!());
+    let kind = match entity_kind(&kind) {
+        Ok(kind) => kind,
+        Err(response) => return response.into_response(),
+    };
+    let ui_entity_repository = Box::new(UiEntityRepositoryImpl::new(state.database_pool.as_ref().clone()));
+    match UiEntityHistoryUseCase::new(ui_entity_repository).history(kind, id).await {
+        Ok(versions) => Json(serde_json::json!({ "versions": versions })).into_response(),
+        Err(e) => super::admin_handlers::error_response(e, location, "ui_entity_history"),
+    }
+}
+
+/// Roll a page, button, field or API back to an earlier version
+///
+/// The restored content becomes a new version; the one it replaces stays in
+/// the history.
+pub async fn restore_ui_entity_version(
+    State(state): State<Arc<ConcreteAppState>>,
+    context: shared::RequestContext,
+    Path((kind, id, version)): Path<(String, Uuid, i64)>,
+) -> impl IntoResponse {
+    use crate::use_cases::ui::UiEntityHistoryUseCase;
+
+    let location = concat!(file!(), ":", line!());
+    let kind = match entity_kind(&kind) {
+        Ok(kind) => kind,
+        Err(response) => return response.into_response(),
+    };
+    let ui_entity_repository = Box::new(UiEntityRepositoryImpl::new(state.database_pool.as_ref().clone()));
+    match UiEntityHistoryUseCase::new(ui_entity_repository).restore(kind, id, version, &context).await {
+        Ok(entity) => Json(entity).into_response(),
+        Err(e) => super::admin_handlers::error_response(e, location, "restore_ui_entity_version"),
+    }
+}
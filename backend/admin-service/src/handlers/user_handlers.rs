@@ -1,9 +1,21 @@
-use axum::{Json, extract::Path, http::StatusCode, response::IntoResponse};
+use axum::{Json, extract::{Path, State}, http::StatusCode, response::IntoResponse};
 use crate::dto::{CreateUserRequest, UpdateUserRequest};
+use shared::ValidatedJson;
+use std::sync::Arc;
 use uuid::Uuid;
 
+// Type aliases for convenience
+type ConcreteAppState = shared::AppState<
+    authz_core::auth::LoginUseCase,
+    authz_core::auth::RefreshTokenUseCase,
+    authz_core::auth::LogoutUseCase,
+    authz_core::auth::UserInfoUseCase,
+    crate::use_cases::setup::SetupOrganizationUseCase,
+    crate::use_cases::setup::CreateSuperAdminUseCase,
+>;
+
 pub async fn create_user(
-    Json(_request): Json<CreateUserRequest>,
+    ValidatedJson(_request): ValidatedJson<CreateUserRequest>,
 ) -> impl IntoResponse {
     // TODO: Implement when database is configured
     (StatusCode::NOT_IMPLEMENTED, Json(serde_json::json!({"error": "Not yet implemented - database not configured"})))
@@ -16,12 +28,51 @@ pub async fn get_user(
     (StatusCode::NOT_IMPLEMENTED, Json(serde_json::json!({"error": "Not yet implemented - database not configured"})))
 }
 
+/// Update a user
+///
+/// The request carries the version the client last read; a stale version
+/// is answered with 409 so the client can refetch instead of overwriting.
 pub async fn update_user(
-    Path(_id): Path<Uuid>,
-    Json(_request): Json<UpdateUserRequest>,
+    State(state): State<Arc<ConcreteAppState>>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<UpdateUserRequest>,
 ) -> impl IntoResponse {
-    // TODO: Implement when database is configured
-    (StatusCode::NOT_IMPLEMENTED, Json(serde_json::json!({"error": "Not yet implemented - database not configured"})))
+    use crate::use_cases::user::UpdateUserUseCase;
+    use shared::infrastructure::repositories::UserRepositoryImpl;
+
+    let user_repository = Box::new(UserRepositoryImpl::new(state.database_service.clone()));
+    let use_case = UpdateUserUseCase::new(user_repository);
+
+    let location = concat!(file!(), ":", line!());
+    match use_case.execute(id, request).await {
+        Ok(user) => (StatusCode::OK, Json(user)).into_response(),
+        Err(e) => super::admin_handlers::error_response(e, location, "update_user"),
+    }
+}
+
+/// Deactivate a user
+///
+/// The user is kept but can no longer sign in, and `UserDeactivated` is
+/// published so their sessions are revoked. Deactivating an inactive user
+/// succeeds with `deactivated: false`.
+pub async fn deactivate_user(
+    State(state): State<Arc<ConcreteAppState>>,
+    context: shared::RequestContext,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    use crate::use_cases::user::DeactivateUserUseCase;
+
+    let use_case = DeactivateUserUseCase::new(state.database_pool.as_ref().clone(), state.outbox_relay.clone());
+
+    let location = concat!(file!(), ":", line!());
+    match use_case.execute(id, Some(context.user_id)).await {
+        Ok(deactivated) => (
+            StatusCode::OK,
+            Json(serde_json::json!({"user_id": id, "deactivated": deactivated})),
+        )
+            .into_response(),
+        Err(e) => super::admin_handlers::error_response(e, location, "deactivate_user"),
+    }
 }
 
 pub async fn delete_user(
@@ -2,6 +2,7 @@ pub mod handlers;
 pub mod dashboard;
 pub mod dto;
 pub mod use_cases;
+pub mod grpc;
 
 pub use handlers::*;
 pub use dashboard::*;
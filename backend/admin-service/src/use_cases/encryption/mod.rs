@@ -0,0 +1,3 @@
+pub mod scan_relationship_integrity;
+
+pub use scan_relationship_integrity::{IntegrityScanReport, ScanRelationshipIntegrityUseCase};
@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use shared::domain::repositories::RelationshipRepository;
+use shared::infrastructure::encryption::{MetadataIntegrity, RelationshipEncryption};
+use shared::AppResult;
+use uuid::Uuid;
+
+/// Relationships loaded per query during a scan
+const SCAN_BATCH_SIZE: u32 = 500;
+
+/// A relationship whose encrypted metadata did not verify
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityFailure {
+    pub relationship_id: Uuid,
+    /// `user#relation@object`
+    pub tuple: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityScanReport {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub scanned: usize,
+    pub valid: usize,
+    /// Marked for encryption but never encrypted
+    pub pending: usize,
+    pub failures: Vec<IntegrityFailure>,
+}
+
+/// Verifies every relationship's encrypted metadata
+///
+/// Intended for periodic consistency audits: each ciphertext is decrypted
+/// and authenticated, and any that fail are reported. Nothing is modified.
+pub struct ScanRelationshipIntegrityUseCase {
+    relationship_repository: Box<dyn RelationshipRepository>,
+    encryption: RelationshipEncryption,
+}
+
+impl ScanRelationshipIntegrityUseCase {
+    pub fn new(
+        relationship_repository: Box<dyn RelationshipRepository>,
+        encryption: RelationshipEncryption,
+    ) -> Self {
+        Self {
+            relationship_repository,
+            encryption,
+        }
+    }
+
+    pub async fn execute(&self) -> AppResult<IntegrityScanReport> {
+        let started_at = Utc::now();
+        let mut report = IntegrityScanReport {
+            started_at,
+            finished_at: started_at,
+            scanned: 0,
+            valid: 0,
+            pending: 0,
+            failures: Vec::new(),
+        };
+
+        let mut after = None;
+        loop {
+            let batch = self
+                .relationship_repository
+                .find_with_encrypted_metadata(after, SCAN_BATCH_SIZE)
+                .await?;
+            let Some(last) = batch.last() else {
+                break;
+            };
+            after = Some(last.id);
+
+            for relationship in &batch {
+                report.scanned += 1;
+                match self.encryption.verify(relationship).await {
+                    MetadataIntegrity::Valid => report.valid += 1,
+                    MetadataIntegrity::PendingEncryption => report.pending += 1,
+                    // Only encrypted rows are loaded; a plaintext row here was
+                    // changed after the query ran
+                    MetadataIntegrity::NotEncrypted => {}
+                    MetadataIntegrity::Failed(reason) => report.failures.push(IntegrityFailure {
+                        relationship_id: relationship.id,
+                        tuple: format!("{}#{}@{}", relationship.user, relationship.relation, relationship.object),
+                        reason,
+                    }),
+                }
+            }
+            if (batch.len() as u32) < SCAN_BATCH_SIZE {
+                break;
+            }
+        }
+
+        report.finished_at = Utc::now();
+        if !report.failures.is_empty() {
+            tracing::warn!(
+                "Relationship integrity scan found {} of {} encrypted relationship(s) failing verification",
+                report.failures.len(),
+                report.scanned
+            );
+        }
+        Ok(report)
+    }
+}
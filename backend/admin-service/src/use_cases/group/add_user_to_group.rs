@@ -1,4 +1,5 @@
 use shared::domain::repositories::UserRepository;
+use shared::infrastructure::encryption::GroupKeyring;
 use shared::infrastructure::zanzibar::RelationshipStore;
 use shared::AppResult;
 use uuid::Uuid;
@@ -7,16 +8,19 @@ use std::sync::Arc;
 pub struct AddUserToGroupUseCase {
     user_repository: Box<dyn UserRepository>,
     relationship_store: Arc<RelationshipStore>,
+    group_keyring: Arc<GroupKeyring>,
 }
 
 impl AddUserToGroupUseCase {
     pub fn new(
         user_repository: Box<dyn UserRepository>,
         relationship_store: Arc<RelationshipStore>,
+        group_keyring: Arc<GroupKeyring>,
     ) -> Self {
         Self {
             user_repository,
             relationship_store,
+            group_keyring,
         }
     }
 
@@ -57,6 +61,15 @@ impl AddUserToGroupUseCase {
                 e
             })?;
 
+        // Share the group DEK with the new member; existing data is not re-encrypted
+        self.group_keyring
+            .grant_member(group_id, user_id)
+            .await
+            .map_err(|e| {
+                e.log_with_operation(location, "add_user_to_group");
+                e
+            })?;
+
         Ok(())
     }
 }
@@ -1,8 +1,12 @@
 pub mod create_group;
 pub mod add_user_to_group;
+pub mod remove_user_from_group;
 pub mod assign_role_to_group;
+pub mod update_group;
 
 pub use create_group::CreateGroupUseCase;
 pub use add_user_to_group::AddUserToGroupUseCase;
+pub use remove_user_from_group::RemoveUserFromGroupUseCase;
 pub use assign_role_to_group::AssignRoleToGroupUseCase;
+pub use update_group::UpdateGroupUseCase;
 
@@ -0,0 +1,53 @@
+use shared::infrastructure::encryption::{GroupKeyRotation, GroupKeyring};
+use shared::infrastructure::zanzibar::RelationshipStore;
+use shared::AppResult;
+use uuid::Uuid;
+use std::sync::Arc;
+
+pub struct RemoveUserFromGroupUseCase {
+    relationship_store: Arc<RelationshipStore>,
+    group_keyring: Arc<GroupKeyring>,
+}
+
+impl RemoveUserFromGroupUseCase {
+    pub fn new(
+        relationship_store: Arc<RelationshipStore>,
+        group_keyring: Arc<GroupKeyring>,
+    ) -> Self {
+        Self {
+            relationship_store,
+            group_keyring,
+        }
+    }
+
+    /// Remove the membership, then revoke the user's group DEK shares
+    ///
+    /// Revocation rotates the group DEK and re-wraps it for the remaining
+    /// members; returns that rotation, or `None` if the user held no shares.
+    pub async fn execute(
+        &self,
+        user_id: Uuid,
+        group_id: Uuid,
+        removed_by: Option<Uuid>,
+    ) -> AppResult<Option<GroupKeyRotation>> {
+        let location = concat!(file!(), ":", line!());
+        let user_str = format!("user:{}", user_id);
+        let group_str = format!("group:{}", group_id);
+
+        self.relationship_store
+            .soft_delete(&user_str, "member", &group_str, removed_by)
+            .await
+            .map_err(|e| {
+                e.log_with_operation(location, "remove_user_from_group");
+                e
+            })?;
+
+        self.group_keyring
+            .revoke_member(group_id, user_id)
+            .await
+            .map_err(|e| {
+                e.log_with_operation(location, "remove_user_from_group");
+                e
+            })
+    }
+}
@@ -0,0 +1,61 @@
+use shared::domain::entities::Group;
+use shared::domain::repositories::GroupRepository;
+use shared::AppResult;
+use uuid::Uuid;
+
+/// Rename or re-describe a group
+///
+/// Relationships reference groups by id, so renaming is safe.
+pub struct UpdateGroupUseCase {
+    group_repository: Box<dyn GroupRepository>,
+}
+
+impl UpdateGroupUseCase {
+    pub fn new(group_repository: Box<dyn GroupRepository>) -> Self {
+        Self { group_repository }
+    }
+
+    /// `version` is the version the client last read
+    pub async fn execute(
+        &self,
+        group_id: Uuid,
+        name: Option<String>,
+        description: Option<String>,
+        version: i64,
+    ) -> AppResult<Group> {
+        let mut group = self.group_repository
+            .find_by_id(group_id)
+            .await?
+            .ok_or_else(|| shared::AppError::NotFound(
+                format!("Group {} not found", group_id)
+            ))?;
+
+        // Fail fast on a stale edit; the repository re-checks atomically
+        shared::AppError::check_version("Group", group_id, version, group.version)?;
+
+        if let Some(name) = name {
+            if name.trim().is_empty() {
+                return Err(shared::AppError::Validation(
+                    "Group name cannot be empty".to_string(),
+                ));
+            }
+            if let Some(existing) = self.group_repository
+                .find_by_name(&name, group.organization_id)
+                .await?
+            {
+                if existing.id != group_id {
+                    return Err(shared::AppError::Validation(
+                        "Group with this name already exists in the organization".to_string(),
+                    ));
+                }
+            }
+            group.name = name;
+        }
+        if description.is_some() {
+            group.description = description;
+        }
+
+        group.updated_at = chrono::Utc::now();
+        self.group_repository.update(group).await
+    }
+}
@@ -4,6 +4,7 @@ pub mod group;
 pub mod permission;
 pub mod role;
 pub mod ui;
+pub mod encryption;
 
 pub use setup::*;
 pub use user::*;
@@ -11,4 +12,5 @@ pub use group::*;
 pub use permission::*;
 pub use role::*;
 pub use ui::*;
+pub use encryption::*;
 
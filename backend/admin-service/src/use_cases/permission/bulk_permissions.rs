@@ -0,0 +1,338 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use shared::domain::entities::Relationship;
+use shared::domain::events::{DomainEvent, EventEnvelope};
+use shared::infrastructure::events::{OutboxRelay, OutboxStore};
+use shared::infrastructure::zanzibar::{RelationshipStore, RelationshipTuple};
+use shared::AppResult;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Largest number of permissions accepted in one batch
+pub const MAX_BULK_PERMISSIONS: usize = 500;
+
+/// Who the permissions are granted to or revoked from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id", rename_all = "snake_case")]
+pub enum PermissionSubject {
+    User(Uuid),
+    Group(Uuid),
+}
+
+impl PermissionSubject {
+    pub fn to_zanzibar(&self) -> String {
+        match self {
+            PermissionSubject::User(id) => format!("user:{}", id),
+            PermissionSubject::Group(id) => format!("group:{}", id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkAction {
+    Grant,
+    Revoke,
+}
+
+impl BulkAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BulkAction::Grant => "grant",
+            BulkAction::Revoke => "revoke",
+        }
+    }
+}
+
+/// One permission in a batch
+#[derive(Debug, Clone, Deserialize)]
+pub struct PermissionSpec {
+    pub relation: String,
+    pub object: String,
+    /// Only used when granting
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// What the batch did to one permission
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionOutcome {
+    Granted,
+    /// Already granted; left untouched
+    AlreadyGranted,
+    Revoked,
+    /// Nothing to revoke
+    NotGranted,
+}
+
+impl PermissionOutcome {
+    pub fn changed(&self) -> bool {
+        matches!(self, PermissionOutcome::Granted | PermissionOutcome::Revoked)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionResult {
+    pub relation: String,
+    pub object: String,
+    pub outcome: PermissionOutcome,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkPermissionResult {
+    pub subject: String,
+    pub action: BulkAction,
+    pub results: Vec<PermissionResult>,
+    pub changed: usize,
+    /// Id of the audit event, absent when nothing changed
+    pub event_id: Option<Uuid>,
+}
+
+/// Grants or revokes a set of permissions for one user or group
+///
+/// The whole batch runs in one transaction: if any permission fails, none
+/// are applied. Re-granting an existing permission (or revoking a missing
+/// one) is a no-op, so a role template can be applied repeatedly. A single
+/// `PermissionsBatchApplied` event is written to the outbox in the same
+/// transaction when anything changed.
+pub struct BulkPermissionUseCase {
+    pool: PgPool,
+    relationship_store: Arc<RelationshipStore>,
+    relay: Option<Arc<OutboxRelay>>,
+}
+
+impl BulkPermissionUseCase {
+    pub fn new(
+        pool: PgPool,
+        relationship_store: Arc<RelationshipStore>,
+        relay: Option<Arc<OutboxRelay>>,
+    ) -> Self {
+        Self {
+            pool,
+            relationship_store,
+            relay,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        subject: PermissionSubject,
+        action: BulkAction,
+        permissions: Vec<PermissionSpec>,
+        applied_by: Option<Uuid>,
+    ) -> AppResult<BulkPermissionResult> {
+        let subject_str = subject.to_zanzibar();
+        self.validate(&subject_str, action, &permissions)?;
+
+        let mut tx = self.pool.begin().await.map_err(shared::AppError::Database)?;
+        let mut results = Vec::with_capacity(permissions.len());
+        for permission in permissions {
+            let outcome = match action {
+                BulkAction::Grant => grant(&mut tx, &subject_str, &permission, applied_by).await,
+                BulkAction::Revoke => revoke(&mut tx, &subject_str, &permission, applied_by).await,
+            }
+            .map_err(|e| shared::AppError::Internal(format!(
+                "Failed to {} {}@{}, batch rolled back: {}",
+                action.as_str(), permission.relation, permission.object, e
+            )))?;
+            results.push(PermissionResult {
+                relation: permission.relation,
+                object: permission.object,
+                outcome,
+            });
+        }
+
+        let changed: Vec<String> = results
+            .iter()
+            .filter(|r| r.outcome.changed())
+            .map(|r| format!("{}@{}", r.relation, r.object))
+            .collect();
+        let event_id = if changed.is_empty() {
+            None
+        } else {
+            let event = EventEnvelope::new(DomainEvent::PermissionsBatchApplied {
+                subject: subject_str.clone(),
+                action: action.as_str().to_string(),
+                unchanged: results.len() - changed.len(),
+                changed: changed.clone(),
+                applied_by,
+            });
+            OutboxStore::append(&mut *tx, &event).await?;
+            Some(event.id)
+        };
+        tx.commit().await.map_err(shared::AppError::Database)?;
+
+        if event_id.is_some() {
+            if let Some(relay) = &self.relay {
+                relay.notify();
+            }
+        }
+
+        Ok(BulkPermissionResult {
+            subject: subject_str,
+            action,
+            results,
+            changed: changed.len(),
+            event_id,
+        })
+    }
+
+    /// Check every tuple up front so a malformed entry fails the batch
+    /// before any statement runs
+    fn validate(&self, subject: &str, action: BulkAction, permissions: &[PermissionSpec]) -> AppResult<()> {
+        if permissions.is_empty() {
+            return Err(shared::AppError::Validation("permissions must not be empty".to_string()));
+        }
+        if permissions.len() > MAX_BULK_PERMISSIONS {
+            return Err(shared::AppError::Validation(format!(
+                "at most {} permissions per batch",
+                MAX_BULK_PERMISSIONS
+            )));
+        }
+
+        let mut errors = Vec::new();
+        for (index, permission) in permissions.iter().enumerate() {
+            let tuple = RelationshipTuple::new(
+                subject.to_string(),
+                permission.relation.clone(),
+                permission.object.clone(),
+            );
+            let mut result = tuple.validate();
+            if result.is_ok() && action == BulkAction::Grant {
+                if let Some(schema) = self.relationship_store.schema() {
+                    result = schema.validate_tuple(&tuple.user, &tuple.relation, &tuple.object);
+                }
+            }
+            if let Err(e) = result {
+                errors.push(format!("permissions[{}] ({}): {}", index, tuple.to_string(), e));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(shared::AppError::Validation(errors.join("; ")))
+        }
+    }
+}
+
+async fn grant(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    subject: &str,
+    permission: &PermissionSpec,
+    granted_by: Option<Uuid>,
+) -> Result<PermissionOutcome, sqlx::Error> {
+    let mut relationship = match permission.expires_at {
+        Some(expires_at) => Relationship::new_with_expiration(
+            subject.to_string(),
+            permission.relation.clone(),
+            permission.object.clone(),
+            expires_at,
+        ),
+        None => Relationship::new(
+            subject.to_string(),
+            permission.relation.clone(),
+            permission.object.clone(),
+        ),
+    };
+    relationship.created_by = granted_by;
+    relationship.updated_by = granted_by;
+
+    // Insert only if no live tuple exists, so re-granting leaves the
+    // existing row (and its version) untouched
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO relationships (
+            id, "user", relation, object, organization_id, created_at, valid_from, expires_at,
+            is_active, metadata, deleted_at, deleted_by, request_id, updated_at,
+            created_by, updated_by, system_id, version
+        )
+        SELECT $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18
+        WHERE NOT EXISTS (
+            SELECT 1 FROM relationships
+            WHERE "user" = $2 AND relation = $3 AND object = $4 AND deleted_at IS NULL
+        )
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(relationship.id)
+    .bind(&relationship.user)
+    .bind(&relationship.relation)
+    .bind(&relationship.object)
+    .bind(relationship.organization_id)
+    .bind(relationship.created_at)
+    .bind(relationship.valid_from)
+    .bind(relationship.expires_at)
+    .bind(relationship.is_active)
+    .bind(&relationship.metadata)
+    .bind(relationship.deleted_at)
+    .bind(relationship.deleted_by)
+    .bind(&relationship.request_id)
+    .bind(relationship.updated_at)
+    .bind(relationship.created_by)
+    .bind(relationship.updated_by)
+    .bind(&relationship.system_id)
+    .bind(relationship.version)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(if inserted.rows_affected() > 0 {
+        PermissionOutcome::Granted
+    } else {
+        PermissionOutcome::AlreadyGranted
+    })
+}
+
+async fn revoke(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    subject: &str,
+    permission: &PermissionSpec,
+    revoked_by: Option<Uuid>,
+) -> Result<PermissionOutcome, sqlx::Error> {
+    let revoked = sqlx::query(
+        r#"
+        UPDATE relationships
+        SET deleted_at = NOW(),
+            deleted_by = $4,
+            is_active = false,
+            updated_at = NOW(),
+            version = version + 1
+        WHERE "user" = $1 AND relation = $2 AND object = $3
+        AND deleted_at IS NULL
+        "#,
+    )
+    .bind(subject)
+    .bind(&permission.relation)
+    .bind(&permission.object)
+    .bind(revoked_by)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(if revoked.rows_affected() > 0 {
+        PermissionOutcome::Revoked
+    } else {
+        PermissionOutcome::NotGranted
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subject_serialization() {
+        let id = Uuid::new_v4();
+        let subject: PermissionSubject =
+            serde_json::from_value(serde_json::json!({"type": "group", "id": id})).unwrap();
+        assert_eq!(subject, PermissionSubject::Group(id));
+        assert_eq!(subject.to_zanzibar(), format!("group:{}", id));
+    }
+
+    #[test]
+    fn test_only_grants_and_revokes_count_as_changes() {
+        assert!(PermissionOutcome::Granted.changed());
+        assert!(PermissionOutcome::Revoked.changed());
+        assert!(!PermissionOutcome::AlreadyGranted.changed());
+        assert!(!PermissionOutcome::NotGranted.changed());
+    }
+}
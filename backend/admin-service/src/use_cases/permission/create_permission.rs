@@ -40,14 +40,16 @@ impl CreatePermissionUseCase {
     ) -> AppResult<Relationship> {
         let user_str = format!("user:{}", user_id);
         
-        // Create relationship
-        let mut relationship = if let (Some(valid_from), Some(expires_at)) = (valid_from, expires_at) {
+        Relationship::validate_window(valid_from, expires_at).map_err(shared::AppError::Validation)?;
+
+        // Create relationship; a future `valid_from` schedules the grant
+        let mut relationship = if let Some(valid_from) = valid_from {
             Relationship::new_with_validity(
                 user_str.clone(),
                 relation.to_string(),
                 object.to_string(),
                 valid_from,
-                Some(expires_at),
+                expires_at,
             )
         } else if let Some(expires_at) = expires_at {
             Relationship::new_with_expiration(
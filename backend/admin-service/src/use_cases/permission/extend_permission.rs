@@ -1,9 +1,11 @@
+use shared::domain::entities::Relationship;
 use shared::infrastructure::zanzibar::RelationshipStore;
 use shared::AppResult;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use std::sync::Arc;
 
+/// Extends or shifts the validity window of a user's grant
 pub struct ExtendPermissionUseCase {
     relationship_store: Arc<RelationshipStore>,
 }
@@ -13,20 +15,27 @@ impl ExtendPermissionUseCase {
         Self { relationship_store }
     }
 
+    /// Set the window to `[new_valid_from, new_expires_at)`
+    ///
+    /// Without `new_valid_from` the current start is kept, which simply
+    /// extends (or shortens) the grant. The window must end in the future.
     pub async fn execute(
         &self,
         user_id: Uuid,
         relation: &str,
         object: &str,
+        new_valid_from: Option<DateTime<Utc>>,
         new_expires_at: DateTime<Utc>,
-    ) -> AppResult<()> {
+    ) -> AppResult<Relationship> {
+        if new_expires_at <= Utc::now() {
+            return Err(shared::AppError::Validation(
+                "valid_until must be in the future; revoke the permission instead".to_string(),
+            ));
+        }
         let user_str = format!("user:{}", user_id);
-        
+
         self.relationship_store
-            .extend_expiration(&user_str, relation, object, new_expires_at)
-            .await?;
-        
-        Ok(())
+            .reschedule(&user_str, relation, object, new_valid_from, Some(new_expires_at))
+            .await
     }
 }
-
@@ -0,0 +1,49 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use shared::domain::entities::Relationship;
+use shared::domain::repositories::RelationshipRepository;
+use shared::AppResult;
+
+/// Look-ahead used when the caller does not give one
+pub const SCHEDULED_WINDOW_DEFAULT_HOURS: i64 = 24 * 7;
+/// Longest look-ahead a caller may request
+pub const SCHEDULED_WINDOW_MAX_HOURS: i64 = 24 * 90;
+/// Largest number of grants returned per list
+pub const SCHEDULED_LIMIT_MAX: u32 = 500;
+
+/// Grants starting or ending within the look-ahead window
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledPermissions {
+    pub until: DateTime<Utc>,
+    /// Not yet in effect, soonest start first
+    pub upcoming: Vec<Relationship>,
+    /// In effect or scheduled, soonest end first
+    pub expiring: Vec<Relationship>,
+}
+
+/// Lists scheduled access so admins can review it before it starts or lapses
+pub struct ListScheduledPermissionsUseCase {
+    relationship_repository: Box<dyn RelationshipRepository>,
+}
+
+impl ListScheduledPermissionsUseCase {
+    pub fn new(relationship_repository: Box<dyn RelationshipRepository>) -> Self {
+        Self { relationship_repository }
+    }
+
+    pub async fn execute(&self, within_hours: Option<i64>, limit: Option<u32>) -> AppResult<ScheduledPermissions> {
+        let hours = within_hours.unwrap_or(SCHEDULED_WINDOW_DEFAULT_HOURS);
+        if hours <= 0 || hours > SCHEDULED_WINDOW_MAX_HOURS {
+            return Err(shared::AppError::Validation(format!(
+                "within_hours must be between 1 and {}",
+                SCHEDULED_WINDOW_MAX_HOURS
+            )));
+        }
+        let limit = limit.unwrap_or(SCHEDULED_LIMIT_MAX).clamp(1, SCHEDULED_LIMIT_MAX);
+        let until = Utc::now() + Duration::hours(hours);
+
+        let upcoming = self.relationship_repository.find_upcoming(until, limit).await?;
+        let expiring = self.relationship_repository.find_expiring(until, limit).await?;
+        Ok(ScheduledPermissions { until, upcoming, expiring })
+    }
+}
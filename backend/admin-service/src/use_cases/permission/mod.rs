@@ -1,8 +1,13 @@
 pub mod create_permission;
 pub mod extend_permission;
 pub mod revoke_permission;
+pub mod update_permission;
+pub mod bulk_permissions;
+pub mod list_scheduled_permissions;
 
 pub use create_permission::CreatePermissionUseCase;
 pub use extend_permission::ExtendPermissionUseCase;
 pub use revoke_permission::RevokePermissionUseCase;
-
+pub use update_permission::UpdatePermissionUseCase;
+pub use bulk_permissions::{BulkAction, BulkPermissionUseCase, PermissionSpec, PermissionSubject};
+pub use list_scheduled_permissions::{ListScheduledPermissionsUseCase, ScheduledPermissions};
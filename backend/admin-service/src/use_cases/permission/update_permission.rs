@@ -0,0 +1,40 @@
+use shared::domain::entities::Permission;
+use shared::domain::repositories::PermissionRepository;
+use shared::AppResult;
+use uuid::Uuid;
+
+/// Update a permission definition's description
+///
+/// Resource and action are what role grants point at, so they are fixed
+/// once the permission exists.
+pub struct UpdatePermissionUseCase {
+    permission_repository: Box<dyn PermissionRepository>,
+}
+
+impl UpdatePermissionUseCase {
+    pub fn new(permission_repository: Box<dyn PermissionRepository>) -> Self {
+        Self { permission_repository }
+    }
+
+    /// `version` is the version the client last read
+    pub async fn execute(
+        &self,
+        permission_id: Uuid,
+        description: Option<String>,
+        version: i64,
+    ) -> AppResult<Permission> {
+        let mut permission = self.permission_repository
+            .find_by_id(permission_id)
+            .await?
+            .ok_or_else(|| shared::AppError::NotFound(
+                format!("Permission {} not found", permission_id)
+            ))?;
+
+        // Fail fast on a stale edit; the repository re-checks atomically
+        shared::AppError::check_version("Permission", permission_id, version, permission.version)?;
+
+        permission.description = description;
+        permission.updated_at = chrono::Utc::now();
+        self.permission_repository.update(permission).await
+    }
+}
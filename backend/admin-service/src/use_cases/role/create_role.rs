@@ -0,0 +1,55 @@
+use shared::domain::entities::Role;
+use shared::domain::repositories::RoleRepository;
+use shared::{AppResult, RequestContext};
+use std::sync::Arc;
+use uuid::Uuid;
+use super::RolePolicySync;
+
+/// Create a role, and its vault policy when policy sync is configured
+///
+/// The role is removed again if the policy cannot be written, so a role
+/// never exists without its policy.
+pub struct CreateRoleUseCase {
+    role_repository: Box<dyn RoleRepository>,
+    policy_sync: Option<Arc<RolePolicySync>>,
+}
+
+impl CreateRoleUseCase {
+    pub fn new(role_repository: Box<dyn RoleRepository>, policy_sync: Option<Arc<RolePolicySync>>) -> Self {
+        Self { role_repository, policy_sync }
+    }
+
+    pub async fn execute(
+        &self,
+        name: &str,
+        description: Option<String>,
+        permissions: Vec<Uuid>,
+        context: &RequestContext,
+    ) -> AppResult<Role> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(shared::AppError::Validation("Role name cannot be empty".to_string()));
+        }
+        if self.role_repository.find_by_name(name).await?.is_some() {
+            return Err(shared::AppError::Validation(format!("Role {} already exists", name)));
+        }
+
+        let mut role = Role::new(name.to_string(), description);
+        role.permissions = permissions;
+        role.request_id = Some(context.request_id.clone());
+        role.created_by = Some(context.user_id);
+        role.updated_by = Some(context.user_id);
+        let role = self.role_repository.create(role).await?;
+
+        if let Some(policy_sync) = &self.policy_sync {
+            if let Err(e) = policy_sync.write(&role.name, context.organization_id).await {
+                tracing::warn!("Role policy write failed for {}, removing the role: {}", role.name, e);
+                if let Err(rollback) = self.role_repository.delete(role.id).await {
+                    tracing::error!("Failed to remove role {} after policy write failed: {}", role.id, rollback);
+                }
+                return Err(e);
+            }
+        }
+        Ok(role)
+    }
+}
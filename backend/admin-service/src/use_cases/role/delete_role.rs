@@ -0,0 +1,40 @@
+use shared::domain::repositories::RoleRepository;
+use shared::{AppResult, RequestContext};
+use std::sync::Arc;
+use uuid::Uuid;
+use super::RolePolicySync;
+
+/// Delete a role, and its vault policy when policy sync is configured
+///
+/// The policy goes first and is written back if the role cannot be
+/// deleted, so a role never exists without its policy.
+pub struct DeleteRoleUseCase {
+    role_repository: Box<dyn RoleRepository>,
+    policy_sync: Option<Arc<RolePolicySync>>,
+}
+
+impl DeleteRoleUseCase {
+    pub fn new(role_repository: Box<dyn RoleRepository>, policy_sync: Option<Arc<RolePolicySync>>) -> Self {
+        Self { role_repository, policy_sync }
+    }
+
+    pub async fn execute(&self, role_id: Uuid, context: &RequestContext) -> AppResult<()> {
+        let role = self.role_repository
+            .find_by_id(role_id)
+            .await?
+            .ok_or_else(|| shared::AppError::NotFound(format!("Role {} not found", role_id)))?;
+
+        if let Some(policy_sync) = &self.policy_sync {
+            policy_sync.delete(&role.name).await?;
+        }
+        if let Err(e) = self.role_repository.delete(role.id).await {
+            if let Some(policy_sync) = &self.policy_sync {
+                if let Err(restore) = policy_sync.write(&role.name, context.organization_id).await {
+                    tracing::error!("Failed to restore policy of role {} after delete failed: {}", role.name, restore);
+                }
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,11 @@
+pub mod create_role;
+pub mod delete_role;
+pub mod role_policy;
 pub mod sync_role_permissions;
+pub mod update_role;
 
+pub use create_role::CreateRoleUseCase;
+pub use delete_role::DeleteRoleUseCase;
+pub use role_policy::RolePolicySync;
 pub use sync_role_permissions::SyncRolePermissionsUseCase;
-
+pub use update_role::UpdateRoleUseCase;
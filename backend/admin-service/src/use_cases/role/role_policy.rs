@@ -0,0 +1,104 @@
+use shared::infrastructure::encryption::RustyVaultClient;
+use shared::{AppError, AppResult};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Used when `ROLE_POLICY_TEMPLATE_PATH` is not set: the role's own area of
+/// the organization's realm
+pub const DEFAULT_ROLE_POLICY_TEMPLATE: &str = r#"# Policy for role {{role}}
+path "secret/data/realms/{{org_id}}/roles/{{role}}/*" {
+    capabilities = ["create", "read", "update", "delete", "list"]
+}
+
+path "secret/metadata/realms/{{org_id}}/roles/{{role}}/*" {
+    capabilities = ["read", "list"]
+}
+"#;
+
+/// Keeps one vault ACL policy per role, named `role-{name}` and rendered
+/// from a template
+///
+/// The template may use `{{role}}` and `{{org_id}}`; the organization is the
+/// one of the admin managing the role.
+pub struct RolePolicySync {
+    vault: Arc<RustyVaultClient>,
+    template: String,
+}
+
+impl RolePolicySync {
+    pub fn new(vault: Arc<RustyVaultClient>, template: impl Into<String>) -> Self {
+        Self { vault, template: template.into() }
+    }
+
+    /// `None` unless `ROLE_POLICY_SYNC_ENABLED` is `true`; the template is
+    /// read from `ROLE_POLICY_TEMPLATE_PATH`, if set
+    pub fn from_env() -> AppResult<Option<Self>> {
+        let enabled = std::env::var("ROLE_POLICY_SYNC_ENABLED")
+            .map(|v| v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        if !enabled {
+            return Ok(None);
+        }
+        let template = match std::env::var("ROLE_POLICY_TEMPLATE_PATH").ok().filter(|p| !p.is_empty()) {
+            Some(path) => std::fs::read_to_string(&path).map_err(|e| {
+                AppError::Configuration(format!("Cannot read role policy template {}: {}", path, e))
+            })?,
+            None => DEFAULT_ROLE_POLICY_TEMPLATE.to_string(),
+        };
+        Ok(Some(Self::new(Arc::new(RustyVaultClient::from_env()?), template)))
+    }
+
+    pub fn policy_name(role_name: &str) -> String {
+        format!("role-{}", role_name)
+    }
+
+    pub fn render(&self, role_name: &str, organization_id: Option<Uuid>) -> AppResult<String> {
+        let policy = self.template.replace("{{role}}", role_name);
+        if !policy.contains("{{org_id}}") {
+            return Ok(policy);
+        }
+        let organization_id = organization_id.ok_or_else(|| {
+            AppError::Validation("The role policy template needs an organization".to_string())
+        })?;
+        Ok(policy.replace("{{org_id}}", &organization_id.to_string()))
+    }
+
+    /// Create or overwrite the role's policy
+    pub async fn write(&self, role_name: &str, organization_id: Option<Uuid>) -> AppResult<String> {
+        let name = Self::policy_name(role_name);
+        let policy = self.render(role_name, organization_id)?;
+        self.vault.write_policy(&name, &policy).await?;
+        Ok(name)
+    }
+
+    pub async fn delete(&self, role_name: &str) -> AppResult<()> {
+        self.vault.delete_policy(&Self::policy_name(role_name)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sync(template: &str) -> RolePolicySync {
+        RolePolicySync::new(Arc::new(RustyVaultClient::new("http://localhost:8201", "token", "secret")), template)
+    }
+
+    #[test]
+    fn test_template_substitutes_role_and_organization() {
+        let org = Uuid::new_v4();
+        let policy = sync(DEFAULT_ROLE_POLICY_TEMPLATE).render("nurse", Some(org)).unwrap();
+        assert!(policy.contains(&format!("secret/data/realms/{}/roles/nurse/*", org)));
+        assert!(!policy.contains("{{"));
+        assert_eq!(RolePolicySync::policy_name("nurse"), "role-nurse");
+    }
+
+    #[test]
+    fn test_organization_only_required_when_referenced() {
+        assert!(sync(DEFAULT_ROLE_POLICY_TEMPLATE).render("nurse", None).is_err());
+        let policy = sync(r#"path "secret/data/{{role}}/*" { capabilities = ["read"] }"#)
+            .render("nurse", None)
+            .unwrap();
+        assert_eq!(policy, r#"path "secret/data/nurse/*" { capabilities = ["read"] }"#);
+    }
+}
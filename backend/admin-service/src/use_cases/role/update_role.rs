@@ -0,0 +1,40 @@
+use shared::domain::entities::Role;
+use shared::domain::repositories::RoleRepository;
+use shared::AppResult;
+use uuid::Uuid;
+
+/// Update a role's description
+///
+/// The name is not editable: Zanzibar relationships key roles by name
+/// (`role:{name}`), so renaming would orphan every grant.
+pub struct UpdateRoleUseCase {
+    role_repository: Box<dyn RoleRepository>,
+}
+
+impl UpdateRoleUseCase {
+    pub fn new(role_repository: Box<dyn RoleRepository>) -> Self {
+        Self { role_repository }
+    }
+
+    /// `version` is the version the client last read
+    pub async fn execute(
+        &self,
+        role_id: Uuid,
+        description: Option<String>,
+        version: i64,
+    ) -> AppResult<Role> {
+        let mut role = self.role_repository
+            .find_by_id(role_id)
+            .await?
+            .ok_or_else(|| shared::AppError::NotFound(
+                format!("Role {} not found", role_id)
+            ))?;
+
+        // Fail fast on a stale edit; the repository re-checks atomically
+        shared::AppError::check_version("Role", role_id, version, role.version)?;
+
+        role.description = description;
+        role.updated_at = chrono::Utc::now();
+        self.role_repository.update(role).await
+    }
+}
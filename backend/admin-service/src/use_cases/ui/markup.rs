@@ -0,0 +1,104 @@
+use shared::{AppError, AppResult};
+
+/// Formatting tags a label may ever be allowed to use; none can carry
+/// attributes or run script
+pub const FORMATTING_TAGS: &[&str] = &[
+    "b", "strong", "i", "em", "u", "s", "small", "mark", "sub", "sup", "code", "br",
+];
+
+/// What markup admin-defined UI text may contain
+///
+/// UI definitions are rendered in other users' browsers, so anything that
+/// looks like a tag is rejected unless it is a bare formatting tag on the
+/// allow-list: `<b>` and `</b>`, never `<b onclick=...>`. The default allows
+/// none.
+#[derive(Debug, Clone, Default)]
+pub struct MarkupPolicy {
+    allowed_tags: Vec<String>,
+}
+
+impl MarkupPolicy {
+    /// Unknown tags are dropped from the list: only [`FORMATTING_TAGS`]
+    /// can be allowed
+    pub fn new<S: AsRef<str>>(allowed_tags: impl IntoIterator<Item = S>) -> Self {
+        let mut allowed = Vec::new();
+        for tag in allowed_tags {
+            let tag = tag.as_ref().trim().to_ascii_lowercase();
+            if tag.is_empty() {
+                continue;
+            }
+            if FORMATTING_TAGS.contains(&tag.as_str()) {
+                allowed.push(tag);
+            } else {
+                tracing::warn!("Ignoring <{}> in the UI label allow-list: not a formatting tag", tag);
+            }
+        }
+        Self { allowed_tags: allowed }
+    }
+
+    /// Tags from the comma-separated `UI_LABEL_ALLOWED_TAGS`, e.g. `b,i,br`
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("UI_LABEL_ALLOWED_TAGS").unwrap_or_default().split(','))
+    }
+
+    /// Reject `value` if it contains markup beyond the allowed tags
+    pub fn check(&self, attribute: &str, value: &str) -> AppResult<()> {
+        check_markup(attribute, value, &self.allowed_tags)
+    }
+}
+
+/// Reject any markup at all, for identifiers and other attributes that are
+/// never formatted
+pub fn check_plain_text(attribute: &str, value: &str) -> AppResult<()> {
+    check_markup(attribute, value, &[])
+}
+
+fn check_markup(attribute: &str, value: &str, allowed: &[String]) -> AppResult<()> {
+    let mut rest = value;
+    while let Some(start) = rest.find('<') {
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('>') else {
+            return Err(AppError::Validation(format!("{} contains an unclosed '<'", attribute)));
+        };
+        let tag = &after[..end];
+        let name = tag.strip_prefix('/').unwrap_or(tag);
+        let name = name.strip_suffix('/').unwrap_or(name).trim_end();
+        let bare = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric());
+        if !bare || !allowed.iter().any(|a| a.eq_ignore_ascii_case(name)) {
+            let allowed_list = if allowed.is_empty() {
+                "no markup is allowed".to_string()
+            } else {
+                format!("only {} are allowed", allowed.iter().map(|a| format!("<{}>", a)).collect::<Vec<_>>().join(", "))
+            };
+            return Err(AppError::Validation(format!(
+                "{} contains disallowed markup <{}>; {}",
+                attribute, tag, allowed_list
+            )));
+        }
+        rest = &after[end + 1..];
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_bare_allowed_tags_pass() {
+        let policy = MarkupPolicy::new(["b", "BR", "script", ""]);
+        assert!(policy.check("label", "Save <b>now</b><br/>").is_ok());
+        assert!(policy.check("label", "Price < 5").is_err());
+        assert!(policy.check("label", "<i>Save</i>").is_err());
+        assert!(policy.check("label", "<b onclick=\"steal()\">Save</b>").is_err());
+        assert!(policy.check("label", "<script>alert(1)</script>").is_err());
+        assert!(policy.check("label", "5 > 4").is_ok());
+    }
+
+    #[test]
+    fn test_plain_text_rejects_every_tag() {
+        assert!(check_plain_text("label", "Email address").is_ok());
+        let err = check_plain_text("label", "<img src=x onerror=alert(1)>").unwrap_err();
+        assert!(err.to_string().contains("no markup is allowed"));
+    }
+}
@@ -2,9 +2,15 @@ pub mod register_page;
 pub mod register_button;
 pub mod register_field;
 pub mod register_api;
+pub mod entity_history;
+pub mod markup;
+pub mod resolve_visibility;
 
 pub use register_page::RegisterPageUseCase;
 pub use register_button::RegisterButtonUseCase;
 pub use register_field::RegisterFieldUseCase;
 pub use register_api::RegisterApiUseCase;
+pub use entity_history::UiEntityHistoryUseCase;
+pub use markup::MarkupPolicy;
+pub use resolve_visibility::ResolveUiVisibilityUseCase;
 
@@ -1,3 +1,4 @@
+use super::markup::{check_plain_text, MarkupPolicy};
 use shared::domain::entities::UiButton;
 use shared::domain::repositories::UiEntityRepository;
 use shared::infrastructure::zanzibar::RelationshipStore;
@@ -9,6 +10,7 @@ pub struct RegisterButtonUseCase {
     ui_entity_repository: Box<dyn UiEntityRepository>,
     #[allow(dead_code)]
     relationship_store: Arc<RelationshipStore>,
+    markup_policy: MarkupPolicy,
 }
 
 impl RegisterButtonUseCase {
@@ -19,9 +21,16 @@ impl RegisterButtonUseCase {
         Self {
             ui_entity_repository,
             relationship_store,
+            markup_policy: MarkupPolicy::default(),
         }
     }
 
+    /// Formatting tags labels may use; none by default
+    pub fn with_markup_policy(mut self, markup_policy: MarkupPolicy) -> Self {
+        self.markup_policy = markup_policy;
+        self
+    }
+
     pub async fn execute(
         &self,
         page_id: Uuid,
@@ -29,18 +38,7 @@ impl RegisterButtonUseCase {
         label: &str,
         action: Option<String>,
     ) -> AppResult<UiButton> {
-        // Validate inputs
-        if button_id.trim().is_empty() {
-            return Err(shared::AppError::Validation(
-                "Button ID cannot be empty".to_string(),
-            ));
-        }
-
-        if label.trim().is_empty() {
-            return Err(shared::AppError::Validation(
-                "Button label cannot be empty".to_string(),
-            ));
-        }
+        Self::validate(&self.markup_policy, button_id, label, action.as_deref())?;
 
         // Verify page exists
         let _page = self.ui_entity_repository
@@ -74,5 +72,44 @@ impl RegisterButtonUseCase {
 
         Ok(created_button)
     }
+
+    /// Reject empty definitions, and markup the policy does not allow
+    ///
+    /// Labels are checked against the policy; the button ID and action
+    /// never contain markup.
+    pub fn validate(markup_policy: &MarkupPolicy, button_id: &str, label: &str, action: Option<&str>) -> AppResult<()> {
+        if button_id.trim().is_empty() {
+            return Err(shared::AppError::Validation(
+                "Button ID cannot be empty".to_string(),
+            ));
+        }
+
+        if label.trim().is_empty() {
+            return Err(shared::AppError::Validation(
+                "Button label cannot be empty".to_string(),
+            ));
+        }
+
+        check_plain_text("Button ID", button_id)?;
+        markup_policy.check("Button label", label)?;
+        match action {
+            Some(action) => check_plain_text("Button action", action),
+            None => Ok(()),
+        }
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_in_label_is_rejected() {
+        let policy = MarkupPolicy::default();
+        let err = RegisterButtonUseCase::validate(&policy, "save", "Save<script src=//evil.example/x.js></script>", None)
+            .unwrap_err();
+        assert!(err.to_string().contains("disallowed markup"));
+        assert!(RegisterButtonUseCase::validate(&policy, "save", "Save", Some("create")).is_ok());
+        assert!(RegisterButtonUseCase::validate(&policy, "save", "Save", Some("<img src=x onerror=alert(1)>")).is_err());
+    }
+}
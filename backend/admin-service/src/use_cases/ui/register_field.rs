@@ -1,3 +1,4 @@
+use super::markup::{check_plain_text, MarkupPolicy};
 use shared::domain::entities::UiField;
 use shared::domain::repositories::UiEntityRepository;
 use shared::infrastructure::zanzibar::RelationshipStore;
@@ -9,6 +10,7 @@ pub struct RegisterFieldUseCase {
     ui_entity_repository: Box<dyn UiEntityRepository>,
     #[allow(dead_code)]
     relationship_store: Arc<RelationshipStore>,
+    markup_policy: MarkupPolicy,
 }
 
 impl RegisterFieldUseCase {
@@ -19,9 +21,16 @@ impl RegisterFieldUseCase {
         Self {
             ui_entity_repository,
             relationship_store,
+            markup_policy: MarkupPolicy::default(),
         }
     }
 
+    /// Formatting tags labels may use; none by default
+    pub fn with_markup_policy(mut self, markup_policy: MarkupPolicy) -> Self {
+        self.markup_policy = markup_policy;
+        self
+    }
+
     pub async fn execute(
         &self,
         page_id: Uuid,
@@ -29,24 +38,7 @@ impl RegisterFieldUseCase {
         label: &str,
         field_type: &str,
     ) -> AppResult<UiField> {
-        // Validate inputs
-        if field_id.trim().is_empty() {
-            return Err(shared::AppError::Validation(
-                "Field ID cannot be empty".to_string(),
-            ));
-        }
-
-        if label.trim().is_empty() {
-            return Err(shared::AppError::Validation(
-                "Field label cannot be empty".to_string(),
-            ));
-        }
-
-        if field_type.trim().is_empty() {
-            return Err(shared::AppError::Validation(
-                "Field type cannot be empty".to_string(),
-            ));
-        }
+        Self::validate(&self.markup_policy, field_id, label, field_type)?;
 
         // Verify page exists
         let _page = self.ui_entity_repository
@@ -80,5 +72,47 @@ impl RegisterFieldUseCase {
 
         Ok(created_field)
     }
+
+    /// Reject empty definitions, and markup the policy does not allow
+    ///
+    /// Labels are checked against the policy; identifiers and the field type
+    /// never contain markup.
+    pub fn validate(markup_policy: &MarkupPolicy, field_id: &str, label: &str, field_type: &str) -> AppResult<()> {
+        if field_id.trim().is_empty() {
+            return Err(shared::AppError::Validation(
+                "Field ID cannot be empty".to_string(),
+            ));
+        }
+
+        if label.trim().is_empty() {
+            return Err(shared::AppError::Validation(
+                "Field label cannot be empty".to_string(),
+            ));
+        }
+
+        if field_type.trim().is_empty() {
+            return Err(shared::AppError::Validation(
+                "Field type cannot be empty".to_string(),
+            ));
+        }
+
+        check_plain_text("Field ID", field_id)?;
+        markup_policy.check("Field label", label)?;
+        check_plain_text("Field type", field_type)
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_in_label_is_rejected() {
+        let policy = MarkupPolicy::new(["b"]);
+        let err = RegisterFieldUseCase::validate(&policy, "email", "<script>alert(document.cookie)</script>", "text")
+            .unwrap_err();
+        assert!(matches!(err, shared::AppError::Validation(_)));
+        assert!(RegisterFieldUseCase::validate(&policy, "email", "<b>Email</b>", "text").is_ok());
+        assert!(RegisterFieldUseCase::validate(&policy, "email", "Email", "text\"><svg onload=x>").is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use shared::domain::repositories::UiEntityRepository;
+use shared::infrastructure::zanzibar::{PermissionChecker, UiPermissions, UiVisibilityCache, VisibleUi};
+use shared::AppResult;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Every page, button and field a user may see, resolved in one pass
+///
+/// Replaces a per-element permission check from the frontend with one
+/// call. Results are cached per user against a stamp of the latest
+/// relationship and UI entity change, so grants, revocations and UI edits
+/// show up on the next call.
+pub struct ResolveUiVisibilityUseCase {
+    ui_entity_repository: Box<dyn UiEntityRepository>,
+    permission_checker: Arc<PermissionChecker>,
+    cache: Arc<UiVisibilityCache>,
+}
+
+impl ResolveUiVisibilityUseCase {
+    pub fn new(
+        ui_entity_repository: Box<dyn UiEntityRepository>,
+        permission_checker: Arc<PermissionChecker>,
+        cache: Arc<UiVisibilityCache>,
+    ) -> Self {
+        Self {
+            ui_entity_repository,
+            permission_checker,
+            cache,
+        }
+    }
+
+    pub async fn execute(&self, user_id: Uuid) -> AppResult<Arc<VisibleUi>> {
+        // Read before resolving: a change landing mid-resolve then leaves
+        // the entry under an already stale stamp rather than a current one
+        let stamp = self.ui_entity_repository.visibility_stamp().await?;
+        if let Some(cached) = self.cache.get(user_id, &stamp) {
+            return Ok(cached);
+        }
+
+        let granted = self
+            .permission_checker
+            .get_all_permissions(&format!("user:{}", user_id))
+            .await?;
+        let permissions = UiPermissions(&granted);
+
+        let mut pages = Vec::new();
+        for page in self.ui_entity_repository.list_pages().await? {
+            if !permissions.page_visible(&page) {
+                continue;
+            }
+            let buttons = self.ui_entity_repository.list_buttons_for_page(page.id).await?;
+            let fields = self.ui_entity_repository.list_fields_for_page(page.id).await?;
+            pages.push(permissions.page(&page, &buttons, &fields));
+        }
+
+        let ui = Arc::new(VisibleUi { user_id, pages });
+        self.cache.insert(user_id, stamp, Arc::clone(&ui));
+        Ok(ui)
+    }
+}
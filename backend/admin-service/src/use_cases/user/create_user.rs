@@ -1,3 +1,23 @@
+//! Creating users, by hand or from a provisioning source
+//!
+//! A request carrying an `external_id` is idempotent, since SCIM and LDAP
+//! sync re-send users they have already provisioned:
+//!
+//! 1. A user with that `external_id` is updated to the request's email and
+//!    username.
+//! 2. Failing that, a user with the request's email and no `external_id` -
+//!    typically one created by hand before provisioning was set up - is
+//!    linked: it takes the `external_id` and the username.
+//! 3. Failing both, a user is created.
+//!
+//! Updating or linking never changes the password. Either way, an email or
+//! username held by any other user is rejected, as is a match on a service
+//! account. Requests without an `external_id` always create a user and
+//! reject an email or username that is already in use.
+//!
+//! Service accounts, the only users API keys are issued to, are created by
+//! hand with `is_service_account`; provisioning sources never create them.
+
 use crate::dto::{CreateUserRequest, UserResponse};
 use shared::domain::entities::{User, UserProvisioningChecklist};
 use shared::domain::repositories::UserRepository;
@@ -8,6 +28,52 @@ use bcrypt::{hash, DEFAULT_COST};
 use uuid::Uuid;
 use std::sync::Arc;
 
+/// What [`CreateUserUseCase::execute`] did with a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreateUserOutcome {
+    Created,
+    /// The user with the request's `external_id` was brought up to date
+    Updated,
+    /// An existing user with the request's email took its `external_id`
+    Linked,
+}
+
+/// The existing user a request applies to, if any, given the users it matches
+fn resolve(
+    request: &CreateUserRequest,
+    by_external_id: Option<User>,
+    by_email: Option<User>,
+    by_username: Option<User>,
+) -> AppResult<Option<(User, CreateUserOutcome)>> {
+    if request.is_service_account && request.external_id.is_some() {
+        return Err(shared::AppError::Validation("Service accounts cannot be provisioned with an external ID".to_string()));
+    }
+    let target = match (&request.external_id, by_external_id) {
+        (Some(_), Some(user)) => Some((user, CreateUserOutcome::Updated)),
+        (Some(_), None) => by_email
+            .clone()
+            .filter(|user| user.external_id.is_none())
+            .map(|user| (user, CreateUserOutcome::Linked)),
+        (None, _) => None,
+    };
+    if target.as_ref().is_some_and(|(user, _)| user.is_service_account) {
+        return Err(shared::AppError::Validation("User is a service account and cannot be provisioned".to_string()));
+    }
+
+    let target_id = target.as_ref().map(|(user, _)| user.id);
+    if let Some(other) = by_email.filter(|user| Some(user.id) != target_id) {
+        let message = match other.external_id {
+            Some(_) => "User with this email is provisioned under another external ID",
+            None => "User with this email already exists",
+        };
+        return Err(shared::AppError::Validation(message.to_string()));
+    }
+    if by_username.is_some_and(|user| Some(user.id) != target_id) {
+        return Err(shared::AppError::Validation("User with this username already exists".to_string()));
+    }
+    Ok(target)
+}
+
 pub struct CreateUserUseCase {
     user_repository: Box<dyn UserRepository>,
     dek_manager: Arc<DekManager>,
@@ -28,25 +94,57 @@ impl CreateUserUseCase {
         }
     }
 
-    pub async fn execute(&self, request: CreateUserRequest) -> AppResult<UserResponse> {
-        // Initialize provisioning checklist
-        let mut checklist = UserProvisioningChecklist::new(Uuid::new_v4()); // Will be updated with actual user_id
-        
-        // Check if user already exists
-        if self.user_repository.find_by_email(&request.email).await?.is_some() {
-            return Err(shared::AppError::Validation("User with this email already exists".to_string()));
+    pub async fn execute(&self, request: CreateUserRequest) -> AppResult<(UserResponse, CreateUserOutcome)> {
+        match self.create_or_update(&request).await {
+            // A concurrent create inserted first; resolving again finds its
+            // user, so a re-sent provision updates instead of failing
+            Err(shared::AppError::Database(sqlx::Error::Database(e)))
+                if e.is_unique_violation() && request.external_id.is_some() =>
+            {
+                self.create_or_update(&request).await
+            }
+            result => result,
         }
+    }
 
-        if self.user_repository.find_by_username(&request.username).await?.is_some() {
-            return Err(shared::AppError::Validation("User with this username already exists".to_string()));
+    async fn create_or_update(&self, request: &CreateUserRequest) -> AppResult<(UserResponse, CreateUserOutcome)> {
+        let by_external_id = match &request.external_id {
+            Some(external_id) => self.user_repository.find_by_external_id(external_id).await?,
+            None => None,
+        };
+        let by_email = self.user_repository.find_by_email(&request.email).await?;
+        let by_username = self.user_repository.find_by_username(&request.username).await?;
+
+        match resolve(request, by_external_id, by_email, by_username)? {
+            Some((user, outcome)) => Ok((UserResponse::from(self.apply(user, request).await?), outcome)),
+            None => Ok((self.create(request).await?, CreateUserOutcome::Created)),
         }
+    }
+
+    /// Bring a matched user up to date; unchanged users are left as they are
+    async fn apply(&self, mut user: User, request: &CreateUserRequest) -> AppResult<User> {
+        if user.email == request.email && user.username == request.username && user.external_id == request.external_id {
+            return Ok(user);
+        }
+        user.email = request.email.clone();
+        user.username = request.username.clone();
+        user.external_id = request.external_id.clone();
+        user.updated_at = chrono::Utc::now();
+        self.user_repository.update(user).await
+    }
+
+    async fn create(&self, request: &CreateUserRequest) -> AppResult<UserResponse> {
+        // Initialize provisioning checklist
+        let mut checklist = UserProvisioningChecklist::new(Uuid::new_v4()); // Will be updated with actual user_id
 
         // Hash password
         let password_hash = hash(&request.password, DEFAULT_COST)
             .map_err(|e| shared::AppError::Internal(format!("Password hashing failed: {}", e)))?;
 
         // Create user
-        let user = User::new(request.email, request.username, password_hash);
+        let mut user = User::new(request.email.clone(), request.username.clone(), password_hash);
+        user.external_id = request.external_id.clone();
+        user.is_service_account = request.is_service_account;
         checklist.user_id = user.id;
         checklist.mark_item_in_progress("create_user");
         
@@ -84,3 +182,77 @@ impl CreateUserUseCase {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(external_id: Option<&str>) -> CreateUserRequest {
+        CreateUserRequest {
+            email: "jane@example.com".to_string(),
+            username: "jdoe".to_string(),
+            password: "password123".to_string(),
+            external_id: external_id.map(str::to_string),
+            is_service_account: false,
+        }
+    }
+
+    fn user(email: &str, username: &str, external_id: Option<&str>) -> User {
+        let mut user = User::new(email.to_string(), username.to_string(), "hash".to_string());
+        user.external_id = external_id.map(str::to_string);
+        user
+    }
+
+    #[test]
+    fn test_new_users_are_created() {
+        assert!(resolve(&request(None), None, None, None).unwrap().is_none());
+        assert!(resolve(&request(Some("00u1")), None, None, None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_reprovisioning_updates_the_same_user() {
+        let existing = user("old@example.com", "old", Some("00u1"));
+        let (matched, outcome) = resolve(&request(Some("00u1")), Some(existing.clone()), None, None).unwrap().unwrap();
+        assert_eq!((matched.id, outcome), (existing.id, CreateUserOutcome::Updated));
+
+        // Its own email and username are not conflicts
+        let same = Some(existing.clone());
+        let (matched, _) = resolve(&request(Some("00u1")), same.clone(), same.clone(), same).unwrap().unwrap();
+        assert_eq!(matched.id, existing.id);
+    }
+
+    #[test]
+    fn test_manual_user_is_linked_by_email() {
+        let manual = user("jane@example.com", "jane", None);
+        let (matched, outcome) = resolve(&request(Some("00u1")), None, Some(manual.clone()), None).unwrap().unwrap();
+        assert_eq!((matched.id, outcome), (manual.id, CreateUserOutcome::Linked));
+    }
+
+    #[test]
+    fn test_conflicts_are_rejected() {
+        let linked_elsewhere = user("jane@example.com", "jane", Some("00u2"));
+        let err = resolve(&request(Some("00u1")), None, Some(linked_elsewhere), None).unwrap_err();
+        assert!(err.to_string().contains("another external ID"));
+
+        let existing = user("old@example.com", "old", Some("00u1"));
+        let other = user("jane@example.com", "jane", None);
+        assert!(resolve(&request(Some("00u1")), Some(existing.clone()), Some(other.clone()), None).is_err());
+        assert!(resolve(&request(Some("00u1")), Some(existing), None, Some(other.clone())).is_err());
+        assert!(resolve(&request(None), None, Some(other), None).is_err());
+
+        let mut service_account = user("jane@example.com", "jane", None);
+        service_account.is_service_account = true;
+        assert!(resolve(&request(Some("00u1")), None, Some(service_account), None).is_err());
+    }
+
+    #[test]
+    fn test_service_accounts_are_created_by_hand_only() {
+        let mut manual = request(None);
+        manual.is_service_account = true;
+        assert!(resolve(&manual, None, None, None).unwrap().is_none());
+
+        let mut provisioned = request(Some("00u1"));
+        provisioned.is_service_account = true;
+        assert!(resolve(&provisioned, None, None, None).is_err());
+    }
+}
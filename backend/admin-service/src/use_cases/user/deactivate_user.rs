@@ -0,0 +1,61 @@
+use shared::domain::events::{DomainEvent, EventEnvelope};
+use shared::infrastructure::events::{OutboxRelay, OutboxStore};
+use shared::AppResult;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Deactivates a user and emits `UserDeactivated`
+///
+/// The user update and the outbox entry are written in one transaction, so
+/// consumers (e.g. session revocation) are notified if and only if the
+/// deactivation commits.
+pub struct DeactivateUserUseCase {
+    pool: PgPool,
+    relay: Option<Arc<OutboxRelay>>,
+}
+
+impl DeactivateUserUseCase {
+    pub fn new(pool: PgPool, relay: Option<Arc<OutboxRelay>>) -> Self {
+        Self { pool, relay }
+    }
+
+    /// Returns `false` if the user was already inactive (no event is emitted)
+    pub async fn execute(&self, user_id: Uuid, deactivated_by: Option<Uuid>) -> AppResult<bool> {
+        let mut tx = self.pool.begin().await.map_err(shared::AppError::Database)?;
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE users
+            SET is_active = false, updated_at = NOW(), updated_by = $2, version = version + 1
+            WHERE id = $1 AND is_active = true
+            "#,
+        )
+        .bind(user_id)
+        .bind(deactivated_by)
+        .execute(&mut *tx)
+        .await
+        .map_err(shared::AppError::Database)?;
+
+        if updated.rows_affected() == 0 {
+            let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+                .bind(user_id)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(shared::AppError::Database)?;
+            if !exists {
+                return Err(shared::AppError::NotFound("User not found".to_string()));
+            }
+            return Ok(false);
+        }
+
+        let event = EventEnvelope::new(DomainEvent::UserDeactivated { user_id, deactivated_by });
+        OutboxStore::append(&mut *tx, &event).await?;
+        tx.commit().await.map_err(shared::AppError::Database)?;
+
+        if let Some(relay) = &self.relay {
+            relay.notify();
+        }
+        Ok(true)
+    }
+}
@@ -1,10 +1,12 @@
 pub mod create_user;
 pub mod update_user;
 pub mod delete_user;
+pub mod deactivate_user;
 pub mod assign_role;
 
-pub use create_user::CreateUserUseCase;
+pub use create_user::{CreateUserOutcome, CreateUserUseCase};
 pub use update_user::UpdateUserUseCase;
 pub use delete_user::DeleteUserUseCase;
+pub use deactivate_user::DeactivateUserUseCase;
 pub use assign_role::AssignRoleUseCase;
 
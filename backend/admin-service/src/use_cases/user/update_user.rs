@@ -19,6 +19,9 @@ impl UpdateUserUseCase {
             .await?
             .ok_or_else(|| shared::AppError::NotFound("User not found".to_string()))?;
 
+        // Fail fast on a stale edit; the repository re-checks atomically
+        shared::AppError::check_version("User", user_id, request.version, user.version)?;
+
         // Update fields if provided
         if let Some(email) = request.email {
             // Check if email is already taken
@@ -44,6 +44,7 @@ fn test_service_status_response_serialization() {
         ],
         overall_status: "operational".to_string(),
         checked_at: "2024-01-01T00:00:00Z".to_string(),
+        concurrency: None,
     };
 
     let json = serde_json::to_string(&response).unwrap();
@@ -10,6 +10,8 @@ fn test_create_user_request_serialization() {
         email: "test@example.com".to_string(),
         username: "testuser".to_string(),
         password: "password123".to_string(),
+        external_id: None,
+        is_service_account: false,
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -26,6 +28,8 @@ fn test_create_user_request_deserialization() {
     assert_eq!(request.email, "test@example.com");
     assert_eq!(request.username, "testuser");
     assert_eq!(request.password, "password123");
+    assert_eq!(request.external_id, None);
+    assert!(!request.is_service_account);
 }
 
 #[test]
@@ -34,6 +38,7 @@ fn test_update_user_request_serialization() {
         email: Some("newemail@example.com".to_string()),
         username: None,
         password: Some("newpassword".to_string()),
+        version: 3,
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -43,12 +48,19 @@ fn test_update_user_request_serialization() {
 
 #[test]
 fn test_update_user_request_deserialization() {
-    let json = r#"{"email":"newemail@example.com","username":null,"password":"newpassword"}"#;
+    let json = r#"{"email":"newemail@example.com","username":null,"password":"newpassword","version":3}"#;
     let request: UpdateUserRequest = serde_json::from_str(json).unwrap();
     
     assert_eq!(request.email, Some("newemail@example.com".to_string()));
     assert_eq!(request.username, None);
     assert_eq!(request.password, Some("newpassword".to_string()));
+    assert_eq!(request.version, 3);
+}
+
+#[test]
+fn test_update_user_request_requires_version() {
+    let json = r#"{"email":"newemail@example.com"}"#;
+    assert!(serde_json::from_str::<UpdateUserRequest>(json).is_err());
 }
 
 #[test]
@@ -66,7 +78,9 @@ fn test_user_response_from_user() {
     assert_eq!(response.username, user.username);
     assert_eq!(response.is_active, user.is_active);
     assert_eq!(response.is_verified, user.is_verified);
+    assert_eq!(response.external_id, user.external_id);
     assert_eq!(response.created_at, user.created_at);
+    assert_eq!(response.version, user.version);
 }
 
 #[test]
@@ -77,7 +91,10 @@ fn test_user_response_serialization() {
         username: "testuser".to_string(),
         is_active: true,
         is_verified: false,
+        external_id: None,
+        is_service_account: false,
         created_at: Utc::now(),
+        version: 1,
     };
 
     let json = serde_json::to_string(&response).unwrap();
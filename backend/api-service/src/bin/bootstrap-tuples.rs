@@ -0,0 +1,101 @@
+//! Load relationship tuples from a JSON or CSV bootstrap file
+//!
+//! Usage: bootstrap-tuples <file> [--batch-size N]
+//!
+//! Uses the database and authorization schema configured for the API
+//! service. Safe to re-run: tuples that already exist are skipped.
+
+use std::process;
+use std::sync::Arc;
+use dotenv::dotenv;
+use shared::config::Settings;
+use shared::infrastructure::database::create_pool;
+use shared::infrastructure::repositories::RelationshipRepositoryImpl;
+use shared::infrastructure::zanzibar::bootstrap::{self, TupleBootstrap, DEFAULT_BATCH_SIZE};
+use shared::infrastructure::zanzibar::{AuthorizationSchema, RelationshipStore};
+
+fn usage() -> ! {
+    eprintln!("Usage: bootstrap-tuples <file.json|file.csv> [--batch-size N]");
+    process::exit(2);
+}
+
+#[tokio::main]
+async fn main() {
+    dotenv().ok();
+    tracing_subscriber::fmt::init();
+
+    let mut args = std::env::args().skip(1);
+    let mut path = None;
+    let mut batch_size = DEFAULT_BATCH_SIZE;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--batch-size" => {
+                batch_size = args.next().and_then(|n| n.parse().ok()).unwrap_or_else(|| usage());
+            }
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => usage(),
+        }
+    }
+    let Some(path) = path else { usage() };
+
+    let settings = match Settings::from_env() {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to load configuration: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let schema = match &settings.authorization.schema_path {
+        Some(schema_path) => match AuthorizationSchema::from_file(schema_path) {
+            Ok(schema) => Some(Arc::new(schema)),
+            Err(e) => {
+                eprintln!("Failed to load authorization schema: {}", e);
+                process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let (rows, mut errors) = match bootstrap::read_file(&path) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+
+    let pool = match create_pool(&settings.database.url).await {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            process::exit(1);
+        }
+    };
+    let store = RelationshipStore::new(Box::new(RelationshipRepositoryImpl::new(pool))).with_schema(schema);
+
+    let mut report = match TupleBootstrap::new(&store).with_batch_size(batch_size).load(rows).await {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Bootstrap failed: {}", e);
+            process::exit(1);
+        }
+    };
+    report.rows += errors.len();
+    errors.append(&mut report.errors);
+    errors.sort_by_key(|error| error.row);
+
+    println!(
+        "{} row(s): {} written, {} already present, {} rejected",
+        report.rows,
+        report.written,
+        report.skipped,
+        errors.len()
+    );
+    for error in &errors {
+        println!("  row {}: {}", error.row, error.message);
+    }
+    if !errors.is_empty() {
+        process::exit(1);
+    }
+}
@@ -598,6 +598,7 @@ async fn main() {
                     "created_by": "setup-admin",
                     "description": "Grants all permissions to super admin"
                 })),
+                None, // Valid immediately
                 None, // No expiration
             )
             .await
@@ -13,6 +13,12 @@ async fn async_main() -> Result<(), String> {
     // Load environment variables
     dotenv::dotenv().ok();
 
+    // Check every setting before anything binds, reporting all problems at once
+    if let Err(errors) = shared::config::validate_env() {
+        eprintln!("{}", errors);
+        std::process::exit(1);
+    }
+
     // Load configuration
     let settings = shared::config::Settings::from_env()
         .map_err(|e| {
@@ -20,13 +26,19 @@ async fn async_main() -> Result<(), String> {
             format!("Failed to load configuration: {}", e)
         })?;
 
+    // Runtime settings are reloadable; everything else in `settings` is fixed
+    let settings_handle = Arc::new(shared::config::SettingsHandle::new(settings.clone()));
+
     // Initialize logger with settings and deployment config (single point of control for dev mode)
     shared::infrastructure::logging::init_from_settings_with_deployment(
-        &settings.logging,
+        &settings.runtime.logging,
         &settings.deployment,
     );
 
     info!("Starting api-service on {}:{}", settings.server.host, settings.server.port);
+    for name in shared::config::FeatureFlags::from_runtime(&settings.runtime).unknown_names() {
+        tracing::warn!("FEATURE_FLAGS sets unknown feature '{}'; no code consults it", name);
+    }
     info!("Tokio runtime configured: worker_threads={}, max_blocking_threads=2", 
         std::env::var("TOKIO_WORKER_THREADS").unwrap_or_else(|_| "2".to_string()));
 
@@ -87,10 +99,35 @@ async fn async_main() -> Result<(), String> {
     );
     let token_manager_arc = Arc::new(token_manager.clone());
 
-    // Initialize Zanzibar services (needed for RoleRepository)
+    // Optional authorization schema: declared relations and their implications
+    let authz_schema = match &settings.authorization.schema_path {
+        Some(path) => {
+            let schema = shared::infrastructure::zanzibar::AuthorizationSchema::from_file(path)
+                .map_err(|e| format!("Failed to load authorization schema: {}", e))?;
+            info!("Authorization schema loaded from {} ({} object types)", path, schema.types.len());
+            Some(Arc::new(schema))
+        }
+        None => None,
+    };
+
+    // Initialize graph cache for complex authorization queries
+    info!("Initializing graph cache...");
+    use shared::infrastructure::zanzibar::GraphCache;
+    let graph_cache = if settings.graph_cache.enabled {
+        Arc::new(GraphCache::new(settings.graph_cache.ttl_seconds, true))
+    } else {
+        info!("Graph cache disabled");
+        Arc::new(GraphCache::disabled())
+    };
+    info!("Graph cache initialized: enabled={}, ttl={}s", 
+        settings.graph_cache.enabled, 
+        settings.graph_cache.ttl_seconds);
+
+    // Initialize Zanzibar services (needed for RoleRepository); writes
+    // through this store are applied to the cached graph in place
     let relationship_store = Arc::new(shared::infrastructure::zanzibar::RelationshipStore::new(
         Box::new(shared::infrastructure::repositories::RelationshipRepositoryImpl::new(pool.clone())),
-    ));
+    ).with_schema(authz_schema.clone()).with_graph_cache(graph_cache.clone()));
     
     let permission_repository = Arc::new(shared::infrastructure::repositories::PermissionRepositoryImpl::new(pool.clone()));
     
@@ -105,18 +142,6 @@ async fn async_main() -> Result<(), String> {
         Box::new(shared::infrastructure::repositories::PermissionRepositoryImpl::new(pool.clone())),
     );
 
-    let login_use_case = Arc::new(authz_core::auth::LoginUseCase::new(
-        Box::new(shared::infrastructure::repositories::UserRepositoryImpl::new(database_service.clone())),
-        Box::new(shared::infrastructure::repositories::RefreshTokenRepositoryImpl::new(pool.clone())),
-        Box::new(shared::infrastructure::repositories::RoleRepositoryImpl::new(
-            database_service.clone(),
-            relationship_store.clone(),
-            permission_repository.clone(),
-        )),
-        Box::new(shared::infrastructure::repositories::PermissionRepositoryImpl::new(pool.clone())),
-        token_manager.clone(),
-    ));
-
     let refresh_token_use_case = Arc::new(authz_core::auth::RefreshTokenUseCase::new(
         Box::new(shared::infrastructure::repositories::UserRepositoryImpl::new(database_service.clone())),
         Box::new(shared::infrastructure::repositories::RefreshTokenRepositoryImpl::new(pool.clone())),
@@ -132,25 +157,12 @@ async fn async_main() -> Result<(), String> {
         get_permissions_use_case,
     ));
 
-    // Initialize graph cache for complex authorization queries
-    info!("Initializing graph cache...");
-    use shared::infrastructure::zanzibar::GraphCache;
-    let graph_cache = if settings.graph_cache.enabled {
-        Arc::new(GraphCache::new(settings.graph_cache.ttl_seconds, true))
-    } else {
-        info!("Graph cache disabled");
-        Arc::new(GraphCache::disabled())
-    };
-    info!("Graph cache initialized: enabled={}, ttl={}s", 
-        settings.graph_cache.enabled, 
-        settings.graph_cache.ttl_seconds);
-
     // Permission checker (uses relationship_store with optional graph cache)
     let permission_checker = Arc::new(
         shared::infrastructure::zanzibar::PermissionChecker::with_graph_cache(
             shared::infrastructure::zanzibar::RelationshipStore::new(
                 Box::new(shared::infrastructure::repositories::RelationshipRepositoryImpl::new(pool.clone())),
-            ),
+            ).with_schema(authz_schema.clone()),
             graph_cache.clone(),
             true, // Enable graph for deep queries
         )
@@ -177,8 +189,20 @@ async fn async_main() -> Result<(), String> {
         .map_err(|e| format!("Failed to load provider config: {}", e))?;
     let vault = create_kms_provider(&provider_config.kms)
         .map_err(|e| format!("Failed to create KMS provider: {}", e))?;
+    // Fail fast while the vault is down instead of timing out every request
+    use shared::infrastructure::encryption::{CircuitBreaker, CircuitBreakerConfig, CircuitBreakerVault};
+    let vault_breaker = Arc::new(CircuitBreaker::new(
+        "vault",
+        CircuitBreakerConfig::from(&settings.encryption),
+    ));
+    let vault: Box<dyn shared::infrastructure::encryption::Vault> =
+        Box::new(CircuitBreakerVault::new(vault, vault_breaker.clone()));
     info!("Vault initialized");
 
+    let counters = shared::infrastructure::providers::create_counter_store(&provider_config.counters)
+        .await
+        .map_err(|e| format!("Failed to create counter store: {}", e))?;
+
     // Initialize master key
     info!("Initializing master key...");
     use shared::infrastructure::encryption::MasterKey;
@@ -265,8 +289,14 @@ async fn async_main() -> Result<(), String> {
     info!("Master key initialized");
 
     // Create DEK Manager
-    use shared::infrastructure::encryption::DekManager;
-    let dek_manager = Arc::new(DekManager::new(master_key, vault));
+    // Concurrent reads of the same DEK share one vault round-trip
+    use shared::infrastructure::encryption::{CoalescingVault, DekManager};
+    let dek_manager = Arc::new(
+        DekManager::new(master_key, Box::new(CoalescingVault::new(vault))).with_cache(
+            std::time::Duration::from_secs(settings.encryption.dek_cache_ttl_seconds),
+            settings.encryption.dek_cache_max_entries,
+        ),
+    );
     info!("DEK Manager initialized");
 
     // Create role repository (uses relationship_store and permission_repository)
@@ -290,6 +320,126 @@ async fn async_main() -> Result<(), String> {
     ));
     info!("Session service initialized");
 
+    // Deliver domain events from the outbox; sessions end when a user is deactivated
+    let event_bus = Arc::new(shared::infrastructure::events::EventBus::new());
+    event_bus.subscribe(
+        shared::domain::events::DomainEvent::USER_DEACTIVATED,
+        Arc::new(shared::infrastructure::events::RevokeSessionsOnDeactivate::new(session_service.clone())),
+    );
+    // Opt-in: a login flagged as anomalous ends the user's earlier sessions
+    if std::env::var("LOGIN_ANOMALY_REVOKE_SESSIONS").is_ok_and(|v| v.eq_ignore_ascii_case("true")) {
+        event_bus.subscribe(
+            shared::domain::events::DomainEvent::LOGIN_ANOMALY_DETECTED,
+            Arc::new(shared::infrastructure::events::RevokeSessionsOnLoginAnomaly::new(session_service.clone())),
+        );
+    }
+    match shared::infrastructure::events::WebhookDispatcher::from_config(pool.clone(), &settings.webhook) {
+        Ok(Some(dispatcher)) => {
+            let dispatcher = Arc::new(dispatcher);
+            shared::infrastructure::events::WebhookSink::subscribe(&event_bus, dispatcher.clone(), &settings.webhook);
+            dispatcher.spawn();
+            info!("Webhook sink forwarding {:?}", settings.webhook.event_types);
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Webhook sink disabled: {}", e),
+    }
+    let outbox_relay = Arc::new(shared::infrastructure::events::OutboxRelay::new(
+        Arc::new(shared::infrastructure::events::OutboxStore::new(pool.clone())),
+        event_bus,
+    ));
+    outbox_relay.clone().spawn();
+    info!("Outbox relay started");
+
+    // Every login attempt lands in login_events, located when a lookup URL is set;
+    // successful ones are checked for impossible travel
+    let geo_locator = shared::infrastructure::login_audit::HttpGeoLocator::from_env().map(|locator| {
+        info!("Login geolocation enabled");
+        Arc::new(locator) as Arc<dyn shared::infrastructure::login_audit::GeoLocator>
+    });
+    let mut login_auditor = shared::infrastructure::login_audit::LoginAuditor::new(
+        Arc::new(shared::infrastructure::repositories::LoginEventRepositoryImpl::new(database_service.clone())),
+        geo_locator,
+    )
+    .with_events(pool.clone(), outbox_relay.clone());
+    if let Some(detector) = shared::infrastructure::login_audit::ImpossibleTravelDetector::from_env() {
+        login_auditor = login_auditor.with_detector(Arc::new(detector));
+    }
+    let login_auditor = Arc::new(login_auditor);
+
+    let login_use_case = Arc::new(authz_core::auth::LoginUseCase::new(
+        Box::new(shared::infrastructure::repositories::UserRepositoryImpl::new(database_service.clone())),
+        Box::new(shared::infrastructure::repositories::RefreshTokenRepositoryImpl::new(pool.clone())),
+        Box::new(shared::infrastructure::repositories::RoleRepositoryImpl::new(
+            database_service.clone(),
+            relationship_store.clone(),
+            permission_repository.clone(),
+        )),
+        Box::new(shared::infrastructure::repositories::PermissionRepositoryImpl::new(pool.clone())),
+        token_manager.clone(),
+    ).with_auditor(login_auditor));
+
+    // Rotate keys on schedule; master keys are tracked but need an operator until
+    // vault backends support master key rotation
+    if settings.key_rotation.enabled {
+        let group_keyring = Arc::new(shared::infrastructure::encryption::GroupKeyring::new(
+            dek_manager.clone(),
+            pool.clone(),
+        ));
+        let scheduler = Arc::new(
+            shared::infrastructure::encryption::KeyRotationScheduler::new(pool.clone(), settings.key_rotation.schedule.clone())
+                .with_rotator(Arc::new(shared::infrastructure::encryption::GroupDekRotator::new(group_keyring, pool.clone())))
+                .with_batch_size(settings.key_rotation.batch_size)
+                .with_check_interval(std::time::Duration::from_secs(settings.key_rotation.check_interval_seconds))
+                .with_relay(outbox_relay.clone()),
+        );
+        scheduler.spawn();
+        info!("Key rotation scheduler started for {:?}", settings.key_rotation.schedule);
+    }
+
+    // Legacy patient reads go through the MUMPS gateway, never Postgres
+    let legacy_patients = shared::infrastructure::database::mumps::MumpsConfig::from_env().map(|config| {
+        info!("Legacy MUMPS gateway configured at {}:{}", config.host, config.port);
+        let client = Arc::new(shared::infrastructure::database::mumps::MumpsClient::new(config));
+        Arc::new(shared::infrastructure::database::mumps::MumpsLegacyPatientRepository::new(client))
+            as Arc<dyn shared::domain::repositories::LegacyPatientRepository>
+    });
+
+    // SAML login is optional; a broken IdP config disables it rather than the service
+    let saml = match shared::infrastructure::saml::SamlConfig::from_env() {
+        Some(config) => {
+            let replay = shared::infrastructure::saml::SamlReplayStore::new(pool.clone());
+            match shared::infrastructure::saml::SamlServiceProvider::load(config, replay).await {
+                Ok(provider) => {
+                    info!("SAML service provider configured");
+                    Some(Arc::new(provider))
+                }
+                Err(e) => {
+                    tracing::error!("SAML login disabled: {}", e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    // Without a mail relay, password reset and email change requests fail
+    let token_sender = match shared::infrastructure::token_delivery::TokenDeliveryConfig::from_env() {
+        Some(config) => {
+            let sender = shared::infrastructure::token_delivery::HttpTokenSender::new(config)
+                .map_err(|e| format!("Failed to configure token delivery: {}", e))?;
+            info!("One-time token delivery configured");
+            Some(Arc::new(sender) as Arc<dyn shared::infrastructure::token_delivery::TokenSender>)
+        }
+        None => {
+            tracing::warn!("TOKEN_DELIVERY_URL is not set; password reset and email change links cannot be sent");
+            None
+        }
+    };
+
+    let concurrency_limiter = Arc::new(shared::infrastructure::api::concurrency::ConcurrencyLimiter::new(
+        &settings.server.concurrency,
+    ));
+
     // Create application state
     use api_service::AppState;
     let app_state = AppState {
@@ -308,17 +458,77 @@ async fn async_main() -> Result<(), String> {
         dek_manager,
         role_repository,
         graph_cache: Some(graph_cache),
+        ui_visibility_cache: Arc::new(shared::infrastructure::zanzibar::UiVisibilityCache::from_env()),
         session_service,
+        outbox_relay: Some(outbox_relay),
+        settings: settings_handle.clone(),
+        legacy_patients,
+        saml,
+        vault_breaker,
+        concurrency: concurrency_limiter.clone(),
+        counters,
+        token_sender,
     };
 
     // Build application router with state, middleware, and CORS
     let app_state_arc = Arc::new(app_state);
+
+    // SIGHUP reloads the runtime settings (log filter, rate limits, feature flags)
+    #[cfg(unix)]
+    {
+        let settings_handle = settings_handle.clone();
+        tokio::spawn(async move {
+            use tokio::signal::unix::{signal, SignalKind};
+            let mut hangups = match signal(SignalKind::hangup()) {
+                Ok(hangups) => hangups,
+                Err(e) => {
+                    tracing::warn!("SIGHUP handler not installed, configuration reload is API-only: {}", e);
+                    return;
+                }
+            };
+            while hangups.recv().await.is_some() {
+                match settings_handle.reload() {
+                    Ok(report) => info!("Configuration reloaded on SIGHUP, changed: {:?}", report.changed),
+                    Err(e) => tracing::error!("Configuration reload on SIGHUP rejected: {}", e),
+                }
+            }
+        });
+    }
+
+    // Internal gRPC permission API, sharing the HTTP server's state
+    if let Some(grpc_port) = settings.server.grpc_port {
+        let grpc_ip: std::net::IpAddr = settings.server.grpc_host.parse()
+            .map_err(|e| format!("Invalid GRPC_HOST {}: {}", settings.server.grpc_host, e))?;
+        if !grpc_ip.is_loopback() && settings.server.grpc_auth_token.is_none() {
+            return Err(format!("GRPC_AUTH_TOKEN is required to serve gRPC on {}", grpc_ip));
+        }
+        let grpc_addr = SocketAddr::new(grpc_ip, grpc_port);
+        let grpc_auth = admin_service::grpc::BearerAuth::new(settings.server.grpc_auth_token.clone());
+        let grpc_service = admin_service::grpc::PermissionGrpcService::new(app_state_arc.clone());
+        tokio::spawn(async move {
+            if let Err(e) = tonic::transport::Server::builder()
+                .add_service(grpc_service.into_server(grpc_auth))
+                .serve(grpc_addr)
+                .await
+            {
+                tracing::error!("gRPC server error: {}", e);
+            }
+        });
+        info!("gRPC permission service listening on {}", grpc_addr);
+    }
     
     // Create public routes (no auth required)
     // All routes use /v1/ prefix for versioning (except /health)
     let public_routes = axum::Router::new()
         .route("/health", axum::routing::get(|| async { "OK" })) // Health check stays unversioned
         .route("/v1/auth/login", axum::routing::post(crate::presentation::api::handlers::login))
+        .route("/v1/auth/password-reset", axum::routing::post(crate::presentation::api::handlers::request_password_reset))
+        .route("/v1/auth/password-reset/confirm", axum::routing::post(crate::presentation::api::handlers::confirm_password_reset))
+        .route("/v1/auth/email-change/confirm", axum::routing::post(crate::presentation::api::handlers::confirm_email_change))
+        // SAML 2.0 SP: metadata, SP-initiated login and the assertion consumer
+        .route("/v1/auth/saml/metadata", axum::routing::get(crate::presentation::api::handlers::saml_metadata))
+        .route("/v1/auth/saml/login", axum::routing::get(crate::presentation::api::handlers::saml_login))
+        .route("/v1/auth/saml/acs", axum::routing::post(crate::presentation::api::handlers::saml_acs))
         .route("/v1/setup/status", axum::routing::get(admin_service::handlers::check_setup_status))
         .route("/v1/setup/initialize", axum::routing::post(admin_service::handlers::initialize_setup))
         .route("/v1/services/status", axum::routing::get(crate::presentation::api::handlers::get_service_status))
@@ -331,22 +541,38 @@ async fn async_main() -> Result<(), String> {
         .route("/v1/auth/logout", axum::routing::post(crate::presentation::api::handlers::logout))
         .route("/v1/auth/token", axum::routing::post(crate::presentation::api::handlers::refresh_token))
         .route("/v1/auth/userinfo", axum::routing::get(crate::presentation::api::handlers::userinfo))
+        .route("/v1/auth/email-change", axum::routing::post(crate::presentation::api::handlers::request_email_change))
         // User routes
+        .route("/v1/users", axum::routing::get(admin_service::handlers::list_users))
         .route("/v1/users", axum::routing::post(admin_service::handlers::create_user))
         .route("/v1/users/{id}", axum::routing::get(admin_service::handlers::get_user))
         .route("/v1/users/{id}", axum::routing::post(admin_service::handlers::update_user))
         .route("/v1/users/{id}", axum::routing::delete(admin_service::handlers::delete_user))
+        .route("/v1/users/{id}/deactivate", axum::routing::post(admin_service::handlers::deactivate_user))
+        // Listing routes (shared ListQuery / Page envelope)
+        .route("/v1/permissions", axum::routing::get(admin_service::handlers::list_permissions))
+        .route("/v1/permissions/{id}", axum::routing::put(admin_service::handlers::update_permission))
+        .route("/v1/admin/roles", axum::routing::get(admin_service::handlers::list_roles).post(admin_service::handlers::create_role))
+        .route("/v1/admin/roles/{id}", axum::routing::put(admin_service::handlers::update_role).delete(admin_service::handlers::delete_role))
+        .route("/v1/admin/request-logs", axum::routing::get(admin_service::handlers::list_request_logs))
+        .route("/v1/admin/login-events", axum::routing::get(admin_service::handlers::list_login_events))
+        .route("/v1/admin/encryption/rotation-schedule", axum::routing::get(admin_service::handlers::get_key_rotation_schedule))
+        .route("/v1/admin/encryption/relationship-integrity-scan", axum::routing::post(admin_service::handlers::scan_relationship_integrity))
         // Permission check routes
         .route("/v1/admin/permissions/check", axum::routing::post(admin_service::handlers::check_permission))
         .route("/v1/admin/permissions/check-batch", axum::routing::post(admin_service::handlers::check_permissions_batch))
         .route("/v1/admin/permissions/user/{id}", axum::routing::get(admin_service::handlers::get_user_permissions))
         .route("/v1/admin/permissions/user/{id}/pages", axum::routing::get(admin_service::handlers::get_user_pages))
+        .route("/v1/admin/permissions/user/{id}/ui", axum::routing::get(admin_service::handlers::get_user_ui))
         .route("/v1/admin/permissions/user/{id}/buttons/{page}", axum::routing::get(admin_service::handlers::get_user_buttons))
         .route("/v1/admin/permissions/user/{id}/fields/{page}", axum::routing::get(admin_service::handlers::get_user_fields))
         // Permission assignment routes
         .route("/v1/admin/permissions/assign", axum::routing::post(admin_service::handlers::assign_permission))
         .route("/v1/admin/permissions/assign-batch", axum::routing::post(admin_service::handlers::assign_permissions_batch))
         .route("/v1/admin/permissions/revoke", axum::routing::delete(admin_service::handlers::revoke_permission))
+        .route("/v1/admin/permissions/bulk", axum::routing::post(admin_service::handlers::bulk_permissions))
+        .route("/v1/admin/permissions/scheduled", axum::routing::get(admin_service::handlers::list_scheduled_permissions))
+        .route("/v1/admin/permissions/{id}/window", axum::routing::patch(admin_service::handlers::extend_permission))
         // UI Entity routes
         .route("/v1/admin/ui/pages", axum::routing::post(admin_service::handlers::register_page))
         .route("/v1/admin/ui/pages", axum::routing::get(admin_service::handlers::list_pages))
@@ -356,14 +582,49 @@ async fn async_main() -> Result<(), String> {
         .route("/v1/admin/ui/fields", axum::routing::post(admin_service::handlers::register_field))
         .route("/v1/admin/ui/apis", axum::routing::post(admin_service::handlers::register_api))
         .route("/v1/admin/ui/apis", axum::routing::get(admin_service::handlers::list_apis))
+        .route("/v1/admin/ui/{kind}/{id}/history", axum::routing::get(admin_service::handlers::ui_entity_history))
+        .route("/v1/admin/ui/{kind}/{id}/versions/{version}/restore", axum::routing::post(admin_service::handlers::restore_ui_entity_version))
         // Groups routes
         .route("/v1/admin/groups", axum::routing::get(admin_service::handlers::list_groups))
         .route("/v1/admin/groups", axum::routing::post(admin_service::handlers::create_group))
         .route("/v1/admin/groups/{id}", axum::routing::get(admin_service::handlers::get_group))
+        .route("/v1/admin/groups/{id}", axum::routing::put(admin_service::handlers::update_group))
         .route("/v1/admin/groups/{id}", axum::routing::delete(admin_service::handlers::delete_group))
         .route("/v1/admin/groups/{group_id}/users/{user_id}", axum::routing::post(admin_service::handlers::add_user_to_group))
         .route("/v1/admin/groups/{group_id}/users/{user_id}", axum::routing::delete(admin_service::handlers::remove_user_from_group))
         .route("/v1/admin/groups/{group_id}/roles/{role_id}", axum::routing::post(admin_service::handlers::assign_role_to_group))
+        // Service account API keys
+        .route("/v1/service-accounts/api-keys", axum::routing::post(crate::presentation::api::handlers::create_api_key))
+        .route("/v1/service-accounts/api-keys", axum::routing::get(crate::presentation::api::handlers::list_api_keys))
+        .route("/v1/service-accounts/api-keys/{id}", axum::routing::delete(crate::presentation::api::handlers::revoke_api_key))
+        // Configuration
+        .route("/v1/admin/config/reload", axum::routing::post(admin_service::handlers::reload_config))
+        // FHIR R4 read access to the directory (application/fhir+json)
+        .route("/v1/fhir/Practitioner/{id}", axum::routing::get(admin_service::handlers::get_fhir_practitioner))
+        .route("/v1/fhir/Patient/{id}", axum::routing::get(admin_service::handlers::get_fhir_patient))
+        // SCIM 2.0 provisioning for identity providers (application/scim+json)
+        .route("/scim/v2/Users", axum::routing::get(admin_service::handlers::list_scim_users).post(admin_service::handlers::create_scim_user))
+        .route("/scim/v2/Users/{id}", axum::routing::get(admin_service::handlers::get_scim_user)
+            .put(admin_service::handlers::replace_scim_user)
+            .patch(admin_service::handlers::patch_scim_user)
+            .delete(admin_service::handlers::delete_scim_user))
+        .route("/scim/v2/Groups", axum::routing::get(admin_service::handlers::list_scim_groups).post(admin_service::handlers::create_scim_group))
+        .route("/scim/v2/Groups/{id}", axum::routing::get(admin_service::handlers::get_scim_group)
+            .put(admin_service::handlers::replace_scim_group)
+            .patch(admin_service::handlers::patch_scim_group)
+            .delete(admin_service::handlers::delete_scim_group))
+        .route("/v1/admin/authorization/diff", axum::routing::get(admin_service::handlers::diff_authorization))
+        // Graph cache debugging, shipped dark
+        .merge(crate::presentation::api::middleware::gated(
+            app_state_arc.clone(),
+            shared::config::features::GRAPH_DEBUG_ENDPOINTS,
+            axum::Router::new()
+                .route("/v1/admin/graph/stats", axum::routing::get(admin_service::handlers::get_graph_stats))
+                .route("/v1/admin/graph/refresh", axum::routing::post(admin_service::handlers::refresh_graph_cache))
+                .route("/v1/admin/graph/invalidate", axum::routing::post(admin_service::handlers::invalidate_graph_cache))
+                .route("/v1/admin/graph/paths", axum::routing::get(admin_service::handlers::find_permission_paths))
+                .route("/v1/admin/graph/explain", axum::routing::get(admin_service::handlers::explain_permission_check)),
+        ))
         // Dashboard routes
         .route("/v1/admin/dashboard/stats", axum::routing::get(admin_service::handlers::get_dashboard_stats))
         .with_state(app_state_arc.clone())
@@ -381,8 +642,9 @@ async fn async_main() -> Result<(), String> {
         .merge(protected_routes)
         // Middleware order (from outer to inner):
         // 1. Request ID middleware - generates request ID
-        // 2. Session middleware - creates/gets session, extracts IP
-        // 3. Request logging middleware - logs requests (runs before and after handler)
+        // 2. API version middleware - negotiates the version, 406 for unsupported ones
+        // 3. Session middleware - creates/gets session, extracts IP
+        // 4. Request logging middleware - logs requests (runs before and after handler)
         .layer(axum::middleware::from_fn_with_state(
             app_state_arc.clone(),
             crate::presentation::api::middleware::request_logging_middleware,
@@ -391,7 +653,10 @@ async fn async_main() -> Result<(), String> {
             app_state_arc.clone(),
             crate::presentation::api::middleware::session_middleware,
         ))
+        .layer(axum::middleware::from_fn(shared::infrastructure::api::version::version_middleware))
         .layer(axum::middleware::from_fn(crate::presentation::api::middleware::request_id_middleware))
+        // Sheds load before any other work, but inside CORS so browsers can read the 503
+        .layer(shared::infrastructure::api::concurrency::ConcurrencyLimitLayer::new(concurrency_limiter))
         .layer({
             // Build CORS layer with app-specific origins (required for credentials)
             // Combine all allowed origins from both admin-ui and client-ui
@@ -426,16 +691,32 @@ async fn async_main() -> Result<(), String> {
                     axum::http::HeaderName::from_static("x-request-id"),
                     axum::http::HeaderName::from_static("x-request-timestamp"),
                     axum::http::HeaderName::from_static("x-session-token"),
+                    axum::http::HeaderName::from_static("x-csrf-token"),
                     axum::http::HeaderName::from_static("x-app-type"),
                     axum::http::HeaderName::from_static("x-app-device"),
+                    axum::http::HeaderName::from_static(shared::config::features::FEATURE_OVERRIDE_HEADER),
+                    axum::http::header::IF_NONE_MATCH,
+                    shared::infrastructure::api::version::ACCEPT_VERSION,
                 ])
                 .expose_headers([
                     axum::http::HeaderName::from_static("x-request-id"),
+                    axum::http::HeaderName::from_static("x-csrf-token"),
+                    axum::http::header::ETAG,
+                    shared::infrastructure::api::version::API_VERSION,
                 ])
         });
 
     // Start server
     let addr = SocketAddr::from(([0, 0, 0, 0], settings.server.port));
+    if settings.server.tls.enabled {
+        let tls_config = shared::infrastructure::tls::server_config(&settings.server.tls)
+            .map_err(|e| format!("Failed to load TLS certificate: {}", e))?;
+        info!("Server listening on {} (TLS)", addr);
+        shared::infrastructure::tls::serve(addr, app, tls_config, &settings.server.tls).await
+            .map_err(|e| format!("Server error: {}", e))?;
+        return Ok(());
+    }
+
     let listener = tokio::net::TcpListener::bind(addr).await
         .map_err(|e| format!("Failed to bind to address {}: {}", addr, e))?;
     
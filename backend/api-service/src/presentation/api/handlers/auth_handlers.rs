@@ -1,10 +1,31 @@
-use axum::{Json, extract::{State, Request}, http::{StatusCode, HeaderValue}, response::IntoResponse};
-use authz_core::dto::{LoginRequest, RefreshTokenRequest};
-use shared::RequestContext;
+use axum::{Json, extract::{State, Request}, http::{HeaderMap, StatusCode, HeaderValue}, response::IntoResponse};
+use authz_core::dto::{
+    EmailChangeConfirmRequest, EmailChangeRequest, LoginRequest, PasswordResetConfirmRequest, PasswordResetRequest,
+    RefreshTokenRequest,
+};
+use shared::domain::entities::{LoginAttempt, Session};
+use shared::infrastructure::email_change::EmailChangeService;
+use shared::infrastructure::password_reset::PasswordResetService;
+use shared::{RequestContext, ValidatedJson};
+use shared::shared::validated_json::validation_error_response;
+use validator::Validate;
 use super::super::AppState;
-use super::super::middleware::session_middleware::{get_session, get_app_type, get_app_device};
+use super::super::middleware::csrf;
+use super::super::middleware::session_middleware::{extract_ip_address, get_session, get_app_type, get_app_device, SESSION_COOKIE};
 use std::sync::Arc;
 
+/// Client details of a login request for the audit trail
+fn login_attempt(headers: &HeaderMap, session: Option<&Session>) -> LoginAttempt {
+    let header = |name: &str| headers.get(name).and_then(|h| h.to_str().ok()).map(|s| s.to_string());
+    LoginAttempt {
+        ip_address: extract_ip_address(headers).or(session.map(|s| s.ip_address)),
+        user_agent: header("User-Agent"),
+        app_type: session.map(|s| s.app_type.clone()),
+        app_device: session.map(|s| s.app_device.clone()),
+        request_id: header("X-Request-ID"),
+    }
+}
+
 pub async fn login(
     State(state): State<Arc<AppState>>,
     request: Request,
@@ -36,9 +57,14 @@ pub async fn login(
             return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("Invalid JSON: {}", e)}))).into_response();
         }
     };
+    if let Err(errors) = login_request.validate() {
+        return validation_error_response(&errors);
+    }
     
-    match state.login_use_case.execute(login_request).await {
+    let attempt = login_attempt(&parts.headers, session.as_ref());
+    match state.login_use_case.execute(login_request, attempt).await {
         Ok(mut response) => {
+            let mut csrf_token = None;
             // If we have a session, authenticate it
             if let Some(sess) = session {
                 // Extract user_id from login response
@@ -60,17 +86,27 @@ pub async fn login(
                         } else {
                             // Add session_token to response
                             response.session_token = Some(sess.session_token.clone());
+                            // Rotate the CSRF token with the privilege change
+                            let token = csrf::generate_csrf_token();
+                            response.csrf_token = Some(token.clone());
+                            csrf_token = Some(token);
                         }
                     }
                 }
             }
-            (StatusCode::OK, Json(response)).into_response()
+            let mut http_response = (StatusCode::OK, Json(response)).into_response();
+            if let Some(token) = csrf_token {
+                csrf::issue(&mut http_response, &token, &state.settings.current().session.cookie);
+            }
+            http_response
         },
         Err(e) => {
             e.log_with_operation(location, "login");
             let status = match e {
                 shared::AppError::Authentication(_) => StatusCode::UNAUTHORIZED,
                 shared::AppError::NotFound(_) => StatusCode::NOT_FOUND,
+                shared::AppError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+                shared::AppError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
                 _ => StatusCode::INTERNAL_SERVER_ERROR,
             };
             (status, Json(serde_json::json!({"error": format!("{}", e)}))).into_response()
@@ -106,26 +142,17 @@ pub async fn logout(
     // Clear session cookie by setting it to expire immediately
     let mut response = (StatusCode::OK, Json(serde_json::json!({"message": "Logged out"}))).into_response();
     
-    // Determine if we should use Secure flag (must match cookie setting)
-    let is_secure = request.headers()
-        .get("X-Forwarded-Proto")
-        .and_then(|h| h.to_str().ok())
-        .map(|s| s == "https")
-        .unwrap_or_else(|| {
-            std::env::var("SESSION_COOKIE_SECURE")
-                .unwrap_or_else(|_| "false".to_string())
-                .parse::<bool>()
-                .unwrap_or(false)
-        });
-    let secure_flag = if is_secure { "; Secure" } else { "" };
-    
-    // Set cookie to expire immediately (clear session cookie) - must match cookie attributes
-    let clear_cookie = format!(
-        "session_token=; Path=/; Max-Age=0; SameSite=Lax; HttpOnly{}",
-        secure_flag
-    );
-    if let Ok(header_value) = HeaderValue::from_str(&clear_cookie) {
-        response.headers_mut().insert("Set-Cookie", header_value);
+    // Set cookies to expire immediately - browsers only delete them when
+    // the domain and path match the ones they were set with
+    let cookie_config = state.settings.current().session.cookie.clone();
+    let clear_cookies = [
+        cookie_config.clear_cookie(SESSION_COOKIE),
+        cookie_config.script_readable().clear_cookie(csrf::CSRF_COOKIE),
+    ];
+    for clear_cookie in clear_cookies {
+        if let Ok(header_value) = HeaderValue::from_str(&clear_cookie) {
+            response.headers_mut().append("Set-Cookie", header_value);
+        }
     }
 
     response
@@ -143,6 +170,8 @@ pub async fn refresh_token(
             let status = match e {
                 shared::AppError::Authentication(_) => StatusCode::UNAUTHORIZED,
                 shared::AppError::NotFound(_) => StatusCode::NOT_FOUND,
+                shared::AppError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+                shared::AppError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
                 _ => StatusCode::INTERNAL_SERVER_ERROR,
             };
             (status, Json(serde_json::json!({"error": format!("{}", e)}))).into_response()
@@ -165,6 +194,105 @@ pub async fn userinfo(
             let status = match e {
                 shared::AppError::NotFound(_) => StatusCode::NOT_FOUND,
                 shared::AppError::Authentication(_) => StatusCode::UNAUTHORIZED,
+                shared::AppError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+                shared::AppError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, Json(serde_json::json!({"error": format!("{}", e)}))).into_response()
+        }
+    }
+}
+
+
+fn password_reset_service(state: &AppState) -> PasswordResetService {
+    PasswordResetService::new(
+        state.database_pool.as_ref().clone(),
+        state.session_service.clone(),
+        state.outbox_relay.clone(),
+        state.token_sender.clone(),
+    )
+    .with_ttl_from_env()
+}
+
+/// Start a password reset; the link is emailed through the token sender
+///
+/// Always 202 with the same body, and the work happens after responding, so
+/// neither the answer nor its timing tells whether the email has an account.
+pub async fn request_password_reset(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    ValidatedJson(request): ValidatedJson<PasswordResetRequest>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let service = password_reset_service(&state);
+    let requested_ip = extract_ip_address(&headers);
+    tokio::spawn(async move {
+        if let Err(e) = service.request_reset(&request.email, requested_ip).await {
+            e.log_with_operation(location, "request_password_reset");
+        }
+    });
+    (
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({
+            "message": "If an account exists for this email, a password reset link has been sent"
+        })),
+    )
+}
+
+/// Set a new password with a reset token; ends every session of the user
+pub async fn confirm_password_reset(
+    State(state): State<Arc<AppState>>,
+    ValidatedJson(request): ValidatedJson<PasswordResetConfirmRequest>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    match password_reset_service(&state).confirm_reset(&request.token, &request.new_password).await {
+        Ok(_) => (StatusCode::OK, Json(serde_json::json!({"message": "Password has been reset"}))).into_response(),
+        Err(e) => {
+            e.log_with_operation(location, "confirm_password_reset");
+            let status = match e {
+                shared::AppError::Validation(_) => StatusCode::BAD_REQUEST,
+                shared::AppError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+                shared::AppError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, Json(serde_json::json!({"error": format!("{}", e)}))).into_response()
+        }
+    }
+}
+
+fn email_change_service(state: &AppState) -> EmailChangeService {
+    EmailChangeService::new(
+        state.database_pool.as_ref().clone(),
+        state.session_service.clone(),
+        state.outbox_relay.clone(),
+        state.token_sender.clone(),
+    )
+    .with_ttl_from_env()
+}
+
+/// Ask to change the caller's email; the current one stays until verified
+pub async fn request_email_change(
+    State(state): State<Arc<AppState>>,
+    context: RequestContext,
+    ValidatedJson(request): ValidatedJson<EmailChangeRequest>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    match email_change_service(&state).request_change(context.user_id, &request.new_email).await {
+        Ok(expires_at) => (
+            StatusCode::ACCEPTED,
+            Json(serde_json::json!({
+                "message": "A verification link has been sent to the new email",
+                "expires_at": expires_at
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            e.log_with_operation(location, "request_email_change");
+            let status = match e {
+                shared::AppError::Validation(_) => StatusCode::CONFLICT,
+                shared::AppError::NotFound(_) => StatusCode::NOT_FOUND,
+                shared::AppError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+                shared::AppError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
                 _ => StatusCode::INTERNAL_SERVER_ERROR,
             };
             (status, Json(serde_json::json!({"error": format!("{}", e)}))).into_response()
@@ -172,3 +300,23 @@ pub async fn userinfo(
     }
 }
 
+/// Verify the new email and switch to it; ends every session of the user
+pub async fn confirm_email_change(
+    State(state): State<Arc<AppState>>,
+    ValidatedJson(request): ValidatedJson<EmailChangeConfirmRequest>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    match email_change_service(&state).confirm_change(&request.token).await {
+        Ok(_) => (StatusCode::OK, Json(serde_json::json!({"message": "Email has been changed"}))).into_response(),
+        Err(e) => {
+            e.log_with_operation(location, "confirm_email_change");
+            let status = match e {
+                shared::AppError::Validation(_) => StatusCode::BAD_REQUEST,
+                shared::AppError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+                shared::AppError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, Json(serde_json::json!({"error": format!("{}", e)}))).into_response()
+        }
+    }
+}
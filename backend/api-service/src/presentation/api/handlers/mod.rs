@@ -1,6 +1,8 @@
 pub mod auth_handlers;
 pub mod service_handlers;
+pub mod saml_handlers;
 
 pub use auth_handlers::*;
 pub use service_handlers::*;
+pub use saml_handlers::*;
 
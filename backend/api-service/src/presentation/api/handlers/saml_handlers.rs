@@ -0,0 +1,144 @@
+use axum::{Form, Json, extract::{FromRequest, Query, Request, State}, http::{StatusCode, header}, response::{IntoResponse, Redirect, Response}};
+use serde::Deserialize;
+use shared::domain::repositories::UserRepository;
+use shared::infrastructure::repositories::UserRepositoryImpl;
+use shared::infrastructure::saml::SamlServiceProvider;
+use super::super::AppState;
+use super::super::middleware::csrf;
+use super::super::middleware::session_middleware::{get_session, get_app_device};
+use std::sync::Arc;
+
+#[derive(Debug, Deserialize)]
+pub struct SamlLoginQuery {
+    /// Local path to return to after login
+    pub relay_state: Option<String>,
+    /// UI the session is for (`admin-ui` or `client-ui`)
+    pub app_type: Option<String>,
+}
+
+/// The IdP's HTTP-POST binding; its `RelayState` is ignored in favor of the
+/// one stored with the request
+#[derive(Debug, Deserialize)]
+pub struct SamlAcsForm {
+    #[serde(rename = "SAMLResponse")]
+    pub saml_response: String,
+}
+
+fn saml_provider(state: &AppState) -> Result<&Arc<SamlServiceProvider>, Response> {
+    state.saml.as_ref().ok_or_else(|| {
+        (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "SAML login is not configured"}))).into_response()
+    })
+}
+
+/// Our SP metadata for the IdP administrator
+pub async fn saml_metadata(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let saml = match saml_provider(&state) {
+        Ok(saml) => saml,
+        Err(response) => return response,
+    };
+    match saml.metadata_xml() {
+        Ok(xml) => ([(header::CONTENT_TYPE, "application/samlmetadata+xml")], xml).into_response(),
+        Err(e) => {
+            e.log_with_operation(location, "saml_metadata");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": "Failed to build SAML metadata"})))
+                .into_response()
+        }
+    }
+}
+
+/// Start SP-initiated login by redirecting the browser to the IdP
+pub async fn saml_login(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SamlLoginQuery>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let saml = match saml_provider(&state) {
+        Ok(saml) => saml,
+        Err(response) => return response,
+    };
+    let app_type = query.app_type.filter(|t| matches!(t.as_str(), "admin-ui" | "client-ui"));
+    match saml.start_login(query.relay_state.as_deref(), app_type.as_deref()).await {
+        Ok(url) => Redirect::to(&url).into_response(),
+        Err(e) => {
+            e.log_with_operation(location, "saml_login");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": "Failed to start SAML login"})))
+                .into_response()
+        }
+    }
+}
+
+/// Assertion Consumer Service: verify the IdP's POSTed response and log in
+///
+/// The session set up by the session middleware is authenticated, so the
+/// browser leaves with a session cookie and is sent to the relay path.
+pub async fn saml_acs(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let saml = match saml_provider(&state) {
+        Ok(saml) => saml,
+        Err(response) => return response,
+    };
+    let session = get_session(&request);
+    let app_device = get_app_device(&request);
+    let Form(form) = match Form::<SamlAcsForm>::from_request(request, &()).await {
+        Ok(form) => form,
+        Err(e) => return e.into_response(),
+    };
+
+    let unauthorized = || {
+        (StatusCode::UNAUTHORIZED, Json(serde_json::json!({"error": "SAML login failed"}))).into_response()
+    };
+    let login = match saml.consume(&form.saml_response).await {
+        Ok(login) => login,
+        Err(e) => {
+            e.log_with_operation(location, "saml_acs");
+            return unauthorized();
+        }
+    };
+
+    let user_repository = UserRepositoryImpl::new(state.database_service.clone());
+    let user = match user_repository.find_by_email(&login.identity.email).await {
+        Ok(Some(user)) if user.is_active && !user.is_service_account => user,
+        Ok(_) => {
+            tracing::warn!("SAML login for unknown or inactive user {}", login.identity.email);
+            return unauthorized();
+        }
+        Err(e) => {
+            e.log_with_operation(location, "saml_acs");
+            return unauthorized();
+        }
+    };
+    let user_id = user.id;
+
+    match session {
+        Some(session) => {
+            if let Err(e) = state
+                .session_service
+                .authenticate_session(
+                    session.id,
+                    user.id,
+                    user.organization_id,
+                    login.app_type.as_deref(),
+                    app_device.as_deref(),
+                )
+                .await
+            {
+                e.log_with_operation(location, "saml_acs");
+                return unauthorized();
+            }
+        }
+        None => {
+            tracing::warn!("SAML login for {} without a session", user_id);
+            return unauthorized();
+        }
+    }
+
+    tracing::info!("SAML login for user {}", user_id);
+    // Rotate the CSRF token with the privilege change
+    let mut response = Redirect::to(&login.redirect_to).into_response();
+    csrf::issue(&mut response, &csrf::generate_csrf_token(), &state.settings.current().session.cookie);
+    response
+}
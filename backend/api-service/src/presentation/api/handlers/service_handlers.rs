@@ -1,10 +1,19 @@
-use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use axum::{Json, extract::{Path, State}, http::StatusCode, response::IntoResponse};
 use chrono::Utc;
-use admin_service::dto::{ServiceStatusResponse, ServiceInfo};
+use admin_service::dto::{
+    ApiKeyListResponse, ApiKeyResponse, CreateApiKeyRequest, CreateApiKeyResponse, ServiceInfo,
+    ServiceStatusResponse,
+};
+use shared::{RequestContext, ValidatedJson};
+use shared::domain::entities::ApiKey;
+use shared::infrastructure::encryption::CircuitState;
+use shared::domain::repositories::{ApiKeyRepository, UserRepository};
+use shared::infrastructure::repositories::{ApiKeyRepositoryImpl, UserRepositoryImpl};
 use super::super::AppState;
 use std::sync::Arc;
 use std::env;
 use std::time::Duration;
+use uuid::Uuid;
 
 /// Helper function to parse boolean environment variable
 fn parse_bool_env(key: &str, default: bool) -> bool {
@@ -186,6 +195,49 @@ pub async fn get_service_status(
         error: openbao_error,
     });
 
+    // Vault circuit breaker: not a probe, just whether vault calls are being short-circuited
+    let breaker = state.vault_breaker.snapshot();
+    let breaker_closed = breaker.state == CircuitState::Closed;
+    enabled_count += 1;
+    if breaker_closed {
+        operational_count += 1;
+    }
+    services.push(ServiceInfo {
+        name: "Vault circuit breaker".to_string(),
+        enabled: true,
+        operational: breaker_closed,
+        health_endpoint: None,
+        last_checked: Some(checked_at.clone()),
+        error: (!breaker_closed).then(|| {
+            format!(
+                "{:?} after {} consecutive failures ({} calls rejected)",
+                breaker.state, breaker.consecutive_failures, breaker.rejected_calls
+            )
+        }),
+    });
+
+    // Request concurrency: saturated while every global slot is taken
+    let concurrency = state.concurrency.snapshot();
+    let saturated = concurrency.global.max_in_flight > 0
+        && concurrency.global.in_flight >= concurrency.global.max_in_flight;
+    enabled_count += 1;
+    if !saturated {
+        operational_count += 1;
+    }
+    services.push(ServiceInfo {
+        name: "Request concurrency".to_string(),
+        enabled: true,
+        operational: !saturated,
+        health_endpoint: None,
+        last_checked: Some(checked_at.clone()),
+        error: saturated.then(|| {
+            format!(
+                "All {} request slots in use ({} requests shed)",
+                concurrency.global.max_in_flight, concurrency.rejected
+            )
+        }),
+    });
+
     // Check LocalStack
     let localstack_enabled = parse_bool_env("ENABLE_LOCALSTACK", true);
     enabled_count += if localstack_enabled { 1 } else { 0 };
@@ -272,8 +324,143 @@ pub async fn get_service_status(
             services,
             overall_status,
             checked_at,
+            concurrency: Some(concurrency),
         }),
     )
         .into_response()
 }
 
+
+fn api_key_error(status: StatusCode, message: impl Into<String>) -> axum::response::Response {
+    (status, Json(serde_json::json!({"error": message.into()}))).into_response()
+}
+
+/// Issue an API key for the calling service account
+///
+/// The key is scoped to one of the account's roles and is returned only in
+/// this response. Keys cannot be issued from a request that itself
+/// authenticated with an API key.
+pub async fn create_api_key(
+    State(state): State<Arc<AppState>>,
+    context: RequestContext,
+    ValidatedJson(request): ValidatedJson<CreateApiKeyRequest>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+
+    if context.api_key_id.is_some() {
+        return api_key_error(StatusCode::FORBIDDEN, "API keys cannot be used to issue API keys");
+    }
+    let name = request.name.trim();
+
+    let user_repository = UserRepositoryImpl::new(state.database_service.clone());
+    match user_repository.find_by_id(context.user_id).await {
+        Ok(Some(user)) if user.is_service_account => {}
+        Ok(_) => return api_key_error(StatusCode::FORBIDDEN, "Only service accounts can issue API keys"),
+        Err(e) => {
+            e.log_with_operation(location, "create_api_key");
+            return api_key_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load service account");
+        }
+    }
+
+    // Counted in the shared store, so the limit holds across replicas; an
+    // attempt counts whether or not a key is issued
+    let issued_key = format!("api_keys:issued:{}", context.user_id);
+    match state.counters.increment(&issued_key, std::time::Duration::from_secs(3600)).await {
+        Ok(count) if count as i64 > state.settings.current().runtime.rate_limits.api_keys_per_hour => {
+            return api_key_error(StatusCode::TOO_MANY_REQUESTS, "API key issuance limit reached, try again later");
+        }
+        Ok(_) => {}
+        Err(e) => {
+            e.log_with_operation(location, "create_api_key");
+            return api_key_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create API key");
+        }
+    }
+
+    let api_key_repository = ApiKeyRepositoryImpl::new(state.database_pool.as_ref().clone());
+
+    // A key can only carry a role the account already holds
+    let role = match state.role_repository.get_user_roles(context.user_id).await {
+        Ok(roles) => roles.into_iter().find(|r| r.name == request.role),
+        Err(e) => {
+            e.log_with_operation(location, "create_api_key");
+            return api_key_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load roles");
+        }
+    };
+    let Some(role) = role else {
+        return api_key_error(
+            StatusCode::FORBIDDEN,
+            format!("Service account does not hold role '{}'", request.role),
+        );
+    };
+
+    let expires_at = request
+        .expires_in_days
+        .map(|days| Utc::now() + chrono::Duration::days(days as i64));
+    let result = match ApiKey::generate(context.user_id, role.id, name.to_string(), expires_at) {
+        Ok((api_key, key)) => api_key_repository.create(api_key).await.map(|api_key| (api_key, key)),
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok((api_key, key)) => {
+            tracing::info!("API key {} issued for service account {}", api_key.id, context.user_id);
+            (
+                StatusCode::CREATED,
+                Json(CreateApiKeyResponse {
+                    key,
+                    api_key: ApiKeyResponse::from(api_key),
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            e.log_with_operation(location, "create_api_key");
+            api_key_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create API key")
+        }
+    }
+}
+
+/// List the calling account's API keys, including revoked and expired ones
+pub async fn list_api_keys(
+    State(state): State<Arc<AppState>>,
+    context: RequestContext,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let api_key_repository = ApiKeyRepositoryImpl::new(state.database_pool.as_ref().clone());
+
+    match api_key_repository.list_by_user(context.user_id).await {
+        Ok(api_keys) => (
+            StatusCode::OK,
+            Json(ApiKeyListResponse {
+                api_keys: api_keys.into_iter().map(ApiKeyResponse::from).collect(),
+            }),
+        )
+            .into_response(),
+        Err(e) => {
+            e.log_with_operation(location, "list_api_keys");
+            api_key_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to list API keys")
+        }
+    }
+}
+
+/// Revoke one of the calling account's API keys
+pub async fn revoke_api_key(
+    State(state): State<Arc<AppState>>,
+    context: RequestContext,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let api_key_repository = ApiKeyRepositoryImpl::new(state.database_pool.as_ref().clone());
+
+    match api_key_repository.revoke(id, context.user_id).await {
+        Ok(true) => {
+            tracing::info!("API key {} revoked by {}", id, context.user_id);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(false) => api_key_error(StatusCode::NOT_FOUND, "API key not found"),
+        Err(e) => {
+            e.log_with_operation(location, "revoke_api_key");
+            api_key_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to revoke API key")
+        }
+    }
+}
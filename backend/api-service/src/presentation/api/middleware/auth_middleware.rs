@@ -7,16 +7,18 @@ use axum::{
 use std::sync::Arc;
 use uuid::Uuid;
 use shared::RequestContext;
-use shared::domain::repositories::UserRepository;
-use shared::infrastructure::repositories::UserRepositoryImpl;
+use shared::domain::entities::ApiKey;
+use shared::domain::repositories::{ApiKeyRepository, PermissionRepository, UserRepository};
+use shared::infrastructure::repositories::{ApiKeyRepositoryImpl, PermissionRepositoryImpl, UserRepositoryImpl};
 use super::super::AppState;
 use super::session_middleware::{get_session, get_app_type, get_app_device};
 
-/// Hybrid authentication middleware that supports both session-based and JWT token authentication.
+/// Hybrid authentication middleware that supports session, API key and JWT token authentication.
 /// 
 /// Priority:
 /// 1. Check for authenticated session (for web UIs using cookies)
-/// 2. Fall back to JWT token validation (for mobile/API clients)
+/// 2. `Authorization: ApiKey <key>` (for service accounts)
+/// 3. Fall back to JWT token validation (for mobile/API clients)
 pub async fn auth_middleware(
     State(state): State<Arc<AppState>>,
     mut request: Request,
@@ -101,7 +103,28 @@ pub async fn auth_middleware(
         }
     }
 
-    // Priority 2: Fall back to JWT token authentication (for mobile/API clients)
+    // Priority 2: API key authentication (for service accounts)
+    let api_key = request.headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("ApiKey "))
+        .map(|key| key.trim().to_string());
+    if let Some(key) = api_key {
+        let mut context = authenticate_api_key(&state, &key, request_id).await?;
+
+        if let Some(app_type) = request_app_type {
+            context = context.with_app_type(app_type);
+        }
+        if let Some(app_device) = request_app_device {
+            context = context.with_app_device(app_device);
+        }
+
+        request.extensions_mut().insert(context);
+        let response = next.run(request).await;
+        return Ok(response);
+    }
+
+    // Priority 3: Fall back to JWT token authentication (for mobile/API clients)
     let auth_header = request.headers()
         .get("Authorization")
         .and_then(|h| h.to_str().ok())
@@ -230,3 +253,75 @@ pub async fn auth_middleware(
     let response = next.run(request).await;
     Ok(response)
 }
+
+/// Resolve an API key to its service account, acting as the key's role
+///
+/// The key must be active, its owner an active service account, and the
+/// account must still hold the role the key was scoped to.
+async fn authenticate_api_key(
+    state: &AppState,
+    key: &str,
+    request_id: String,
+) -> Result<RequestContext, (StatusCode, axum::Json<serde_json::Value>)> {
+    let unauthorized = || {
+        (
+            StatusCode::UNAUTHORIZED,
+            axum::Json(serde_json::json!({
+                "error": "Invalid or expired API key"
+            })),
+        )
+    };
+    let internal_error = |e: shared::AppError| {
+        tracing::error!("Failed to authenticate API key: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            axum::Json(serde_json::json!({
+                "error": "Failed to authenticate API key"
+            })),
+        )
+    };
+
+    let api_key_repository = ApiKeyRepositoryImpl::new(state.database_pool.as_ref().clone());
+    let api_key = api_key_repository.find_by_hash(&ApiKey::hash_key(key)).await
+        .map_err(internal_error)?
+        .filter(|api_key| api_key.is_active_at(chrono::Utc::now()))
+        .ok_or_else(unauthorized)?;
+
+    let user_repository = UserRepositoryImpl::new(state.database_service.clone());
+    let user = user_repository.find_by_id(api_key.user_id).await
+        .map_err(internal_error)?
+        .filter(|user| user.is_active && user.is_service_account)
+        .ok_or_else(unauthorized)?;
+
+    let role = state.role_repository.get_user_roles(user.id).await
+        .map_err(internal_error)?
+        .into_iter()
+        .find(|role| role.id == api_key.role_id)
+        .ok_or_else(unauthorized)?;
+
+    let permission_repository = PermissionRepositoryImpl::new(state.database_pool.as_ref().clone());
+    let permissions = permission_repository.find_by_ids(&role.permissions).await
+        .map_err(internal_error)?
+        .into_iter()
+        .map(|permission| permission.name)
+        .collect();
+
+    if let Err(e) = api_key_repository.touch_last_used(api_key.id).await {
+        tracing::warn!("Failed to record API key usage: {}", e);
+    }
+
+    let mut context = RequestContext::new(
+        request_id,
+        user.id,
+        user.email,
+        Some(role.name),
+        permissions,
+    )
+    .with_api_key(api_key.id);
+
+    if let Some(org_id) = user.organization_id {
+        context = context.with_organization(org_id);
+    }
+
+    Ok(context)
+}
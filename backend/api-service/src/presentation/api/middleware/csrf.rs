@@ -0,0 +1,139 @@
+use axum::{
+    Json,
+    http::{HeaderMap, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Response},
+};
+use shared::config::CookieConfig;
+use uuid::Uuid;
+
+/// Double-submit CSRF protection for cookie-authenticated requests
+///
+/// A token is issued alongside the session, both as a cookie scripts on the
+/// UI origin can read and as the `X-CSRF-Token` response header (and
+/// `csrfToken` in the login response). State-changing requests riding on the
+/// session cookie must echo it in the `X-CSRF-Token` header; another site
+/// can make the browser send the cookie but cannot read it to do so.
+pub const CSRF_COOKIE: &str = "csrf_token";
+pub const CSRF_HEADER: &str = "X-CSRF-Token";
+pub const CSRF_ERROR_CODE: &str = "csrf_token_invalid";
+
+/// Paths that establish the session rather than act on it
+const EXEMPT_PATHS: &[&str] = &["/v1/auth/login", "/v1/auth/saml/acs"];
+
+pub(crate) fn generate_csrf_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Append the cookie and header that hand `token` to the client
+///
+/// The cookie shares the session cookie's attributes, except that it is not
+/// HttpOnly: a UI on the same site reads it to fill the header.
+pub(crate) fn issue(response: &mut Response, token: &str, cookie_config: &CookieConfig) {
+    if let Ok(value) = HeaderValue::from_str(&cookie_config.script_readable().set_cookie(CSRF_COOKIE, token)) {
+        response.headers_mut().append("Set-Cookie", value);
+    }
+    if let Ok(value) = HeaderValue::from_str(token) {
+        response.headers_mut().insert(CSRF_HEADER, value);
+    }
+}
+
+/// Whether `response` already hands out a token, e.g. one rotated at login
+pub(crate) fn issued(response: &Response) -> bool {
+    response.headers().contains_key(CSRF_HEADER)
+}
+
+/// Whether the request must carry a matching `X-CSRF-Token`
+///
+/// Only unsafe methods on a session taken from the cookie are checked:
+/// browsers never attach `Authorization` or `X-Session-Token` on their own,
+/// so requests carrying either are not forgeable this way.
+pub(crate) fn requires_check(method: &Method, path: &str, headers: &HeaderMap, cookie_session: bool) -> bool {
+    cookie_session
+        && !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+        && !headers.contains_key("Authorization")
+        && !EXEMPT_PATHS.contains(&path)
+}
+
+/// Whether the header matches the cookie
+pub(crate) fn verify(headers: &HeaderMap, cookie_token: Option<&str>) -> bool {
+    let header_token = headers.get(CSRF_HEADER).and_then(|h| h.to_str().ok());
+    match (header_token, cookie_token) {
+        (Some(header), Some(cookie)) if !cookie.is_empty() => constant_time_eq(header.as_bytes(), cookie.as_bytes()),
+        _ => false,
+    }
+}
+
+pub(crate) fn rejection() -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(serde_json::json!({
+            "error": format!("Missing or invalid {} header", CSRF_HEADER),
+            "code": CSRF_ERROR_CODE
+        })),
+    )
+        .into_response()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_only_unsafe_cookie_requests_are_checked() {
+        let none = HeaderMap::new();
+        assert!(requires_check(&Method::POST, "/v1/users", &none, true));
+        assert!(requires_check(&Method::DELETE, "/v1/users/1", &none, true));
+        assert!(!requires_check(&Method::GET, "/v1/users", &none, true));
+        assert!(!requires_check(&Method::HEAD, "/v1/users", &none, true));
+        assert!(!requires_check(&Method::OPTIONS, "/v1/users", &none, true));
+        // A session from `X-Session-Token` is not one a browser attaches
+        assert!(!requires_check(&Method::POST, "/v1/users", &none, false));
+    }
+
+    #[test]
+    fn test_login_and_saml_acs_are_exempt() {
+        let none = HeaderMap::new();
+        assert!(!requires_check(&Method::POST, "/v1/auth/login", &none, true));
+        assert!(!requires_check(&Method::POST, "/v1/auth/saml/acs", &none, true));
+        assert!(requires_check(&Method::POST, "/v1/auth/logout", &none, true));
+    }
+
+    #[test]
+    fn test_authorization_header_bypasses_the_check() {
+        let bearer = headers(&[("Authorization", "Bearer abc")]);
+        assert!(!requires_check(&Method::POST, "/v1/users", &bearer, true));
+    }
+
+    #[test]
+    fn test_verify_needs_a_matching_pair() {
+        assert!(verify(&headers(&[(CSRF_HEADER, "abc123")]), Some("abc123")));
+
+        assert!(!verify(&HeaderMap::new(), Some("abc123")));
+        assert!(!verify(&headers(&[(CSRF_HEADER, "abc123")]), None));
+        assert!(!verify(&headers(&[(CSRF_HEADER, "abc124")]), Some("abc123")));
+        assert!(!verify(&headers(&[(CSRF_HEADER, "abc")]), Some("abc123")));
+        // An empty cookie never matches, even an empty header
+        assert!(!verify(&headers(&[(CSRF_HEADER, "")]), Some("")));
+    }
+
+    #[tokio::test]
+    async fn test_rejection_is_403_csrf_token_invalid() {
+        let response = rejection();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], CSRF_ERROR_CODE);
+    }
+}
@@ -0,0 +1,44 @@
+use axum::{
+    Router,
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use shared::RequestContext;
+use shared::config::FeatureFlags;
+use std::sync::Arc;
+use crate::presentation::api::AppState;
+
+/// Mount `router` behind feature `flag`
+///
+/// Routes stay registered so the flag can be flipped by a config reload; while
+/// the flag is off they answer 404 as if they did not exist. Merge the result
+/// before auth middleware is layered on so admin overrides can be honoured.
+pub fn gated(state: Arc<AppState>, flag: &'static str, router: Router<Arc<AppState>>) -> Router<Arc<AppState>> {
+    router.route_layer(axum::middleware::from_fn_with_state((state, flag), feature_gate))
+}
+
+async fn feature_gate(
+    State((state, flag)): State<(Arc<AppState>, &'static str)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let settings = state.settings.current();
+    let flags = FeatureFlags::for_request(
+        &settings.runtime,
+        request.headers(),
+        request.extensions().get::<RequestContext>(),
+    );
+    if flags.is_enabled(flag) {
+        return next.run(request).await;
+    }
+
+    tracing::warn!(
+        feature = flag,
+        method = %request.method(),
+        path = %request.uri().path(),
+        "Gated route hit while feature is disabled"
+    );
+    StatusCode::NOT_FOUND.into_response()
+}
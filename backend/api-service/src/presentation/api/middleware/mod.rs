@@ -4,11 +4,14 @@ pub mod encryption_middleware;
 pub mod request_id;
 pub mod app_access_middleware;
 pub mod session_middleware;
+pub mod csrf;
 pub mod request_logging_middleware;
+pub mod feature_gate;
 
 pub use auth_middleware::auth_middleware;
 pub use acl_middleware::acl_middleware;
 pub use request_id::request_id_middleware;
 pub use session_middleware::session_middleware;
 pub use request_logging_middleware::request_logging_middleware;
+pub use feature_gate::gated;
 
@@ -22,6 +22,9 @@ pub async fn request_id_middleware(
 
     // Add request ID to request extensions for handlers to use
     request.extensions_mut().insert(request_id.clone());
+    if let Ok(header_value) = HeaderValue::from_str(&request_id) {
+        request.headers_mut().insert("X-Request-ID", header_value);
+    }
 
     // Create a tracing span with request_id that will be included in all logs
     let span = tracing::span!(
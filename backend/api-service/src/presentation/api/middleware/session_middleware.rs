@@ -8,15 +8,17 @@ use shared::domain::entities::Session;
 use std::net::IpAddr;
 use std::sync::Arc;
 use super::super::AppState;
+use super::csrf;
 use uuid::Uuid;
 
 const SESSION_TOKEN_HEADER: &str = "X-Session-Token";
 const SESSION_TOKEN_COOKIE_HEADER: &str = "Cookie";
+pub(crate) const SESSION_COOKIE: &str = "session_token";
 const APP_TYPE_HEADER: &str = "X-App-Type";
 const APP_DEVICE_HEADER: &str = "X-App-Device";
 
 /// Extract IP address from request, handling proxy headers
-fn extract_ip_address(headers: &HeaderMap) -> Option<IpAddr> {
+pub(crate) fn extract_ip_address(headers: &HeaderMap) -> Option<IpAddr> {
     // Try X-Forwarded-For first (first IP if multiple)
     if let Some(forwarded_for) = headers.get("X-Forwarded-For") {
         if let Ok(forwarded_str) = forwarded_for.to_str() {
@@ -56,6 +58,11 @@ fn generate_session_token() -> String {
 
 /// Extract session token from Cookie header
 fn extract_session_token_from_cookies(headers: &HeaderMap) -> Option<String> {
+    extract_cookie(headers, SESSION_COOKIE)
+}
+
+/// Extract a cookie value from the Cookie header
+fn extract_cookie(headers: &HeaderMap, cookie_name: &str) -> Option<String> {
     let cookie_header = headers.get(SESSION_TOKEN_COOKIE_HEADER)?;
     let cookie_str = cookie_header.to_str().ok()?;
     
@@ -65,7 +72,7 @@ fn extract_session_token_from_cookies(headers: &HeaderMap) -> Option<String> {
         if parts.len() == 2 {
             let name = parts[0].trim();
             let value = parts[1].trim();
-            if name == "session_token" {
+            if name == cookie_name {
                 return Some(value.to_string());
             }
         }
@@ -139,10 +146,12 @@ pub async fn session_middleware(
     next: Next,
 ) -> Response {
     // Extract or generate session token
-    let session_token = headers
+    let header_token = headers
         .get(SESSION_TOKEN_HEADER)
         .and_then(|h| h.to_str().ok())
-        .map(|s| s.to_string())
+        .map(|s| s.to_string());
+    let cookie_session = header_token.is_none() && extract_session_token_from_cookies(&headers).is_some();
+    let session_token = header_token
         .or_else(|| extract_session_token_from_cookies(&headers))
         .unwrap_or_else(|| generate_session_token());
 
@@ -185,6 +194,21 @@ pub async fn session_middleware(
         }
     };
 
+    // Cookie-authenticated state changes must echo the CSRF cookie
+    let csrf_cookie = extract_cookie(&headers, csrf::CSRF_COOKIE);
+    if !session.is_ghost_session()
+        && csrf::requires_check(request.method(), request.uri().path(), &headers, cookie_session)
+        && !csrf::verify(&headers, csrf_cookie.as_deref())
+    {
+        tracing::warn!(
+            method = %request.method(),
+            path = %request.uri().path(),
+            session_id = %session.id,
+            "Rejected cookie-authenticated request without a valid CSRF token"
+        );
+        return csrf::rejection();
+    }
+
     // Store app_type and app_device in request extensions for use in other middleware
     request.extensions_mut().insert(app_type.clone());
     request.extensions_mut().insert(app_device.clone());
@@ -205,34 +229,22 @@ pub async fn session_middleware(
     // Add session token to response if it's new
     let mut response = next.run(request).await;
 
+    // Cookie attributes (Secure, SameSite, domain, ...) come from settings
+    let cookie_config = state.settings.current().session.cookie.clone();
+
     // Set session cookie if it's a new session (check if cookie was in request)
     if extract_session_token_from_cookies(&headers).is_none() {
-        // Determine if we should use Secure flag (HTTPS only)
-        // Check if request is over HTTPS or if environment variable is set
-        let is_secure = headers
-            .get("X-Forwarded-Proto")
-            .and_then(|h| h.to_str().ok())
-            .map(|s| s == "https")
-            .unwrap_or_else(|| {
-                // Check environment variable as fallback
-                std::env::var("SESSION_COOKIE_SECURE")
-                    .unwrap_or_else(|_| "false".to_string())
-                    .parse::<bool>()
-                    .unwrap_or(false)
-            });
-
-        // Build secure cookie string
-        let secure_flag = if is_secure { "; Secure" } else { "" };
-        let cookie = format!(
-            "session_token={}; Path=/; Max-Age={}; SameSite=Lax; HttpOnly{}",
-            session_token, 3600 * 24 * 7, // 7 days
-            secure_flag
-        );
+        let cookie = cookie_config.set_cookie(SESSION_COOKIE, &session_token);
         if let Ok(header_value) = HeaderValue::from_str(&cookie) {
-            response.headers_mut().insert("Set-Cookie", header_value);
+            response.headers_mut().append("Set-Cookie", header_value);
         }
     }
 
+    // Hand out a CSRF token with the session, unless the handler rotated it
+    if csrf_cookie.is_none() && !csrf::issued(&response) {
+        csrf::issue(&mut response, &csrf::generate_csrf_token(), &cookie_config);
+    }
+
     response
 }
 
@@ -1,5 +1,7 @@
 use crate::dto::{LoginRequest, LoginResponse, LoginUserResponse};
+use shared::domain::entities::{LoginAttempt, LoginEvent, LoginFailureReason, User};
 use shared::domain::repositories::{UserRepository, RefreshTokenRepository, RoleRepository, PermissionRepository};
+use shared::infrastructure::login_audit::LoginAuditor;
 use crate::oidc::TokenManager;
 use shared::AppResult;
 use bcrypt::verify;
@@ -7,6 +9,7 @@ use uuid::Uuid;
 use chrono::{Utc, Duration};
 use sha2::{Sha256, Digest};
 use std::collections::HashSet;
+use std::sync::Arc;
 
 pub struct LoginUseCase {
     user_repository: Box<dyn UserRepository>,
@@ -14,6 +17,7 @@ pub struct LoginUseCase {
     role_repository: Box<dyn RoleRepository>,
     permission_repository: Box<dyn PermissionRepository>,
     token_manager: TokenManager,
+    auditor: Option<Arc<LoginAuditor>>,
 }
 
 impl LoginUseCase {
@@ -30,8 +34,55 @@ impl LoginUseCase {
             role_repository,
             permission_repository,
             token_manager,
+            auditor: None,
         }
     }
+
+    /// Record every attempt, successful or not, with `auditor`
+    pub fn with_auditor(mut self, auditor: Arc<LoginAuditor>) -> Self {
+        self.auditor = Some(auditor);
+        self
+    }
+
+    fn audit(&self, event: impl FnOnce() -> LoginEvent) {
+        if let Some(auditor) = &self.auditor {
+            auditor.record(event());
+        }
+    }
+
+    /// The user `request` authenticates, or why it does not
+    ///
+    /// Every failure surfaces to the client as the same "Invalid
+    /// credentials" error; only the audit trail records the reason.
+    async fn authenticate(&self, request: &LoginRequest) -> AppResult<Result<User, (Option<Uuid>, LoginFailureReason)>> {
+        let location = concat!(file!(), ":", line!());
+        let Some(user) = self.user_repository
+            .find_by_email(&request.email)
+            .await
+            .map_err(|e| {
+                e.log_with_operation(location, "login");
+                e
+            })?
+        else {
+            return Ok(Err((None, LoginFailureReason::NoSuchUser)));
+        };
+
+        // A hash bcrypt cannot parse verifies nothing
+        let password_ok = verify(&request.password, &user.password_hash).unwrap_or_else(|_| {
+            let err = shared::AppError::Authentication("Password verification failed".to_string());
+            err.log_with_operation(location, "login");
+            false
+        });
+        if !password_ok {
+            return Ok(Err((Some(user.id), LoginFailureReason::BadPassword)));
+        }
+
+        if !user.is_active {
+            return Ok(Err((Some(user.id), LoginFailureReason::Inactive)));
+        }
+
+        Ok(Ok(user))
+    }
     
     async fn get_user_role_and_permissions(&self, user_id: Uuid, is_super_user: bool) -> AppResult<(String, Vec<String>)> {
         // Super users bypass permission checks - return all permissions
@@ -74,40 +125,17 @@ impl LoginUseCase {
         Ok((primary_role, permission_names))
     }
 
-    pub async fn execute(&self, request: LoginRequest) -> AppResult<LoginResponse> {
+    pub async fn execute(&self, request: LoginRequest, attempt: LoginAttempt) -> AppResult<LoginResponse> {
         let location = concat!(file!(), ":", line!());
-        // Find user by email
-        let user = self.user_repository
-            .find_by_email(&request.email)
-            .await
-            .map_err(|e| {
-                e.log_with_operation(location, "login");
-                e
-            })?
-            .ok_or_else(|| {
+        let user = match self.authenticate(&request).await? {
+            Ok(user) => user,
+            Err((user_id, reason)) => {
+                self.audit(|| LoginEvent::failure(user_id, &request.email, reason, attempt));
                 let err = shared::AppError::Authentication("Invalid credentials".to_string());
                 err.log_with_operation(location, "login");
-                err
-            })?;
-
-        // Verify password
-        if !verify(&request.password, &user.password_hash)
-            .map_err(|_| {
-                let err = shared::AppError::Authentication("Password verification failed".to_string());
-                err.log_with_operation(location, "login");
-                err
-            })? {
-            let err = shared::AppError::Authentication("Invalid credentials".to_string());
-            err.log_with_operation(location, "login");
-            return Err(err);
-        }
-
-        // Check if user is active
-        if !user.is_active {
-            let err = shared::AppError::Authentication("User account is inactive".to_string());
-            err.log_with_operation(location, "login");
-            return Err(err);
-        }
+                return Err(err);
+            }
+        };
 
         // Get user roles and permissions
         let (primary_role, permissions) = self.get_user_role_and_permissions(user.id, user.is_super_user).await
@@ -148,6 +176,8 @@ impl LoginUseCase {
         updated_user.record_login();
         let _ = self.user_repository.update(updated_user).await;
 
+        self.audit(|| LoginEvent::success(user.id, &request.email, attempt));
+
         // Create user response
         let user_response = LoginUserResponse {
             id: user.id.to_string(),
@@ -164,6 +194,7 @@ impl LoginUseCase {
             expires_in: 3600,
             user: user_response,
             session_token: None, // Will be set by handler if session exists
+            csrf_token: None,
         })
     }
 }
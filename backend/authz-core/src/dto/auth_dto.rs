@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
+use validator::Validate;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct LoginRequest {
+    #[validate(email)]
     pub email: String,
+    #[validate(length(min = 1, message = "is required"))]
     pub password: String,
 }
 
@@ -16,6 +19,37 @@ pub struct LoginResponse {
     pub user: LoginUserResponse,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_token: Option<String>,
+    /// Echo as `X-CSRF-Token` on cookie-authenticated state changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub csrf_token: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct PasswordResetRequest {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordResetConfirmRequest {
+    #[validate(length(min = 1, message = "is required"))]
+    pub token: String,
+    #[validate(length(min = 1, message = "is required"))]
+    pub new_password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailChangeRequest {
+    #[validate(email)]
+    pub new_email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct EmailChangeConfirmRequest {
+    #[validate(length(min = 1, message = "is required"))]
+    pub token: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
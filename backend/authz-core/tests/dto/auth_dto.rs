@@ -36,6 +36,8 @@ fn test_login_response_serialization() {
             permissions: vec!["read:users".to_string()],
             created_at: Some("2024-01-01T00:00:00Z".to_string()),
         },
+        session_token: None,
+        csrf_token: None,
     };
 
     let json = serde_json::to_string(&response).unwrap();
@@ -0,0 +1,25 @@
+-- Migration: Create event_outbox table
+-- Description: Transactional outbox for domain events (UserDeactivated, PolicyChanged, SecretRotated)
+-- Related Entity: src/domain/events/domain_event.rs (DomainEvent)
+
+-- Tables Created:
+--   - event_outbox
+
+-- Indexes Created:
+--   - idx_event_outbox_pending (B-tree, on next_attempt_at WHERE published_at IS NULL)
+--   - idx_event_outbox_aggregate (B-tree, on event_type, aggregate_id)
+
+CREATE TABLE IF NOT EXISTS event_outbox (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event_type VARCHAR(100) NOT NULL,
+    aggregate_id TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    published_at TIMESTAMPTZ,
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_event_outbox_pending ON event_outbox(next_attempt_at) WHERE published_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_event_outbox_aggregate ON event_outbox(event_type, aggregate_id);
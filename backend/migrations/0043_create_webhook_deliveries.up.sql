@@ -0,0 +1,27 @@
+-- Migration: Create webhook_deliveries table
+-- Description: Outbound webhook queue with retry state and dead-lettering
+-- Related Entity: src/infrastructure/events/webhook.rs (WebhookDispatcher)
+
+-- Tables Created:
+--   - webhook_deliveries
+
+-- Indexes Created:
+--   - idx_webhook_deliveries_pending (B-tree, on next_attempt_at WHERE status = 'pending')
+--   - idx_webhook_deliveries_dead_letter (B-tree, on created_at WHERE status = 'dead_letter')
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    -- Same as the event id, so redelivered events are enqueued once
+    id UUID PRIMARY KEY,
+    event_type VARCHAR(100) NOT NULL,
+    payload JSONB NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'delivered', 'dead_letter')),
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_pending ON webhook_deliveries(next_attempt_at) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_dead_letter ON webhook_deliveries(created_at) WHERE status = 'dead_letter';
@@ -0,0 +1,3 @@
+-- Drop relationship read indexes
+DROP INDEX IF EXISTS idx_relationships_object_page;
+DROP INDEX IF EXISTS idx_relationships_subject_page;
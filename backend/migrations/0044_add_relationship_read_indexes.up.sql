@@ -0,0 +1,17 @@
+-- Migration: Add relationship read indexes
+-- Description: Support paginated reads by subject or object (RelationshipStore::read_relationships)
+-- Related Entity: src/domain/entities/relationship.rs (Relationship)
+
+-- Indexes Created:
+--   - idx_relationships_subject_page (B-tree, on "user", relation, object, id WHERE deleted_at IS NULL)
+--   - idx_relationships_object_page (B-tree, on object, relation, "user", id WHERE deleted_at IS NULL)
+
+-- Both match the ORDER BY of their query, so a page is read straight off the
+-- index with or without a relation filter.
+CREATE INDEX IF NOT EXISTS idx_relationships_subject_page
+    ON relationships("user", relation, object, id)
+    WHERE deleted_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_relationships_object_page
+    ON relationships(object, relation, "user", id)
+    WHERE deleted_at IS NULL;
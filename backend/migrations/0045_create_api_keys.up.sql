@@ -0,0 +1,31 @@
+-- Migration: Create api_keys table and service account flag
+-- Description: Long-lived API keys for service accounts, hashed at rest and scoped to one role
+-- Related Entity: src/domain/entities/api_key.rs (ApiKey)
+--
+-- Tables Created:
+--   - api_keys
+--
+-- Columns Added:
+--   - users.is_service_account
+--
+-- Indexes Created:
+--   - idx_api_keys_user_id (B-tree, on user_id, created_at)
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_service_account BOOLEAN NOT NULL DEFAULT false;
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    -- First characters of the key, kept in clear so owners can tell keys apart
+    key_prefix VARCHAR(32) NOT NULL,
+    -- SHA-256 of the full key; the key itself is only returned once
+    key_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_user_id ON api_keys(user_id, created_at);
@@ -0,0 +1,2 @@
+-- Rollback: Drop relationship validity window check
+ALTER TABLE relationships DROP CONSTRAINT IF EXISTS chk_relationships_window;
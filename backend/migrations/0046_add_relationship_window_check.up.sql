@@ -0,0 +1,15 @@
+-- Migration: Require non-empty validity windows on relationships
+-- Description: Scheduled grants take effect at valid_from and lapse at expires_at; a window
+--              that ends before it starts would never take effect
+-- Related Entity: src/domain/entities/relationship.rs (Relationship)
+--
+-- Constraints Added:
+--   - chk_relationships_window (valid_from < expires_at when both are set)
+--
+-- NOT VALID skips existing rows so the migration cannot fail on legacy data;
+-- new and updated rows are checked.
+
+ALTER TABLE relationships
+ADD CONSTRAINT chk_relationships_window
+CHECK (valid_from IS NULL OR expires_at IS NULL OR valid_from < expires_at)
+NOT VALID;
@@ -0,0 +1,3 @@
+-- Drop group DEK share tables
+DROP TABLE IF EXISTS group_dek_shares;
+DROP TABLE IF EXISTS group_dek_versions;
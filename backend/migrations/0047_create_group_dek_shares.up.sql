@@ -0,0 +1,26 @@
+-- Migration: Create group DEK version and share tables
+-- Description: Group DEKs are escrowed in the vault per version and wrapped for each member
+--              with the member's own DEK, so data shared with a group is encrypted once
+-- Related Module: src/infrastructure/encryption/group_keys.rs (GroupKeyring)
+--
+-- Tables Created:
+--   - group_dek_versions
+--   - group_dek_shares (primary key also serves lookups by group and member)
+
+CREATE TABLE IF NOT EXISTS group_dek_versions (
+    group_id UUID NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    key_version INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (group_id, key_version)
+);
+
+CREATE TABLE IF NOT EXISTS group_dek_shares (
+    group_id UUID NOT NULL,
+    member_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    key_version INTEGER NOT NULL,
+    -- Group DEK wrapped with the member's DEK: nonce || ciphertext
+    wrapped_dek BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (group_id, member_id, key_version),
+    FOREIGN KEY (group_id, key_version) REFERENCES group_dek_versions(group_id, key_version) ON DELETE CASCADE
+);
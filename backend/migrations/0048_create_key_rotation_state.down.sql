@@ -0,0 +1,3 @@
+-- Drop key_rotation_state table
+DROP INDEX IF EXISTS idx_key_rotation_state_next_due_at;
+DROP TABLE IF EXISTS key_rotation_state;
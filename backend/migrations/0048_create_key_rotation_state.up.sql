@@ -0,0 +1,25 @@
+-- Migration: Create key_rotation_state table
+-- Description: Schedule and progress of automatic key rotation per key type
+-- Related Module: src/infrastructure/encryption/key_rotation.rs (KeyRotationScheduler)
+--
+-- Tables Created:
+--   - key_rotation_state
+--
+-- Indexes Created:
+--   - idx_key_rotation_state_next_due_at (B-tree, on next_due_at)
+
+CREATE TABLE IF NOT EXISTS key_rotation_state (
+    key_type VARCHAR(100) PRIMARY KEY,
+    max_age_days INTEGER NOT NULL CHECK (max_age_days > 0),
+    last_rotated_at TIMESTAMPTZ,
+    next_due_at TIMESTAMPTZ NOT NULL,
+    -- Resume point and progress of an unfinished run
+    cursor TEXT,
+    run_started_at TIMESTAMPTZ,
+    rotated_in_run BIGINT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_key_rotation_state_next_due_at ON key_rotation_state(next_due_at);
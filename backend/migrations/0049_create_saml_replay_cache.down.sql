@@ -0,0 +1,5 @@
+-- Drop SAML request and assertion tracking tables
+DROP INDEX IF EXISTS idx_saml_assertions_expires_at;
+DROP TABLE IF EXISTS saml_assertions;
+DROP INDEX IF EXISTS idx_saml_requests_expires_at;
+DROP TABLE IF EXISTS saml_requests;
@@ -0,0 +1,33 @@
+-- Migration: Create SAML request and assertion tracking tables
+-- Description: Outstanding AuthnRequests (for InResponseTo checks) and consumed assertion ids (replay detection)
+-- Related Module: src/infrastructure/saml/replay.rs (SamlReplayStore)
+--
+-- Tables Created:
+--   - saml_requests
+--   - saml_assertions
+--
+-- Indexes Created:
+--   - idx_saml_requests_expires_at (B-tree, on expires_at)
+--   - idx_saml_assertions_expires_at (B-tree, on expires_at)
+
+CREATE TABLE IF NOT EXISTS saml_requests (
+    -- AuthnRequest ID, echoed by the IdP as InResponseTo
+    id VARCHAR(255) PRIMARY KEY,
+    -- Where to send the browser after login; validated as a local path
+    relay_state TEXT,
+    app_type VARCHAR(50),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_saml_requests_expires_at ON saml_requests(expires_at);
+
+CREATE TABLE IF NOT EXISTS saml_assertions (
+    -- Assertion ID; a second use of the same id is a replay
+    id VARCHAR(255) PRIMARY KEY,
+    consumed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Kept until the assertion could no longer be accepted anyway
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_saml_assertions_expires_at ON saml_assertions(expires_at);
@@ -0,0 +1,7 @@
+-- Drop login_events table
+DROP INDEX IF EXISTS idx_login_events_failures;
+DROP INDEX IF EXISTS idx_login_events_created_at;
+DROP INDEX IF EXISTS idx_login_events_ip_address;
+DROP INDEX IF EXISTS idx_login_events_email;
+DROP INDEX IF EXISTS idx_login_events_user_id_created_at;
+DROP TABLE IF EXISTS login_events;
@@ -0,0 +1,43 @@
+-- Migration: Create login_events table
+-- Description: One row per password login attempt, successful or not, for security review and anomaly detection
+-- Related Entity: src/domain/entities/login_event.rs (LoginEvent)
+--
+-- Tables Created:
+--   - login_events
+--
+-- Indexes Created:
+--   - idx_login_events_user_id_created_at (B-tree, on user_id, created_at WHERE user_id IS NOT NULL)
+--   - idx_login_events_email (B-tree, on email)
+--   - idx_login_events_ip_address (B-tree, on ip_address)
+--   - idx_login_events_created_at (B-tree, on created_at)
+--   - idx_login_events_failures (B-tree, on failure_reason, created_at WHERE NOT succeeded)
+
+CREATE TABLE IF NOT EXISTS login_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- NULL when the email matched no user
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    -- As submitted, trimmed and lowercased
+    email VARCHAR(255) NOT NULL,
+    succeeded BOOLEAN NOT NULL,
+    -- Why a failed login failed; never shown to the client
+    failure_reason VARCHAR(32) CHECK (failure_reason IN ('no_such_user', 'bad_password', 'inactive', 'locked')),
+    ip_address INET,
+    user_agent TEXT,
+    app_type VARCHAR(50),
+    app_device VARCHAR(50),
+    request_id VARCHAR(255),
+    -- Coarse location of ip_address, when a geo lookup is configured
+    country VARCHAR(64),
+    region VARCHAR(128),
+    city VARCHAR(128),
+    latitude DOUBLE PRECISION,
+    longitude DOUBLE PRECISION,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (succeeded = (failure_reason IS NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_login_events_user_id_created_at ON login_events(user_id, created_at) WHERE user_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_login_events_email ON login_events(email);
+CREATE INDEX IF NOT EXISTS idx_login_events_ip_address ON login_events(ip_address);
+CREATE INDEX IF NOT EXISTS idx_login_events_created_at ON login_events(created_at);
+CREATE INDEX IF NOT EXISTS idx_login_events_failures ON login_events(failure_reason, created_at) WHERE NOT succeeded;
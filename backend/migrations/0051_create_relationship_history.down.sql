@@ -0,0 +1,4 @@
+-- Rollback: Drop relationship history
+DROP TRIGGER IF EXISTS trg_relationships_history ON relationships;
+DROP FUNCTION IF EXISTS record_relationship_history();
+DROP TABLE IF EXISTS relationship_history;
@@ -0,0 +1,98 @@
+-- Migration: Create relationship_history table
+-- Description: Append-only log of every change to relationships, so the authorization graph
+--              can be rebuilt as it stood at a past time or consistency token
+-- Related Entity: src/domain/entities/relationship.rs (Relationship)
+--
+-- Tables Created:
+--   - relationship_history
+--
+-- Functions Created:
+--   - record_relationship_history() (trigger function)
+--
+-- Triggers Created:
+--   - trg_relationships_history (AFTER INSERT OR UPDATE OR DELETE on relationships)
+--
+-- Indexes Created:
+--   - idx_relationship_history_changed_at (B-tree, on changed_at)
+--   - idx_relationship_history_txid (B-tree, on txid)
+--   - idx_relationship_history_tuple (B-tree composite, on user, relation, object, changed_at, id)
+--
+-- Rows are keyed by tuple rather than relationship id: an upsert of a live tuple
+-- replaces its id. A tuple's state at a point is its latest change by changed_at,
+-- then id. Existing relationships are backfilled from created_at and
+-- deleted_at; their txid is the migration's, so token-based reads only see
+-- history recorded from here on.
+
+CREATE TABLE IF NOT EXISTS relationship_history (
+    id BIGSERIAL PRIMARY KEY,
+    relationship_id UUID NOT NULL,
+    "user" VARCHAR(255) NOT NULL,
+    relation VARCHAR(255) NOT NULL,
+    object VARCHAR(255) NOT NULL,
+    organization_id UUID,
+    -- State of the row after the change (before it, for deletes)
+    created_at TIMESTAMPTZ NOT NULL,
+    valid_from TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ,
+    is_active BOOLEAN NOT NULL,
+    deleted_at TIMESTAMPTZ,
+    operation VARCHAR(10) NOT NULL CHECK (operation IN ('insert', 'update', 'delete')),
+    -- Same value a ConsistencyToken carries for the writing transaction
+    txid XID8 NOT NULL DEFAULT pg_current_xact_id(),
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_relationship_history_changed_at ON relationship_history(changed_at);
+CREATE INDEX IF NOT EXISTS idx_relationship_history_txid ON relationship_history(txid);
+CREATE INDEX IF NOT EXISTS idx_relationship_history_tuple ON relationship_history("user", relation, object, changed_at, id);
+
+CREATE OR REPLACE FUNCTION record_relationship_history()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        INSERT INTO relationship_history (
+            relationship_id, "user", relation, object, organization_id, created_at,
+            valid_from, expires_at, is_active, deleted_at, operation
+        )
+        VALUES (
+            OLD.id, OLD."user", OLD.relation, OLD.object, OLD.organization_id, OLD.created_at,
+            OLD.valid_from, OLD.expires_at, OLD.is_active, OLD.deleted_at, 'delete'
+        );
+        RETURN OLD;
+    END IF;
+
+    INSERT INTO relationship_history (
+        relationship_id, "user", relation, object, organization_id, created_at,
+        valid_from, expires_at, is_active, deleted_at, operation
+    )
+    VALUES (
+        NEW.id, NEW."user", NEW.relation, NEW.object, NEW.organization_id, NEW.created_at,
+        NEW.valid_from, NEW.expires_at, NEW.is_active, NEW.deleted_at, LOWER(TG_OP)
+    );
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_relationships_history ON relationships;
+CREATE TRIGGER trg_relationships_history
+AFTER INSERT OR UPDATE OR DELETE ON relationships
+FOR EACH ROW EXECUTE FUNCTION record_relationship_history();
+
+-- Backfill: each existing relationship was added at created_at...
+INSERT INTO relationship_history (
+    relationship_id, "user", relation, object, organization_id, created_at,
+    valid_from, expires_at, is_active, deleted_at, operation, changed_at
+)
+SELECT id, "user", relation, object, organization_id, created_at,
+       valid_from, expires_at, true, NULL, 'insert', created_at
+FROM relationships;
+
+-- ...and the deleted ones removed at deleted_at
+INSERT INTO relationship_history (
+    relationship_id, "user", relation, object, organization_id, created_at,
+    valid_from, expires_at, is_active, deleted_at, operation, changed_at
+)
+SELECT id, "user", relation, object, organization_id, created_at,
+       valid_from, expires_at, is_active, deleted_at, 'update', deleted_at
+FROM relationships
+WHERE deleted_at IS NOT NULL;
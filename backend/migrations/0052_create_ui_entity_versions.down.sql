@@ -0,0 +1,7 @@
+-- Rollback: Drop UI entity version history
+DROP TRIGGER IF EXISTS trg_ui_api_endpoints_versions ON ui_api_endpoints;
+DROP TRIGGER IF EXISTS trg_ui_fields_versions ON ui_fields;
+DROP TRIGGER IF EXISTS trg_ui_buttons_versions ON ui_buttons;
+DROP TRIGGER IF EXISTS trg_ui_pages_versions ON ui_pages;
+DROP FUNCTION IF EXISTS record_ui_entity_version();
+DROP TABLE IF EXISTS ui_entity_versions;
@@ -0,0 +1,79 @@
+-- Migration: Create ui_entity_versions table
+-- Description: Prior versions of UI pages, buttons, fields and API endpoints, so a bad
+--              edit can be diffed against and rolled back
+-- Related Entity: src/domain/entities/ui_entity_version.rs (UiEntityVersion)
+--
+-- Tables Created:
+--   - ui_entity_versions
+--
+-- Functions Created:
+--   - record_ui_entity_version() (trigger function)
+--
+-- Triggers Created:
+--   - trg_ui_pages_versions (BEFORE UPDATE on ui_pages)
+--   - trg_ui_buttons_versions (BEFORE UPDATE on ui_buttons)
+--   - trg_ui_fields_versions (BEFORE UPDATE on ui_fields)
+--   - trg_ui_api_endpoints_versions (BEFORE UPDATE on ui_api_endpoints)
+--
+-- Indexes Created:
+--   - ui_entity_versions_entity_version_key (unique, on entity_type, entity_id, version)
+--
+-- Every update, soft deletes included, stores the row as it was before. changed_by and
+-- changed_at are who wrote that version and when; superseded_at is when it was replaced.
+-- Only the 50 most recent versions of each entity are kept.
+
+CREATE TABLE IF NOT EXISTS ui_entity_versions (
+    id BIGSERIAL PRIMARY KEY,
+    entity_type VARCHAR(20) NOT NULL CHECK (entity_type IN ('page', 'button', 'field', 'api')),
+    entity_id UUID NOT NULL,
+    version BIGINT NOT NULL,
+    snapshot JSONB NOT NULL,
+    changed_by UUID,
+    changed_at TIMESTAMPTZ NOT NULL,
+    superseded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT ui_entity_versions_entity_version_key UNIQUE (entity_type, entity_id, version)
+);
+
+-- TG_ARGV[0] is the entity type
+CREATE OR REPLACE FUNCTION record_ui_entity_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO ui_entity_versions (entity_type, entity_id, version, snapshot, changed_by, changed_at)
+    VALUES (
+        TG_ARGV[0], OLD.id, OLD.version, to_jsonb(OLD),
+        COALESCE(OLD.updated_by, OLD.created_by), OLD.updated_at
+    )
+    ON CONFLICT (entity_type, entity_id, version) DO NOTHING;
+
+    DELETE FROM ui_entity_versions
+    WHERE entity_type = TG_ARGV[0]
+      AND entity_id = OLD.id
+      AND version <= (
+          SELECT version FROM ui_entity_versions
+          WHERE entity_type = TG_ARGV[0] AND entity_id = OLD.id
+          ORDER BY version DESC
+          OFFSET 50 LIMIT 1
+      );
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_ui_pages_versions ON ui_pages;
+CREATE TRIGGER trg_ui_pages_versions
+BEFORE UPDATE ON ui_pages
+FOR EACH ROW EXECUTE FUNCTION record_ui_entity_version('page');
+
+DROP TRIGGER IF EXISTS trg_ui_buttons_versions ON ui_buttons;
+CREATE TRIGGER trg_ui_buttons_versions
+BEFORE UPDATE ON ui_buttons
+FOR EACH ROW EXECUTE FUNCTION record_ui_entity_version('button');
+
+DROP TRIGGER IF EXISTS trg_ui_fields_versions ON ui_fields;
+CREATE TRIGGER trg_ui_fields_versions
+BEFORE UPDATE ON ui_fields
+FOR EACH ROW EXECUTE FUNCTION record_ui_entity_version('field');
+
+DROP TRIGGER IF EXISTS trg_ui_api_endpoints_versions ON ui_api_endpoints;
+CREATE TRIGGER trg_ui_api_endpoints_versions
+BEFORE UPDATE ON ui_api_endpoints
+FOR EACH ROW EXECUTE FUNCTION record_ui_entity_version('api');
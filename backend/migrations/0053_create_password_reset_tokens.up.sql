@@ -0,0 +1,27 @@
+-- Migration: Create password_reset_tokens table
+-- Description: Single-use, short-lived tokens for the self-service password reset flow
+-- Related Module: src/infrastructure/password_reset.rs (PasswordResetService)
+--
+-- Tables Created:
+--   - password_reset_tokens
+--
+-- Indexes Created:
+--   - password_reset_tokens_token_hash_key (unique, on token_hash)
+--   - idx_password_reset_tokens_user_id (B-tree, on user_id)
+--
+-- Only the SHA-256 of a token is stored. A token is consumed when it is used, when it
+-- is presented after expiry, or when a newer reset is requested for the same user;
+-- consumed rows stay for the audit trail.
+
+CREATE TABLE IF NOT EXISTS password_reset_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ,
+    requested_ip TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT password_reset_tokens_token_hash_key UNIQUE (token_hash)
+);
+
+CREATE INDEX IF NOT EXISTS idx_password_reset_tokens_user_id ON password_reset_tokens(user_id);
@@ -0,0 +1,2 @@
+-- Rollback: Drop email change requests
+DROP TABLE IF EXISTS email_change_requests;
@@ -0,0 +1,31 @@
+-- Migration: Create email_change_requests table
+-- Description: Pending email changes, applied only once the new address is verified
+-- Related Module: src/infrastructure/email_change.rs (EmailChangeService)
+--
+-- Tables Created:
+--   - email_change_requests
+--
+-- Indexes Created:
+--   - email_change_requests_token_hash_key (unique, on token_hash)
+--   - idx_email_change_requests_user_id (B-tree, on user_id)
+--   - idx_email_change_requests_pending_email (B-tree, on LOWER(new_email), pending rows only)
+--
+-- The user's current email stays in effect until the token sent to new_email is
+-- confirmed. Only the SHA-256 of the token is stored. A request is consumed when
+-- confirmed, when presented after expiry, or when superseded by a newer request of
+-- the same user or by another user claiming the address first.
+
+CREATE TABLE IF NOT EXISTS email_change_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    new_email VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(64) NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT email_change_requests_token_hash_key UNIQUE (token_hash)
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_change_requests_user_id ON email_change_requests(user_id);
+CREATE INDEX IF NOT EXISTS idx_email_change_requests_pending_email
+    ON email_change_requests(LOWER(new_email)) WHERE consumed_at IS NULL;
@@ -0,0 +1,2 @@
+-- Rollback: Drop vault token denylist
+DROP TABLE IF EXISTS vault_token_denylist;
@@ -0,0 +1,22 @@
+-- Migration: Create vault_token_denylist table
+-- Description: Revoked signed vault tokens, until they would have expired anyway
+-- Related Module: rustyvault-service/src/modules/auth/signed_token.rs (TokenDenylist)
+--
+-- Tables Created:
+--   - vault_token_denylist
+--
+-- Indexes Created:
+--   - idx_vault_token_denylist_expires_at (B-tree, on expires_at)
+--
+-- Signed tokens are validated from their own claims without reading vault_tokens,
+-- so deleting the row is not enough to revoke one. Each replica reloads this table
+-- periodically and refuses the listed tokens. Rows are reaped once expires_at has
+-- passed, when the token's own expiry rejects it.
+
+CREATE TABLE IF NOT EXISTS vault_token_denylist (
+    token_id UUID PRIMARY KEY,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_vault_token_denylist_expires_at ON vault_token_denylist(expires_at);
@@ -0,0 +1,2 @@
+-- Rollback: Drop vault tokens listing index
+DROP INDEX IF EXISTS idx_vault_tokens_created_id;
@@ -0,0 +1,11 @@
+-- Migration: Add vault_tokens listing index
+-- Description: Keyset pagination for the admin token listing
+-- Related Module: rustyvault-service/src/modules/auth/token.rs (TokenStore::list_tokens)
+--
+-- Indexes Created:
+--   - idx_vault_tokens_created_id (B-tree, on created_at, id)
+--
+-- Pages are ordered by (created_at, id) and continue after the last row of the
+-- previous page, so each page is a range scan on this index.
+
+CREATE INDEX IF NOT EXISTS idx_vault_tokens_created_id ON vault_tokens(created_at, id);
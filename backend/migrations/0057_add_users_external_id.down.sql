@@ -0,0 +1,3 @@
+-- Rollback: Drop users.external_id and its index
+DROP INDEX IF EXISTS idx_users_external_id;
+ALTER TABLE users DROP COLUMN IF EXISTS external_id;
@@ -0,0 +1,16 @@
+-- Migration: Add external_id to users
+-- Description: Identity of a user in an external provisioning source (SCIM, LDAP)
+-- Related Entity: src/domain/entities/user.rs (User)
+--
+-- Columns Added:
+--   - users.external_id
+--
+-- Indexes Created:
+--   - idx_users_external_id (B-tree, unique, on external_id where set)
+--
+-- Provisioning re-sends users it has already created; the unique index makes
+-- a concurrent duplicate insert fail instead of creating a second account.
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS external_id VARCHAR(255);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_external_id ON users(external_id) WHERE external_id IS NOT NULL;
@@ -0,0 +1,16 @@
+-- Migration: Scrub password reset tokens from stored events
+-- Description: PasswordResetRequested no longer carries the single-use token;
+--              remove it from events already written
+-- Related Module: src/infrastructure/password_reset.rs (PasswordResetService)
+--
+-- The token now goes straight to the mail relay. Copies left in the outbox
+-- and webhook deliveries would let anyone with read access to the database
+-- complete a pending reset, so they are removed along with the address.
+
+UPDATE event_outbox
+SET payload = payload - 'token' - 'email'
+WHERE event_type = 'PasswordResetRequested';
+
+UPDATE webhook_deliveries
+SET payload = jsonb_set(payload, '{event}', (payload -> 'event') - 'token' - 'email')
+WHERE event_type = 'PasswordResetRequested' AND payload ? 'event';
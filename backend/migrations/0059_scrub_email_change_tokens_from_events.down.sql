@@ -0,0 +1,2 @@
+-- Rollback: nothing to restore; scrubbed tokens are gone for good
+SELECT 1;
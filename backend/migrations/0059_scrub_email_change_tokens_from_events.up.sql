@@ -0,0 +1,15 @@
+-- Migration: Scrub email change tokens from stored events
+-- Description: EmailChangeRequested no longer carries the verification token;
+--              remove it from events already written
+-- Related Module: src/infrastructure/email_change.rs (EmailChangeService)
+--
+-- With a stored token anyone able to read the database could confirm a
+-- pending change to an address they control. The addresses go too.
+
+UPDATE event_outbox
+SET payload = payload - 'token' - 'current_email' - 'new_email'
+WHERE event_type = 'EmailChangeRequested';
+
+UPDATE webhook_deliveries
+SET payload = jsonb_set(payload, '{event}', (payload -> 'event') - 'token' - 'current_email' - 'new_email')
+WHERE event_type = 'EmailChangeRequested' AND payload ? 'event';
@@ -0,0 +1,96 @@
+//! ACL evaluation for a token holding many large policies
+//!
+//! Builds 50 policies of 100 rules each, a mix of exact, prefix, segment
+//! wildcard and glob rules spread over many mounts, and compares the indexed
+//! lookup against a scan of every wildcard and glob rule.
+//!
+//! Run with `cargo bench -p rustyvault-service --bench acl --features bench`.
+
+use std::hint::black_box;
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rustyvault_service::logical::{Operation, Request};
+use rustyvault_service::modules::policy::acl::ACL;
+use rustyvault_service::modules::policy::Policy;
+
+const POLICIES: usize = 50;
+const RULES_PER_POLICY: usize = 100;
+
+fn rule(policy: usize, rule: usize) -> String {
+    let mount = format!("mount{}", (policy * RULES_PER_POLICY + rule) % 200);
+    let pattern = match rule % 5 {
+        0 => format!("{}/app{}/config", mount, rule),
+        1 => format!("{}/app{}/*", mount, rule),
+        2 => format!("{}/+/svc{}", mount, rule),
+        3 => format!("{}/**/svc{}/audit", mount, rule),
+        _ => format!("{}/team-{}*/config", mount, rule),
+    };
+    let capability = if rule % 7 == 0 { "deny" } else { "read" };
+    format!(r#""{}": {{"capabilities": ["{}", "list"]}}"#, pattern, capability)
+}
+
+fn policies() -> Vec<Arc<Policy>> {
+    (0..POLICIES)
+        .map(|p| {
+            let rules: Vec<String> = (0..RULES_PER_POLICY).map(|r| rule(p, r)).collect();
+            let mut policy = Policy::from_json(&format!(r#"{{"path": {{{}}}}}"#, rules.join(", "))).unwrap();
+            policy.name = format!("policy-{}", p);
+            Arc::new(policy)
+        })
+        .collect()
+}
+
+fn requests() -> Vec<Request> {
+    [
+        "mount3/app3/config",
+        "mount42/app42/secret/key",
+        "mount17/anything/svc17",
+        "mount88/a/b/c/svc88/audit",
+        "mount9/team-9x/config",
+        "mount150/unmatched/path/here",
+        "nomount/at/all",
+    ]
+    .into_iter()
+    .map(|path| Request {
+        path: path.to_string(),
+        operation: Operation::Read,
+        ..Default::default()
+    })
+    .collect()
+}
+
+fn bench_acl(c: &mut Criterion) {
+    let policies = policies();
+    let requests = requests();
+    let acl = ACL::new(&policies).unwrap();
+
+    // The index must not change any decision
+    for req in &requests {
+        let indexed = acl.allow_operation(req, false).unwrap();
+        let scanned = acl.allow_operation_unindexed(req, false).unwrap();
+        assert_eq!(indexed.capabilities_bitmap, scanned.capabilities_bitmap, "{}", req.path);
+    }
+
+    let mut group = c.benchmark_group("acl_allow_operation");
+    group.bench_function("indexed", |b| {
+        b.iter(|| {
+            for req in &requests {
+                black_box(acl.allow_operation(black_box(req), false).unwrap());
+            }
+        })
+    });
+    group.bench_function("full_scan", |b| {
+        b.iter(|| {
+            for req in &requests {
+                black_box(acl.allow_operation_unindexed(black_box(req), false).unwrap());
+            }
+        })
+    });
+    group.finish();
+
+    c.bench_function("acl_build", |b| b.iter(|| ACL::new(black_box(&policies)).unwrap()));
+}
+
+criterion_group!(benches, bench_acl);
+criterion_main!(benches);
@@ -0,0 +1,79 @@
+//! File audit device
+//!
+//! Appends one JSON object per line. Each entry is synced to disk before the
+//! write is reported as successful, so a blocking broker never serves a
+//! request whose record a crash could still lose.
+
+use std::path::Path;
+use async_trait::async_trait;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use crate::audit::{AuditDevice, AuditEntry};
+use crate::errors::VaultResult;
+
+pub struct FileAuditDevice {
+    file: Mutex<File>,
+}
+
+impl FileAuditDevice {
+    /// Open (or create) the audit log for appending
+    pub async fn open(path: impl AsRef<Path>) -> VaultResult<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path).await?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+#[async_trait]
+impl AuditDevice for FileAuditDevice {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    async fn log(&self, entry: &AuditEntry) -> VaultResult<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        let mut file = self.file.lock().await;
+        file.write_all(&line).await?;
+        file.flush().await?;
+        file.sync_data().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditBroker;
+    use crate::logical::Request;
+
+    #[tokio::test]
+    async fn test_appends_json_lines() {
+        let dir = std::env::temp_dir().join(format!("vault-audit-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("audit.log");
+        let device = std::sync::Arc::new(FileAuditDevice::open(&path).await.unwrap());
+        let broker = AuditBroker::new(b"key".to_vec(), true).with_device(device.clone());
+
+        let req = Request::new_read_request("secret/a");
+        broker.log_request(None, &req).await.unwrap();
+        broker.log_response(None, &req, &Ok(None)).await.unwrap();
+
+        let contents = tokio::fs::read_to_string(&path).await.unwrap();
+        let lines: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["type"], "response");
+        assert_eq!(lines[1]["request"]["id"], lines[0]["request"]["id"]);
+
+        tokio::fs::remove_dir_all(dir).await.unwrap();
+    }
+}
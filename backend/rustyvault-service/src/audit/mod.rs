@@ -0,0 +1,389 @@
+//! Audit devices
+//!
+//! Every logical request and its response is recorded to the configured audit
+//! devices. Request and response data are HMAC'd with the audit key (see
+//! `shared::shared::masking::hmac_field`) so entries can be correlated with a
+//! known value without the log ever containing the secret itself.
+//!
+//! Fields matched by the broker's [`Redactor`] (the built-in sensitive set
+//! plus `REDACT_FIELDS`) are replaced by `[REDACTED]` instead. An HMAC of a
+//! low-entropy value such as a date of birth can be reversed by hashing
+//! every candidate, so PII like that should be redacted, not hashed.
+//!
+//! In blocking mode a failed audit write fails the request, so a secret is
+//! never served without a matching audit record.
+//!
+//! Logical requests are audited by the handlers that make them. Every other
+//! endpoint (auth, sys, policy) is audited from its HTTP request and
+//! response by [`crate::http::middleware::audit_middleware`].
+
+pub mod file;
+
+pub use file::FileAuditDevice;
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use shared::shared::masking::{hmac_field, hmac_json, Redactor};
+use crate::errors::{VaultError, VaultResult};
+use crate::http::middleware::auth_middleware::AuthInfo;
+use crate::logical::{Operation, Request, Response};
+
+/// A sink for audit entries
+#[async_trait]
+pub trait AuditDevice: Send + Sync {
+    /// Name used in logs
+    fn name(&self) -> &str;
+
+    /// Durably record one entry
+    async fn log(&self, entry: &AuditEntry) -> VaultResult<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditEntryType {
+    Request,
+    Response,
+}
+
+/// Caller identity as recorded in the audit log
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AuditAuth {
+    /// HMAC of the client token
+    pub client_token: Option<String>,
+    pub token_id: Option<String>,
+    pub display_name: Option<String>,
+    pub policies: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRequest {
+    pub id: String,
+    pub operation: String,
+    pub path: String,
+    pub mount_point: String,
+    /// HMAC'd request data
+    pub data: Option<Value>,
+    /// The caller's request ID, for correlating with other services' logs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_address: Option<String>,
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    #[serde(rename = "type")]
+    pub entry_type: AuditEntryType,
+    pub time: DateTime<Utc>,
+    pub auth: AuditAuth,
+    pub request: AuditRequest,
+    /// HMAC'd response data (response entries only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Fans audit entries out to every enabled device
+pub struct AuditBroker {
+    devices: Vec<Arc<dyn AuditDevice>>,
+    hmac_key: Vec<u8>,
+    /// Fields dropped from request and response data before hashing the rest
+    redactor: Redactor,
+    /// Fail requests whose audit record could not be written
+    blocking: bool,
+}
+
+impl AuditBroker {
+    pub fn new(hmac_key: Vec<u8>, blocking: bool) -> Self {
+        Self {
+            devices: Vec::new(),
+            hmac_key,
+            redactor: Redactor::default(),
+            blocking,
+        }
+    }
+
+    /// Redact the fields `redactor` matches instead of the built-in set alone
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Enable an audit device
+    pub fn with_device(mut self, device: Arc<dyn AuditDevice>) -> Self {
+        self.devices.push(device);
+        self
+    }
+
+    /// HMAC a value with the audit key, for correlating log entries
+    pub fn hash(&self, value: &str) -> String {
+        hmac_field(&self.hmac_key, value)
+    }
+
+    /// Request or response data as recorded: HMAC'd, with redacted fields
+    /// replaced outright
+    fn scrub(&self, data: &serde_json::Map<String, Value>) -> Value {
+        self.redactor.redact_json(&hmac_json(&self.hmac_key, &Value::Object(data.clone())))
+    }
+
+    /// Record an incoming request before it is handled
+    pub async fn log_request(&self, auth: Option<&AuthInfo>, req: &Request) -> VaultResult<()> {
+        let entry = self.entry(AuditEntryType::Request, auth, req);
+        self.dispatch(&entry).await
+    }
+
+    /// Record the outcome of a request before it is returned to the caller
+    pub async fn log_response(
+        &self,
+        auth: Option<&AuthInfo>,
+        req: &Request,
+        result: &VaultResult<Option<Response>>,
+    ) -> VaultResult<()> {
+        let mut entry = self.entry(AuditEntryType::Response, auth, req);
+        match result {
+            Ok(Some(resp)) => {
+                entry.response = resp.data.as_ref().map(|data| self.scrub(data));
+            }
+            Ok(None) => entry.error = Some("not found".to_string()),
+            Err(e) => entry.error = Some(e.to_string()),
+        }
+        self.dispatch(&entry).await
+    }
+
+    /// Record the outcome of a request served outside the logical layer
+    ///
+    /// `body` is the JSON response body; on a failed request its `error` or
+    /// `errors` become the entry's error.
+    pub async fn log_http_response(
+        &self,
+        auth: Option<&AuthInfo>,
+        req: &Request,
+        status: u16,
+        body: Option<&serde_json::Map<String, Value>>,
+    ) -> VaultResult<()> {
+        let mut entry = self.entry(AuditEntryType::Response, auth, req);
+        if (200..400).contains(&status) {
+            entry.response = body.map(|data| self.scrub(data));
+        } else {
+            let message = body.and_then(|body| match (body.get("error"), body.get("errors")) {
+                (Some(Value::String(error)), _) => Some(error.clone()),
+                (_, Some(Value::Array(errors))) => {
+                    Some(errors.iter().filter_map(Value::as_str).collect::<Vec<_>>().join("; "))
+                }
+                _ => None,
+            });
+            entry.error = Some(match message {
+                Some(message) => format!("{}: {}", status, message),
+                None => status.to_string(),
+            });
+        }
+        self.dispatch(&entry).await
+    }
+
+    fn entry(&self, entry_type: AuditEntryType, auth: Option<&AuthInfo>, req: &Request) -> AuditEntry {
+        let auth = auth
+            .map(|a| AuditAuth {
+                client_token: Some(self.hash(&a.raw_token)),
+                token_id: Some(a.token.id.to_string()),
+                display_name: Some(a.token.display_name.clone()),
+                policies: a.token.policies.clone(),
+            })
+            .unwrap_or_default();
+
+        AuditEntry {
+            entry_type,
+            time: Utc::now(),
+            auth,
+            request: AuditRequest {
+                id: req.id.clone(),
+                operation: operation_name(req.operation).to_string(),
+                path: req.path.clone(),
+                mount_point: req.mount_point.clone(),
+                data: req.data.as_ref().map(|data| self.scrub(data)),
+                request_id: req.context.as_ref().map(|c| c.request_id.clone()),
+                remote_address: req.context.as_ref().and_then(|c| c.client_ip).map(|ip| ip.to_string()),
+            },
+            response: None,
+            error: None,
+        }
+    }
+
+    /// Write to every device
+    ///
+    /// In blocking mode this fails if any device failed; otherwise failures
+    /// are only logged.
+    async fn dispatch(&self, entry: &AuditEntry) -> VaultResult<()> {
+        let mut failed = Vec::new();
+        for device in &self.devices {
+            if let Err(e) = device.log(entry).await {
+                tracing::error!("Audit device {} failed: {}", device.name(), e);
+                failed.push(device.name().to_string());
+            }
+        }
+
+        if self.blocking && !failed.is_empty() {
+            return Err(VaultError::Vault(format!(
+                "failed to write audit record to: {}",
+                failed.join(", ")
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn operation_name(operation: Operation) -> &'static str {
+    match operation {
+        Operation::Read => "read",
+        Operation::Write => "write",
+        Operation::Delete => "delete",
+        Operation::List => "list",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use serde_json::{json, Map};
+    use shared::shared::masking::REDACTED;
+
+    #[derive(Default)]
+    struct MemoryDevice {
+        entries: Mutex<Vec<Value>>,
+    }
+
+    #[async_trait]
+    impl AuditDevice for MemoryDevice {
+        fn name(&self) -> &str {
+            "memory"
+        }
+
+        async fn log(&self, entry: &AuditEntry) -> VaultResult<()> {
+            self.entries.lock().unwrap().push(serde_json::to_value(entry)?);
+            Ok(())
+        }
+    }
+
+    struct BrokenDevice;
+
+    #[async_trait]
+    impl AuditDevice for BrokenDevice {
+        fn name(&self) -> &str {
+            "broken"
+        }
+
+        async fn log(&self, _entry: &AuditEntry) -> VaultResult<()> {
+            Err(VaultError::Storage("disk full".to_string()))
+        }
+    }
+
+    fn write_request() -> Request {
+        let mut data = Map::new();
+        data.insert("username".to_string(), json!("ada"));
+        data.insert("password".to_string(), json!("hunter2"));
+        Request::new_write_request("secret/db", Some(data))
+    }
+
+    #[tokio::test]
+    async fn test_entries_hash_sensitive_data() {
+        let device = Arc::new(MemoryDevice::default());
+        let broker = AuditBroker::new(b"audit-key".to_vec(), true).with_device(device.clone());
+        let req = write_request();
+
+        broker.log_request(None, &req).await.unwrap();
+        let mut resp = Response::new();
+        resp.data = req.data.clone();
+        broker.log_response(None, &req, &Ok(Some(resp))).await.unwrap();
+
+        let entries = device.entries.lock().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["type"], "request");
+        assert_eq!(entries[0]["request"]["path"], "secret/db");
+        assert_eq!(entries[0]["request"]["operation"], "write");
+        let hashed = json!(broker.hash("ada"));
+        assert_eq!(entries[0]["request"]["data"]["username"], hashed);
+        assert_eq!(entries[1]["response"]["username"], hashed);
+        // Built-in sensitive fields are not even hashed
+        assert_eq!(entries[0]["request"]["data"]["password"], json!(REDACTED));
+        assert_eq!(entries[1]["response"]["password"], json!(REDACTED));
+        assert!(!serde_json::to_string(&*entries).unwrap().contains("hunter2"));
+        assert!(entries[0]["request"].get("remote_address").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_entries_carry_the_request_context() {
+        let device = Arc::new(MemoryDevice::default());
+        let broker = AuditBroker::new(b"audit-key".to_vec(), true).with_device(device.clone());
+        let mut req = write_request();
+        req.context = Some(crate::logical::RequestContext {
+            identity: "svc-billing".to_string(),
+            token_accessor: "accessor.1".to_string(),
+            request_id: "req-42".to_string(),
+            client_ip: Some("10.0.0.7".parse().unwrap()),
+            namespace: None,
+        });
+
+        broker.log_request(None, &req).await.unwrap();
+        let entries = device.entries.lock().unwrap();
+        assert_eq!(entries[0]["request"]["request_id"], "req-42");
+        assert_eq!(entries[0]["request"]["remote_address"], "10.0.0.7");
+    }
+
+    #[tokio::test]
+    async fn test_configured_fields_are_redacted() {
+        let device = Arc::new(MemoryDevice::default());
+        let config = shared::config::RedactionConfig { fields: vec!["*_mrn".to_string()] };
+        let broker = AuditBroker::new(b"audit-key".to_vec(), true)
+            .with_redactor(Redactor::new(&config))
+            .with_device(device.clone());
+        let mut req = write_request();
+        req.data.as_mut().unwrap().insert("patient_mrn".to_string(), json!("MRN-1"));
+
+        broker.log_request(None, &req).await.unwrap();
+        let entries = device.entries.lock().unwrap();
+        assert_eq!(entries[0]["request"]["data"]["patient_mrn"], json!(REDACTED));
+        assert_eq!(entries[0]["request"]["data"]["username"], json!(broker.hash("ada")));
+    }
+
+    #[tokio::test]
+    async fn test_http_responses_record_errors() {
+        let device = Arc::new(MemoryDevice::default());
+        let broker = AuditBroker::new(b"audit-key".to_vec(), true).with_device(device.clone());
+        let req = write_request();
+
+        let body = json!({"name": "ops"});
+        broker.log_http_response(None, &req, 200, body.as_object()).await.unwrap();
+        let body = json!({"errors": ["policy name is invalid"]});
+        broker.log_http_response(None, &req, 400, body.as_object()).await.unwrap();
+        broker.log_http_response(None, &req, 502, None).await.unwrap();
+
+        let entries = device.entries.lock().unwrap();
+        assert_eq!(entries[0]["response"]["name"], json!(broker.hash("ops")));
+        assert!(entries[0].get("error").is_none());
+        assert_eq!(entries[1]["error"], "400: policy name is invalid");
+        assert!(entries[1].get("response").is_none());
+        assert_eq!(entries[2]["error"], "502");
+    }
+
+    #[tokio::test]
+    async fn test_blocking_mode_fails_on_device_error() {
+        let broker = AuditBroker::new(b"audit-key".to_vec(), true).with_device(Arc::new(BrokenDevice));
+        assert!(broker.log_request(None, &write_request()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_non_blocking_mode_tolerates_device_error() {
+        let device = Arc::new(MemoryDevice::default());
+        let broker = AuditBroker::new(b"audit-key".to_vec(), false)
+            .with_device(Arc::new(BrokenDevice))
+            .with_device(device.clone());
+
+        broker.log_request(None, &write_request()).await.unwrap();
+        assert_eq!(device.entries.lock().unwrap().len(), 1);
+    }
+}
@@ -4,5 +4,5 @@
 
 mod vault_config;
 
-pub use vault_config::VaultSettings;
+pub use vault_config::{VaultSettings, HaConfig};
 
@@ -17,6 +17,18 @@ pub struct VaultSettings {
     pub seal: SealConfig,
     pub storage: StorageConfig,
     pub mounts: MountsConfig,
+    pub access: AccessConfig,
+    pub reaper: ReaperConfig,
+    pub audit: AuditConfig,
+    pub ha: HaConfig,
+    pub tokens: TokenConfig,
+    pub policy_limits: crate::modules::policy::policy::PolicyLimits,
+    /// Start with logical writes rejected; toggled at runtime through `sys/read-only`
+    pub read_only: bool,
+    /// Fail `sys/health` when the crypto self-test does
+    pub health_crypto_selftest: bool,
+    /// Seconds a response recorded under an `Idempotency-Key` is replayed for
+    pub idempotency_ttl_secs: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +36,7 @@ pub struct ServerConfig {
     pub host: String,
     pub port: u16,
     pub cors_allowed_origins: Vec<String>,
+    pub tls: shared::config::TlsConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,12 +50,22 @@ pub struct DatabaseConfig {
 pub struct BarrierConfig {
     pub algorithm: String,
     pub key_length: usize,
+    /// KDF used to derive the key wrapping barrier-init from the unseal key
+    pub kdf: shared::infrastructure::encryption::KdfParams,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SealConfig {
     pub secret_shares: u8,
     pub secret_threshold: u8,
+    /// Distinct operators that must confirm a manual seal; 0 or 1 disables the guard
+    pub seal_quorum: u8,
+    /// Seconds a partially confirmed seal stays open before its progress is discarded
+    pub seal_quorum_timeout_secs: u64,
+    /// Seconds a sys/generate-root attempt stays open before its shares are discarded
+    pub generate_root_timeout_secs: u64,
+    /// TTL of root tokens minted by sys/generate-root; 0 never expires
+    pub generated_root_ttl_secs: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +78,54 @@ pub struct StorageConfig {
 pub struct MountsConfig {
     pub default_lease_ttl: u64,
     pub max_lease_ttl: u64,
+    /// Random ± percentage applied to issued token and lease TTLs (at most 50)
+    pub lease_ttl_jitter_percent: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessConfig {
+    /// How denied requests are reported to callers
+    pub denial_policy: crate::logical::DenialPolicy,
+    /// Confine tokens carrying an `org_id` to their organization's namespace
+    pub namespace_isolation: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReaperConfig {
+    /// Seconds between expired-entry reaper runs
+    pub interval_secs: u64,
+    /// Maximum entries removed per batch
+    pub batch_size: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditConfig {
+    /// Append audit entries to this file; auditing is disabled when unset
+    pub file_path: Option<String>,
+    /// Fail requests whose audit record cannot be written
+    pub blocking: bool,
+    /// Key used to HMAC audited values; random per process when unset
+    pub hmac_key: Option<String>,
+    /// Fields redacted outright instead of HMAC'd, beyond the built-in set
+    pub redaction: shared::config::RedactionConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenConfig {
+    /// Shape of newly issued tokens; opaque unless configured otherwise
+    pub format: crate::modules::auth::TokenFormat,
+    /// Key signed tokens are HMAC'd with; random per process when unset
+    pub signing_key: Option<String>,
+    /// Seconds between reloads of the revoked signed token denylist
+    pub denylist_refresh_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HaConfig {
+    /// Seconds a node stays standby after `sys/step-down`
+    pub step_down_hold_secs: u64,
+    /// Seconds `sys/step-down` waits for in-flight requests to finish
+    pub drain_timeout_secs: u64,
 }
 
 impl VaultSettings {
@@ -70,6 +141,7 @@ impl VaultSettings {
                 .split(',')
                 .map(|s| s.trim().to_string())
                 .collect(),
+            tls: shared::config::TlsConfig::from_env("VAULT_"),
         };
 
         let database = DatabaseConfig {
@@ -96,12 +168,18 @@ impl VaultSettings {
                 format!("Failed to load deployment config: {}", e)
             ))))?;
 
+        let kdf_algorithm: shared::infrastructure::encryption::KdfAlgorithm = env::var("VAULT_BARRIER_KDF")
+            .unwrap_or_else(|_| "argon2id".to_string())
+            .parse()
+            .map_err(|e: String| config::ConfigError::Message(e))?;
+
         let barrier = BarrierConfig {
             algorithm: env::var("VAULT_BARRIER_ALGORITHM").unwrap_or_else(|_| "aes-gcm".to_string()),
             key_length: env::var("VAULT_BARRIER_KEY_LENGTH")
                 .unwrap_or_else(|_| "32".to_string())
                 .parse()
                 .unwrap_or(32),
+            kdf: shared::infrastructure::encryption::KdfParams::recommended(kdf_algorithm),
         };
 
         let seal = SealConfig {
@@ -113,6 +191,22 @@ impl VaultSettings {
                 .unwrap_or_else(|_| "3".to_string())
                 .parse()
                 .unwrap_or(3),
+            seal_quorum: env::var("VAULT_SEAL_QUORUM")
+                .unwrap_or_else(|_| "1".to_string())
+                .parse()
+                .unwrap_or(1),
+            seal_quorum_timeout_secs: env::var("VAULT_SEAL_QUORUM_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "600".to_string())
+                .parse()
+                .unwrap_or(600),
+            generate_root_timeout_secs: env::var("VAULT_GENERATE_ROOT_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "600".to_string())
+                .parse()
+                .unwrap_or(600),
+            generated_root_ttl_secs: env::var("VAULT_GENERATED_ROOT_TTL_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
         };
 
         let storage = StorageConfig {
@@ -129,6 +223,92 @@ impl VaultSettings {
                 .unwrap_or_else(|_| "768h".to_string())
                 .parse()
                 .unwrap_or(2764800),
+            lease_ttl_jitter_percent: env::var("VAULT_LEASE_TTL_JITTER_PERCENT")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+        };
+
+        let mut denial_policy = crate::logical::DenialPolicy::default();
+        if let Ok(mode) = env::var("VAULT_DENIAL_MODE") {
+            denial_policy.mode = mode.parse()
+                .map_err(|e: String| config::ConfigError::Message(e))?;
+        }
+        if let Ok(policies) = env::var("VAULT_DENIAL_REVEAL_POLICIES") {
+            denial_policy.reveal_policies = policies
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        let access = AccessConfig {
+            denial_policy,
+            namespace_isolation: env::var("VAULT_NAMESPACE_ISOLATION")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+        };
+
+        let reaper = ReaperConfig {
+            interval_secs: env::var("VAULT_REAPER_INTERVAL_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            batch_size: env::var("VAULT_REAPER_BATCH_SIZE")
+                .unwrap_or_else(|_| "500".to_string())
+                .parse()
+                .unwrap_or(500),
+        };
+
+        let audit = AuditConfig {
+            file_path: env::var("VAULT_AUDIT_FILE_PATH").ok().filter(|p| !p.is_empty()),
+            blocking: env::var("VAULT_AUDIT_BLOCKING")
+                .map(|v| v != "false")
+                .unwrap_or(true),
+            hmac_key: env::var("VAULT_AUDIT_HMAC_KEY").ok().filter(|k| !k.is_empty()),
+            redaction: shared::config::RedactionConfig::from_env(),
+        };
+
+        let ha = HaConfig {
+            step_down_hold_secs: env::var("VAULT_STEP_DOWN_HOLD_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            drain_timeout_secs: env::var("VAULT_DRAIN_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+        };
+
+        let tokens = TokenConfig {
+            format: match env::var("VAULT_TOKEN_FORMAT") {
+                Ok(format) => format.parse().map_err(config::ConfigError::Message)?,
+                Err(_) => crate::modules::auth::TokenFormat::default(),
+            },
+            signing_key: env::var("VAULT_TOKEN_SIGNING_KEY").ok().filter(|k| !k.is_empty()),
+            denylist_refresh_secs: env::var("VAULT_TOKEN_DENYLIST_REFRESH_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+        };
+
+        let default_limits = crate::modules::policy::policy::PolicyLimits::default();
+        let policy_limits = crate::modules::policy::policy::PolicyLimits {
+            max_bytes: env::var("VAULT_POLICY_MAX_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default_limits.max_bytes),
+            max_paths: env::var("VAULT_POLICY_MAX_PATHS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default_limits.max_paths),
+            max_parameter_values: env::var("VAULT_POLICY_MAX_PARAMETER_VALUES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default_limits.max_parameter_values),
+            max_value_depth: env::var("VAULT_POLICY_MAX_VALUE_DEPTH")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default_limits.max_value_depth),
         };
 
         Ok(VaultSettings {
@@ -140,6 +320,22 @@ impl VaultSettings {
             seal,
             storage,
             mounts,
+            access,
+            reaper,
+            audit,
+            ha,
+            tokens,
+            policy_limits,
+            read_only: env::var("VAULT_READ_ONLY")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            health_crypto_selftest: env::var("VAULT_HEALTH_CRYPTO_SELFTEST")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            idempotency_ttl_secs: env::var("VAULT_IDEMPOTENCY_TTL_SECS")
+                .unwrap_or_else(|_| "86400".to_string())
+                .parse()
+                .unwrap_or(86400),
         })
     }
 }
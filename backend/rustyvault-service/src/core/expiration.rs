@@ -0,0 +1,247 @@
+//! Background reaping of expired entries
+//!
+//! Expired tokens (and other time-limited records) are rejected on lookup,
+//! but their rows remain in storage until removed. The reaper periodically
+//! asks every registered source to delete a bounded batch of expired
+//! entries. Sources must tolerate several reapers running concurrently.
+//! Ticks are skipped while the vault is sealed or standing by.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use shared::infrastructure::database::DistributedLock;
+use crate::core::VaultCore;
+use crate::errors::VaultResult;
+
+/// Advisory lock name shared by all reaper instances
+const REAPER_LOCK_KEY: &str = "rustyvault:expiration-reaper";
+
+/// A store whose expired entries can be reaped
+#[async_trait]
+pub trait ExpiredEntrySource: Send + Sync {
+    /// Name used in logs and status output
+    fn name(&self) -> &str;
+
+    /// Remove up to `batch_size` expired entries, returning how many were removed
+    async fn reap_expired(&self, batch_size: usize) -> VaultResult<u64>;
+}
+
+/// Observability snapshot of the reaper
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReaperStatus {
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_run_reaped: u64,
+    pub total_reaped: u64,
+    pub last_error: Option<String>,
+}
+
+/// Periodically removes expired entries from registered sources
+pub struct ExpirationReaper {
+    sources: Vec<Arc<dyn ExpiredEntrySource>>,
+    interval: Duration,
+    batch_size: usize,
+    /// Upper bound on batches per source in one run, to keep runs short
+    max_batches: usize,
+    /// Optional cross-replica lock so only one instance reaps at a time
+    lock: Option<DistributedLock>,
+    /// Vault whose seal and standby state gate each tick
+    vault: Option<Arc<VaultCore>>,
+    status: Mutex<ReaperStatus>,
+}
+
+impl ExpirationReaper {
+    pub fn new(interval: Duration, batch_size: usize) -> Self {
+        Self {
+            sources: Vec::new(),
+            interval,
+            batch_size: batch_size.max(1),
+            max_batches: 10,
+            lock: None,
+            vault: None,
+            status: Mutex::new(ReaperStatus::default()),
+        }
+    }
+
+    /// Register a source of expiring entries
+    pub fn with_source(mut self, source: Arc<dyn ExpiredEntrySource>) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Coordinate runs across replicas with a distributed lock
+    pub fn with_lock(mut self, lock: DistributedLock) -> Self {
+        self.lock = Some(lock);
+        self
+    }
+
+    /// Only reap while `vault` is unsealed and active
+    pub fn with_vault(mut self, vault: Arc<VaultCore>) -> Self {
+        self.vault = Some(vault);
+        self
+    }
+
+    /// Whether this tick should be skipped
+    fn paused(&self) -> bool {
+        self.vault.as_ref().is_some_and(|vault| vault.is_sealed() || vault.active.is_standby())
+    }
+
+    pub fn status(&self) -> ReaperStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Reap every source once, returning the number of entries removed
+    pub async fn run_once(&self) -> u64 {
+        let mut reaped = 0;
+        let mut last_error = None;
+
+        for source in &self.sources {
+            for _ in 0..self.max_batches {
+                match source.reap_expired(self.batch_size).await {
+                    Ok(count) => {
+                        reaped += count;
+                        if (count as usize) < self.batch_size {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to reap expired {}: {}", source.name(), e);
+                        last_error = Some(format!("{}: {}", source.name(), e));
+                        break;
+                    }
+                }
+            }
+        }
+
+        let mut status = self.status.lock().unwrap();
+        status.last_run_at = Some(Utc::now());
+        status.last_run_reaped = reaped;
+        status.total_reaped += reaped;
+        status.last_error = last_error;
+        reaped
+    }
+
+    /// Spawn the reaper loop on the current runtime
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if self.paused() {
+                    continue;
+                }
+                let reaped = match &self.lock {
+                    Some(lock) => match lock.try_with_lock(REAPER_LOCK_KEY, || self.run_once()).await {
+                        Ok(Some(reaped)) => reaped,
+                        // Another replica holds the lock and is reaping
+                        Ok(None) => continue,
+                        Err(e) => {
+                            tracing::warn!("Failed to acquire reaper lock: {}", e);
+                            continue;
+                        }
+                    },
+                    None => self.run_once().await,
+                };
+                if reaped > 0 {
+                    tracing::info!("Reaped {} expired entries", reaped);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use crate::errors::VaultError;
+
+    struct CountingSource {
+        remaining: AtomicU64,
+    }
+
+    #[async_trait]
+    impl ExpiredEntrySource for CountingSource {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        async fn reap_expired(&self, batch_size: usize) -> VaultResult<u64> {
+            let remaining = self.remaining.load(Ordering::SeqCst);
+            let reaped = remaining.min(batch_size as u64);
+            self.remaining.store(remaining - reaped, Ordering::SeqCst);
+            Ok(reaped)
+        }
+    }
+
+    struct FailingSource;
+
+    #[async_trait]
+    impl ExpiredEntrySource for FailingSource {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        async fn reap_expired(&self, _batch_size: usize) -> VaultResult<u64> {
+            Err(VaultError::Storage("unavailable".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_once_reaps_in_batches() {
+        let source = Arc::new(CountingSource { remaining: AtomicU64::new(25) });
+        let reaper = ExpirationReaper::new(Duration::from_secs(60), 10)
+            .with_source(source.clone());
+
+        assert_eq!(reaper.run_once().await, 25);
+        assert_eq!(source.remaining.load(Ordering::SeqCst), 0);
+
+        let status = reaper.status();
+        assert!(status.last_run_at.is_some());
+        assert_eq!(status.last_run_reaped, 25);
+        assert_eq!(status.total_reaped, 25);
+    }
+
+    #[tokio::test]
+    async fn test_run_once_bounds_batches_per_run() {
+        let source = Arc::new(CountingSource { remaining: AtomicU64::new(1000) });
+        let reaper = ExpirationReaper::new(Duration::from_secs(60), 10)
+            .with_source(source.clone());
+
+        assert_eq!(reaper.run_once().await, 100);
+        assert_eq!(source.remaining.load(Ordering::SeqCst), 900);
+    }
+
+    #[tokio::test]
+    async fn test_pauses_while_sealed_or_standing_by() {
+        use crate::storage::physical_inmem::InMemoryBackend;
+        use shared::infrastructure::encryption::KdfParams;
+
+        let vault = Arc::new(VaultCore::with_kdf(
+            Arc::new(InMemoryBackend::new()),
+            KdfParams::Argon2id { memory_kib: 64, iterations: 1, parallelism: 1 },
+        ));
+        let reaper = ExpirationReaper::new(Duration::from_secs(60), 10).with_vault(vault.clone());
+        assert!(reaper.paused());
+
+        let init = vault.init(&crate::core::SealConfig { secret_shares: 1, secret_threshold: 1 }).await.unwrap();
+        vault.unseal(&init.secret_shares[0]).await.unwrap();
+        assert!(!reaper.paused());
+
+        vault.active.step_down(Duration::from_secs(60), Duration::from_millis(10)).await;
+        assert!(reaper.paused());
+    }
+
+    #[tokio::test]
+    async fn test_failing_source_does_not_stop_others() {
+        let source = Arc::new(CountingSource { remaining: AtomicU64::new(3) });
+        let reaper = ExpirationReaper::new(Duration::from_secs(60), 10)
+            .with_source(Arc::new(FailingSource))
+            .with_source(source);
+
+        assert_eq!(reaper.run_once().await, 3);
+        assert!(reaper.status().last_error.unwrap().starts_with("failing"));
+    }
+}
@@ -0,0 +1,264 @@
+//! Emergency root token generation
+//!
+//! When every root and admin token is lost, `sys/generate-root` mints a new
+//! root token, like the flow of the same name in Vault. Unseal key holders
+//! are the authority: an initiator starts an attempt with a one-time pad and
+//! gets a nonce, then holders submit their shares quoting it. Once a
+//! threshold of shares has been collected they are checked against the key
+//! the vault is unsealed with (see `VaultCore::verify_unseal_shares`), and
+//! only then is the token minted. It is returned XORed with the pad, so
+//! only the initiator, who kept the pad, can read it.
+//!
+//! Shares are held in memory until the attempt completes, is cancelled or
+//! times out, and are zeroized when dropped.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use serde::Serialize;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use crate::errors::{VaultError, VaultResult};
+
+/// Progress of the generate-root attempt in progress
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GenerateRootProgress {
+    pub nonce: String,
+    /// Distinct shares submitted
+    pub progress: usize,
+    pub required: usize,
+    /// Seconds until the attempt is discarded
+    pub expires_in_secs: u64,
+}
+
+/// Outcome of one submitted share
+pub enum GenerateRootVote {
+    /// More shares are needed
+    Pending(GenerateRootProgress),
+    /// Threshold reached; the caller verifies the shares and mints the token
+    Reached(GenerateRootQuorum),
+}
+
+/// What a completed attempt hands over; the attempt itself is gone
+pub struct GenerateRootQuorum {
+    pub nonce: String,
+    pub shares: Vec<Zeroizing<Vec<u8>>>,
+    otp: Zeroizing<Vec<u8>>,
+}
+
+impl GenerateRootQuorum {
+    /// `token` XORed with the initiator's one-time pad, base64 encoded
+    pub fn encode_token(&self, token: &str) -> VaultResult<String> {
+        if token.len() != self.otp.len() {
+            return Err(VaultError::Vault("one-time pad does not match the token length".to_string()));
+        }
+        Ok(base64::engine::general_purpose::STANDARD.encode(xor(token.as_bytes(), &self.otp)))
+    }
+}
+
+struct GenerateRootAttempt {
+    nonce: String,
+    started: Instant,
+    otp: Zeroizing<Vec<u8>>,
+    required: usize,
+    shares: Vec<Zeroizing<Vec<u8>>>,
+}
+
+/// Tracks the single generate-root attempt
+pub struct GenerateRoot {
+    otp_length: usize,
+    timeout: Duration,
+    attempt: Mutex<Option<GenerateRootAttempt>>,
+}
+
+impl GenerateRoot {
+    /// Pads must be `otp_length` bytes, the length of a raw token
+    pub fn new(otp_length: usize, timeout: Duration) -> Self {
+        Self {
+            otp_length,
+            timeout,
+            attempt: Mutex::new(None),
+        }
+    }
+
+    pub fn otp_length(&self) -> usize {
+        self.otp_length
+    }
+
+    /// Start an attempt needing `required` shares
+    ///
+    /// `otp` is the base64 of `otp_length` random bytes kept by the
+    /// initiator. Only one attempt runs at a time; an abandoned one has to
+    /// be cancelled or time out first.
+    pub fn start(&self, otp: &str, required: u8) -> VaultResult<GenerateRootProgress> {
+        let otp = decode_otp(otp, self.otp_length)?;
+
+        let mut attempt = self.attempt.lock().unwrap();
+        self.discard_expired(&mut attempt);
+        if attempt.is_some() {
+            return Err(VaultError::Validation(
+                "a root token generation is already in progress; cancel it first".to_string(),
+            ));
+        }
+        let current = attempt.insert(GenerateRootAttempt {
+            nonce: Uuid::new_v4().to_string(),
+            started: Instant::now(),
+            otp,
+            required: usize::from(required.max(1)),
+            shares: Vec::new(),
+        });
+        Ok(self.progress_of(current))
+    }
+
+    /// Add an unseal key share to the attempt named by `nonce`
+    ///
+    /// A share submitted twice counts once.
+    pub fn submit(&self, nonce: &str, share: Vec<u8>) -> VaultResult<GenerateRootVote> {
+        let share = Zeroizing::new(share);
+        if share.is_empty() {
+            return Err(VaultError::Validation("empty unseal key share".to_string()));
+        }
+
+        let mut attempt = self.attempt.lock().unwrap();
+        self.discard_expired(&mut attempt);
+        let current = match attempt.as_mut() {
+            Some(current) if current.nonce == nonce => current,
+            _ => {
+                return Err(VaultError::Validation(
+                    "no root token generation in progress for that nonce".to_string(),
+                ));
+            }
+        };
+
+        if !current.shares.contains(&share) {
+            current.shares.push(share);
+        }
+        if current.shares.len() < current.required {
+            return Ok(GenerateRootVote::Pending(self.progress_of(current)));
+        }
+
+        // Taken out whatever the shares turn out to be worth: a failed
+        // verification starts over rather than mixing in later shares
+        let completed = attempt.take().expect("attempt checked above");
+        Ok(GenerateRootVote::Reached(GenerateRootQuorum {
+            nonce: completed.nonce,
+            shares: completed.shares,
+            otp: completed.otp,
+        }))
+    }
+
+    /// The attempt in progress, if any
+    pub fn progress(&self) -> Option<GenerateRootProgress> {
+        let mut attempt = self.attempt.lock().unwrap();
+        self.discard_expired(&mut attempt);
+        attempt.as_ref().map(|current| self.progress_of(current))
+    }
+
+    /// Abandon the attempt in progress, returning its nonce
+    pub fn cancel(&self) -> Option<String> {
+        self.attempt.lock().unwrap().take().map(|attempt| attempt.nonce)
+    }
+
+    fn discard_expired(&self, attempt: &mut Option<GenerateRootAttempt>) {
+        if attempt.as_ref().is_some_and(|current| current.started.elapsed() >= self.timeout) {
+            tracing::warn!(target: "security", "Root token generation timed out before reaching threshold");
+            *attempt = None;
+        }
+    }
+
+    fn progress_of(&self, attempt: &GenerateRootAttempt) -> GenerateRootProgress {
+        GenerateRootProgress {
+            nonce: attempt.nonce.clone(),
+            progress: attempt.shares.len(),
+            required: attempt.required,
+            expires_in_secs: self.timeout.saturating_sub(attempt.started.elapsed()).as_secs(),
+        }
+    }
+}
+
+fn decode_otp(otp: &str, expected_len: usize) -> VaultResult<Zeroizing<Vec<u8>>> {
+    let otp = Zeroizing::new(
+        base64::engine::general_purpose::STANDARD
+            .decode(otp)
+            .map_err(|_| VaultError::Validation("one-time pad is not valid base64".to_string()))?,
+    );
+    if otp.len() != expected_len {
+        return Err(VaultError::Validation(format!(
+            "one-time pad must be {} bytes",
+            expected_len
+        )));
+    }
+    Ok(otp)
+}
+
+fn xor(data: &[u8], pad: &[u8]) -> Vec<u8> {
+    data.iter().zip(pad).map(|(d, p)| d ^ p).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "hvs.abcdefghijklmnopqrstuvwxyz";
+
+    fn otp() -> String {
+        base64::engine::general_purpose::STANDARD.encode([7u8; 30])
+    }
+
+    /// What the initiator does with the encoded token and their pad
+    fn decode_token(encoded_token: &str, otp: &str) -> String {
+        let encoded = base64::engine::general_purpose::STANDARD.decode(encoded_token).unwrap();
+        let otp = decode_otp(otp, encoded.len()).unwrap();
+        String::from_utf8(xor(&encoded, &otp)).unwrap()
+    }
+
+    fn pending(vote: GenerateRootVote) -> GenerateRootProgress {
+        match vote {
+            GenerateRootVote::Pending(progress) => progress,
+            GenerateRootVote::Reached(_) => panic!("threshold reached early"),
+        }
+    }
+
+    #[test]
+    fn test_threshold_of_distinct_shares_completes() {
+        let generate = GenerateRoot::new(30, Duration::from_secs(60));
+        assert!(generate.start("not base64!", 2).is_err());
+        assert!(generate.start(&base64::engine::general_purpose::STANDARD.encode([1u8; 8]), 2).is_err());
+
+        let started = generate.start(&otp(), 2).unwrap();
+        assert_eq!((started.progress, started.required), (0, 2));
+        assert!(generate.start(&otp(), 2).is_err());
+        assert!(generate.submit("other", vec![1, 2, 3]).is_err());
+
+        let first = pending(generate.submit(&started.nonce, vec![1, 2, 3]).unwrap());
+        assert_eq!(first.progress, 1);
+        assert_eq!(pending(generate.submit(&started.nonce, vec![1, 2, 3]).unwrap()).progress, 1);
+
+        let quorum = match generate.submit(&started.nonce, vec![4, 5, 6]).unwrap() {
+            GenerateRootVote::Reached(quorum) => quorum,
+            GenerateRootVote::Pending(_) => panic!("threshold not reached"),
+        };
+        assert_eq!(quorum.shares.len(), 2);
+        assert!(generate.progress().is_none());
+
+        let encoded = quorum.encode_token(TOKEN).unwrap();
+        assert_ne!(encoded, base64::engine::general_purpose::STANDARD.encode(TOKEN));
+        assert_eq!(decode_token(&encoded, &otp()), TOKEN);
+    }
+
+    #[test]
+    fn test_attempts_time_out_and_cancel() {
+        let expired = GenerateRoot::new(30, Duration::ZERO);
+        let started = expired.start(&otp(), 2).unwrap();
+        assert!(expired.progress().is_none());
+        assert!(expired.submit(&started.nonce, vec![1]).is_err());
+
+        let generate = GenerateRoot::new(30, Duration::from_secs(60));
+        let started = generate.start(&otp(), 3).unwrap();
+        assert_eq!(generate.cancel(), Some(started.nonce));
+        assert_eq!(generate.cancel(), None);
+        generate.start(&otp(), 3).unwrap();
+    }
+}
@@ -0,0 +1,265 @@
+//! Lease registry for dynamic secrets
+//!
+//! Engines that issue short-lived credentials register a lease for each one
+//! and a [`LeaseRevoker`] for their mount. When a lease is revoked, either
+//! explicitly or by the expiration reaper once it expires, the registry asks
+//! the owning engine to revoke the credential before forgetting the lease.
+//!
+//! Engines pass TTLs through [`LeaseManager::jitter_ttl`] before issuing, so
+//! credentials created in a burst do not all expire in one.
+
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::core::expiration::ExpiredEntrySource;
+use crate::core::ttl_jitter::TtlJitter;
+use crate::errors::{VaultError, VaultResult};
+use crate::storage::StorageBackend;
+
+/// Storage prefix for registered leases
+const LEASE_PREFIX: &str = "sys/leases/";
+
+/// A credential issued for a limited time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lease {
+    /// `{mount}{path}/{id}`, e.g. `database/creds/readonly/3f2a…`
+    pub lease_id: String,
+    /// Mount of the engine that issued the credential
+    pub mount_point: String,
+    pub issue_time: DateTime<Utc>,
+    pub expire_time: DateTime<Utc>,
+    /// Engine-private data needed to revoke the credential
+    #[serde(default)]
+    pub internal: Map<String, Value>,
+}
+
+impl Lease {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expire_time <= now
+    }
+
+    /// Seconds left before the lease expires
+    pub fn ttl(&self, now: DateTime<Utc>) -> u64 {
+        (self.expire_time - now).num_seconds().max(0) as u64
+    }
+}
+
+/// An engine able to revoke the credentials behind its leases
+#[async_trait]
+pub trait LeaseRevoker: Send + Sync {
+    /// Revoke the credential; must succeed if it is already gone
+    async fn revoke(&self, lease: &Lease) -> VaultResult<()>;
+}
+
+/// Persists leases and revokes them through their engine
+pub struct LeaseManager {
+    storage: Arc<dyn StorageBackend>,
+    /// Revokers keyed by mount path
+    revokers: DashMap<String, Arc<dyn LeaseRevoker>>,
+    ttl_jitter: TtlJitter,
+}
+
+impl LeaseManager {
+    pub fn new(storage: Arc<dyn StorageBackend>) -> Self {
+        Self {
+            storage,
+            revokers: DashMap::new(),
+            ttl_jitter: TtlJitter::default(),
+        }
+    }
+
+    /// Spread lease TTLs handed out through [`LeaseManager::jitter_ttl`]
+    pub fn with_ttl_jitter(mut self, jitter: TtlJitter) -> Self {
+        self.ttl_jitter = jitter;
+        self
+    }
+
+    /// TTL to issue a lease for, given the nominal `ttl` and its `max` (0 = none)
+    ///
+    /// The reaper revokes by expiry time, so jittered leases need no special
+    /// handling once created.
+    pub fn jitter_ttl(&self, ttl: u64, max: u64) -> u64 {
+        self.ttl_jitter.apply(ttl, max)
+    }
+
+    /// Route revocation of leases issued under `mount_point` to `revoker`
+    pub fn register_revoker(&self, mount_point: &str, revoker: Arc<dyn LeaseRevoker>) {
+        self.revokers.insert(mount_point.to_string(), revoker);
+    }
+
+    pub fn remove_revoker(&self, mount_point: &str) {
+        self.revokers.remove(mount_point);
+    }
+
+    fn storage_key(lease_id: &str) -> VaultResult<String> {
+        let id = lease_id.rsplit('/').next().unwrap_or_default();
+        if id.is_empty() {
+            return Err(VaultError::Validation(format!("invalid lease id: {}", lease_id)));
+        }
+        Ok(format!("{}{}", LEASE_PREFIX, id))
+    }
+
+    /// Register a lease for a credential issued at `mount_point` + `path`
+    pub async fn create(
+        &self,
+        mount_point: &str,
+        path: &str,
+        ttl: Duration,
+        internal: Map<String, Value>,
+    ) -> VaultResult<Lease> {
+        let now = Utc::now();
+        let ttl = chrono::Duration::from_std(ttl)
+            .map_err(|_| VaultError::Validation("lease TTL out of range".to_string()))?;
+        let lease = Lease {
+            lease_id: format!("{}{}/{}", mount_point, path.trim_end_matches('/'), uuid::Uuid::new_v4()),
+            mount_point: mount_point.to_string(),
+            issue_time: now,
+            expire_time: now + ttl,
+            internal,
+        };
+        let data = serde_json::to_vec(&lease)?;
+        self.storage.put(&Self::storage_key(&lease.lease_id)?, &data).await?;
+        Ok(lease)
+    }
+
+    pub async fn lookup(&self, lease_id: &str) -> VaultResult<Option<Lease>> {
+        let Some(data) = self.storage.get(&Self::storage_key(lease_id)?).await? else {
+            return Ok(None);
+        };
+        let lease: Lease = serde_json::from_slice(&data)?;
+        // The key only holds the trailing id; a mismatched prefix is a different lease
+        Ok((lease.lease_id == lease_id).then_some(lease))
+    }
+
+    /// Revoke the credential and forget the lease
+    pub async fn revoke(&self, lease_id: &str) -> VaultResult<()> {
+        let lease = self.lookup(lease_id).await?
+            .ok_or_else(|| VaultError::NotFound(format!("lease {} not found", lease_id)))?;
+        self.revoke_lease(&lease).await
+    }
+
+    /// Revoke every lease whose id starts with `prefix`, returning how many
+    pub async fn revoke_prefix(&self, prefix: &str) -> VaultResult<u64> {
+        let mut revoked = 0;
+        for lease in self.list().await? {
+            if lease.lease_id.starts_with(prefix) {
+                self.revoke_lease(&lease).await?;
+                revoked += 1;
+            }
+        }
+        Ok(revoked)
+    }
+
+    async fn list(&self) -> VaultResult<Vec<Lease>> {
+        let mut leases = Vec::new();
+        for key in self.storage.list(LEASE_PREFIX).await? {
+            if let Some(data) = self.storage.get(&key).await? {
+                leases.push(serde_json::from_slice(&data)?);
+            }
+        }
+        Ok(leases)
+    }
+
+    async fn revoke_lease(&self, lease: &Lease) -> VaultResult<()> {
+        let revoker = self.revokers.get(&lease.mount_point)
+            .map(|r| r.value().clone())
+            .ok_or_else(|| VaultError::Vault(format!(
+                "no engine mounted at {} to revoke lease {}",
+                lease.mount_point, lease.lease_id
+            )))?;
+        revoker.revoke(lease).await?;
+        self.storage.delete(&Self::storage_key(&lease.lease_id)?).await
+    }
+}
+
+#[async_trait]
+impl ExpiredEntrySource for LeaseManager {
+    fn name(&self) -> &str {
+        "leases"
+    }
+
+    async fn reap_expired(&self, batch_size: usize) -> VaultResult<u64> {
+        let now = Utc::now();
+        let mut reaped = 0;
+        for lease in self.list().await? {
+            if reaped as usize >= batch_size {
+                break;
+            }
+            if !lease.is_expired(now) {
+                continue;
+            }
+            // One failing revocation must not keep the others alive; it is
+            // retried on the next run
+            match self.revoke_lease(&lease).await {
+                Ok(()) => reaped += 1,
+                Err(e) => tracing::warn!("Failed to revoke expired lease {}: {}", lease.lease_id, e),
+            }
+        }
+        Ok(reaped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use crate::storage::physical_inmem::InMemoryBackend;
+
+    #[derive(Default)]
+    struct RecordingRevoker {
+        revoked: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl LeaseRevoker for RecordingRevoker {
+        async fn revoke(&self, lease: &Lease) -> VaultResult<()> {
+            self.revoked.lock().unwrap().push(lease.lease_id.clone());
+            Ok(())
+        }
+    }
+
+    fn manager() -> (LeaseManager, Arc<RecordingRevoker>) {
+        let manager = LeaseManager::new(Arc::new(InMemoryBackend::new()));
+        let revoker = Arc::new(RecordingRevoker::default());
+        manager.register_revoker("database/", revoker.clone());
+        (manager, revoker)
+    }
+
+    #[tokio::test]
+    async fn test_create_lookup_and_revoke() {
+        let (manager, revoker) = manager();
+        let lease = manager.create("database/", "creds/readonly", Duration::from_secs(60), Map::new()).await.unwrap();
+        assert!(lease.lease_id.starts_with("database/creds/readonly/"));
+        assert_eq!(manager.lookup(&lease.lease_id).await.unwrap(), Some(lease.clone()));
+
+        manager.revoke(&lease.lease_id).await.unwrap();
+        assert_eq!(*revoker.revoked.lock().unwrap(), vec![lease.lease_id.clone()]);
+        assert!(manager.lookup(&lease.lease_id).await.unwrap().is_none());
+        assert!(matches!(manager.revoke(&lease.lease_id).await, Err(VaultError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_reap_revokes_only_expired_leases() {
+        let (manager, revoker) = manager();
+        let expired = manager.create("database/", "creds/a", Duration::ZERO, Map::new()).await.unwrap();
+        let live = manager.create("database/", "creds/b", Duration::from_secs(60), Map::new()).await.unwrap();
+
+        assert_eq!(manager.reap_expired(10).await.unwrap(), 1);
+        assert_eq!(*revoker.revoked.lock().unwrap(), vec![expired.lease_id]);
+        assert!(manager.lookup(&live.lease_id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_lease_kept_when_engine_missing() {
+        let (manager, _) = manager();
+        let lease = manager.create("database/", "creds/a", Duration::ZERO, Map::new()).await.unwrap();
+        manager.remove_revoker("database/");
+
+        assert_eq!(manager.reap_expired(10).await.unwrap(), 0);
+        assert!(manager.lookup(&lease.lease_id).await.unwrap().is_some());
+    }
+}
@@ -3,6 +3,22 @@
 //! Adapted from RustyVault to work with health-v1 infrastructure
 
 pub mod vault_core;
+pub mod mounts;
+pub mod expiration;
+pub mod standby;
+pub mod lease;
+pub mod seal_quorum;
+pub mod generate_root;
+pub mod ttl_jitter;
+pub mod namespace;
+pub mod selftest;
 
 pub use vault_core::{VaultCore, SealConfig};
+pub use mounts::MountManager;
+pub use expiration::ExpirationReaper;
+pub use lease::LeaseManager;
+pub use seal_quorum::{SealQuorum, SealVote};
+pub use generate_root::{GenerateRoot, GenerateRootProgress, GenerateRootVote};
+pub use ttl_jitter::TtlJitter;
+pub use namespace::Namespace;
 
@@ -0,0 +1,448 @@
+//! Secrets engine mount management
+//!
+//! Enables and disables secrets engines at runtime and persists the mount
+//! table so mounts survive restarts.
+
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::errors::{VaultError, VaultResult};
+use crate::core::lease::{LeaseManager, LeaseRevoker};
+use crate::core::namespace::{self, Namespace};
+use crate::logical::Backend;
+use crate::modules::database::{DatabaseBackend, PgStatementExecutor};
+use crate::modules::kv::{FsckReport, KvBackend, KvFsck};
+use crate::modules::pki::PkiBackend;
+use crate::modules::transit::TransitBackend;
+use crate::router::{MountEntry, MountTable, Router};
+use crate::storage::{SealWrapper, StorageBackend};
+
+/// Storage path of the persisted mount table
+pub const MOUNT_TABLE_PATH: &str = "core/mounts";
+
+/// Mount paths reserved for the system backend and core auth methods
+pub const PROTECTED_MOUNTS: &[&str] = &["sys/", "auth/token/", "auth/userpass/"];
+
+/// A mounted engine, plus its lease revoker if it issues dynamic secrets
+type BuiltBackend = (Arc<dyn Backend>, Option<Arc<dyn LeaseRevoker>>);
+
+/// Persisted configuration of a mounted secrets engine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MountConfig {
+    /// Normalized mount path (e.g. `secret/`)
+    pub path: String,
+    /// Secrets engine type (`kv`, `pki`, `transit` or `database`)
+    #[serde(rename = "type")]
+    pub backend_type: String,
+    #[serde(default)]
+    pub description: String,
+    /// Engine-specific configuration
+    #[serde(default)]
+    pub config: Map<String, Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Manages the lifecycle of mounted secrets engines
+pub struct MountManager {
+    router: Arc<Router>,
+    /// Storage for the mount table itself
+    metadata: Arc<dyn StorageBackend>,
+    /// Storage handed to secrets engines for their data
+    secrets: Arc<dyn StorageBackend>,
+    /// Lease registry for engines issuing dynamic secrets
+    leases: Option<Arc<LeaseManager>>,
+    /// Seal wrapping for KV mounts configured with `seal_wrap`
+    seal_wrapper: Option<Arc<dyn SealWrapper>>,
+    mounts: tokio::sync::Mutex<Vec<MountConfig>>,
+}
+
+impl MountManager {
+    pub fn new(
+        router: Arc<Router>,
+        metadata: Arc<dyn StorageBackend>,
+        secrets: Arc<dyn StorageBackend>,
+    ) -> Self {
+        Self {
+            router,
+            metadata,
+            secrets,
+            leases: None,
+            seal_wrapper: None,
+            mounts: tokio::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Enable engines that issue leased dynamic secrets
+    pub fn with_leases(mut self, leases: Arc<LeaseManager>) -> Self {
+        self.leases = Some(leases);
+        self
+    }
+
+    /// Let KV mounts seal wrap their most sensitive secrets
+    pub fn with_seal_wrapper(mut self, wrapper: Arc<dyn SealWrapper>) -> Self {
+        self.seal_wrapper = Some(wrapper);
+        self
+    }
+
+    /// Load the persisted mount table and mount every engine in it
+    pub async fn load(&self) -> VaultResult<()> {
+        let stored: Vec<MountConfig> = match self.metadata.get(MOUNT_TABLE_PATH).await? {
+            Some(data) => serde_json::from_slice(&data)?,
+            None => Vec::new(),
+        };
+
+        let mut mounts = self.mounts.lock().await;
+        for mount in stored {
+            let (backend, revoker) = self.build_backend(&mount)?;
+            self.router.mount(&mount.path, &mount.backend_type, backend)?;
+            self.register_revoker(&mount.path, revoker);
+            mounts.push(mount);
+        }
+        Ok(())
+    }
+
+    /// Enable a secrets engine at `path`
+    pub async fn mount(
+        &self,
+        path: &str,
+        backend_type: &str,
+        description: &str,
+        config: Map<String, Value>,
+    ) -> VaultResult<MountConfig> {
+        let path = MountTable::normalize_path(path);
+        if path.starts_with("sys/") || path.starts_with("auth/") {
+            return Err(VaultError::Validation(format!("cannot mount secrets engine at reserved path {}", path)));
+        }
+        if namespace::is_namespace_path(&path) {
+            // Namespace mounts follow the same rules inside their namespace
+            let inner = Namespace::split_path(&path).map(|(_, inner)| inner).unwrap_or("");
+            if inner.is_empty() || inner.starts_with("sys/") || inner.starts_with("auth/") {
+                return Err(VaultError::Validation(format!("cannot mount secrets engine at reserved path {}", path)));
+            }
+        }
+
+        let mount = MountConfig {
+            path,
+            backend_type: backend_type.to_string(),
+            description: description.to_string(),
+            config,
+            created_at: Utc::now(),
+        };
+        let (backend, revoker) = self.build_backend(&mount)?;
+
+        let mut mounts = self.mounts.lock().await;
+        self.router.mount(&mount.path, &mount.backend_type, backend)?;
+        self.register_revoker(&mount.path, revoker);
+        mounts.push(mount.clone());
+
+        if let Err(e) = self.persist(&mounts).await {
+            // Roll back so the in-memory table matches what is persisted
+            mounts.pop();
+            self.router.unmount(&mount.path);
+            self.remove_revoker(&mount.path);
+            return Err(e);
+        }
+        Ok(mount)
+    }
+
+    /// Disable the secrets engine at `path`
+    ///
+    /// When `purge` is set all data stored by the engine is deleted; otherwise
+    /// it is left in place and becomes visible again if the path is remounted.
+    pub async fn unmount(&self, path: &str, purge: bool) -> VaultResult<MountConfig> {
+        let path = MountTable::normalize_path(path);
+        if is_protected(&path) {
+            return Err(VaultError::Validation(format!("cannot unmount core mount {}", path)));
+        }
+
+        let mut mounts = self.mounts.lock().await;
+        let index = mounts.iter().position(|m| m.path == path)
+            .ok_or_else(|| VaultError::NotFound(format!("no mount at {}", path)))?;
+
+        // Credentials issued by the engine must not outlive it
+        if let Some(leases) = &self.leases {
+            let revoked = leases.revoke_prefix(&path).await?;
+            if revoked > 0 {
+                tracing::info!("Revoked {} leases issued by {}", revoked, path);
+            }
+        }
+
+        // Nothing reaches the engine from here on, so nothing writes mid-purge
+        let routed = self.router.unmount(&path);
+
+        // Purged while the mount is still persisted, so a failed purge can be
+        // retried by unmounting again rather than leaving unreachable data
+        if purge {
+            if let Err(e) = purge_prefix(self.secrets.as_ref(), &path).await {
+                self.restore_route(routed)?;
+                return Err(e);
+            }
+        }
+
+        let removed = mounts.remove(index);
+        if let Err(e) = self.persist(&mounts).await {
+            mounts.insert(index, removed);
+            self.restore_route(routed)?;
+            return Err(e);
+        }
+        self.remove_revoker(&path);
+        Ok(removed)
+    }
+
+    /// Route to an engine again after its unmount failed
+    fn restore_route(&self, routed: Option<MountEntry>) -> VaultResult<()> {
+        match routed {
+            Some(entry) => self.router.mount(&entry.path, &entry.backend_type, entry.backend),
+            None => Ok(()),
+        }
+    }
+
+    /// List the configuration of every mounted engine
+    pub async fn list(&self) -> Vec<MountConfig> {
+        let mut mounts = self.mounts.lock().await.clone();
+        mounts.sort_by(|a, b| a.path.cmp(&b.path));
+        mounts
+    }
+
+    /// Whether an engine is mounted at exactly `path`
+    pub async fn is_mounted(&self, path: &str) -> bool {
+        let path = MountTable::normalize_path(path);
+        self.mounts.lock().await.iter().any(|m| m.path == path)
+    }
+
+    /// Check the KV engine mounted at `path` for drift between metadata and
+    /// data, repairing the safe cases only if `repair` is set
+    pub async fn fsck(&self, path: &str, repair: bool) -> VaultResult<FsckReport> {
+        let path = MountTable::normalize_path(path);
+        let mounts = self.mounts.lock().await;
+        let mount = mounts
+            .iter()
+            .find(|m| m.path == path)
+            .ok_or_else(|| VaultError::NotFound(format!("no engine mounted at {}", path)))?;
+        if mount.backend_type != "kv" {
+            return Err(VaultError::Validation(format!("{} is a {} engine; only kv can be checked", path, mount.backend_type)));
+        }
+        // Held so the mount cannot be removed or purged mid-check
+        KvFsck::new(self.secrets.clone(), path.trim_end_matches('/').to_string()).run(repair).await
+    }
+
+    /// Build the engine for a mount, plus its lease revoker if it issues
+    /// dynamic secrets
+    fn build_backend(
+        &self,
+        mount: &MountConfig,
+    ) -> VaultResult<BuiltBackend> {
+        match mount.backend_type.as_str() {
+            "kv" => {
+                let cache_ttls = KvBackend::parse_cache_ttls(&mount.config)?;
+                let seal_wrap = KvBackend::parse_seal_wrap(&mount.config)?;
+                let track_reads = KvBackend::parse_track_reads(&mount.config)?;
+                let mut kv = KvBackend::new(self.secrets.clone(), mount.path.trim_end_matches('/').to_string())
+                    .with_cache_ttls(cache_ttls)
+                    .with_read_tracking(track_reads);
+                // Attached even without prefixes, so blobs wrapped under an earlier
+                // config stay readable
+                if let Some(wrapper) = self.seal_wrapper.clone() {
+                    kv = kv.with_seal_wrap(wrapper, seal_wrap);
+                } else if !seal_wrap.is_empty() {
+                    return Err(VaultError::Config("seal wrapping is not enabled".to_string()));
+                }
+                let backend: Arc<dyn Backend> = Arc::new(kv);
+                Ok((backend, None))
+            }
+            "pki" => {
+                let backend: Arc<dyn Backend> = Arc::new(PkiBackend::new());
+                Ok((backend, None))
+            }
+            "transit" => {
+                let backend: Arc<dyn Backend> = Arc::new(
+                    TransitBackend::new(self.secrets.clone(), mount.path.trim_end_matches('/').to_string()),
+                );
+                Ok((backend, None))
+            }
+            "database" => {
+                let Some(leases) = self.leases.clone() else {
+                    return Err(VaultError::Config("dynamic secrets are not enabled".to_string()));
+                };
+                let max_ttl = mount.config.get("max_ttl").and_then(|v| v.as_u64()).unwrap_or(0);
+                let backend = Arc::new(DatabaseBackend::new(
+                    self.secrets.clone(),
+                    mount.path.trim_end_matches('/').to_string(),
+                    Arc::new(PgStatementExecutor::new()),
+                    leases,
+                    max_ttl,
+                ));
+                let revoker: Arc<dyn LeaseRevoker> = backend.clone();
+                let backend: Arc<dyn Backend> = backend;
+                Ok((backend, Some(revoker)))
+            }
+            other => Err(VaultError::Validation(format!("unsupported secrets engine type: {}", other))),
+        }
+    }
+
+    fn register_revoker(&self, path: &str, revoker: Option<Arc<dyn LeaseRevoker>>) {
+        if let (Some(leases), Some(revoker)) = (&self.leases, revoker) {
+            leases.register_revoker(path, revoker);
+        }
+    }
+
+    fn remove_revoker(&self, path: &str) {
+        if let Some(leases) = &self.leases {
+            leases.remove_revoker(path);
+        }
+    }
+
+    async fn persist(&self, mounts: &[MountConfig]) -> VaultResult<()> {
+        let data = serde_json::to_vec(mounts)?;
+        self.metadata.put(MOUNT_TABLE_PATH, &data).await
+    }
+}
+
+fn is_protected(path: &str) -> bool {
+    PROTECTED_MOUNTS.contains(&path)
+}
+
+/// Recursively delete every key stored under `prefix`
+async fn purge_prefix(storage: &dyn StorageBackend, prefix: &str) -> VaultResult<()> {
+    let mut pending = vec![prefix.to_string()];
+    while let Some(dir) = pending.pop() {
+        for key in storage.list(&dir).await? {
+            if key.ends_with('/') {
+                pending.push(key);
+            } else {
+                storage.delete(&key).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical::Request;
+    use crate::storage::physical_inmem::InMemoryBackend;
+
+    fn manager(metadata: Arc<InMemoryBackend>, secrets: Arc<InMemoryBackend>) -> MountManager {
+        MountManager::new(Arc::new(Router::new()), metadata, secrets)
+    }
+
+    #[tokio::test]
+    async fn test_mount_table_survives_reload() {
+        let metadata = Arc::new(InMemoryBackend::new());
+        let secrets = Arc::new(InMemoryBackend::new());
+
+        let first = manager(metadata.clone(), secrets.clone());
+        first.mount("team-a", "kv", "team secrets", Map::new()).await.unwrap();
+        first.mount("pki", "pki", "", Map::new()).await.unwrap();
+
+        let second = manager(metadata, secrets);
+        second.load().await.unwrap();
+        let paths: Vec<String> = second.list().await.into_iter().map(|m| m.path).collect();
+        assert_eq!(paths, vec!["pki/", "team-a/"]);
+        assert!(second.router.mounts().lookup("team-a/db").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_mount_rejects_reserved_and_unknown() {
+        let mgr = manager(Arc::new(InMemoryBackend::new()), Arc::new(InMemoryBackend::new()));
+        assert!(mgr.mount("sys/custom", "kv", "", Map::new()).await.is_err());
+        assert!(mgr.mount("auth/token", "kv", "", Map::new()).await.is_err());
+        assert!(mgr.mount("transit", "unknown", "", Map::new()).await.is_err());
+        assert!(mgr.mount("ns/acme", "kv", "", Map::new()).await.is_err());
+        assert!(mgr.mount("ns/acme/sys/custom", "kv", "", Map::new()).await.is_err());
+        assert!(mgr.mount("ns/bad.name/secret", "kv", "", Map::new()).await.is_err());
+        let seal_wrapped = serde_json::json!({ "seal_wrap": true }).as_object().cloned().unwrap();
+        assert!(mgr.mount("vault", "kv", "", seal_wrapped).await.is_err());
+        assert!(mgr.list().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_namespaced_requests_stay_in_their_namespace() {
+        let mgr = manager(Arc::new(InMemoryBackend::new()), Arc::new(InMemoryBackend::new()));
+        mgr.mount("secret", "kv", "", Map::new()).await.unwrap();
+        mgr.mount("ns/acme/secret", "kv", "", Map::new()).await.unwrap();
+
+        let data = serde_json::json!({ "password": "acme" }).as_object().cloned();
+        let mut write = Request::new_write_request("secret/db", data);
+        write.namespace = Some(Namespace::new("acme").unwrap());
+        mgr.router.route(&mut write).await.unwrap();
+
+        let mut read = Request::new_read_request("secret/db");
+        read.namespace = Some(Namespace::new("acme").unwrap());
+        assert!(mgr.router.route(&mut read).await.unwrap().is_some());
+
+        // Neither the global mount nor another namespace sees it
+        assert!(mgr.router.route(&mut Request::new_read_request("secret/db")).await.unwrap().is_none());
+        let mut other = Request::new_read_request("secret/db");
+        other.namespace = Some(Namespace::new("globex").unwrap());
+        assert!(mgr.router.route(&mut other).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unmount_protected_paths() {
+        let mgr = manager(Arc::new(InMemoryBackend::new()), Arc::new(InMemoryBackend::new()));
+        assert!(matches!(mgr.unmount("auth/userpass", false).await, Err(VaultError::Validation(_))));
+        assert!(matches!(mgr.unmount("auth/token/", true).await, Err(VaultError::Validation(_))));
+        assert!(matches!(mgr.unmount("missing", false).await, Err(VaultError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_unmount_purge_and_preserve() {
+        let secrets = Arc::new(InMemoryBackend::new());
+        let mgr = manager(Arc::new(InMemoryBackend::new()), secrets.clone());
+        mgr.mount("keep", "kv", "", Map::new()).await.unwrap();
+        mgr.mount("drop", "kv", "", Map::new()).await.unwrap();
+
+        secrets.put("keep/data/app/db", b"1").await.unwrap();
+        secrets.put("drop/data/app/db", b"1").await.unwrap();
+        secrets.put("drop/metadata/app/db", b"1").await.unwrap();
+
+        mgr.unmount("keep", false).await.unwrap();
+        mgr.unmount("drop", true).await.unwrap();
+
+        assert!(secrets.get("keep/data/app/db").await.unwrap().is_some());
+        assert!(secrets.get("drop/data/app/db").await.unwrap().is_none());
+        assert!(secrets.get("drop/metadata/app/db").await.unwrap().is_none());
+        assert!(mgr.list().await.is_empty());
+    }
+
+    /// Storage whose deletes always fail
+    struct UndeletableStorage(InMemoryBackend);
+
+    #[async_trait::async_trait]
+    impl StorageBackend for UndeletableStorage {
+        async fn get(&self, key: &str) -> VaultResult<Option<Vec<u8>>> {
+            self.0.get(key).await
+        }
+
+        async fn put(&self, key: &str, value: &[u8]) -> VaultResult<()> {
+            self.0.put(key, value).await
+        }
+
+        async fn delete(&self, _key: &str) -> VaultResult<()> {
+            Err(VaultError::Storage("disk is read-only".to_string()))
+        }
+
+        async fn list(&self, prefix: &str) -> VaultResult<Vec<String>> {
+            self.0.list(prefix).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_purge_keeps_the_mount() {
+        let metadata = Arc::new(InMemoryBackend::new());
+        let secrets = Arc::new(UndeletableStorage(InMemoryBackend::new()));
+        let mgr = MountManager::new(Arc::new(Router::new()), metadata.clone(), secrets.clone());
+        mgr.mount("drop", "kv", "", Map::new()).await.unwrap();
+        secrets.put("drop/data/app/db", b"1").await.unwrap();
+
+        assert!(mgr.unmount("drop", true).await.is_err());
+        assert!(mgr.is_mounted("drop").await);
+        assert!(mgr.router.mounts().get("drop/").is_some());
+        let persisted: Vec<MountConfig> =
+            serde_json::from_slice(&metadata.get(MOUNT_TABLE_PATH).await.unwrap().unwrap()).unwrap();
+        assert_eq!(persisted.len(), 1);
+    }
+}
@@ -0,0 +1,185 @@
+//! Per-organization namespaces
+//!
+//! With namespace isolation enabled, a token whose metadata carries an
+//! `org_id` belongs to that organization's namespace. Its logical paths,
+//! mounts and policy names are prefixed transparently, below the ACL check,
+//! so no policy can grant it anything outside its own namespace. Addressing
+//! `ns/` paths directly is reserved for root tokens outside any namespace.
+
+use serde_json::{Map, Value};
+use crate::errors::{VaultError, VaultResult};
+use crate::modules::auth::TokenEntry;
+use crate::modules::policy::policy::IMMUTABLE_POLICIES;
+
+/// Token metadata field naming the organization a token belongs to
+pub const NAMESPACE_META_KEY: &str = "org_id";
+
+/// Path prefix under which every namespace's mounts live
+pub const NAMESPACE_PATH_PREFIX: &str = "ns/";
+
+/// Name prefix of namespace policies
+const NAMESPACE_POLICY_PREFIX: &str = "ns:";
+
+const MAX_NAME_LEN: usize = 64;
+
+/// `sys/` and `auth/` endpoints open to namespaced tokens; each scopes itself
+/// to the caller's namespace
+const NAMESPACED_ENDPOINTS: &[&str] = &["sys/mounts", "sys/policies/acl", "sys/secrets/batch-read", "auth/token"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Namespace {
+    name: String,
+}
+
+impl Namespace {
+    /// Names are case-insensitive and limited to letters, digits, `-` and `_`
+    pub fn new(name: &str) -> VaultResult<Self> {
+        let valid = !name.is_empty()
+            && name.len() <= MAX_NAME_LEN
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(VaultError::Validation(format!("invalid namespace name '{}'", name)));
+        }
+        Ok(Self {
+            name: name.to_lowercase(),
+        })
+    }
+
+    /// The namespace `token` belongs to, if any
+    pub fn of_token(token: &TokenEntry) -> VaultResult<Option<Self>> {
+        match token.meta.as_ref().and_then(|meta| meta.get(NAMESPACE_META_KEY)) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(org)) => Self::new(org).map(Some),
+            Some(_) => Err(VaultError::Validation(format!(
+                "token metadata '{}' must be a string",
+                NAMESPACE_META_KEY
+            ))),
+        }
+    }
+
+    /// Split a `ns/{name}/...` path into its namespace and the path inside it
+    pub fn split_path(path: &str) -> Option<(Self, &str)> {
+        let (name, rest) = path.strip_prefix(NAMESPACE_PATH_PREFIX)?.split_once('/')?;
+        Self::new(name).ok().map(|namespace| (namespace, rest))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn path_prefix(&self) -> String {
+        format!("{}{}/", NAMESPACE_PATH_PREFIX, self.name)
+    }
+
+    /// Where `path`, as seen from inside the namespace, is routed and stored
+    pub fn scope_path(&self, path: &str) -> String {
+        format!("{}{}", self.path_prefix(), path.trim_start_matches('/'))
+    }
+
+    /// `path` as seen from inside the namespace; `None` outside it
+    pub fn unscope_path<'a>(&self, path: &'a str) -> Option<&'a str> {
+        path.strip_prefix(self.path_prefix().as_str())
+    }
+
+    /// Stored name of the namespace's policy `name`
+    ///
+    /// The built-in `root` and `default` policies are shared: a namespaced
+    /// root token administers its namespace and nothing else.
+    pub fn scope_policy(&self, name: &str) -> String {
+        if IMMUTABLE_POLICIES.contains(&name) {
+            return name.to_string();
+        }
+        format!("{}{}:{}", NAMESPACE_POLICY_PREFIX, self.name, name)
+    }
+
+    pub fn scope_policies(&self, names: &[String]) -> Vec<String> {
+        names.iter().map(|name| self.scope_policy(name)).collect()
+    }
+
+    /// Policy name as seen from inside the namespace; `None` for policies of
+    /// other namespaces or global ones
+    pub fn unscope_policy<'a>(&self, name: &'a str) -> Option<&'a str> {
+        name.strip_prefix(NAMESPACE_POLICY_PREFIX)?
+            .strip_prefix(self.name.as_str())?
+            .strip_prefix(':')
+    }
+
+    /// Metadata for a token created by one of the namespace's tokens
+    ///
+    /// The namespace is always stamped over whatever the caller asked for,
+    /// so a namespaced token cannot mint tokens outside its namespace.
+    pub fn stamp_meta(&self, meta: Option<Value>) -> VaultResult<Value> {
+        let mut meta = match meta {
+            None | Some(Value::Null) => Map::new(),
+            Some(Value::Object(meta)) => meta,
+            Some(_) => return Err(VaultError::Validation("token meta must be an object".to_string())),
+        };
+        meta.insert(NAMESPACE_META_KEY.to_string(), Value::String(self.name.clone()));
+        Ok(Value::Object(meta))
+    }
+}
+
+/// Whether a namespaced token may call the `sys/` or `auth/` endpoint at
+/// `path`; logical paths are always allowed since they are scoped
+pub fn namespaced_endpoint_allowed(path: &str) -> bool {
+    if !path.starts_with("sys/") && !path.starts_with("auth/") {
+        return true;
+    }
+    NAMESPACED_ENDPOINTS.iter().any(|endpoint| {
+        path.strip_prefix(endpoint)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+/// Whether `path` addresses a namespace's data directly
+pub fn is_namespace_path(path: &str) -> bool {
+    path.starts_with(NAMESPACE_PATH_PREFIX)
+}
+
+/// Whether `name` is a namespace's policy
+pub fn is_namespace_policy(name: &str) -> bool {
+    name.to_lowercase().starts_with(NAMESPACE_POLICY_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_and_policies_are_scoped() {
+        let acme = Namespace::new("Acme").unwrap();
+        assert_eq!(acme.scope_path("secret/data/db"), "ns/acme/secret/data/db");
+        assert_eq!(acme.unscope_path("ns/acme/secret/"), Some("secret/"));
+        assert_eq!(acme.unscope_path("ns/acme2/secret/"), None);
+        assert_eq!(Namespace::split_path("ns/acme/secret/x"), Some((acme.clone(), "secret/x")));
+
+        assert_eq!(acme.scope_policy("reader"), "ns:acme:reader");
+        assert_eq!(acme.scope_policy("root"), "root");
+        assert_eq!(acme.unscope_policy("ns:acme:reader"), Some("reader"));
+        assert_eq!(acme.unscope_policy("ns:acme2:reader"), None);
+        assert_eq!(acme.unscope_policy("reader"), None);
+
+        assert!(Namespace::new("../other").is_err());
+        assert!(Namespace::new("").is_err());
+    }
+
+    #[test]
+    fn test_namespaced_endpoints() {
+        assert!(namespaced_endpoint_allowed("secret/data/db"));
+        assert!(namespaced_endpoint_allowed("sys/mounts/kv2"));
+        assert!(namespaced_endpoint_allowed("auth/token/create"));
+        assert!(!namespaced_endpoint_allowed("sys/mountsx"));
+        assert!(!namespaced_endpoint_allowed("sys/seal"));
+        assert!(!namespaced_endpoint_allowed("sys/storage/snapshot"));
+        assert!(!namespaced_endpoint_allowed("auth/userpass/users/admin"));
+    }
+
+    #[test]
+    fn test_created_tokens_inherit_the_namespace() {
+        let acme = Namespace::new("acme").unwrap();
+        let meta = acme.stamp_meta(Some(serde_json::json!({"org_id": "other", "team": "a"}))).unwrap();
+        assert_eq!(meta["org_id"], "acme");
+        assert_eq!(meta["team"], "a");
+        assert!(acme.stamp_meta(Some(Value::String("x".to_string()))).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//! Operator quorum for manual seals
+//!
+//! With a quorum of M, `sys/seal` only seals once M distinct operators have
+//! confirmed the same attempt, like rekey and generate-root in Vault. The
+//! first confirmation starts an attempt and returns its nonce; the others
+//! must quote it. Progress is discarded when the attempt times out, so a
+//! stray confirmation cannot linger until someone else happens to seal.
+//!
+//! This only guards the HTTP endpoint. `VaultCore::seal` stays unguarded for
+//! automatic seals.
+
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::errors::{VaultError, VaultResult};
+
+/// Progress of a seal attempt that has not reached quorum
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SealProgress {
+    pub nonce: String,
+    /// Distinct operators that have confirmed
+    pub progress: usize,
+    pub required: usize,
+    /// Seconds until the attempt is discarded
+    pub expires_in_secs: u64,
+}
+
+/// Outcome of one confirmation
+#[derive(Debug, Clone, PartialEq)]
+pub enum SealVote {
+    /// More confirmations are needed
+    Pending(SealProgress),
+    /// Quorum reached; the caller should seal now
+    Reached,
+}
+
+struct SealAttempt {
+    nonce: String,
+    started: Instant,
+    operators: BTreeSet<String>,
+}
+
+/// Tracks confirmations for the current seal attempt
+pub struct SealQuorum {
+    required: usize,
+    timeout: Duration,
+    attempt: Mutex<Option<SealAttempt>>,
+}
+
+impl SealQuorum {
+    /// A quorum of 0 or 1 leaves the guard off: one request seals
+    pub fn new(required: u8, timeout: Duration) -> Self {
+        Self {
+            required: usize::from(required.max(1)),
+            timeout,
+            attempt: Mutex::new(None),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.required > 1
+    }
+
+    /// Record `operator`'s confirmation
+    ///
+    /// `nonce` must match the attempt in progress, if any. An operator
+    /// confirming twice counts once.
+    pub fn confirm(&self, operator: &str, nonce: Option<&str>) -> VaultResult<SealVote> {
+        if !self.enabled() {
+            return Ok(SealVote::Reached);
+        }
+
+        let mut attempt = self.attempt.lock().unwrap();
+        self.discard_expired(&mut attempt);
+        match (attempt.as_ref(), nonce) {
+            (Some(current), Some(nonce)) if current.nonce == nonce => {}
+            (Some(_), _) => {
+                return Err(VaultError::Validation(
+                    "a seal is already in progress; confirm it with its nonce or cancel it".to_string(),
+                ));
+            }
+            (None, Some(_)) => {
+                return Err(VaultError::Validation("no seal in progress for that nonce".to_string()));
+            }
+            (None, None) => {}
+        }
+        let current = attempt.get_or_insert_with(|| SealAttempt {
+            nonce: Uuid::new_v4().to_string(),
+            started: Instant::now(),
+            operators: BTreeSet::new(),
+        });
+
+        current.operators.insert(operator.to_string());
+        if current.operators.len() >= self.required {
+            *attempt = None;
+            return Ok(SealVote::Reached);
+        }
+        Ok(SealVote::Pending(self.progress_of(current)))
+    }
+
+    /// The attempt in progress, if any
+    pub fn progress(&self) -> Option<SealProgress> {
+        let mut attempt = self.attempt.lock().unwrap();
+        self.discard_expired(&mut attempt);
+        attempt.as_ref().map(|current| self.progress_of(current))
+    }
+
+    /// Abandon the attempt in progress; false when there was none
+    pub fn cancel(&self) -> bool {
+        self.attempt.lock().unwrap().take().is_some()
+    }
+
+    fn discard_expired(&self, attempt: &mut Option<SealAttempt>) {
+        if attempt.as_ref().is_some_and(|current| current.started.elapsed() >= self.timeout) {
+            tracing::info!("Seal attempt timed out before reaching quorum");
+            *attempt = None;
+        }
+    }
+
+    fn progress_of(&self, attempt: &SealAttempt) -> SealProgress {
+        SealProgress {
+            nonce: attempt.nonce.clone(),
+            progress: attempt.operators.len(),
+            required: self.required,
+            expires_in_secs: self.timeout.saturating_sub(attempt.started.elapsed()).as_secs(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(vote: SealVote) -> SealProgress {
+        match vote {
+            SealVote::Pending(progress) => progress,
+            SealVote::Reached => panic!("quorum reached early"),
+        }
+    }
+
+    #[test]
+    fn test_disabled_guard_seals_at_once() {
+        let quorum = SealQuorum::new(1, Duration::from_secs(60));
+        assert!(!quorum.enabled());
+        assert_eq!(quorum.confirm("alice", None).unwrap(), SealVote::Reached);
+    }
+
+    #[test]
+    fn test_distinct_operators_reach_quorum() {
+        let quorum = SealQuorum::new(3, Duration::from_secs(60));
+        let first = pending(quorum.confirm("alice", None).unwrap());
+        assert_eq!((first.progress, first.required), (1, 3));
+
+        // Repeats count once, and the nonce is required once an attempt exists
+        let again = pending(quorum.confirm("alice", Some(&first.nonce)).unwrap());
+        assert_eq!(again.progress, 1);
+        assert!(quorum.confirm("bob", None).is_err());
+        assert!(quorum.confirm("bob", Some("other")).is_err());
+
+        assert_eq!(pending(quorum.confirm("bob", Some(&first.nonce)).unwrap()).progress, 2);
+        assert_eq!(quorum.confirm("carol", Some(&first.nonce)).unwrap(), SealVote::Reached);
+        assert!(quorum.progress().is_none());
+    }
+
+    #[test]
+    fn test_attempts_time_out_and_cancel() {
+        let expired = SealQuorum::new(2, Duration::ZERO);
+        let first = pending(expired.confirm("alice", None).unwrap());
+        assert!(expired.progress().is_none());
+        assert!(expired.confirm("bob", Some(&first.nonce)).is_err());
+
+        let quorum = SealQuorum::new(2, Duration::from_secs(60));
+        pending(quorum.confirm("alice", None).unwrap());
+        assert!(quorum.cancel());
+        assert!(!quorum.cancel());
+        assert!(quorum.progress().is_none());
+    }
+}
@@ -0,0 +1,193 @@
+//! Crypto self-test
+//!
+//! Exercises every primitive the vault relies on, in memory and without
+//! touching stored secrets: key generation, AES-256-GCM and HMAC-SHA256
+//! against published test vectors and a round trip each, the barrier, and
+//! seal wrapping of a freshly generated key. A broken crypto library or a
+//! barrier that "decrypts" to garbage then fails here, instead of silently
+//! corrupting the data written after it.
+//!
+//! Checks needing the barrier key are skipped while sealed. The whole run
+//! takes well under a millisecond, so it is cheap enough to run on every
+//! readiness probe.
+
+use std::time::Instant;
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use ring::hmac;
+use serde::Serialize;
+
+use crate::errors::{VaultError, VaultResult};
+use crate::storage::barrier_aes_gcm::AESGCMBarrier;
+use crate::storage::{SealWrapper, SecurityBarrier};
+
+/// Plaintext round-tripped by the checks
+const PLAINTEXT: &[u8] = b"health-v1 crypto self-test";
+
+/// Outcome of one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    /// Not run, e.g. because the vault is sealed
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_us: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    /// Whether no check failed; skipped checks do not count
+    pub passed: bool,
+    pub checks: Vec<SelfTestCheck>,
+}
+
+/// Run every check against `barrier`
+pub fn run(barrier: &AESGCMBarrier) -> SelfTestReport {
+    let sealed = barrier.sealed().unwrap_or(true);
+    let mut checks = Vec::new();
+    let mut check = |name: &'static str, needs_unseal: bool, f: &dyn Fn() -> VaultResult<()>| {
+        if needs_unseal && sealed {
+            checks.push(SelfTestCheck { name, status: CheckStatus::Skipped, error: None, duration_us: 0 });
+            return;
+        }
+        let started = Instant::now();
+        let result = f();
+        checks.push(SelfTestCheck {
+            name,
+            status: if result.is_ok() { CheckStatus::Passed } else { CheckStatus::Failed },
+            error: result.err().map(|e| e.to_string()),
+            duration_us: started.elapsed().as_micros() as u64,
+        });
+    };
+
+    check("key_generation", false, &|| generate_dek(barrier).map(|_| ()));
+    check("aes_gcm", false, &|| check_aes_gcm(barrier));
+    check("hmac_sha256", false, &check_hmac);
+    check("barrier", true, &|| barrier.self_test(PLAINTEXT));
+    check("seal_wrap", true, &|| check_seal_wrap(barrier));
+
+    let passed = checks.iter().all(|c| c.status != CheckStatus::Failed);
+    if !passed {
+        for failed in checks.iter().filter(|c| c.status == CheckStatus::Failed) {
+            tracing::error!("Crypto self-test {} failed: {}", failed.name, failed.error.as_deref().unwrap_or(""));
+        }
+    }
+    SelfTestReport { passed, checks }
+}
+
+fn fail(message: &str) -> VaultError {
+    VaultError::Vault(message.to_string())
+}
+
+/// Two fresh data keys, which must be full length, non-zero and distinct
+fn generate_dek(barrier: &AESGCMBarrier) -> VaultResult<Vec<u8>> {
+    let first = barrier.generate_key()?;
+    let second = barrier.generate_key()?;
+    if first.len() != 32 || first.iter().all(|&b| b == 0) || first == second {
+        return Err(fail("Key generation returned weak keys"));
+    }
+    Ok(first.to_vec())
+}
+
+/// GCM spec test case 14 (all-zero key, nonce and block), then a round trip
+/// under a generated key that must refuse a corrupted copy
+fn check_aes_gcm(barrier: &AESGCMBarrier) -> VaultResult<()> {
+    let expected = hex::decode("cea7403d4d606b6e074ec5d3baf39d18d0d1c8a799996bf0265b98b5d48ab919")
+        .map_err(|_| fail("Invalid AES-GCM test vector"))?;
+    let cipher = Aes256Gcm::new_from_slice(&[0u8; 32]).map_err(|_| fail("AES-GCM rejected a 256-bit key"))?;
+    let nonce = Nonce::from_slice(&[0u8; 12]);
+    if cipher.encrypt(nonce, [0u8; 16].as_slice()).ok() != Some(expected) {
+        return Err(fail("AES-GCM does not match its known-answer test"));
+    }
+
+    let dek = generate_dek(barrier)?;
+    let cipher = Aes256Gcm::new_from_slice(&dek).map_err(|_| fail("AES-GCM rejected a generated key"))?;
+    let mut ciphertext = cipher.encrypt(nonce, PLAINTEXT).map_err(|_| fail("AES-GCM encryption failed"))?;
+    if cipher.decrypt(nonce, ciphertext.as_slice()).ok().as_deref() != Some(PLAINTEXT) {
+        return Err(fail("AES-GCM round trip returned different data"));
+    }
+    ciphertext[0] ^= 0x01;
+    if cipher.decrypt(nonce, ciphertext.as_slice()).is_ok() {
+        return Err(fail("AES-GCM accepted corrupted ciphertext"));
+    }
+    Ok(())
+}
+
+/// RFC 4231 test case 1, then sign and verify, refusing a changed message
+fn check_hmac() -> VaultResult<()> {
+    let expected = hex::decode("b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7")
+        .map_err(|_| fail("Invalid HMAC test vector"))?;
+    let key = hmac::Key::new(hmac::HMAC_SHA256, &[0x0b; 20]);
+    if hmac::sign(&key, b"Hi There").as_ref() != expected.as_slice() {
+        return Err(fail("HMAC-SHA256 does not match its known-answer test"));
+    }
+
+    let tag = hmac::sign(&key, PLAINTEXT);
+    hmac::verify(&key, PLAINTEXT, tag.as_ref()).map_err(|_| fail("HMAC-SHA256 rejected its own signature"))?;
+    if hmac::verify(&key, b"health-v1 crypto self-tesT", tag.as_ref()).is_ok() {
+        return Err(fail("HMAC-SHA256 accepted a changed message"));
+    }
+    Ok(())
+}
+
+/// Wrap a generated key with the seal and unwrap it again
+fn check_seal_wrap(barrier: &AESGCMBarrier) -> VaultResult<()> {
+    let dek = generate_dek(barrier)?;
+    let wrapped = barrier.seal_wrap("sys/health/crypto-selftest", &dek)?;
+    if barrier.seal_unwrap("sys/health/crypto-selftest", &wrapped)? != dek {
+        return Err(fail("Seal unwrap returned a different key"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use shared::infrastructure::encryption::KdfParams;
+    use crate::storage::physical_inmem::InMemoryBackend;
+
+    fn statuses(report: &SelfTestReport) -> Vec<(&str, CheckStatus)> {
+        report.checks.iter().map(|c| (c.name, c.status)).collect()
+    }
+
+    #[tokio::test]
+    async fn test_all_checks_pass_and_sealed_ones_are_skipped() {
+        let barrier = AESGCMBarrier::with_kdf(
+            Arc::new(InMemoryBackend::new()),
+            KdfParams::Pbkdf2Sha256 { iterations: 10 },
+        );
+        let kek = barrier.generate_key().unwrap();
+        barrier.init(&kek).await.unwrap();
+
+        let sealed = run(&barrier);
+        assert!(sealed.passed);
+        assert_eq!(
+            statuses(&sealed),
+            vec![
+                ("key_generation", CheckStatus::Passed),
+                ("aes_gcm", CheckStatus::Passed),
+                ("hmac_sha256", CheckStatus::Passed),
+                ("barrier", CheckStatus::Skipped),
+                ("seal_wrap", CheckStatus::Skipped),
+            ]
+        );
+
+        barrier.unseal(&kek).await.unwrap();
+        let unsealed = run(&barrier);
+        assert!(unsealed.passed, "{:?}", unsealed.checks);
+        assert!(unsealed.checks.iter().all(|c| c.status == CheckStatus::Passed));
+    }
+}
@@ -0,0 +1,155 @@
+//! Active/standby state and request draining
+//!
+//! A node serves requests while active. `step_down` turns it into a standby
+//! for a hold period so a load balancer (following `sys/health`) moves
+//! traffic to another replica; requests already running are drained first.
+//! After the hold period the node becomes active again, which keeps a
+//! single-node deployment from staying unavailable.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// Outcome of a step-down
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepDownResult {
+    /// All in-flight requests finished before the drain timeout
+    pub drained: bool,
+    /// Requests still running when the drain stopped waiting
+    pub in_flight: usize,
+}
+
+/// Tracks in-flight requests and whether this node is standing by
+pub struct ActiveState {
+    in_flight: AtomicUsize,
+    idle: Notify,
+    standby_until: Mutex<Option<Instant>>,
+    active_since: Mutex<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Default for ActiveState {
+    fn default() -> Self {
+        Self {
+            in_flight: AtomicUsize::new(0),
+            idle: Notify::new(),
+            standby_until: Mutex::new(None),
+            active_since: Mutex::new(chrono::Utc::now()),
+        }
+    }
+}
+
+impl ActiveState {
+    pub fn is_standby(&self) -> bool {
+        let mut standby_until = self.standby_until.lock().unwrap();
+        match *standby_until {
+            Some(until) if Instant::now() < until => true,
+            Some(_) => {
+                *standby_until = None;
+                *self.active_since.lock().unwrap() = chrono::Utc::now();
+                tracing::info!("Step-down hold period over, node is active again");
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// When this node last became active
+    pub fn active_since(&self) -> chrono::DateTime<chrono::Utc> {
+        *self.active_since.lock().unwrap()
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Register a request; `None` while standing by
+    pub fn enter(&self) -> Option<InFlightGuard<'_>> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = InFlightGuard { state: self };
+        // Checked after counting so a concurrent step-down either sees this
+        // request or this request sees the standby
+        if self.is_standby() {
+            return None;
+        }
+        Some(guard)
+    }
+
+    /// Stop accepting requests for `hold`, waiting up to `drain_timeout`
+    /// for running ones to finish
+    pub async fn step_down(&self, hold: Duration, drain_timeout: Duration) -> StepDownResult {
+        *self.standby_until.lock().unwrap() = Some(Instant::now() + hold);
+
+        let drain = async {
+            loop {
+                let idle = self.idle.notified();
+                if self.in_flight() == 0 {
+                    break;
+                }
+                idle.await;
+            }
+        };
+        let drained = tokio::time::timeout(drain_timeout, drain).await.is_ok();
+        StepDownResult { drained, in_flight: self.in_flight() }
+    }
+}
+
+/// Marks a request as running until dropped
+pub struct InFlightGuard<'a> {
+    state: &'a ActiveState,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        if self.state.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.state.idle.notify_waiters();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_step_down_rejects_new_requests_until_hold_expires() {
+        let state = ActiveState::default();
+        let result = state.step_down(Duration::from_millis(50), Duration::from_secs(1)).await;
+        assert_eq!(result, StepDownResult { drained: true, in_flight: 0 });
+        assert!(state.is_standby());
+        assert!(state.enter().is_none());
+        assert_eq!(state.in_flight(), 0);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(!state.is_standby());
+        assert!(state.enter().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_step_down_waits_for_in_flight_requests() {
+        let state = Arc::new(ActiveState::default());
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let worker = {
+            let state = state.clone();
+            tokio::spawn(async move {
+                let _guard = state.enter().unwrap();
+                started_tx.send(()).unwrap();
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            })
+        };
+        started_rx.await.unwrap();
+
+        let result = state.step_down(Duration::from_secs(10), Duration::from_secs(5)).await;
+        assert!(result.drained);
+        assert!(worker.is_finished() || state.in_flight() == 0);
+    }
+
+    #[tokio::test]
+    async fn test_drain_timeout_reports_remaining_requests() {
+        let state = ActiveState::default();
+        let _guard = state.enter().unwrap();
+        let result = state.step_down(Duration::from_secs(10), Duration::from_millis(20)).await;
+        assert_eq!(result, StepDownResult { drained: false, in_flight: 1 });
+    }
+}
@@ -0,0 +1,70 @@
+//! Random spread for issued TTLs
+//!
+//! Tokens and leases created together would otherwise expire together, and
+//! their renewals or revocations would arrive in one burst. Each issued TTL
+//! is moved by a random amount of up to ± the configured percentage, never
+//! past the applicable maximum.
+
+use rand::Rng;
+
+/// Largest accepted jitter; more would let a TTL shrink to almost nothing
+pub const MAX_JITTER_PERCENT: u8 = 50;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TtlJitter {
+    percent: u8,
+}
+
+impl TtlJitter {
+    /// Percentages above [`MAX_JITTER_PERCENT`] are clamped to it
+    pub fn new(percent: u8) -> Self {
+        Self {
+            percent: percent.min(MAX_JITTER_PERCENT),
+        }
+    }
+
+    /// `ttl` seconds moved by a random amount within the jitter
+    ///
+    /// `max` of 0 means uncapped. The result never exceeds `max`, or `ttl`
+    /// itself when that is already above `max`, and is at least 1 second.
+    pub fn apply(&self, ttl: u64, max: u64) -> u64 {
+        let spread = ttl.saturating_mul(u64::from(self.percent)) / 100;
+        if spread == 0 {
+            return ttl;
+        }
+        let offset = rand::thread_rng().gen_range(0..=spread.saturating_mul(2));
+        let jittered = (ttl - spread).saturating_add(offset).max(1);
+        match max {
+            0 => jittered,
+            max => jittered.min(max.max(ttl)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stays_within_the_spread() {
+        let jitter = TtlJitter::new(10);
+        for _ in 0..200 {
+            let ttl = jitter.apply(1000, 0);
+            assert!((900..=1100).contains(&ttl));
+        }
+        assert_eq!(TtlJitter::new(0).apply(1000, 0), 1000);
+        assert_eq!(TtlJitter::new(90).percent, MAX_JITTER_PERCENT);
+    }
+
+    #[test]
+    fn test_never_exceeds_the_max() {
+        let jitter = TtlJitter::new(50);
+        for _ in 0..200 {
+            assert!(jitter.apply(1000, 1000) <= 1000);
+            assert!(jitter.apply(1000, 1200) <= 1200);
+            // A TTL already above the max is not pushed further up
+            assert!(jitter.apply(3000, 1000) <= 3000);
+        }
+        assert_eq!(jitter.apply(1, 0), 1);
+    }
+}
@@ -2,14 +2,18 @@
 //!
 //! Adapted from RustyVault's Core to integrate with health-v1 infrastructure
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use serde::{Deserialize, Serialize};
 use zeroize::{Zeroize, Zeroizing};
 use crate::errors::{VaultError, VaultResult};
-use crate::logical::{Request, Response};
+use crate::logical::{Operation, Request, Response};
 use crate::storage::{StorageBackend, SecurityBarrier, barrier_aes_gcm::AESGCMBarrier};
 use crate::shamir::{ShamirSecret, SHAMIR_OVERHEAD};
 use crate::router::Router;
+use crate::core::standby::{ActiveState, StepDownResult};
+use shared::infrastructure::encryption::KdfParams;
 
 const SEAL_CONFIG_PATH: &str = "core/seal-config";
 
@@ -23,12 +27,41 @@ pub struct SealConfig {
 impl SealConfig {
     pub fn validate(&self) -> VaultResult<()> {
         if self.secret_threshold > self.secret_shares {
-            return Err(VaultError::Vault("Invalid seal config: threshold > shares".to_string()));
+            return Err(VaultError::Validation("Invalid seal config: threshold > shares".to_string()));
         }
         Ok(())
     }
 }
 
+/// `sys/seal-status` response, following Vault's field names
+///
+/// `barrier_kdf` is specific to this service: the KDF that wraps the
+/// barrier key, `null` before initialization.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SealStatus {
+    #[serde(rename = "type")]
+    pub seal_type: String,
+    pub initialized: bool,
+    pub sealed: bool,
+    /// Unseal key shares required
+    pub t: u8,
+    /// Unseal key shares issued
+    pub n: u8,
+    /// Unseal key shares submitted so far
+    pub progress: usize,
+    pub nonce: String,
+    pub version: String,
+    pub migration: bool,
+    pub cluster_name: String,
+    pub cluster_id: String,
+    pub recovery_seal: bool,
+    pub storage_type: String,
+    pub standby: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_time: Option<chrono::DateTime<chrono::Utc>>,
+    pub barrier_kdf: Option<String>,
+}
+
 /// Initialization result
 #[derive(Debug, Clone, PartialEq, Zeroize)]
 #[zeroize(drop)]
@@ -64,25 +97,50 @@ pub struct VaultCore {
     pub barrier: Arc<AESGCMBarrier>,
     pub router: Arc<Router>,
     pub state: Arc<std::sync::Mutex<CoreState>>,
+    /// Active/standby state and in-flight request tracking
+    pub active: Arc<ActiveState>,
+    /// Reject logical writes and deletes while set; see `set_read_only`
+    read_only: AtomicBool,
+    /// Held for the whole of `init`, so two concurrent inits cannot both
+    /// pass the initialized check
+    ///
+    /// This only serializes inits within one process. Instances sharing a
+    /// storage backend must still be initialized through a single one.
+    init_lock: tokio::sync::Mutex<()>,
 }
 
 impl VaultCore {
     pub fn new(storage: Arc<dyn StorageBackend>) -> Self {
-        let barrier = Arc::new(AESGCMBarrier::new(storage.clone()));
+        Self::with_kdf(storage, KdfParams::default())
+    }
+
+    /// Create a core whose barrier wraps its key with the given KDF
+    pub fn with_kdf(storage: Arc<dyn StorageBackend>, kdf: KdfParams) -> Self {
+        let barrier = Arc::new(AESGCMBarrier::with_kdf(storage.clone(), kdf));
         Self {
             storage,
             barrier,
             router: Arc::new(Router::new()),
             state: Arc::new(std::sync::Mutex::new(CoreState::default())),
+            active: Arc::new(ActiveState::default()),
+            read_only: AtomicBool::new(false),
+            init_lock: tokio::sync::Mutex::new(()),
         }
     }
 
+    /// Initialize the vault, exactly once
+    ///
+    /// Fails if barrier-init data already exists: writing a new barrier key
+    /// over it would leave everything encrypted under the old one
+    /// unrecoverable.
     pub async fn init(&self, seal_config: &SealConfig) -> VaultResult<InitResult> {
         seal_config.validate()?;
-        
-        let inited = self.inited().await?;
-        if inited {
-            return Err(VaultError::Vault("Vault already initialized".to_string()));
+
+        let _guard = self.init_lock.lock().await;
+        if self.is_initialized().await? {
+            return Err(VaultError::Validation(
+                "Vault is already initialized; re-initializing would orphan all existing encrypted data".to_string(),
+            ));
         }
 
         // Store seal config
@@ -124,19 +182,22 @@ impl VaultCore {
         })
     }
 
-    pub async fn inited(&self) -> VaultResult<bool> {
+    /// Whether barrier-init data exists
+    ///
+    /// A seal config without it is left by an init that failed part way and
+    /// does not count: nothing was encrypted yet, so init may run again.
+    pub async fn is_initialized(&self) -> VaultResult<bool> {
         self.barrier.inited().await
     }
 
     pub async fn unseal(&self, key: &[u8]) -> VaultResult<bool> {
-        let inited = self.inited().await?;
-        if !inited {
+        if !self.is_initialized().await? {
             return Err(VaultError::Vault("Vault not initialized".to_string()));
         }
 
         let sealed = self.barrier.sealed()?;
         if !sealed {
-            return Err(VaultError::Vault("Vault already unsealed".to_string()));
+            return Err(VaultError::Validation("Vault already unsealed".to_string()));
         }
 
         // Get seal config - this is the await before we acquire the lock
@@ -148,7 +209,7 @@ impl VaultCore {
         let (min, mut max) = self.barrier.key_length_range();
         max += SHAMIR_OVERHEAD;
         if key.len() < min || key.len() > max {
-            return Err(VaultError::Vault("Invalid key length".to_string()));
+            return Err(VaultError::Validation("Invalid key length".to_string()));
         }
 
         // Process unseal key and get KEK - all in a block so lock is dropped before await
@@ -213,23 +274,216 @@ impl VaultCore {
 
     pub async fn handle_request(&self, req: &mut Request) -> VaultResult<Option<Response>> {
         if self.is_sealed() {
-            return Err(VaultError::Vault("Vault is sealed".to_string()));
+            return Err(VaultError::Sealed);
+        }
+        if self.active.is_standby() {
+            return Err(VaultError::Vault("Vault is in standby".to_string()));
+        }
+        if self.is_read_only() && matches!(req.operation, Operation::Write | Operation::Delete) {
+            return Err(VaultError::ReadOnly);
         }
         self.router.route(req).await
     }
 
+    /// Freeze or unfreeze writes, returning the previous setting
+    ///
+    /// While read-only, logical writes and deletes fail with
+    /// `VaultError::ReadOnly` and reads and lists are served as usual, so
+    /// writes can be held still during a backup or an incident without
+    /// sealing. Takes effect for the next request. Token use counts are still
+    /// decremented on lookup: refusing that would either fail every request
+    /// made with a limited-use token or let it be used without limit.
+    pub fn set_read_only(&self, read_only: bool) -> bool {
+        self.read_only.swap(read_only, Ordering::SeqCst)
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::SeqCst)
+    }
+
     pub fn is_sealed(&self) -> bool {
         let state = self.state.lock().unwrap();
         state.sealed
     }
 
-    pub fn seal_config(&self) -> VaultResult<SealConfig> {
-        // This would need to be async in real implementation
-        // For now, return a default
-        Ok(SealConfig {
-            secret_shares: 5,
-            secret_threshold: 3,
+    /// Stored seal configuration; `None` before initialization
+    pub async fn seal_config(&self) -> VaultResult<Option<SealConfig>> {
+        let Some(data) = self.storage.get(SEAL_CONFIG_PATH).await? else {
+            return Ok(None);
+        };
+        let seal_config = serde_json::from_slice(&data)
+            .map_err(|e| VaultError::Serialization(e))?;
+        Ok(Some(seal_config))
+    }
+
+    /// Unseal key shares submitted toward the threshold
+    pub fn unseal_progress(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state.unseal_key_shares.len()
+    }
+
+    /// Check that `shares` reconstruct the key the vault is unsealed with
+    ///
+    /// For quorum operations such as generate-root: holding a threshold of
+    /// unseal key shares is the proof of authority. Only works while
+    /// unsealed, when the key is in memory to compare against.
+    pub fn verify_unseal_shares(&self, shares: &[Vec<u8>], threshold: u8) -> VaultResult<()> {
+        if shares.len() < usize::from(threshold.max(1)) {
+            return Err(VaultError::Validation("not enough unseal key shares".to_string()));
+        }
+        let combined = if threshold <= 1 {
+            Zeroizing::new(shares[0].clone())
+        } else {
+            Zeroizing::new(
+                ShamirSecret::combine(shares.to_vec())
+                    .ok_or_else(|| VaultError::Auth("unseal key shares could not be combined".to_string()))?,
+            )
+        };
+
+        let state = self.state.lock().unwrap();
+        if state.sealed || state.kek.is_empty() {
+            return Err(VaultError::Sealed);
+        }
+        let matches = combined.len() == state.kek.len()
+            && combined.iter().zip(&state.kek).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0;
+        if !matches {
+            return Err(VaultError::Auth("unseal key shares do not match this vault".to_string()));
+        }
+        Ok(())
+    }
+
+    pub async fn seal_status(&self) -> VaultResult<SealStatus> {
+        let initialized = self.is_initialized().await?;
+        let seal_config = self.seal_config().await?;
+        // Before init there is no barrier entry to read the KDF from
+        let barrier_kdf = if initialized {
+            self.barrier.stored_kdf().await?.map(|record| record.params.algorithm().to_string())
+        } else {
+            None
+        };
+        let standby = self.active.is_standby();
+
+        Ok(SealStatus {
+            seal_type: "shamir".to_string(),
+            initialized,
+            sealed: self.is_sealed(),
+            t: seal_config.as_ref().map_or(0, |c| c.secret_threshold),
+            n: seal_config.as_ref().map_or(0, |c| c.secret_shares),
+            progress: self.unseal_progress(),
+            nonce: String::new(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            migration: false,
+            cluster_name: String::new(),
+            cluster_id: String::new(),
+            recovery_seal: false,
+            storage_type: self.storage.storage_type(),
+            standby,
+            active_time: (!standby).then(|| self.active.active_since()),
+            barrier_kdf,
         })
     }
+
+    /// Relinquish active status for `hold`, draining in-flight requests
+    ///
+    /// The node stays unsealed; it only refuses requests until the hold
+    /// period ends so traffic fails over to another replica.
+    pub async fn step_down(&self, hold: Duration, drain_timeout: Duration) -> VaultResult<StepDownResult> {
+        if self.is_sealed() {
+            return Err(VaultError::Sealed);
+        }
+        Ok(self.active.step_down(hold, drain_timeout).await)
+    }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::physical_inmem::InMemoryBackend;
+    use crate::storage::BARRIER_INIT_PATH;
+
+    fn core(storage: Arc<dyn StorageBackend>) -> VaultCore {
+        VaultCore::with_kdf(storage, KdfParams::Argon2id { memory_kib: 64, iterations: 1, parallelism: 1 })
+    }
+
+    fn seal_config() -> SealConfig {
+        SealConfig { secret_shares: 1, secret_threshold: 1 }
+    }
+
+    #[tokio::test]
+    async fn test_second_init_fails_and_keeps_init_data() {
+        let storage: Arc<dyn StorageBackend> = Arc::new(InMemoryBackend::new());
+        let vault = core(storage.clone());
+        assert!(!vault.is_initialized().await.unwrap());
+
+        let first = vault.init(&seal_config()).await.unwrap();
+        let init_data = storage.get(BARRIER_INIT_PATH).await.unwrap().unwrap();
+        assert!(vault.is_initialized().await.unwrap());
+
+        // A fresh core over the same storage, as after a restart
+        let err = core(storage.clone()).init(&seal_config()).await.err().unwrap();
+        assert!(err.to_string().contains("already initialized"));
+        assert_eq!(storage.get(BARRIER_INIT_PATH).await.unwrap().unwrap(), init_data);
+        assert!(vault.unseal(&first.secret_shares[0]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_inits_succeed_once() {
+        let vault = core(Arc::new(InMemoryBackend::new()));
+        let (first, second) = (seal_config(), seal_config());
+        let (a, b) = tokio::join!(vault.init(&first), vault.init(&second));
+        assert_eq!([a.is_ok(), b.is_ok()].iter().filter(|ok| **ok).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_seal_status_reports_the_storage_backend() {
+        let vault = core(Arc::new(InMemoryBackend::new()));
+        let status = vault.seal_status().await.unwrap();
+        assert!(!status.initialized);
+        assert_eq!(status.storage_type, "inmem");
+    }
+
+    #[tokio::test]
+    async fn test_read_only_rejects_writes_but_serves_reads() {
+        let vault = core(Arc::new(InMemoryBackend::new()));
+        let result = vault.init(&seal_config()).await.unwrap();
+        vault.unseal(&result.secret_shares[0]).await.unwrap();
+        let kv = crate::modules::kv::KvBackend::new(Arc::new(InMemoryBackend::new()), "secret".to_string());
+        vault.router.mount("secret", "kv", Arc::new(kv)).unwrap();
+
+        let data = serde_json::json!({ "password": "s3cret" }).as_object().cloned();
+        vault.handle_request(&mut Request::new_write_request("secret/db", data.clone())).await.unwrap();
+
+        assert!(!vault.set_read_only(true));
+        let err = vault.handle_request(&mut Request::new_write_request("secret/db", data.clone())).await;
+        assert!(matches!(err, Err(VaultError::ReadOnly)));
+        let mut delete = Request::new_read_request("secret/db");
+        delete.operation = Operation::Delete;
+        assert!(matches!(vault.handle_request(&mut delete).await, Err(VaultError::ReadOnly)));
+        assert!(vault.handle_request(&mut Request::new_read_request("secret/db")).await.unwrap().is_some());
+
+        assert!(vault.set_read_only(false));
+        vault.handle_request(&mut Request::new_write_request("secret/db", data)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_verify_unseal_shares_needs_the_real_key() {
+        let vault = core(Arc::new(InMemoryBackend::new()));
+        let config = SealConfig { secret_shares: 3, secret_threshold: 2 };
+        let result = vault.init(&config).await.unwrap();
+        let shares: Vec<Vec<u8>> = result.secret_shares.iter().take(2).cloned().collect();
+
+        // Nothing to compare against while sealed
+        assert!(matches!(vault.verify_unseal_shares(&shares, 2), Err(VaultError::Sealed)));
+        vault.unseal(&shares[0]).await.unwrap();
+        assert!(vault.unseal(&shares[1]).await.unwrap());
+
+        vault.verify_unseal_shares(&shares, 2).unwrap();
+        assert!(vault.verify_unseal_shares(&shares[..1], 2).is_err());
+
+        let other = core(Arc::new(InMemoryBackend::new()));
+        let foreign = other.init(&config).await.unwrap();
+        let foreign: Vec<Vec<u8>> = foreign.secret_shares.iter().take(2).cloned().collect();
+        assert!(vault.verify_unseal_shares(&foreign, 2).is_err());
+    }
+}
@@ -22,6 +22,14 @@ pub enum VaultError {
     #[error("Barrier error: {0}")]
     Barrier(String),
 
+    /// The barrier is sealed, so nothing can be read or written until unseal
+    #[error("Vault is sealed")]
+    Sealed,
+
+    /// Writes are frozen through `sys/read-only`; reads are still served
+    #[error("Vault is in read-only mode")]
+    ReadOnly,
+
     // Shared error types (integrated from AppError)
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
@@ -47,6 +55,10 @@ pub enum VaultError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    /// The request was based on a stale copy of what it changes
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
@@ -55,6 +67,50 @@ pub enum VaultError {
 
     #[error("Internal error: {0}")]
     Internal(String),
+
+    /// An error annotated with what was being done, e.g. "write secret/foo"
+    ///
+    /// Displays as "{context}: {source}" so a single log line carries the whole
+    /// chain; `source()` still yields the wrapped error.
+    #[error("{context}: {source}")]
+    Context {
+        context: String,
+        #[source]
+        source: Box<VaultError>,
+    },
+}
+
+impl VaultError {
+    /// Wrap this error with a description of the operation that failed
+    pub fn context(self, context: impl Into<String>) -> Self {
+        VaultError::Context {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// The innermost error, beneath any context layers
+    ///
+    /// Match on this rather than on the error itself when deciding how to
+    /// handle a failure, since context can wrap any variant.
+    pub fn root_cause(&self) -> &VaultError {
+        match self {
+            VaultError::Context { source, .. } => source.root_cause(),
+            other => other,
+        }
+    }
+}
+
+/// `.with_context()` for results whose error converts into [`VaultError`]
+pub trait ResultExt<T> {
+    /// Wrap the error with context, building the message only on failure
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> VaultResult<T>;
+}
+
+impl<T, E: Into<VaultError>> ResultExt<T> for Result<T, E> {
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> VaultResult<T> {
+        self.map_err(|e| e.into().context(context()))
+    }
 }
 
 /// Convert from shared::AppError to VaultError
@@ -69,6 +125,10 @@ impl From<shared::AppError> for VaultError {
             shared::AppError::Storage(msg) => VaultError::Storage(msg),
             shared::AppError::Validation(msg) => VaultError::Validation(msg),
             shared::AppError::NotFound(msg) => VaultError::NotFound(msg),
+            e @ shared::AppError::VersionConflict { .. } => VaultError::Conflict(e.to_string()),
+            e @ shared::AppError::Mumps(_) => VaultError::Storage(e.to_string()),
+            e @ shared::AppError::Unavailable(_) => VaultError::Storage(e.to_string()),
+            e @ shared::AppError::Timeout(_) => VaultError::Storage(e.to_string()),
             shared::AppError::Internal(msg) => VaultError::Internal(msg),
         }
     }
@@ -76,10 +136,43 @@ impl From<shared::AppError> for VaultError {
 
 impl From<anyhow::Error> for VaultError {
     fn from(err: anyhow::Error) -> Self {
-        VaultError::Internal(err.to_string())
+        // The alternate format keeps anyhow's context chain in the message
+        VaultError::Internal(format!("{:#}", err))
     }
 }
 
 /// Result type alias
 pub type VaultResult<T> = Result<T, VaultError>;
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_context_keeps_the_chain() {
+        let result: VaultResult<()> = Err(VaultError::Storage("storage unavailable".to_string()));
+        let err = result
+            .with_context(|| "read secret/data/foo")
+            .with_context(|| format!("kv {}", "read"))
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "kv read: read secret/data/foo: Storage error: storage unavailable"
+        );
+        assert!(matches!(err.root_cause(), VaultError::Storage(_)));
+        let source = err.source().unwrap();
+        assert_eq!(source.to_string(), "read secret/data/foo: Storage error: storage unavailable");
+        assert_eq!(source.source().unwrap().to_string(), "Storage error: storage unavailable");
+    }
+
+    #[test]
+    fn test_context_converts_foreign_errors() {
+        let result: Result<Value, serde_json::Error> = serde_json::from_slice(b"{");
+        let err = result.with_context(|| "decode secret/data/foo").unwrap_err();
+        assert!(matches!(err.root_cause(), VaultError::Serialization(_)));
+        assert!(err.to_string().starts_with("decode secret/data/foo: Serialization error"));
+    }
+}
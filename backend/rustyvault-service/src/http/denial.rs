@@ -0,0 +1,65 @@
+//! Responses for missing secrets and denied access
+//!
+//! Shared by the auth middleware, which denies before a handler runs, and
+//! the handlers, so a hidden denial and a missing secret cannot drift apart.
+
+use axum::{http::StatusCode, response::Json};
+use serde_json::{json, Value};
+
+use crate::logical::{DenialMode, DenialPolicy, Operation};
+
+/// Error returned when a secret does not exist
+///
+/// Hidden denials reuse this exact response so they are indistinguishable
+/// from a genuinely missing secret.
+pub fn secret_not_found() -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({"error": "Secret not found"})),
+    )
+}
+
+/// Error returned when the caller is not permitted to perform `operation` on `path`
+///
+/// Depending on the configured denial policy and the caller's policies this
+/// is either a 403 or a response identical to [`secret_not_found`].
+pub fn access_denied(
+    policy: &DenialPolicy,
+    caller_policies: &[String],
+    path: &str,
+    operation: Operation,
+) -> (StatusCode, Json<Value>) {
+    match policy.resolve(caller_policies) {
+        DenialMode::NotFound => secret_not_found(),
+        DenialMode::Forbidden => (
+            StatusCode::FORBIDDEN,
+            Json(json!({
+                "error": "permission denied",
+                "path": path,
+                "operation": format!("{:?}", operation)
+            })),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hidden_denial_matches_not_found() {
+        let policy = DenialPolicy {
+            mode: DenialMode::NotFound,
+            reveal_policies: vec!["admin".to_string()],
+        };
+        let (status, Json(body)) = access_denied(&policy, &["default".to_string()], "secret/db", Operation::Read);
+        let (missing_status, Json(missing_body)) = secret_not_found();
+        assert_eq!(status, missing_status);
+        assert_eq!(body, missing_body);
+
+        let (status, Json(body)) = access_denied(&policy, &["admin".to_string()], "secret/db", Operation::Read);
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["path"], "secret/db");
+        assert_eq!(body["operation"], "Read");
+    }
+}
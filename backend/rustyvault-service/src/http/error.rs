@@ -0,0 +1,96 @@
+//! Mapping from `VaultError` to HTTP responses
+//!
+//! Bodies follow the Vault API shape, `{ "errors": [...] }`, so Vault
+//! clients and SDKs can read them.
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde_json::{json, Value};
+
+use crate::errors::VaultError;
+
+/// Message returned in place of the details of a 5xx error
+const INTERNAL_ERROR_MESSAGE: &str = "internal error";
+
+/// Status code for an error, decided by its root cause
+pub fn status_code(e: &VaultError) -> StatusCode {
+    match e.root_cause() {
+        VaultError::NotFound(_) => StatusCode::NOT_FOUND,
+        VaultError::Auth(_) => StatusCode::UNAUTHORIZED,
+        VaultError::Authorization(_) => StatusCode::FORBIDDEN,
+        VaultError::Validation(_) => StatusCode::BAD_REQUEST,
+        VaultError::Conflict(_) => StatusCode::CONFLICT,
+        VaultError::Sealed | VaultError::ReadOnly => StatusCode::SERVICE_UNAVAILABLE,
+        // Request bodies are parsed before they reach the core, so a
+        // serialization error here means stored data failed to decode
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Error response for handlers returning `(StatusCode, Json<Value>)`
+///
+/// Client errors carry the root cause's message; the context around it names
+/// storage paths, which are not the caller's business. Server errors are
+/// logged in full and answered with a generic message.
+pub fn error_response(e: VaultError) -> (StatusCode, Json<Value>) {
+    let status = status_code(&e);
+    let message = if status == StatusCode::INTERNAL_SERVER_ERROR {
+        tracing::error!("{}", e);
+        INTERNAL_ERROR_MESSAGE.to_string()
+    } else {
+        e.root_cause().to_string()
+    };
+    (status, Json(json!({ "errors": [message] })))
+}
+
+impl IntoResponse for VaultError {
+    fn into_response(self) -> Response {
+        error_response(self).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(e: VaultError) -> (StatusCode, Value) {
+        let (status, Json(body)) = error_response(e);
+        (status, body)
+    }
+
+    #[test]
+    fn test_statuses_follow_the_root_cause() {
+        let cases = [
+            (VaultError::NotFound("secret".to_string()), StatusCode::NOT_FOUND),
+            (VaultError::Authorization("denied".to_string()), StatusCode::FORBIDDEN),
+            (VaultError::Validation("bad limit".to_string()), StatusCode::BAD_REQUEST),
+            (VaultError::Conflict("stale".to_string()), StatusCode::CONFLICT),
+            (VaultError::Sealed, StatusCode::SERVICE_UNAVAILABLE),
+            (VaultError::ReadOnly, StatusCode::SERVICE_UNAVAILABLE),
+            (VaultError::Storage("disk".to_string()), StatusCode::INTERNAL_SERVER_ERROR),
+        ];
+        for (e, status) in cases {
+            assert_eq!(status_code(&e.context("read secret/data/foo")), status);
+        }
+    }
+
+    #[test]
+    fn test_bodies_use_the_vault_shape() {
+        let (status, payload) = body(VaultError::Validation("bad limit".to_string()).context("list secret/data/"));
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(payload, json!({ "errors": ["Validation error: bad limit"] }));
+
+        let (_, payload) = body(VaultError::Sealed);
+        assert_eq!(payload, json!({ "errors": ["Vault is sealed"] }));
+    }
+
+    #[test]
+    fn test_internal_details_stay_in_the_logs() {
+        let e = VaultError::Storage("connection to 10.0.0.5:5432 refused".to_string()).context("read secret/data/foo");
+        let (status, payload) = body(e);
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(payload, json!({ "errors": ["internal error"] }));
+    }
+}
@@ -1,5 +1,6 @@
 //! HTTP handlers for authentication
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use axum::{
@@ -8,16 +9,26 @@ use axum::{
 };
 use serde_json::{json, Value};
 
+use crate::core::namespace::NAMESPACE_META_KEY;
+use crate::errors::VaultError;
+use crate::http::error::error_response;
+use crate::http::middleware::auth_middleware::AuthInfo;
 use crate::http::routes::AppState;
-use crate::modules::auth::{CreateTokenRequest, CreateUserRequest};
+use crate::modules::auth::{CreateTokenRequest, CreateUserRequest, RevokeMode, TokenListFilter};
 
 // ============================================================================
 // Token Handlers
 // ============================================================================
 
 /// Create a new token
+///
+/// The token is a child of the caller's: it can only carry policies the
+/// caller holds, and is revoked with it. Root callers may pass
+/// `no_parent: true` for an orphan token. Tokens created by a namespaced
+/// token belong to the same namespace.
 pub async fn create_token(
     state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
     payload: Json<Value>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     let token_store = state.token_store.as_ref().ok_or_else(|| {
@@ -27,7 +38,7 @@ pub async fn create_token(
         )
     })?;
 
-    let request = CreateTokenRequest {
+    let mut request = CreateTokenRequest {
         display_name: payload
             .get("display_name")
             .and_then(|v| v.as_str())
@@ -53,23 +64,43 @@ pub async fn create_token(
             .unwrap_or(0) as i32,
         meta: payload.get("meta").cloned(),
     };
+    if let Some(namespace) = auth.and_then(|a| a.namespace.as_ref()) {
+        request.meta = Some(namespace.stamp_meta(request.meta.take()).map_err(error_response)?);
+    } else if state.namespace_isolation
+        && !auth.is_some_and(AuthInfo::is_root)
+        && request.meta.as_ref().and_then(|m| m.get(NAMESPACE_META_KEY)).is_some()
+    {
+        return Err(error_response(VaultError::Authorization(
+            "creating tokens in a namespace requires the root policy".to_string(),
+        )));
+    }
 
-    // TODO: Get parent token from request headers and validate
-    match token_store.create_token(&request, None, "auth/token/create").await {
+    let no_parent = payload.get("no_parent").and_then(|v| v.as_bool()).unwrap_or(false);
+    let created = match auth {
+        Some(auth) if no_parent => {
+            if !auth.is_root() {
+                return Err(error_response(VaultError::Authorization(
+                    "creating orphan tokens requires the root policy".to_string(),
+                )));
+            }
+            token_store.create_token(&request, None, "auth/token/create-orphan").await
+        }
+        Some(auth) => token_store.create_child_token(&auth.token, &request, "auth/token/create").await,
+        // Only reachable when token validation is unavailable
+        None => token_store.create_token(&request, None, "auth/token/create").await,
+    };
+    match created {
         Ok((entry, raw_token)) => Ok(Json(json!({
             "auth": {
                 "client_token": raw_token,
-                "accessor": format!("accessor.{}", entry.id),
+                "accessor": entry.accessor(),
                 "policies": entry.policies,
                 "token_ttl": entry.ttl,
                 "renewable": entry.renewable,
                 "expires_at": entry.expires_at
             }
         }))),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )),
+        Err(e) => Err(error_response(e)),
     }
 }
 
@@ -113,10 +144,7 @@ pub async fn lookup_token(
             StatusCode::NOT_FOUND,
             Json(json!({ "error": "token not found or expired" })),
         )),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )),
+        Err(e) => Err(error_response(e)),
     }
 }
 
@@ -150,13 +178,144 @@ pub async fn lookup_self_token(
             StatusCode::NOT_FOUND,
             Json(json!({ "error": "token not found or expired" })),
         )),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+fn accessor_param(payload: &Value) -> Result<&str, (StatusCode, Json<Value>)> {
+    payload
+        .get("accessor")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "accessor is required" })),
+            )
+        })
+}
+
+/// Look up a token by its accessor
+///
+/// Returns the sanitized view, never the token. Namespaced callers only
+/// find tokens of their namespace.
+pub async fn lookup_token_by_accessor(
+    state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
+    payload: Json<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let token_store = state.token_store.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "token store not initialized" })),
+        )
+    })?;
+
+    let namespace = auth.and_then(|a| a.namespace.as_ref());
+    match token_store.lookup_by_accessor(accessor_param(&payload)?, namespace).await {
+        Ok(Some(view)) => Ok(Json(json!({ "data": view }))),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "token not found or expired" })),
+        )),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Revoke a token, and its children, by its accessor
+pub async fn revoke_token_by_accessor(
+    state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
+    payload: Json<Value>,
+) -> Result<StatusCode, (StatusCode, Json<Value>)> {
+    let token_store = state.token_store.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "token store not initialized" })),
+        )
+    })?;
+
+    let accessor = accessor_param(&payload)?;
+    let namespace = auth.and_then(|a| a.namespace.as_ref());
+    match token_store.revoke_by_accessor(accessor, namespace).await {
+        Ok(Some(revoked)) => {
+            tracing::info!(
+                "Token {} revoked by accessor by {} ({} tokens including descendants)",
+                accessor,
+                auth.map_or("unknown", |a| a.token.display_name.as_str()),
+                revoked
+            );
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "token not found or expired" })),
         )),
+        Err(e) => Err(error_response(e)),
     }
 }
 
+/// List the accessors of all live tokens, for auditing
+pub async fn list_token_accessors(
+    state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let token_store = state.token_store.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "token store not initialized" })),
+        )
+    })?;
+
+    let namespace = auth.and_then(|a| a.namespace.as_ref());
+    let accessors = token_store.list_accessors(namespace).await.map_err(error_response)?;
+    Ok(Json(json!({ "data": { "keys": accessors } })))
+}
+
+/// Default page size of the token listing
+const DEFAULT_TOKEN_PAGE_SIZE: usize = 100;
+
+/// List live tokens page by page, for auditing and cleanup
+///
+/// Query parameters: `policy`, `display_name_prefix`, `expiring_within`
+/// (seconds), `limit` and `after` (the previous page's `next_cursor`).
+/// Namespaced callers only see their namespace's tokens.
+pub async fn list_tokens(
+    state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
+    query: HashMap<String, String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let token_store = state.token_store.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "token store not initialized" })),
+        )
+    })?;
+
+    let non_empty = |name: &str| query.get(name).filter(|v| !v.is_empty()).cloned();
+    let number = |name: &str| -> Result<Option<i64>, (StatusCode, Json<Value>)> {
+        non_empty(name)
+            .map(|v| v.parse::<i64>())
+            .transpose()
+            .map_err(|_| error_response(VaultError::Validation(format!("{} must be a number", name))))
+    };
+    let filter = TokenListFilter {
+        policy: non_empty("policy"),
+        display_name_prefix: non_empty("display_name_prefix"),
+        expiring_within: number("expiring_within")?,
+    };
+    let limit = match number("limit")? {
+        Some(limit) => usize::try_from(limit).unwrap_or(0),
+        None => DEFAULT_TOKEN_PAGE_SIZE,
+    };
+
+    let namespace = auth.and_then(|a| a.namespace.as_ref());
+    let page = token_store
+        .list_tokens(&filter, namespace, non_empty("after").as_deref(), limit)
+        .await
+        .map_err(error_response)?;
+    Ok(Json(json!({ "data": page })))
+}
+
 /// Renew a token
 pub async fn renew_token(
     state: Arc<AppState>,
@@ -199,8 +358,13 @@ pub async fn renew_token(
 }
 
 /// Revoke a token
+///
+/// Tokens it spawned are revoked with it. With `orphan: true`, which needs
+/// the root policy, they are kept and become top-level tokens instead. The
+/// response counts the tokens revoked.
 pub async fn revoke_token(
     state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
     payload: Json<Value>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     let token_store = state.token_store.as_ref().ok_or_else(|| {
@@ -220,12 +384,20 @@ pub async fn revoke_token(
             )
         })?;
 
-    match token_store.revoke_token(token).await {
-        Ok(_) => Ok(Json(json!({}))),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )),
+    let mode = if payload.get("orphan").and_then(|v| v.as_bool()).unwrap_or(false) {
+        if !auth.is_some_and(AuthInfo::is_root) {
+            return Err(error_response(VaultError::Authorization(
+                "revoking without descendants requires the root policy".to_string(),
+            )));
+        }
+        RevokeMode::Orphan
+    } else {
+        RevokeMode::Cascade
+    };
+
+    match token_store.revoke_token(token, mode).await {
+        Ok(revoked) => Ok(Json(json!({ "revoked": revoked }))),
+        Err(e) => Err(error_response(e)),
     }
 }
 
@@ -241,12 +413,9 @@ pub async fn revoke_self_token(
         )
     })?;
 
-    match token_store.revoke_token(&token).await {
-        Ok(_) => Ok(Json(json!({}))),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )),
+    match token_store.revoke_token(&token, RevokeMode::Cascade).await {
+        Ok(revoked) => Ok(Json(json!({ "revoked": revoked }))),
+        Err(e) => Err(error_response(e)),
     }
 }
 
@@ -269,10 +438,7 @@ pub async fn list_userpass_users(
         Ok(users) => Ok(Json(json!({
             "keys": users
         }))),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )),
+        Err(e) => Err(error_response(e)),
     }
 }
 
@@ -325,10 +491,7 @@ pub async fn create_userpass_user(
 
     match userpass.create_user(&request).await {
         Ok(_) => Ok(Json(json!({}))),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )),
+        Err(e) => Err(error_response(e)),
     }
 }
 
@@ -357,10 +520,7 @@ pub async fn read_userpass_user(
             StatusCode::NOT_FOUND,
             Json(json!({ "error": "user not found" })),
         )),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )),
+        Err(e) => Err(error_response(e)),
     }
 }
 
@@ -378,10 +538,7 @@ pub async fn delete_userpass_user(
 
     match userpass.delete_user(&username).await {
         Ok(_) => Ok(Json(json!({}))),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )),
+        Err(e) => Err(error_response(e)),
     }
 }
 
@@ -425,3 +582,152 @@ pub async fn userpass_login(
     }
 }
 
+
+// ============================================================================
+// LDAP Handlers
+// ============================================================================
+
+fn ldap_backend(
+    state: &AppState,
+) -> Result<&Arc<crate::modules::auth::LdapBackend>, (StatusCode, Json<Value>)> {
+    state.ldap.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "ldap auth not enabled" })),
+        )
+    })
+}
+
+/// Read the LDAP config (without the bind password)
+pub async fn read_ldap_config(
+    state: Arc<AppState>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    match ldap_backend(&state)?.read_config().await {
+        Ok(Some(config)) => Ok(Json(json!({ "data": config.to_response_data() }))),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "ldap auth is not configured" })),
+        )),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Write the LDAP config
+pub async fn write_ldap_config(
+    state: Arc<AppState>,
+    payload: Json<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let data = payload.0.as_object().cloned().unwrap_or_default();
+    match ldap_backend(&state)?.write_config(&data).await {
+        Ok(config) => Ok(Json(json!({ "data": config.to_response_data() }))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// List LDAP group mappings
+pub async fn list_ldap_groups(
+    state: Arc<AppState>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    match ldap_backend(&state)?.list_groups().await {
+        Ok(groups) => Ok(Json(json!({ "keys": groups }))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Read the policies mapped to an LDAP group
+pub async fn read_ldap_group(
+    state: Arc<AppState>,
+    name: String,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    match ldap_backend(&state)?.get_group(&name).await {
+        Ok(Some(mapping)) => Ok(Json(json!({
+            "data": {
+                "policies": mapping.policies
+            }
+        }))),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "group mapping not found" })),
+        )),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Map an LDAP group to policies; takes effect on the members' next login
+pub async fn write_ldap_group(
+    state: Arc<AppState>,
+    name: String,
+    payload: Json<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let policies: Vec<String> = payload
+        .get("policies")
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    match ldap_backend(&state)?.write_group(&name, policies).await {
+        Ok(_) => Ok(Json(json!({}))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Delete an LDAP group mapping
+pub async fn delete_ldap_group(
+    state: Arc<AppState>,
+    name: String,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    match ldap_backend(&state)?.delete_group(&name).await {
+        Ok(_) => Ok(Json(json!({}))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Login with LDAP credentials
+pub async fn ldap_login(
+    state: Arc<AppState>,
+    username: String,
+    payload: Json<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let ldap = ldap_backend(&state)?;
+
+    let password = payload
+        .get("password")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "password is required" })),
+            )
+        })?;
+
+    match ldap.login(&username, password).await {
+        Ok(response) => Ok(Json(json!({
+            "auth": {
+                "client_token": response.client_token,
+                "accessor": response.accessor,
+                "policies": response.policies,
+                "token_ttl": response.token_ttl,
+                "renewable": response.renewable,
+                "metadata": {
+                    "username": username,
+                    "groups": response.groups
+                }
+            }
+        }))),
+        Err(e) => {
+            tracing::warn!("LDAP login failed for {}: {}", username, e);
+            Err(match e.root_cause() {
+                // Don't tell callers whether the user or the directory was at fault
+                crate::errors::VaultError::Auth(_) => (
+                    StatusCode::UNAUTHORIZED,
+                    Json(json!({ "error": "invalid username or password" })),
+                ),
+                _ => error_response(e),
+            })
+        }
+    }
+}
@@ -8,12 +8,39 @@ use axum::{
 };
 use serde_json::{json, Value};
 
+use crate::core::namespace;
+use crate::errors::VaultError;
+use crate::http::error::error_response;
+use crate::http::middleware::auth_middleware::AuthInfo;
 use crate::http::routes::AppState;
-use crate::modules::policy::Policy;
+use crate::logical::{Operation, Request};
+use crate::modules::policy::lint::Severity;
+
+/// Stored name of a policy named by the caller
+///
+/// Namespaced callers name their namespace's policies. Anyone else touching
+/// a namespace's policy directly must hold the root policy.
+fn scoped_policy_name(
+    auth: Option<&AuthInfo>,
+    name: &str,
+) -> Result<String, (StatusCode, Json<Value>)> {
+    if let Some(namespace) = auth.and_then(|a| a.namespace.as_ref()) {
+        return Ok(namespace.scope_policy(name));
+    }
+    if namespace::is_namespace_policy(name) && !auth.is_some_and(AuthInfo::is_root) {
+        return Err(error_response(VaultError::Authorization(
+            "managing a namespace's policies requires the root policy".to_string(),
+        )));
+    }
+    Ok(name.to_string())
+}
 
 /// List all policies
+///
+/// Namespaced callers only see their namespace's policies.
 pub async fn list_policies(
     state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     let policy_store = state.policy_store.as_ref().ok_or_else(|| {
         (
@@ -23,19 +50,25 @@ pub async fn list_policies(
     })?;
 
     match policy_store.list_policies().await {
-        Ok(policies) => Ok(Json(json!({
-            "keys": policies
-        }))),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )),
+        Ok(policies) => {
+            let policies: Vec<String> = match auth.and_then(|a| a.namespace.as_ref()) {
+                Some(namespace) => policies.iter()
+                    .filter_map(|name| namespace.unscope_policy(name).map(String::from))
+                    .collect(),
+                None => policies,
+            };
+            Ok(Json(json!({
+                "keys": policies
+            })))
+        }
+        Err(e) => Err(error_response(e)),
     }
 }
 
 /// Read a policy
 pub async fn read_policy(
     state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
     name: String,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     let policy_store = state.policy_store.as_ref().ok_or_else(|| {
@@ -45,9 +78,10 @@ pub async fn read_policy(
         )
     })?;
 
-    match policy_store.get_policy(&name).await {
+    let namespace = auth.and_then(|a| a.namespace.as_ref());
+    match policy_store.get_policy(&scoped_policy_name(auth, &name)?).await {
         Ok(Some(policy)) => Ok(Json(json!({
-            "name": policy.name,
+            "name": namespace.and_then(|ns| ns.unscope_policy(&policy.name)).unwrap_or(&policy.name),
             "policy": policy.raw,
             "type": policy.policy_type.to_string()
         }))),
@@ -55,16 +89,14 @@ pub async fn read_policy(
             StatusCode::NOT_FOUND,
             Json(json!({ "error": "policy not found" })),
         )),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )),
+        Err(e) => Err(error_response(e)),
     }
 }
 
 /// Create or update a policy
 pub async fn write_policy(
     state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
     name: String,
     payload: Json<Value>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
@@ -86,30 +118,42 @@ pub async fn write_policy(
             )
         })?;
 
-    // Parse the policy
-    let mut policy = Policy::from_json(policy_content).map_err(|e| {
+    // Parse the policy, turning away oversized ones before deserializing
+    let mut policy = policy_store.parse_policy(policy_content).map_err(|e| {
         (
             StatusCode::BAD_REQUEST,
             Json(json!({ "error": format!("invalid policy: {}", e) })),
         )
     })?;
 
-    policy.name = name;
+    policy.name = scoped_policy_name(auth, &name)?;
+    let report = policy_store.lint(&policy);
 
     // Save the policy
     match policy_store.set_policy(&policy).await {
-        Ok(_) => Ok(Json(json!({}))),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )),
+        Ok(_) if report.findings.is_empty() => Ok(Json(json!({}))),
+        Ok(_) => {
+            tracing::warn!(
+                policy = %policy.name,
+                findings = report.findings.len(),
+                highest_severity = ?report.highest_severity,
+                "Policy written with lint findings"
+            );
+            Ok(Json(json!({ "warnings": report.findings })))
+        }
+        Err(e) => Err(error_response(e)),
     }
 }
 
-/// Delete a policy
-pub async fn delete_policy(
+/// Lint a policy without writing it
+///
+/// Takes the `policy` document, or the `name` of a stored policy, and
+/// returns the findings with the highest severity. With `fail_on` set to a
+/// severity, `passed` tells whether nothing that severe was found, for CI.
+pub async fn lint_policy(
     state: Arc<AppState>,
-    name: String,
+    auth: Option<&AuthInfo>,
+    payload: Json<Value>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     let policy_store = state.policy_store.as_ref().ok_or_else(|| {
         (
@@ -118,21 +162,66 @@ pub async fn delete_policy(
         )
     })?;
 
-    match policy_store.delete_policy(&name).await {
-        Ok(_) => Ok(Json(json!({}))),
-        Err(e) => {
-            if e.to_string().contains("cannot delete") {
-                Err((
+    let fail_on = match payload.get("fail_on").and_then(|v| v.as_str()) {
+        Some(severity) => Some(severity.parse::<Severity>().map_err(|e| {
+            (StatusCode::BAD_REQUEST, Json(json!({ "error": e })))
+        })?),
+        None => None,
+    };
+
+    let policy_content = payload.get("policy").and_then(|v| v.as_str());
+    let name = payload.get("name").and_then(|v| v.as_str());
+    let report = match (policy_content, name) {
+        (Some(policy_content), _) => {
+            let policy = policy_store.parse_policy(policy_content).map_err(|e| {
+                (
                     StatusCode::BAD_REQUEST,
-                    Json(json!({ "error": e.to_string() })),
-                ))
-            } else {
-                Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({ "error": e.to_string() })),
+                    Json(json!({ "error": format!("invalid policy: {}", e) })),
+                )
+            })?;
+            policy_store.lint(&policy)
+        }
+        (None, Some(name)) => match policy_store.get_policy(&scoped_policy_name(auth, name)?).await {
+            Ok(Some(policy)) => policy_store.lint(&policy),
+            Ok(None) => {
+                return Err((
+                    StatusCode::NOT_FOUND,
+                    Json(json!({ "error": "policy not found" })),
                 ))
             }
+            Err(e) => return Err(error_response(e)),
+        },
+        (None, None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "policy or name is required" })),
+            ))
         }
+    };
+
+    let mut body = json!(report);
+    if let Some(threshold) = fail_on {
+        body["passed"] = json!(report.passes(threshold));
+    }
+    Ok(Json(body))
+}
+
+/// Delete a policy
+pub async fn delete_policy(
+    state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
+    name: String,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let policy_store = state.policy_store.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "policy store not initialized" })),
+        )
+    })?;
+
+    match policy_store.delete_policy(&scoped_policy_name(auth, &name)?).await {
+        Ok(_) => Ok(Json(json!({}))),
+        Err(e) => Err(error_response(e)),
     }
 }
 
@@ -173,10 +262,99 @@ pub async fn check_capabilities(
             "capabilities": capabilities,
             "path": path
         }))),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )),
+        Err(e) => Err(error_response(e)),
     }
 }
 
+
+/// Explain how the ACL decides an operation on a path
+///
+/// Takes either a `token` (whose policies are used) or a list of `policies`,
+/// plus the `path` and `operation` to dry-run. Nothing is executed.
+pub async fn explain_policy(
+    state: Arc<AppState>,
+    payload: Json<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let policy_store = state.policy_store.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "policy store not initialized" })),
+        )
+    })?;
+
+    let path = payload
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "path is required" })),
+            )
+        })?;
+
+    let operation = match payload.get("operation").and_then(|v| v.as_str()) {
+        Some(op @ ("read" | "write" | "delete" | "list")) => Operation::from(op),
+        Some(op) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": format!("unknown operation '{}'", op) })),
+            ))
+        }
+        None => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "operation is required" })),
+            ))
+        }
+    };
+
+    let policies: Vec<String> = match payload.get("token").and_then(|v| v.as_str()) {
+        Some(token) => {
+            let token_store = state.token_store.as_ref().ok_or_else(|| {
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(json!({ "error": "token store not initialized" })),
+                )
+            })?;
+            match token_store.lookup_token(token).await {
+                Ok(Some(entry)) => entry.policies,
+                Ok(None) => {
+                    return Err((
+                        StatusCode::BAD_REQUEST,
+                        Json(json!({ "error": "invalid or expired token" })),
+                    ))
+                }
+                Err(e) => return Err(error_response(e)),
+            }
+        }
+        None => payload
+            .get("policies")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error": "token or policies is required" })),
+                )
+            })?,
+    };
+
+    let req = Request {
+        path: path.to_string(),
+        operation,
+        ..Default::default()
+    };
+
+    match policy_store.explain(&policies, &req).await {
+        Ok((explanation, missing)) => {
+            let mut body = json!(explanation);
+            body["missing_policies"] = json!(missing);
+            Ok(Json(body))
+        }
+        Err(e) => Err(error_response(e)),
+    }
+}
@@ -1,22 +1,31 @@
 //! Secrets operation handlers
 
 use axum::{
-    extract::{Path, State},
-    http::{StatusCode, Method},
-    response::Json,
+    extract::{Extension, Path, State},
+    http::{header, StatusCode, Method},
+    response::{IntoResponse, Json, Response},
 };
 use serde_json::{json, Value, Map};
+use std::collections::HashMap;
 use std::sync::Arc;
+use crate::http::denial::secret_not_found;
+use crate::http::middleware::audit_middleware::audit_failed;
+use crate::http::error::error_response;
 use crate::http::routes::AppState;
-use crate::logical::{Request as LogicalRequest, Operation};
+use crate::http::middleware::auth_middleware::{self, AuthInfo};
+use crate::logical::{Request as LogicalRequest, Operation, CacheHint};
 
 /// Handle secret operations by routing through core
+///
+/// Returns the body along with the backend's cache hint for it.
 async fn handle_secret_request(
     state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
     method: Method,
     path: String,
     data: Option<Map<String, Value>>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    fields: Option<Vec<String>>,
+) -> Result<(CacheHint, Json<Value>), (StatusCode, Json<Value>)> {
     // Determine operation - LIST is typically GET with ?list=true or trailing /
     let operation = match method {
         Method::GET => {
@@ -40,80 +49,156 @@ async fn handle_secret_request(
         Operation::Read => LogicalRequest::new_read_request(&path),
         Operation::Write => LogicalRequest::new_write_request(&path, data),
         Operation::Delete => LogicalRequest::new_delete_request(&path, data),
-        Operation::List => LogicalRequest::new_list_request(&path),
+        Operation::List => {
+            let mut req = LogicalRequest::new_list_request(&path);
+            // Pagination parameters (`limit`, `after`) travel as request data
+            req.data = data;
+            req
+        }
     };
+    req.fields = fields;
+    req.namespace = auth.and_then(|a| a.namespace.clone());
+    req.client_cert = auth.and_then(|a| a.client_cert.clone());
+    req.context = auth.map(|a| a.request_context(&req.id));
+
+    // Nothing is handled (or served) without an audit record in blocking mode
+    if let Some(audit) = state.audit.as_ref() {
+        audit.log_request(auth, &req).await.map_err(|_| audit_failed())?;
+    }
 
     // Route through core
-    let response = state.core.handle_request(&mut req).await
-        .map_err(|e| (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": e.to_string()})),
-        ))?;
+    let result = state.core.handle_request(&mut req).await;
+
+    if let Some(audit) = state.audit.as_ref() {
+        audit.log_response(auth, &req, &result).await.map_err(|_| audit_failed())?;
+    }
+
+    let response = result
+        .map_err(error_response)?;
 
     match response {
         Some(resp) => {
             let mut result = Map::new();
+            if let Some(lease) = resp.lease {
+                result.insert("lease_id".to_string(), Value::String(lease.lease_id));
+                result.insert("lease_duration".to_string(), json!(lease.lease_duration));
+                result.insert("renewable".to_string(), Value::Bool(lease.renewable));
+            }
             if let Some(data) = resp.data {
                 result.insert("data".to_string(), Value::Object(data));
             }
-            Ok(Json(Value::Object(result)))
+            if !resp.warnings.is_empty() {
+                result.insert("warnings".to_string(), json!(resp.warnings));
+            }
+            Ok((resp.cache, Json(Value::Object(result))))
         }
-        None => Err((
-            StatusCode::NOT_FOUND,
-            Json(json!({"error": "Secret not found"})),
-        )),
+        None => Err(secret_not_found()),
+    }
+}
+
+/// Send `body` with the `Cache-Control` header its cache hint asks for
+fn cacheable(hint: CacheHint, body: Json<Value>) -> Response {
+    ([(header::CACHE_CONTROL, hint.header_value())], body).into_response()
+}
+
+/// Parse a comma-separated `fields` query parameter into a field projection
+fn parse_fields(raw: Option<&String>) -> Option<Vec<String>> {
+    raw.map(|s| {
+        s.split(',')
+            .map(|f| f.trim().to_string())
+            .filter(|f| !f.is_empty())
+            .collect()
+    })
+}
+
+/// Extract explicit list pagination parameters (`limit`, `after`) from the query
+fn list_page_params(query: &HashMap<String, String>) -> Option<Map<String, Value>> {
+    let limit = query.get("limit")?;
+    let mut params = Map::new();
+    params.insert("limit".to_string(), json!(limit.parse::<u64>().unwrap_or(0)));
+    if let Some(after) = query.get("after") {
+        params.insert("after".to_string(), Value::String(after.clone()));
     }
+    Some(params)
+}
+
+/// Request data for a GET: list pagination plus a subkeys `depth`
+fn read_params(query: &HashMap<String, String>) -> Option<Map<String, Value>> {
+    let Some(depth) = query.get("depth") else {
+        return list_page_params(query);
+    };
+    let mut params = list_page_params(query).unwrap_or_default();
+    let depth = depth.parse::<u64>().map_or_else(|_| Value::String(depth.clone()), Value::from);
+    params.insert("depth".to_string(), depth);
+    Some(params)
 }
 
 /// Read secret endpoint (with State extractor)
 pub async fn read_secret(
     State(state): State<Arc<AppState>>,
+    auth: Option<Extension<AuthInfo>>,
     Path(path): Path<String>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    read_secret_with_state(state, path).await
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    read_secret_with_state(state, auth.as_deref(), path, HashMap::new()).await
 }
 
 /// Read secret endpoint (direct state parameter)
+///
+/// Query parameters:
+/// - `fields`: comma-separated keys to restrict the returned secret data to
+/// - `limit`, `after`: paginate a list request (path ending in `/`)
+/// - `depth`: levels of nesting to walk in a `subkeys/` read
 pub async fn read_secret_with_state(
     state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
     path: String,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    handle_secret_request(state, Method::GET, format!("secret/{}", path), None).await
+    query: HashMap<String, String>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let fields = parse_fields(query.get("fields"));
+    let params = read_params(&query);
+    let (hint, body) = handle_secret_request(state, auth, Method::GET, format!("secret/{}", path), params, fields).await?;
+    Ok(cacheable(hint, body))
 }
 
 /// Write secret endpoint (with State extractor)
 pub async fn write_secret(
     State(state): State<Arc<AppState>>,
+    auth: Option<Extension<AuthInfo>>,
     Path(path): Path<String>,
     payload: axum::extract::Json<Value>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    write_secret_with_state(state, path, payload).await
+    write_secret_with_state(state, auth.as_deref(), path, payload).await
 }
 
 /// Write secret endpoint (direct state parameter)
 pub async fn write_secret_with_state(
     state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
     path: String,
     payload: axum::extract::Json<Value>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     let data = payload.as_object().cloned();
-    handle_secret_request(state, Method::POST, format!("secret/{}", path), data).await
+    // Writes are never cacheable; the response middleware marks them no-store
+    let (_, body) = handle_secret_request(state, auth, Method::POST, format!("secret/{}", path), data, None).await?;
+    Ok(body)
 }
 
 /// Delete secret endpoint (with State extractor)
 pub async fn delete_secret(
     State(state): State<Arc<AppState>>,
+    auth: Option<Extension<AuthInfo>>,
     Path(path): Path<String>,
 ) -> Result<StatusCode, (StatusCode, Json<Value>)> {
-    delete_secret_with_state(state, path).await
+    delete_secret_with_state(state, auth.as_deref(), path).await
 }
 
 /// Delete secret endpoint (direct state parameter)
 pub async fn delete_secret_with_state(
     state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
     path: String,
 ) -> Result<StatusCode, (StatusCode, Json<Value>)> {
-    match handle_secret_request(state, Method::DELETE, format!("secret/{}", path), None).await {
+    match handle_secret_request(state, auth, Method::DELETE, format!("secret/{}", path), None, None).await {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err(e),
     }
@@ -122,22 +207,242 @@ pub async fn delete_secret_with_state(
 /// List secrets endpoint (with State extractor)
 pub async fn list_secrets(
     State(state): State<Arc<AppState>>,
+    auth: Option<Extension<AuthInfo>>,
     Path(path): Path<String>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    list_secrets_with_state(state, path).await
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    list_secrets_with_state(state, auth.as_deref(), path).await
 }
 
 /// List secrets endpoint (direct state parameter)
 pub async fn list_secrets_with_state(
     state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
     path: String,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Response, (StatusCode, Json<Value>)> {
     // For list, ensure path ends with /
     let list_path = if path.ends_with('/') {
         format!("secret/{}", path)
     } else {
         format!("secret/{}/", path)
     };
-    handle_secret_request(state, Method::GET, list_path, None).await
+    let (hint, body) = handle_secret_request(state, auth, Method::GET, list_path, None, None).await?;
+    Ok(cacheable(hint, body))
+}
+
+/// Most paths one batch read may name
+pub const MAX_BATCH_READ_PATHS: usize = 100;
+
+/// Batch read endpoint, `POST /v1/sys/secrets/batch-read`
+///
+/// Served under `sys/` so it cannot shadow a secret. Reads every path in `{"paths": [...]}`, each relative to `secret/` like
+/// the single-secret routes, and returns a map of path to its response body,
+/// or to `{"status", "error"}` when that read failed. Each path is
+/// authorized and audited on its own exactly as a GET of it would be, so a
+/// path the caller may not read fails alone (with the 403, or 404, the
+/// denial policy gives) without failing the batch.
+pub async fn batch_read_with_state(
+    state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
+    payload: Value,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let paths: Vec<String> = payload
+        .get("paths")
+        .cloned()
+        .and_then(|paths| serde_json::from_value(paths).ok())
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, Json(json!({"error": "paths must be a list of strings"}))))?;
+    if paths.len() > MAX_BATCH_READ_PATHS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": format!("at most {} paths may be read in one batch", MAX_BATCH_READ_PATHS)})),
+        ));
+    }
+
+    let mut results = Map::new();
+    for path in paths {
+        if results.contains_key(&path) {
+            continue;
+        }
+        let result = batch_read_one(state.clone(), auth, &path).await;
+        let body = match result {
+            Ok(Json(body)) => body,
+            Err((status, Json(error))) => {
+                let mut entry = error.as_object().cloned().unwrap_or_default();
+                entry.insert("status".to_string(), json!(status.as_u16()));
+                Value::Object(entry)
+            }
+        };
+        results.insert(path, body);
+    }
+    Ok(Json(json!({ "data": results })))
+}
+
+/// One read of a batch, authorized as the auth middleware would a GET of it
+async fn batch_read_one(
+    state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
+    path: &str,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if path.is_empty() || path.ends_with('/') {
+        return Err((StatusCode::BAD_REQUEST, Json(json!({"error": "batch reads cannot list"}))));
+    }
+    // Refused before the ACL check, which matches the path as written
+    if path.split('/').any(|segment| segment.is_empty() || segment == "." || segment == "..") {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "paths cannot contain empty, '.' or '..' segments"})),
+        ));
+    }
+    let vault_path = format!("secret/{}", path);
+    if let Some(auth) = auth {
+        auth_middleware::check_namespace(&auth.token, auth.namespace.as_ref(), &vault_path)?;
+        auth_middleware::check_acl(&state, &auth.token, auth.namespace.as_ref(), &vault_path, Operation::Read).await?;
+    }
+    let (_, body) = handle_secret_request(state, auth, Method::GET, vault_path, None, None).await?;
+    Ok(body)
+}
+
+/// Generic logical request endpoint for engines mounted at arbitrary paths
+pub async fn logical_request_with_state(
+    state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
+    method: Method,
+    path: String,
+    payload: Option<Value>,
+    query: HashMap<String, String>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let fields = parse_fields(query.get("fields"));
+    let data = if method == Method::GET {
+        read_params(&query)
+    } else {
+        payload.and_then(|v| v.as_object().cloned())
+    };
+    let (hint, body) = handle_secret_request(state, auth, method, path, data, fields).await?;
+    Ok(cacheable(hint, body))
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::VaultCore;
+    use crate::logical::DenialPolicy;
+    use crate::modules::auth::TokenEntry;
+    use crate::modules::kv::KvBackend;
+    use crate::modules::policy::{Policy, PolicyStore};
+    use crate::storage::physical_inmem::InMemoryBackend;
+    use shared::infrastructure::encryption::KdfParams;
+
+    /// Unsealed core with KV at `secret/`, where the `reader` policy may
+    /// read `secret/app/*` only
+    async fn state() -> Arc<AppState> {
+        let core = VaultCore::with_kdf(
+            Arc::new(InMemoryBackend::new()),
+            KdfParams::Argon2id { memory_kib: 64, iterations: 1, parallelism: 1 },
+        );
+        let init = core.init(&crate::core::SealConfig { secret_shares: 1, secret_threshold: 1 }).await.unwrap();
+        core.unseal(&init.secret_shares[0]).await.unwrap();
+        let kv = KvBackend::new(Arc::new(InMemoryBackend::new()), "secret".to_string());
+        core.router.mount("secret", "kv", Arc::new(kv)).unwrap();
+        for path in ["secret/app/db", "secret/other/db"] {
+            let data = json!({ "password": "s3cret" }).as_object().cloned();
+            core.handle_request(&mut LogicalRequest::new_write_request(path, data)).await.unwrap();
+        }
+
+        let pool = sqlx::PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let policy_store = PolicyStore::new(pool);
+        let mut reader = Policy::from_json(r#"{"path": {"secret/app/*": {"capabilities": ["read"]}}}"#).unwrap();
+        reader.name = "reader".to_string();
+        policy_store.cache_policy(reader);
+
+        Arc::new(AppState {
+            core: Arc::new(core),
+            policy_store: Some(Arc::new(policy_store)),
+            token_store: None,
+            userpass: None,
+            ldap: None,
+            mounts: None,
+            leases: None,
+            denial_policy: DenialPolicy::default(),
+            namespace_isolation: false,
+            idempotency: None,
+            reaper: None,
+            audit: None,
+            ha: crate::config::HaConfig { step_down_hold_secs: 0, drain_timeout_secs: 0 },
+            snapshots: None,
+            seal_quorum: Arc::new(crate::core::SealQuorum::new(1, std::time::Duration::from_secs(60))),
+            generate_root: Arc::new(crate::core::GenerateRoot::new(24, std::time::Duration::from_secs(60))),
+            generated_root_ttl_secs: 0,
+            health_crypto_selftest: false,
+        })
+    }
+
+    fn reader() -> AuthInfo {
+        AuthInfo {
+            token: TokenEntry {
+                id: uuid::Uuid::new_v4(),
+                token_hash: String::new(),
+                display_name: "reader".to_string(),
+                policies: vec!["reader".to_string()],
+                parent: None,
+                ttl: 0,
+                expires_at: None,
+                created_at: chrono::Utc::now(),
+                last_used_at: None,
+                num_uses: 0,
+                path: String::new(),
+                meta: None,
+                renewable: false,
+                entity_id: None,
+            },
+            raw_token: String::new(),
+            namespace: None,
+            client_cert: None,
+            client_ip: None,
+            request_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_read_is_limited() {
+        let state = state().await;
+        let paths: Vec<String> = (0..=MAX_BATCH_READ_PATHS).map(|i| format!("app/{}", i)).collect();
+        let (status, _) = batch_read_with_state(state.clone(), Some(&reader()), json!({ "paths": paths }))
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = batch_read_with_state(state, Some(&reader()), json!({ "paths": "app/db" }))
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_batch_read_authorizes_each_path() {
+        let state = state().await;
+        let payload = json!({ "paths": ["app/db", "other/db", "app/missing"] });
+        let Json(body) = batch_read_with_state(state, Some(&reader()), payload).await.unwrap();
+        let results = &body["data"];
+
+        assert_eq!(results["app/db"]["data"]["data"]["password"], "s3cret");
+        assert!(results["app/db"].get("status").is_none());
+        // A denied path fails alone, shaped like the error a GET of it returns
+        assert_eq!(results["other/db"]["status"], 403);
+        assert_eq!(results["other/db"]["error"], "permission denied");
+        assert_eq!(results["other/db"]["path"], "secret/other/db");
+        assert!(results["other/db"].get("data").is_none());
+        assert_eq!(results["app/missing"]["status"], 404);
+        assert_eq!(results["app/missing"]["error"], "Secret not found");
+    }
+
+    #[tokio::test]
+    async fn test_batch_read_refuses_dot_and_empty_segments() {
+        let state = state().await;
+        // Each would match `secret/app/*` as written
+        let paths = ["app/../other/db", "app/./db", "app//db", "/app/db", "app/"];
+        let Json(body) = batch_read_with_state(state, Some(&reader()), json!({ "paths": paths })).await.unwrap();
+        for path in paths {
+            assert_eq!(body["data"][path]["status"], 400, "{}", path);
+            assert!(body["data"][path].get("data").is_none(), "{}", path);
+        }
+    }
+}
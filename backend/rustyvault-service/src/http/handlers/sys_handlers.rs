@@ -1,30 +1,99 @@
 //! System operation handlers
 
 use axum::{
-    extract::State,
+    extract::{Extension, State},
     http::StatusCode,
-    response::Json,
+    response::{IntoResponse, Json, Response},
 };
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::sync::Arc;
 use base64::Engine;
+use crate::core::namespace;
+use crate::core::{GenerateRootVote, SealVote};
+use crate::errors::{VaultError, VaultResult};
+use crate::http::error::error_response;
+use crate::http::middleware::audit_middleware::audit_failed;
+use crate::http::middleware::auth_middleware::AuthInfo;
 use crate::http::routes::AppState;
+use crate::logical::{Request as LogicalRequest, Response as LogicalResponse};
 use crate::modules::auth::CreateTokenRequest;
 
 /// Health check endpoint
-pub async fn health_check() -> Result<Json<Value>, StatusCode> {
-    Ok(Json(json!({
-        "initialized": false,
-        "sealed": true,
-        "standby": false,
+///
+/// Status codes follow Vault so load balancers can route on them: 200
+/// active, 429 standby, 501 not initialized, 503 sealed. As in Vault,
+/// `activecode`, `standbycode`, `uninitcode` and `sealedcode` override
+/// them and `standbyok` answers for a standby as if it were active, so a
+/// liveness probe can accept a node that is up but not yet unsealed. With
+/// `VAULT_HEALTH_CRYPTO_SELFTEST` a failed crypto self-test is a 503 too,
+/// so a node whose crypto is broken stops receiving traffic.
+pub async fn health_check(
+    state: Arc<AppState>,
+    query: HashMap<String, String>,
+) -> (StatusCode, Json<Value>) {
+    let status = match state.core.seal_status().await {
+        Ok(status) => status,
+        Err(e) => return error_response(e),
+    };
+
+    let crypto_selftest = state.health_crypto_selftest
+        .then(|| crate::core::selftest::run(&state.core.barrier).passed);
+
+    let code = if crypto_selftest == Some(false) {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        health_code(&query, status.initialized, status.sealed, status.standby)
+    };
+
+    let mut body = json!({
+        "initialized": status.initialized,
+        "sealed": status.sealed,
+        "standby": status.standby,
         "performance_standby": false,
         "replication_performance_mode": "disabled",
         "replication_dr_mode": "disabled",
         "server_time_utc": chrono::Utc::now().timestamp(),
-        "version": "0.1.0",
-        "cluster_name": "",
-        "cluster_id": ""
-    })))
+        "version": status.version,
+        "cluster_name": status.cluster_name,
+        "cluster_id": status.cluster_id
+    });
+    if let Some(passed) = crypto_selftest {
+        body["crypto_selftest"] = json!(passed);
+    }
+    (code, Json(body))
+}
+
+/// Status code for a node in the given state, honouring the query overrides
+fn health_code(query: &HashMap<String, String>, initialized: bool, sealed: bool, standby: bool) -> StatusCode {
+    let code = |name: &str, default: StatusCode| {
+        query.get(name)
+            .and_then(|v| v.parse::<u16>().ok())
+            .and_then(|v| StatusCode::from_u16(v).ok())
+            .unwrap_or(default)
+    };
+    // A bare `?standbyok` counts as true, as in Vault
+    let standby_ok = query.get("standbyok").is_some_and(|v| v.is_empty() || v == "true");
+
+    if !initialized {
+        code("uninitcode", StatusCode::NOT_IMPLEMENTED)
+    } else if sealed {
+        code("sealedcode", StatusCode::SERVICE_UNAVAILABLE)
+    } else if standby && !standby_ok {
+        code("standbycode", StatusCode::TOO_MANY_REQUESTS)
+    } else {
+        code("activecode", StatusCode::OK)
+    }
+}
+
+/// Run the crypto self-test and report each primitive
+///
+/// 200 when nothing failed, 503 otherwise. Checks that need the barrier key
+/// are reported as skipped while sealed.
+pub async fn crypto_selftest(state: Arc<AppState>) -> (StatusCode, Json<Value>) {
+    let report = crate::core::selftest::run(&state.core.barrier);
+    let code = if report.passed { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (code, Json(json!(report)))
 }
 
 /// Seal status endpoint (with State extractor)
@@ -38,43 +107,138 @@ pub async fn seal_status(
 pub async fn seal_status_with_state(
     state: Arc<AppState>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let sealed = state.core.is_sealed();
-    let seal_config = state.core.seal_config()
-        .unwrap_or(crate::core::SealConfig {
-            secret_shares: 5,
-            secret_threshold: 3,
-        });
-    
-    Ok(Json(json!({
-        "sealed": sealed,
-        "t": seal_config.secret_threshold,
-        "n": seal_config.secret_shares,
-        "progress": 0,
-        "nonce": "",
-        "version": "0.1.0",
-        "cluster_name": "",
-        "cluster_id": "",
-        "recovery_seal": false,
-        "storage_type": "file"
-    })))
+    let status = state.core.seal_status().await
+        .map_err(error_response)?;
+    Ok(Json(json!(status)))
+}
+
+/// Step-down endpoint (direct state parameter)
+///
+/// Stops serving requests for the configured hold period so traffic fails
+/// over to another replica, after draining requests already in flight.
+pub async fn step_down_with_state(
+    state: Arc<AppState>,
+) -> Result<StatusCode, (StatusCode, Json<Value>)> {
+    let result = state.core
+        .step_down(
+            std::time::Duration::from_secs(state.ha.step_down_hold_secs),
+            std::time::Duration::from_secs(state.ha.drain_timeout_secs),
+        )
+        .await
+        .map_err(|e| (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        ))?;
+
+    if result.drained {
+        tracing::info!("Stepped down for {}s after draining in-flight requests", state.ha.step_down_hold_secs);
+    } else {
+        tracing::warn!(
+            "Stepped down with {} requests still in flight after {}s drain timeout",
+            result.in_flight,
+            state.ha.drain_timeout_secs
+        );
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Whether writes are frozen
+pub async fn read_only_status(state: Arc<AppState>) -> Json<Value> {
+    Json(json!({ "data": { "enabled": state.core.is_read_only() } }))
+}
+
+/// Freeze or unfreeze logical writes without sealing
+///
+/// Takes `{"enabled": bool}`. Reads keep being served; see
+/// `VaultCore::set_read_only` for what is covered.
+pub async fn set_read_only(
+    state: Arc<AppState>,
+    payload: axum::extract::Json<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let enabled = payload.get("enabled")
+        .and_then(|v| v.as_bool())
+        .ok_or_else(|| (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Missing boolean 'enabled' field"})),
+        ))?;
+
+    let was_enabled = state.core.set_read_only(enabled);
+    if enabled != was_enabled {
+        tracing::warn!(target: "security", "Read-only mode {}", if enabled { "enabled" } else { "lifted" });
+    }
+    Ok(Json(json!({ "data": { "enabled": enabled } })))
 }
 
 /// Seal endpoint (with State extractor)
 pub async fn seal(
     State(state): State<Arc<AppState>>,
-) -> Result<StatusCode, (StatusCode, Json<Value>)> {
-    seal_with_state(state).await
+    auth: Option<Extension<AuthInfo>>,
+    payload: Option<Json<Value>>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    seal_with_state(state, auth.as_deref(), payload.map(|p| p.0)).await
 }
 
 /// Seal endpoint (direct state parameter)
+///
+/// With a seal quorum configured this records the caller's confirmation and
+/// answers 202 with the attempt's progress until enough distinct operators
+/// have confirmed. Later confirmations pass the attempt's `nonce` in the body.
 pub async fn seal_with_state(
     state: Arc<AppState>,
-) -> Result<StatusCode, (StatusCode, Json<Value>)> {
+    auth: Option<&AuthInfo>,
+    payload: Option<Value>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    if state.seal_quorum.enabled() {
+        // Tokens minted with a display name are credited to that name, so one
+        // person's several login tokens count once
+        let operator = auth
+            .map(|a| match a.token.display_name.as_str() {
+                "" => a.token.id.to_string(),
+                name => name.to_string(),
+            })
+            .ok_or_else(|| error_response(VaultError::Auth("sealing requires a token".to_string())))?;
+        let nonce = payload.as_ref().and_then(|p| p.get("nonce")).and_then(|v| v.as_str());
+        match state.seal_quorum.confirm(&operator, nonce).map_err(error_response)? {
+            SealVote::Pending(progress) => {
+                tracing::warn!(
+                    "Seal confirmed by {} ({}/{})",
+                    operator,
+                    progress.progress,
+                    progress.required
+                );
+                return Ok((StatusCode::ACCEPTED, Json(json!(progress))).into_response());
+            }
+            SealVote::Reached => tracing::warn!("Seal quorum reached, sealing"),
+        }
+    }
+
     state.core.seal().await
-        .map_err(|e| (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": e.to_string()})),
-        ))?;
+        .map_err(error_response)?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Progress of the seal attempt awaiting quorum
+pub async fn seal_progress_with_state(
+    state: Arc<AppState>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    match state.seal_quorum.progress() {
+        Some(progress) => Ok(Json(json!(progress))),
+        None => Err(error_response(VaultError::NotFound("no seal in progress".to_string()))),
+    }
+}
+
+/// Abandon the seal attempt awaiting quorum
+pub async fn cancel_seal_with_state(
+    state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
+) -> Result<StatusCode, (StatusCode, Json<Value>)> {
+    if !state.seal_quorum.cancel() {
+        return Err(error_response(VaultError::NotFound("no seal in progress".to_string())));
+    }
+    tracing::info!(
+        "Seal attempt cancelled by {}",
+        auth.map_or("unknown", |a| a.token.display_name.as_str())
+    );
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -105,16 +269,274 @@ pub async fn unseal_with_state(
         ))?;
 
     let unsealed = state.core.unseal(&key).await
-        .map_err(|e| (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": e.to_string()})),
-        ))?;
+        .map_err(error_response)?;
 
     Ok(Json(json!({
         "sealed": !unsealed,
     })))
 }
 
+/// Status of the root token generation in progress
+pub async fn generate_root_status_with_state(
+    state: Arc<AppState>,
+) -> Json<Value> {
+    let progress = state.generate_root.progress();
+    Json(json!({
+        "started": progress.is_some(),
+        "nonce": progress.as_ref().map(|p| p.nonce.clone()),
+        "progress": progress.as_ref().map_or(0, |p| p.progress),
+        "required": progress.as_ref().map_or(0, |p| p.required),
+        "expires_in_secs": progress.as_ref().map(|p| p.expires_in_secs),
+        "otp_length": state.generate_root.otp_length(),
+    }))
+}
+
+/// Start generating an emergency root token
+///
+/// Takes the initiator's one-time pad (`otp`, base64) and needs as many
+/// unseal key shares as unsealing does. Every step of the flow is audited.
+pub async fn start_generate_root_with_state(
+    state: Arc<AppState>,
+    payload: Value,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let req = LogicalRequest::new_write_request("sys/generate-root/attempt", None);
+    audit_generate_root_request(&state, &req).await?;
+
+    let result = start_generate_root(&state, &payload).await;
+    let response = generate_root_response(&result, |progress| json!({
+        "nonce": progress.nonce,
+        "required": progress.required,
+    }));
+    audit_generate_root_response(&state, &req, &response).await?;
+
+    let progress = result.map_err(error_response)?;
+    tracing::warn!(
+        target: "security",
+        nonce = %progress.nonce,
+        required = progress.required,
+        "Root token generation started"
+    );
+    Ok(Json(json!({
+        "started": true,
+        "nonce": progress.nonce,
+        "progress": progress.progress,
+        "required": progress.required,
+        "expires_in_secs": progress.expires_in_secs,
+        "otp_length": state.generate_root.otp_length(),
+    })))
+}
+
+async fn start_generate_root(
+    state: &AppState,
+    payload: &Value,
+) -> VaultResult<crate::core::GenerateRootProgress> {
+    if state.core.is_sealed() {
+        return Err(VaultError::Sealed);
+    }
+    if state.token_store.is_none() {
+        return Err(VaultError::Vault("token store not available".to_string()));
+    }
+    let otp = payload.get("otp")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| VaultError::Validation("missing 'otp' field".to_string()))?;
+    let seal_config = state.core.seal_config().await?
+        .ok_or_else(|| VaultError::Vault("Vault not initialized".to_string()))?;
+    state.generate_root.start(otp, seal_config.secret_threshold)
+}
+
+/// Abandon the root token generation in progress, discarding its shares
+pub async fn cancel_generate_root_with_state(
+    state: Arc<AppState>,
+) -> Result<StatusCode, (StatusCode, Json<Value>)> {
+    let req = LogicalRequest::new_delete_request("sys/generate-root/attempt", None);
+    audit_generate_root_request(&state, &req).await?;
+
+    let result = state.generate_root
+        .cancel()
+        .ok_or_else(|| VaultError::NotFound("no root token generation in progress".to_string()));
+    let response = generate_root_response(&result, |nonce| json!({"nonce": nonce}));
+    audit_generate_root_response(&state, &req, &response).await?;
+
+    let nonce = result.map_err(error_response)?;
+    tracing::warn!(target: "security", nonce = %nonce, "Root token generation cancelled");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Submit one unseal key share (`key`, base64) to the attempt `nonce`
+///
+/// The share reaching the threshold completes the attempt: the shares are
+/// checked against the vault's key and, if they match, a root token flagged
+/// as generated is minted and returned XORed with the one-time pad as
+/// `encoded_token`. Shares that do not match end the attempt.
+pub async fn update_generate_root_with_state(
+    state: Arc<AppState>,
+    payload: Value,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let nonce = payload.get("nonce").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    // The share itself never reaches the audit log, not even HMAC'd
+    let mut data = serde_json::Map::new();
+    data.insert("nonce".to_string(), Value::String(nonce.clone()));
+    let req = LogicalRequest::new_write_request("sys/generate-root/update", Some(data));
+    audit_generate_root_request(&state, &req).await?;
+
+    let result = update_generate_root(&state, &nonce, &payload).await;
+    let response = generate_root_response(&result, |outcome| match outcome {
+        GenerateRootOutcome::Pending(progress) => json!({
+            "complete": false,
+            "progress": progress.progress,
+            "required": progress.required,
+        }),
+        GenerateRootOutcome::Complete { accessor, .. } => json!({
+            "complete": true,
+            "accessor": accessor,
+        }),
+    });
+    if let Err(e) = audit_generate_root_response(&state, &req, &response).await {
+        // A root token must not exist without a record of its creation
+        if let (Ok(GenerateRootOutcome::Complete { token_id, .. }), Some(token_store)) = (&result, &state.token_store) {
+            if let Err(revoke_err) = token_store.revoke_token_by_id(*token_id).await {
+                tracing::error!(target: "security", "Failed to revoke unaudited generated root token: {}", revoke_err);
+            }
+        }
+        return Err(e);
+    }
+
+    match result.map_err(error_response)? {
+        GenerateRootOutcome::Pending(progress) => {
+            tracing::warn!(
+                target: "security",
+                nonce = %nonce,
+                "Root token generation share submitted ({}/{})",
+                progress.progress,
+                progress.required
+            );
+            Ok(Json(json!({
+                "started": true,
+                "complete": false,
+                "nonce": progress.nonce,
+                "progress": progress.progress,
+                "required": progress.required,
+                "expires_in_secs": progress.expires_in_secs,
+            })))
+        }
+        GenerateRootOutcome::Complete { accessor, encoded_token, .. } => {
+            tracing::warn!(
+                target: "security",
+                nonce = %nonce,
+                accessor = %accessor,
+                "Root token generated from unseal key quorum; revoke it once the emergency is over"
+            );
+            Ok(Json(json!({
+                "started": false,
+                "complete": true,
+                "nonce": nonce,
+                "encoded_token": encoded_token,
+                "accessor": accessor,
+            })))
+        }
+    }
+}
+
+enum GenerateRootOutcome {
+    Pending(crate::core::GenerateRootProgress),
+    Complete {
+        token_id: uuid::Uuid,
+        accessor: String,
+        encoded_token: String,
+    },
+}
+
+async fn update_generate_root(
+    state: &AppState,
+    nonce: &str,
+    payload: &Value,
+) -> VaultResult<GenerateRootOutcome> {
+    let key = payload.get("key")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| VaultError::Validation("missing 'key' field".to_string()))?;
+    let key = base64::engine::general_purpose::STANDARD.decode(key)
+        .map_err(|_| VaultError::Validation("invalid base64 key".to_string()))?;
+
+    let quorum = match state.generate_root.submit(nonce, key)? {
+        GenerateRootVote::Pending(progress) => return Ok(GenerateRootOutcome::Pending(progress)),
+        GenerateRootVote::Reached(quorum) => quorum,
+    };
+
+    let seal_config = state.core.seal_config().await?
+        .ok_or_else(|| VaultError::Vault("Vault not initialized".to_string()))?;
+    let shares = zeroize::Zeroizing::new(quorum.shares.iter().map(|s| s.to_vec()).collect::<Vec<_>>());
+    if let Err(e) = state.core.verify_unseal_shares(&shares, seal_config.secret_threshold) {
+        tracing::error!(
+            target: "security",
+            nonce = %quorum.nonce,
+            "Root token generation failed: submitted shares do not reconstruct the unseal key"
+        );
+        return Err(e);
+    }
+
+    let token_store = state.token_store.as_ref()
+        .ok_or_else(|| VaultError::Vault("token store not available".to_string()))?;
+    let (entry, raw_token) = token_store
+        .create_generated_root_token(state.generated_root_ttl_secs as i64)
+        .await?;
+    let raw_token = zeroize::Zeroizing::new(raw_token);
+    let encoded_token = match quorum.encode_token(&raw_token) {
+        Ok(encoded) => encoded,
+        Err(e) => {
+            token_store.revoke_token_by_id(entry.id).await?;
+            return Err(e);
+        }
+    };
+    Ok(GenerateRootOutcome::Complete {
+        token_id: entry.id,
+        accessor: entry.accessor(),
+        encoded_token,
+    })
+}
+
+/// What the audit log records of a generate-root step's outcome
+fn generate_root_response<T>(
+    result: &VaultResult<T>,
+    data: impl FnOnce(&T) -> Value,
+) -> VaultResult<Option<LogicalResponse>> {
+    match result {
+        Ok(value) => {
+            let mut resp = LogicalResponse::new();
+            if let Value::Object(map) = data(value) {
+                resp.data = Some(map);
+            }
+            Ok(Some(resp))
+        }
+        Err(e) => Err(VaultError::Vault(e.to_string())),
+    }
+}
+
+/// Generate-root steps are audited like logical requests, and fail in
+/// blocking mode when they cannot be
+async fn audit_generate_root_request(
+    state: &AppState,
+    req: &LogicalRequest,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    match state.audit.as_ref() {
+        Some(audit) => audit.log_request(None, req).await.map_err(|_| audit_failed()),
+        None => {
+            tracing::warn!(target: "security", path = %req.path, "Root token generation step with no audit device enabled");
+            Ok(())
+        }
+    }
+}
+
+async fn audit_generate_root_response(
+    state: &AppState,
+    req: &LogicalRequest,
+    response: &VaultResult<Option<LogicalResponse>>,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    match state.audit.as_ref() {
+        Some(audit) => audit.log_response(None, req, response).await.map_err(|_| audit_failed()),
+        None => Ok(()),
+    }
+}
+
 /// Initialize endpoint (with State extractor)
 pub async fn init(
     State(state): State<Arc<AppState>>,
@@ -144,10 +566,7 @@ pub async fn init_with_state(
     };
 
     let result = state.core.init(&seal_config).await
-        .map_err(|e| (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": e.to_string()})),
-        ))?;
+        .map_err(error_response)?;
 
     // Convert keys to base64
     let keys: Vec<String> = result.secret_shares.iter()
@@ -184,3 +603,265 @@ pub async fn init_with_state(
     }
 }
 
+
+fn mount_manager(
+    state: &AppState,
+) -> Result<&Arc<crate::core::MountManager>, (StatusCode, Json<Value>)> {
+    state.mounts.as_ref().ok_or_else(|| (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(json!({"error": "mount manager not initialized"})),
+    ))
+}
+
+/// Stored path of a mount named by the caller
+///
+/// Namespaced callers name mounts inside their namespace. Anyone else
+/// reaching into a namespace directly must hold the root policy.
+fn scoped_mount_path(
+    auth: Option<&AuthInfo>,
+    path: &str,
+) -> Result<String, (StatusCode, Json<Value>)> {
+    if let Some(namespace) = auth.and_then(|a| a.namespace.as_ref()) {
+        return Ok(namespace.scope_path(path));
+    }
+    if namespace::is_namespace_path(path.trim_start_matches('/')) && !auth.is_some_and(AuthInfo::is_root) {
+        return Err(error_response(VaultError::Authorization(
+            "managing a namespace's mounts requires the root policy".to_string(),
+        )));
+    }
+    Ok(path.to_string())
+}
+
+/// List mounted secrets engines with their types and configs
+///
+/// Namespaced callers only see their namespace's mounts.
+pub async fn list_mounts(
+    state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let manager = mount_manager(&state)?;
+    let namespace = auth.and_then(|a| a.namespace.as_ref());
+
+    let mut data = serde_json::Map::new();
+    for mount in manager.list().await {
+        let path = match namespace {
+            Some(namespace) => match namespace.unscope_path(&mount.path) {
+                Some(path) => path.to_string(),
+                None => continue,
+            },
+            None => mount.path.clone(),
+        };
+        data.insert(path, json!({
+            "type": mount.backend_type,
+            "description": mount.description,
+            "config": mount.config,
+            "created_at": mount.created_at,
+        }));
+    }
+
+    Ok(Json(json!({ "data": data })))
+}
+
+/// Enable a secrets engine at the given path
+pub async fn enable_mount(
+    state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
+    path: String,
+    payload: axum::extract::Json<Value>,
+) -> Result<StatusCode, (StatusCode, Json<Value>)> {
+    let manager = mount_manager(&state)?;
+    let path = scoped_mount_path(auth, &path)?;
+
+    let backend_type = payload.get("type")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Missing 'type' field"})),
+        ))?;
+    let description = payload.get("description")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let config = payload.get("config")
+        .and_then(|v| v.as_object())
+        .cloned()
+        .unwrap_or_default();
+
+    manager.mount(&path, backend_type, description, config).await
+        .map_err(error_response)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Disable the secrets engine at the given path
+///
+/// Data is preserved unless `purge=true` is passed as a query parameter.
+pub async fn disable_mount(
+    state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
+    path: String,
+    purge: bool,
+) -> Result<StatusCode, (StatusCode, Json<Value>)> {
+    let manager = mount_manager(&state)?;
+    let path = scoped_mount_path(auth, &path)?;
+
+    manager.unmount(&path, purge).await
+        .map_err(error_response)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Report when the expiration reaper last ran and how much it removed
+pub async fn expiration_status(
+    state: Arc<AppState>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let reaper = state.reaper.as_ref().ok_or_else(|| (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(json!({"error": "expiration reaper not running"})),
+    ))?;
+
+    Ok(Json(json!({ "data": reaper.status() })))
+}
+
+fn snapshots(
+    state: &AppState,
+) -> Result<&Arc<crate::storage::SnapshotBackend>, (StatusCode, Json<Value>)> {
+    state.snapshots.as_ref().ok_or_else(|| (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(json!({"error": "storage snapshots not available"})),
+    ))
+}
+
+/// Download a snapshot of all barrier-backed storage
+///
+/// Values stay encrypted; the snapshot can only be read by unsealing a vault
+/// restored from it.
+pub async fn storage_snapshot(
+    state: Arc<AppState>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let snapshot = snapshots(&state)?.snapshot().await.map_err(error_response)?;
+    tracing::info!("Storage snapshot taken ({} bytes)", snapshot.len());
+    Ok((
+        [
+            (axum::http::header::CONTENT_TYPE, "application/octet-stream"),
+            (axum::http::header::CONTENT_DISPOSITION, "attachment; filename=\"vault.snap\""),
+        ],
+        snapshot,
+    ).into_response())
+}
+
+/// Replace barrier-backed storage with an uploaded snapshot
+///
+/// The vault is sealed afterwards: the restored data may be under a
+/// different barrier key, so it has to be unsealed with the keys that were
+/// current when the snapshot was taken.
+pub async fn restore_storage_snapshot(
+    state: Arc<AppState>,
+    body: axum::body::Bytes,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let restored = snapshots(&state)?.restore(&body).await.map_err(error_response)?;
+    tracing::warn!("Restored {} entries from a storage snapshot, sealing", restored);
+    state.core.seal().await.map_err(error_response)?;
+    Ok(Json(json!({ "data": { "entries": restored, "sealed": true } })))
+}
+
+/// Check a KV mount for drift between metadata and data
+///
+/// Read-only unless `repair` is true. The report lists every inconsistency
+/// with whether it was, or could be, repaired.
+pub async fn storage_fsck(
+    state: Arc<AppState>,
+    auth: Option<&AuthInfo>,
+    mount: String,
+    repair: bool,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let manager = mount_manager(&state)?;
+    let path = scoped_mount_path(auth, &mount)?;
+    let report = manager.fsck(&path, repair).await.map_err(error_response)?;
+    if report.repaired > 0 {
+        tracing::warn!("Storage fsck repaired {} issue(s) in {}", report.repaired, report.mount);
+    } else if !report.issues.is_empty() {
+        tracing::info!("Storage fsck found {} issue(s) in {}", report.issues.len(), report.mount);
+    }
+    Ok(Json(json!({ "data": report })))
+}
+
+fn lease_manager(
+    state: &AppState,
+) -> Result<&Arc<crate::core::LeaseManager>, (StatusCode, Json<Value>)> {
+    state.leases.as_ref().ok_or_else(|| (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(json!({"error": "lease manager not initialized"})),
+    ))
+}
+
+fn lease_id(payload: &Value) -> Result<&str, (StatusCode, Json<Value>)> {
+    payload.get("lease_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Missing 'lease_id' field"})),
+        ))
+}
+
+/// Look up when a lease was issued and when it expires
+pub async fn lookup_lease(
+    state: Arc<AppState>,
+    payload: axum::extract::Json<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let leases = lease_manager(&state)?;
+    let lease = leases.lookup(lease_id(&payload)?).await
+        .map_err(error_response)?
+        .ok_or_else(|| (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "lease not found"})),
+        ))?;
+
+    Ok(Json(json!({
+        "data": {
+            "id": lease.lease_id,
+            "issue_time": lease.issue_time,
+            "expire_time": lease.expire_time,
+            "ttl": lease.ttl(chrono::Utc::now()),
+            "renewable": false,
+        }
+    })))
+}
+
+/// Revoke a lease now, revoking its credential in the issuing engine
+pub async fn revoke_lease(
+    state: Arc<AppState>,
+    payload: axum::extract::Json<Value>,
+) -> Result<StatusCode, (StatusCode, Json<Value>)> {
+    let leases = lease_manager(&state)?;
+    leases.revoke(lease_id(&payload)?).await
+        .map_err(error_response)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_health_codes_follow_vault() {
+        let none = query(&[]);
+        assert_eq!(health_code(&none, false, true, false), StatusCode::NOT_IMPLEMENTED);
+        assert_eq!(health_code(&none, true, true, false), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(health_code(&none, true, false, true), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(health_code(&none, true, false, false), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_health_codes_can_be_overridden() {
+        let probe = query(&[("uninitcode", "200"), ("sealedcode", "200"), ("standbyok", "true")]);
+        assert_eq!(health_code(&probe, false, true, false), StatusCode::OK);
+        assert_eq!(health_code(&probe, true, true, false), StatusCode::OK);
+        assert_eq!(health_code(&probe, true, false, true), StatusCode::OK);
+
+        let bare = query(&[("standbyok", ""), ("sealedcode", "not-a-code")]);
+        assert_eq!(health_code(&bare, true, false, true), StatusCode::OK);
+        assert_eq!(health_code(&bare, true, true, false), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -0,0 +1,396 @@
+//! Idempotency keys for create operations
+//!
+//! Clients may send an `Idempotency-Key` header on create requests. The first
+//! successful response is stored under that key (scoped to the caller) and
+//! replayed for retries instead of performing the operation again. Reusing a
+//! key with a different request body is rejected with 409 Conflict.
+//!
+//! Recorded responses hold secrets (a created token's `client_token`, a
+//! written secret's data), so each is encrypted under a key derived from
+//! the caller's own token on top of the barrier: reading storage is not
+//! enough to recover them, only replaying as the same caller is.
+
+use std::future::Future;
+use std::sync::Arc;
+use axum::{http::{HeaderMap, StatusCode}, Json};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashSet;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+use crate::core::expiration::ExpiredEntrySource;
+use crate::errors::{VaultError, VaultResult};
+use crate::http::error::error_response;
+use crate::http::middleware::auth_middleware::AuthInfo;
+use crate::storage::{StorageBackend, StorageCipher};
+
+/// Header carrying the client-chosen idempotency key
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+
+/// Storage prefix for recorded responses
+const IDEMPOTENCY_PREFIX: &str = "sys/idempotency/";
+
+/// Maximum accepted length of an idempotency key
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Domain separator for the per-caller response key
+const RESPONSE_KEY_CONTEXT: &[u8] = b"rustyvault idempotency response v1\0";
+
+type HandlerResult = Result<Json<Value>, (StatusCode, Json<Value>)>;
+
+/// Stored record; only the expiry is readable without the caller's token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    /// Base64 of the [`SealedResponse`], encrypted per caller
+    sealed: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SealedResponse {
+    request_hash: String,
+    body: Value,
+}
+
+/// Outcome of looking up an idempotency key
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdempotencyLookup {
+    /// No response recorded; the operation should run
+    Miss,
+    /// A response was recorded for the same request body
+    Replay(Value),
+    /// The key was used before with a different request body
+    Conflict,
+}
+
+/// Store of responses keyed by caller scope and idempotency key
+pub struct IdempotencyStore {
+    storage: Arc<dyn StorageBackend>,
+    ttl: Duration,
+    in_flight: DashSet<String>,
+}
+
+impl IdempotencyStore {
+    pub fn new(storage: Arc<dyn StorageBackend>, ttl: Duration) -> Self {
+        Self {
+            storage,
+            ttl,
+            in_flight: DashSet::new(),
+        }
+    }
+
+    /// Storage key for a caller scope and idempotency key
+    ///
+    /// Both parts are hashed so raw tokens and client input never appear in
+    /// storage paths.
+    fn storage_key(scope: &str, key: &str) -> String {
+        format!("{}{}/{}", IDEMPOTENCY_PREFIX, sha256_hex(scope.as_bytes()), sha256_hex(key.as_bytes()))
+    }
+
+    /// Cipher for the responses recorded for `scope`
+    fn cipher(scope: &str) -> VaultResult<StorageCipher> {
+        let mut hasher = Sha256::new();
+        hasher.update(RESPONSE_KEY_CONTEXT);
+        hasher.update(scope.as_bytes());
+        let key = Zeroizing::new(hasher.finalize());
+        StorageCipher::new(key.as_slice())
+    }
+
+    /// Look up a previously recorded response
+    pub async fn lookup(&self, scope: &str, key: &str, request: &Value) -> VaultResult<IdempotencyLookup> {
+        let storage_key = Self::storage_key(scope, key);
+        let Some(data) = self.storage.get(&storage_key).await? else {
+            return Ok(IdempotencyLookup::Miss);
+        };
+
+        let recorded: RecordedResponse = serde_json::from_slice(&data)?;
+        if recorded.expires_at <= Utc::now() {
+            self.storage.delete(&storage_key).await?;
+            return Ok(IdempotencyLookup::Miss);
+        }
+
+        let sealed = STANDARD.decode(&recorded.sealed)
+            .map_err(|_| VaultError::Storage(format!("{} is not a valid idempotency record", storage_key)))?;
+        let opened = Zeroizing::new(Self::cipher(scope)?.decrypt(&storage_key, &sealed)?);
+        let response: SealedResponse = serde_json::from_slice(&opened)?;
+        if response.request_hash != request_hash(request)? {
+            return Ok(IdempotencyLookup::Conflict);
+        }
+        Ok(IdempotencyLookup::Replay(response.body))
+    }
+
+    /// Record the response for a request
+    pub async fn record(&self, scope: &str, key: &str, request: &Value, response: &Value) -> VaultResult<()> {
+        let storage_key = Self::storage_key(scope, key);
+        let plaintext = Zeroizing::new(serde_json::to_vec(&SealedResponse {
+            request_hash: request_hash(request)?,
+            body: response.clone(),
+        })?);
+        let recorded = RecordedResponse {
+            sealed: STANDARD.encode(Self::cipher(scope)?.encrypt(&storage_key, &plaintext)?),
+            expires_at: Utc::now() + self.ttl,
+        };
+        let data = serde_json::to_vec(&recorded)?;
+        self.storage.put(&storage_key, &data).await
+    }
+
+    /// Run `handler` at most once per caller scope and idempotency key
+    ///
+    /// Without a key the handler simply runs. Only successful responses are
+    /// recorded, so a failed attempt can be retried with the same key.
+    pub async fn run<F, Fut>(
+        &self,
+        scope: &str,
+        key: Option<String>,
+        request: &Value,
+        handler: F,
+    ) -> HandlerResult
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = HandlerResult>,
+    {
+        let Some(key) = key else {
+            return handler().await;
+        };
+        if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": format!("{} must be 1-{} characters", IDEMPOTENCY_HEADER, MAX_KEY_LENGTH) })),
+            ));
+        }
+
+        // Reject concurrent retries while the first attempt is still running
+        let guard_key = Self::storage_key(scope, &key);
+        if !self.in_flight.insert(guard_key.clone()) {
+            return Err(conflict("a request with this idempotency key is already in progress"));
+        }
+        // Released on drop so a cancelled request does not wedge the key
+        let _guard = InFlightGuard { set: &self.in_flight, key: guard_key };
+        self.run_exclusive(scope, &key, request, handler).await
+    }
+
+    async fn run_exclusive<F, Fut>(
+        &self,
+        scope: &str,
+        key: &str,
+        request: &Value,
+        handler: F,
+    ) -> HandlerResult
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = HandlerResult>,
+    {
+        match self.lookup(scope, key, request).await.map_err(error_response)? {
+            IdempotencyLookup::Replay(body) => return Ok(Json(body)),
+            IdempotencyLookup::Conflict => {
+                return Err(conflict("idempotency key was already used with a different request body"));
+            }
+            IdempotencyLookup::Miss => {}
+        }
+
+        let response = handler().await?;
+        if let Err(e) = self.record(scope, key, request, &response.0).await {
+            tracing::warn!("Failed to record idempotent response: {}", e);
+        }
+        Ok(response)
+    }
+}
+
+#[async_trait::async_trait]
+impl ExpiredEntrySource for IdempotencyStore {
+    fn name(&self) -> &str {
+        "idempotency records"
+    }
+
+    async fn reap_expired(&self, batch_size: usize) -> VaultResult<u64> {
+        let now = Utc::now();
+        let mut reaped = 0;
+        for scope in self.storage.list(IDEMPOTENCY_PREFIX).await? {
+            for key in self.storage.list(&scope).await? {
+                if reaped as usize >= batch_size {
+                    return Ok(reaped);
+                }
+                let Some(data) = self.storage.get(&key).await? else {
+                    continue;
+                };
+                // Unreadable records are treated as expired
+                let expired = serde_json::from_slice::<RecordedResponse>(&data)
+                    .map(|r| r.expires_at <= now)
+                    .unwrap_or(true);
+                if expired {
+                    self.storage.delete(&key).await?;
+                    reaped += 1;
+                }
+            }
+        }
+        Ok(reaped)
+    }
+}
+
+struct InFlightGuard<'a> {
+    set: &'a DashSet<String>,
+    key: String,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.set.remove(&self.key);
+    }
+}
+
+/// Run `handler` through the idempotency store when one is configured
+///
+/// Keys are scoped to the authenticated caller's token so different callers
+/// can never replay, or decrypt, each other's responses.
+pub async fn run_idempotent<F, Fut>(
+    store: Option<&Arc<IdempotencyStore>>,
+    headers: &HeaderMap,
+    auth: Option<&AuthInfo>,
+    request: &Value,
+    handler: F,
+) -> HandlerResult
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = HandlerResult>,
+{
+    match store {
+        Some(store) => {
+            let scope = auth
+                .map(|a| a.raw_token.clone())
+                .unwrap_or_else(|| "anonymous".to_string());
+            store.run(&scope, idempotency_key(headers), request, handler).await
+        }
+        None => handler().await,
+    }
+}
+
+/// Extract the idempotency key header, if present
+pub fn idempotency_key(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(IDEMPOTENCY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.trim().to_string())
+}
+
+fn request_hash(request: &Value) -> VaultResult<String> {
+    let bytes = serde_json::to_vec(request).map_err(VaultError::Serialization)?;
+    Ok(sha256_hex(&bytes))
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn conflict(message: &str) -> (StatusCode, Json<Value>) {
+    (StatusCode::CONFLICT, Json(json!({ "error": message })))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::storage::physical_inmem::InMemoryBackend;
+
+    fn store() -> IdempotencyStore {
+        IdempotencyStore::new(Arc::new(InMemoryBackend::new()), Duration::hours(1))
+    }
+
+    async fn create(store: &IdempotencyStore, scope: &str, key: &str, body: Value, calls: &AtomicUsize) -> HandlerResult {
+        store.run(scope, Some(key.to_string()), &body, || async {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Json(json!({ "token": n })))
+        }).await
+    }
+
+    #[tokio::test]
+    async fn test_retry_replays_first_response() {
+        let store = store();
+        let calls = AtomicUsize::new(0);
+
+        let first = create(&store, "caller-a", "k1", json!({"ttl": 60}), &calls).await.unwrap();
+        let retry = create(&store, "caller-a", "k1", json!({"ttl": 60}), &calls).await.unwrap();
+
+        assert_eq!(first.0, retry.0);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_keys_are_scoped_per_caller() {
+        let store = store();
+        let calls = AtomicUsize::new(0);
+
+        assert!(create(&store, "caller-a", "k1", json!({}), &calls).await.is_ok());
+        assert!(create(&store, "caller-b", "k1", json!({}), &calls).await.is_ok());
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_reuse_with_different_body_conflicts() {
+        let store = store();
+        let calls = AtomicUsize::new(0);
+
+        assert!(create(&store, "caller-a", "k1", json!({"ttl": 60}), &calls).await.is_ok());
+        let err = create(&store, "caller-a", "k1", json!({"ttl": 120}), &calls).await.unwrap_err();
+
+        assert_eq!(err.0, StatusCode::CONFLICT);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_not_recorded() {
+        let store = store();
+        let body = json!({});
+
+        let err = store.run("caller-a", Some("k1".to_string()), &body, || async {
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "boom" }))))
+        }).await.unwrap_err();
+        assert_eq!(err.0, StatusCode::INTERNAL_SERVER_ERROR);
+
+        assert_eq!(store.lookup("caller-a", "k1", &body).await.unwrap(), IdempotencyLookup::Miss);
+    }
+
+    #[tokio::test]
+    async fn test_reap_expired_records() {
+        let storage = Arc::new(InMemoryBackend::new());
+        let expired = IdempotencyStore::new(storage.clone(), Duration::seconds(-1));
+        let live = IdempotencyStore::new(storage, Duration::hours(1));
+        let body = json!({});
+        expired.record("caller-a", "old-1", &body, &json!({})).await.unwrap();
+        expired.record("caller-b", "old-2", &body, &json!({})).await.unwrap();
+        live.record("caller-a", "new", &body, &json!({"token": 1})).await.unwrap();
+
+        assert_eq!(live.reap_expired(100).await.unwrap(), 2);
+        assert_eq!(
+            live.lookup("caller-a", "new", &body).await.unwrap(),
+            IdempotencyLookup::Replay(json!({"token": 1}))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_responses_are_sealed_per_caller() {
+        let storage = Arc::new(InMemoryBackend::new());
+        let store = IdempotencyStore::new(storage.clone(), Duration::hours(1));
+        let body = json!({});
+        store.record("caller-a", "k1", &body, &json!({"client_token": "hvs.secret"})).await.unwrap();
+
+        let stored = storage.get(&IdempotencyStore::storage_key("caller-a", "k1")).await.unwrap().unwrap();
+        assert!(!String::from_utf8_lossy(&stored).contains("hvs.secret"));
+
+        // A record moved under another caller's path cannot be opened by them
+        storage.put(&IdempotencyStore::storage_key("caller-b", "k1"), &stored).await.unwrap();
+        assert!(store.lookup("caller-b", "k1", &body).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_expired_entries_are_ignored() {
+        let store = IdempotencyStore::new(Arc::new(InMemoryBackend::new()), Duration::seconds(-1));
+        let body = json!({});
+        store.record("caller-a", "k1", &body, &json!({"token": 1})).await.unwrap();
+
+        assert_eq!(store.lookup("caller-a", "k1", &body).await.unwrap(), IdempotencyLookup::Miss);
+    }
+}
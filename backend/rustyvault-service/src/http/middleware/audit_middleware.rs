@@ -0,0 +1,315 @@
+//! Audit of the endpoints outside the logical layer
+//!
+//! Handlers that route logical requests audit each one themselves, with the
+//! data the engine sees, so their routes are skipped here. Every other
+//! request (auth, sys and policy alike) is recorded from its method, path
+//! and JSON bodies before and after it is handled, and in blocking mode is
+//! refused, or its response withheld, when the record cannot be written.
+//!
+//! On protected routes this runs inside the auth middleware so entries name
+//! the caller; requests the auth middleware turns away are recorded by
+//! [`audit_refusal`] instead.
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{MatchedPath, Request},
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+
+use crate::audit::AuditBroker;
+use crate::http::middleware::auth_middleware::AuthInfo;
+use crate::logical::{Operation, Request as LogicalRequest};
+
+/// Largest request body read for the log, the JSON extractor's own limit
+const MAX_AUDITED_BODY: usize = 2 * 1024 * 1024;
+
+/// Fields holding unseal key shares, which never reach the log, not even HMAC'd
+const KEY_SHARE_FIELDS: &[&str] = &["key", "keys", "keys_base64"];
+
+/// Polled by load balancers; recording every poll would bury the rest
+const UNAUDITED_PATHS: &[&str] = &["/v1/sys/health"];
+
+/// Error returned when the request could not be audited
+pub(crate) fn audit_failed() -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({"error": "audit logging failed"})),
+    )
+}
+
+/// Whether the handler of `route` audits the logical requests it makes
+fn audited_by_handler(method: &Method, route: &str) -> bool {
+    match route {
+        "/v1/secret/{*path}" | "/v1/{*path}" | "/v1/sys/secrets/batch-read" | "/v1/sys/generate-root/update" => true,
+        // Starting and cancelling are audited by the handler, the status read is not
+        "/v1/sys/generate-root/attempt" => *method != Method::GET,
+        _ => false,
+    }
+}
+
+pub async fn audit_middleware(audit: Option<Arc<AuditBroker>>, req: Request, next: Next) -> Response {
+    let Some(audit) = audit else {
+        return next.run(req).await;
+    };
+    let route = req.extensions().get::<MatchedPath>().map(|route| route.as_str().to_string());
+    if UNAUDITED_PATHS.contains(&req.uri().path())
+        || route.is_some_and(|route| audited_by_handler(req.method(), &route))
+    {
+        return next.run(req).await;
+    }
+
+    let (parts, body) = req.into_parts();
+    let (body, data) = match read_json(&parts.headers, body, MAX_AUDITED_BODY).await {
+        Ok(read) => read,
+        Err(_) => {
+            return (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                Json(json!({"error": "request body too large"})),
+            )
+                .into_response();
+        }
+    };
+    let auth = parts.extensions.get::<AuthInfo>().cloned();
+    let mut logical = LogicalRequest {
+        id: uuid::Uuid::new_v4().to_string(),
+        operation: Operation::from(parts.method.as_str()),
+        path: parts.uri.path().trim_start_matches("/v1/").to_string(),
+        data,
+        ..Default::default()
+    };
+    logical.context = auth.as_ref().map(|auth| auth.request_context(&logical.id));
+
+    if audit.log_request(auth.as_ref(), &logical).await.is_err() {
+        return audit_failed().into_response();
+    }
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    let (parts, body) = response.into_parts();
+    let (body, data) = match read_json(&parts.headers, body, usize::MAX).await {
+        Ok(read) => read,
+        Err(e) => {
+            tracing::error!("Failed to read response of {} for auditing: {}", logical.path, e);
+            return audit_failed().into_response();
+        }
+    };
+    if audit
+        .log_http_response(auth.as_ref(), &logical, parts.status.as_u16(), data.as_ref())
+        .await
+        .is_err()
+    {
+        return audit_failed().into_response();
+    }
+    Response::from_parts(parts, body)
+}
+
+/// Record a request the auth middleware refused, without a caller
+///
+/// Nothing was served, so in blocking mode a failed record only changes
+/// which error the caller sees.
+pub async fn audit_refusal(audit: Option<&AuditBroker>, method: &Method, path: &str, refusal: Response) -> Response {
+    let Some(audit) = audit else {
+        return refusal;
+    };
+    if UNAUDITED_PATHS.contains(&path) {
+        return refusal;
+    }
+
+    let logical = LogicalRequest {
+        id: uuid::Uuid::new_v4().to_string(),
+        operation: Operation::from(method.as_str()),
+        path: path.trim_start_matches("/v1/").to_string(),
+        ..Default::default()
+    };
+    let (parts, body) = refusal.into_parts();
+    let (body, data) = match read_json(&parts.headers, body, usize::MAX).await {
+        Ok(read) => read,
+        Err(_) => return audit_failed().into_response(),
+    };
+    let logged = match audit.log_request(None, &logical).await {
+        Ok(()) => audit.log_http_response(None, &logical, parts.status.as_u16(), data.as_ref()).await,
+        Err(e) => Err(e),
+    };
+    if logged.is_err() {
+        return audit_failed().into_response();
+    }
+    Response::from_parts(parts, body)
+}
+
+/// Buffer a JSON body, returning it with the object it holds minus key
+/// shares; other bodies pass through unread
+async fn read_json(
+    headers: &HeaderMap,
+    body: Body,
+    limit: usize,
+) -> Result<(Body, Option<Map<String, Value>>), axum::Error> {
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !is_json {
+        return Ok((body, None));
+    }
+
+    let bytes = to_bytes(body, limit).await?;
+    let data = match serde_json::from_slice(&bytes) {
+        Ok(Value::Object(mut data)) => {
+            for field in KEY_SHARE_FIELDS {
+                data.remove(*field);
+            }
+            Some(data)
+        }
+        _ => None,
+    };
+    Ok((Body::from(bytes), data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{AuditDevice, AuditEntry};
+    use crate::errors::{VaultError, VaultResult};
+    use axum::routing::{get, post};
+    use axum::Router;
+    use std::sync::Mutex;
+    use tower::Service;
+
+    #[derive(Default)]
+    struct MemoryDevice {
+        entries: Mutex<Vec<Value>>,
+    }
+
+    #[async_trait::async_trait]
+    impl AuditDevice for MemoryDevice {
+        fn name(&self) -> &str {
+            "memory"
+        }
+
+        async fn log(&self, entry: &AuditEntry) -> VaultResult<()> {
+            self.entries.lock().unwrap().push(serde_json::to_value(entry)?);
+            Ok(())
+        }
+    }
+
+    struct BrokenDevice;
+
+    #[async_trait::async_trait]
+    impl AuditDevice for BrokenDevice {
+        fn name(&self) -> &str {
+            "broken"
+        }
+
+        async fn log(&self, _entry: &AuditEntry) -> VaultResult<()> {
+            Err(VaultError::Storage("disk full".to_string()))
+        }
+    }
+
+    fn app(audit: Arc<AuditBroker>) -> Router {
+        Router::new()
+            .route("/v1/sys/policies/acl/{name}", post(|Json(body): Json<Value>| async move {
+                Json(json!({ "name": body["name"] }))
+            }))
+            .route("/v1/sys/unseal", post(|| async { Json(json!({ "sealed": false, "keys": ["share"] })) }))
+            .route("/v1/auth/token/lookup", post(|| async {
+                (StatusCode::NOT_FOUND, Json(json!({ "error": "token not found" })))
+            }))
+            .route("/v1/secret/{*path}", get(|| async { Json(json!({ "data": {} })) }))
+            .route("/v1/sys/health", get(|| async { Json(json!({ "sealed": false })) }))
+            .layer(axum::middleware::from_fn(move |req: Request, next: Next| {
+                audit_middleware(Some(audit.clone()), req, next)
+            }))
+    }
+
+    async fn send(app: &mut Router, method: Method, path: &str, body: Value) -> Response {
+        let req = axum::http::Request::builder()
+            .method(method)
+            .uri(path)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        std::future::poll_fn(|cx| <Router as Service<axum::http::Request<Body>>>::poll_ready(app, cx))
+            .await
+            .unwrap();
+        app.call(req).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_non_logical_endpoints_are_audited() {
+        let device = Arc::new(MemoryDevice::default());
+        let broker = Arc::new(AuditBroker::new(b"audit-key".to_vec(), true).with_device(device.clone()));
+        let mut app = app(broker.clone());
+
+        let response = send(&mut app, Method::POST, "/v1/sys/policies/acl/ops", json!({ "name": "ops" })).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(serde_json::from_slice::<Value>(&body).unwrap()["name"], "ops");
+
+        let response = send(&mut app, Method::POST, "/v1/auth/token/lookup", json!({})).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let entries = device.entries.lock().unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0]["type"], "request");
+        assert_eq!(entries[0]["request"]["path"], "sys/policies/acl/ops");
+        assert_eq!(entries[0]["request"]["operation"], "write");
+        assert_eq!(entries[0]["request"]["data"]["name"], json!(broker.hash("ops")));
+        assert_eq!(entries[1]["type"], "response");
+        assert_eq!(entries[1]["response"]["name"], json!(broker.hash("ops")));
+        assert_eq!(entries[3]["request"]["path"], "auth/token/lookup");
+        assert_eq!(entries[3]["error"], "404: token not found");
+    }
+
+    #[tokio::test]
+    async fn test_key_shares_never_reach_the_log() {
+        let device = Arc::new(MemoryDevice::default());
+        let mut app = app(Arc::new(AuditBroker::new(b"audit-key".to_vec(), true).with_device(device.clone())));
+
+        let response = send(&mut app, Method::POST, "/v1/sys/unseal", json!({ "key": "share" })).await;
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(serde_json::from_slice::<Value>(&body).unwrap()["keys"][0], "share");
+
+        let entries = device.entries.lock().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0]["request"]["data"].get("key").is_none());
+        assert!(entries[1]["response"].get("keys").is_none());
+        assert_eq!(entries[1]["response"]["sealed"], false);
+    }
+
+    #[tokio::test]
+    async fn test_logical_routes_and_health_are_left_to_others() {
+        let device = Arc::new(MemoryDevice::default());
+        let mut app = app(Arc::new(AuditBroker::new(b"audit-key".to_vec(), true).with_device(device.clone())));
+
+        send(&mut app, Method::GET, "/v1/secret/app/db", json!({})).await;
+        send(&mut app, Method::GET, "/v1/sys/health", json!({})).await;
+        assert!(device.entries.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_refusals_are_audited() {
+        let device = Arc::new(MemoryDevice::default());
+        let broker = AuditBroker::new(b"audit-key".to_vec(), true).with_device(device.clone());
+        let refusal = (StatusCode::UNAUTHORIZED, Json(json!({ "error": "missing authentication token" }))).into_response();
+
+        let response = audit_refusal(Some(&broker), &Method::DELETE, "/v1/sys/policies/acl/ops", refusal).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let entries = device.entries.lock().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["request"]["path"], "sys/policies/acl/ops");
+        assert_eq!(entries[0]["request"]["operation"], "delete");
+        assert_eq!(entries[1]["error"], "401: missing authentication token");
+    }
+
+    #[tokio::test]
+    async fn test_blocking_mode_refuses_unaudited_requests() {
+        let mut app = app(Arc::new(AuditBroker::new(b"audit-key".to_vec(), true).with_device(Arc::new(BrokenDevice))));
+        let response = send(&mut app, Method::POST, "/v1/sys/policies/acl/ops", json!({ "name": "ops" })).await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -3,8 +3,9 @@
 //! This middleware:
 //! 1. Extracts the token from headers
 //! 2. Validates the token against the TokenStore
-//! 3. Checks ACL policies for the requested path
-//! 4. Attaches token info to request for handlers
+//! 3. Confines namespaced tokens to their namespace
+//! 4. Checks ACL policies for the requested path
+//! 5. Attaches token info to request for handlers
 
 use axum::{
     extract::Request,
@@ -13,18 +14,60 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
-use serde_json::json;
+use serde_json::{json, Value};
+use shared::infrastructure::tls::ClientCertificate;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 
+use crate::core::namespace::{self, Namespace};
+use crate::http::denial;
+use crate::http::error::error_response;
 use crate::http::routes::AppState;
 use crate::modules::auth::TokenEntry;
 use crate::logical::request::Operation;
+use crate::logical::RequestContext;
 
 /// Token information attached to requests after authentication
 #[derive(Clone, Debug)]
 pub struct AuthInfo {
     pub token: TokenEntry,
     pub raw_token: String,
+    /// Namespace the token is confined to, when namespace isolation is on
+    pub namespace: Option<Namespace>,
+    /// Verified TLS client certificate of the connection, if one was presented
+    pub client_cert: Option<ClientCertificate>,
+    /// Peer address of the connection
+    pub client_ip: Option<IpAddr>,
+    /// Caller-supplied `X-Request-Id`
+    pub request_id: Option<String>,
+}
+
+impl AuthInfo {
+    pub fn is_root(&self) -> bool {
+        self.token.policies.iter().any(|p| p == "root")
+    }
+
+    /// Context for a logical request with ID `id` made with this token
+    pub fn request_context(&self, id: &str) -> RequestContext {
+        RequestContext {
+            identity: self.token.entity_id
+                .map(|entity| entity.to_string())
+                .unwrap_or_else(|| self.token.display_name.clone()),
+            token_accessor: self.token.accessor(),
+            request_id: self.request_id.clone().unwrap_or_else(|| id.to_string()),
+            client_ip: self.client_ip,
+            namespace: self.namespace.clone(),
+        }
+    }
+}
+
+/// Longest `X-Request-Id` carried into request contexts
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// The caller's `X-Request-Id`, if it is printable and not too long
+fn caller_request_id(headers: &HeaderMap) -> Option<String> {
+    let id = headers.get("X-Request-Id")?.to_str().ok()?.trim();
+    (!id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN).then(|| id.to_string())
 }
 
 /// Extract token from headers
@@ -57,6 +100,8 @@ const PUBLIC_PATHS: &[&str] = &[
     "/v1/sys/init",
     "/v1/sys/seal-status",
     "/v1/sys/unseal",
+    // Authorized by unseal key shares, for when no root token is left
+    "/v1/sys/generate-root",
     "/v1/auth/token/lookup", // Allow token validation without auth
 ];
 
@@ -67,6 +112,10 @@ const SELF_PATHS: &[&str] = &[
     "/v1/auth/token/revoke-self",
 ];
 
+/// Authenticated paths whose handlers authorize each path named in the body
+/// instead of the request path itself
+const BATCH_PATHS: &[&str] = &["/v1/sys/secrets/batch-read"];
+
 /// Convert HTTP method to Operation
 fn method_to_operation(method: &str) -> Operation {
     match method {
@@ -87,13 +136,17 @@ pub async fn auth_middleware(
     let path = req.uri().path().to_string();
     let method = req.method().as_str().to_string();
 
-    // Allow public paths without auth
-    if PUBLIC_PATHS.iter().any(|p| path.starts_with(p)) {
+    // Allow public paths without auth; matched on whole segments so that
+    // `auth/token/lookup` does not open `auth/token/lookup-accessor`
+    let is_public = PUBLIC_PATHS.iter().any(|p| {
+        path.strip_prefix(p).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    });
+    if is_public {
         return Ok(next.run(req).await);
     }
 
-    // Allow userpass login without auth
-    if path.starts_with("/v1/auth/userpass/login/") {
+    // Allow userpass and LDAP login without auth
+    if path.starts_with("/v1/auth/userpass/login/") || path.starts_with("/v1/auth/ldap/login/") {
         return Ok(next.run(req).await);
     }
 
@@ -166,92 +219,129 @@ pub async fn auth_middleware(
         }
     }
 
-    // Allow self-paths for any authenticated token
-    if SELF_PATHS.iter().any(|p| path == *p) {
-        // Attach auth info to request
-        let auth_info = AuthInfo {
-            token: token_entry,
-            raw_token,
-        };
-        req.extensions_mut().insert(auth_info);
-        return Ok(next.run(req).await);
-    }
+    // Namespace confinement comes before, and does not depend on, policies
+    let namespace = if state.namespace_isolation {
+        Namespace::of_token(&token_entry).map_err(|e| error_response(e).into_response())?
+    } else {
+        None
+    };
+    let vault_path = path.trim_start_matches("/v1/").to_string();
+    check_namespace(&token_entry, namespace.as_ref(), &vault_path).map_err(IntoResponse::into_response)?;
 
-    // Root policy bypasses all ACL checks
-    if token_entry.policies.contains(&"root".to_string()) {
-        let auth_info = AuthInfo {
-            token: token_entry,
-            raw_token,
-        };
-        req.extensions_mut().insert(auth_info);
-        return Ok(next.run(req).await);
-    }
+    // Absent unless the connection is TLS and presented a certificate
+    let client_cert = req.extensions().get::<ClientCertificate>().cloned();
+    let client_ip = req
+        .extensions()
+        .get::<axum::extract::ConnectInfo<SocketAddr>>()
+        .map(|info| info.0.ip());
+    let request_id = caller_request_id(req.headers());
 
-    // Check ACL policies
-    if let Some(policy_store) = &state.policy_store {
+    // Allow self-paths for any authenticated token, and batch paths whose
+    // handlers check every path they touch
+    let is_batch = method == "POST" && BATCH_PATHS.contains(&path.as_str());
+    if !is_batch && !SELF_PATHS.iter().any(|p| path == *p) {
         let operation = method_to_operation(&method);
-        
-        // Convert path to vault path (remove /v1/ prefix)
-        let vault_path = path.trim_start_matches("/v1/").to_string();
-
-        // Build ACL from token's policies
-        match policy_store.new_acl(&token_entry.policies).await {
-            Ok(acl) => {
-                // Create a request for ACL checking
-                let acl_req = crate::logical::Request {
-                    path: vault_path.clone(),
-                    operation,
-                    ..Default::default()
-                };
-
-                match acl.allow_operation(&acl_req, false) {
-                    Ok(result) => {
-                        if !result.allowed {
-                            tracing::warn!(
-                                "Access denied for path '{}' with policies {:?}",
-                                vault_path,
-                                token_entry.policies
-                            );
-                            return Err((
-                                StatusCode::FORBIDDEN,
-                                Json(json!({ 
-                                    "error": "permission denied",
-                                    "path": vault_path,
-                                    "operation": format!("{:?}", operation)
-                                })),
-                            )
-                                .into_response());
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!("ACL check failed: {}", e);
-                        return Err((
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            Json(json!({ "error": "failed to check permissions" })),
-                        )
-                            .into_response());
-                    }
-                }
-            }
-            Err(e) => {
-                tracing::error!("Failed to build ACL: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({ "error": "failed to load policies" })),
-                )
-                    .into_response());
-            }
-        }
-    } else {
-        tracing::warn!("Policy store not available, skipping ACL check");
+        check_acl(&state, &token_entry, namespace.as_ref(), &vault_path, operation)
+            .await
+            .map_err(IntoResponse::into_response)?;
     }
 
     // Attach auth info to request for handlers
     let auth_info = AuthInfo {
         token: token_entry,
         raw_token,
+        namespace,
+        client_cert,
+        client_ip,
+        request_id,
     };
     req.extensions_mut().insert(auth_info);
 
     Ok(next.run(req).await)
 }
+
+/// Refuse `vault_path` to a token confined to a namespace it lies outside of
+pub fn check_namespace(
+    token_entry: &TokenEntry,
+    namespace: Option<&Namespace>,
+    vault_path: &str,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    if namespace.is_some() && !namespace::namespaced_endpoint_allowed(vault_path) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({ "error": "endpoint not available to namespaced tokens" })),
+        ));
+    }
+    if namespace.is_none()
+        && namespace::is_namespace_path(vault_path)
+        && !token_entry.policies.iter().any(|p| p == "root")
+    {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({ "error": "cross-namespace access requires the root policy" })),
+        ));
+    }
+    Ok(())
+}
+
+/// Check the token's ACL policies allow `operation` on `vault_path`
+///
+/// The root policy bypasses all ACL checks (a namespaced root token stays
+/// confined to its namespace by [`check_namespace`]).
+pub async fn check_acl(
+    state: &AppState,
+    token_entry: &TokenEntry,
+    namespace: Option<&Namespace>,
+    vault_path: &str,
+    operation: Operation,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    if token_entry.policies.contains(&"root".to_string()) {
+        return Ok(());
+    }
+    let Some(policy_store) = &state.policy_store else {
+        tracing::warn!("Policy store not available, skipping ACL check");
+        return Ok(());
+    };
+
+    // Build ACL from token's policies, which for a namespaced token are
+    // the namespace's policies of those names
+    let policies = match namespace {
+        Some(namespace) => namespace.scope_policies(&token_entry.policies),
+        None => token_entry.policies.clone(),
+    };
+    let acl = policy_store.new_acl(&policies).await.map_err(|e| {
+        tracing::error!("Failed to build ACL: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": "failed to load policies" })),
+        )
+    })?;
+
+    // Create a request for ACL checking
+    let acl_req = crate::logical::Request {
+        path: vault_path.to_string(),
+        operation,
+        ..Default::default()
+    };
+    let result = acl.allow_operation(&acl_req, false).map_err(|e| {
+        tracing::error!("ACL check failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": "failed to check permissions" })),
+        )
+    })?;
+    if !result.allowed {
+        tracing::warn!(
+            "Access denied for path '{}' with policies {:?}",
+            vault_path,
+            token_entry.policies
+        );
+        return Err(denial::access_denied(
+            &state.denial_policy,
+            &token_entry.policies,
+            vault_path,
+            operation,
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,17 @@
+//! Default `Cache-Control` for vault API responses
+//!
+//! Nearly every response carries a secret, token or policy, so anything a
+//! handler did not mark cacheable is sent as `no-store`.
+
+use axum::{
+    http::{header, HeaderValue},
+    response::Response,
+};
+
+pub async fn cache_control_middleware(mut response: Response) -> Response {
+    response
+        .headers_mut()
+        .entry(header::CACHE_CONTROL)
+        .or_insert(HeaderValue::from_static("no-store"));
+    response
+}
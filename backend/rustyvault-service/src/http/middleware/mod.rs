@@ -1,6 +1,12 @@
 //! Middleware for vault HTTP layer
 
+pub mod audit_middleware;
 pub mod auth_middleware;
+pub mod cache_control_middleware;
+pub mod standby_middleware;
 
+pub use audit_middleware::{audit_middleware, audit_refusal};
 pub use auth_middleware::auth_middleware;
+pub use cache_control_middleware::cache_control_middleware;
+pub use standby_middleware::standby_middleware;
 
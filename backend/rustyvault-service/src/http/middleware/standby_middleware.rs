@@ -0,0 +1,45 @@
+//! Standby gate for vault API
+//!
+//! Counts each request as in flight so `sys/step-down` can drain them, and
+//! rejects requests while the node is standing by. Status and seal
+//! endpoints stay reachable so operators and load balancers can see and
+//! change the node's state.
+
+use axum::{
+    extract::Request,
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::http::routes::AppState;
+
+/// Paths served regardless of standby and not counted as in flight
+const UNGATED_PATHS: &[&str] = &[
+    "/v1/sys/health",
+    "/v1/sys/health/crypto-selftest",
+    "/v1/sys/seal-status",
+    "/v1/sys/seal",
+    "/v1/sys/seal/progress",
+    "/v1/sys/unseal",
+    "/v1/sys/step-down",
+];
+
+pub async fn standby_middleware(state: Arc<AppState>, request: Request, next: Next) -> Response {
+    if UNGATED_PATHS.contains(&request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let active = state.core.active.clone();
+    let Some(_in_flight) = active.enter() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"errors": ["Vault is in standby"]})),
+        )
+            .into_response();
+    };
+    next.run(request).await
+}
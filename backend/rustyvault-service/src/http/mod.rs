@@ -2,8 +2,11 @@
 //!
 //! Migrated from Actix-web to Axum for consistency with health-v1
 
+pub mod denial;
+pub mod error;
 pub mod routes;
 pub mod handlers;
 pub mod middleware;
+pub mod idempotency;
 
 
@@ -8,10 +8,15 @@ use axum::{
 use std::sync::Arc;
 use tower_http::cors::{CorsLayer, AllowOrigin};
 use crate::http::handlers::{sys_handlers, secrets_handlers, policy_handlers, auth_handlers};
-use crate::http::middleware::auth_middleware;
-use crate::modules::auth::{TokenStore, UserPassBackend};
+use crate::http::middleware::{audit_middleware, audit_refusal, auth_middleware, cache_control_middleware, standby_middleware};
+use crate::modules::auth::{LdapBackend, TokenStore, UserPassBackend};
 use crate::modules::policy::PolicyStore;
 use crate::config::VaultSettings;
+use crate::audit::AuditBroker;
+use crate::core::{ExpirationReaper, LeaseManager, MountManager};
+use crate::logical::DenialPolicy;
+use crate::http::idempotency::{self, IdempotencyStore};
+use crate::http::middleware::auth_middleware::AuthInfo;
 
 /// App state for routes
 pub struct AppState {
@@ -19,6 +24,31 @@ pub struct AppState {
     pub policy_store: Option<Arc<PolicyStore>>,
     pub token_store: Option<Arc<TokenStore>>,
     pub userpass: Option<Arc<UserPassBackend>>,
+    pub ldap: Option<Arc<LdapBackend>>,
+    pub mounts: Option<Arc<MountManager>>,
+    /// Leases of dynamically issued secrets
+    pub leases: Option<Arc<LeaseManager>>,
+    /// How ACL denials are reported (403 vs disguised 404)
+    pub denial_policy: DenialPolicy,
+    /// Whether tokens are confined to their organization's namespace
+    pub namespace_isolation: bool,
+    /// Replay store for `Idempotency-Key` create requests
+    pub idempotency: Option<Arc<IdempotencyStore>>,
+    pub reaper: Option<Arc<ExpirationReaper>>,
+    /// Audit devices every logical request is recorded to
+    pub audit: Option<Arc<AuditBroker>>,
+    /// Step-down hold and drain timings
+    pub ha: crate::config::HaConfig,
+    /// Physical storage under the barrier, for `sys/storage/snapshot`
+    pub snapshots: Option<Arc<crate::storage::SnapshotBackend>>,
+    /// Confirmations required before `sys/seal` seals
+    pub seal_quorum: Arc<crate::core::SealQuorum>,
+    /// Unseal-key quorum for `sys/generate-root`
+    pub generate_root: Arc<crate::core::GenerateRoot>,
+    /// TTL of generated root tokens in seconds; 0 never expires
+    pub generated_root_ttl_secs: u64,
+    /// Whether `sys/health` also runs the crypto self-test
+    pub health_crypto_selftest: bool,
 }
 
 /// Create the vault API router
@@ -60,13 +90,31 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
                 axum::http::header::ACCEPT,
                 axum::http::HeaderName::from_static("x-rustyvault-token"),
                 axum::http::HeaderName::from_static("x-vault-token"),
+                axum::http::HeaderName::from_static("idempotency-key"),
             ])
             .allow_credentials(true)
     };
     // Public routes (no auth required)
     let state_clone = state.clone();
     let public_routes = Router::new()
-        .route("/v1/sys/health", axum::routing::get(sys_handlers::health_check))
+        .route("/v1/sys/health", axum::routing::get({
+            let state = state_clone.clone();
+            move |query: axum::extract::Query<std::collections::HashMap<String, String>>| {
+                let state = state.clone();
+                async move {
+                    sys_handlers::health_check(state, query.0).await
+                }
+            }
+        }))
+        .route("/v1/sys/health/crypto-selftest", axum::routing::get({
+            let state = state_clone.clone();
+            move || {
+                let state = state.clone();
+                async move {
+                    sys_handlers::crypto_selftest(state).await
+                }
+            }
+        }))
         .route("/v1/sys/init", axum::routing::post({
             let state = state_clone.clone();
             move |payload: axum::extract::Json<serde_json::Value>| {
@@ -95,6 +143,42 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
                 }
             }
         }))
+        // Generate-root is authorized by unseal key shares, not a token:
+        // it exists for when no root token is left
+        .route("/v1/sys/generate-root/attempt", axum::routing::get({
+            let state = state_clone.clone();
+            move || {
+                let state = state.clone();
+                async move {
+                    sys_handlers::generate_root_status_with_state(state).await
+                }
+            }
+        }).post({
+            let state = state_clone.clone();
+            move |payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                async move {
+                    sys_handlers::start_generate_root_with_state(state, payload.0).await
+                }
+            }
+        }).delete({
+            let state = state_clone.clone();
+            move || {
+                let state = state.clone();
+                async move {
+                    sys_handlers::cancel_generate_root_with_state(state).await
+                }
+            }
+        }))
+        .route("/v1/sys/generate-root/update", axum::routing::post({
+            let state = state_clone.clone();
+            move |payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                async move {
+                    sys_handlers::update_generate_root_with_state(state, payload.0).await
+                }
+            }
+        }))
         // UserPass login doesn't require auth
         .route("/v1/auth/userpass/login/{username}", axum::routing::post({
             let state = state_clone.clone();
@@ -105,6 +189,24 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
                     auth_handlers::userpass_login(state, username, payload).await
                 }
             }
+        }))
+        // LDAP login doesn't require auth
+        .route("/v1/auth/ldap/login/{username}", axum::routing::post({
+            let state = state_clone.clone();
+            move |path: axum::extract::Path<String>, payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                let username = path.0;
+                async move {
+                    auth_handlers::ldap_login(state, username, payload).await
+                }
+            }
+        }))
+        // Inside auth on protected routes, so entries name the caller
+        .layer(middleware::from_fn({
+            let audit = state.audit.clone();
+            move |req: axum::extract::Request, next: axum::middleware::Next| {
+                audit_middleware(audit.clone(), req, next)
+            }
         }));
     
     // Protected routes (auth required)
@@ -112,11 +214,167 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
     let protected_routes = Router::new()
         // System routes
         .route("/v1/sys/seal", axum::routing::post({
+            let state = state_clone2.clone();
+            move |auth: Option<axum::Extension<AuthInfo>>,
+                  payload: Option<axum::extract::Json<serde_json::Value>>| {
+                let state = state.clone();
+                async move {
+                    sys_handlers::seal_with_state(state, auth.as_deref(), payload.map(|p| p.0)).await
+                }
+            }
+        }))
+        .route("/v1/sys/seal/progress", axum::routing::get({
+            let state = state_clone2.clone();
+            move || {
+                let state = state.clone();
+                async move {
+                    sys_handlers::seal_progress_with_state(state).await
+                }
+            }
+        }).delete({
+            let state = state_clone2.clone();
+            move |auth: Option<axum::Extension<AuthInfo>>| {
+                let state = state.clone();
+                async move {
+                    sys_handlers::cancel_seal_with_state(state, auth.as_deref()).await
+                }
+            }
+        }))
+        .route("/v1/sys/step-down", axum::routing::put({
+            let state = state_clone2.clone();
+            move || {
+                let state = state.clone();
+                async move {
+                    sys_handlers::step_down_with_state(state).await
+                }
+            }
+        }))
+        .route("/v1/sys/read-only", axum::routing::get({
             let state = state_clone2.clone();
             move || {
                 let state = state.clone();
                 async move {
-                    sys_handlers::seal_with_state(state).await
+                    sys_handlers::read_only_status(state).await
+                }
+            }
+        }).put({
+            let state = state_clone2.clone();
+            move |payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                async move {
+                    sys_handlers::set_read_only(state, payload).await
+                }
+            }
+        }))
+        .route("/v1/sys/storage/snapshot", axum::routing::get({
+            let state = state_clone2.clone();
+            move || {
+                let state = state.clone();
+                async move {
+                    sys_handlers::storage_snapshot(state).await
+                }
+            }
+        }))
+        .route("/v1/sys/storage/snapshot/restore", axum::routing::post({
+            let state = state_clone2.clone();
+            move |body: axum::body::Bytes| {
+                let state = state.clone();
+                async move {
+                    sys_handlers::restore_storage_snapshot(state, body).await
+                }
+            }
+        // Snapshots hold the whole store and easily exceed the default 2 MB
+        }).layer(axum::extract::DefaultBodyLimit::disable()))
+        // GET only reports; a POST repairs only with an explicit `repair=true`
+        .route("/v1/sys/storage/fsck/{*path}", axum::routing::get({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>,
+                  auth: Option<axum::Extension<AuthInfo>>| {
+                let state = state.clone();
+                let path_str = path.0;
+                async move {
+                    sys_handlers::storage_fsck(state, auth.as_deref(), path_str, false).await
+                }
+            }
+        }).post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>,
+                  auth: Option<axum::Extension<AuthInfo>>,
+                  query: axum::extract::Query<std::collections::HashMap<String, String>>| {
+                let state = state.clone();
+                let path_str = path.0;
+                let repair = query.get("repair").map(|v| v == "true").unwrap_or(false);
+                async move {
+                    sys_handlers::storage_fsck(state, auth.as_deref(), path_str, repair).await
+                }
+            }
+        }))
+        .route("/v1/sys/expiration/status", axum::routing::get({
+            let state = state_clone2.clone();
+            move || {
+                let state = state.clone();
+                async move {
+                    sys_handlers::expiration_status(state).await
+                }
+            }
+        }))
+        
+        // ============================================================
+        // Lease routes
+        // ============================================================
+        .route("/v1/sys/leases/lookup", axum::routing::put({
+            let state = state_clone2.clone();
+            move |payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                async move {
+                    sys_handlers::lookup_lease(state, payload).await
+                }
+            }
+        }))
+        .route("/v1/sys/leases/revoke", axum::routing::put({
+            let state = state_clone2.clone();
+            move |payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                async move {
+                    sys_handlers::revoke_lease(state, payload).await
+                }
+            }
+        }))
+
+        // ============================================================
+        // Mount routes
+        // ============================================================
+        .route("/v1/sys/mounts", axum::routing::get({
+            let state = state_clone2.clone();
+            move |auth: Option<axum::Extension<AuthInfo>>| {
+                let state = state.clone();
+                async move {
+                    sys_handlers::list_mounts(state, auth.as_deref()).await
+                }
+            }
+        }))
+        .route("/v1/sys/mounts/{*path}", axum::routing::post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>,
+                  auth: Option<axum::Extension<AuthInfo>>,
+                  payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                let path_str = path.0;
+                async move {
+                    sys_handlers::enable_mount(state, auth.as_deref(), path_str, payload).await
+                }
+            }
+        }))
+        .route("/v1/sys/mounts/{*path}", axum::routing::delete({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>,
+                  auth: Option<axum::Extension<AuthInfo>>,
+                  query: axum::extract::Query<std::collections::HashMap<String, String>>| {
+                let state = state.clone();
+                let path_str = path.0;
+                let purge = query.get("purge").map(|v| v == "true").unwrap_or(false);
+                async move {
+                    sys_handlers::disable_mount(state, auth.as_deref(), path_str, purge).await
                 }
             }
         }))
@@ -124,33 +382,56 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
         // ============================================================
         // Secrets routes
         // ============================================================
+        .route("/v1/sys/secrets/batch-read", axum::routing::post({
+            let state = state_clone2.clone();
+            move |auth: Option<axum::Extension<AuthInfo>>,
+                  payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                async move {
+                    secrets_handlers::batch_read_with_state(state, auth.as_deref(), payload.0).await
+                }
+            }
+        }))
         .route("/v1/secret/{*path}", axum::routing::get({
             let state = state_clone2.clone();
-            move |path: axum::extract::Path<String>| {
+            move |path: axum::extract::Path<String>,
+                  auth: Option<axum::Extension<AuthInfo>>,
+                  query: axum::extract::Query<std::collections::HashMap<String, String>>| {
                 let state = state.clone();
                 let path_str = path.0;
+                let query = query.0;
                 async move {
-                    secrets_handlers::read_secret_with_state(state, path_str).await
+                    secrets_handlers::read_secret_with_state(state, auth.as_deref(), path_str, query).await
                 }
             }
         }))
         .route("/v1/secret/{*path}", axum::routing::post({
             let state = state_clone2.clone();
-            move |path: axum::extract::Path<String>, payload: axum::extract::Json<serde_json::Value>| {
+            move |path: axum::extract::Path<String>,
+                  headers: axum::http::HeaderMap,
+                  auth: Option<axum::Extension<AuthInfo>>,
+                  payload: axum::extract::Json<serde_json::Value>| {
                 let state = state.clone();
                 let path_str = path.0;
                 async move {
-                    secrets_handlers::write_secret_with_state(state, path_str, payload).await
+                    let request = serde_json::json!({ "path": &path_str, "data": &payload.0 });
+                    idempotency::run_idempotent(
+                        state.idempotency.as_ref(),
+                        &headers,
+                        auth.as_deref(),
+                        &request,
+                        || secrets_handlers::write_secret_with_state(state.clone(), auth.as_deref(), path_str, payload),
+                    ).await
                 }
             }
         }))
         .route("/v1/secret/{*path}", axum::routing::delete({
             let state = state_clone2.clone();
-            move |path: axum::extract::Path<String>| {
+            move |path: axum::extract::Path<String>, auth: Option<axum::Extension<AuthInfo>>| {
                 let state = state.clone();
                 let path_str = path.0;
                 async move {
-                    secrets_handlers::delete_secret_with_state(state, path_str).await
+                    secrets_handlers::delete_secret_with_state(state, auth.as_deref(), path_str).await
                 }
             }
         }))
@@ -160,40 +441,42 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
         // ============================================================
         .route("/v1/sys/policies/acl", axum::routing::get({
             let state = state_clone2.clone();
-            move || {
+            move |auth: Option<axum::Extension<AuthInfo>>| {
                 let state = state.clone();
                 async move {
-                    policy_handlers::list_policies(state).await
+                    policy_handlers::list_policies(state, auth.as_deref()).await
                 }
             }
         }))
         .route("/v1/sys/policies/acl/{name}", axum::routing::get({
             let state = state_clone2.clone();
-            move |path: axum::extract::Path<String>| {
+            move |path: axum::extract::Path<String>, auth: Option<axum::Extension<AuthInfo>>| {
                 let state = state.clone();
                 let name = path.0;
                 async move {
-                    policy_handlers::read_policy(state, name).await
+                    policy_handlers::read_policy(state, auth.as_deref(), name).await
                 }
             }
         }))
         .route("/v1/sys/policies/acl/{name}", axum::routing::post({
             let state = state_clone2.clone();
-            move |path: axum::extract::Path<String>, payload: axum::extract::Json<serde_json::Value>| {
+            move |path: axum::extract::Path<String>,
+                  auth: Option<axum::Extension<AuthInfo>>,
+                  payload: axum::extract::Json<serde_json::Value>| {
                 let state = state.clone();
                 let name = path.0;
                 async move {
-                    policy_handlers::write_policy(state, name, payload).await
+                    policy_handlers::write_policy(state, auth.as_deref(), name, payload).await
                 }
             }
         }))
         .route("/v1/sys/policies/acl/{name}", axum::routing::delete({
             let state = state_clone2.clone();
-            move |path: axum::extract::Path<String>| {
+            move |path: axum::extract::Path<String>, auth: Option<axum::Extension<AuthInfo>>| {
                 let state = state.clone();
                 let name = path.0;
                 async move {
-                    policy_handlers::delete_policy(state, name).await
+                    policy_handlers::delete_policy(state, auth.as_deref(), name).await
                 }
             }
         }))
@@ -206,16 +489,44 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
                 }
             }
         }))
+        .route("/v1/sys/policy/lint", axum::routing::post({
+            let state = state_clone2.clone();
+            move |auth: Option<axum::Extension<AuthInfo>>,
+                  payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                async move {
+                    policy_handlers::lint_policy(state, auth.as_deref(), payload).await
+                }
+            }
+        }))
+        .route("/v1/sys/policy/explain", axum::routing::post({
+            let state = state_clone2.clone();
+            move |payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                async move {
+                    policy_handlers::explain_policy(state, payload).await
+                }
+            }
+        }))
         
         // ============================================================
         // Token routes
         // ============================================================
         .route("/v1/auth/token/create", axum::routing::post({
             let state = state_clone2.clone();
-            move |payload: axum::extract::Json<serde_json::Value>| {
+            move |headers: axum::http::HeaderMap,
+                  auth: Option<axum::Extension<AuthInfo>>,
+                  payload: axum::extract::Json<serde_json::Value>| {
                 let state = state.clone();
                 async move {
-                    auth_handlers::create_token(state, payload).await
+                    let request = payload.0.clone();
+                    idempotency::run_idempotent(
+                        state.idempotency.as_ref(),
+                        &headers,
+                        auth.as_deref(),
+                        &request,
+                        || auth_handlers::create_token(state.clone(), auth.as_deref(), payload),
+                    ).await
                 }
             }
         }))
@@ -242,6 +553,43 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
                 }
             }
         }))
+        .route("/v1/auth/token/lookup-accessor", axum::routing::post({
+            let state = state_clone2.clone();
+            move |auth: Option<axum::Extension<AuthInfo>>, payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                async move {
+                    auth_handlers::lookup_token_by_accessor(state, auth.as_deref(), payload).await
+                }
+            }
+        }))
+        .route("/v1/auth/token/revoke-accessor", axum::routing::post({
+            let state = state_clone2.clone();
+            move |auth: Option<axum::Extension<AuthInfo>>, payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                async move {
+                    auth_handlers::revoke_token_by_accessor(state, auth.as_deref(), payload).await
+                }
+            }
+        }))
+        .route("/v1/auth/token/accessors", axum::routing::get({
+            let state = state_clone2.clone();
+            move |auth: Option<axum::Extension<AuthInfo>>| {
+                let state = state.clone();
+                async move {
+                    auth_handlers::list_token_accessors(state, auth.as_deref()).await
+                }
+            }
+        }))
+        .route("/v1/auth/token/list", axum::routing::get({
+            let state = state_clone2.clone();
+            move |auth: Option<axum::Extension<AuthInfo>>,
+                  query: axum::extract::Query<std::collections::HashMap<String, String>>| {
+                let state = state.clone();
+                async move {
+                    auth_handlers::list_tokens(state, auth.as_deref(), query.0).await
+                }
+            }
+        }))
         .route("/v1/auth/token/renew", axum::routing::post({
             let state = state_clone2.clone();
             move |payload: axum::extract::Json<serde_json::Value>| {
@@ -253,10 +601,10 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
         }))
         .route("/v1/auth/token/revoke", axum::routing::post({
             let state = state_clone2.clone();
-            move |payload: axum::extract::Json<serde_json::Value>| {
+            move |auth: Option<axum::Extension<AuthInfo>>, payload: axum::extract::Json<serde_json::Value>| {
                 let state = state.clone();
                 async move {
-                    auth_handlers::revoke_token(state, payload).await
+                    auth_handlers::revoke_token(state, auth.as_deref(), payload).await
                 }
             }
         }))
@@ -317,13 +665,128 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
                 }
             }
         }))
+
+        // ============================================================
+        // LDAP routes
+        // ============================================================
+        .route("/v1/auth/ldap/config", axum::routing::get({
+            let state = state_clone2.clone();
+            move || {
+                let state = state.clone();
+                async move {
+                    auth_handlers::read_ldap_config(state).await
+                }
+            }
+        }))
+        .route("/v1/auth/ldap/config", axum::routing::post({
+            let state = state_clone2.clone();
+            move |payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                async move {
+                    auth_handlers::write_ldap_config(state, payload).await
+                }
+            }
+        }))
+        .route("/v1/auth/ldap/groups", axum::routing::get({
+            let state = state_clone2.clone();
+            move || {
+                let state = state.clone();
+                async move {
+                    auth_handlers::list_ldap_groups(state).await
+                }
+            }
+        }))
+        .route("/v1/auth/ldap/groups/{name}", axum::routing::get({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>| {
+                let state = state.clone();
+                let name = path.0;
+                async move {
+                    auth_handlers::read_ldap_group(state, name).await
+                }
+            }
+        }))
+        .route("/v1/auth/ldap/groups/{name}", axum::routing::post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>, payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                let name = path.0;
+                async move {
+                    auth_handlers::write_ldap_group(state, name, payload).await
+                }
+            }
+        }))
+        .route("/v1/auth/ldap/groups/{name}", axum::routing::delete({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>| {
+                let state = state.clone();
+                let name = path.0;
+                async move {
+                    auth_handlers::delete_ldap_group(state, name).await
+                }
+            }
+        }))
+        
+        // ============================================================
+        // Catch-all routes, after every explicit route
+        // ============================================================
+        // Unknown sys/ paths are not logical requests
+        .route("/v1/sys/{*path}", axum::routing::any(|| async {
+            crate::errors::VaultError::NotFound("unsupported path".to_string())
+        }))
+        // Engines mounted through sys/mounts at other paths
+        .route("/v1/{*path}", axum::routing::get({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>,
+                  auth: Option<axum::Extension<AuthInfo>>,
+                  query: axum::extract::Query<std::collections::HashMap<String, String>>| {
+                let state = state.clone();
+                let path_str = path.0;
+                let query = query.0;
+                async move {
+                    secrets_handlers::logical_request_with_state(state, auth.as_deref(), axum::http::Method::GET, path_str, None, query).await
+                }
+            }
+        }))
+        .route("/v1/{*path}", axum::routing::post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>,
+                  auth: Option<axum::Extension<AuthInfo>>,
+                  payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                let path_str = path.0;
+                async move {
+                    secrets_handlers::logical_request_with_state(state, auth.as_deref(), axum::http::Method::POST, path_str, Some(payload.0), Default::default()).await
+                }
+            }
+        }))
+        .route("/v1/{*path}", axum::routing::delete({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>, auth: Option<axum::Extension<AuthInfo>>| {
+                let state = state.clone();
+                let path_str = path.0;
+                async move {
+                    secrets_handlers::logical_request_with_state(state, auth.as_deref(), axum::http::Method::DELETE, path_str, None, Default::default()).await
+                }
+            }
+        }))
         
+        .layer(middleware::from_fn({
+            let audit = state.audit.clone();
+            move |req: axum::extract::Request, next: axum::middleware::Next| {
+                audit_middleware(audit.clone(), req, next)
+            }
+        }))
         .layer(middleware::from_fn({
             let state = state.clone();
             move |req: axum::extract::Request, next: axum::middleware::Next| {
                 let state = state.clone();
                 async move {
-                    auth_middleware(state, req, next).await
+                    let (method, path) = (req.method().clone(), req.uri().path().to_string());
+                    match auth_middleware(state.clone(), req, next).await {
+                        Ok(response) => response,
+                        Err(refusal) => audit_refusal(state.audit.as_deref(), &method, &path, refusal).await,
+                    }
                 }
             }
         }));
@@ -331,5 +794,15 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
     Router::new()
         .merge(public_routes)
         .merge(protected_routes)
+        .layer(middleware::from_fn({
+            let state = state.clone();
+            move |req: axum::extract::Request, next: axum::middleware::Next| {
+                let state = state.clone();
+                async move {
+                    standby_middleware(state, req, next).await
+                }
+            }
+        }))
+        .layer(middleware::map_response(cache_control_middleware))
         .layer(cors_layer)
 }
@@ -3,6 +3,7 @@
 //! This crate provides Hashicorp Vault-compatible secrets management functionality
 //! while leveraging health-v1's shared infrastructure (database, logging, config).
 
+pub mod audit;
 pub mod core;
 pub mod errors;
 pub mod logical;
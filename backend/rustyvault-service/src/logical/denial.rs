@@ -0,0 +1,91 @@
+//! Policy for reporting denied access to callers
+//!
+//! Returning 403 for a path the caller may not see confirms that the path
+//! exists. The denial policy decides, per caller, whether a denial is
+//! reported as forbidden or disguised as not found.
+
+use serde::{Deserialize, Serialize};
+
+/// How a denied request is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DenialMode {
+    /// Report denials as forbidden (403)
+    Forbidden,
+    /// Report denials as not found (404) to hide whether the path exists
+    NotFound,
+}
+
+impl std::str::FromStr for DenialMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "forbidden" | "403" => Ok(DenialMode::Forbidden),
+            "not_found" | "notfound" | "404" => Ok(DenialMode::NotFound),
+            other => Err(format!("unknown denial mode: {}", other)),
+        }
+    }
+}
+
+/// Configurable denial reporting policy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DenialPolicy {
+    /// Mode applied to ordinary callers
+    pub mode: DenialMode,
+    /// Callers holding any of these policies always see the real 403
+    pub reveal_policies: Vec<String>,
+}
+
+impl DenialPolicy {
+    /// Resolve how a denial is reported to a caller with the given policies
+    pub fn resolve(&self, policies: &[String]) -> DenialMode {
+        if self.mode == DenialMode::NotFound
+            && !policies.iter().any(|p| self.reveal_policies.contains(p))
+        {
+            DenialMode::NotFound
+        } else {
+            DenialMode::Forbidden
+        }
+    }
+}
+
+impl Default for DenialPolicy {
+    fn default() -> Self {
+        Self {
+            mode: DenialMode::Forbidden,
+            reveal_policies: vec!["admin".to_string()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forbidden_mode_always_reveals() {
+        let policy = DenialPolicy::default();
+        assert_eq!(policy.resolve(&["default".to_string()]), DenialMode::Forbidden);
+    }
+
+    #[test]
+    fn test_not_found_mode_hides_from_low_privilege_callers() {
+        let policy = DenialPolicy {
+            mode: DenialMode::NotFound,
+            reveal_policies: vec!["admin".to_string()],
+        };
+        assert_eq!(policy.resolve(&["default".to_string()]), DenialMode::NotFound);
+        assert_eq!(
+            policy.resolve(&["default".to_string(), "admin".to_string()]),
+            DenialMode::Forbidden
+        );
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!("not_found".parse::<DenialMode>().unwrap(), DenialMode::NotFound);
+        assert_eq!("Forbidden".parse::<DenialMode>().unwrap(), DenialMode::Forbidden);
+        assert!("hide".parse::<DenialMode>().is_err());
+    }
+}
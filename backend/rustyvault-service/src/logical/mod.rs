@@ -6,8 +6,10 @@
 pub mod request;
 pub mod response;
 pub mod backend;
+pub mod denial;
 
-pub use request::{Request, Operation};
-pub use response::{Response, ResponseAuth};
+pub use request::{Request, RequestContext, Operation};
+pub use response::{CacheHint, Response, ResponseAuth, ResponseLease};
 pub use backend::Backend;
+pub use denial::{DenialMode, DenialPolicy};
 
@@ -2,6 +2,9 @@
 
 use serde_json::{Map, Value};
 use std::collections::HashMap;
+use std::net::IpAddr;
+use shared::infrastructure::tls::ClientCertificate;
+use crate::core::Namespace;
 
 /// Logical request for vault operations
 #[derive(Debug, Clone, Default)]
@@ -9,9 +12,34 @@ pub struct Request {
     pub id: String,
     pub operation: Operation,
     pub path: String,
+    /// Mount path of the backend handling this request (set by the router)
+    pub mount_point: String,
     pub client_token: String,
     pub data: Option<Map<String, Value>>,
+    /// Optional projection: only these fields are returned in the response data
+    pub fields: Option<Vec<String>>,
     pub headers: HashMap<String, String>,
+    /// Namespace of the requesting token; the router scopes the path to it
+    pub namespace: Option<Namespace>,
+    /// Certificate the client authenticated the TLS connection with
+    pub client_cert: Option<ClientCertificate>,
+    /// Who sent the request, for audit records and per-identity paths;
+    /// `None` for requests not made over HTTP
+    pub context: Option<RequestContext>,
+}
+
+/// The caller of a request, as seen by the HTTP layer
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestContext {
+    /// Entity ID of the token, or its display name when it has no entity
+    pub identity: String,
+    pub token_accessor: String,
+    /// The caller's `X-Request-Id`, or the request's own ID without one
+    pub request_id: String,
+    /// Peer address of the connection (the proxy's, behind a proxy)
+    pub client_ip: Option<IpAddr>,
+    /// Namespace the token is confined to; routing uses `Request::namespace`
+    pub namespace: Option<Namespace>,
 }
 
 impl Default for Operation {
@@ -46,9 +74,14 @@ impl Request {
             id: uuid::Uuid::new_v4().to_string(),
             operation: Operation::Read,
             path: path.into(),
+            mount_point: String::new(),
             client_token: String::new(),
             data: None,
+            fields: None,
             headers: HashMap::new(),
+            namespace: None,
+            client_cert: None,
+            context: None,
         }
     }
 
@@ -57,9 +90,14 @@ impl Request {
             id: uuid::Uuid::new_v4().to_string(),
             operation: Operation::Write,
             path: path.into(),
+            mount_point: String::new(),
             client_token: String::new(),
             data,
+            fields: None,
             headers: HashMap::new(),
+            namespace: None,
+            client_cert: None,
+            context: None,
         }
     }
 
@@ -68,9 +106,14 @@ impl Request {
             id: uuid::Uuid::new_v4().to_string(),
             operation: Operation::Delete,
             path: path.into(),
+            mount_point: String::new(),
             client_token: String::new(),
             data,
+            fields: None,
             headers: HashMap::new(),
+            namespace: None,
+            client_cert: None,
+            context: None,
         }
     }
 
@@ -79,9 +122,14 @@ impl Request {
             id: uuid::Uuid::new_v4().to_string(),
             operation: Operation::List,
             path: path.into(),
+            mount_point: String::new(),
             client_token: String::new(),
             data: None,
+            fields: None,
             headers: HashMap::new(),
+            namespace: None,
+            client_cert: None,
+            context: None,
         }
     }
 }
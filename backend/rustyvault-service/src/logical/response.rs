@@ -25,14 +25,50 @@ pub struct ResponseAuth {
 /// Alias for backwards compatibility
 pub type Auth = ResponseAuth;
 
+/// Lease attached to a dynamically issued secret
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResponseLease {
+    pub lease_id: String,
+    /// Seconds until the secret is revoked
+    pub lease_duration: u64,
+    #[serde(default)]
+    pub renewable: bool,
+}
+
+/// How long clients may cache a response, sent as `Cache-Control`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheHint {
+    /// Never cache; the default, since most responses carry secrets
+    #[default]
+    NoStore,
+    /// Clients may reuse the response for this many seconds
+    MaxAge(u64),
+}
+
+impl CacheHint {
+    /// Value for the `Cache-Control` header
+    pub fn header_value(&self) -> String {
+        match self {
+            CacheHint::NoStore | CacheHint::MaxAge(0) => "no-store".to_string(),
+            // Private: shared caches must never hold a secret
+            CacheHint::MaxAge(secs) => format!("private, max-age={}", secs),
+        }
+    }
+}
+
 /// Logical response for vault operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Response {
     pub data: Option<Map<String, Value>>,
     pub auth: Option<Auth>,
+    #[serde(default)]
+    pub lease: Option<ResponseLease>,
     pub warnings: Vec<String>,
     pub wrap_info: Option<Map<String, Value>>,
     pub redirect: Option<String>,
+    #[serde(default)]
+    pub cache: CacheHint,
 }
 
 impl Response {
@@ -40,9 +76,11 @@ impl Response {
         Self {
             data: None,
             auth: None,
+            lease: None,
             warnings: Vec::new(),
             wrap_info: None,
             redirect: None,
+            cache: CacheHint::NoStore,
         }
     }
 
@@ -55,6 +93,45 @@ impl Response {
         self.auth = Some(auth);
         self
     }
+
+    pub fn lease(mut self, lease: ResponseLease) -> Self {
+        self.lease = Some(lease);
+        self
+    }
+
+    pub fn cache(mut self, cache: CacheHint) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Attach a non-fatal notice, returned to the caller as `warnings`
+    pub fn warning(mut self, warning: impl Into<String>) -> Self {
+        self.warnings.push(warning.into());
+        self
+    }
+
+    /// Restrict the response data to the named fields
+    ///
+    /// Responses of `versioned` (KV v2) engines keep the secret under a
+    /// nested `data` object; the projection applies to that object and
+    /// leaves the envelope (e.g. `version`) intact, and responses without
+    /// one are left alone. Requested fields that are absent are silently
+    /// omitted.
+    pub fn project_fields(&mut self, fields: &[String], versioned: bool) {
+        let Some(data) = self.data.as_mut() else {
+            return;
+        };
+
+        let target = if versioned {
+            match data.get_mut("data") {
+                Some(Value::Object(inner)) => inner,
+                _ => return,
+            }
+        } else {
+            data
+        };
+        target.retain(|key, _| fields.iter().any(|f| f == key));
+    }
 }
 
 impl Default for Response {
@@ -63,3 +140,58 @@ impl Default for Response {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fields(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_project_flat_data() {
+        let data = json!({"username": "app", "password": "s3cret", "host": "db"});
+        let mut resp = Response::new().data(data.as_object().unwrap().clone());
+
+        resp.project_fields(&fields(&["username", "host", "missing"]), false);
+
+        assert_eq!(Value::Object(resp.data.unwrap()), json!({"username": "app", "host": "db"}));
+    }
+
+    #[test]
+    fn test_cache_hint_header() {
+        assert_eq!(Response::new().cache.header_value(), "no-store");
+        assert_eq!(CacheHint::MaxAge(0).header_value(), "no-store");
+        assert_eq!(CacheHint::MaxAge(60).header_value(), "private, max-age=60");
+    }
+
+    #[test]
+    fn test_project_versioned_data() {
+        let data = json!({"data": {"username": "app", "password": "s3cret"}, "version": 3});
+        let mut resp = Response::new().data(data.as_object().unwrap().clone());
+
+        resp.project_fields(&fields(&["username"]), true);
+
+        assert_eq!(
+            Value::Object(resp.data.unwrap()),
+            json!({"data": {"username": "app"}, "version": 3})
+        );
+    }
+
+    #[test]
+    fn test_nested_data_is_only_special_for_versioned_engines() {
+        // A flat secret that happens to have a `data` field
+        let data = json!({"data": {"a": 1}, "username": "app"});
+        let mut resp = Response::new().data(data.as_object().unwrap().clone());
+        resp.project_fields(&fields(&["username"]), false);
+        assert_eq!(Value::Object(resp.data.unwrap()), json!({"username": "app"}));
+
+        // Versioned responses without a secret, such as metadata, are kept
+        let metadata = json!({"current_version": 2, "versions": {}});
+        let mut resp = Response::new().data(metadata.as_object().unwrap().clone());
+        resp.project_fields(&fields(&["username"]), true);
+        assert_eq!(Value::Object(resp.data.unwrap()), metadata);
+    }
+}
+
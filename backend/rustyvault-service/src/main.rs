@@ -1,3 +1,4 @@
+mod audit;
 mod core;
 mod errors;
 mod logical;
@@ -66,50 +67,194 @@ async fn async_main() -> Result<(), String> {
         .unwrap_or_else(|| "./vault-data".to_string());
     let physical_backend = Arc::new(storage::physical_file::FileBackend::new(&storage_path)
         .map_err(|e| format!("Failed to create file backend: {}", e))?);
+    // Optional second layer under the barrier, keyed independently of unsealing
+    let storage_cipher = storage::StorageCipher::from_env()
+        .map_err(|e| format!("Failed to load storage encryption key: {}", e))?;
+    if storage_cipher.is_some() {
+        info!("Storage encryption at rest enabled");
+    }
+    let physical_backend = storage::EncryptedBackend::open(physical_backend, storage_cipher).await
+        .map_err(|e| format!("Failed to open storage: {}", e))?;
+    // Writes through the barrier pass the snapshot lock so sys/storage/snapshot
+    // captures a consistent point in time
+    let snapshots = Arc::new(storage::SnapshotBackend::new(physical_backend));
 
     // Initialize storage
     let metadata_store = Arc::new(storage::MetadataStore::new(Arc::new(pool.clone())));
-    let barrier_store = Arc::new(storage::BarrierStore::new(physical_backend.clone()));
+    let barrier_store = Arc::new(storage::BarrierStore::new(snapshots.clone()));
     let storage_adapter = Arc::new(storage::StorageAdapter::new(
         metadata_store,
         barrier_store.clone(),
     ));
 
     // Initialize vault core
-    let vault_core = Arc::new(core::VaultCore::new(storage_adapter));
+    let vault_core = Arc::new(core::VaultCore::with_kdf(
+        storage_adapter.clone(),
+        settings.barrier.kdf.clone(),
+    ));
+    // Refuse to start on a crypto stack that fails its known answers
+    let selftest = core::selftest::run(&vault_core.barrier);
+    if !selftest.passed {
+        return Err("Crypto self-test failed; refusing to start".to_string());
+    }
+    info!("Crypto self-test passed");
+    if settings.read_only {
+        vault_core.set_read_only(true);
+        tracing::warn!("Starting in read-only mode; lift it with PUT /v1/sys/read-only");
+    }
     
+    // Leases of dynamic secrets, revoked by the reaper once expired
+    let ttl_jitter = core::TtlJitter::new(settings.mounts.lease_ttl_jitter_percent);
+    let lease_manager = Arc::new(
+        core::LeaseManager::new(barrier_store.barrier()).with_ttl_jitter(ttl_jitter),
+    );
+
+    // Restore persisted secrets engine mounts
+    let mount_manager = Arc::new(
+        core::MountManager::new(
+            vault_core.router.clone(),
+            storage_adapter.clone(),
+            barrier_store.barrier(),
+        )
+        .with_leases(lease_manager.clone())
+        .with_seal_wrapper(vault_core.barrier.clone()),
+    );
+    mount_manager.load().await
+        .map_err(|e| format!("Failed to load mount table: {}", e))?;
+
     // Register default KV backend at "secret" mount
-    let kv_backend = Arc::new(modules::kv::KvBackend::new(
-        barrier_store.barrier(),
-        "secret".to_string(),
-    ));
-    vault_core.router.add_backend("secret".to_string(), kv_backend);
+    if !mount_manager.is_mounted("secret").await {
+        mount_manager.mount("secret", "kv", "key/value secret storage", Default::default()).await
+            .map_err(|e| format!("Failed to mount KV backend: {}", e))?;
+    }
     
     info!("Vault core initialized");
 
     // Initialize policy store
-    let policy_store = Arc::new(modules::policy::PolicyStore::new(pool.clone()));
+    let policy_store = Arc::new(modules::policy::PolicyStore::new(pool.clone()).with_limits(settings.policy_limits));
     policy_store.init().await
         .map_err(|e| format!("Failed to initialize policy store: {}", e))?;
     info!("Policy store initialized");
 
     // Initialize token store
-    let token_store = Arc::new(modules::auth::TokenStore::new(pool.clone()));
-    info!("Token store initialized");
+    let signing_key = match &settings.tokens.signing_key {
+        Some(key) => key.as_bytes().to_vec(),
+        None => {
+            if settings.tokens.format == modules::auth::TokenFormat::Signed {
+                tracing::warn!("VAULT_TOKEN_SIGNING_KEY not set; signed tokens will need a storage lookup after a restart or on other replicas");
+            }
+            let mut key = vec![0u8; 32];
+            ring::rand::SecureRandom::fill(&ring::rand::SystemRandom::new(), &mut key)
+                .map_err(|_| "Failed to generate token signing key".to_string())?;
+            key
+        }
+    };
+    let token_store = Arc::new(
+        modules::auth::TokenStore::new(pool.clone())
+            .with_ttl_jitter(ttl_jitter, settings.mounts.max_lease_ttl)
+            .with_format(
+                settings.tokens.format,
+                &signing_key,
+                Duration::from_secs(settings.tokens.denylist_refresh_secs),
+            ),
+    );
+    info!("Token store initialized (format={})", settings.tokens.format);
+
+    // Login failure counts; with COUNTER_STORE=redis they are shared by every replica
+    let counter_config = shared::config::providers::CounterStoreConfig::from_env()
+        .map_err(|e| format!("Failed to load counter store config: {}", e))?;
+    let counters = shared::infrastructure::providers::create_counter_store(&counter_config)
+        .await
+        .map_err(|e| format!("Failed to create counter store: {}", e))?;
 
     // Initialize UserPass backend
     let userpass_backend = Arc::new(modules::auth::UserPassBackend::new(
         pool.clone(),
         "auth/userpass",
+        counters,
     ));
     info!("UserPass backend initialized");
 
+    // Initialize LDAP backend; its config and group mappings live behind the barrier
+    let ldap_backend = Arc::new(modules::auth::LdapBackend::new(
+        barrier_store.barrier(),
+        token_store.clone(),
+        Arc::new(modules::auth::Ldap3Connector),
+        "auth/ldap",
+    ));
+    info!("LDAP backend initialized");
+
+    let idempotency_store = Arc::new(http::idempotency::IdempotencyStore::new(
+        barrier_store.barrier(),
+        chrono::Duration::seconds(settings.idempotency_ttl_secs as i64),
+    ));
+
+    // Start the expired token / record reaper
+    let reaper = Arc::new(
+        core::ExpirationReaper::new(
+            Duration::from_secs(settings.reaper.interval_secs),
+            settings.reaper.batch_size,
+        )
+        .with_source(token_store.clone())
+        .with_source(idempotency_store.clone())
+        .with_source(lease_manager.clone())
+        .with_lock(shared::infrastructure::database::DistributedLock::new(pool.clone()))
+        .with_vault(vault_core.clone()),
+    );
+    reaper.clone().spawn();
+    info!("Expiration reaper started (interval={}s)", settings.reaper.interval_secs);
+
+    // Enable audit devices
+    let audit_broker = match &settings.audit.file_path {
+        Some(path) => {
+            let hmac_key = match &settings.audit.hmac_key {
+                Some(key) => key.as_bytes().to_vec(),
+                None => {
+                    tracing::warn!("VAULT_AUDIT_HMAC_KEY not set; audit HMACs will not be comparable across restarts");
+                    let mut key = vec![0u8; 32];
+                    ring::rand::SecureRandom::fill(&ring::rand::SystemRandom::new(), &mut key)
+                        .map_err(|_| "Failed to generate audit HMAC key".to_string())?;
+                    key
+                }
+            };
+            let device = audit::FileAuditDevice::open(path).await
+                .map_err(|e| format!("Failed to open audit log {}: {}", path, e))?;
+            info!("Audit file device enabled at {} (blocking={})", path, settings.audit.blocking);
+            Some(Arc::new(
+                audit::AuditBroker::new(hmac_key, settings.audit.blocking)
+                    .with_redactor(shared::shared::masking::Redactor::new(&settings.audit.redaction))
+                    .with_device(Arc::new(device)),
+            ))
+        }
+        None => None,
+    };
+
     // Create app state
     let app_state = Arc::new(http::routes::AppState {
         core: vault_core,
         policy_store: Some(policy_store),
         token_store: Some(token_store),
         userpass: Some(userpass_backend),
+        ldap: Some(ldap_backend),
+        mounts: Some(mount_manager),
+        leases: Some(lease_manager),
+        denial_policy: settings.access.denial_policy.clone(),
+        namespace_isolation: settings.access.namespace_isolation,
+        idempotency: Some(idempotency_store),
+        reaper: Some(reaper),
+        audit: audit_broker,
+        ha: settings.ha.clone(),
+        snapshots: Some(snapshots),
+        seal_quorum: Arc::new(core::SealQuorum::new(
+            settings.seal.seal_quorum,
+            std::time::Duration::from_secs(settings.seal.seal_quorum_timeout_secs),
+        )),
+        generate_root: Arc::new(core::GenerateRoot::new(
+            modules::auth::RAW_TOKEN_LEN,
+            std::time::Duration::from_secs(settings.seal.generate_root_timeout_secs),
+        )),
+        generated_root_ttl_secs: settings.seal.generated_root_ttl_secs,
+        health_crypto_selftest: settings.health_crypto_selftest,
     });
 
     // Create router - using closures to capture state
@@ -117,12 +262,21 @@ async fn async_main() -> Result<(), String> {
 
     // Start server - need to convert Router<Arc<AppState>> to service
     let addr = SocketAddr::from(([0, 0, 0, 0], settings.server.port));
+    if settings.server.tls.enabled {
+        let tls_config = shared::infrastructure::tls::server_config(&settings.server.tls)
+            .map_err(|e| format!("Failed to load TLS certificate: {}", e))?;
+        info!("RustyVault service listening on {} (TLS)", addr);
+        shared::infrastructure::tls::serve(addr, app, tls_config, &settings.server.tls).await
+            .map_err(|e| format!("Server error: {}", e))?;
+        return Ok(());
+    }
+
     let listener = tokio::net::TcpListener::bind(addr).await
         .map_err(|e| format!("Failed to bind to address {}: {}", addr, e))?;
     
     info!("RustyVault service listening on {}", addr);
     // Router<Arc<AppState>> needs IntoMakeService - the router has state already filled
-    axum::serve(listener, app).await
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await
         .map_err(|e| format!("Server error: {}", e))?;
     
     Ok(())
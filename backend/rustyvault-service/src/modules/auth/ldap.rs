@@ -0,0 +1,744 @@
+//! LDAP authentication method for RustyVault
+//!
+//! Authenticates users against an LDAP or Active Directory server and issues
+//! a token carrying the policies mapped to their LDAP groups:
+//! - Direct bind: the user DN is built from `userdn_template`, e.g.
+//!   `uid={{username}},ou=people,dc=example,dc=com` or, for AD,
+//!   `{{username}}@corp.example.com`
+//! - Search+bind: a service account (`binddn`) finds the user by `userattr`
+//!   under `userdn`, then the user's own credentials are bound
+//!
+//! The connection must use `ldaps://` or StartTLS unless `insecure_plaintext`
+//! is set. Configuration and group mappings live behind the barrier and are
+//! read on every login, so mapping changes apply on the next login.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::token::{CreateTokenRequest, TokenStore};
+use crate::errors::{VaultError, VaultResult};
+use crate::logical::{Backend, Operation, Request, Response, ResponseAuth};
+use crate::storage::StorageBackend;
+
+/// Default token TTL for LDAP logins (1 hour)
+const DEFAULT_LDAP_TTL: i64 = 3600;
+/// Default connect and per-operation timeout
+const DEFAULT_LDAP_TIMEOUT_SECS: u64 = 10;
+
+/// Connection and lookup settings, stored at `{mount}/config`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LdapConfig {
+    /// `ldaps://host:636` or `ldap://host:389`
+    pub url: String,
+    /// Upgrade an `ldap://` connection with StartTLS
+    #[serde(default)]
+    pub starttls: bool,
+    /// Allow `ldap://` without StartTLS; credentials cross the network in clear
+    #[serde(default)]
+    pub insecure_plaintext: bool,
+    /// Direct bind: DN (or AD UPN) with a `{{username}}` placeholder
+    #[serde(default)]
+    pub userdn_template: Option<String>,
+    /// Search+bind: service account DN and password
+    #[serde(default)]
+    pub binddn: Option<String>,
+    #[serde(default)]
+    pub bindpass: Option<String>,
+    /// Search+bind: base DN users are searched under
+    #[serde(default)]
+    pub userdn: Option<String>,
+    /// Search+bind: attribute matched against the login name
+    #[serde(default = "default_userattr")]
+    pub userattr: String,
+    /// Base DN groups are searched under; no group lookup when unset
+    #[serde(default)]
+    pub groupdn: Option<String>,
+    /// Group filter with `{{user_dn}}` and `{{username}}` placeholders
+    #[serde(default = "default_groupfilter")]
+    pub groupfilter: String,
+    /// Attribute naming a group in the mappings, or `dn` for its DN
+    #[serde(default = "default_groupattr")]
+    pub groupattr: String,
+    /// Policies every LDAP token gets in addition to the mapped ones
+    #[serde(default)]
+    pub token_policies: Vec<String>,
+    #[serde(default = "default_ttl")]
+    pub ttl: i64,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_userattr() -> String {
+    "uid".to_string()
+}
+
+fn default_groupfilter() -> String {
+    "(|(member={{user_dn}})(uniqueMember={{user_dn}})(memberUid={{username}}))".to_string()
+}
+
+fn default_groupattr() -> String {
+    "cn".to_string()
+}
+
+fn default_ttl() -> i64 {
+    DEFAULT_LDAP_TTL
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_LDAP_TIMEOUT_SECS
+}
+
+/// How the user's DN is found before their credentials are bound
+#[derive(Debug, Clone, PartialEq)]
+pub enum BindFlow<'a> {
+    Direct { template: &'a str },
+    SearchBind { bind_dn: &'a str, bind_password: &'a str, base_dn: &'a str },
+}
+
+impl LdapConfig {
+    /// Parse and validate a config from a write request body
+    ///
+    /// `bindpass` may be omitted to keep the one in `existing`, so a config
+    /// can be read, edited and written back without resending the secret.
+    pub fn from_request(data: &Map<String, Value>, existing: Option<&LdapConfig>) -> VaultResult<Self> {
+        let mut config: LdapConfig = serde_json::from_value(Value::Object(data.clone()))
+            .map_err(|e| VaultError::Validation(format!("invalid ldap config: {}", e)))?;
+        if config.bindpass.is_none() {
+            config.bindpass = existing
+                .filter(|existing| existing.binddn == config.binddn)
+                .and_then(|existing| existing.bindpass.clone());
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> VaultResult<()> {
+        let url = url::Url::parse(&self.url)
+            .map_err(|e| VaultError::Validation(format!("url '{}' is invalid: {}", self.url, e)))?;
+        match url.scheme() {
+            "ldaps" if self.starttls => {
+                return Err(VaultError::Validation("starttls cannot be used with ldaps://".to_string()))
+            }
+            "ldaps" => {}
+            "ldap" if self.starttls || self.insecure_plaintext => {}
+            "ldap" => {
+                return Err(VaultError::Validation(
+                    "ldap:// requires starttls; set insecure_plaintext to allow an unencrypted connection".to_string(),
+                ))
+            }
+            other => return Err(VaultError::Validation(format!("url scheme '{}' is not ldap or ldaps", other))),
+        }
+        if self.ttl <= 0 {
+            return Err(VaultError::Validation("ttl must be positive".to_string()));
+        }
+        if self.timeout_secs == 0 {
+            return Err(VaultError::Validation("timeout_secs must be positive".to_string()));
+        }
+        self.flow().map(|_| ())
+    }
+
+    pub fn flow(&self) -> VaultResult<BindFlow<'_>> {
+        match (&self.userdn_template, &self.binddn) {
+            (Some(template), None) if template.contains("{{username}}") => Ok(BindFlow::Direct { template }),
+            (Some(_), None) => Err(VaultError::Validation("userdn_template must contain {{username}}".to_string())),
+            (None, Some(bind_dn)) => {
+                let bind_password = self.bindpass.as_deref().filter(|p| !p.is_empty()).ok_or_else(|| {
+                    VaultError::Validation("bindpass is required with binddn".to_string())
+                })?;
+                let base_dn = self.userdn.as_deref().ok_or_else(|| {
+                    VaultError::Validation("userdn is required with binddn".to_string())
+                })?;
+                Ok(BindFlow::SearchBind { bind_dn, bind_password, base_dn })
+            }
+            (Some(_), Some(_)) => Err(VaultError::Validation(
+                "set either userdn_template (direct bind) or binddn (search+bind), not both".to_string(),
+            )),
+            (None, None) => Err(VaultError::Validation(
+                "one of userdn_template (direct bind) or binddn (search+bind) is required".to_string(),
+            )),
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    /// Config as returned on reads; the bind password is never returned
+    pub fn to_response_data(&self) -> Map<String, Value> {
+        let mut data = match serde_json::to_value(self) {
+            Ok(Value::Object(data)) => data,
+            _ => Map::new(),
+        };
+        data.remove("bindpass");
+        data
+    }
+}
+
+/// Policies granted to members of an LDAP group, stored at `{mount}/groups/{name}`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LdapGroupMapping {
+    #[serde(default)]
+    pub policies: Vec<String>,
+}
+
+/// A directory entry returned by a search
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LdapEntry {
+    pub dn: String,
+    pub attrs: HashMap<String, Vec<String>>,
+}
+
+/// Opens sessions to the directory server
+#[async_trait]
+pub trait LdapConnector: Send + Sync {
+    async fn connect(&self, config: &LdapConfig) -> VaultResult<Box<dyn LdapSession>>;
+}
+
+/// An open, TLS-protected (unless configured otherwise) LDAP connection
+#[async_trait]
+pub trait LdapSession: Send {
+    /// Simple bind; `Ok(false)` when the server rejects the credentials
+    async fn bind(&mut self, dn: &str, password: &str) -> VaultResult<bool>;
+
+    /// Subtree search under `base`
+    async fn search(&mut self, base: &str, filter: &str, attrs: &[&str]) -> VaultResult<Vec<LdapEntry>>;
+
+    async fn unbind(&mut self);
+}
+
+/// `LdapConnector` backed by the `ldap3` client
+pub struct Ldap3Connector;
+
+/// LDAP result code for rejected credentials
+const LDAP_INVALID_CREDENTIALS: u32 = 49;
+
+/// Transport and server failures, as opposed to rejected credentials
+fn ldap_error(e: ldap3::LdapError) -> VaultError {
+    VaultError::Internal(format!("ldap error: {}", e))
+}
+
+#[async_trait]
+impl LdapConnector for Ldap3Connector {
+    async fn connect(&self, config: &LdapConfig) -> VaultResult<Box<dyn LdapSession>> {
+        config.validate()?;
+        let settings = ldap3::LdapConnSettings::new()
+            .set_conn_timeout(config.timeout())
+            .set_starttls(config.starttls);
+        let (conn, ldap) = ldap3::LdapConnAsync::with_settings(settings, &config.url)
+            .await
+            .map_err(ldap_error)?;
+        ldap3::drive!(conn);
+        Ok(Box::new(Ldap3Session { ldap, timeout: config.timeout() }))
+    }
+}
+
+struct Ldap3Session {
+    ldap: ldap3::Ldap,
+    timeout: Duration,
+}
+
+#[async_trait]
+impl LdapSession for Ldap3Session {
+    async fn bind(&mut self, dn: &str, password: &str) -> VaultResult<bool> {
+        let result = self.ldap.with_timeout(self.timeout).simple_bind(dn, password).await.map_err(ldap_error)?;
+        match result.rc {
+            0 => Ok(true),
+            LDAP_INVALID_CREDENTIALS => Ok(false),
+            _ => Err(ldap_error(ldap3::LdapError::LdapResult { result })),
+        }
+    }
+
+    async fn search(&mut self, base: &str, filter: &str, attrs: &[&str]) -> VaultResult<Vec<LdapEntry>> {
+        let (entries, _) = self
+            .ldap
+            .with_timeout(self.timeout)
+            .search(base, ldap3::Scope::Subtree, filter, attrs.to_vec())
+            .await
+            .and_then(|result| result.success())
+            .map_err(ldap_error)?;
+        Ok(entries
+            .into_iter()
+            .map(|entry| {
+                let entry = ldap3::SearchEntry::construct(entry);
+                LdapEntry { dn: entry.dn, attrs: entry.attrs }
+            })
+            .collect())
+    }
+
+    async fn unbind(&mut self) {
+        if let Err(e) = self.ldap.unbind().await {
+            tracing::debug!("LDAP unbind failed: {}", e);
+        }
+    }
+}
+
+/// Login response
+#[derive(Debug, Clone, Serialize)]
+pub struct LdapLoginResponse {
+    pub client_token: String,
+    pub accessor: String,
+    pub policies: Vec<String>,
+    pub token_ttl: i64,
+    pub renewable: bool,
+    /// LDAP groups the user was found in
+    pub groups: Vec<String>,
+}
+
+/// LDAP backend for authentication
+pub struct LdapBackend {
+    storage: Arc<dyn StorageBackend>,
+    token_store: Arc<TokenStore>,
+    connector: Arc<dyn LdapConnector>,
+    mount_path: String,
+}
+
+impl LdapBackend {
+    pub fn new(
+        storage: Arc<dyn StorageBackend>,
+        token_store: Arc<TokenStore>,
+        connector: Arc<dyn LdapConnector>,
+        mount_path: &str,
+    ) -> Self {
+        Self {
+            storage,
+            token_store,
+            connector,
+            mount_path: mount_path.to_string(),
+        }
+    }
+
+    fn config_path(&self) -> String {
+        format!("{}/config", self.mount_path)
+    }
+
+    fn group_path(&self, name: &str) -> String {
+        format!("{}/groups/{}", self.mount_path, normalize_group(name))
+    }
+
+    pub async fn read_config(&self) -> VaultResult<Option<LdapConfig>> {
+        match self.storage.get(&self.config_path()).await? {
+            Some(raw) => Ok(Some(serde_json::from_slice(&raw)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn write_config(&self, data: &Map<String, Value>) -> VaultResult<LdapConfig> {
+        let existing = self.read_config().await?;
+        let config = LdapConfig::from_request(data, existing.as_ref())?;
+        self.storage.put(&self.config_path(), &serde_json::to_vec(&config)?).await?;
+        Ok(config)
+    }
+
+    pub async fn list_groups(&self) -> VaultResult<Vec<String>> {
+        let prefix = format!("{}/groups/", self.mount_path);
+        let mut names: Vec<String> = self
+            .storage
+            .list(&prefix)
+            .await?
+            .iter()
+            .map(|k| k.strip_prefix(&prefix).unwrap_or(k).to_string())
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    pub async fn get_group(&self, name: &str) -> VaultResult<Option<LdapGroupMapping>> {
+        match self.storage.get(&self.group_path(name)).await? {
+            Some(raw) => Ok(Some(serde_json::from_slice(&raw)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Map an LDAP group (matched case-insensitively) to policies
+    pub async fn write_group(&self, name: &str, policies: Vec<String>) -> VaultResult<LdapGroupMapping> {
+        if normalize_group(name).is_empty() || name.contains('/') {
+            return Err(VaultError::Validation("group name must be non-empty and contain no '/'".to_string()));
+        }
+        let mapping = LdapGroupMapping { policies };
+        self.storage.put(&self.group_path(name), &serde_json::to_vec(&mapping)?).await?;
+        Ok(mapping)
+    }
+
+    pub async fn delete_group(&self, name: &str) -> VaultResult<()> {
+        self.storage.delete(&self.group_path(name)).await
+    }
+
+    /// Authenticate against the directory and issue a token
+    pub async fn login(&self, username: &str, password: &str) -> VaultResult<LdapLoginResponse> {
+        let username = username.trim();
+        // An empty password is an unauthenticated bind, which servers accept
+        if username.is_empty() || password.is_empty() {
+            return Err(invalid_credentials());
+        }
+        let config = self
+            .read_config()
+            .await?
+            .ok_or_else(|| VaultError::Config("ldap auth is not configured".to_string()))?;
+
+        let mut session = self.connector.connect(&config).await?;
+        let result = authenticate(session.as_mut(), &config, username, password).await;
+        session.unbind().await;
+        let groups = result?;
+
+        let mut policies: BTreeSet<String> = config.token_policies.iter().cloned().collect();
+        for group in &groups {
+            if let Some(mapping) = self.get_group(group).await? {
+                policies.extend(mapping.policies);
+            }
+        }
+        let policies: Vec<String> = policies.into_iter().collect();
+
+        let request = CreateTokenRequest {
+            display_name: format!("ldap-{}", username),
+            policies: policies.clone(),
+            ttl: config.ttl,
+            renewable: true,
+            num_uses: 0,
+            meta: Some(serde_json::json!({
+                "username": username,
+                "auth_method": "ldap",
+                "groups": groups,
+            })),
+        };
+        let path = format!("{}/login/{}", self.mount_path, username);
+        let (entry, raw_token) = self.token_store.create_token(&request, None, &path).await?;
+        tracing::info!("LDAP login for {} with policies {:?}", username, policies);
+
+        Ok(LdapLoginResponse {
+            client_token: raw_token,
+            accessor: entry.accessor(),
+            policies,
+            token_ttl: config.ttl,
+            renewable: true,
+            groups,
+        })
+    }
+}
+
+fn invalid_credentials() -> VaultError {
+    VaultError::Auth("invalid username or password".to_string())
+}
+
+fn normalize_group(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Bind the user and return the (normalized) names of their groups
+async fn authenticate(
+    session: &mut dyn LdapSession,
+    config: &LdapConfig,
+    username: &str,
+    password: &str,
+) -> VaultResult<Vec<String>> {
+    let flow = config.flow()?;
+    let user_dn = match &flow {
+        BindFlow::Direct { template } => template.replace("{{username}}", &ldap3::dn_escape(username)),
+        BindFlow::SearchBind { bind_dn, bind_password, base_dn } => {
+            if !session.bind(bind_dn, bind_password).await? {
+                return Err(VaultError::Config("ldap service account bind was rejected".to_string()));
+            }
+            let filter = format!("({}={})", config.userattr, ldap3::ldap_escape(username));
+            let mut entries = session.search(base_dn, &filter, &["dn"]).await?;
+            // Unknown and ambiguous names both fail without saying which
+            if entries.len() != 1 {
+                return Err(invalid_credentials());
+            }
+            entries.remove(0).dn
+        }
+    };
+
+    if !session.bind(&user_dn, password).await? {
+        return Err(invalid_credentials());
+    }
+
+    let Some(group_dn) = &config.groupdn else {
+        return Ok(Vec::new());
+    };
+    // Search groups with the service account where there is one
+    if let BindFlow::SearchBind { bind_dn, bind_password, .. } = &flow {
+        if !session.bind(bind_dn, bind_password).await? {
+            return Err(VaultError::Config("ldap service account bind was rejected".to_string()));
+        }
+    }
+    let filter = config
+        .groupfilter
+        .replace("{{user_dn}}", &ldap3::ldap_escape(&user_dn))
+        .replace("{{username}}", &ldap3::ldap_escape(username));
+    let entries = session.search(group_dn, &filter, &[config.groupattr.as_str()]).await?;
+
+    let mut groups = BTreeSet::new();
+    for entry in entries {
+        if config.groupattr.eq_ignore_ascii_case("dn") {
+            groups.insert(normalize_group(&entry.dn));
+            continue;
+        }
+        let values = entry
+            .attrs
+            .iter()
+            .find(|(attr, _)| attr.eq_ignore_ascii_case(&config.groupattr))
+            .map(|(_, values)| values.as_slice())
+            .unwrap_or_default();
+        groups.extend(values.iter().map(|v| normalize_group(v)));
+    }
+    Ok(groups.into_iter().collect())
+}
+
+fn policies_from(data: &Map<String, Value>) -> Vec<String> {
+    match data.get("policies") {
+        Some(Value::Array(arr)) => arr.iter().filter_map(|v| v.as_str().map(String::from)).collect(),
+        Some(Value::String(s)) => s.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[async_trait]
+impl Backend for LdapBackend {
+    async fn handle_request(&self, req: &mut Request) -> VaultResult<Option<Response>> {
+        let path = req.path.trim_start_matches(&self.mount_path).trim_start_matches('/').to_string();
+        let parts: Vec<&str> = path.split('/').collect();
+
+        match (req.operation, parts.as_slice()) {
+            // Read config: GET /auth/ldap/config
+            (Operation::Read, ["config"]) => {
+                Ok(self.read_config().await?.map(|config| Response::new().data(config.to_response_data())))
+            }
+
+            // Write config: POST /auth/ldap/config
+            (Operation::Write, ["config"]) => {
+                let data = req.data.take().unwrap_or_default();
+                let config = self.write_config(&data).await?;
+                Ok(Some(Response::new().data(config.to_response_data())))
+            }
+
+            // List group mappings: GET /auth/ldap/groups
+            (Operation::List, ["groups"]) | (Operation::List, ["groups", ""]) => {
+                let mut data = Map::new();
+                data.insert("keys".to_string(), serde_json::json!(self.list_groups().await?));
+                Ok(Some(Response::new().data(data)))
+            }
+
+            // Read group mapping: GET /auth/ldap/groups/:name
+            (Operation::Read, ["groups", name]) => Ok(self.get_group(name).await?.map(|mapping| {
+                let mut data = Map::new();
+                data.insert("policies".to_string(), serde_json::json!(mapping.policies));
+                Response::new().data(data)
+            })),
+
+            // Write group mapping: POST /auth/ldap/groups/:name
+            (Operation::Write, ["groups", name]) => {
+                let data = req.data.take().unwrap_or_default();
+                self.write_group(name, policies_from(&data)).await?;
+                Ok(Some(Response::default()))
+            }
+
+            // Delete group mapping: DELETE /auth/ldap/groups/:name
+            (Operation::Delete, ["groups", name]) => {
+                self.delete_group(name).await?;
+                Ok(Some(Response::default()))
+            }
+
+            // Login: POST /auth/ldap/login/:username
+            (Operation::Write, ["login", username]) => {
+                let password = req
+                    .data
+                    .as_ref()
+                    .and_then(|body| body.get("password"))
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| VaultError::Validation("password is required".to_string()))?;
+                let response = self.login(username, password).await?;
+
+                let mut data = Map::new();
+                data.insert("groups".to_string(), serde_json::json!(response.groups));
+                Ok(Some(Response::new().data(data).auth(ResponseAuth {
+                    client_token: response.client_token,
+                    accessor: response.accessor,
+                    policies: response.policies,
+                    token_ttl: response.token_ttl,
+                    renewable: response.renewable,
+                    ..Default::default()
+                })))
+            }
+
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use serde_json::json;
+
+    /// Directory with one user `alice` (password `secret`) in `admins` and `ops`
+    struct FakeDirectory {
+        binds: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct FakeSession {
+        binds: Arc<Mutex<Vec<String>>>,
+    }
+
+    const ALICE_DN: &str = "uid=alice,ou=people,dc=example,dc=com";
+    const SERVICE_DN: &str = "cn=vault,dc=example,dc=com";
+
+    #[async_trait]
+    impl LdapConnector for FakeDirectory {
+        async fn connect(&self, _config: &LdapConfig) -> VaultResult<Box<dyn LdapSession>> {
+            Ok(Box::new(FakeSession { binds: self.binds.clone() }))
+        }
+    }
+
+    #[async_trait]
+    impl LdapSession for FakeSession {
+        async fn bind(&mut self, dn: &str, password: &str) -> VaultResult<bool> {
+            self.binds.lock().unwrap().push(dn.to_string());
+            Ok(matches!((dn, password), (ALICE_DN, "secret") | (SERVICE_DN, "service")))
+        }
+
+        async fn search(&mut self, base: &str, filter: &str, _attrs: &[&str]) -> VaultResult<Vec<LdapEntry>> {
+            let entry = |dn: &str, cn: &str| LdapEntry {
+                dn: dn.to_string(),
+                attrs: HashMap::from([("cn".to_string(), vec![cn.to_string()])]),
+            };
+            Ok(match (base, filter) {
+                ("ou=people,dc=example,dc=com", "(uid=alice)") => vec![entry(ALICE_DN, "alice")],
+                ("ou=groups,dc=example,dc=com", f) if f.contains(ALICE_DN) => vec![
+                    entry("cn=Admins,ou=groups,dc=example,dc=com", "Admins"),
+                    entry("cn=ops,ou=groups,dc=example,dc=com", "ops"),
+                ],
+                _ => vec![],
+            })
+        }
+
+        async fn unbind(&mut self) {}
+    }
+
+    fn direct_config() -> Map<String, Value> {
+        json!({
+            "url": "ldaps://ldap.example.com",
+            "userdn_template": "uid={{username}},ou=people,dc=example,dc=com",
+            "groupdn": "ou=groups,dc=example,dc=com",
+        })
+        .as_object()
+        .cloned()
+        .unwrap()
+    }
+
+    fn search_config() -> Map<String, Value> {
+        json!({
+            "url": "ldap://ldap.example.com",
+            "starttls": true,
+            "binddn": SERVICE_DN,
+            "bindpass": "service",
+            "userdn": "ou=people,dc=example,dc=com",
+            "groupdn": "ou=groups,dc=example,dc=com",
+        })
+        .as_object()
+        .cloned()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_config_requires_tls() {
+        let mut data = direct_config();
+        data.insert("url".to_string(), json!("ldap://ldap.example.com"));
+        assert!(LdapConfig::from_request(&data, None).is_err());
+
+        data.insert("starttls".to_string(), json!(true));
+        assert!(LdapConfig::from_request(&data, None).is_ok());
+
+        data.insert("starttls".to_string(), json!(false));
+        data.insert("insecure_plaintext".to_string(), json!(true));
+        assert!(LdapConfig::from_request(&data, None).is_ok());
+
+        data.insert("url".to_string(), json!("https://ldap.example.com"));
+        assert!(LdapConfig::from_request(&data, None).is_err());
+    }
+
+    #[test]
+    fn test_config_requires_one_bind_flow() {
+        let config = LdapConfig::from_request(&direct_config(), None).unwrap();
+        assert!(matches!(config.flow().unwrap(), BindFlow::Direct { .. }));
+        let config = LdapConfig::from_request(&search_config(), None).unwrap();
+        assert!(matches!(config.flow().unwrap(), BindFlow::SearchBind { .. }));
+
+        let mut both = search_config();
+        both.insert("userdn_template".to_string(), json!("{{username}}@example.com"));
+        assert!(LdapConfig::from_request(&both, None).is_err());
+
+        let mut no_password = search_config();
+        no_password.remove("bindpass");
+        assert!(LdapConfig::from_request(&no_password, None).is_err());
+    }
+
+    #[test]
+    fn test_bindpass_is_kept_and_never_returned() {
+        let existing = LdapConfig::from_request(&search_config(), None).unwrap();
+        let data = existing.to_response_data();
+        assert!(!data.contains_key("bindpass"));
+
+        let rewritten = LdapConfig::from_request(&data, Some(&existing)).unwrap();
+        assert_eq!(rewritten.bindpass.as_deref(), Some("service"));
+    }
+
+    async fn groups_for(config: Map<String, Value>, username: &str, password: &str) -> (VaultResult<Vec<String>>, Vec<String>) {
+        let config = LdapConfig::from_request(&config, None).unwrap();
+        let binds = Arc::new(Mutex::new(Vec::new()));
+        let mut session = FakeSession { binds: binds.clone() };
+        let result = authenticate(&mut session, &config, username, password).await;
+        let binds = binds.lock().unwrap().clone();
+        (result, binds)
+    }
+
+    #[tokio::test]
+    async fn test_direct_bind() {
+        let (groups, binds) = groups_for(direct_config(), "alice", "secret").await;
+        assert_eq!(groups.unwrap(), vec!["admins", "ops"]);
+        assert_eq!(binds, vec![ALICE_DN]);
+
+        let (result, _) = groups_for(direct_config(), "alice", "wrong").await;
+        assert!(matches!(result, Err(VaultError::Auth(_))));
+    }
+
+    #[tokio::test]
+    async fn test_search_bind() {
+        let (groups, binds) = groups_for(search_config(), "alice", "secret").await;
+        assert_eq!(groups.unwrap(), vec!["admins", "ops"]);
+        assert_eq!(binds, vec![SERVICE_DN, ALICE_DN, SERVICE_DN]);
+
+        let (result, _) = groups_for(search_config(), "mallory", "secret").await;
+        assert!(matches!(result, Err(VaultError::Auth(_))));
+    }
+
+    #[tokio::test]
+    async fn test_usernames_are_escaped() {
+        let (result, binds) = groups_for(direct_config(), "alice,ou=admins", "secret").await;
+        assert!(result.is_err());
+        assert_eq!(binds.len(), 1);
+        assert!(binds[0].starts_with("uid=alice\\"), "{}", binds[0]);
+
+        let (result, _) = groups_for(search_config(), "*", "secret").await;
+        assert!(matches!(result, Err(VaultError::Auth(_))));
+    }
+
+    #[tokio::test]
+    async fn test_group_mappings_are_case_insensitive() {
+        let storage = Arc::new(crate::storage::physical_inmem::InMemoryBackend::new());
+        let connector = Arc::new(FakeDirectory { binds: Arc::new(Mutex::new(Vec::new())) });
+        let pool = sqlx::PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let backend = LdapBackend::new(storage, Arc::new(TokenStore::new(pool)), connector, "auth/ldap");
+
+        backend.write_group("ADMINS", vec!["admin".to_string()]).await.unwrap();
+        assert_eq!(backend.list_groups().await.unwrap(), vec!["admins"]);
+        assert_eq!(backend.get_group("Admins").await.unwrap().unwrap().policies, vec!["admin"]);
+        assert!(backend.write_group("a/b", vec![]).await.is_err());
+
+        // Empty passwords never reach the directory
+        assert!(matches!(backend.login("alice", "").await, Err(VaultError::Auth(_))));
+    }
+}
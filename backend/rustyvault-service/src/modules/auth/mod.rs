@@ -3,15 +3,20 @@
 //! This module provides various authentication methods:
 //! - Token: Token-based authentication (core)
 //! - UserPass: Username/password authentication
+//! - LDAP: LDAP/Active Directory bind with group-to-policy mapping
 //! - AppRole: Application role-based authentication (planned)
 //! - Cert: X.509 certificate authentication (planned)
 
+pub mod ldap;
+pub mod signed_token;
 pub mod token;
 pub mod userpass;
 
 // Re-export commonly used types
+pub use ldap::{Ldap3Connector, LdapBackend};
+pub use signed_token::TokenFormat;
 pub use token::{
-    CreateTokenRequest, TokenEntry, TokenStore,
+    CreateTokenRequest, RevokeMode, TokenEntry, TokenListFilter, TokenStore, RAW_TOKEN_LEN,
 };
 pub use userpass::{
     CreateUserRequest, UserPassBackend,
@@ -0,0 +1,262 @@
+//! Self-describing signed tokens
+//!
+//! With the `signed` token format a token carries its own entry: the claims
+//! are serialized into the token and HMAC'd with the store's signing key, so
+//! validating it needs no storage round-trip. Signed tokens are still stored
+//! like opaque ones, which keeps accessors, renewal and revocation working,
+//! and any token the fast path cannot vouch for (bad signature, another
+//! key, expired claims, limited uses) is simply looked up in storage.
+//!
+//! Revocation reaches the fast path through a denylist of revoked,
+//! unexpired signed tokens. It is shared through the database and reloaded
+//! at most every refresh interval, which bounds how long another replica may
+//! keep accepting a token revoked elsewhere.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, TimeZone, Utc};
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::token::TokenEntry;
+use crate::errors::VaultError;
+
+/// Prefix of signed tokens; opaque ones start with `hvs.`
+pub const SIGNED_TOKEN_PREFIX: &str = "hvb.";
+
+/// How newly created tokens are shaped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenFormat {
+    /// Random strings resolved through storage on every use
+    #[default]
+    Opaque,
+    /// HMAC-signed claims validated in memory
+    Signed,
+}
+
+impl fmt::Display for TokenFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TokenFormat::Opaque => "opaque",
+            TokenFormat::Signed => "signed",
+        })
+    }
+}
+
+impl FromStr for TokenFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "opaque" => Ok(TokenFormat::Opaque),
+            "signed" => Ok(TokenFormat::Signed),
+            other => Err(format!("unknown token format '{}' (expected opaque or signed)", other)),
+        }
+    }
+}
+
+/// What a signed token says about itself
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct TokenClaims {
+    pub id: Uuid,
+    pub display_name: String,
+    pub policies: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Uuid>,
+    pub ttl: i64,
+    /// Expiry as a Unix timestamp; `None` never expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    /// Creation as a Unix timestamp
+    pub iat: i64,
+    pub num_uses: i32,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<serde_json::Value>,
+    pub renewable: bool,
+}
+
+impl TokenClaims {
+    pub fn of(entry: &TokenEntry) -> Self {
+        TokenClaims {
+            id: entry.id,
+            display_name: entry.display_name.clone(),
+            policies: entry.policies.clone(),
+            parent: entry.parent,
+            ttl: entry.ttl,
+            exp: entry.expires_at.map(|t| t.timestamp()),
+            iat: entry.created_at.timestamp(),
+            num_uses: entry.num_uses,
+            path: entry.path.clone(),
+            meta: entry.meta.clone(),
+            renewable: entry.renewable,
+        }
+    }
+
+    /// Whether the claims alone are enough to accept the token now
+    ///
+    /// Tokens without expiry would need a denylist entry forever, and use
+    /// counts live in storage, so both always go through a lookup. So do
+    /// expired claims: the stored token may have been renewed since.
+    pub fn self_sufficient(&self, now: DateTime<Utc>) -> bool {
+        self.num_uses == 0 && self.exp.is_some_and(|exp| exp > now.timestamp())
+    }
+
+    pub fn into_entry(self, token_hash: String) -> TokenEntry {
+        TokenEntry {
+            id: self.id,
+            token_hash,
+            display_name: self.display_name,
+            policies: self.policies,
+            parent: self.parent,
+            ttl: self.ttl,
+            expires_at: self.exp.and_then(|exp| Utc.timestamp_opt(exp, 0).single()),
+            created_at: Utc.timestamp_opt(self.iat, 0).single().unwrap_or_else(Utc::now),
+            last_used_at: None,
+            num_uses: self.num_uses,
+            path: self.path,
+            meta: self.meta,
+            renewable: self.renewable,
+            entity_id: None,
+        }
+    }
+}
+
+/// Signs and verifies token claims with one HMAC-SHA256 key
+pub(crate) struct TokenSigner {
+    key: hmac::Key,
+}
+
+impl TokenSigner {
+    pub fn new(key: &[u8]) -> Self {
+        TokenSigner {
+            key: hmac::Key::new(hmac::HMAC_SHA256, key),
+        }
+    }
+
+    pub fn sign(&self, claims: &TokenClaims) -> Result<String, VaultError> {
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims)?);
+        let tag = hmac::sign(&self.key, payload.as_bytes());
+        Ok(format!("{}{}.{}", SIGNED_TOKEN_PREFIX, payload, URL_SAFE_NO_PAD.encode(tag.as_ref())))
+    }
+
+    /// Claims of a token signed with this key; `None` for anything else
+    pub fn verify(&self, raw_token: &str) -> Option<TokenClaims> {
+        let (payload, tag) = raw_token.strip_prefix(SIGNED_TOKEN_PREFIX)?.split_once('.')?;
+        let tag = URL_SAFE_NO_PAD.decode(tag).ok()?;
+        hmac::verify(&self.key, payload.as_bytes(), &tag).ok()?;
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()
+    }
+}
+
+/// Revoked signed tokens that have not expired yet
+pub(crate) struct TokenDenylist {
+    refresh: Duration,
+    /// Token ID to the expiry after which the entry is pointless
+    entries: Mutex<HashMap<Uuid, DateTime<Utc>>>,
+    loaded_at: Mutex<Option<Instant>>,
+}
+
+impl TokenDenylist {
+    pub fn new(refresh: Duration) -> Self {
+        TokenDenylist {
+            refresh,
+            entries: Mutex::new(HashMap::new()),
+            loaded_at: Mutex::new(None),
+        }
+    }
+
+    /// Whether the shared list is due to be reloaded
+    pub fn stale(&self) -> bool {
+        self.loaded_at.lock().unwrap().is_none_or(|at| at.elapsed() >= self.refresh)
+    }
+
+    /// Replace the list with a fresh copy of the shared one
+    pub fn replace(&self, entries: impl IntoIterator<Item = (Uuid, DateTime<Utc>)>) {
+        let now = Utc::now();
+        *self.entries.lock().unwrap() = entries.into_iter().filter(|(_, exp)| *exp > now).collect();
+        *self.loaded_at.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// Deny a token revoked on this node straight away
+    pub fn insert(&self, id: Uuid, expires_at: DateTime<Utc>) {
+        self.entries.lock().unwrap().insert(id, expires_at);
+    }
+
+    pub fn contains(&self, id: &Uuid) -> bool {
+        self.entries.lock().unwrap().contains_key(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(exp: Option<i64>, num_uses: i32) -> TokenClaims {
+        TokenClaims {
+            id: Uuid::new_v4(),
+            display_name: "svc".to_string(),
+            policies: vec!["default".to_string()],
+            parent: None,
+            ttl: 3600,
+            exp,
+            iat: Utc::now().timestamp(),
+            num_uses,
+            path: "auth/token/create".to_string(),
+            meta: Some(serde_json::json!({"org_id": "acme"})),
+            renewable: true,
+        }
+    }
+
+    #[test]
+    fn test_signed_tokens_round_trip_and_reject_tampering() {
+        let signer = TokenSigner::new(b"signing-key");
+        let original = claims(Some(Utc::now().timestamp() + 60), 0);
+        let token = signer.sign(&original).unwrap();
+        assert!(token.starts_with(SIGNED_TOKEN_PREFIX));
+        assert_eq!(signer.verify(&token), Some(original.clone()));
+
+        // Another key, an opaque token, and edited claims are all refused
+        assert_eq!(TokenSigner::new(b"other-key").verify(&token), None);
+        assert_eq!(signer.verify("hvs.abcdefghijklmnopqrstuvwxyz"), None);
+        let mut escalated = original;
+        escalated.policies = vec!["root".to_string()];
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&escalated).unwrap());
+        let (_, tag) = token.rsplit_once('.').unwrap();
+        assert_eq!(signer.verify(&format!("{}{}.{}", SIGNED_TOKEN_PREFIX, payload, tag)), None);
+    }
+
+    #[test]
+    fn test_only_expiring_unlimited_claims_skip_storage() {
+        let now = Utc::now();
+        assert!(claims(Some(now.timestamp() + 60), 0).self_sufficient(now));
+        assert!(!claims(Some(now.timestamp() - 1), 0).self_sufficient(now));
+        assert!(!claims(None, 0).self_sufficient(now));
+        assert!(!claims(Some(now.timestamp() + 60), 3).self_sufficient(now));
+    }
+
+    #[test]
+    fn test_denylist_drops_expired_entries_on_reload() {
+        let denylist = TokenDenylist::new(Duration::from_secs(60));
+        assert!(denylist.stale());
+
+        let (live, expired, local) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        denylist.replace([
+            (live, Utc::now() + chrono::Duration::minutes(5)),
+            (expired, Utc::now() - chrono::Duration::minutes(5)),
+        ]);
+        assert!(!denylist.stale());
+        assert!(denylist.contains(&live));
+        assert!(!denylist.contains(&expired));
+
+        denylist.insert(local, Utc::now() + chrono::Duration::minutes(5));
+        assert!(denylist.contains(&local));
+    }
+}
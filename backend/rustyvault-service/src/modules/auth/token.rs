@@ -3,12 +3,19 @@
 //! Handles token creation, validation, renewal, and revocation.
 
 
+use std::collections::HashSet;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use shared::domain::events::{DomainEvent, EventEnvelope};
+use shared::infrastructure::events::OutboxStore;
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::core::namespace::NAMESPACE_META_KEY;
+use crate::core::{Namespace, TtlJitter};
 use crate::errors::{VaultError, VaultResult};
+use super::signed_token::{TokenClaims, TokenDenylist, TokenFormat, TokenSigner, SIGNED_TOKEN_PREFIX};
 
 /// Token entry stored in the database
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,7 +30,8 @@ pub struct TokenEntry {
     pub policies: Vec<String>,
     /// Parent token ID (if this token was created by another token)
     pub parent: Option<Uuid>,
-    /// Time-to-live in seconds
+    /// Requested time-to-live in seconds; `expires_at` may differ from
+    /// creation plus this by the configured TTL jitter
     pub ttl: i64,
     /// When the token expires
     pub expires_at: Option<DateTime<Utc>>,
@@ -57,6 +65,194 @@ impl TokenEntry {
     pub fn has_uses_remaining(&self) -> bool {
         self.num_uses == 0 || self.num_uses > 0
     }
+
+    /// Accessor naming this token without granting its use
+    pub fn accessor(&self) -> String {
+        format!("{}{}", ACCESSOR_PREFIX, self.id)
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.policies.iter().any(|p| p == "root")
+    }
+}
+
+/// Refuse children of a use-limited parent
+///
+/// Creating the child would spend one of the parent's uses without the
+/// child being bound by the rest, so such parents cannot have children.
+pub fn ensure_can_parent(parent: &TokenEntry) -> VaultResult<()> {
+    if parent.num_uses > 0 {
+        return Err(VaultError::Authorization(
+            "use-limited tokens cannot create child tokens".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Expiry of a child of `parent` that would otherwise expire at `expiry`
+///
+/// A child never outlives its parent, jitter included.
+pub fn child_expiry(parent: &TokenEntry, expiry: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+    match (parent.expires_at, expiry) {
+        (Some(parent_expiry), Some(expiry)) => Some(expiry.min(parent_expiry)),
+        (Some(parent_expiry), None) => Some(parent_expiry),
+        (None, expiry) => expiry,
+    }
+}
+
+/// Policies a child of `parent` may carry when `requested`
+///
+/// A child never gets more than its parent: every requested policy must be
+/// one the parent holds, unless the parent is root. Asking for none
+/// inherits the parent's policies.
+pub fn child_policies(parent: &TokenEntry, requested: &[String]) -> VaultResult<Vec<String>> {
+    if requested.is_empty() {
+        return Ok(parent.policies.clone());
+    }
+
+    let mut policies: Vec<String> = Vec::with_capacity(requested.len());
+    for policy in requested {
+        if !policies.contains(policy) {
+            policies.push(policy.clone());
+        }
+    }
+    if parent.is_root() {
+        return Ok(policies);
+    }
+
+    let exceeding: Vec<&str> = policies
+        .iter()
+        .filter(|p| !parent.policies.contains(p))
+        .map(String::as_str)
+        .collect();
+    if !exceeding.is_empty() {
+        return Err(VaultError::Authorization(format!(
+            "child token cannot have policies its parent lacks: {}",
+            exceeding.join(", ")
+        )));
+    }
+    Ok(policies)
+}
+
+const ACCESSOR_PREFIX: &str = "accessor.";
+
+const TOKEN_PREFIX: &str = "hvs.";
+const TOKEN_RANDOM_LEN: usize = 26;
+
+/// Length of a raw token, which a generate-root one-time pad must match
+pub const RAW_TOKEN_LEN: usize = TOKEN_PREFIX.len() + TOKEN_RANDOM_LEN;
+
+/// Token meta flag set on root tokens minted by `sys/generate-root`
+pub const GENERATED_ROOT_META_KEY: &str = "generated_root";
+
+/// Token ID behind an accessor; `None` for malformed accessors
+fn parse_accessor(accessor: &str) -> Option<Uuid> {
+    accessor.strip_prefix(ACCESSOR_PREFIX)?.parse().ok()
+}
+
+/// What may be shown about a token to someone other than its holder: no
+/// raw token and no hash of it
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenView {
+    pub accessor: String,
+    pub display_name: String,
+    pub policies: Vec<String>,
+    pub ttl: i64,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub num_uses: i32,
+    pub renewable: bool,
+    pub path: String,
+    pub meta: Option<serde_json::Value>,
+}
+
+impl From<&TokenEntry> for TokenView {
+    fn from(entry: &TokenEntry) -> Self {
+        TokenView {
+            accessor: entry.accessor(),
+            display_name: entry.display_name.clone(),
+            policies: entry.policies.clone(),
+            ttl: entry.ttl,
+            expires_at: entry.expires_at,
+            created_at: entry.created_at,
+            last_used_at: entry.last_used_at,
+            num_uses: entry.num_uses,
+            renewable: entry.renewable,
+            path: entry.path.clone(),
+            meta: entry.meta.clone(),
+        }
+    }
+}
+
+/// What happens to a revoked token's descendants
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RevokeMode {
+    /// Revoke the whole subtree, so nothing a compromised token spawned
+    /// survives it
+    #[default]
+    Cascade,
+    /// Revoke only the token; its children become top-level tokens
+    Orphan,
+}
+
+/// Breadth-first walk over a token subtree that visits each ID once
+struct TreeWalk {
+    seen: HashSet<Uuid>,
+    visited: Vec<Uuid>,
+    frontier: Vec<Uuid>,
+}
+
+impl TreeWalk {
+    fn new(root: Uuid) -> Self {
+        TreeWalk {
+            seen: HashSet::from([root]),
+            visited: vec![root],
+            frontier: vec![root],
+        }
+    }
+
+    /// The level whose children are to be looked up next; `None` when done
+    fn frontier(&mut self) -> Option<Vec<Uuid>> {
+        (!self.frontier.is_empty()).then(|| std::mem::take(&mut self.frontier))
+    }
+
+    /// Record the children found for the last frontier
+    fn visit(&mut self, children: impl IntoIterator<Item = Uuid>) {
+        for child in children {
+            if self.seen.insert(child) {
+                self.visited.push(child);
+                self.frontier.push(child);
+            }
+        }
+    }
+
+    fn into_visited(self) -> Vec<Uuid> {
+        self.visited
+    }
+}
+
+/// Upper bound on tokens returned by one page of [`TokenStore::list_tokens`]
+pub const MAX_TOKEN_PAGE_SIZE: usize = 500;
+
+/// Which live tokens [`TokenStore::list_tokens`] returns
+#[derive(Debug, Clone, Default)]
+pub struct TokenListFilter {
+    /// Only tokens carrying this policy
+    pub policy: Option<String>,
+    /// Only tokens whose display name starts with this
+    pub display_name_prefix: Option<String>,
+    /// Only tokens expiring within this many seconds
+    pub expiring_within: Option<i64>,
+}
+
+/// One page of a token listing
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenPage {
+    pub tokens: Vec<TokenView>,
+    /// Cursor of the next page; absent on the last one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 /// Request to create a new token
@@ -120,15 +316,82 @@ pub struct CreateTokenResponse {
     pub renewable: bool,
 }
 
+/// Columns of a token row, in `SELECT_TOKEN` order
+type TokenRow = (
+    Uuid,
+    String,
+    String,
+    Vec<String>,
+    Option<Uuid>,
+    i64,
+    Option<DateTime<Utc>>,
+    DateTime<Utc>,
+    Option<DateTime<Utc>>,
+    i32,
+    String,
+    Option<serde_json::Value>,
+    bool,
+    Option<Uuid>,
+);
+
+const SELECT_TOKEN: &str = r#"
+    SELECT id, token_hash, display_name, policies, parent_id,
+           ttl, expires_at, created_at, last_used_at, num_uses,
+           path, meta, renewable, entity_id
+    FROM vault_tokens"#;
+
+/// How often the shared denylist of revoked signed tokens is reloaded by default
+const DEFAULT_DENYLIST_REFRESH: std::time::Duration = std::time::Duration::from_secs(5);
+
 /// Token store for managing tokens
 pub struct TokenStore {
     pool: PgPool,
+    ttl_jitter: TtlJitter,
+    /// Ceiling for jittered TTLs in seconds; 0 means uncapped
+    max_ttl: u64,
+    /// Shape of newly created tokens
+    format: TokenFormat,
+    /// Validates signed tokens without a lookup; set for the signed format
+    signer: Option<TokenSigner>,
+    denylist: TokenDenylist,
 }
 
 impl TokenStore {
     /// Create a new token store
     pub fn new(pool: PgPool) -> Self {
-        TokenStore { pool }
+        TokenStore {
+            pool,
+            ttl_jitter: TtlJitter::default(),
+            max_ttl: 0,
+            format: TokenFormat::default(),
+            signer: None,
+            denylist: TokenDenylist::new(DEFAULT_DENYLIST_REFRESH),
+        }
+    }
+
+    /// Issue tokens in `format`
+    ///
+    /// Signed tokens are HMAC'd with `signing_key`; every replica must share
+    /// it for their tokens to validate without a lookup anywhere. Revocations
+    /// made elsewhere are picked up within `denylist_refresh`.
+    pub fn with_format(mut self, format: TokenFormat, signing_key: &[u8], denylist_refresh: std::time::Duration) -> Self {
+        self.format = format;
+        self.signer = (format == TokenFormat::Signed).then(|| TokenSigner::new(signing_key));
+        self.denylist = TokenDenylist::new(denylist_refresh);
+        self
+    }
+
+    /// Spread token expiries by `jitter`, never past `max_ttl` seconds
+    pub fn with_ttl_jitter(mut self, jitter: TtlJitter, max_ttl: u64) -> Self {
+        self.ttl_jitter = jitter;
+        self.max_ttl = max_ttl;
+        self
+    }
+
+    /// Expiry of a token issued or renewed now for `ttl` seconds
+    fn jittered_expiry(&self, ttl: i64) -> DateTime<Utc> {
+        let ttl = self.ttl_jitter.apply(ttl.max(0) as u64, self.max_ttl);
+        Utc::now() + chrono::Duration::seconds(ttl as i64)
     }
 
     /// Create a new token
@@ -138,18 +401,18 @@ impl TokenStore {
         parent_token: Option<&TokenEntry>,
         path: &str,
     ) -> VaultResult<(TokenEntry, String)> {
-        // Generate token ID and raw token
         let id = Uuid::new_v4();
-        let raw_token = format!("hvs.{}", generate_random_string(26));
-        let token_hash = hash_token(&raw_token);
-        let _accessor = format!("accessor.{}", generate_random_string(20));
 
-        // Calculate expiration
+        // Calculate expiration; clamped before signing, as signed tokens carry it
         let expires_at = if request.ttl > 0 {
-            Some(Utc::now() + chrono::Duration::seconds(request.ttl))
+            Some(self.jittered_expiry(request.ttl))
         } else {
             None
         };
+        let expires_at = match parent_token {
+            Some(parent) => child_expiry(parent, expires_at),
+            None => expires_at,
+        };
 
         // Determine policies (child tokens can only have subset of parent policies)
         let policies = if let Some(parent) = parent_token {
@@ -168,9 +431,9 @@ impl TokenStore {
             request.policies.clone()
         };
 
-        let entry = TokenEntry {
+        let mut entry = TokenEntry {
             id,
-            token_hash: token_hash.clone(),
+            token_hash: String::new(),
             display_name: request.display_name.clone(),
             policies,
             parent: parent_token.map(|p| p.id),
@@ -185,6 +448,13 @@ impl TokenStore {
             entity_id: None,
         };
 
+        // Signed tokens embed the finished entry, so the raw token comes last
+        let raw_token = match (&self.signer, self.format) {
+            (Some(signer), TokenFormat::Signed) => signer.sign(&TokenClaims::of(&entry))?,
+            _ => format!("{}{}", TOKEN_PREFIX, generate_random_string(TOKEN_RANDOM_LEN)),
+        };
+        entry.token_hash = hash_token(&raw_token);
+
         // Store in database
         sqlx::query(
             r#"
@@ -214,72 +484,220 @@ impl TokenStore {
         Ok((entry, raw_token))
     }
 
+    /// Create a token scoped to a subset of `parent`'s policies
+    ///
+    /// Requests for any policy the parent lacks are rejected rather than
+    /// trimmed. The child is linked to the parent, so revoking the parent
+    /// revokes it, and it never outlives the parent. Use-limited parents
+    /// cannot have children.
+    pub async fn create_child_token(
+        &self,
+        parent: &TokenEntry,
+        request: &CreateTokenRequest,
+        path: &str,
+    ) -> VaultResult<(TokenEntry, String)> {
+        ensure_can_parent(parent)?;
+        let mut request = request.clone();
+        request.policies = child_policies(parent, &request.policies)?;
+
+        if let Some(parent_expiry) = parent.expires_at {
+            let remaining = (parent_expiry - Utc::now()).num_seconds();
+            if remaining <= 0 {
+                return Err(VaultError::Auth("parent token has expired".to_string()));
+            }
+            if request.ttl <= 0 || request.ttl > remaining {
+                request.ttl = remaining;
+            }
+        }
+
+        self.create_token(&request, Some(parent), path).await
+    }
+
     /// Look up a token by its raw value
+    ///
+    /// Either format is accepted. A signed token whose claims can be
+    /// trusted on their own is resolved without reading its row; anything
+    /// else is looked up in storage.
     pub async fn lookup_token(&self, raw_token: &str) -> VaultResult<Option<TokenEntry>> {
-        let token_hash = hash_token(raw_token);
-
-        let row: Option<(
-            Uuid,
-            String,
-            String,
-            Vec<String>,
-            Option<Uuid>,
-            i64,
-            Option<DateTime<Utc>>,
-            DateTime<Utc>,
-            Option<DateTime<Utc>>,
-            i32,
-            String,
-            Option<serde_json::Value>,
-            bool,
-            Option<Uuid>,
-        )> = sqlx::query_as(
+        if let Some(claims) = self.trusted_claims(raw_token).await? {
+            return Ok(Some(claims.into_entry(hash_token(raw_token))));
+        }
+
+        let row: Option<TokenRow> = sqlx::query_as(&format!("{} WHERE token_hash = $1", SELECT_TOKEN))
+            .bind(hash_token(raw_token))
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| VaultError::Vault(format!("failed to lookup token: {}", e)))?;
+        self.live_entry(row).await
+    }
+
+    /// Claims of a signed token that needs no lookup: signed with our key,
+    /// self-sufficient and not revoked
+    async fn trusted_claims(&self, raw_token: &str) -> VaultResult<Option<TokenClaims>> {
+        let Some(signer) = &self.signer else {
+            return Ok(None);
+        };
+        if !raw_token.starts_with(SIGNED_TOKEN_PREFIX) {
+            return Ok(None);
+        }
+        let Some(claims) = signer.verify(raw_token).filter(|c| c.self_sufficient(Utc::now())) else {
+            return Ok(None);
+        };
+
+        if self.denylist.stale() {
+            self.reload_denylist().await?;
+        }
+        if self.denylist.contains(&claims.id) {
+            return Ok(None);
+        }
+        Ok(Some(claims))
+    }
+
+    async fn reload_denylist(&self) -> VaultResult<()> {
+        let rows: Vec<(Uuid, DateTime<Utc>)> =
+            sqlx::query_as("SELECT token_id, expires_at FROM vault_token_denylist WHERE expires_at > NOW()")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| VaultError::Vault(format!("failed to load token denylist: {}", e)))?;
+        self.denylist.replace(rows);
+        Ok(())
+    }
+
+    /// Look up a token by its accessor
+    ///
+    /// With a namespace, tokens outside it are not found.
+    pub async fn lookup_by_accessor(
+        &self,
+        accessor: &str,
+        namespace: Option<&Namespace>,
+    ) -> VaultResult<Option<TokenView>> {
+        Ok(self.entry_by_accessor(accessor, namespace).await?.as_ref().map(TokenView::from))
+    }
+
+    /// Revoke a token, and its descendants, by its accessor
+    ///
+    /// Returns how many tokens were revoked, or `None` when no live token
+    /// has that accessor.
+    pub async fn revoke_by_accessor(&self, accessor: &str, namespace: Option<&Namespace>) -> VaultResult<Option<u64>> {
+        match self.entry_by_accessor(accessor, namespace).await? {
+            Some(entry) => Ok(Some(self.revoke_token_by_id(entry.id).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Accessors of every live token, optionally only those in `namespace`
+    pub async fn list_accessors(&self, namespace: Option<&Namespace>) -> VaultResult<Vec<String>> {
+        let ids: Vec<(Uuid,)> = sqlx::query_as(&format!(
             r#"
-            SELECT id, token_hash, display_name, policies, parent_id,
-                   ttl, expires_at, created_at, last_used_at, num_uses,
-                   path, meta, renewable, entity_id
-            FROM vault_tokens
-            WHERE token_hash = $1
+            SELECT id FROM vault_tokens
+            WHERE (expires_at IS NULL OR expires_at > NOW())
+              AND ($1::text IS NULL OR lower(meta->>'{}') = $1)
+            ORDER BY created_at
             "#,
-        )
-        .bind(&token_hash)
-        .fetch_optional(&self.pool)
+            NAMESPACE_META_KEY
+        ))
+        .bind(namespace.map(Namespace::name))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| VaultError::Vault(format!("failed to list token accessors: {}", e)))?;
+
+        Ok(ids.into_iter().map(|(id,)| format!("{}{}", ACCESSOR_PREFIX, id)).collect())
+    }
+
+    /// One page of live tokens matching `filter`, oldest first
+    ///
+    /// Entries are sanitized views named by accessor. Ties on creation time
+    /// are broken by token ID, so pages never overlap or skip. `after` is the
+    /// `next_cursor` of the previous page.
+    pub async fn list_tokens(
+        &self,
+        filter: &TokenListFilter,
+        namespace: Option<&Namespace>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> VaultResult<TokenPage> {
+        if limit == 0 || limit > MAX_TOKEN_PAGE_SIZE {
+            return Err(VaultError::Validation(format!(
+                "limit must be between 1 and {}", MAX_TOKEN_PAGE_SIZE
+            )));
+        }
+        let after = after.map(decode_list_cursor).transpose()?;
+        let expiring_before = match filter.expiring_within {
+            Some(secs) if secs < 0 => {
+                return Err(VaultError::Validation("expiring_within must not be negative".to_string()));
+            }
+            Some(secs) => Some(Utc::now() + chrono::Duration::seconds(secs)),
+            None => None,
+        };
+        let name_pattern = filter.display_name_prefix.as_deref().map(|prefix| {
+            format!("{}%", prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"))
+        });
+
+        // One extra row tells whether another page follows
+        let rows: Vec<TokenRow> = sqlx::query_as(&format!(
+            r#"{}
+            WHERE (expires_at IS NULL OR expires_at > NOW())
+              AND ($1::text IS NULL OR lower(meta->>'{}') = $1)
+              AND ($2::text IS NULL OR $2 = ANY(policies))
+              AND ($3::text IS NULL OR display_name LIKE $3 ESCAPE '\')
+              AND ($4::timestamptz IS NULL OR expires_at <= $4)
+              AND ($5::timestamptz IS NULL OR (created_at, id) > ($5, $6))
+            ORDER BY created_at, id
+            LIMIT $7
+            "#,
+            SELECT_TOKEN, NAMESPACE_META_KEY
+        ))
+        .bind(namespace.map(Namespace::name))
+        .bind(filter.policy.as_deref())
+        .bind(name_pattern)
+        .bind(expiring_before)
+        .bind(after.map(|(created_at, _)| created_at))
+        .bind(after.map(|(_, id)| id))
+        .bind((limit + 1) as i64)
+        .fetch_all(&self.pool)
         .await
-        .map_err(|e| VaultError::Vault(format!("failed to lookup token: {}", e)))?;
+        .map_err(|e| VaultError::Vault(format!("failed to list tokens: {}", e)))?;
+
+        let mut entries: Vec<TokenEntry> = rows.into_iter().map(entry_from_row).collect();
+        let next_cursor = if entries.len() > limit {
+            entries.truncate(limit);
+            entries.last().map(|last| encode_list_cursor(last.created_at, last.id))
+        } else {
+            None
+        };
+        Ok(TokenPage {
+            tokens: entries.iter().map(TokenView::from).collect(),
+            next_cursor,
+        })
+    }
+
+    async fn entry_by_accessor(
+        &self,
+        accessor: &str,
+        namespace: Option<&Namespace>,
+    ) -> VaultResult<Option<TokenEntry>> {
+        let Some(id) = parse_accessor(accessor) else {
+            return Ok(None);
+        };
+        let row: Option<TokenRow> = sqlx::query_as(&format!("{} WHERE id = $1", SELECT_TOKEN))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| VaultError::Vault(format!("failed to lookup token: {}", e)))?;
+        let entry = self.live_entry(row).await?;
 
+        // A malformed org_id can belong to no namespace
+        Ok(match namespace {
+            Some(namespace) => entry.filter(|e| Namespace::of_token(e).ok().flatten().as_ref() == Some(namespace)),
+            None => entry,
+        })
+    }
+
+    /// Decode a token row, revoking it instead if it has expired
+    async fn live_entry(&self, row: Option<TokenRow>) -> VaultResult<Option<TokenEntry>> {
         match row {
-            Some((
-                id,
-                hash,
-                display_name,
-                policies,
-                parent,
-                ttl,
-                expires_at,
-                created_at,
-                last_used_at,
-                num_uses,
-                path,
-                meta,
-                renewable,
-                entity_id,
-            )) => {
-                let entry = TokenEntry {
-                    id,
-                    token_hash: hash,
-                    display_name,
-                    policies,
-                    parent,
-                    ttl,
-                    expires_at,
-                    created_at,
-                    last_used_at,
-                    num_uses,
-                    path,
-                    meta,
-                    renewable,
-                    entity_id,
-                };
+            Some(row) => {
+                let entry = entry_from_row(row);
 
                 // Check if token is expired
                 if entry.is_expired() {
@@ -312,24 +730,101 @@ impl TokenStore {
         Ok(())
     }
 
-    /// Revoke a token by its ID
-    pub async fn revoke_token_by_id(&self, token_id: Uuid) -> VaultResult<()> {
-        // Also revoke all child tokens
-        sqlx::query("DELETE FROM vault_tokens WHERE id = $1 OR parent_id = $1")
-            .bind(token_id)
-            .execute(&self.pool)
+    /// Revoke a token and everything it spawned, returning how many
+    /// tokens were revoked
+    pub async fn revoke_token_by_id(&self, token_id: Uuid) -> VaultResult<u64> {
+        self.revoke_tree(token_id, RevokeMode::Cascade).await
+    }
+
+    /// Revoke a token, handling its descendants as `mode` says
+    ///
+    /// Returns how many tokens were revoked; 0 when the token does not
+    /// exist. The subtree is walked level by level and each token is visited
+    /// once, so a corrupt parent link that forms a cycle cannot loop.
+    pub async fn revoke_tree(&self, token_id: Uuid, mode: RevokeMode) -> VaultResult<u64> {
+        let mut tx = self.pool.begin()
             .await
             .map_err(|e| VaultError::Vault(format!("failed to revoke token: {}", e)))?;
 
-        Ok(())
+        let targets = match mode {
+            RevokeMode::Cascade => {
+                let mut walk = TreeWalk::new(token_id);
+                while let Some(level) = walk.frontier() {
+                    let children: Vec<(Uuid,)> =
+                        sqlx::query_as("SELECT id FROM vault_tokens WHERE parent_id = ANY($1) FOR UPDATE")
+                            .bind(level)
+                            .fetch_all(&mut *tx)
+                            .await
+                            .map_err(|e| VaultError::Vault(format!("failed to revoke token: {}", e)))?;
+                    walk.visit(children.into_iter().map(|(id,)| id));
+                }
+                walk.into_visited()
+            }
+            RevokeMode::Orphan => {
+                sqlx::query("UPDATE vault_tokens SET parent_id = NULL WHERE parent_id = $1 AND id <> $1")
+                    .bind(token_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| VaultError::Vault(format!("failed to orphan child tokens: {}", e)))?;
+                vec![token_id]
+            }
+        };
+
+        let revoked: Vec<(Uuid, Option<DateTime<Utc>>)> =
+            sqlx::query_as("DELETE FROM vault_tokens WHERE id = ANY($1) RETURNING id, expires_at")
+                .bind(&targets)
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(|e| VaultError::Vault(format!("failed to revoke token: {}", e)))?;
+
+        for (id, _) in &revoked {
+            let envelope = EventEnvelope::new(DomainEvent::TokenRevoked { token_id: *id });
+            OutboxStore::append(&mut *tx, &envelope)
+                .await
+                .map_err(|e| VaultError::Vault(format!("failed to record token revocation: {}", e)))?;
+        }
+
+        // Signed tokens stay valid on their face until they expire, so other
+        // replicas must learn of the revocation; tokens that never expire
+        // are always looked up and need no entry
+        let denied: Vec<(Uuid, DateTime<Utc>)> = match self.signer {
+            Some(_) => revoked.iter().filter_map(|(id, exp)| exp.map(|exp| (*id, exp))).collect(),
+            None => Vec::new(),
+        };
+        for (id, expires_at) in &denied {
+            sqlx::query(
+                r#"
+                INSERT INTO vault_token_denylist (token_id, expires_at)
+                VALUES ($1, $2)
+                ON CONFLICT (token_id) DO NOTHING
+                "#,
+            )
+            .bind(id)
+            .bind(expires_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| VaultError::Vault(format!("failed to deny revoked token: {}", e)))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| VaultError::Vault(format!("failed to revoke token: {}", e)))?;
+
+        for (id, expires_at) in denied {
+            self.denylist.insert(id, expires_at);
+        }
+        if revoked.len() > 1 {
+            tracing::info!("Revoked token {} and {} descendant tokens", token_id, revoked.len() - 1);
+        }
+        Ok(revoked.len() as u64)
     }
 
-    /// Revoke a token by its raw value
-    pub async fn revoke_token(&self, raw_token: &str) -> VaultResult<()> {
-        if let Some(entry) = self.lookup_token(raw_token).await? {
-            self.revoke_token_by_id(entry.id).await?;
+    /// Revoke a token by its raw value, returning how many tokens were revoked
+    pub async fn revoke_token(&self, raw_token: &str, mode: RevokeMode) -> VaultResult<u64> {
+        match self.lookup_token(raw_token).await? {
+            Some(entry) => self.revoke_tree(entry.id, mode).await,
+            None => Ok(0),
         }
-        Ok(())
     }
 
     /// Renew a token
@@ -343,8 +838,10 @@ impl TokenStore {
             return Err(VaultError::Vault("token is not renewable".to_string()));
         }
 
+        // Renewals are jittered afresh from the requested TTL, so repeated
+        // renewals do not drift
         let ttl = increment.unwrap_or(entry.ttl);
-        let new_expires_at = Utc::now() + chrono::Duration::seconds(ttl);
+        let new_expires_at = self.jittered_expiry(ttl);
 
         sqlx::query(
             r#"
@@ -382,17 +879,137 @@ impl TokenStore {
         Ok(raw_token)
     }
 
-    /// Clean up expired tokens
-    pub async fn cleanup_expired_tokens(&self) -> VaultResult<u64> {
-        let result = sqlx::query("DELETE FROM vault_tokens WHERE expires_at < NOW()")
-            .execute(&self.pool)
-            .await
-            .map_err(|e| VaultError::Vault(format!("failed to cleanup tokens: {}", e)))?;
+    /// Create an emergency root token for `sys/generate-root`
+    ///
+    /// Flagged with [`GENERATED_ROOT_META_KEY`] so it stands out in accessor
+    /// listings: it is meant to be revoked once the emergency is over. A
+    /// `ttl` of 0 never expires.
+    pub async fn create_generated_root_token(&self, ttl: i64) -> VaultResult<(TokenEntry, String)> {
+        let request = CreateTokenRequest {
+            display_name: "generated-root".to_string(),
+            policies: vec!["root".to_string()],
+            ttl,
+            renewable: false,
+            num_uses: 0,
+            meta: Some(serde_json::json!({
+                GENERATED_ROOT_META_KEY: true,
+                "generated_at": Utc::now().to_rfc3339(),
+            })),
+        };
+
+        self.create_token(&request, None, "sys/generate-root").await
+    }
+
+    /// Clean up to `batch_size` expired tokens
+    ///
+    /// Rows locked by a concurrent reaper are skipped, so overlapping runs
+    /// never block each other or double-count.
+    pub async fn cleanup_expired_tokens(&self, batch_size: usize) -> VaultResult<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM vault_tokens
+            WHERE id IN (
+                SELECT id FROM vault_tokens
+                WHERE expires_at < NOW()
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            "#,
+        )
+        .bind(batch_size as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VaultError::Vault(format!("failed to reap tokens: {}", e)))?;
+
+        // Denylist entries outlive their purpose once the token has expired
+        let denied = sqlx::query(
+            r#"
+            DELETE FROM vault_token_denylist
+            WHERE token_id IN (
+                SELECT token_id FROM vault_token_denylist
+                WHERE expires_at < NOW()
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            "#,
+        )
+        .bind(batch_size as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VaultError::Vault(format!("failed to reap token denylist: {}", e)))?;
+
+        Ok(result.rows_affected() + denied.rows_affected())
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::core::expiration::ExpiredEntrySource for TokenStore {
+    fn name(&self) -> &str {
+        "tokens"
+    }
+
+    async fn reap_expired(&self, batch_size: usize) -> VaultResult<u64> {
+        self.cleanup_expired_tokens(batch_size).await
+    }
+}
 
-        Ok(result.rows_affected())
+fn entry_from_row(row: TokenRow) -> TokenEntry {
+    let (
+        id,
+        token_hash,
+        display_name,
+        policies,
+        parent,
+        ttl,
+        expires_at,
+        created_at,
+        last_used_at,
+        num_uses,
+        path,
+        meta,
+        renewable,
+        entity_id,
+    ) = row;
+    TokenEntry {
+        id,
+        token_hash,
+        display_name,
+        policies,
+        parent,
+        ttl,
+        expires_at,
+        created_at,
+        last_used_at,
+        num_uses,
+        path,
+        meta,
+        renewable,
+        entity_id,
     }
 }
 
+/// Cursor naming the last token of a page by its sort key
+fn encode_list_cursor(created_at: DateTime<Utc>, id: Uuid) -> String {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    let key = format!("{}|{}", created_at.to_rfc3339_opts(chrono::SecondsFormat::Micros, true), id);
+    URL_SAFE_NO_PAD.encode(key.as_bytes())
+}
+
+fn decode_list_cursor(cursor: &str) -> VaultResult<(DateTime<Utc>, Uuid)> {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    URL_SAFE_NO_PAD.decode(cursor)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .and_then(|key| {
+            let (created_at, id) = key.split_once('|')?;
+            Some((
+                DateTime::parse_from_rfc3339(created_at).ok()?.with_timezone(&Utc),
+                id.parse().ok()?,
+            ))
+        })
+        .ok_or_else(|| VaultError::Validation("invalid list cursor".to_string()))
+}
+
 /// Hash a token for storage
 fn hash_token(token: &str) -> String {
     use sha2::{Digest, Sha256};
@@ -418,6 +1035,112 @@ fn generate_random_string(len: usize) -> String {
 mod tests {
     use super::*;
 
+    fn token_with(policies: &[&str]) -> TokenEntry {
+        TokenEntry {
+            id: Uuid::new_v4(),
+            token_hash: String::new(),
+            display_name: "parent".to_string(),
+            policies: policies.iter().map(|p| p.to_string()).collect(),
+            parent: None,
+            ttl: 3600,
+            expires_at: None,
+            created_at: Utc::now(),
+            last_used_at: None,
+            num_uses: 0,
+            path: "auth/token/create".to_string(),
+            meta: None,
+            renewable: true,
+            entity_id: None,
+        }
+    }
+
+    fn names(policies: &[&str]) -> Vec<String> {
+        policies.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_child_policies_never_exceed_the_parent() {
+        let parent = token_with(&["billing-read", "reports", "default"]);
+
+        // Overlapping sets are refused as a whole, naming what is missing
+        let err = child_policies(&parent, &names(&["reports", "billing-write"])).unwrap_err();
+        assert!(matches!(err, VaultError::Authorization(_)));
+        assert!(err.to_string().contains("billing-write"));
+        assert!(child_policies(&parent, &names(&["root"])).is_err());
+
+        assert_eq!(child_policies(&parent, &names(&["reports", "reports"])).unwrap(), names(&["reports"]));
+        assert_eq!(
+            child_policies(&parent, &names(&["default", "billing-read"])).unwrap(),
+            names(&["default", "billing-read"])
+        );
+        assert_eq!(child_policies(&parent, &[]).unwrap(), parent.policies);
+    }
+
+    #[test]
+    fn test_children_never_outlive_their_parent() {
+        let mut parent = token_with(&["reports"]);
+        let now = Utc::now();
+        assert_eq!(child_expiry(&parent, None), None);
+        assert_eq!(child_expiry(&parent, Some(now)), Some(now));
+
+        parent.expires_at = Some(now + chrono::Duration::seconds(60));
+        // Jitter past the parent is cut back; shorter expiries are kept
+        assert_eq!(child_expiry(&parent, Some(now + chrono::Duration::seconds(90))), parent.expires_at);
+        assert_eq!(child_expiry(&parent, Some(now)), Some(now));
+        assert_eq!(child_expiry(&parent, None), parent.expires_at);
+    }
+
+    #[test]
+    fn test_use_limited_tokens_cannot_have_children() {
+        let mut parent = token_with(&["reports"]);
+        assert!(ensure_can_parent(&parent).is_ok());
+        parent.num_uses = 3;
+        assert!(matches!(ensure_can_parent(&parent), Err(VaultError::Authorization(_))));
+    }
+
+    #[test]
+    fn test_root_parents_may_grant_any_policy() {
+        let root = token_with(&["root"]);
+        assert_eq!(child_policies(&root, &names(&["anything"])).unwrap(), names(&["anything"]));
+        assert_eq!(child_policies(&root, &names(&["root"])).unwrap(), names(&["root"]));
+    }
+
+    /// Walk `edges` (parent to children) from `root` like `revoke_tree` does
+    fn walk(root: Uuid, edges: &[(Uuid, Uuid)]) -> Vec<Uuid> {
+        let mut walk = TreeWalk::new(root);
+        let mut levels = 0;
+        while let Some(level) = walk.frontier() {
+            levels += 1;
+            assert!(levels <= edges.len() + 1, "walk did not terminate");
+            walk.visit(edges.iter().filter(|(parent, _)| level.contains(parent)).map(|(_, child)| *child));
+        }
+        walk.into_visited()
+    }
+
+    #[test]
+    fn test_tree_walk_covers_the_subtree_once() {
+        let [root, a, b, a1, other] = [(); 5].map(|_| Uuid::new_v4());
+        let edges = [(root, a), (root, b), (a, a1), (other, root)];
+        assert_eq!(walk(root, &edges), vec![root, a, b, a1]);
+        assert_eq!(walk(a1, &edges), vec![a1]);
+    }
+
+    #[test]
+    fn test_tree_walk_survives_cycles() {
+        let [root, a, b] = [(); 3].map(|_| Uuid::new_v4());
+        let edges = [(root, a), (a, b), (b, root), (b, b)];
+        assert_eq!(walk(root, &edges), vec![root, a, b]);
+    }
+
+    #[test]
+    fn test_list_cursor_round_trips_and_rejects_garbage() {
+        let created_at = DateTime::parse_from_rfc3339("2026-01-02T03:04:05.123456Z").unwrap().with_timezone(&Utc);
+        let id = Uuid::new_v4();
+        assert_eq!(decode_list_cursor(&encode_list_cursor(created_at, id)).unwrap(), (created_at, id));
+        assert!(decode_list_cursor("not a cursor").is_err());
+        assert!(decode_list_cursor("bm8tc2VwYXJhdG9y").is_err());
+    }
+
     #[test]
     fn test_hash_token() {
         let token = "hvs.test_token_123";
@@ -472,5 +1195,35 @@ mod tests {
         entry.expires_at = None;
         assert!(!entry.is_expired());
     }
+
+    #[test]
+    fn test_accessor_names_the_token_without_revealing_it() {
+        let id = Uuid::new_v4();
+        let accessor = format!("accessor.{}", id);
+        assert_eq!(parse_accessor(&accessor), Some(id));
+        assert_eq!(parse_accessor(&id.to_string()), None);
+        assert_eq!(parse_accessor("accessor.not-a-uuid"), None);
+
+        let entry = TokenEntry {
+            id,
+            token_hash: "hash".to_string(),
+            display_name: "ci".to_string(),
+            policies: vec!["default".to_string()],
+            parent: None,
+            ttl: 60,
+            expires_at: None,
+            created_at: Utc::now(),
+            last_used_at: None,
+            num_uses: 0,
+            path: "auth/token/create".to_string(),
+            meta: None,
+            renewable: true,
+            entity_id: None,
+        };
+        let view = serde_json::to_value(TokenView::from(&entry)).unwrap();
+        assert_eq!(view["accessor"], accessor);
+        assert!(view.get("token_hash").is_none());
+        assert!(view.get("id").is_none());
+    }
 }
 
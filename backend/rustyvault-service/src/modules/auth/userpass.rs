@@ -9,7 +9,12 @@ use async_trait::async_trait;
 use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use shared::domain::events::{DomainEvent, EventEnvelope};
+use shared::infrastructure::counters::CounterStore;
+use shared::infrastructure::events::OutboxStore;
 use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
 use super::token::{CreateTokenRequest, TokenStore};
@@ -76,27 +81,84 @@ pub struct LoginResponse {
     pub renewable: bool,
 }
 
+/// Failed logins per username within a window before a burst is reported
+const LOGIN_FAILURE_BURST_THRESHOLD: u64 = 5;
+const LOGIN_FAILURE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Usernames are stored and looked up lowercased and trimmed
+fn normalize_username(username: &str) -> String {
+    username.trim().to_lowercase()
+}
+
+/// Fixed-window counter of failed logins per username
+///
+/// Counts live in the shared [`CounterStore`], so with Redis every replica
+/// sees the same count. Keys are normalized here, so "Alice" and "alice "
+/// count as one user in both `record_failure` and `clear`.
+pub struct LoginFailureTracker {
+    counters: Arc<dyn CounterStore>,
+    /// Keeps the counts of separate userpass mounts apart
+    key_prefix: String,
+    threshold: u64,
+    window: Duration,
+}
+
+impl LoginFailureTracker {
+    pub fn new(counters: Arc<dyn CounterStore>, mount_path: &str, threshold: u64, window: Duration) -> Self {
+        Self {
+            counters,
+            key_prefix: format!("vault:{}:login_failures:", mount_path.trim_matches('/')),
+            threshold,
+            window,
+        }
+    }
+
+    fn key(&self, username: &str) -> String {
+        format!("{}{}", self.key_prefix, normalize_username(username))
+    }
+
+    /// Record a failure, returning the count when it reaches the threshold
+    ///
+    /// Reports once per burst: further failures inside the same window do
+    /// not report again until the window has ended.
+    pub async fn record_failure(&self, username: &str) -> VaultResult<Option<u64>> {
+        let count = self.counters.increment(&self.key(username), self.window).await?;
+        Ok((count == self.threshold).then_some(count))
+    }
+
+    pub async fn clear(&self, username: &str) -> VaultResult<()> {
+        Ok(self.counters.reset(&self.key(username)).await?)
+    }
+}
+
 /// UserPass backend for authentication
 pub struct UserPassBackend {
     pool: PgPool,
     token_store: TokenStore,
     mount_path: String,
+    login_failures: LoginFailureTracker,
 }
 
 impl UserPassBackend {
     /// Create a new UserPass backend
-    pub fn new(pool: PgPool, mount_path: &str) -> Self {
+    pub fn new(pool: PgPool, mount_path: &str, counters: Arc<dyn CounterStore>) -> Self {
         let token_store = TokenStore::new(pool.clone());
         UserPassBackend {
             pool,
             token_store,
             mount_path: mount_path.to_string(),
+            login_failures: LoginFailureTracker::new(
+                counters,
+                mount_path,
+                LOGIN_FAILURE_BURST_THRESHOLD,
+                LOGIN_FAILURE_WINDOW,
+            ),
         }
     }
 
     /// Create a new user
     pub async fn create_user(&self, request: &CreateUserRequest) -> VaultResult<UserEntry> {
-        let username = request.username.to_lowercase().trim().to_string();
+        let username = normalize_username(&request.username);
 
         if username.is_empty() {
             return Err(VaultError::Vault("username cannot be empty".to_string()));
@@ -154,7 +216,7 @@ impl UserPassBackend {
 
     /// Get a user by username
     pub async fn get_user(&self, username: &str) -> VaultResult<Option<UserEntry>> {
-        let username = username.to_lowercase().trim().to_string();
+        let username = normalize_username(username);
 
         let row: Option<(
             Uuid,
@@ -210,7 +272,7 @@ impl UserPassBackend {
 
     /// Delete a user
     pub async fn delete_user(&self, username: &str) -> VaultResult<()> {
-        let username = username.to_lowercase().trim().to_string();
+        let username = normalize_username(username);
 
         sqlx::query("DELETE FROM vault_users WHERE username = $1")
             .bind(&username)
@@ -223,7 +285,7 @@ impl UserPassBackend {
 
     /// Update user password
     pub async fn update_password(&self, username: &str, new_password: &str) -> VaultResult<()> {
-        let username = username.to_lowercase().trim().to_string();
+        let username = normalize_username(username);
 
         if new_password.is_empty() {
             return Err(VaultError::Vault("password cannot be empty".to_string()));
@@ -250,7 +312,7 @@ impl UserPassBackend {
 
     /// Update user policies
     pub async fn update_policies(&self, username: &str, policies: &[String]) -> VaultResult<()> {
-        let username = username.to_lowercase().trim().to_string();
+        let username = normalize_username(username);
 
         sqlx::query(
             r#"
@@ -270,17 +332,20 @@ impl UserPassBackend {
 
     /// Login with username and password
     pub async fn login(&self, username: &str, password: &str) -> VaultResult<LoginResponse> {
-        let user = self
-            .get_user(username)
-            .await?
-            .ok_or_else(|| VaultError::Vault("invalid username or password".to_string()))?;
+        let user = match self.get_user(username).await? {
+            Some(user) => user,
+            None => return Err(self.login_failed(username).await),
+        };
 
         // Verify password
         let valid = verify(password, &user.password_hash)
             .map_err(|e| VaultError::Vault(format!("failed to verify password: {}", e)))?;
 
         if !valid {
-            return Err(VaultError::Vault("invalid username or password".to_string()));
+            return Err(self.login_failed(username).await);
+        }
+        if let Err(e) = self.login_failures.clear(&user.username).await {
+            tracing::warn!("Failed to clear login failures: {}", e);
         }
 
         // Create token for the user
@@ -301,7 +366,7 @@ impl UserPassBackend {
 
         Ok(LoginResponse {
             client_token: raw_token,
-            accessor: format!("accessor.{}", entry.id),
+            accessor: entry.accessor(),
             policies: user.policies,
             token_ttl: user.ttl,
             renewable: true,
@@ -309,6 +374,32 @@ impl UserPassBackend {
     }
 }
 
+impl UserPassBackend {
+    /// Count a failed login and report a `LoginFailureBurst` when the threshold is hit
+    async fn login_failed(&self, username: &str) -> VaultError {
+        let username = normalize_username(username);
+        // Best effort, like the event below: a counter store outage must not
+        // change the login outcome
+        let burst = self.login_failures.record_failure(&username).await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to count login failure: {}", e);
+            None
+        });
+        if let Some(failures) = burst {
+            tracing::warn!("{} failed logins for userpass user {} within {:?}", failures, username, LOGIN_FAILURE_WINDOW);
+            let envelope = EventEnvelope::new(DomainEvent::LoginFailureBurst {
+                username,
+                failures: failures as u32,
+                window_secs: LOGIN_FAILURE_WINDOW.as_secs(),
+            });
+            // Best effort: failing to record the event must not change the login outcome
+            if let Err(e) = OutboxStore::append(&self.pool, &envelope).await {
+                tracing::warn!("Failed to record login failure burst: {}", e);
+            }
+        }
+        VaultError::Vault("invalid username or password".to_string())
+    }
+}
+
 #[async_trait]
 impl Backend for UserPassBackend {
     async fn handle_request(&self, req: &mut Request) -> VaultResult<Option<Response>> {
@@ -447,5 +538,45 @@ mod tests {
         assert!(verify(password, &hash).unwrap());
         assert!(!verify("wrong_password", &hash).unwrap());
     }
+
+    fn tracker(threshold: u64, window: Duration) -> LoginFailureTracker {
+        let counters = Arc::new(shared::infrastructure::counters::InMemoryCounterStore::new());
+        LoginFailureTracker::new(counters, "auth/userpass", threshold, window)
+    }
+
+    #[tokio::test]
+    async fn test_login_failure_burst_reported_once_per_window() {
+        let tracker = tracker(3, Duration::from_millis(50));
+
+        assert_eq!(tracker.record_failure("alice").await.unwrap(), None);
+        assert_eq!(tracker.record_failure("alice").await.unwrap(), None);
+        assert_eq!(tracker.record_failure("bob").await.unwrap(), None);
+        assert_eq!(tracker.record_failure("alice").await.unwrap(), Some(3));
+        assert_eq!(tracker.record_failure("alice").await.unwrap(), None);
+
+        // Once the window has passed the count starts over
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert_eq!(tracker.record_failure("alice").await.unwrap(), None);
+        assert_eq!(tracker.record_failure("alice").await.unwrap(), None);
+        assert_eq!(tracker.record_failure("alice").await.unwrap(), Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_login_success_clears_failures() {
+        let tracker = tracker(2, Duration::from_secs(60));
+        tracker.record_failure("alice").await.unwrap();
+        tracker.clear("alice").await.unwrap();
+        assert_eq!(tracker.record_failure("alice").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_login_failures_are_keyed_by_normalized_username() {
+        let tracker = tracker(2, Duration::from_secs(60));
+        tracker.record_failure("Alice").await.unwrap();
+        assert_eq!(tracker.record_failure(" alice").await.unwrap(), Some(2));
+
+        tracker.clear("ALICE").await.unwrap();
+        assert_eq!(tracker.record_failure("alice").await.unwrap(), None);
+    }
 }
 
@@ -0,0 +1,179 @@
+//! Database role configuration
+//!
+//! A role holds the SQL that creates and drops the ephemeral user. Statements
+//! may reference `{{name}}`, `{{password}}` and `{{expiration}}`, which are
+//! substituted before execution.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::errors::{VaultError, VaultResult};
+
+/// Lease TTL used when a role does not set one (1 hour)
+pub const DEFAULT_ROLE_TTL: u64 = 3600;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseRole {
+    pub creation_statements: Vec<String>,
+    /// Statements run on revocation; defaults to dropping the user
+    #[serde(default)]
+    pub revocation_statements: Vec<String>,
+    /// Lease TTL in seconds
+    #[serde(default = "default_ttl")]
+    pub default_ttl: u64,
+    /// Upper bound on `default_ttl` in seconds; 0 means no bound
+    #[serde(default)]
+    pub max_ttl: u64,
+}
+
+fn default_ttl() -> u64 {
+    DEFAULT_ROLE_TTL
+}
+
+impl DatabaseRole {
+    /// Parse a role from a write request body
+    ///
+    /// Statements are accepted either as a list or as a single string.
+    pub fn from_request(data: &Map<String, Value>) -> VaultResult<Self> {
+        let creation_statements = statements(data, "creation_statements")?;
+        if creation_statements.is_empty() {
+            return Err(VaultError::Validation("creation_statements is required".to_string()));
+        }
+        let role = Self {
+            creation_statements,
+            revocation_statements: statements(data, "revocation_statements")?,
+            default_ttl: seconds(data, "default_ttl")?.unwrap_or(DEFAULT_ROLE_TTL),
+            max_ttl: seconds(data, "max_ttl")?.unwrap_or(0),
+        };
+        if role.default_ttl == 0 {
+            return Err(VaultError::Validation("default_ttl must be positive".to_string()));
+        }
+        Ok(role)
+    }
+
+    /// TTL of credentials issued for this role, capped by the mount's maximum
+    pub fn lease_ttl(&self, mount_max_ttl: u64) -> u64 {
+        match self.ttl_cap(mount_max_ttl) {
+            0 => self.default_ttl,
+            cap => self.default_ttl.min(cap),
+        }
+    }
+
+    /// Notice for callers when the cap cuts `default_ttl` short
+    pub fn ttl_warning(&self, mount_max_ttl: u64) -> Option<String> {
+        let ttl = self.lease_ttl(mount_max_ttl);
+        (ttl < self.default_ttl).then(|| {
+            format!("default_ttl of {}s exceeds max_ttl; leases are clamped to {}s", self.default_ttl, ttl)
+        })
+    }
+
+    /// The tighter of the role's and the mount's maximum TTL; 0 when neither is set
+    pub fn ttl_cap(&self, mount_max_ttl: u64) -> u64 {
+        [self.max_ttl, mount_max_ttl].into_iter().filter(|&max| max > 0).min().unwrap_or(0)
+    }
+
+    /// Statements that drop `name`
+    pub fn revocation_for(&self, name: &str) -> Vec<String> {
+        if self.revocation_statements.is_empty() {
+            return Self::default_revocation(name);
+        }
+        self.revocation_statements.iter()
+            .map(|s| render(s, name, "", ""))
+            .collect()
+    }
+
+    /// Revocation used when a role sets none (or no longer exists)
+    pub fn default_revocation(name: &str) -> Vec<String> {
+        vec![format!("DROP ROLE IF EXISTS \"{}\";", name)]
+    }
+
+    pub fn to_response_data(&self) -> Map<String, Value> {
+        match serde_json::to_value(self) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        }
+    }
+}
+
+/// Substitute credential placeholders into a statement
+pub fn render(statement: &str, name: &str, password: &str, expiration: &str) -> String {
+    statement
+        .replace("{{name}}", name)
+        .replace("{{password}}", password)
+        .replace("{{expiration}}", expiration)
+}
+
+fn statements(data: &Map<String, Value>, field: &str) -> VaultResult<Vec<String>> {
+    match data.get(field) {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(Value::String(s)) => Ok(vec![s.clone()]),
+        Some(Value::Array(items)) => items.iter()
+            .map(|item| item.as_str().map(|s| s.to_string()).ok_or_else(|| {
+                VaultError::Validation(format!("{} must contain only strings", field))
+            }))
+            .collect(),
+        Some(_) => Err(VaultError::Validation(format!("{} must be a string or list", field))),
+    }
+}
+
+fn seconds(data: &Map<String, Value>, field: &str) -> VaultResult<Option<u64>> {
+    match data.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => value.as_u64()
+            .map(Some)
+            .ok_or_else(|| VaultError::Validation(format!("{} must be a number of seconds", field))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_request_accepts_string_or_list() {
+        let data = json!({
+            "creation_statements": "CREATE ROLE \"{{name}}\"",
+            "revocation_statements": ["REVOKE ALL ON ALL TABLES IN SCHEMA public FROM \"{{name}}\"", "DROP ROLE \"{{name}}\""],
+            "default_ttl": 600,
+        });
+        let role = DatabaseRole::from_request(data.as_object().unwrap()).unwrap();
+        assert_eq!(role.creation_statements.len(), 1);
+        assert_eq!(role.revocation_statements.len(), 2);
+        assert_eq!(role.default_ttl, 600);
+        assert_eq!(role.max_ttl, 0);
+
+        assert!(DatabaseRole::from_request(json!({}).as_object().unwrap()).is_err());
+        assert!(DatabaseRole::from_request(json!({"creation_statements": [1]}).as_object().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_lease_ttl_is_capped() {
+        let role = DatabaseRole {
+            creation_statements: vec![],
+            revocation_statements: vec![],
+            default_ttl: 7200,
+            max_ttl: 3600,
+        };
+        assert_eq!(role.lease_ttl(0), 3600);
+        assert_eq!(role.lease_ttl(60), 60);
+        assert!(role.ttl_warning(0).unwrap().contains("clamped to 3600s"));
+        assert!(role.ttl_warning(60).unwrap().contains("clamped to 60s"));
+
+        let uncapped = DatabaseRole { max_ttl: 0, ..role };
+        assert_eq!(uncapped.ttl_warning(0), None);
+    }
+
+    #[test]
+    fn test_revocation_defaults_to_drop() {
+        let mut role = DatabaseRole {
+            creation_statements: vec![],
+            revocation_statements: vec![],
+            default_ttl: 60,
+            max_ttl: 0,
+        };
+        assert_eq!(role.revocation_for("v-app-x"), vec!["DROP ROLE IF EXISTS \"v-app-x\";"]);
+
+        role.revocation_statements = vec!["DROP USER \"{{name}}\"".to_string()];
+        assert_eq!(role.revocation_for("v-app-x"), vec!["DROP USER \"v-app-x\""]);
+    }
+}
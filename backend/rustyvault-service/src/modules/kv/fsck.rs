@@ -0,0 +1,345 @@
+//! Consistency check of a KV mount
+//!
+//! Each secret is a metadata entry at `{mount}/metadata/{key}` and a data
+//! blob at `{mount}/data/{key}` holding the latest version. A write stores
+//! the blob before the metadata, so a crash in between, a partial restore or
+//! a manual edit can leave the two disagreeing. The check reads both trees
+//! and reports every secret where they do.
+//!
+//! It never writes unless asked to repair, and repair only touches the safe
+//! cases: metadata is rebuilt from the blob, which is the data callers read,
+//! and a blob whose version metadata records as destroyed is removed. A
+//! blob without metadata is kept and given metadata rather than deleted, as
+//! it may be the only copy of a secret. Missing data cannot be recreated and
+//! is only reported.
+//!
+//! Last-read records under `{mount}/last_read/` are checked too: one left
+//! for a secret that is gone, deleted or destroyed is stale and removed on
+//! repair.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use crate::errors::{ResultExt, VaultResult};
+use crate::modules::kv::{SecretMetadata, VersionMetadata};
+use crate::storage::StorageBackend;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// Metadata points at a live version but there is no data blob
+    MetadataWithoutData,
+    /// A data blob with no metadata
+    OrphanedData,
+    /// The blob holds a different version than metadata's `current_version`
+    VersionMismatch,
+    /// The blob of a version metadata records as destroyed
+    DestroyedDataPresent,
+    /// An entry that is not valid JSON of the expected shape
+    Undecodable,
+    /// A last-read record of a secret that is missing, deleted or destroyed
+    StaleLastRead,
+}
+
+impl IssueKind {
+    pub fn repairable(&self) -> bool {
+        matches!(
+            self,
+            Self::OrphanedData | Self::VersionMismatch | Self::DestroyedDataPresent | Self::StaleLastRead
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FsckIssue {
+    /// Secret key within the mount
+    pub key: String,
+    pub kind: IssueKind,
+    pub detail: String,
+    pub repairable: bool,
+    pub repaired: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FsckReport {
+    pub mount: String,
+    pub repair: bool,
+    /// Secrets examined
+    pub scanned: usize,
+    pub issues: Vec<FsckIssue>,
+    pub repaired: usize,
+}
+
+/// Checks, and on request repairs, one KV mount
+pub struct KvFsck {
+    storage: Arc<dyn StorageBackend>,
+    mount_path: String,
+}
+
+impl KvFsck {
+    /// `mount_path` without a trailing slash, as given to `KvBackend`
+    pub fn new(storage: Arc<dyn StorageBackend>, mount_path: String) -> Self {
+        Self { storage, mount_path }
+    }
+
+    pub async fn run(&self, repair: bool) -> VaultResult<FsckReport> {
+        let data_prefix = format!("{}/data/", self.mount_path);
+        let metadata_prefix = format!("{}/metadata/", self.mount_path);
+        let last_read_prefix = format!("{}/last_read/", self.mount_path);
+        let mut keys = self.keys_under(&data_prefix).await?;
+        keys.extend(self.keys_under(&metadata_prefix).await?);
+
+        let mut report = FsckReport {
+            mount: format!("{}/", self.mount_path),
+            repair,
+            scanned: keys.len(),
+            issues: Vec::new(),
+            repaired: 0,
+        };
+        for key in keys {
+            let Some((kind, detail, fix)) = self.check(&key, &data_prefix, &metadata_prefix).await? else {
+                continue;
+            };
+            let mut issue = FsckIssue { key, kind, detail, repairable: kind.repairable(), repaired: false };
+            if repair {
+                if let Some(fix) = fix {
+                    self.apply(&issue.key, &data_prefix, &metadata_prefix, fix).await?;
+                    issue.repaired = true;
+                    report.repaired += 1;
+                }
+            }
+            report.issues.push(issue);
+        }
+
+        // Not secrets of their own, so not counted as scanned
+        for key in self.keys_under(&last_read_prefix).await? {
+            let Some(detail) = self.check_last_read(&key, &metadata_prefix).await? else {
+                continue;
+            };
+            let kind = IssueKind::StaleLastRead;
+            let mut issue = FsckIssue { key, kind, detail, repairable: kind.repairable(), repaired: false };
+            if repair {
+                let path = format!("{}{}", last_read_prefix, issue.key);
+                self.storage.delete(&path).await.with_context(|| format!("delete {}", path))?;
+                issue.repaired = true;
+                report.repaired += 1;
+            }
+            report.issues.push(issue);
+        }
+        Ok(report)
+    }
+
+    /// Why the last-read record of `key` is stale, if it is
+    async fn check_last_read(&self, key: &str, metadata_prefix: &str) -> VaultResult<Option<String>> {
+        let metadata_path = format!("{}{}", metadata_prefix, key);
+        let Some(raw) = self.storage.get(&metadata_path).await.with_context(|| format!("read {}", metadata_path))? else {
+            return Ok(Some("last read recorded for a secret with no metadata".to_string()));
+        };
+        // Undecodable metadata is reported by the secret check
+        let Ok(metadata) = serde_json::from_slice::<SecretMetadata>(&raw) else {
+            return Ok(None);
+        };
+        if metadata.deleted {
+            return Ok(Some("last read recorded for a deleted secret".to_string()));
+        }
+        if metadata.versions.get(&metadata.current_version).is_some_and(|v| v.destroyed) {
+            return Ok(Some("last read recorded for a destroyed secret".to_string()));
+        }
+        Ok(None)
+    }
+
+    /// Keys of every entry under `prefix`, relative to it
+    async fn keys_under(&self, prefix: &str) -> VaultResult<BTreeSet<String>> {
+        let mut keys = BTreeSet::new();
+        let mut pending = vec![prefix.to_string()];
+        while let Some(dir) = pending.pop() {
+            let entries = self.storage.list(&dir).await.with_context(|| format!("list {}", dir))?;
+            for entry in entries {
+                if entry.ends_with('/') {
+                    pending.push(entry);
+                } else if let Some(key) = entry.strip_prefix(prefix) {
+                    keys.insert(key.to_string());
+                }
+            }
+        }
+        Ok(keys)
+    }
+
+    async fn check(
+        &self,
+        key: &str,
+        data_prefix: &str,
+        metadata_prefix: &str,
+    ) -> VaultResult<Option<(IssueKind, String, Option<Fix>)>> {
+        let metadata_path = format!("{}{}", metadata_prefix, key);
+        let metadata = match self.storage.get(&metadata_path).await.with_context(|| format!("read {}", metadata_path))? {
+            Some(raw) => match serde_json::from_slice::<SecretMetadata>(&raw) {
+                Ok(metadata) => Some(metadata),
+                Err(e) => return Ok(Some((IssueKind::Undecodable, format!("metadata: {}", e), None))),
+            },
+            None => None,
+        };
+        let data_path = format!("{}{}", data_prefix, key);
+        let data_version = match self.storage.get(&data_path).await.with_context(|| format!("read {}", data_path))? {
+            Some(raw) => match serde_json::from_slice::<Map<String, Value>>(&raw) {
+                Ok(blob) => Some(blob.get("version").and_then(Value::as_u64)),
+                Err(e) => return Ok(Some((IssueKind::Undecodable, format!("data: {}", e), None))),
+            },
+            None => None,
+        };
+
+        let issue = match (metadata, data_version) {
+            (Some(metadata), None) => {
+                let destroyed = metadata.versions.get(&metadata.current_version).is_some_and(|v| v.destroyed);
+                // Metadata written on its own, before any version, has no data
+                (metadata.current_version > 0 && !metadata.deleted && !destroyed).then(|| {
+                    (
+                        IssueKind::MetadataWithoutData,
+                        format!("version {} has no data", metadata.current_version),
+                        None,
+                    )
+                })
+            }
+            (None, Some(version)) => {
+                let version = version.unwrap_or(1);
+                Some((
+                    IssueKind::OrphanedData,
+                    format!("data for version {} has no metadata", version),
+                    Some(Fix::SetCurrentVersion(version)),
+                ))
+            }
+            (Some(metadata), Some(version)) => {
+                let version = version.unwrap_or(metadata.current_version);
+                if metadata.versions.get(&version).is_some_and(|v| v.destroyed) {
+                    Some((
+                        IssueKind::DestroyedDataPresent,
+                        format!("version {} is destroyed but its data remains", version),
+                        Some(Fix::RemoveData),
+                    ))
+                } else if version != metadata.current_version {
+                    Some((
+                        IssueKind::VersionMismatch,
+                        format!("data holds version {}, metadata current_version is {}", version, metadata.current_version),
+                        Some(Fix::SetCurrentVersion(version)),
+                    ))
+                } else {
+                    None
+                }
+            }
+            (None, None) => None,
+        };
+        Ok(issue)
+    }
+
+    async fn apply(&self, key: &str, data_prefix: &str, metadata_prefix: &str, fix: Fix) -> VaultResult<()> {
+        match fix {
+            Fix::RemoveData => {
+                let data_path = format!("{}{}", data_prefix, key);
+                self.storage.delete(&data_path).await.with_context(|| format!("delete {}", data_path))?;
+                // Nobody can read a destroyed version
+                let last_read_path = format!("{}/last_read/{}", self.mount_path, key);
+                self.storage.delete(&last_read_path).await.with_context(|| format!("delete {}", last_read_path))
+            }
+            Fix::SetCurrentVersion(version) => {
+                let metadata_path = format!("{}{}", metadata_prefix, key);
+                let now = Utc::now();
+                let mut metadata = match self.storage.get(&metadata_path).await.with_context(|| format!("read {}", metadata_path))? {
+                    Some(raw) => serde_json::from_slice(&raw).with_context(|| format!("decode {}", metadata_path))?,
+                    None => SecretMetadata::new(now),
+                };
+                metadata.current_version = version;
+                metadata.updated_time = Some(now);
+                metadata.versions.entry(version).or_insert(VersionMetadata {
+                    created_time: now,
+                    deletion_time: None,
+                    destroyed: false,
+                });
+                let raw = serde_json::to_vec(&metadata).with_context(|| format!("encode {}", metadata_path))?;
+                self.storage.put(&metadata_path, &raw).await.with_context(|| format!("write {}", metadata_path))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Fix {
+    /// Point metadata, created if missing, at the version the blob holds
+    SetCurrentVersion(u64),
+    RemoveData,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::physical_inmem::InMemoryBackend;
+    use serde_json::json;
+
+    async fn put_json(storage: &dyn StorageBackend, path: &str, value: Value) {
+        storage.put(path, &serde_json::to_vec(&value).unwrap()).await.unwrap();
+    }
+
+    fn metadata(version: u64) -> Value {
+        let mut metadata = SecretMetadata::new(Utc::now());
+        for _ in 0..version {
+            metadata.add_version(Utc::now());
+        }
+        serde_json::to_value(metadata).unwrap()
+    }
+
+    async fn seeded() -> Arc<dyn StorageBackend> {
+        let storage: Arc<dyn StorageBackend> = Arc::new(InMemoryBackend::new());
+        // Consistent
+        put_json(storage.as_ref(), "secret/metadata/app/db", metadata(2)).await;
+        put_json(storage.as_ref(), "secret/data/app/db", json!({"data": {"a": 1}, "version": 2})).await;
+        // Crashed between the data and metadata writes
+        put_json(storage.as_ref(), "secret/metadata/app/api", metadata(1)).await;
+        put_json(storage.as_ref(), "secret/data/app/api", json!({"data": {"a": 2}, "version": 2})).await;
+        put_json(storage.as_ref(), "secret/data/orphan", json!({"data": {}, "version": 3})).await;
+        put_json(storage.as_ref(), "secret/metadata/lost", metadata(4)).await;
+        storage.put("secret/metadata/garbled", b"not json").await.unwrap();
+        // Last reads of a live secret and of ones that are gone
+        let last_read = json!({"time": Utc::now(), "identity": "alice", "request_id": "req-1"});
+        put_json(storage.as_ref(), "secret/last_read/app/db", last_read.clone()).await;
+        put_json(storage.as_ref(), "secret/last_read/removed", last_read).await;
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_check_reports_without_writing() {
+        let storage = seeded().await;
+        let report = KvFsck::new(storage.clone(), "secret".to_string()).run(false).await.unwrap();
+
+        assert_eq!(report.scanned, 5);
+        let kinds: Vec<(&str, IssueKind)> = report.issues.iter().map(|i| (i.key.as_str(), i.kind)).collect();
+        assert_eq!(kinds, vec![
+            ("app/api", IssueKind::VersionMismatch),
+            ("garbled", IssueKind::Undecodable),
+            ("lost", IssueKind::MetadataWithoutData),
+            ("orphan", IssueKind::OrphanedData),
+            ("removed", IssueKind::StaleLastRead),
+        ]);
+        assert_eq!(report.repaired, 0);
+        assert!(storage.get("secret/metadata/orphan").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_repair_fixes_only_the_safe_cases() {
+        let storage = seeded().await;
+        let fsck = KvFsck::new(storage.clone(), "secret".to_string());
+        let report = fsck.run(true).await.unwrap();
+        assert_eq!(report.repaired, 3);
+        assert!(report.issues.iter().all(|i| i.repaired == i.repairable));
+
+        let after = fsck.run(false).await.unwrap();
+        let kinds: Vec<IssueKind> = after.issues.iter().map(|i| i.kind).collect();
+        assert_eq!(kinds, vec![IssueKind::Undecodable, IssueKind::MetadataWithoutData]);
+
+        let raw = storage.get("secret/metadata/orphan").await.unwrap().unwrap();
+        let orphan: SecretMetadata = serde_json::from_slice(&raw).unwrap();
+        assert_eq!(orphan.current_version, 3);
+        assert!(storage.get("secret/last_read/removed").await.unwrap().is_none());
+        assert!(storage.get("secret/last_read/app/db").await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,98 @@
+//! KV secret metadata
+//!
+//! Metadata is stored separately from secret data so it can be read (for
+//! audit and listing screens) without loading or decrypting any data blob.
+
+use std::collections::BTreeMap;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Per-version metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionMetadata {
+    pub created_time: DateTime<Utc>,
+    #[serde(default)]
+    pub deletion_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub destroyed: bool,
+}
+
+/// The last read of a secret's data
+///
+/// Stored beside, not inside, [`SecretMetadata`] so that recording a read
+/// can never race a write's metadata update and lose a version.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LastRead {
+    pub time: DateTime<Utc>,
+    /// Entity ID of the reader's token, or its display name
+    pub identity: String,
+    pub request_id: String,
+}
+
+/// Metadata of a versioned secret
+///
+/// Fields other than `current_version` default so entries written before
+/// version history was tracked still load.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SecretMetadata {
+    pub current_version: u64,
+    pub created_time: DateTime<Utc>,
+    #[serde(default)]
+    pub updated_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub custom_metadata: Map<String, Value>,
+    /// Whether the current version is deleted
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(default)]
+    pub deletion_time: Option<DateTime<Utc>>,
+    /// Version history keyed by version number
+    #[serde(default)]
+    pub versions: BTreeMap<u64, VersionMetadata>,
+}
+
+impl SecretMetadata {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            current_version: 0,
+            created_time: now,
+            updated_time: None,
+            custom_metadata: Map::new(),
+            deleted: false,
+            deletion_time: None,
+            versions: BTreeMap::new(),
+        }
+    }
+
+    /// Record a newly written version, returning its number
+    pub fn add_version(&mut self, now: DateTime<Utc>) -> u64 {
+        self.current_version += 1;
+        self.updated_time = Some(now);
+        self.deleted = false;
+        self.deletion_time = None;
+        self.versions.insert(self.current_version, VersionMetadata {
+            created_time: now,
+            deletion_time: None,
+            destroyed: false,
+        });
+        self.current_version
+    }
+
+    /// Mark the current version as deleted
+    pub fn delete_current(&mut self, now: DateTime<Utc>) {
+        self.deleted = true;
+        self.deletion_time = Some(now);
+        if let Some(version) = self.versions.get_mut(&self.current_version) {
+            version.deletion_time = Some(now);
+        }
+    }
+
+    /// Response body for a metadata read
+    pub fn to_response_data(&self) -> Map<String, Value> {
+        match serde_json::to_value(self) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        }
+    }
+}
@@ -1,18 +1,71 @@
 //! KV (Key-Value) secrets engine module
 //!
 //! KV2 implementation for versioned key-value storage
+//!
+//! Paths under `metadata/` address a secret's metadata instead of its data:
+//! reading `secret/metadata/{key}` returns the version history without
+//! touching the data blob.
+//!
+//! Reading `secret/subkeys/{key}` returns the latest version's key structure
+//! with every value replaced by null, for callers that may see a secret's
+//! shape but not its values. An optional `depth` limits how many levels of
+//! nested objects are walked; 0 (the default) walks them all.
+//!
+//! Secret reads are `no-store` unless the mount's `cache_ttls` config lets
+//! clients cache keys under a prefix, e.g. `{"config/": 60}`.
+//!
+//! The mount's `seal_wrap` config seal wraps the data of the most sensitive
+//! secrets (see `storage::seal_wrap`): `true` for every key, or a list of key
+//! prefixes such as `["pki-root/", "hsm/"]`. Wrapped blobs keep their version
+//! in plaintext beside the wrapped data, so metadata and fsck work without
+//! the seal. Secrets outside the prefixes are stored exactly as before, and
+//! entries written before a prefix was added are wrapped on their next write.
+//!
+//! Seal wrapping costs one more AES-256-GCM pass over the data on every read
+//! and write of a wrapped secret, plus base64 in storage, which makes those
+//! blobs about a third larger. The key is derived once per unseal, so the
+//! cost grows with the size of the secret rather than the request count;
+//! keep it to the secrets compliance requires it for.
+//!
+//! The mount's `track_reads` config records when each secret's data was
+//! last read and by whom, returned as `last_read` by the metadata read.
+//! It is off by default because it turns every read into a write. The
+//! record is written by a background task after the read has been answered,
+//! so it adds no latency but is eventually consistent: a metadata read
+//! straight after a data read may still show the previous reader, and a
+//! record whose write fails is only logged. Concurrent reads race, and the
+//! last write wins. Deleting a secret removes its record; one that lands
+//! after the delete is left for fsck to remove.
+
+pub mod fsck;
+pub mod metadata;
+
+pub use fsck::{FsckReport, KvFsck};
+pub use metadata::{LastRead, SecretMetadata, VersionMetadata};
 
 use std::sync::Arc;
 use async_trait::async_trait;
 use serde_json::{Map, Value};
-use crate::errors::VaultResult;
-use crate::logical::{Backend, Request, Response, Operation};
-use crate::storage::StorageBackend;
+use base64::{engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD}, Engine};
+use crate::errors::{ResultExt, VaultError, VaultResult};
+use crate::logical::{Backend, CacheHint, Request, RequestContext, Response, Operation};
+use crate::storage::{SealWrapper, StorageBackend};
+
+/// Field of a data blob holding its seal-wrapped contents
+const SEAL_WRAPPED_FIELD: &str = "seal_wrapped";
 
 /// KV secrets engine backend
 pub struct KvBackend {
     storage: Arc<dyn StorageBackend>,
     mount_path: String,
+    /// Key prefixes clients may cache reads of, with the max age in seconds
+    cache_ttls: Vec<(String, u64)>,
+    /// Wraps data of keys under `seal_wrap_prefixes`, and unwraps any
+    /// wrapped blob on read
+    seal_wrap: Option<Arc<dyn SealWrapper>>,
+    seal_wrap_prefixes: Vec<String>,
+    /// Record the last read of each secret's data
+    track_reads: bool,
 }
 
 impl KvBackend {
@@ -20,9 +73,90 @@ impl KvBackend {
         Self {
             storage,
             mount_path,
+            cache_ttls: Vec::new(),
+            seal_wrap: None,
+            seal_wrap_prefixes: Vec::new(),
+            track_reads: false,
         }
     }
 
+    /// Let clients cache reads of keys under each prefix for its max age
+    pub fn with_cache_ttls(mut self, cache_ttls: Vec<(String, u64)>) -> Self {
+        self.cache_ttls = cache_ttls;
+        self
+    }
+
+    /// Parse a mount's `cache_ttls` config: an object of key prefix to seconds
+    pub fn parse_cache_ttls(config: &Map<String, Value>) -> VaultResult<Vec<(String, u64)>> {
+        let Some(raw) = config.get("cache_ttls") else {
+            return Ok(Vec::new());
+        };
+        let Value::Object(entries) = raw else {
+            return Err(VaultError::Validation("cache_ttls must be an object of prefix to seconds".to_string()));
+        };
+        entries
+            .iter()
+            .map(|(prefix, ttl)| {
+                ttl.as_u64()
+                    .map(|ttl| (prefix.clone(), ttl))
+                    .ok_or_else(|| VaultError::Validation(format!("cache_ttls.{} must be a number of seconds", prefix)))
+            })
+            .collect()
+    }
+
+    /// Seal wrap the data of keys under each prefix; `""` covers every key
+    pub fn with_seal_wrap(mut self, wrapper: Arc<dyn SealWrapper>, prefixes: Vec<String>) -> Self {
+        self.seal_wrap = Some(wrapper);
+        self.seal_wrap_prefixes = prefixes;
+        self
+    }
+
+    /// Parse a mount's `seal_wrap` config: `true` for every key, or a list
+    /// of key prefixes
+    pub fn parse_seal_wrap(config: &Map<String, Value>) -> VaultResult<Vec<String>> {
+        match config.get("seal_wrap") {
+            None | Some(Value::Bool(false)) => Ok(Vec::new()),
+            Some(Value::Bool(true)) => Ok(vec![String::new()]),
+            Some(Value::Array(prefixes)) => prefixes
+                .iter()
+                .map(|prefix| {
+                    prefix.as_str()
+                        .map(|s| s.to_string())
+                        .ok_or_else(|| VaultError::Validation("seal_wrap prefixes must be strings".to_string()))
+                })
+                .collect(),
+            Some(_) => Err(VaultError::Validation("seal_wrap must be true, false or a list of key prefixes".to_string())),
+        }
+    }
+
+    /// Record the time and reader of every data read
+    pub fn with_read_tracking(mut self, track_reads: bool) -> Self {
+        self.track_reads = track_reads;
+        self
+    }
+
+    /// Parse a mount's `track_reads` config: a boolean, off when absent
+    pub fn parse_track_reads(config: &Map<String, Value>) -> VaultResult<bool> {
+        match config.get("track_reads") {
+            None => Ok(false),
+            Some(Value::Bool(track_reads)) => Ok(*track_reads),
+            Some(_) => Err(VaultError::Validation("track_reads must be true or false".to_string())),
+        }
+    }
+
+    fn seal_wraps(&self, key: &str) -> bool {
+        self.seal_wrap_prefixes.iter().any(|prefix| key.starts_with(prefix.as_str()))
+    }
+
+    /// The longest configured prefix of `key` decides; keys under none are no-store
+    fn cache_hint(&self, key: &str) -> CacheHint {
+        self.cache_ttls
+            .iter()
+            .filter(|(prefix, _)| key.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(CacheHint::NoStore, |&(_, ttl)| CacheHint::MaxAge(ttl))
+    }
+
     fn storage_path(&self, key: &str) -> String {
         format!("{}/data/{}", self.mount_path, key)
     }
@@ -31,77 +165,201 @@ impl KvBackend {
         format!("{}/metadata/{}", self.mount_path, key)
     }
 
+    fn last_read_path(&self, key: &str) -> String {
+        format!("{}/last_read/{}", self.mount_path, key)
+    }
+
+    async fn load_last_read(&self, key: &str) -> VaultResult<Option<LastRead>> {
+        let path = self.last_read_path(key);
+        match self.storage.get(&path).await.with_context(|| format!("read {}", path))? {
+            Some(raw) => Ok(Some(serde_json::from_slice(&raw).with_context(|| format!("decode {}", path))?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Record a read of `key` by `context` in the background, if tracking
+    ///
+    /// Reads without a context come from inside the vault and are not
+    /// recorded.
+    fn track_read(&self, key: &str, context: Option<&RequestContext>) {
+        let (true, Some(context)) = (self.track_reads, context) else {
+            return;
+        };
+        let last_read = LastRead {
+            time: chrono::Utc::now(),
+            identity: context.identity.clone(),
+            request_id: context.request_id.clone(),
+        };
+        let storage = self.storage.clone();
+        let path = self.last_read_path(key);
+        tokio::spawn(async move {
+            let stored = match serde_json::to_vec(&last_read) {
+                Ok(raw) => storage.put(&path, &raw).await,
+                Err(e) => Err(e.into()),
+            };
+            if let Err(e) = stored {
+                tracing::warn!("Failed to record last read of {}: {}", path, e);
+            }
+        });
+    }
+
+    async fn load_metadata(&self, key: &str) -> VaultResult<Option<SecretMetadata>> {
+        let path = self.metadata_path(key);
+        match self.storage.get(&path).await.with_context(|| format!("read {}", path))? {
+            Some(raw) => Ok(Some(serde_json::from_slice(&raw).with_context(|| format!("decode {}", path))?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn store_metadata(&self, key: &str, metadata: &SecretMetadata) -> VaultResult<()> {
+        let path = self.metadata_path(key);
+        let meta_json = serde_json::to_vec(metadata).with_context(|| format!("encode {}", path))?;
+        self.storage.put(&path, &meta_json).await.with_context(|| format!("write {}", path))
+    }
+
+    /// Load the data blob of `key`, unwrapping it if it is seal wrapped
+    async fn load_data(&self, key: &str) -> VaultResult<Option<Map<String, Value>>> {
+        let path = self.storage_path(key);
+        let Some(raw) = self.storage.get(&path).await.with_context(|| format!("read {}", path))? else {
+            return Ok(None);
+        };
+        let stored: Map<String, Value> = serde_json::from_slice(&raw).with_context(|| format!("decode {}", path))?;
+        let Some(wrapped) = stored.get(SEAL_WRAPPED_FIELD) else {
+            return Ok(Some(stored));
+        };
+
+        // Wrapped blobs are read even if the prefix has since been removed
+        let wrapper = self.seal_wrap.as_ref().ok_or_else(|| {
+            VaultError::Config(format!("{} is seal wrapped but seal wrapping is not available", path))
+        })?;
+        let wrapped = wrapped.as_str()
+            .and_then(|w| STANDARD.decode(w).ok())
+            .ok_or_else(|| VaultError::Vault(format!("Invalid seal-wrapped value at {}", path)))?;
+        let plaintext = zeroize::Zeroizing::new(wrapper.seal_unwrap(&path, &wrapped)?);
+        Ok(Some(serde_json::from_slice(&plaintext).with_context(|| format!("decode {}", path))?))
+    }
+
+    /// Store the data blob of `key`, seal wrapping it if its prefix asks for that
+    async fn store_data(&self, key: &str, version: u64, data: Map<String, Value>) -> VaultResult<()> {
+        let path = self.storage_path(key);
+        let mut versioned_data = Map::new();
+        versioned_data.insert("data".to_string(), Value::Object(data));
+        versioned_data.insert("version".to_string(), Value::Number(version.into()));
+
+        let data_json = serde_json::to_vec(&versioned_data).with_context(|| format!("encode {}", path))?;
+        let data_json = match self.seal_wrap.as_ref().filter(|_| self.seal_wraps(key)) {
+            Some(wrapper) => {
+                let wrapped = wrapper.seal_wrap(&path, &zeroize::Zeroizing::new(data_json))?;
+                let mut stored = Map::new();
+                stored.insert(SEAL_WRAPPED_FIELD.to_string(), Value::String(STANDARD.encode(wrapped)));
+                stored.insert("version".to_string(), Value::Number(version.into()));
+                serde_json::to_vec(&stored).with_context(|| format!("encode {}", path))?
+            }
+            None => data_json,
+        };
+        self.storage.put(&path, &data_json).await.with_context(|| format!("write {}", path))
+    }
+
+    /// Read a secret's metadata and version history without loading its data
+    async fn read_metadata(&self, key: &str) -> VaultResult<Option<Response>> {
+        let Some(metadata) = self.load_metadata(key).await? else {
+            return Ok(None);
+        };
+        let mut data = metadata.to_response_data();
+        if let Some(last_read) = self.load_last_read(key).await? {
+            data.insert("last_read".to_string(), serde_json::to_value(last_read)?);
+        }
+        Ok(Some(Response::new().data(data)))
+    }
+
+    /// Replace a secret's `custom_metadata`
+    async fn write_metadata(&self, key: &str, data: Map<String, Value>) -> VaultResult<Option<Response>> {
+        let custom_metadata = match data.get("custom_metadata") {
+            Some(Value::Object(custom)) => custom.clone(),
+            Some(_) => {
+                return Err(VaultError::Validation("custom_metadata must be an object".to_string()));
+            }
+            None => Map::new(),
+        };
+
+        let mut metadata = self.load_metadata(key).await?
+            .unwrap_or_else(|| SecretMetadata::new(chrono::Utc::now()));
+        metadata.custom_metadata = custom_metadata;
+        metadata.updated_time = Some(chrono::Utc::now());
+        self.store_metadata(key, &metadata).await?;
+
+        Ok(Some(Response::new().data(metadata.to_response_data())))
+    }
+
     async fn read_secret(&self, key: &str) -> VaultResult<Option<Response>> {
-        let data_path = self.storage_path(key);
-        let data = self.storage.get(&data_path).await?;
-        
-        if data.is_none() {
+        let Some(value) = self.load_data(key).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(Response::new().data(value).cache(self.cache_hint(key))))
+    }
+
+    /// Read the key structure of a secret's latest version with values nulled
+    ///
+    /// Deleted and destroyed versions have no structure to show.
+    async fn read_subkeys(&self, key: &str, depth: u64) -> VaultResult<Option<Response>> {
+        let Some(metadata) = self.load_metadata(key).await? else {
+            return Ok(None);
+        };
+        let destroyed = metadata.versions.get(&metadata.current_version).is_some_and(|v| v.destroyed);
+        if metadata.deleted || destroyed {
             return Ok(None);
         }
 
-        let value: Map<String, Value> = serde_json::from_slice(&data.unwrap())
-            .map_err(|e| crate::errors::VaultError::Serialization(e))?;
+        let Some(stored) = self.load_data(key).await? else {
+            return Ok(None);
+        };
+        let secret = match stored.get("data") {
+            Some(Value::Object(secret)) => secret,
+            _ => &stored,
+        };
 
-        Ok(Some(Response::new().data(value)))
+        let mut data = Map::new();
+        data.insert("subkeys".to_string(), Value::Object(redact_values(secret, depth)));
+        data.insert("version".to_string(), Value::from(metadata.current_version));
+        Ok(Some(Response::new().data(data)))
     }
 
     async fn write_secret(&self, key: &str, data: Map<String, Value>) -> VaultResult<Option<Response>> {
-        let data_path = self.storage_path(key);
-        let metadata_path = self.metadata_path(key);
+        let now = chrono::Utc::now();
 
         // Get existing version
-        let version = if let Some(meta_data) = self.storage.get(&metadata_path).await? {
-            let meta: Map<String, Value> = serde_json::from_slice(&meta_data)
-                .map_err(|e| crate::errors::VaultError::Serialization(e))?;
-            meta.get("current_version")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(0) + 1
-        } else {
-            1
-        };
+        let mut metadata = self.load_metadata(key).await?
+            .unwrap_or_else(|| SecretMetadata::new(now));
+        let version = metadata.add_version(now);
 
         // Store data with version
-        let mut versioned_data = Map::new();
-        versioned_data.insert("data".to_string(), Value::Object(data.clone()));
-        versioned_data.insert("version".to_string(), Value::Number(version.into()));
-
-        let data_json = serde_json::to_vec(&versioned_data)
-            .map_err(|e| crate::errors::VaultError::Serialization(e))?;
-        self.storage.put(&data_path, &data_json).await?;
+        self.store_data(key, version, data.clone()).await?;
 
         // Update metadata
-        let mut metadata = Map::new();
-        metadata.insert("current_version".to_string(), Value::Number(version.into()));
-        metadata.insert("created_time".to_string(), Value::String(chrono::Utc::now().to_rfc3339()));
-        
-        let meta_json = serde_json::to_vec(&metadata)
-            .map_err(|e| crate::errors::VaultError::Serialization(e))?;
-        self.storage.put(&metadata_path, &meta_json).await?;
+        self.store_metadata(key, &metadata).await?;
 
         Ok(Some(Response::new().data(data)))
     }
 
     async fn delete_secret(&self, key: &str) -> VaultResult<Option<Response>> {
-        let metadata_path = self.metadata_path(key);
-
         // Mark as deleted in metadata instead of actually deleting
-        if let Some(meta_data) = self.storage.get(&metadata_path).await? {
-            let mut meta: Map<String, Value> = serde_json::from_slice(&meta_data)
-                .map_err(|e| crate::errors::VaultError::Serialization(e))?;
-            meta.insert("deleted".to_string(), Value::Bool(true));
-            meta.insert("deletion_time".to_string(), Value::String(chrono::Utc::now().to_rfc3339()));
-            
-            let meta_json = serde_json::to_vec(&meta)
-                .map_err(|e| crate::errors::VaultError::Serialization(e))?;
-            self.storage.put(&metadata_path, &meta_json).await?;
+        if let Some(mut metadata) = self.load_metadata(key).await? {
+            metadata.delete_current(chrono::Utc::now());
+            self.store_metadata(key, &metadata).await?;
         }
 
+        // Who last read a deleted secret is not kept
+        let path = self.last_read_path(key);
+        self.storage.delete(&path).await.with_context(|| format!("delete {}", path))?;
+
         Ok(None)
     }
 
     async fn list_secrets(&self, prefix: &str) -> VaultResult<Option<Response>> {
         let list_path = format!("{}/data/{}", self.mount_path, prefix);
-        let keys = self.storage.list(&list_path).await?;
+        let keys = self.storage.list(&list_path).await
+            .with_context(|| format!("list {}", list_path))?;
         
         // Extract just the key names
         let key_names: Vec<String> = keys.iter()
@@ -119,25 +377,489 @@ impl KvBackend {
 
         Ok(Some(Response::new().data(data)))
     }
+
+    /// List one page of keys under `prefix`
+    ///
+    /// `after` is the opaque cursor returned as `next_cursor` by the previous
+    /// page. `next_cursor` is only present when more keys may follow.
+    async fn list_secrets_page(
+        &self,
+        prefix: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> VaultResult<Option<Response>> {
+        if limit == 0 || limit > MAX_LIST_PAGE_SIZE {
+            return Err(VaultError::Validation(format!(
+                "limit must be between 1 and {}", MAX_LIST_PAGE_SIZE
+            )));
+        }
+
+        let data_prefix = format!("{}/data/", self.mount_path);
+        let after_key = match after {
+            Some(cursor) => Some(format!("{}{}", data_prefix, decode_cursor(cursor)?)),
+            None => None,
+        };
+
+        let list_path = format!("{}{}", data_prefix, prefix);
+        let keys = self.storage.list_page(&list_path, after_key.as_deref(), limit).await
+            .with_context(|| format!("list {}", list_path))?;
+
+        let key_names: Vec<String> = keys.iter()
+            .map(|k| k.strip_prefix(&data_prefix).unwrap_or(k).to_string())
+            .collect();
+
+        let mut data = Map::new();
+        if key_names.len() == limit {
+            if let Some(last) = key_names.last() {
+                data.insert("next_cursor".to_string(), Value::String(encode_cursor(last)));
+            }
+        }
+        data.insert("keys".to_string(), Value::Array(
+            key_names.into_iter().map(Value::String).collect()
+        ));
+
+        Ok(Some(Response::new().data(data)))
+    }
+}
+
+/// Upper bound on keys returned by a single paginated list
+pub const MAX_LIST_PAGE_SIZE: usize = 1000;
+
+/// Copy of `secret` with every non-object value replaced by null
+///
+/// Objects nested `depth` levels down are nulled too; a depth of 0 walks
+/// the whole structure.
+fn redact_values(secret: &Map<String, Value>, depth: u64) -> Map<String, Value> {
+    secret
+        .iter()
+        .map(|(name, value)| {
+            let redacted = match value {
+                Value::Object(nested) if depth != 1 => Value::Object(redact_values(nested, depth.saturating_sub(1))),
+                _ => Value::Null,
+            };
+            (name.clone(), redacted)
+        })
+        .collect()
+}
+
+fn encode_cursor(key: &str) -> String {
+    URL_SAFE_NO_PAD.encode(key.as_bytes())
+}
+
+fn decode_cursor(cursor: &str) -> VaultResult<String> {
+    URL_SAFE_NO_PAD.decode(cursor)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| VaultError::Validation("invalid list cursor".to_string()))
 }
 
 #[async_trait]
 impl Backend for KvBackend {
     async fn handle_request(&self, req: &mut Request) -> VaultResult<Option<Response>> {
-        // Remove mount path from request path
-        let key = req.path.strip_prefix(&format!("{}/", self.mount_path))
+        // Remove the mount point resolved by the router from the request path
+        let key = req.path.strip_prefix(req.mount_point.as_str())
             .unwrap_or(&req.path)
             .to_string();
 
+        // Metadata requests never load the secret data
+        let key = match key.strip_prefix("metadata/") {
+            Some(meta_key) => match req.operation {
+                Operation::Read => return self.read_metadata(meta_key).await,
+                Operation::Write => {
+                    let data = req.data.take();
+                    return self.write_metadata(meta_key, data.unwrap_or_default()).await;
+                }
+                Operation::Delete => {
+                    return Err(VaultError::Validation(
+                        "deleting metadata is not supported".to_string(),
+                    ));
+                }
+                // Listing only returns key names, same as listing data
+                Operation::List => meta_key.to_string(),
+            },
+            None => key,
+        };
+
+        if let Some(subkeys_key) = key.strip_prefix("subkeys/") {
+            if req.operation != Operation::Read {
+                return Err(VaultError::Validation("subkeys only supports reads".to_string()));
+            }
+            let depth = match req.data.as_ref().and_then(|d| d.get("depth")) {
+                Some(depth) => depth.as_u64()
+                    .ok_or_else(|| VaultError::Validation("depth must be a non-negative integer".to_string()))?,
+                None => 0,
+            };
+            return self.read_subkeys(subkeys_key, depth).await;
+        }
+
         match req.operation {
-            Operation::Read => self.read_secret(&key).await,
+            Operation::Read => {
+                let response = self.read_secret(&key).await?;
+                if response.is_some() {
+                    self.track_read(&key, req.context.as_ref());
+                }
+                Ok(response)
+            }
             Operation::Write => {
                 let data = req.data.take();
                 self.write_secret(&key, data.unwrap_or_default()).await
             }
             Operation::Delete => self.delete_secret(&key).await,
-            Operation::List => self.list_secrets(&key).await,
+            Operation::List => {
+                // Pagination is explicit: only requested when a limit is supplied
+                let page = req.data.as_ref().and_then(|d| d.get("limit").map(|limit| {
+                    let after = d.get("after").and_then(|v| v.as_str()).map(|s| s.to_string());
+                    (limit.as_u64().unwrap_or(0) as usize, after)
+                }));
+                match page {
+                    Some((limit, after)) => self.list_secrets_page(&key, after.as_deref(), limit).await,
+                    None => self.list_secrets(&key).await,
+                }
+            }
         }
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::physical_inmem::InMemoryBackend;
+
+    async fn backend_with_keys(n: usize) -> KvBackend {
+        let kv = KvBackend::new(Arc::new(InMemoryBackend::new()), "secret".to_string());
+        for i in 0..n {
+            kv.write_secret(&format!("key-{:03}", i), Map::new()).await.unwrap();
+        }
+        kv
+    }
+
+    fn keys_of(resp: &Response) -> Vec<String> {
+        resp.data.as_ref().unwrap()["keys"].as_array().unwrap()
+            .iter().map(|k| k.as_str().unwrap().to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_paginated_list_walks_all_keys() {
+        let kv = backend_with_keys(7).await;
+
+        let mut seen = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let resp = kv.list_secrets_page("", cursor.as_deref(), 3).await.unwrap().unwrap();
+            seen.extend(keys_of(&resp));
+            match resp.data.as_ref().unwrap().get("next_cursor") {
+                Some(Value::String(next)) => cursor = Some(next.clone()),
+                _ => break,
+            }
+        }
+
+        let expected: Vec<String> = (0..7).map(|i| format!("key-{:03}", i)).collect();
+        assert_eq!(seen, expected);
+    }
+
+    #[tokio::test]
+    async fn test_paginated_list_is_explicit() {
+        let kv = backend_with_keys(3).await;
+
+        let mut req = Request::new_list_request("secret/");
+        req.mount_point = "secret/".to_string();
+        let resp = kv.handle_request(&mut req).await.unwrap().unwrap();
+        assert_eq!(keys_of(&resp).len(), 3);
+        assert!(resp.data.unwrap().get("next_cursor").is_none());
+
+        let mut data = Map::new();
+        data.insert("limit".to_string(), Value::from(2));
+        let mut req = Request::new_list_request("secret/");
+        req.mount_point = "secret/".to_string();
+        req.data = Some(data);
+        let resp = kv.handle_request(&mut req).await.unwrap().unwrap();
+        assert_eq!(keys_of(&resp), vec!["key-000", "key-001"]);
+        assert!(resp.data.unwrap().get("next_cursor").is_some());
+    }
+
+    /// Records every key read so tests can assert what was touched
+    struct RecordingStorage {
+        inner: InMemoryBackend,
+        reads: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl StorageBackend for RecordingStorage {
+        async fn get(&self, key: &str) -> VaultResult<Option<Vec<u8>>> {
+            self.reads.lock().unwrap().push(key.to_string());
+            self.inner.get(key).await
+        }
+
+        async fn put(&self, key: &str, value: &[u8]) -> VaultResult<()> {
+            self.inner.put(key, value).await
+        }
+
+        async fn delete(&self, key: &str) -> VaultResult<()> {
+            self.inner.delete(key).await
+        }
+
+        async fn list(&self, prefix: &str) -> VaultResult<Vec<String>> {
+            self.inner.list(prefix).await
+        }
+    }
+
+    fn metadata_request(key: &str) -> Request {
+        let mut req = Request::new_read_request(format!("secret/metadata/{}", key));
+        req.mount_point = "secret/".to_string();
+        req
+    }
+
+    #[tokio::test]
+    async fn test_metadata_read_skips_data() {
+        let storage = Arc::new(RecordingStorage {
+            inner: InMemoryBackend::new(),
+            reads: Default::default(),
+        });
+        let kv = KvBackend::new(storage.clone(), "secret".to_string());
+        let mut data = Map::new();
+        data.insert("password".to_string(), Value::from("hunter2"));
+        kv.write_secret("db", data.clone()).await.unwrap();
+        kv.write_secret("db", data).await.unwrap();
+        kv.delete_secret("db").await.unwrap();
+        storage.reads.lock().unwrap().clear();
+
+        let resp = kv.handle_request(&mut metadata_request("db")).await.unwrap().unwrap();
+        let meta = resp.data.unwrap();
+
+        assert!(storage.reads.lock().unwrap().iter().all(|k| !k.contains("/data/")));
+        assert!(meta.get("data").is_none());
+        assert_eq!(meta["current_version"], 2);
+        assert_eq!(meta["deleted"], true);
+        let versions = meta["versions"].as_object().unwrap();
+        assert_eq!(versions.len(), 2);
+        assert!(versions["1"]["deletion_time"].is_null());
+        assert!(versions["2"]["deletion_time"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_metadata_of_missing_secret_is_none() {
+        let kv = backend_with_keys(0).await;
+        assert!(kv.handle_request(&mut metadata_request("nope")).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_custom_metadata_survives_writes() {
+        let kv = backend_with_keys(0).await;
+        kv.write_secret("db", Map::new()).await.unwrap();
+
+        let mut custom = Map::new();
+        custom.insert("owner".to_string(), Value::from("team-a"));
+        let mut body = Map::new();
+        body.insert("custom_metadata".to_string(), Value::Object(custom));
+        kv.write_metadata("db", body).await.unwrap();
+        kv.write_secret("db", Map::new()).await.unwrap();
+
+        let meta = kv.load_metadata("db").await.unwrap().unwrap();
+        assert_eq!(meta.current_version, 2);
+        assert_eq!(meta.custom_metadata["owner"], "team-a");
+    }
+
+    #[tokio::test]
+    async fn test_legacy_metadata_still_loads() {
+        let kv = backend_with_keys(0).await;
+        let legacy = serde_json::json!({
+            "current_version": 3,
+            "created_time": "2024-01-01T00:00:00+00:00",
+        });
+        kv.storage.put(&kv.metadata_path("old"), &serde_json::to_vec(&legacy).unwrap()).await.unwrap();
+
+        kv.write_secret("old", Map::new()).await.unwrap();
+        let meta = kv.load_metadata("old").await.unwrap().unwrap();
+        assert_eq!(meta.current_version, 4);
+        assert_eq!(meta.versions.keys().copied().collect::<Vec<_>>(), vec![4]);
+    }
+
+    #[tokio::test]
+    async fn test_cache_hints_follow_prefixes() {
+        let config = serde_json::json!({ "cache_ttls": { "config/": 60, "config/flags/": 5 } });
+        let rules = KvBackend::parse_cache_ttls(config.as_object().unwrap()).unwrap();
+        let kv = backend_with_keys(0).await.with_cache_ttls(rules);
+        for key in ["config/app", "config/flags/beta", "tokens/one-time"] {
+            kv.write_secret(key, Map::new()).await.unwrap();
+        }
+
+        for (key, expected) in [
+            ("config/app", CacheHint::MaxAge(60)),
+            ("config/flags/beta", CacheHint::MaxAge(5)),
+            ("tokens/one-time", CacheHint::NoStore),
+        ] {
+            assert_eq!(kv.read_secret(key).await.unwrap().unwrap().cache, expected);
+        }
+
+        let bad = serde_json::json!({ "cache_ttls": { "config/": "soon" } });
+        assert!(KvBackend::parse_cache_ttls(bad.as_object().unwrap()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_errors_name_the_path() {
+        let kv = backend_with_keys(0).await;
+        kv.storage.put(&kv.storage_path("broken"), b"not json").await.unwrap();
+
+        let err = kv.read_secret("broken").await.unwrap_err();
+        assert!(err.to_string().starts_with("decode secret/data/broken: "));
+        assert!(matches!(err.root_cause(), VaultError::Serialization(_)));
+    }
+
+    fn subkeys_request(key: &str, depth: Option<u64>) -> Request {
+        let mut req = Request::new_read_request(format!("secret/subkeys/{}", key));
+        req.mount_point = "secret/".to_string();
+        req.data = depth.map(|depth| serde_json::json!({ "depth": depth }).as_object().cloned().unwrap());
+        req
+    }
+
+    #[tokio::test]
+    async fn test_subkeys_hide_values() {
+        let kv = backend_with_keys(0).await;
+        let secret = serde_json::json!({
+            "username": "app",
+            "tls": { "cert": "-----BEGIN", "key": { "pem": "secret", "passphrase": "hunter2" } },
+        });
+        kv.write_secret("db", secret.as_object().cloned().unwrap()).await.unwrap();
+
+        let resp = kv.handle_request(&mut subkeys_request("db", None)).await.unwrap().unwrap();
+        let data = resp.data.unwrap();
+        assert_eq!(data["version"], 1);
+        assert_eq!(data["subkeys"], serde_json::json!({
+            "username": null,
+            "tls": { "cert": null, "key": { "pem": null, "passphrase": null } },
+        }));
+
+        let resp = kv.handle_request(&mut subkeys_request("db", Some(2))).await.unwrap().unwrap();
+        assert_eq!(resp.data.unwrap()["subkeys"], serde_json::json!({
+            "username": null,
+            "tls": { "cert": null, "key": null },
+        }));
+
+        kv.delete_secret("db").await.unwrap();
+        assert!(kv.handle_request(&mut subkeys_request("db", None)).await.unwrap().is_none());
+        assert!(kv.handle_request(&mut subkeys_request("missing", None)).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_paginated_list_rejects_bad_input() {
+        let kv = backend_with_keys(1).await;
+        assert!(kv.list_secrets_page("", None, 0).await.is_err());
+        assert!(kv.list_secrets_page("", None, MAX_LIST_PAGE_SIZE + 1).await.is_err());
+        assert!(kv.list_secrets_page("", Some("%%%"), 10).await.is_err());
+    }
+
+    struct TestWrapper;
+
+    impl SealWrapper for TestWrapper {
+        fn seal_wrap(&self, path: &str, plaintext: &[u8]) -> VaultResult<Vec<u8>> {
+            crate::storage::seal_wrap::wrap(&[7; 32], path, plaintext)
+        }
+
+        fn seal_unwrap(&self, path: &str, wrapped: &[u8]) -> VaultResult<Vec<u8>> {
+            crate::storage::seal_wrap::unwrap(&[7; 32], path, wrapped)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_seal_wrap_only_applies_to_configured_prefixes() {
+        let storage = Arc::new(InMemoryBackend::new());
+        let config = serde_json::json!({ "seal_wrap": ["hsm/"] });
+        let prefixes = KvBackend::parse_seal_wrap(config.as_object().unwrap()).unwrap();
+        let kv = KvBackend::new(storage.clone(), "secret".to_string()).with_seal_wrap(Arc::new(TestWrapper), prefixes);
+
+        let secret = serde_json::json!({ "pin": "1234" }).as_object().cloned().unwrap();
+        kv.write_secret("hsm/root", secret.clone()).await.unwrap();
+        kv.write_secret("app/db", secret.clone()).await.unwrap();
+
+        let raw: Map<String, Value> =
+            serde_json::from_slice(&storage.get("secret/data/hsm/root").await.unwrap().unwrap()).unwrap();
+        assert!(raw.contains_key(SEAL_WRAPPED_FIELD));
+        assert_eq!(raw["version"], 1);
+        assert!(!String::from_utf8_lossy(&storage.get("secret/data/hsm/root").await.unwrap().unwrap()).contains("1234"));
+        assert!(String::from_utf8_lossy(&storage.get("secret/data/app/db").await.unwrap().unwrap()).contains("1234"));
+
+        for key in ["hsm/root", "app/db"] {
+            let data = kv.read_secret(key).await.unwrap().unwrap().data.unwrap();
+            assert_eq!(data["data"], Value::Object(secret.clone()));
+        }
+
+        // Wrapped data cannot be read without the seal
+        let unsealed = KvBackend::new(storage, "secret".to_string());
+        assert!(matches!(unsealed.read_secret("hsm/root").await, Err(VaultError::Config(_))));
+        assert!(unsealed.read_secret("app/db").await.unwrap().is_some());
+    }
+
+    #[test]
+    fn test_parse_seal_wrap() {
+        let parse = |config: Value| KvBackend::parse_seal_wrap(config.as_object().unwrap());
+        assert_eq!(parse(serde_json::json!({})).unwrap(), Vec::<String>::new());
+        assert_eq!(parse(serde_json::json!({ "seal_wrap": true })).unwrap(), vec![String::new()]);
+        assert!(parse(serde_json::json!({ "seal_wrap": "hsm/" })).is_err());
+        assert!(parse(serde_json::json!({ "seal_wrap": [1] })).is_err());
+    }
+
+    fn read_request(key: &str, identity: &str) -> Request {
+        let mut req = Request::new_read_request(format!("secret/{}", key));
+        req.mount_point = "secret/".to_string();
+        req.context = Some(RequestContext {
+            identity: identity.to_string(),
+            request_id: format!("req-{}", identity),
+            ..Default::default()
+        });
+        req
+    }
+
+    /// The recorded last read of `key`, once the background write lands
+    async fn last_read_of(kv: &KvBackend, key: &str) -> Option<Value> {
+        for _ in 0..100 {
+            let meta = kv.handle_request(&mut metadata_request(key)).await.unwrap().unwrap().data.unwrap();
+            if let Some(last_read) = meta.get("last_read") {
+                return Some(last_read.clone());
+            }
+            tokio::task::yield_now().await;
+        }
+        None
+    }
+
+    #[tokio::test]
+    async fn test_reads_are_tracked_only_when_enabled() {
+        let untracked = backend_with_keys(1).await;
+        untracked.handle_request(&mut read_request("key-000", "alice")).await.unwrap().unwrap();
+        assert!(last_read_of(&untracked, "key-000").await.is_none());
+
+        let kv = backend_with_keys(1).await.with_read_tracking(true);
+        kv.handle_request(&mut read_request("key-000", "alice")).await.unwrap().unwrap();
+        let last_read = last_read_of(&kv, "key-000").await.expect("read was not recorded");
+        assert_eq!(last_read["identity"], "alice");
+        assert_eq!(last_read["request_id"], "req-alice");
+        assert!(last_read["time"].is_string());
+
+        // Recording never touches the version history
+        let meta = kv.handle_request(&mut metadata_request("key-000")).await.unwrap().unwrap().data.unwrap();
+        assert_eq!(meta["current_version"], 1);
+
+        // Misses are not reads of anything
+        assert!(kv.handle_request(&mut read_request("missing", "bob")).await.unwrap().is_none());
+        assert!(kv.load_last_read("missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delete_removes_the_last_read() {
+        let kv = backend_with_keys(1).await.with_read_tracking(true);
+        kv.handle_request(&mut read_request("key-000", "alice")).await.unwrap().unwrap();
+        assert!(last_read_of(&kv, "key-000").await.is_some());
+
+        kv.delete_secret("key-000").await.unwrap();
+        assert!(kv.load_last_read("key-000").await.unwrap().is_none());
+        assert!(kv.storage.get("secret/last_read/key-000").await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_track_reads() {
+        let parse = |config: Value| KvBackend::parse_track_reads(config.as_object().unwrap());
+        assert!(!parse(serde_json::json!({})).unwrap());
+        assert!(parse(serde_json::json!({ "track_reads": true })).unwrap());
+        assert!(parse(serde_json::json!({ "track_reads": "yes" })).is_err());
+    }
+}
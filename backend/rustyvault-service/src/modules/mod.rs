@@ -1,7 +1,9 @@
 //! Vault modules (secrets engines, auth methods, etc.)
 
 pub mod kv;
+pub mod database;
 pub mod pki;
+pub mod transit;
 pub mod auth;
 pub mod policy;
 pub mod realm;
@@ -5,10 +5,41 @@
 //! - Exact path matching
 //! - Prefix matching (paths ending with *)
 //! - Segment wildcard matching (paths containing +)
+//! - Glob matching (paths containing ** or a non-trailing *)
+//!
+//! # Path grammar
+//!
+//! A rule path is split on `/` into segments:
+//! - `secret/data` matches that path only (exact)
+//! - `secret/*`, a single `*` at the very end, matches anything starting
+//!   with `secret/`, at any depth (prefix); `secret/app-*` works the same way
+//! - `+` as a segment matches any one segment: `secret/+/config`
+//! - A path with `**` as a segment, or a `*` anywhere but the very end, is a
+//!   glob. `**` matches zero or more whole segments, so `secret/**` matches
+//!   `secret` and everything under it and `secret/**/config` matches
+//!   `secret/config` and `secret/a/b/config`. Elsewhere `*` and `+` match
+//!   within one segment only: `secret/*/config` matches `secret/a/config`
+//!   but not `secret/a/b/config`, and `secret/app-*/config` matches
+//!   `secret/app-1/config`.
+//!
+//! # Precedence
+//!
+//! The first kind with a matching rule decides, in this order: exact,
+//! longest prefix, most specific segment wildcard, most specific glob.
+//! A glob's specificity counts 10 per literal segment, 5 per segment mixing
+//! literal text with `*`, 1 per `*` or `+` segment and nothing for `**`; of
+//! equally specific globs the first one added wins. Rules for the same
+//! pattern in several policies are merged before matching.
+//!
+//! Exact and prefix rules live in radix tries. Segment wildcard and glob
+//! rules are bucketed by the literal segments they start with, so a request
+//! only evaluates the rules anchored to one of its own leading segments.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use radix_trie::Trie;
+use radix_trie::{Trie, TrieCommon};
+use serde::Serialize;
 
 use super::policy::{Capability, Permissions, Policy, PolicyPathRules, PolicyType};
 use crate::errors::{VaultError, VaultResult};
@@ -29,6 +60,54 @@ pub struct ACLResults {
     pub granting_policies: Vec<String>,
 }
 
+/// How a policy path rule matched a request path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    Exact,
+    Prefix,
+    SegmentWildcard,
+    Glob,
+}
+
+/// A rule of one policy whose path pattern matches the request path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleMatch {
+    pub policy: String,
+    /// The pattern as written in the policy
+    pub path: String,
+    pub kind: MatchKind,
+    pub capabilities: Vec<String>,
+    /// Whether this is the rule the ACL applied
+    pub deciding: bool,
+}
+
+/// A dry run of an ACL check, detailing how the decision was reached
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ACLExplanation {
+    pub path: String,
+    pub operation: String,
+    /// Policies the ACL was built from
+    pub policies: Vec<String>,
+    /// Every rule matching the path, whether or not it was applied
+    pub matched_rules: Vec<RuleMatch>,
+    /// Pattern of the applied rule; `None` when nothing matched
+    pub deciding_rule: Option<String>,
+    /// Capabilities of the applied rule, merged across policies
+    pub capabilities: Vec<String>,
+    pub allowed: bool,
+    pub is_root: bool,
+    /// Human-readable summary of the decision
+    pub reason: String,
+}
+
+/// The rule the ACL applies to a path
+struct AppliedRule {
+    path: String,
+    kind: MatchKind,
+    permissions: Permissions,
+}
+
 /// Access Control List for evaluating policy permissions
 #[derive(Debug, Clone, Default)]
 pub struct ACL {
@@ -38,6 +117,12 @@ pub struct ACL {
     prefix_rules: Trie<String, Permissions>,
     /// Rules for paths with segment wildcards (+)
     segment_wildcard_paths: Vec<(String, Permissions, bool)>, // (path, perms, is_prefix)
+    /// Rules for glob paths (** or a non-trailing *)
+    glob_rules: Vec<(String, Permissions)>,
+    /// Positions in `segment_wildcard_paths` by leading literal segments
+    wildcard_index: AnchorIndex,
+    /// Positions in `glob_rules` by leading literal segments
+    glob_index: AnchorIndex,
     /// Whether this is the root policy
     root: bool,
 }
@@ -87,7 +172,11 @@ impl ACL {
 
     /// Get permissions for a path rule
     fn get_permissions(&self, pr: &PolicyPathRules) -> VaultResult<Option<Permissions>> {
-        if pr.has_segment_wildcards {
+        if pr.is_glob {
+            if let Some((_, perms)) = self.glob_rules.iter().find(|(path, _)| path == &pr.path) {
+                return Ok(Some(perms.clone()));
+            }
+        } else if pr.has_segment_wildcards {
             // Look up in segment wildcard paths
             for (path, perms, is_prefix) in &self.segment_wildcard_paths {
                 if path == &pr.path && *is_prefix == pr.is_prefix {
@@ -107,7 +196,16 @@ impl ACL {
 
     /// Insert permissions for a path rule
     fn insert_permissions(&mut self, pr: &PolicyPathRules, perms: Permissions) -> VaultResult<()> {
-        if pr.has_segment_wildcards {
+        if pr.is_glob {
+            match self.glob_rules.iter_mut().find(|(path, _)| path == &pr.path) {
+                Some((_, existing_perms)) => *existing_perms = perms,
+                None => {
+                    let anchor = pr.path.split('/').take_while(|s| !s.contains('*') && *s != "+");
+                    self.glob_index.insert(anchor, self.glob_rules.len());
+                    self.glob_rules.push((pr.path.clone(), perms));
+                }
+            }
+        } else if pr.has_segment_wildcards {
             // Update or insert segment wildcard path
             let mut found = false;
             for (path, existing_perms, is_prefix) in &mut self.segment_wildcard_paths {
@@ -118,6 +216,11 @@ impl ACL {
                 }
             }
             if !found {
+                // With a prefix match the last segment only has to start with the pattern's
+                let parts: Vec<&str> = pr.path.split('/').collect();
+                let literal = if pr.is_prefix { &parts[..parts.len() - 1] } else { &parts[..] };
+                let anchor = literal.iter().copied().take_while(|s| *s != "+");
+                self.wildcard_index.insert(anchor, self.segment_wildcard_paths.len());
                 self.segment_wildcard_paths
                     .push((pr.path.clone(), perms, pr.is_prefix));
             }
@@ -143,86 +246,118 @@ impl ACL {
             });
         }
 
+        match self.applied_rule(req) {
+            Some(rule) => self.check_permissions(&rule.permissions, req, check_only),
+            // No match found, deny
+            None => Ok(ACLResults::default()),
+        }
+    }
+
+    /// [`Self::allow_operation`] scanning every wildcard and glob rule
+    ///
+    /// The reference the indexed lookup is tested and benchmarked against.
+    #[cfg(any(test, feature = "bench"))]
+    #[doc(hidden)]
+    pub fn allow_operation_unindexed(&self, req: &Request, check_only: bool) -> VaultResult<ACLResults> {
+        if self.root {
+            return self.allow_operation(req, check_only);
+        }
+        match self.applied_rule_with(req, false) {
+            Some(rule) => self.check_permissions(&rule.permissions, req, check_only),
+            None => Ok(ACLResults::default()),
+        }
+    }
+
+    /// Find the rule that governs a request
+    fn applied_rule(&self, req: &Request) -> Option<AppliedRule> {
+        self.applied_rule_with(req, true)
+    }
+
+    fn applied_rule_with(&self, req: &Request, indexed: bool) -> Option<AppliedRule> {
         let path = ensure_no_leading_slash(&req.path);
+        let applied = |path: &str, kind, permissions: &Permissions| AppliedRule {
+            path: path.to_string(),
+            kind,
+            permissions: permissions.clone(),
+        };
 
         // Try exact match first
         if let Some(perms) = self.exact_rules.get(&path) {
-            return self.check_permissions(perms, req, check_only);
+            return Some(applied(&path, MatchKind::Exact, perms));
         }
 
         // For list operations, also try without trailing slash
         if req.operation == Operation::List {
             let trimmed = path.trim_end_matches('/');
             if let Some(perms) = self.exact_rules.get(trimmed) {
-                return self.check_permissions(perms, req, check_only);
+                return Some(applied(trimmed, MatchKind::Exact, perms));
             }
         }
 
         // Try prefix match
-        if let Some(perms) = self.get_prefix_permissions(&path) {
-            return self.check_permissions(&perms, req, check_only);
+        if let Some((prefix, permissions)) = self.get_prefix_permissions(&path) {
+            return Some(AppliedRule { path: prefix, kind: MatchKind::Prefix, permissions });
         }
 
         // Try segment wildcard match
-        if let Some(perms) = self.get_wildcard_permissions(&path) {
-            return self.check_permissions(&perms, req, check_only);
+        let candidates = if indexed {
+            self.wildcard_index.candidates(&path)
+        } else {
+            (0..self.segment_wildcard_paths.len()).collect()
+        };
+        if let Some((wc_path, perms)) = self.get_wildcard_permissions(&path, candidates) {
+            return Some(applied(wc_path, MatchKind::SegmentWildcard, perms));
         }
 
-        // No match found, deny
-        Ok(ACLResults::default())
+        // Try glob match
+        let candidates = if indexed {
+            self.glob_index.candidates(&path)
+        } else {
+            (0..self.glob_rules.len()).collect()
+        };
+        self.get_glob_permissions(&path, candidates)
+            .map(|(glob, perms)| applied(glob, MatchKind::Glob, perms))
     }
 
     /// Get permissions from prefix rules
-    fn get_prefix_permissions(&self, path: &str) -> Option<Permissions> {
+    fn get_prefix_permissions(&self, path: &str) -> Option<(String, Permissions)> {
         // Find the longest matching prefix
-        self.prefix_rules.get_ancestor_value(path).cloned()
+        let ancestor = self.prefix_rules.get_ancestor(path)?;
+        Some((ancestor.key()?.clone(), ancestor.value()?.clone()))
     }
 
-    /// Get permissions from segment wildcard rules
-    fn get_wildcard_permissions(&self, path: &str) -> Option<Permissions> {
-        let path_parts: Vec<&str> = path.split('/').collect();
-        let mut best_match: Option<&Permissions> = None;
+    /// Get permissions from the `candidates` among segment wildcard rules
+    fn get_wildcard_permissions(&self, path: &str, candidates: Vec<usize>) -> Option<(&String, &Permissions)> {
+        let mut best_match = None;
         let mut best_specificity = 0;
 
-        for (wc_path, perms, is_prefix) in &self.segment_wildcard_paths {
-            let wc_parts: Vec<&str> = wc_path.split('/').collect();
-
-            // Check if this pattern matches
-            if !*is_prefix && wc_parts.len() != path_parts.len() {
-                continue;
+        for (wc_path, perms, is_prefix) in candidates.into_iter().map(|i| &self.segment_wildcard_paths[i]) {
+            if let Some(specificity) = wildcard_specificity(wc_path, *is_prefix, path) {
+                if specificity > best_specificity {
+                    best_specificity = specificity;
+                    best_match = Some((wc_path, perms));
+                }
             }
+        }
 
-            if wc_parts.len() > path_parts.len() {
-                continue;
-            }
+        best_match
+    }
 
-            let mut matches = true;
-            let mut specificity = 0;
-
-            for (i, wc_part) in wc_parts.iter().enumerate() {
-                if *wc_part == "+" {
-                    // Wildcard matches any segment
-                    specificity += 1;
-                } else if *wc_part == path_parts[i] {
-                    // Exact match
-                    specificity += 10;
-                } else if *is_prefix && i == wc_parts.len() - 1 && path_parts[i].starts_with(wc_part)
-                {
-                    // Prefix match on last segment
-                    specificity += 5;
-                } else {
-                    matches = false;
-                    break;
-                }
-            }
+    /// Get permissions from the most specific matching glob rule among `candidates`
+    fn get_glob_permissions(&self, path: &str, candidates: Vec<usize>) -> Option<(&String, &Permissions)> {
+        let mut best_match = None;
+        let mut best_specificity = None;
 
-            if matches && specificity > best_specificity {
-                best_specificity = specificity;
-                best_match = Some(perms);
+        for (glob, perms) in candidates.into_iter().map(|i| &self.glob_rules[i]) {
+            if let Some(specificity) = glob_specificity(glob, path) {
+                if best_specificity.is_none_or(|best| specificity > best) {
+                    best_specificity = Some(specificity);
+                    best_match = Some((glob, perms));
+                }
             }
         }
 
-        best_match.cloned()
+        best_match
     }
 
     /// Check if permissions allow the operation
@@ -282,6 +417,236 @@ impl ACL {
     }
 }
 
+/// Wildcard rules bucketed by the literal segments they start with
+///
+/// A rule anchored to `secret/app` can only match paths starting with those
+/// segments, so the rules worth evaluating for a path are the ones in the
+/// buckets of its leading segments, plus those starting with a wildcard.
+#[derive(Debug, Clone, Default)]
+struct AnchorIndex {
+    /// Joined leading literal segments to rule positions, ascending
+    buckets: HashMap<String, Vec<usize>>,
+    /// Most leading literal segments of any rule
+    max_depth: usize,
+}
+
+impl AnchorIndex {
+    fn insert<'a>(&mut self, anchor: impl Iterator<Item = &'a str>, position: usize) {
+        let anchor: Vec<&str> = anchor.collect();
+        self.max_depth = self.max_depth.max(anchor.len());
+        self.buckets.entry(anchor.join("/")).or_default().push(position);
+    }
+
+    /// Positions of the rules that may match `path`, in insertion order
+    ///
+    /// Keeping the order means ties between equally specific rules resolve
+    /// exactly as a scan of every rule would.
+    fn candidates(&self, path: &str) -> Vec<usize> {
+        let ends = path.match_indices('/').map(|(i, _)| i).chain(std::iter::once(path.len()));
+        let keys = std::iter::once("").chain(ends.take(self.max_depth).map(|end| &path[..end]));
+
+        let mut found = Vec::new();
+        for key in keys {
+            if let Some(positions) = self.buckets.get(key) {
+                found.extend_from_slice(positions);
+            }
+        }
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+}
+
+/// Dry-run a request against `policies`, reporting every matching rule and
+/// which one decided the outcome
+pub fn explain(policies: &[Arc<Policy>], req: &Request) -> VaultResult<ACLExplanation> {
+    let acl = ACL::new(policies)?;
+    let path = ensure_no_leading_slash(&req.path);
+    let mut explanation = ACLExplanation {
+        path: path.clone(),
+        operation: format!("{:?}", req.operation).to_lowercase(),
+        policies: policies.iter().map(|p| p.name.clone()).collect(),
+        matched_rules: Vec::new(),
+        deciding_rule: None,
+        capabilities: Vec::new(),
+        allowed: false,
+        is_root: false,
+        reason: String::new(),
+    };
+
+    if acl.root {
+        explanation.allowed = true;
+        explanation.is_root = true;
+        explanation.capabilities = vec![Capability::Root.to_string()];
+        explanation.reason = "the root policy allows everything".to_string();
+        return Ok(explanation);
+    }
+
+    let applied = acl.applied_rule(req);
+    for policy in policies {
+        for pr in &policy.paths {
+            let Some(kind) = rule_match_kind(pr, &path, req.operation) else {
+                continue;
+            };
+            let deciding = applied.as_ref().is_some_and(|rule| rule.kind == kind && rule.path == pr.path);
+            explanation.matched_rules.push(RuleMatch {
+                policy: policy.name.clone(),
+                path: display_pattern(pr),
+                kind,
+                capabilities: super::policy::to_capability_strings(pr.permissions.capabilities_bitmap),
+                deciding,
+            });
+        }
+    }
+
+    let Some(rule) = applied else {
+        explanation.reason = "no policy rule matches the path".to_string();
+        return Ok(explanation);
+    };
+    let pattern = match rule.kind {
+        MatchKind::Prefix => format!("{}*", rule.path),
+        _ => rule.path.clone(),
+    };
+    let denied = rule.permissions.capabilities_bitmap & Capability::Deny.to_bits() != 0;
+    explanation.allowed = acl.check_permissions(&rule.permissions, req, false)?.allowed;
+    explanation.capabilities = if denied {
+        vec![Capability::Deny.to_string()]
+    } else {
+        super::policy::to_capability_strings(rule.permissions.capabilities_bitmap)
+    };
+    explanation.reason = if denied {
+        format!("rule '{}' explicitly denies the path", pattern)
+    } else if explanation.allowed {
+        format!("rule '{}' grants {}", pattern, explanation.operation)
+    } else {
+        format!("rule '{}' does not grant {}", pattern, explanation.operation)
+    };
+    explanation.deciding_rule = Some(pattern);
+
+    Ok(explanation)
+}
+
+/// How a single rule matches `path`, using the same semantics as the ACL
+fn rule_match_kind(pr: &PolicyPathRules, path: &str, operation: Operation) -> Option<MatchKind> {
+    if pr.is_glob {
+        glob_specificity(&pr.path, path).map(|_| MatchKind::Glob)
+    } else if pr.has_segment_wildcards {
+        wildcard_specificity(&pr.path, pr.is_prefix, path).map(|_| MatchKind::SegmentWildcard)
+    } else if pr.is_prefix {
+        path.starts_with(&pr.path).then_some(MatchKind::Prefix)
+    } else if pr.path == path
+        || (operation == Operation::List && pr.path == path.trim_end_matches('/'))
+    {
+        Some(MatchKind::Exact)
+    } else {
+        None
+    }
+}
+
+/// The path pattern of a rule as it was written
+pub(crate) fn display_pattern(pr: &PolicyPathRules) -> String {
+    if pr.is_prefix && !pr.has_segment_wildcards && !pr.is_glob {
+        format!("{}*", pr.path)
+    } else {
+        pr.path.clone()
+    }
+}
+
+/// How specifically a segment wildcard pattern matches `path`, if it does
+fn wildcard_specificity(wc_path: &str, is_prefix: bool, path: &str) -> Option<u32> {
+    let path_parts: Vec<&str> = path.split('/').collect();
+    let wc_parts: Vec<&str> = wc_path.split('/').collect();
+
+    // Check if this pattern matches
+    if !is_prefix && wc_parts.len() != path_parts.len() {
+        return None;
+    }
+
+    if wc_parts.len() > path_parts.len() {
+        return None;
+    }
+
+    let mut specificity = 0;
+
+    for (i, wc_part) in wc_parts.iter().enumerate() {
+        if *wc_part == "+" {
+            // Wildcard matches any segment
+            specificity += 1;
+        } else if *wc_part == path_parts[i] {
+            // Exact match
+            specificity += 10;
+        } else if is_prefix && i == wc_parts.len() - 1 && path_parts[i].starts_with(wc_part) {
+            // Prefix match on last segment
+            specificity += 5;
+        } else {
+            return None;
+        }
+    }
+
+    Some(specificity)
+}
+
+/// How specifically a glob matches `path`, if it does
+fn glob_specificity(glob: &str, path: &str) -> Option<u32> {
+    let pattern: Vec<&str> = glob.split('/').collect();
+    let segments: Vec<&str> = path.split('/').collect();
+
+    // matches[j]: whether the pattern from the current segment on matches
+    // the path from segment j on, filled in from the last pattern segment
+    let mut matches = vec![false; segments.len() + 1];
+    matches[segments.len()] = true;
+    for part in pattern.iter().rev() {
+        let mut next = vec![false; segments.len() + 1];
+        for j in (0..=segments.len()).rev() {
+            next[j] = if *part == "**" {
+                matches[j] || (j < segments.len() && next[j + 1])
+            } else {
+                j < segments.len() && matches[j + 1] && segment_matches(part, segments[j])
+            };
+        }
+        matches = next;
+    }
+    if !matches[0] {
+        return None;
+    }
+
+    Some(
+        pattern
+            .iter()
+            .map(|part| match *part {
+                "**" => 0,
+                "*" | "+" => 1,
+                part if part.contains('*') => 5,
+                _ => 10,
+            })
+            .sum(),
+    )
+}
+
+/// Whether one glob segment matches one path segment; `*` never spans a `/`
+fn segment_matches(part: &str, segment: &str) -> bool {
+    if part == "+" {
+        return true;
+    }
+    let mut pieces = part.split('*');
+    let first = pieces.next().unwrap_or_default();
+    let Some(mut rest) = segment.strip_prefix(first) else {
+        return false;
+    };
+    let pieces: Vec<&str> = pieces.collect();
+    let Some((last, middle)) = pieces.split_last() else {
+        // No '*' at all
+        return rest.is_empty();
+    };
+    for piece in middle {
+        match rest.find(piece) {
+            Some(at) => rest = &rest[at + piece.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
 /// Remove leading slash from path
 fn ensure_no_leading_slash(path: &str) -> String {
     path.trim_start_matches('/').to_string()
@@ -452,5 +817,206 @@ mod tests {
         assert!(caps.contains(&"create".to_string()));
         assert!(!caps.contains(&"delete".to_string()));
     }
-}
 
+    #[test]
+    fn test_acl_glob_match() {
+        let policy = create_test_policy(
+            "test",
+            r#"{
+                "path": {
+                    "secret/**": { "capabilities": ["read"] },
+                    "secret/*/config": { "capabilities": ["update"] },
+                    "secret/**/audit": { "capabilities": ["deny"] },
+                    "secret/team-*/**": { "capabilities": ["list"] },
+                    "secret/public": { "capabilities": ["create"] }
+                }
+            }"#,
+        );
+        let acl = ACL::new(&[Arc::new(policy)]).unwrap();
+        let caps = |path: &str| acl.capabilities(path);
+
+        // `**` reaches any depth, including the base itself
+        assert_eq!(caps("secret"), vec!["read"]);
+        assert_eq!(caps("secret/a/b/c/d"), vec!["read"]);
+        // `*` spans exactly one segment, and beats the bare `**`
+        assert_eq!(caps("secret/app/config"), vec!["update"]);
+        assert_eq!(caps("secret/app/nested/config"), vec!["read"]);
+        // More literal segments win, at any depth
+        assert_eq!(caps("secret/audit"), vec!["deny"]);
+        assert_eq!(caps("secret/a/b/audit"), vec!["deny"]);
+        assert_eq!(caps("secret/team-x/a/b"), vec!["list"]);
+        assert_eq!(caps("secret/team-x/config"), vec!["update"]);
+        // Exact rules come first
+        assert_eq!(caps("secret/public"), vec!["create"]);
+        assert_eq!(caps("other/a"), vec!["deny"]);
+
+        let req = Request {
+            path: "secret/app/nested/config".to_string(),
+            operation: Operation::Read,
+            ..Default::default()
+        };
+        let glob_only = create_test_policy(
+            "glob",
+            r#"{"path": {"secret/**": {"capabilities": ["read"]}}}"#,
+        );
+        let explanation = explain(&[Arc::new(glob_only)], &req).unwrap();
+        assert!(explanation.allowed);
+        assert_eq!(explanation.deciding_rule.as_deref(), Some("secret/**"));
+        assert_eq!(explanation.matched_rules[0].kind, MatchKind::Glob);
+    }
+
+    #[test]
+    fn test_glob_prefix_rules_take_precedence() {
+        let policy = create_test_policy(
+            "test",
+            r#"{
+                "path": {
+                    "secret/*": { "capabilities": ["read"] },
+                    "secret/*/config": { "capabilities": ["deny"] }
+                }
+            }"#,
+        );
+        assert!(!policy.paths.iter().find(|p| p.path == "secret/").unwrap().is_glob);
+        let acl = ACL::new(&[Arc::new(policy)]).unwrap();
+        assert_eq!(acl.capabilities("secret/app/config"), vec!["read"]);
+        assert!(!segment_matches("app-*-x", "app-x"));
+        assert!(segment_matches("app-*-x", "app--x"));
+    }
+
+    #[test]
+    fn test_indexed_lookup_matches_full_scan() {
+        let patterns = [
+            "secret/+/config",
+            "secret/app/+",
+            "secret/+/+/deep",
+            "+/data/*",
+            "kv/**",
+            "kv/*/config",
+            "kv/**/audit",
+            "**/public",
+            "kv/team-*/**",
+            "secret/app/*",
+            "sys/mounts",
+        ];
+        let capabilities = ["read", "update", "deny", "list"];
+        let policies: Vec<Arc<Policy>> = (0..8)
+            .map(|p| {
+                let paths: Vec<String> = patterns
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| (i + p) % 3 != 0)
+                    .map(|(i, pattern)| {
+                        format!(r#""{}": {{"capabilities": ["{}"]}}"#, pattern, capabilities[(i * p) % 4])
+                    })
+                    .collect();
+                let json = format!(r#"{{"path": {{{}}}}}"#, paths.join(", "));
+                Arc::new(create_test_policy(&format!("p{}", p), &json))
+            })
+            .collect();
+        let acl = ACL::new(&policies).unwrap();
+
+        let paths = [
+            "",
+            "secret",
+            "secret/app",
+            "secret/app/config",
+            "secret/db/config",
+            "secret/a/b/deep",
+            "other/data/x",
+            "kv",
+            "kv/x/config",
+            "kv/x/y/audit",
+            "kv/team-1/z",
+            "a/b/public",
+            "public",
+            "sys/mounts",
+            "unrelated/path",
+        ];
+        for path in paths {
+            for operation in [Operation::Read, Operation::Write, Operation::List] {
+                let req = Request {
+                    path: path.to_string(),
+                    operation,
+                    ..Default::default()
+                };
+                let indexed = acl.allow_operation(&req, false).unwrap();
+                let scanned = acl.allow_operation_unindexed(&req, false).unwrap();
+                assert_eq!(
+                    (indexed.allowed, indexed.capabilities_bitmap),
+                    (scanned.allowed, scanned.capabilities_bitmap),
+                    "{} {:?}",
+                    path,
+                    operation
+                );
+                assert_eq!(
+                    acl.applied_rule(&req).map(|r| (r.path, r.kind)),
+                    acl.applied_rule_with(&req, false).map(|r| (r.path, r.kind))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_explain_reports_deciding_rule() {
+        let allow = create_test_policy(
+            "allow",
+            r#"{
+                "path": {
+                    "secret/*": { "capabilities": ["read", "list"] },
+                    "secret/+/config": { "capabilities": ["update"] }
+                }
+            }"#,
+        );
+        let deny = create_test_policy(
+            "deny",
+            r#"{
+                "path": {
+                    "secret/sensitive/*": { "capabilities": ["deny"] }
+                }
+            }"#,
+        );
+        let policies = [Arc::new(allow), Arc::new(deny)];
+
+        let req = Request {
+            path: "/secret/sensitive/key".to_string(),
+            operation: Operation::Read,
+            ..Default::default()
+        };
+        let explanation = explain(&policies, &req).unwrap();
+        assert!(!explanation.allowed);
+        assert_eq!(explanation.policies, vec!["allow", "deny"]);
+        assert_eq!(explanation.deciding_rule.as_deref(), Some("secret/sensitive/*"));
+        assert_eq!(explanation.capabilities, vec!["deny"]);
+        // The broader rule matched too, but the longest prefix decided
+        let matched: Vec<(&str, bool)> = explanation
+            .matched_rules
+            .iter()
+            .map(|m| (m.path.as_str(), m.deciding))
+            .collect();
+        assert_eq!(matched, vec![("secret/*", false), ("secret/sensitive/*", true)]);
+
+        let req = Request {
+            path: "secret/data/config".to_string(),
+            operation: Operation::Write,
+            ..Default::default()
+        };
+        let explanation = explain(&policies, &req).unwrap();
+        assert!(!explanation.allowed);
+        assert_eq!(explanation.deciding_rule.as_deref(), Some("secret/*"));
+        assert!(explanation.reason.contains("does not grant write"));
+        assert!(explanation
+            .matched_rules
+            .iter()
+            .any(|m| m.kind == MatchKind::SegmentWildcard && !m.deciding));
+
+        let req = Request {
+            path: "other/path".to_string(),
+            operation: Operation::Read,
+            ..Default::default()
+        };
+        let explanation = explain(&policies, &req).unwrap();
+        assert!(!explanation.allowed);
+        assert!(explanation.matched_rules.is_empty());
+        assert_eq!(explanation.deciding_rule, None);
+    }
+}
@@ -0,0 +1,299 @@
+//! Policy linter
+//!
+//! Flags grants that are valid but probably broader than intended, such as
+//! `sudo` on `secret/*`. Findings are warnings: writing a policy never fails
+//! because of them, but they are returned with the write and from
+//! `sys/policy/lint`, whose severities CI can gate on.
+//!
+//! Each check is a [`LintRule`]; [`PolicyLinter::default`] runs the built-in
+//! ones.
+
+use serde::Serialize;
+
+use super::acl::display_pattern;
+use super::policy::{Capability, Policy, PolicyPathRules};
+
+/// How risky a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+impl std::str::FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Severity::Low),
+            "medium" => Ok(Severity::Medium),
+            "high" => Ok(Severity::High),
+            other => Err(format!("unknown severity '{}' (expected low, medium or high)", other)),
+        }
+    }
+}
+
+/// One risky grant found in a policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintFinding {
+    /// Code of the rule that raised it, e.g. `sudo-on-broad-path`
+    pub rule: &'static str,
+    pub severity: Severity,
+    /// The path pattern as written in the policy
+    pub path: String,
+    pub message: String,
+}
+
+/// Outcome of linting one policy
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LintReport {
+    pub findings: Vec<LintFinding>,
+    /// Severity of the worst finding; `None` when there are none
+    pub highest_severity: Option<Severity>,
+}
+
+impl LintReport {
+    /// Whether nothing at or above `threshold` was found
+    pub fn passes(&self, threshold: Severity) -> bool {
+        self.highest_severity.is_none_or(|highest| highest < threshold)
+    }
+}
+
+/// A check run against every path rule of a policy
+pub trait LintRule: Send + Sync {
+    /// Stable identifier reported with findings
+    fn code(&self) -> &'static str;
+
+    /// Severity and message for a risky rule; `None` when it is fine
+    fn check(&self, rule: &PolicyPathRules) -> Option<(Severity, String)>;
+}
+
+/// Runs a set of lint rules over policies
+pub struct PolicyLinter {
+    rules: Vec<Box<dyn LintRule>>,
+}
+
+impl Default for PolicyLinter {
+    /// The built-in rules
+    fn default() -> Self {
+        PolicyLinter {
+            rules: vec![
+                Box::new(SudoOnBroadPath),
+                Box::new(WildcardWrite),
+                Box::new(SysGrant),
+            ],
+        }
+    }
+}
+
+impl PolicyLinter {
+    /// A linter without any rules
+    #[cfg(test)]
+    pub fn empty() -> Self {
+        PolicyLinter { rules: Vec::new() }
+    }
+
+    /// Also run `rule`
+    #[cfg(test)]
+    pub fn with_rule(mut self, rule: impl LintRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    pub fn lint(&self, policy: &Policy) -> LintReport {
+        let mut findings = Vec::new();
+        for path_rule in &policy.paths {
+            // Deny rules only ever narrow access
+            if path_rule.permissions.capabilities_bitmap & Capability::Deny.to_bits() != 0 {
+                continue;
+            }
+            for rule in &self.rules {
+                if let Some((severity, message)) = rule.check(path_rule) {
+                    findings.push(LintFinding {
+                        rule: rule.code(),
+                        severity,
+                        path: display_pattern(path_rule),
+                        message,
+                    });
+                }
+            }
+        }
+        // Policy paths come from a map, so sort for stable output
+        findings.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.path.cmp(&b.path)));
+
+        LintReport {
+            highest_severity: findings.first().map(|f| f.severity),
+            findings,
+        }
+    }
+}
+
+const WRITE_CAPABILITIES: [Capability; 4] = [
+    Capability::Create,
+    Capability::Update,
+    Capability::Delete,
+    Capability::Patch,
+];
+
+fn grants(rule: &PolicyPathRules, capability: Capability) -> bool {
+    rule.permissions.capabilities_bitmap & capability.to_bits() != 0
+}
+
+fn grants_write(rule: &PolicyPathRules) -> bool {
+    WRITE_CAPABILITIES.iter().any(|cap| grants(rule, *cap))
+}
+
+/// Literal segments a rule is anchored to, before any wildcard
+///
+/// An exact rule is anchored to its whole path; `secret/*` and
+/// `secret/+/config` are anchored to `secret` alone.
+fn anchor(rule: &PolicyPathRules) -> Vec<&str> {
+    let wildcarded = is_wildcarded(rule);
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in rule.path.split('/') {
+        if wildcarded && (segment.contains('*') || segment == "+") {
+            break;
+        }
+        segments.push(segment);
+    }
+    if rule.is_prefix && !rule.path.ends_with('/') {
+        // `secret/app-*`: the last segment is only partly literal
+        segments.pop();
+    }
+    segments.retain(|s| !s.is_empty());
+    segments
+}
+
+fn is_wildcarded(rule: &PolicyPathRules) -> bool {
+    rule.is_prefix || rule.has_segment_wildcards || rule.is_glob
+}
+
+/// `sudo` or `root` on a rule anchored to at most one segment
+struct SudoOnBroadPath;
+
+impl LintRule for SudoOnBroadPath {
+    fn code(&self) -> &'static str {
+        "sudo-on-broad-path"
+    }
+
+    fn check(&self, rule: &PolicyPathRules) -> Option<(Severity, String)> {
+        let privileged = grants(rule, Capability::Sudo) || grants(rule, Capability::Root);
+        if !privileged || !is_wildcarded(rule) || anchor(rule).len() > 1 {
+            return None;
+        }
+        Some((
+            Severity::High,
+            "grants sudo on a whole mount or more; scope it to the paths that need it".to_string(),
+        ))
+    }
+}
+
+/// Write capabilities on a rule with no literal anchor, i.e. every path
+struct WildcardWrite;
+
+impl LintRule for WildcardWrite {
+    fn code(&self) -> &'static str {
+        "wildcard-write"
+    }
+
+    fn check(&self, rule: &PolicyPathRules) -> Option<(Severity, String)> {
+        if !grants_write(rule) || !is_wildcarded(rule) || !anchor(rule).is_empty() {
+            return None;
+        }
+        Some((Severity::High, "grants write access to every path".to_string()))
+    }
+}
+
+/// Any grant covering more than one path under `sys/`
+struct SysGrant;
+
+impl LintRule for SysGrant {
+    fn code(&self) -> &'static str {
+        "sys-grant"
+    }
+
+    fn check(&self, rule: &PolicyPathRules) -> Option<(Severity, String)> {
+        if !is_wildcarded(rule) || anchor(rule) != ["sys"] {
+            return None;
+        }
+        if grants_write(rule) || grants(rule, Capability::Sudo) {
+            Some((
+                Severity::High,
+                "grants write access across sys/, which controls the whole vault".to_string(),
+            ))
+        } else {
+            Some((
+                Severity::Medium,
+                "grants access across sys/; list the sys paths needed instead".to_string(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(json: &str) -> LintReport {
+        PolicyLinter::default().lint(&Policy::from_json(json).unwrap())
+    }
+
+    fn codes(report: &LintReport) -> Vec<(&str, &str, Severity)> {
+        report
+            .findings
+            .iter()
+            .map(|f| (f.rule, f.path.as_str(), f.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_flags_broad_grants() {
+        let report = lint(
+            r#"{"path": {
+                "secret/*": {"capabilities": ["read", "sudo"]},
+                "*": {"capabilities": ["update"]},
+                "sys/*": {"capabilities": ["read"]},
+                "sys/**": {"capabilities": ["deny"]},
+                "secret/app/*": {"capabilities": ["sudo", "update"]},
+                "sys/mounts": {"capabilities": ["update", "sudo"]}
+            }}"#,
+        );
+        assert_eq!(
+            codes(&report),
+            vec![
+                ("wildcard-write", "*", Severity::High),
+                ("sudo-on-broad-path", "secret/*", Severity::High),
+                ("sys-grant", "sys/*", Severity::Medium),
+            ]
+        );
+        assert_eq!(report.highest_severity, Some(Severity::High));
+        assert!(!report.passes(Severity::High));
+        assert!(lint(r#"{"path": {"secret/app/*": {"capabilities": ["read"]}}}"#).passes(Severity::Low));
+    }
+
+    struct NoDelete;
+
+    impl LintRule for NoDelete {
+        fn code(&self) -> &'static str {
+            "no-delete"
+        }
+
+        fn check(&self, rule: &PolicyPathRules) -> Option<(Severity, String)> {
+            grants(rule, Capability::Delete).then(|| (Severity::Low, "deletes".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_custom_rules_and_thresholds() {
+        let policy = Policy::from_json(r#"{"path": {"secret/app/data": {"capabilities": ["delete"]}}}"#).unwrap();
+        assert!(PolicyLinter::default().lint(&policy).findings.is_empty());
+
+        let report = PolicyLinter::empty().with_rule(NoDelete).lint(&policy);
+        assert_eq!(codes(&report), vec![("no-delete", "secret/app/data", Severity::Low)]);
+        assert!(report.passes(Severity::Medium));
+        assert!(!report.passes(Severity::Low));
+        assert_eq!("HIGH".parse::<Severity>(), Ok(Severity::High));
+    }
+}
@@ -4,9 +4,11 @@
 //! It provides:
 //! - Policy structures for defining access rules
 //! - ACL evaluation engine
+//! - A linter for overly broad grants
 //! - Policy storage with PostgreSQL backend
 
 pub mod acl;
+pub mod lint;
 pub mod policy;
 pub mod policy_store;
 
@@ -261,6 +261,8 @@ pub struct PolicyPathRules {
     pub is_prefix: bool,
     /// Whether this path has segment wildcards (+)
     pub has_segment_wildcards: bool,
+    /// Whether this path is a glob (`**`, or `*` before the end)
+    pub is_glob: bool,
 }
 
 /// A policy containing rules for multiple paths
@@ -278,6 +280,95 @@ pub struct Policy {
     pub paths: Vec<PolicyPathRules>,
 }
 
+/// Bounds on policy documents accepted for writing
+///
+/// Checked by the policy store before a policy is parsed and stored, so a
+/// huge document is turned away without being deserialized. Policies
+/// already stored are loaded whatever their size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyLimits {
+    /// Size of the raw policy document in bytes
+    pub max_bytes: usize,
+    /// Number of path rules in one policy
+    pub max_paths: usize,
+    /// Values listed for one allowed or denied parameter
+    pub max_parameter_values: usize,
+    /// Nesting depth of one such value; a scalar is depth 1
+    pub max_value_depth: usize,
+}
+
+impl Default for PolicyLimits {
+    fn default() -> Self {
+        PolicyLimits {
+            max_bytes: 64 * 1024,
+            max_paths: 256,
+            max_parameter_values: 64,
+            max_value_depth: 4,
+        }
+    }
+}
+
+impl PolicyLimits {
+    /// Reject a raw document too large to parse
+    pub fn check_size(&self, raw: &str) -> VaultResult<()> {
+        if raw.len() > self.max_bytes {
+            return Err(VaultError::Validation(format!(
+                "policy is {} bytes, more than the maximum of {}",
+                raw.len(),
+                self.max_bytes
+            )));
+        }
+        Ok(())
+    }
+
+    /// Reject a parsed policy with too many rules or oversized parameter lists
+    pub fn check(&self, policy: &Policy) -> VaultResult<()> {
+        self.check_size(&policy.raw)?;
+        if policy.paths.len() > self.max_paths {
+            return Err(VaultError::Validation(format!(
+                "policy has {} path rules, more than the maximum of {}",
+                policy.paths.len(),
+                self.max_paths
+            )));
+        }
+        for rules in &policy.paths {
+            let permissions = &rules.permissions;
+            let parameters = permissions
+                .allowed_parameters
+                .iter()
+                .map(|p| ("allowed_parameters", p))
+                .chain(permissions.denied_parameters.iter().map(|p| ("denied_parameters", p)));
+            for (field, (name, values)) in parameters {
+                if values.len() > self.max_parameter_values {
+                    return Err(VaultError::Validation(format!(
+                        "{} '{}' on path '{}' lists {} values, more than the maximum of {}",
+                        field,
+                        name,
+                        rules.path,
+                        values.len(),
+                        self.max_parameter_values
+                    )));
+                }
+                if values.iter().any(|v| value_depth(v) > self.max_value_depth) {
+                    return Err(VaultError::Validation(format!(
+                        "{} '{}' on path '{}' nests values deeper than {} levels",
+                        field, name, rules.path, self.max_value_depth
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn value_depth(value: &Value) -> usize {
+    match value {
+        Value::Array(items) => 1 + items.iter().map(value_depth).max().unwrap_or(0),
+        Value::Object(fields) => 1 + fields.values().map(value_depth).max().unwrap_or(0),
+        _ => 1,
+    }
+}
+
 /// JSON format for policy path configuration
 #[derive(Debug, Clone, Deserialize)]
 pub struct PolicyPathConfig {
@@ -330,13 +421,17 @@ impl Policy {
             // Process path
             let mut processed_path = path.trim_start_matches('/').to_string();
 
-            // Check for segment wildcards
-            if processed_path.contains('+') {
+            // Check for globs; a lone trailing '*' stays a prefix match
+            let interior = processed_path.strip_suffix('*').unwrap_or(&processed_path);
+            if interior.contains('*') || processed_path.split('/').any(|s| s == "**") {
+                rules.is_glob = true;
+            } else if processed_path.contains('+') {
+                // Check for segment wildcards
                 rules.has_segment_wildcards = true;
             }
 
             // Check for prefix match
-            if processed_path.ends_with('*') && !rules.has_segment_wildcards {
+            if processed_path.ends_with('*') && !rules.has_segment_wildcards && !rules.is_glob {
                 processed_path = processed_path.trim_end_matches('*').to_string();
                 rules.is_prefix = true;
             }
@@ -479,6 +574,36 @@ mod tests {
         assert!(!secret_path.permissions.check_operation(&Operation::Write));
     }
 
+    #[test]
+    fn test_policy_limits_reject_oversized_policies() {
+        let limits = PolicyLimits {
+            max_bytes: 512,
+            max_paths: 2,
+            max_parameter_values: 3,
+            max_value_depth: 2,
+        };
+        let policy_with = |rules: &str| format!(r#"{{"name": "p", "path": {{{}}}}}"#, rules);
+
+        let fits = policy_with(
+            r#""a/*": {"capabilities": ["read"], "allowed_parameters": {"k": [1, [2, 3], "x"]}}"#,
+        );
+        limits.check(&Policy::from_json(&fits).unwrap()).unwrap();
+
+        let too_big = policy_with(&format!(r#""a": {{"capabilities": ["{}"]}}"#, "r".repeat(600)));
+        assert!(matches!(limits.check_size(&too_big), Err(VaultError::Validation(_))));
+
+        let too_many_paths = policy_with(
+            r#""a": {"capabilities": ["read"]}, "b": {"capabilities": ["read"]}, "c": {"capabilities": ["read"]}"#,
+        );
+        assert!(limits.check(&Policy::from_json(&too_many_paths).unwrap()).is_err());
+
+        let too_many_values = policy_with(r#""a": {"denied_parameters": {"k": [1, 2, 3, 4]}}"#);
+        assert!(limits.check(&Policy::from_json(&too_many_values).unwrap()).is_err());
+
+        let too_deep = policy_with(r#""a": {"allowed_parameters": {"k": [[[1]]]}}"#);
+        assert!(limits.check(&Policy::from_json(&too_deep).unwrap()).is_err());
+    }
+
     #[test]
     fn test_permissions_merge() {
         let mut p1 = Permissions {
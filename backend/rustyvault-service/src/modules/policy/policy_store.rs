@@ -1,18 +1,30 @@
 //! Policy Store for managing policies in PostgreSQL
 //!
 //! Provides CRUD operations for policies and maintains an in-memory cache
-//! for fast policy lookups.
+//! for fast policy lookups, plus the ACLs built from them per set of policy
+//! names, so requests reuse an indexed ACL instead of merging policies anew.
 
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 
+use lru::LruCache;
+
+use shared::domain::events::{DomainEvent, EventEnvelope};
+use shared::infrastructure::events::OutboxStore;
 use sqlx::PgPool;
 
-use super::acl::ACL;
+use super::acl::{self, ACLExplanation, ACL};
+use super::lint::{LintReport, PolicyLinter};
 use super::policy::{
-    Policy, PolicyEntry, DEFAULT_POLICY, IMMUTABLE_POLICIES,
+    Policy, PolicyEntry, PolicyLimits, DEFAULT_POLICY, IMMUTABLE_POLICIES,
 };
 use crate::errors::{VaultError, VaultResult};
+use crate::logical::Request;
+
+/// Distinct policy sets whose ACLs are kept
+const ACL_CACHE_ENTRIES: usize = 1024;
 
 /// Policy store for managing vault policies
 pub struct PolicyStore {
@@ -20,6 +32,15 @@ pub struct PolicyStore {
     pool: PgPool,
     /// In-memory cache of policies
     cache: RwLock<HashMap<String, Arc<Policy>>>,
+    /// ACLs by the policy names they were built from, in the order given;
+    /// emptied whenever a policy changes
+    acl_cache: Mutex<LruCache<Vec<String>, Arc<ACL>>>,
+    /// Bumped on every policy change, so an ACL built meanwhile is not cached
+    acl_generation: AtomicU64,
+    /// Bounds on policies written through the store
+    limits: PolicyLimits,
+    /// Checks run on written policies; findings are only warnings
+    linter: PolicyLinter,
 }
 
 impl PolicyStore {
@@ -28,9 +49,36 @@ impl PolicyStore {
         PolicyStore {
             pool,
             cache: RwLock::new(HashMap::new()),
+            acl_cache: Mutex::new(LruCache::new(
+                NonZeroUsize::new(ACL_CACHE_ENTRIES).expect("cache size is non-zero"),
+            )),
+            acl_generation: AtomicU64::new(0),
+            limits: PolicyLimits::default(),
+            linter: PolicyLinter::default(),
         }
     }
 
+    /// Use `limits` instead of the defaults for written policies
+    pub fn with_limits(mut self, limits: PolicyLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Risky grants in `policy`; they never stop it from being written
+    pub fn lint(&self, policy: &Policy) -> LintReport {
+        self.linter.lint(policy)
+    }
+
+    /// Parse a policy submitted for writing, within the store's limits
+    ///
+    /// The size is checked before the document is deserialized.
+    pub fn parse_policy(&self, raw: &str) -> VaultResult<Policy> {
+        self.limits.check_size(raw)?;
+        let policy = Policy::from_json(raw)?;
+        self.limits.check(&policy)?;
+        Ok(policy)
+    }
+
     /// Initialize the policy store with default policies
     pub async fn init(&self) -> VaultResult<()> {
         // Load default policy if it doesn't exist
@@ -60,11 +108,12 @@ impl PolicyStore {
 
         // Check if policy is immutable (except during init)
         if IMMUTABLE_POLICIES.contains(&name.as_str()) && name != "default" {
-            return Err(VaultError::Vault(format!(
+            return Err(VaultError::Validation(format!(
                 "cannot update {} policy",
                 name
             )));
         }
+        self.limits.check(policy)?;
 
         let mut policy = policy.clone();
         policy.name = name;
@@ -84,6 +133,10 @@ impl PolicyStore {
             policy.raw.clone()
         };
 
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| VaultError::Vault(format!("failed to save policy: {}", e)))?;
+
         // Upsert into database
         // Note: 'policy' column is for backwards compatibility with base migration
         sqlx::query(
@@ -103,19 +156,36 @@ impl PolicyStore {
         .bind(policy.policy_type.to_string())
         .bind(&raw_policy)
         .bind(&entry_json)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await
         .map_err(|e| VaultError::Vault(format!("failed to save policy: {}", e)))?;
 
+        Self::record_change(&mut tx, &policy.name).await?;
+        tx.commit()
+            .await
+            .map_err(|e| VaultError::Vault(format!("failed to save policy: {}", e)))?;
+
         // Update cache
         self.cache.write().unwrap().insert(
             policy.name.clone(),
             Arc::new(policy.clone()),
         );
+        self.invalidate_acls();
 
         Ok(())
     }
 
+    /// Record a `PolicyChanged` event in the same transaction as the change
+    async fn record_change(tx: &mut sqlx::PgConnection, name: &str) -> VaultResult<()> {
+        let envelope = EventEnvelope::new(DomainEvent::PolicyChanged {
+            policy_name: name.to_string(),
+            changed_by: None,
+        });
+        OutboxStore::append(tx, &envelope)
+            .await
+            .map_err(|e| VaultError::Vault(format!("failed to record policy change: {}", e)))
+    }
+
     /// Get a policy by name
     pub async fn get_policy(&self, name: &str) -> VaultResult<Option<Arc<Policy>>> {
         let name = self.sanitize_name(name);
@@ -183,27 +253,44 @@ impl PolicyStore {
 
         // Check if policy is immutable
         if IMMUTABLE_POLICIES.contains(&name.as_str()) {
-            return Err(VaultError::Vault(format!(
+            return Err(VaultError::Validation(format!(
                 "cannot delete {} policy",
                 name
             )));
         }
 
         // Delete from database
-        sqlx::query("DELETE FROM vault_policies WHERE name = $1")
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| VaultError::Vault(format!("failed to delete policy: {}", e)))?;
+        let deleted = sqlx::query("DELETE FROM vault_policies WHERE name = $1")
             .bind(&name)
-            .execute(&self.pool)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| VaultError::Vault(format!("failed to delete policy: {}", e)))?;
+        if deleted.rows_affected() > 0 {
+            Self::record_change(&mut tx, &name).await?;
+        }
+        tx.commit()
             .await
             .map_err(|e| VaultError::Vault(format!("failed to delete policy: {}", e)))?;
 
         // Remove from cache
         self.cache.write().unwrap().remove(&name);
+        self.invalidate_acls();
 
         Ok(())
     }
 
     /// Create an ACL from a list of policy names
-    pub async fn new_acl(&self, policy_names: &[String]) -> VaultResult<ACL> {
+    ///
+    /// Built once per list and reused until a policy changes.
+    pub async fn new_acl(&self, policy_names: &[String]) -> VaultResult<Arc<ACL>> {
+        if let Some(acl) = self.acl_cache.lock().unwrap().get(policy_names) {
+            return Ok(acl.clone());
+        }
+        let generation = self.acl_generation.load(Ordering::SeqCst);
+
         let mut policies: Vec<Arc<Policy>> = Vec::new();
 
         for name in policy_names {
@@ -212,7 +299,19 @@ impl PolicyStore {
             }
         }
 
-        ACL::new(&policies)
+        let acl = Arc::new(ACL::new(&policies)?);
+        let mut cache = self.acl_cache.lock().unwrap();
+        if self.acl_generation.load(Ordering::SeqCst) == generation {
+            cache.put(policy_names.to_vec(), acl.clone());
+        }
+        Ok(acl)
+    }
+
+    /// Drop every cached ACL after a policy change
+    fn invalidate_acls(&self) {
+        let mut cache = self.acl_cache.lock().unwrap();
+        self.acl_generation.fetch_add(1, Ordering::SeqCst);
+        cache.clear();
     }
 
     /// Check if a token with the given policies can perform an operation
@@ -225,14 +324,45 @@ impl PolicyStore {
         Ok(acl.capabilities(path))
     }
 
+    /// Dry-run an operation for a token with the given policies
+    ///
+    /// Also returns the names that do not resolve to a policy, since those are
+    /// silently skipped when building the ACL.
+    pub async fn explain(
+        &self,
+        policy_names: &[String],
+        req: &Request,
+    ) -> VaultResult<(ACLExplanation, Vec<String>)> {
+        let mut policies: Vec<Arc<Policy>> = Vec::new();
+        let mut missing = Vec::new();
+
+        for name in policy_names {
+            match self.get_policy(name).await? {
+                Some(policy) => policies.push(policy),
+                None => missing.push(name.clone()),
+            }
+        }
+
+        Ok((acl::explain(&policies, req)?, missing))
+    }
+
     /// Sanitize a policy name
     fn sanitize_name(&self, name: &str) -> String {
         name.to_lowercase().trim().to_string()
     }
 
-    /// Clear the policy cache (useful for testing)
+    /// Seed the cache with `policy`, for tests without a database
+    #[cfg(test)]
+    pub(crate) fn cache_policy(&self, policy: Policy) {
+        let name = self.sanitize_name(&policy.name);
+        self.cache.write().unwrap().insert(name, Arc::new(policy));
+    }
+
+    /// Clear the policy cache
+    #[cfg(test)]
     pub fn clear_cache(&self) {
         self.cache.write().unwrap().clear();
+        self.invalidate_acls();
     }
 }
 
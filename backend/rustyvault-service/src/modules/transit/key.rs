@@ -0,0 +1,389 @@
+//! Named transit keys
+//!
+//! A key is a ring of versions. Encryption always uses the latest version;
+//! decryption accepts any version from `min_decryption_version` on, so data
+//! encrypted before a rotation stays readable until the operator retires
+//! the old versions.
+//!
+//! Convergent keys derive the nonce from the plaintext and a caller-supplied
+//! context with HKDF, so equal plaintexts under the same key version and
+//! context encrypt to equal ciphertexts. That allows deduplicating encrypted
+//! blobs but reveals which ones are equal, so it is opt-in per key.
+//!
+//! Signing keys follow the same versioning: signatures carry the version
+//! that made them, and verification accepts any version from
+//! `min_decryption_version` on. HMACs work the same way with a separate
+//! HMAC key per version.
+
+use std::collections::BTreeMap;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use ring::hkdf;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use shared::infrastructure::encryption::aead;
+use crate::errors::{VaultError, VaultResult};
+use super::hmac::{HmacAlgorithm, HMAC_KEY_LEN};
+use super::key_type::KeyType;
+
+/// Prefix of every transit ciphertext and signature, followed by `v{version}:`
+pub const CIPHERTEXT_PREFIX: &str = "vault:v";
+
+/// HKDF info label separating convergent nonces from other derivations
+const CONVERGENT_NONCE_INFO: &[u8] = b"transit convergent nonce";
+
+/// Warning returned when convergent encryption is turned off
+pub const CONVERGENCE_NOT_RETROACTIVE: &str = "disabling convergent encryption is not retroactive: \
+    ciphertexts already produced stay deterministic until they are rewrapped";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyVersion {
+    /// Base64 key material; only ever stored behind the barrier
+    pub key: String,
+    /// Base64 public key of a signing key version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    /// Base64 HMAC key, separate from the encryption or signing material
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hmac_key: Option<String>,
+    pub created_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransitKey {
+    pub key_type: KeyType,
+    pub latest_version: u32,
+    /// Oldest version still allowed to decrypt
+    pub min_decryption_version: u32,
+    /// Whether the key may be deleted; off so a stray DELETE cannot
+    /// destroy every ciphertext made with it
+    #[serde(default)]
+    pub deletion_allowed: bool,
+    /// Whether encryption derives the nonce from plaintext and context
+    ///
+    /// Decryption reads the nonce from the ciphertext, so it works the same
+    /// either way. Turning this off only affects new encryptions; existing
+    /// convergent ciphertexts keep leaking equality until rewrapped.
+    #[serde(default)]
+    pub convergent_encryption: bool,
+    pub versions: BTreeMap<u32, KeyVersion>,
+}
+
+impl TransitKey {
+    pub fn generate(key_type: KeyType, convergent_encryption: bool) -> VaultResult<Self> {
+        if convergent_encryption && key_type.aead().is_none() {
+            return Err(key_type.unsupported("convergent encryption"));
+        }
+        let mut key = Self {
+            key_type,
+            latest_version: 0,
+            min_decryption_version: 1,
+            deletion_allowed: false,
+            convergent_encryption,
+            versions: BTreeMap::new(),
+        };
+        key.rotate()?;
+        Ok(key)
+    }
+
+    /// Add a new latest version
+    pub fn rotate(&mut self) -> VaultResult<()> {
+        let material = self.key_type.generate()?;
+        let mut hmac_key = [0u8; HMAC_KEY_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut hmac_key);
+        self.latest_version += 1;
+        self.versions.insert(
+            self.latest_version,
+            KeyVersion {
+                key: STANDARD.encode(material.private),
+                public_key: material.public.map(|public| STANDARD.encode(public)),
+                hmac_key: Some(STANDARD.encode(hmac_key)),
+                created_time: Utc::now(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Apply a `keys/{name}/config` write, returning warnings for the caller
+    pub fn configure(&mut self, data: &Map<String, Value>) -> VaultResult<Vec<String>> {
+        let mut warnings = Vec::new();
+        if let Some(min) = data.get("min_decryption_version") {
+            let min = min.as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .filter(|v| (1..=self.latest_version).contains(v))
+                .ok_or_else(|| VaultError::Validation(format!(
+                    "min_decryption_version must be between 1 and {}", self.latest_version
+                )))?;
+            self.min_decryption_version = min;
+        }
+        if let Some(allowed) = data.get("deletion_allowed") {
+            self.deletion_allowed = allowed.as_bool()
+                .ok_or_else(|| VaultError::Validation("deletion_allowed must be a boolean".to_string()))?;
+        }
+        if let Some(convergent) = data.get("convergent_encryption") {
+            let convergent = convergent.as_bool()
+                .ok_or_else(|| VaultError::Validation("convergent_encryption must be a boolean".to_string()))?;
+            if convergent && self.key_type.aead().is_none() {
+                return Err(self.key_type.unsupported("convergent encryption"));
+            }
+            if self.convergent_encryption && !convergent {
+                warnings.push(CONVERGENCE_NOT_RETROACTIVE.to_string());
+            }
+            self.convergent_encryption = convergent;
+        }
+        Ok(warnings)
+    }
+
+    /// Encrypt with the latest version into `vault:v{N}:{base64}`
+    ///
+    /// Convergent keys require `context`; other keys ignore it.
+    pub fn encrypt(&self, plaintext: &[u8], context: Option<&[u8]>) -> VaultResult<String> {
+        let algorithm = self.key_type.aead().ok_or_else(|| self.key_type.unsupported("encryption"))?;
+        let key = self.material(self.latest_version)?;
+        let blob = if self.convergent_encryption {
+            let context = context.ok_or_else(|| {
+                VaultError::Validation("context is required for convergent encryption".to_string())
+            })?;
+            let nonce = convergent_nonce(&key, plaintext, context)?;
+            algorithm.seal_with_nonce(&key, &nonce, plaintext)?
+        } else {
+            algorithm.seal(&key, plaintext)?
+        };
+        Ok(self.versioned(self.latest_version, &blob))
+    }
+
+    /// Decrypt a ciphertext made by any version still allowed to decrypt
+    pub fn decrypt(&self, ciphertext: &str) -> VaultResult<Vec<u8>> {
+        if self.key_type.aead().is_none() {
+            return Err(self.key_type.unsupported("decryption"));
+        }
+        let (version, blob) = parse_versioned(ciphertext, "ciphertext")?;
+        self.check_version(version)?;
+        let key = self.material(version)?;
+        // The blob names its algorithm, so versions made before an algorithm
+        // change still open
+        aead::open(&key, &blob)
+            .map_err(|_| VaultError::Validation("ciphertext could not be decrypted".to_string()))
+    }
+
+    /// Sign `input` with the latest version into `vault:v{N}:{base64}`
+    pub fn sign(&self, input: &[u8]) -> VaultResult<String> {
+        let key = self.material(self.latest_version)?;
+        let signature = self.key_type.sign(&key, input)?;
+        Ok(self.versioned(self.latest_version, &signature))
+    }
+
+    /// Check a signature made by any version still allowed to decrypt
+    ///
+    /// A well-formed signature that does not match is `Ok(false)`.
+    pub fn verify(&self, input: &[u8], signature: &str) -> VaultResult<bool> {
+        if !self.key_type.supports_signing() {
+            return Err(self.key_type.unsupported("verification"));
+        }
+        let (version, signature) = parse_versioned(signature, "signature")?;
+        self.check_version(version)?;
+        let public = self.versions.get(&version)
+            .and_then(|v| v.public_key.as_deref())
+            .ok_or_else(|| VaultError::Validation(format!("key version {} does not exist", version)))?;
+        let public = STANDARD.decode(public)
+            .map_err(|e| VaultError::Internal(format!("stored transit public key is corrupt: {}", e)))?;
+        self.key_type.verify(&public, input, &signature)
+    }
+
+    /// HMAC `input` with the latest version into `vault:v{N}:{base64}`
+    pub fn hmac(&self, input: &[u8], algorithm: HmacAlgorithm) -> VaultResult<String> {
+        let key = self.hmac_material(self.latest_version)?;
+        Ok(self.versioned(self.latest_version, &algorithm.sign(&key, input)))
+    }
+
+    /// Check an HMAC made by any version still allowed to decrypt
+    ///
+    /// The HMAC is recomputed with the version it names and compared in
+    /// constant time. A well-formed HMAC that does not match is `Ok(false)`.
+    pub fn verify_hmac(&self, input: &[u8], hmac: &str, algorithm: HmacAlgorithm) -> VaultResult<bool> {
+        let (version, tag) = parse_versioned(hmac, "hmac")?;
+        self.check_version(version)?;
+        let key = self.hmac_material(version)?;
+        Ok(algorithm.verify(&key, input, &tag))
+    }
+
+    /// Response data for a key read; never includes key material
+    pub fn to_response_data(&self, name: &str) -> Map<String, Value> {
+        let versions: Map<String, Value> = self.versions.iter()
+            .map(|(version, v)| {
+                let created = Value::String(v.created_time.to_rfc3339());
+                let entry = match &v.public_key {
+                    Some(public_key) => serde_json::json!({ "creation_time": created, "public_key": public_key }),
+                    None => created,
+                };
+                (version.to_string(), entry)
+            })
+            .collect();
+        let mut data = Map::new();
+        data.insert("name".to_string(), Value::String(name.to_string()));
+        data.insert("type".to_string(), Value::String(self.key_type.to_string()));
+        data.insert("latest_version".to_string(), Value::from(self.latest_version));
+        data.insert("min_decryption_version".to_string(), Value::from(self.min_decryption_version));
+        data.insert("deletion_allowed".to_string(), Value::Bool(self.deletion_allowed));
+        data.insert("convergent_encryption".to_string(), Value::Bool(self.convergent_encryption));
+        data.insert("keys".to_string(), Value::Object(versions));
+        data
+    }
+
+    fn versioned(&self, version: u32, bytes: &[u8]) -> String {
+        format!("{}{}:{}", CIPHERTEXT_PREFIX, version, STANDARD.encode(bytes))
+    }
+
+    fn check_version(&self, version: u32) -> VaultResult<()> {
+        if version < self.min_decryption_version {
+            return Err(VaultError::Validation(format!(
+                "key version {} is below min_decryption_version {}",
+                version, self.min_decryption_version
+            )));
+        }
+        Ok(())
+    }
+
+    fn hmac_material(&self, version: u32) -> VaultResult<Vec<u8>> {
+        let encoded = self.versions.get(&version)
+            .ok_or_else(|| VaultError::Validation(format!("key version {} does not exist", version)))?
+            .hmac_key.as_deref()
+            .ok_or_else(|| VaultError::Validation(format!("key version {} has no HMAC key", version)))?;
+        STANDARD.decode(encoded)
+            .map_err(|e| VaultError::Internal(format!("stored transit HMAC key is corrupt: {}", e)))
+    }
+
+    fn material(&self, version: u32) -> VaultResult<Vec<u8>> {
+        let entry = self.versions.get(&version)
+            .ok_or_else(|| VaultError::Validation(format!("key version {} does not exist", version)))?;
+        STANDARD.decode(&entry.key)
+            .map_err(|e| VaultError::Internal(format!("stored transit key is corrupt: {}", e)))
+    }
+}
+
+/// Output length for HKDF expansion into a nonce
+struct NonceLen;
+
+impl hkdf::KeyType for NonceLen {
+    fn len(&self) -> usize {
+        aead::NONCE_LEN
+    }
+}
+
+/// Nonce for convergent encryption
+///
+/// The key version's material salts the extraction so the nonce reveals
+/// nothing about the plaintext to anyone without the key, and the context
+/// goes into the expansion so the same plaintext in different contexts
+/// encrypts differently.
+fn convergent_nonce(key: &[u8], plaintext: &[u8], context: &[u8]) -> VaultResult<[u8; aead::NONCE_LEN]> {
+    let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, key).extract(plaintext);
+    let info = [CONVERGENT_NONCE_INFO, context];
+    let mut nonce = [0u8; aead::NONCE_LEN];
+    prk.expand(&info, NonceLen)
+        .and_then(|okm| okm.fill(&mut nonce))
+        .map_err(|_| VaultError::Encryption("failed to derive convergent nonce".to_string()))?;
+    Ok(nonce)
+}
+
+/// Split `vault:v{N}:{base64}` into the version and the payload
+///
+/// `what` names the value in the error, e.g. "ciphertext".
+pub fn parse_versioned(value: &str, what: &str) -> VaultResult<(u32, Vec<u8>)> {
+    let invalid = || VaultError::Validation(format!("invalid {}: expected {}N:<base64>", what, CIPHERTEXT_PREFIX));
+    let rest = value.strip_prefix(CIPHERTEXT_PREFIX).ok_or_else(invalid)?;
+    let (version, encoded) = rest.split_once(':').ok_or_else(invalid)?;
+    let version = version.parse().map_err(|_| invalid())?;
+    let blob = STANDARD.decode(encoded).map_err(|_| invalid())?;
+    Ok((version, blob))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_old_versions_decrypt_after_rotation() {
+        let mut key = TransitKey::generate(KeyType::default(), false).unwrap();
+        let v1 = key.encrypt(b"lab result", None).unwrap();
+        assert!(v1.starts_with("vault:v1:"));
+
+        key.rotate().unwrap();
+        let v2 = key.encrypt(b"lab result", None).unwrap();
+        assert!(v2.starts_with("vault:v2:"));
+        assert_eq!(key.decrypt(&v1).unwrap(), b"lab result");
+        assert_eq!(key.decrypt(&v2).unwrap(), b"lab result");
+
+        key.configure(json!({ "min_decryption_version": 2 }).as_object().unwrap()).unwrap();
+        assert!(key.decrypt(&v1).is_err());
+        assert!(key.configure(json!({ "min_decryption_version": 3 }).as_object().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_convergent_encryption() {
+        let mut key = TransitKey::generate(KeyType::default(), true).unwrap();
+        assert!(key.encrypt(b"scan.dcm", None).is_err());
+
+        let first = key.encrypt(b"scan.dcm", Some(b"tenant-a".as_slice())).unwrap();
+        assert_eq!(first, key.encrypt(b"scan.dcm", Some(b"tenant-a".as_slice())).unwrap());
+        assert_ne!(first, key.encrypt(b"scan.dcm", Some(b"tenant-b".as_slice())).unwrap());
+        assert_ne!(first, key.encrypt(b"other.dcm", Some(b"tenant-a".as_slice())).unwrap());
+        assert_eq!(key.decrypt(&first).unwrap(), b"scan.dcm");
+
+        // A new version gives new ciphertexts for the same input
+        key.rotate().unwrap();
+        assert_ne!(first, key.encrypt(b"scan.dcm", Some(b"tenant-a".as_slice())).unwrap());
+
+        let warnings = key.configure(json!({ "convergent_encryption": false }).as_object().unwrap()).unwrap();
+        assert_eq!(warnings, vec![CONVERGENCE_NOT_RETROACTIVE.to_string()]);
+        assert_ne!(key.encrypt(b"scan.dcm", None).unwrap(), key.encrypt(b"scan.dcm", None).unwrap());
+        assert_eq!(key.decrypt(&first).unwrap(), b"scan.dcm");
+    }
+
+    #[test]
+    fn test_signatures_survive_rotation() {
+        for key_type in [KeyType::Ed25519, KeyType::EcdsaP256] {
+            let mut key = TransitKey::generate(key_type, false).unwrap();
+            let v1 = key.sign(b"artifact").unwrap();
+            assert!(v1.starts_with("vault:v1:"));
+
+            key.rotate().unwrap();
+            assert!(key.sign(b"artifact").unwrap().starts_with("vault:v2:"));
+            assert!(key.verify(b"artifact", &v1).unwrap());
+            assert!(!key.verify(b"other artifact", &v1).unwrap());
+
+            key.configure(json!({ "min_decryption_version": 2 }).as_object().unwrap()).unwrap();
+            assert!(key.verify(b"artifact", &v1).is_err());
+            assert!(key.encrypt(b"x", None).is_err());
+        }
+        assert!(TransitKey::generate(KeyType::Ed25519, true).is_err());
+    }
+
+    #[test]
+    fn test_hmacs_survive_rotation() {
+        let mut key = TransitKey::generate(KeyType::Ed25519, false).unwrap();
+        let v1 = key.hmac(b"chain link", HmacAlgorithm::Sha512).unwrap();
+        assert!(v1.starts_with("vault:v1:"));
+
+        key.rotate().unwrap();
+        assert!(key.verify_hmac(b"chain link", &v1, HmacAlgorithm::Sha512).unwrap());
+        assert!(!key.verify_hmac(b"chain link", &v1, HmacAlgorithm::Sha256).unwrap());
+        assert!(!key.verify_hmac(b"broken link", &v1, HmacAlgorithm::Sha512).unwrap());
+        assert_ne!(key.hmac(b"chain link", HmacAlgorithm::Sha512).unwrap(), v1);
+
+        let malformed = key.verify_hmac(b"chain link", "v1:AAAA", HmacAlgorithm::Sha512).unwrap_err();
+        assert!(malformed.to_string().contains("invalid hmac"));
+    }
+
+    #[test]
+    fn test_rejects_foreign_ciphertext() {
+        let key = TransitKey::generate(KeyType::ChaCha20Poly1305, false).unwrap();
+        let other = TransitKey::generate(KeyType::ChaCha20Poly1305, false).unwrap();
+        assert!(key.decrypt(&other.encrypt(b"x", None).unwrap()).is_err());
+        assert!(key.decrypt("vault:v9:AAAA").is_err());
+        assert!(key.decrypt("not a ciphertext").is_err());
+        assert!(!key.to_response_data("k").contains_key("key"));
+    }
+}
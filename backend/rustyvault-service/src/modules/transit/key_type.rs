@@ -0,0 +1,184 @@
+//! Transit key types
+//!
+//! Symmetric keys encrypt with one of the shared AEADs; asymmetric keys sign.
+//! Signing keys are stored as PKCS#8 documents and their public half is
+//! kept alongside so it can be published without touching the private key.
+
+use std::fmt;
+use std::str::FromStr;
+use rand::RngCore;
+use ring::rand::SystemRandom;
+use ring::signature::{self, EcdsaKeyPair, Ed25519KeyPair, KeyPair, UnparsedPublicKey};
+use serde::{Deserialize, Serialize};
+use shared::infrastructure::encryption::AeadAlgorithm;
+use crate::errors::{VaultError, VaultResult};
+
+/// Both supported AEADs take a 256-bit key
+const SYMMETRIC_KEY_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyType {
+    #[default]
+    #[serde(rename = "aes-256-gcm")]
+    Aes256Gcm,
+    #[serde(rename = "chacha20-poly1305")]
+    ChaCha20Poly1305,
+    #[serde(rename = "ed25519")]
+    Ed25519,
+    /// ECDSA over P-256 with SHA-256, ASN.1 DER signatures
+    #[serde(rename = "ecdsa-p256")]
+    EcdsaP256,
+}
+
+/// Freshly generated material for one key version
+pub struct GeneratedKey {
+    /// Symmetric key, or PKCS#8 document for signing keys
+    pub private: Vec<u8>,
+    /// Public key for signing keys
+    pub public: Option<Vec<u8>>,
+}
+
+impl KeyType {
+    /// The AEAD used by a symmetric key
+    pub fn aead(self) -> Option<AeadAlgorithm> {
+        match self {
+            KeyType::Aes256Gcm => Some(AeadAlgorithm::Aes256Gcm),
+            KeyType::ChaCha20Poly1305 => Some(AeadAlgorithm::ChaCha20Poly1305),
+            KeyType::Ed25519 | KeyType::EcdsaP256 => None,
+        }
+    }
+
+    pub fn supports_signing(self) -> bool {
+        matches!(self, KeyType::Ed25519 | KeyType::EcdsaP256)
+    }
+
+    pub fn generate(self) -> VaultResult<GeneratedKey> {
+        let rng = SystemRandom::new();
+        let generation_failed = || VaultError::Encryption(format!("failed to generate {} key", self));
+        match self {
+            KeyType::Aes256Gcm | KeyType::ChaCha20Poly1305 => {
+                let mut private = vec![0u8; SYMMETRIC_KEY_LEN];
+                rand::rngs::OsRng.fill_bytes(&mut private);
+                Ok(GeneratedKey { private, public: None })
+            }
+            KeyType::Ed25519 => {
+                let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).map_err(|_| generation_failed())?;
+                let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).map_err(|_| generation_failed())?;
+                Ok(GeneratedKey {
+                    private: pkcs8.as_ref().to_vec(),
+                    public: Some(pair.public_key().as_ref().to_vec()),
+                })
+            }
+            KeyType::EcdsaP256 => {
+                let algorithm = &signature::ECDSA_P256_SHA256_ASN1_SIGNING;
+                let pkcs8 = EcdsaKeyPair::generate_pkcs8(algorithm, &rng).map_err(|_| generation_failed())?;
+                let pair = EcdsaKeyPair::from_pkcs8(algorithm, pkcs8.as_ref(), &rng).map_err(|_| generation_failed())?;
+                Ok(GeneratedKey {
+                    private: pkcs8.as_ref().to_vec(),
+                    public: Some(pair.public_key().as_ref().to_vec()),
+                })
+            }
+        }
+    }
+
+    /// Sign `input` with a PKCS#8 private key
+    ///
+    /// Ed25519 signs the input as is; ECDSA hashes it with SHA-256 first.
+    pub fn sign(self, pkcs8: &[u8], input: &[u8]) -> VaultResult<Vec<u8>> {
+        let corrupt = |_| VaultError::Internal("stored transit signing key is corrupt".to_string());
+        match self {
+            KeyType::Ed25519 => {
+                let pair = Ed25519KeyPair::from_pkcs8(pkcs8).map_err(corrupt)?;
+                Ok(pair.sign(input).as_ref().to_vec())
+            }
+            KeyType::EcdsaP256 => {
+                let rng = SystemRandom::new();
+                let pair = EcdsaKeyPair::from_pkcs8(&signature::ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8, &rng)
+                    .map_err(corrupt)?;
+                let signature = pair.sign(&rng, input)
+                    .map_err(|_| VaultError::Encryption("signing failed".to_string()))?;
+                Ok(signature.as_ref().to_vec())
+            }
+            KeyType::Aes256Gcm | KeyType::ChaCha20Poly1305 => Err(self.unsupported("signing")),
+        }
+    }
+
+    /// Check a signature against a public key
+    pub fn verify(self, public: &[u8], input: &[u8], signature: &[u8]) -> VaultResult<bool> {
+        let algorithm: &'static dyn signature::VerificationAlgorithm = match self {
+            KeyType::Ed25519 => &signature::ED25519,
+            KeyType::EcdsaP256 => &signature::ECDSA_P256_SHA256_ASN1,
+            KeyType::Aes256Gcm | KeyType::ChaCha20Poly1305 => return Err(self.unsupported("verification")),
+        };
+        Ok(UnparsedPublicKey::new(algorithm, public).verify(input, signature).is_ok())
+    }
+
+    /// Error for an operation this key type cannot perform
+    pub fn unsupported(self, operation: &str) -> VaultError {
+        VaultError::Validation(format!("{} keys do not support {}", self, operation))
+    }
+}
+
+impl From<AeadAlgorithm> for KeyType {
+    fn from(algorithm: AeadAlgorithm) -> Self {
+        match algorithm {
+            AeadAlgorithm::Aes256Gcm => KeyType::Aes256Gcm,
+            AeadAlgorithm::ChaCha20Poly1305 => KeyType::ChaCha20Poly1305,
+        }
+    }
+}
+
+impl fmt::Display for KeyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            KeyType::Aes256Gcm => "aes-256-gcm",
+            KeyType::ChaCha20Poly1305 => "chacha20-poly1305",
+            KeyType::Ed25519 => "ed25519",
+            KeyType::EcdsaP256 => "ecdsa-p256",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for KeyType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ed25519" => Ok(KeyType::Ed25519),
+            "ecdsa-p256" => Ok(KeyType::EcdsaP256),
+            other => other.parse::<AeadAlgorithm>().map(KeyType::from).map_err(|_| {
+                format!(
+                    "unknown transit key type '{}' (expected aes-256-gcm, chacha20-poly1305, ed25519 or ecdsa-p256)",
+                    other
+                )
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signing_roundtrip() {
+        for key_type in [KeyType::Ed25519, KeyType::EcdsaP256] {
+            let key = key_type.generate().unwrap();
+            let public = key.public.unwrap();
+            let signature = key_type.sign(&key.private, b"webhook body").unwrap();
+            assert!(key_type.verify(&public, b"webhook body", &signature).unwrap());
+            assert!(!key_type.verify(&public, b"tampered body", &signature).unwrap());
+        }
+        assert!(KeyType::Aes256Gcm.sign(&[0u8; 32], b"x").is_err());
+    }
+
+    #[test]
+    fn test_names() {
+        for key_type in [KeyType::Aes256Gcm, KeyType::ChaCha20Poly1305, KeyType::Ed25519, KeyType::EcdsaP256] {
+            assert_eq!(key_type.to_string().parse::<KeyType>().unwrap(), key_type);
+            assert_eq!(serde_json::to_value(key_type).unwrap(), key_type.to_string());
+        }
+        assert!("rsa-2048".parse::<KeyType>().is_err());
+    }
+}
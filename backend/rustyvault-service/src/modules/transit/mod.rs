@@ -0,0 +1,406 @@
+//! Transit secrets engine
+//!
+//! Encryption as a service: callers send plaintext and get ciphertext back
+//! (and the reverse) while the keys never leave the vault. Keys are stored
+//! behind the barrier and versioned; ciphertext records the version that
+//! made it, so rotation does not break existing data.
+//!
+//! Paths (relative to the mount):
+//! - `keys/{name}`: create (optional `type`, `convergent_encryption`), read or
+//!   delete a key
+//! - `keys/`: list keys
+//! - `keys/{name}/rotate`: add a new key version
+//! - `keys/{name}/config`: set `min_decryption_version`, `deletion_allowed`
+//!   and `convergent_encryption`
+//! - `encrypt/{name}`: `plaintext` (base64) to `ciphertext`; convergent keys
+//!   also take a base64 `context`
+//! - `decrypt/{name}`: `ciphertext` to `plaintext` (base64)
+//! - `rewrap/{name}`: re-encrypt `ciphertext` with the latest key version
+//!   (convergent keys take `context` here too)
+//! - `sign/{name}`: sign `input` (base64) with an `ed25519` or `ecdsa-p256`
+//!   key, returning `signature` and `key_version`
+//! - `hmac/{name}`: HMAC `input` (base64) with the key's HMAC key; optional
+//!   `algorithm` of `sha2-256` (default), `sha2-384` or `sha2-512`
+//! - `verify/{name}`: check `signature` or `hmac` over `input`, returning
+//!   `valid`
+
+pub mod hmac;
+pub mod key;
+pub mod key_type;
+
+pub use key::TransitKey;
+pub use key_type::KeyType;
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{Map, Value};
+use tokio::sync::Mutex;
+use crate::errors::{ResultExt, VaultError, VaultResult};
+use crate::logical::{Backend, Operation, Request, Response};
+use crate::storage::StorageBackend;
+
+/// Transit secrets engine backend
+pub struct TransitBackend {
+    storage: Arc<dyn StorageBackend>,
+    mount_path: String,
+    /// Serializes read-modify-write of keys so concurrent rotations cannot
+    /// hand out the same version twice
+    write_lock: Mutex<()>,
+}
+
+impl TransitBackend {
+    pub fn new(storage: Arc<dyn StorageBackend>, mount_path: String) -> Self {
+        Self {
+            storage,
+            mount_path,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    fn key_path(&self, name: &str) -> String {
+        format!("{}/keys/{}", self.mount_path, name)
+    }
+
+    async fn load_key(&self, name: &str) -> VaultResult<Option<TransitKey>> {
+        let path = self.key_path(name);
+        match self.storage.get(&path).await.with_context(|| format!("read {}", path))? {
+            Some(raw) => Ok(Some(serde_json::from_slice(&raw).with_context(|| format!("decode {}", path))?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn require_key(&self, name: &str) -> VaultResult<TransitKey> {
+        self.load_key(name).await?
+            .ok_or_else(|| VaultError::NotFound(format!("transit key {} does not exist", name)))
+    }
+
+    async fn store_key(&self, name: &str, key: &TransitKey) -> VaultResult<()> {
+        let path = self.key_path(name);
+        let raw = serde_json::to_vec(key)?;
+        self.storage.put(&path, &raw).await.with_context(|| format!("write {}", path))
+    }
+
+    /// Create a key; writing an existing key leaves it unchanged
+    async fn create_key(&self, name: &str, data: &Map<String, Value>) -> VaultResult<Option<Response>> {
+        validate_key_name(name)?;
+        let key_type = match data.get("type").and_then(|v| v.as_str()) {
+            Some(raw) => raw.parse::<KeyType>().map_err(VaultError::Validation)?,
+            None => KeyType::default(),
+        };
+        let convergent = match data.get("convergent_encryption") {
+            Some(v) => v.as_bool()
+                .ok_or_else(|| VaultError::Validation("convergent_encryption must be a boolean".to_string()))?,
+            None => false,
+        };
+
+        let _guard = self.write_lock.lock().await;
+        let key = match self.load_key(name).await? {
+            Some(existing) => existing,
+            None => {
+                let key = TransitKey::generate(key_type, convergent)?;
+                self.store_key(name, &key).await?;
+                key
+            }
+        };
+        Ok(Some(Response::new().data(key.to_response_data(name))))
+    }
+
+    /// Load, change and store a key under the write lock
+    ///
+    /// `update` returns warnings to pass back to the caller.
+    async fn update_key<F>(&self, name: &str, update: F) -> VaultResult<Option<Response>>
+    where
+        F: FnOnce(&mut TransitKey) -> VaultResult<Vec<String>>,
+    {
+        let _guard = self.write_lock.lock().await;
+        let mut key = self.require_key(name).await?;
+        let warnings = update(&mut key)?;
+        self.store_key(name, &key).await?;
+
+        let mut response = Response::new().data(key.to_response_data(name));
+        response.warnings = warnings;
+        Ok(Some(response))
+    }
+
+    async fn delete_key(&self, name: &str) -> VaultResult<Option<Response>> {
+        let _guard = self.write_lock.lock().await;
+        let key = self.require_key(name).await?;
+        if !key.deletion_allowed {
+            return Err(VaultError::Validation(format!(
+                "deletion is not allowed for transit key {}; set deletion_allowed first",
+                name
+            )));
+        }
+        self.storage.delete(&self.key_path(name)).await?;
+        Ok(None)
+    }
+
+    async fn list_keys(&self) -> VaultResult<Option<Response>> {
+        let prefix = format!("{}/keys/", self.mount_path);
+        let names: Vec<Value> = self.storage.list(&prefix).await?
+            .iter()
+            .map(|k| Value::String(k.strip_prefix(&prefix).unwrap_or(k).to_string()))
+            .collect();
+
+        let mut data = Map::new();
+        data.insert("keys".to_string(), Value::Array(names));
+        Ok(Some(Response::new().data(data)))
+    }
+
+    async fn encrypt(&self, name: &str, data: &Map<String, Value>) -> VaultResult<Option<Response>> {
+        let plaintext = required_base64(data, "plaintext")?;
+        let key = self.require_key(name).await?;
+        let context = optional_base64(data, "context")?;
+        Ok(Some(ciphertext_response(key.encrypt(&plaintext, context.as_deref())?, key.latest_version)))
+    }
+
+    async fn decrypt(&self, name: &str, data: &Map<String, Value>) -> VaultResult<Option<Response>> {
+        let key = self.require_key(name).await?;
+        let plaintext = key.decrypt(required_str(data, "ciphertext")?)?;
+
+        let mut out = Map::new();
+        out.insert("plaintext".to_string(), Value::String(STANDARD.encode(plaintext)));
+        Ok(Some(Response::new().data(out)))
+    }
+
+    /// Decrypt and re-encrypt with the latest version; the plaintext never
+    /// leaves the vault
+    async fn rewrap(&self, name: &str, data: &Map<String, Value>) -> VaultResult<Option<Response>> {
+        let key = self.require_key(name).await?;
+        let plaintext = key.decrypt(required_str(data, "ciphertext")?)?;
+        let context = optional_base64(data, "context")?;
+        Ok(Some(ciphertext_response(key.encrypt(&plaintext, context.as_deref())?, key.latest_version)))
+    }
+
+    async fn sign(&self, name: &str, data: &Map<String, Value>) -> VaultResult<Option<Response>> {
+        let input = required_base64(data, "input")?;
+        let key = self.require_key(name).await?;
+
+        let mut out = Map::new();
+        out.insert("signature".to_string(), Value::String(key.sign(&input)?));
+        out.insert("key_version".to_string(), Value::from(key.latest_version));
+        Ok(Some(Response::new().data(out)))
+    }
+
+    async fn hmac(&self, name: &str, data: &Map<String, Value>) -> VaultResult<Option<Response>> {
+        let input = required_base64(data, "input")?;
+        let algorithm = hmac::parse_algorithm(data.get("algorithm").and_then(|v| v.as_str()))?;
+        let key = self.require_key(name).await?;
+
+        let mut out = Map::new();
+        out.insert("hmac".to_string(), Value::String(key.hmac(&input, algorithm)?));
+        out.insert("key_version".to_string(), Value::from(key.latest_version));
+        Ok(Some(Response::new().data(out)))
+    }
+
+    /// Verify either a `signature` or an `hmac`, whichever the request carries
+    async fn verify(&self, name: &str, data: &Map<String, Value>) -> VaultResult<Option<Response>> {
+        let input = required_base64(data, "input")?;
+        let key = self.require_key(name).await?;
+        let signature = data.get("signature").and_then(|v| v.as_str());
+        let hmac = data.get("hmac").and_then(|v| v.as_str());
+        let valid = match (signature, hmac) {
+            (Some(signature), None) => key.verify(&input, signature)?,
+            (None, Some(hmac)) => {
+                let algorithm = hmac::parse_algorithm(data.get("algorithm").and_then(|v| v.as_str()))?;
+                key.verify_hmac(&input, hmac, algorithm)?
+            }
+            _ => {
+                return Err(VaultError::Validation("exactly one of signature or hmac is required".to_string()));
+            }
+        };
+
+        let mut out = Map::new();
+        out.insert("valid".to_string(), Value::Bool(valid));
+        Ok(Some(Response::new().data(out)))
+    }
+}
+
+#[async_trait]
+impl Backend for TransitBackend {
+    async fn handle_request(&self, req: &mut Request) -> VaultResult<Option<Response>> {
+        let path = req.path.strip_prefix(req.mount_point.as_str())
+            .unwrap_or(&req.path)
+            .to_string();
+        let parts: Vec<&str> = path.split('/').collect();
+        let data = req.data.take().unwrap_or_default();
+
+        match (req.operation, parts.as_slice()) {
+            (Operation::List, ["keys", ""]) | (Operation::List, ["keys"]) => self.list_keys().await,
+            (Operation::Read, ["keys", name]) => Ok(self.load_key(name).await?
+                .map(|key| Response::new().data(key.to_response_data(name)))),
+            (Operation::Write, ["keys", name]) => self.create_key(name, &data).await,
+            (Operation::Delete, ["keys", name]) => self.delete_key(name).await,
+            (Operation::Write, ["keys", name, "rotate"]) => self.update_key(name, |key| {
+                key.rotate()?;
+                Ok(Vec::new())
+            }).await,
+            (Operation::Write, ["keys", name, "config"]) => self.update_key(name, |key| key.configure(&data)).await,
+            (Operation::Write, ["encrypt", name]) => self.encrypt(name, &data).await,
+            (Operation::Write, ["decrypt", name]) => self.decrypt(name, &data).await,
+            (Operation::Write, ["rewrap", name]) => self.rewrap(name, &data).await,
+            (Operation::Write, ["sign", name]) => self.sign(name, &data).await,
+            (Operation::Write, ["hmac", name]) => self.hmac(name, &data).await,
+            (Operation::Write, ["verify", name]) => self.verify(name, &data).await,
+            _ => Err(VaultError::Validation(format!("unsupported transit engine path: {}", path))),
+        }
+    }
+}
+
+fn ciphertext_response(ciphertext: String, key_version: u32) -> Response {
+    let mut data = Map::new();
+    data.insert("ciphertext".to_string(), Value::String(ciphertext));
+    data.insert("key_version".to_string(), Value::from(key_version));
+    Response::new().data(data)
+}
+
+fn required_str<'a>(data: &'a Map<String, Value>, field: &str) -> VaultResult<&'a str> {
+    data.get(field)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| VaultError::Validation(format!("{} is required", field)))
+}
+
+fn required_base64(data: &Map<String, Value>, field: &str) -> VaultResult<Vec<u8>> {
+    STANDARD.decode(required_str(data, field)?)
+        .map_err(|_| VaultError::Validation(format!("{} must be base64", field)))
+}
+
+fn optional_base64(data: &Map<String, Value>, field: &str) -> VaultResult<Option<Vec<u8>>> {
+    data.get(field)
+        .and_then(|v| v.as_str())
+        .map(|raw| STANDARD.decode(raw).map_err(|_| VaultError::Validation(format!("{} must be base64", field))))
+        .transpose()
+}
+
+fn validate_key_name(name: &str) -> VaultResult<()> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(VaultError::Validation(
+            "key name may only contain letters, digits, '-' and '_'".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::storage::physical_inmem::InMemoryBackend;
+
+    fn backend() -> TransitBackend {
+        TransitBackend::new(Arc::new(InMemoryBackend::new()), "transit".to_string())
+    }
+
+    async fn write(backend: &TransitBackend, path: &str, body: Value) -> VaultResult<Map<String, Value>> {
+        let mut req = Request::new_write_request(format!("transit/{}", path), body.as_object().cloned());
+        req.mount_point = "transit/".to_string();
+        Ok(backend.handle_request(&mut req).await?.and_then(|r| r.data).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_rotate_rewrap() {
+        let transit = backend();
+        write(&transit, "keys/records", json!({})).await.unwrap();
+        let plaintext = STANDARD.encode("blood type: O-");
+
+        let v1 = write(&transit, "encrypt/records", json!({ "plaintext": plaintext })).await.unwrap();
+        let v1 = v1["ciphertext"].as_str().unwrap().to_string();
+        assert!(v1.starts_with("vault:v1:"));
+
+        write(&transit, "keys/records/rotate", json!({})).await.unwrap();
+        let decrypted = write(&transit, "decrypt/records", json!({ "ciphertext": v1 })).await.unwrap();
+        assert_eq!(decrypted["plaintext"], plaintext);
+
+        let rewrapped = write(&transit, "rewrap/records", json!({ "ciphertext": v1 })).await.unwrap();
+        assert_eq!(rewrapped["key_version"], 2);
+        let v2 = rewrapped["ciphertext"].as_str().unwrap();
+        assert!(v2.starts_with("vault:v2:"));
+        let decrypted = write(&transit, "decrypt/records", json!({ "ciphertext": v2 })).await.unwrap();
+        assert_eq!(decrypted["plaintext"], plaintext);
+    }
+
+    #[tokio::test]
+    async fn test_convergent_keys() {
+        let transit = backend();
+        let created = write(&transit, "keys/blobs", json!({ "convergent_encryption": true })).await.unwrap();
+        assert_eq!(created["convergent_encryption"], true);
+
+        let body = json!({ "plaintext": STANDARD.encode("x-ray"), "context": STANDARD.encode("patient-7") });
+        let first = write(&transit, "encrypt/blobs", body.clone()).await.unwrap();
+        let second = write(&transit, "encrypt/blobs", body).await.unwrap();
+        assert_eq!(first["ciphertext"], second["ciphertext"]);
+        let no_context = write(&transit, "encrypt/blobs", json!({ "plaintext": STANDARD.encode("x-ray") })).await;
+        assert!(matches!(no_context, Err(VaultError::Validation(_))));
+
+        let mut req = Request::new_write_request(
+            "transit/keys/blobs/config",
+            json!({ "convergent_encryption": false }).as_object().cloned(),
+        );
+        req.mount_point = "transit/".to_string();
+        let response = transit.handle_request(&mut req).await.unwrap().unwrap();
+        assert_eq!(response.warnings, vec![key::CONVERGENCE_NOT_RETROACTIVE.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_verify() {
+        let transit = backend();
+        let created = write(&transit, "keys/webhooks", json!({ "type": "ed25519" })).await.unwrap();
+        assert!(created["keys"]["1"]["public_key"].is_string());
+        let input = STANDARD.encode("{\"event\":\"admission\"}");
+
+        let signed = write(&transit, "sign/webhooks", json!({ "input": input })).await.unwrap();
+        assert_eq!(signed["key_version"], 1);
+        write(&transit, "keys/webhooks/rotate", json!({})).await.unwrap();
+
+        let body = json!({ "input": input, "signature": signed["signature"] });
+        assert_eq!(write(&transit, "verify/webhooks", body).await.unwrap()["valid"], true);
+        let body = json!({ "input": STANDARD.encode("forged"), "signature": signed["signature"] });
+        assert_eq!(write(&transit, "verify/webhooks", body).await.unwrap()["valid"], false);
+
+        let encrypt = write(&transit, "encrypt/webhooks", json!({ "plaintext": input })).await;
+        assert!(matches!(encrypt, Err(VaultError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_hmac_and_verify() {
+        let transit = backend();
+        write(&transit, "keys/audit", json!({})).await.unwrap();
+        let input = STANDARD.encode("entry 41");
+
+        let body = json!({ "input": input, "algorithm": "sha2-384" });
+        let hmac = write(&transit, "hmac/audit", body).await.unwrap()["hmac"].clone();
+        assert!(hmac.as_str().unwrap().starts_with("vault:v1:"));
+        write(&transit, "keys/audit/rotate", json!({})).await.unwrap();
+
+        let body = json!({ "input": input, "hmac": hmac, "algorithm": "sha2-384" });
+        assert_eq!(write(&transit, "verify/audit", body).await.unwrap()["valid"], true);
+        let body = json!({ "input": STANDARD.encode("entry 42"), "hmac": hmac, "algorithm": "sha2-384" });
+        assert_eq!(write(&transit, "verify/audit", body).await.unwrap()["valid"], false);
+
+        let body = json!({ "input": input, "hmac": "sha384:abc" });
+        assert!(matches!(write(&transit, "verify/audit", body).await, Err(VaultError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_key_material_stays_inside() {
+        let transit = backend();
+        let created = write(&transit, "keys/records", json!({ "type": "chacha20-poly1305" })).await.unwrap();
+        assert_eq!(created["type"], "chacha20-poly1305");
+        assert!(!serde_json::to_string(&created).unwrap().contains(
+            &transit.load_key("records").await.unwrap().unwrap().versions[&1].key
+        ));
+
+        // Deletion is refused until explicitly allowed
+        let mut delete = Request::new_delete_request("transit/keys/records", None);
+        delete.mount_point = "transit/".to_string();
+        assert!(transit.handle_request(&mut delete).await.is_err());
+        write(&transit, "keys/records/config", json!({ "deletion_allowed": true })).await.unwrap();
+        assert!(transit.handle_request(&mut delete).await.is_ok());
+
+        let missing = write(&transit, "encrypt/records", json!({ "plaintext": "" })).await;
+        assert!(matches!(missing, Err(VaultError::NotFound(_))));
+    }
+}
@@ -1,6 +1,7 @@
 //! Router for vault requests
 
 pub mod router;
+pub mod mount_table;
 
 pub use router::Router;
-
+pub use mount_table::{MountEntry, MountTable};
@@ -0,0 +1,178 @@
+//! Mount table mapping path prefixes to logical backends
+//!
+//! Every secrets engine and auth method is mounted at a path prefix
+//! (e.g. `secret/`, `pki/`, `auth/userpass/`). Requests are dispatched to the
+//! backend with the longest mount path that is a prefix of the request path.
+
+use std::sync::{Arc, RwLock};
+use radix_trie::{Trie, TrieCommon};
+use crate::errors::{VaultError, VaultResult};
+use crate::logical::Backend;
+
+/// A backend mounted at a path prefix
+#[derive(Clone)]
+pub struct MountEntry {
+    /// Mount path, normalized with a trailing slash (e.g. `secret/`)
+    pub path: String,
+    /// Backend type (e.g. `kv`, `pki`, `userpass`)
+    pub backend_type: String,
+    /// The mounted backend
+    pub backend: Arc<dyn Backend>,
+}
+
+impl std::fmt::Debug for MountEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MountEntry")
+            .field("path", &self.path)
+            .field("backend_type", &self.backend_type)
+            .finish()
+    }
+}
+
+/// Table of mounted backends keyed by normalized mount path
+pub struct MountTable {
+    mounts: RwLock<Trie<String, MountEntry>>,
+}
+
+impl MountTable {
+    pub fn new() -> Self {
+        Self {
+            mounts: RwLock::new(Trie::new()),
+        }
+    }
+
+    /// Normalize a mount path: no leading slash, exactly one trailing slash
+    pub fn normalize_path(path: &str) -> String {
+        let trimmed = path.trim_matches('/');
+        format!("{}/", trimmed)
+    }
+
+    /// Mount a backend at the given path
+    ///
+    /// Fails if the path is empty or a backend is already mounted there.
+    pub fn mount(
+        &self,
+        path: &str,
+        backend_type: &str,
+        backend: Arc<dyn Backend>,
+    ) -> VaultResult<()> {
+        let path = Self::normalize_path(path);
+        if path == "/" {
+            return Err(VaultError::Validation("mount path cannot be empty".to_string()));
+        }
+
+        let mut mounts = self.mounts.write().unwrap();
+        if mounts.get(&path).is_some() {
+            return Err(VaultError::Validation(format!("path is already in use at {}", path)));
+        }
+
+        mounts.insert(path.clone(), MountEntry {
+            path,
+            backend_type: backend_type.to_string(),
+            backend,
+        });
+        Ok(())
+    }
+
+    /// Remove the backend mounted at exactly the given path
+    pub fn unmount(&self, path: &str) -> Option<MountEntry> {
+        let path = Self::normalize_path(path);
+        let mut mounts = self.mounts.write().unwrap();
+        mounts.remove(&path)
+    }
+
+    /// Find the mount with the longest path that prefixes `path`
+    ///
+    /// Matching happens on whole path segments, so `secrets/foo` does not
+    /// match a mount at `secret/`.
+    pub fn lookup(&self, path: &str) -> Option<MountEntry> {
+        let key = Self::normalize_path(path);
+        let mounts = self.mounts.read().unwrap();
+        mounts.get_ancestor_value(&key).cloned()
+    }
+
+    /// Get the mount registered at exactly the given path
+    #[cfg(test)]
+    pub fn get(&self, path: &str) -> Option<MountEntry> {
+        let path = Self::normalize_path(path);
+        let mounts = self.mounts.read().unwrap();
+        mounts.get(&path).cloned()
+    }
+
+    /// List all mounts ordered by path
+    #[cfg(test)]
+    pub fn list(&self) -> Vec<MountEntry> {
+        let mounts = self.mounts.read().unwrap();
+        let mut entries: Vec<MountEntry> = mounts.values().cloned().collect();
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        entries
+    }
+}
+
+impl Default for MountTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use crate::logical::{Request, Response};
+
+    struct NamedBackend(&'static str);
+
+    #[async_trait]
+    impl Backend for NamedBackend {
+        async fn handle_request(&self, _req: &mut Request) -> VaultResult<Option<Response>> {
+            let mut data = serde_json::Map::new();
+            data.insert("backend".to_string(), serde_json::Value::String(self.0.to_string()));
+            Ok(Some(Response::new().data(data)))
+        }
+    }
+
+    fn table() -> MountTable {
+        let table = MountTable::new();
+        table.mount("secret", "kv", Arc::new(NamedBackend("secret"))).unwrap();
+        table.mount("pki/", "pki", Arc::new(NamedBackend("pki"))).unwrap();
+        table.mount("auth/userpass", "userpass", Arc::new(NamedBackend("userpass"))).unwrap();
+        table
+    }
+
+    #[test]
+    fn test_longest_prefix_match() {
+        let table = table();
+
+        assert_eq!(table.lookup("secret/foo/bar").unwrap().path, "secret/");
+        assert_eq!(table.lookup("pki/issue/web").unwrap().path, "pki/");
+        assert_eq!(table.lookup("auth/userpass/login/alice").unwrap().path, "auth/userpass/");
+        assert!(table.lookup("auth/token/create").is_none());
+
+        table.mount("secret/team", "kv", Arc::new(NamedBackend("team"))).unwrap();
+        assert_eq!(table.lookup("secret/team/db").unwrap().path, "secret/team/");
+        assert_eq!(table.lookup("secret/other").unwrap().path, "secret/");
+    }
+
+    #[test]
+    fn test_lookup_respects_segment_boundaries() {
+        let table = table();
+        assert!(table.lookup("secrets/foo").is_none());
+        assert_eq!(table.lookup("secret").unwrap().path, "secret/");
+    }
+
+    #[test]
+    fn test_mount_conflicts_and_unmount() {
+        let table = table();
+        assert!(table.mount("secret/", "kv", Arc::new(NamedBackend("dup"))).is_err());
+        assert!(table.mount("/", "kv", Arc::new(NamedBackend("root"))).is_err());
+
+        let removed = table.unmount("pki").unwrap();
+        assert_eq!(removed.backend_type, "pki");
+        assert!(table.lookup("pki/issue/web").is_none());
+        assert!(table.unmount("pki").is_none());
+
+        let paths: Vec<String> = table.list().into_iter().map(|m| m.path).collect();
+        assert_eq!(paths, vec!["auth/userpass/", "secret/"]);
+    }
+}
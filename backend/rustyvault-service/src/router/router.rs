@@ -1,56 +1,62 @@
 //! Request router for vault operations
 
 use std::sync::Arc;
-use std::sync::Mutex;
-use radix_trie::{Trie, TrieCommon};
 use crate::errors::VaultResult;
 use crate::logical::{Request, Response, Backend};
+use crate::router::mount_table::{MountEntry, MountTable};
 
 /// Router for vault requests
+///
+/// Dispatches each request to the backend mounted at the longest matching
+/// path prefix in the mount table.
 pub struct Router {
-    backends: Arc<Mutex<Trie<String, Arc<dyn Backend>>>>,
+    mounts: MountTable,
 }
 
 impl Router {
     pub fn new() -> Self {
         Self {
-            backends: Arc::new(Mutex::new(Trie::new())),
+            mounts: MountTable::new(),
         }
     }
 
-    pub fn add_backend(&self, path: String, backend: Arc<dyn Backend>) {
-        let mut backends = self.backends.lock().unwrap();
-        backends.insert(path, backend);
+    /// Mount a backend of the given type at a path prefix
+    pub fn mount(&self, path: &str, backend_type: &str, backend: Arc<dyn Backend>) -> VaultResult<()> {
+        self.mounts.mount(path, backend_type, backend)
+    }
+
+    /// Unmount the backend at exactly the given path
+    pub fn unmount(&self, path: &str) -> Option<MountEntry> {
+        self.mounts.unmount(path)
+    }
+
+    /// Access the underlying mount table
+    #[cfg(test)]
+    pub fn mounts(&self) -> &MountTable {
+        &self.mounts
     }
 
     pub async fn route(&self, req: &mut Request) -> VaultResult<Option<Response>> {
-        // Find the backend in a block so the lock is dropped before any await
-        let best_match: Option<Arc<dyn Backend>> = {
-            let backends = self.backends.lock().unwrap();
-            
-            // Find the longest matching backend path
-            let mut best_match: Option<Arc<dyn Backend>> = None;
-            let mut best_len = 0;
-            
-            // Iterate through the trie to find matching paths
-            for (path, backend) in backends.iter() {
-                if req.path.starts_with(path) && path.len() > best_len {
-                    best_len = path.len();
-                    best_match = Some(backend.clone());
-                }
-            }
-            
-            best_match
-            // Lock is dropped here at end of block
-        };
-        
-        // Now we can await - lock is not held
-        if let Some(backend) = best_match {
-            backend.handle_request(req).await
-        } else {
+        // A namespaced request only ever sees mounts under its namespace
+        if let Some(namespace) = &req.namespace {
+            req.path = namespace.scope_path(&req.path);
+        }
+
+        // Resolve the mount before awaiting so no lock is held across the call
+        let Some(entry) = self.mounts.lookup(&req.path) else {
             // Default: no backend found for this path
-            Ok(None)
+            return Ok(None);
+        };
+
+        req.mount_point = entry.path.clone();
+        let mut response = entry.backend.handle_request(req).await?;
+
+        // Apply the requested field projection once for every backend;
+        // only KV (always v2 here) nests the secret under `data`
+        if let (Some(fields), Some(resp)) = (req.fields.as_ref(), response.as_mut()) {
+            resp.project_fields(fields, entry.backend_type == "kv");
         }
+        Ok(response)
     }
 }
 
@@ -59,4 +65,3 @@ impl Default for Router {
         Self::new()
     }
 }
-
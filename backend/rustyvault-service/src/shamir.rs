@@ -66,7 +66,7 @@ impl ShamirSecret {
 
     pub fn get_share(&self, id: u8) -> VaultResult<Vec<u8>> {
         if id == 0 {
-            return Err(VaultError::Vault("Invalid share ID".to_string()));
+            return Err(VaultError::Validation("Invalid share ID".to_string()));
         }
         let mut share_bytes: Vec<u8> = vec![];
         let coefficients = self.coefficients.clone();
@@ -142,7 +142,7 @@ impl ShamirSecret {
 
     fn accumulate_share_bytes(id: u8, coefficient_bytes: Vec<u8>) -> VaultResult<u8> {
         if id == 0 {
-            return Err(VaultError::Vault("Invalid share ID".to_string()));
+            return Err(VaultError::Validation("Invalid share ID".to_string()));
         }
         let mut accumulator: u8 = 0;
         let mut x_i: u8 = 1;
@@ -31,6 +31,17 @@ impl StorageAdapter {
 
 #[async_trait]
 impl StorageBackend for StorageAdapter {
+    /// Both stores, metadata first, when they differ (e.g. `postgresql+file`)
+    fn storage_type(&self) -> String {
+        let metadata = self.metadata_store.storage_type();
+        let barrier = self.barrier_store.storage_type();
+        if metadata == barrier {
+            metadata
+        } else {
+            format!("{}+{}", metadata, barrier)
+        }
+    }
+
     async fn get(&self, key: &str) -> VaultResult<Option<Vec<u8>>> {
         if self.is_metadata_key(key) {
             self.metadata_store.get(key).await
@@ -62,5 +73,18 @@ impl StorageBackend for StorageAdapter {
             self.barrier_store.list(prefix).await
         }
     }
+
+    async fn list_page(
+        &self,
+        prefix: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> VaultResult<Vec<String>> {
+        if self.is_metadata_key(prefix) {
+            self.metadata_store.list_page(prefix, after, limit).await
+        } else {
+            self.barrier_store.list_page(prefix, after, limit).await
+        }
+    }
 }
 
@@ -1,6 +1,17 @@
 //! AES-GCM barrier implementation
 //!
 //! Adapted from RustyVault to use aes-gcm crate instead of OpenSSL
+//!
+//! The barrier-init entry (holding the real encryption key) is wrapped with
+//! a key derived from the unseal key through a configurable KDF. The KDF
+//! record is stored in plaintext next to the ciphertext so unseal can derive
+//! the same wrapping key, and repeated inside the encrypted entry to detect
+//! tampering. Entries in older layouts (see `barrier_init`), including those
+//! written before KDFs were introduced and wrapped with the unseal key
+//! directly, still unseal and are upgraded in place.
+//!
+//! While unsealed the barrier also holds the seal-wrap key derived from the
+//! unseal key (see `seal_wrap`), and drops it again when sealed.
 
 use std::sync::Arc;
 use arc_swap::ArcSwap;
@@ -12,7 +23,10 @@ use serde::{Deserialize, Serialize};
 use zeroize::{Zeroize, Zeroizing};
 use sha2::{Sha256, Digest};
 use async_trait::async_trait;
+use shared::infrastructure::encryption::{KdfParams, KdfRecord};
 use crate::errors::{VaultError, VaultResult};
+use crate::storage::barrier_init::{BarrierInit, SealType, StoredBarrierInit, BARRIER_INIT_VERSION};
+use crate::storage::seal_wrap::{self, SealWrapper};
 use crate::storage::{StorageBackend, SecurityBarrier, BARRIER_INIT_PATH};
 
 const EPOCH_SIZE: usize = 4;
@@ -23,12 +37,17 @@ const AES_BLOCK_SIZE: usize = 16;
 const NONCE_SIZE: usize = 12; // GCM standard nonce size
 const TAG_SIZE: usize = 16;
 
+/// The encrypted part of the barrier-init entry
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Zeroize)]
 #[serde(deny_unknown_fields)]
 #[zeroize(drop)]
-struct BarrierInit {
+struct BarrierKey {
     version: u32,
     key: Vec<u8>,
+    /// KDF used to wrap this entry (absent in version 1 entries)
+    #[serde(default)]
+    #[zeroize(skip)]
+    kdf: Option<KdfRecord>,
 }
 
 #[derive(Debug, Clone, Zeroize)]
@@ -36,6 +55,8 @@ struct BarrierInit {
 struct BarrierInfo {
     sealed: bool,
     key: Option<Zeroizing<Vec<u8>>>,
+    /// Key for seal-wrapped entries, set only while unsealed
+    seal_wrap_key: Option<Zeroizing<Vec<u8>>>,
     aes_gcm_version_byte: u8,
 }
 
@@ -44,6 +65,7 @@ impl Default for BarrierInfo {
         Self {
             sealed: true,
             key: None,
+            seal_wrap_key: None,
             aes_gcm_version_byte: AES_GCM_VERSION2,
         }
     }
@@ -52,16 +74,78 @@ impl Default for BarrierInfo {
 pub struct AESGCMBarrier {
     barrier_info: ArcSwap<BarrierInfo>,
     backend: Arc<dyn StorageBackend>,
+    /// KDF parameters for newly wrapped barrier-init entries
+    kdf: KdfParams,
 }
 
 impl AESGCMBarrier {
     pub fn new(backend: Arc<dyn StorageBackend>) -> Self {
+        Self::with_kdf(backend, KdfParams::default())
+    }
+
+    pub fn with_kdf(backend: Arc<dyn StorageBackend>, kdf: KdfParams) -> Self {
         Self {
             backend,
             barrier_info: ArcSwap::from_pointee(BarrierInfo::default()),
+            kdf,
         }
     }
 
+    /// The stored barrier-init entry, in whichever layout it was written
+    pub async fn stored_init(&self) -> VaultResult<StoredBarrierInit> {
+        let entry = self.backend.get(BARRIER_INIT_PATH).await?
+            .ok_or_else(|| VaultError::Vault("Barrier not initialized".to_string()))?;
+        StoredBarrierInit::decode(&entry)
+    }
+
+    /// KDF record of the stored barrier-init entry, `None` for legacy entries
+    pub async fn stored_kdf(&self) -> VaultResult<Option<KdfRecord>> {
+        Ok(self.stored_init().await?.kdf().cloned())
+    }
+
+    /// Encrypt and store the barrier-init entry under a key derived from `kek`
+    async fn write_barrier_init(&self, kek: &[u8], key: &[u8]) -> VaultResult<()> {
+        let record = KdfRecord::generate(self.kdf.clone())
+            .map_err(|e| VaultError::Vault(e.to_string()))?;
+        let wrapping_key = derive_wrapping_key(&record, kek)?;
+
+        let barrier_key = BarrierKey {
+            version: BARRIER_INIT_VERSION,
+            key: key.to_vec(),
+            kdf: Some(record.clone()),
+        };
+        let serialized_barrier_key = Zeroizing::new(serde_json::to_vec(&barrier_key)
+            .map_err(|e| VaultError::Serialization(e))?);
+
+        // Encrypt with a scratch cipher so the current key is left untouched
+        let scratch = AESGCMBarrier::new(self.backend.clone());
+        scratch.init_cipher(wrapping_key.as_slice())?;
+        let ciphertext = scratch.encrypt(BARRIER_INIT_PATH, &serialized_barrier_key)?;
+        scratch.reset_cipher()?;
+
+        let value = BarrierInit::new(SealType::Shamir, record, &ciphertext).encode()?;
+        self.backend.put(BARRIER_INIT_PATH, &value).await
+    }
+
+    /// Encrypt and decrypt `plaintext` in memory with the barrier key, and
+    /// check a corrupted copy is refused; nothing is read or written
+    pub fn self_test(&self, plaintext: &[u8]) -> VaultResult<()> {
+        if self.sealed()? {
+            return Err(VaultError::Sealed);
+        }
+        let mut ciphertext = self.encrypt("sys/health/crypto-selftest", plaintext)?;
+        if self.decrypt("sys/health/crypto-selftest", &ciphertext)? != plaintext {
+            return Err(VaultError::Vault("Barrier round trip returned different data".to_string()));
+        }
+        if let Some(last) = ciphertext.last_mut() {
+            *last ^= 0x01;
+        }
+        if self.decrypt("sys/health/crypto-selftest", &ciphertext).is_ok() {
+            return Err(VaultError::Vault("Barrier accepted corrupted ciphertext".to_string()));
+        }
+        Ok(())
+    }
+
     fn init_cipher(&self, key: &[u8]) -> VaultResult<()> {
         let mut barrier_info = (*self.barrier_info.load_full()).clone();
         barrier_info.key = Some(Zeroizing::new(key.to_vec()));
@@ -135,6 +219,12 @@ impl AESGCMBarrier {
     }
 }
 
+fn derive_wrapping_key(record: &KdfRecord, kek: &[u8]) -> VaultResult<Zeroizing<Vec<u8>>> {
+    record.derive(kek, 2 * AES_BLOCK_SIZE)
+        .map(Zeroizing::new)
+        .map_err(|e| VaultError::Vault(e.to_string()))
+}
+
 #[async_trait]
 impl SecurityBarrier for AESGCMBarrier {
     async fn inited(&self) -> VaultResult<bool> {
@@ -154,24 +244,9 @@ impl SecurityBarrier for AESGCMBarrier {
             return Err(VaultError::Vault("Barrier already initialized".to_string()));
         }
 
-        // Generate encryption key
+        // Generate encryption key and wrap it with a key derived from the KEK
         let encrypt_key = self.generate_key()?;
-
-        let barrier_init = BarrierInit {
-            version: 1,
-            key: encrypt_key.to_vec(),
-        };
-
-        let serialized_barrier_init = serde_json::to_string(&barrier_init)
-            .map_err(|e| VaultError::Serialization(e))?;
-
-        // Use KEK to encrypt the barrier init
-        self.init_cipher(kek)?;
-        let value = self.encrypt(BARRIER_INIT_PATH, serialized_barrier_init.as_bytes())?;
-        self.backend.put(BARRIER_INIT_PATH, &value).await?;
-        self.reset_cipher()?;
-
-        Ok(())
+        self.write_barrier_init(kek, encrypt_key.as_slice()).await
     }
 
     fn generate_key(&self) -> VaultResult<Zeroizing<Vec<u8>>> {
@@ -196,21 +271,42 @@ impl SecurityBarrier for AESGCMBarrier {
             return Ok(());
         }
 
-        let entry = self.backend.get(BARRIER_INIT_PATH).await?;
-        let entry = entry.ok_or_else(|| VaultError::Vault("Barrier not initialized".to_string()))?;
+        let stored = self.stored_init().await?;
 
-        // Decrypt with KEK
-        self.init_cipher(kek)?;
-        let value = self.decrypt(BARRIER_INIT_PATH, &entry)?;
+        // Legacy entries are raw ciphertext under the KEK itself
+        let (ciphertext, wrapping_key) = match &stored {
+            StoredBarrierInit::Wrapped(init) => (init.ciphertext()?, derive_wrapping_key(&init.kdf, kek)?),
+            StoredBarrierInit::Legacy(ciphertext) => (ciphertext.clone(), Zeroizing::new(kek.to_vec())),
+        };
+
+        self.init_cipher(wrapping_key.as_slice())?;
+        let value = self.decrypt(BARRIER_INIT_PATH, &ciphertext);
         self.reset_cipher()?;
+        let value = Zeroizing::new(value?);
 
-        let barrier_init: BarrierInit = serde_json::from_slice(&value)
+        let barrier_key: BarrierKey = serde_json::from_slice(&value)
             .map_err(|e| VaultError::Serialization(e))?;
+        let record = stored.kdf();
+        if barrier_key.kdf.as_ref() != record {
+            return Err(VaultError::Vault("Barrier KDF parameters do not match".to_string()));
+        }
+
+        // Re-wrap entries in older layouts or using outdated KDF parameters
+        if stored.outdated() || record.map(|r| &r.params) != Some(&self.kdf) {
+            tracing::info!(
+                "Re-wrapping barrier key (format version {} to {}) with {} KDF",
+                stored.version(),
+                BARRIER_INIT_VERSION,
+                self.kdf.algorithm()
+            );
+            self.write_barrier_init(kek, barrier_key.key.as_slice()).await?;
+        }
 
         // Use the real encryption key
-        self.init_cipher(barrier_init.key.as_slice())?;
+        self.init_cipher(barrier_key.key.as_slice())?;
 
         let mut barrier_info = (*self.barrier_info.load_full()).clone();
+        barrier_info.seal_wrap_key = Some(seal_wrap::derive_key(kek));
         barrier_info.sealed = false;
         self.barrier_info.store(Arc::new(barrier_info));
 
@@ -220,6 +316,10 @@ impl SecurityBarrier for AESGCMBarrier {
     fn seal(&self) -> VaultResult<()> {
         self.reset_cipher()?;
         let mut barrier_info = (*self.barrier_info.load_full()).clone();
+        if let Some(ref mut key) = barrier_info.seal_wrap_key {
+            key.zeroize();
+        }
+        barrier_info.seal_wrap_key = None;
         barrier_info.sealed = true;
         self.barrier_info.store(Arc::new(barrier_info));
         Ok(())
@@ -231,7 +331,7 @@ impl SecurityBarrier for AESGCMBarrier {
             .ok_or_else(|| VaultError::Vault("Barrier not initialized".to_string()))?;
 
         if self.sealed()? {
-            return Err(VaultError::Vault("Barrier is sealed".to_string()));
+            return Err(VaultError::Sealed);
         }
 
         let mut hasher = Sha256::new();
@@ -240,11 +340,29 @@ impl SecurityBarrier for AESGCMBarrier {
     }
 }
 
+impl SealWrapper for AESGCMBarrier {
+    fn seal_wrap(&self, path: &str, plaintext: &[u8]) -> VaultResult<Vec<u8>> {
+        let barrier_info = self.barrier_info.load();
+        let key = barrier_info.seal_wrap_key.as_ref().ok_or(VaultError::Sealed)?;
+        seal_wrap::wrap(key, path, plaintext)
+    }
+
+    fn seal_unwrap(&self, path: &str, wrapped: &[u8]) -> VaultResult<Vec<u8>> {
+        let barrier_info = self.barrier_info.load();
+        let key = barrier_info.seal_wrap_key.as_ref().ok_or(VaultError::Sealed)?;
+        seal_wrap::unwrap(key, path, wrapped)
+    }
+}
+
 #[async_trait]
 impl StorageBackend for AESGCMBarrier {
+    fn storage_type(&self) -> String {
+        self.backend.storage_type()
+    }
+
     async fn get(&self, key: &str) -> VaultResult<Option<Vec<u8>>> {
         if self.sealed()? {
-            return Err(VaultError::Vault("Barrier is sealed".to_string()));
+            return Err(VaultError::Sealed);
         }
 
         let encrypted = self.backend.get(key).await?;
@@ -258,7 +376,7 @@ impl StorageBackend for AESGCMBarrier {
 
     async fn put(&self, key: &str, value: &[u8]) -> VaultResult<()> {
         if self.sealed()? {
-            return Err(VaultError::Vault("Barrier is sealed".to_string()));
+            return Err(VaultError::Sealed);
         }
 
         let ciphertext = self.encrypt(key, value)?;
@@ -268,18 +386,171 @@ impl StorageBackend for AESGCMBarrier {
 
     async fn delete(&self, key: &str) -> VaultResult<()> {
         if self.sealed()? {
-            return Err(VaultError::Vault("Barrier is sealed".to_string()));
+            return Err(VaultError::Sealed);
         }
         self.backend.delete(key).await
     }
 
     async fn list(&self, prefix: &str) -> VaultResult<Vec<String>> {
         if self.sealed()? {
-            return Err(VaultError::Vault("Barrier is sealed".to_string()));
+            return Err(VaultError::Sealed);
         }
         let mut keys = self.backend.list(prefix).await?;
         keys.sort();
         Ok(keys)
     }
+
+    async fn list_page(
+        &self,
+        prefix: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> VaultResult<Vec<String>> {
+        if self.sealed()? {
+            return Err(VaultError::Sealed);
+        }
+        self.backend.list_page(prefix, after, limit).await
+    }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::physical_inmem::InMemoryBackend;
+
+    fn cheap_argon2() -> KdfParams {
+        KdfParams::Argon2id { memory_kib: 64, iterations: 1, parallelism: 1 }
+    }
+
+    fn cheap_pbkdf2() -> KdfParams {
+        KdfParams::Pbkdf2Sha256 { iterations: 10 }
+    }
+
+    async fn round_trip(barrier: &AESGCMBarrier, kek: &[u8]) {
+        barrier.unseal(kek).await.unwrap();
+        barrier.put("secret/a", b"value").await.unwrap();
+        assert_eq!(barrier.get("secret/a").await.unwrap().unwrap(), b"value");
+        barrier.seal().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_init_records_kdf() {
+        let backend: Arc<dyn StorageBackend> = Arc::new(InMemoryBackend::new());
+        let barrier = AESGCMBarrier::with_kdf(backend, cheap_argon2());
+        let kek = barrier.generate_key().unwrap();
+
+        barrier.init(&kek).await.unwrap();
+        assert_eq!(barrier.stored_kdf().await.unwrap().unwrap().params, cheap_argon2());
+        round_trip(&barrier, &kek).await;
+
+        let wrong = barrier.generate_key().unwrap();
+        assert!(barrier.unseal(&wrong).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_changed_params_are_migrated_on_unseal() {
+        let backend: Arc<dyn StorageBackend> = Arc::new(InMemoryBackend::new());
+        let old = AESGCMBarrier::with_kdf(backend.clone(), cheap_pbkdf2());
+        let kek = old.generate_key().unwrap();
+        old.init(&kek).await.unwrap();
+        round_trip(&old, &kek).await;
+
+        let new = AESGCMBarrier::with_kdf(backend, cheap_argon2());
+        new.unseal(&kek).await.unwrap();
+        assert_eq!(new.get("secret/a").await.unwrap().unwrap(), b"value");
+        assert_eq!(new.stored_kdf().await.unwrap().unwrap().params, cheap_argon2());
+        new.seal().unwrap();
+        round_trip(&new, &kek).await;
+    }
+
+    #[tokio::test]
+    async fn test_legacy_entry_still_unseals() {
+        let backend: Arc<dyn StorageBackend> = Arc::new(InMemoryBackend::new());
+        let barrier = AESGCMBarrier::with_kdf(backend.clone(), cheap_argon2());
+        let kek = barrier.generate_key().unwrap();
+
+        // Version 1 layout: barrier init encrypted directly with the KEK
+        let legacy = BarrierKey { version: 1, key: barrier.generate_key().unwrap().to_vec(), kdf: None };
+        barrier.init_cipher(&kek).unwrap();
+        let value = barrier.encrypt(BARRIER_INIT_PATH, &serde_json::to_vec(&legacy).unwrap()).unwrap();
+        barrier.reset_cipher().unwrap();
+        backend.put(BARRIER_INIT_PATH, &value).await.unwrap();
+
+        round_trip(&barrier, &kek).await;
+        assert_eq!(barrier.stored_kdf().await.unwrap().unwrap().params, cheap_argon2());
+        assert_eq!(barrier.stored_init().await.unwrap().version(), BARRIER_INIT_VERSION);
+        round_trip(&barrier, &kek).await;
+    }
+
+    #[tokio::test]
+    async fn test_unversioned_entry_is_migrated() {
+        let backend: Arc<dyn StorageBackend> = Arc::new(InMemoryBackend::new());
+        let barrier = AESGCMBarrier::with_kdf(backend.clone(), cheap_pbkdf2());
+        let kek = barrier.generate_key().unwrap();
+        barrier.init(&kek).await.unwrap();
+        round_trip(&barrier, &kek).await;
+
+        // Version 2 layout: the same envelope without version or seal type
+        let StoredBarrierInit::Wrapped(current) = barrier.stored_init().await.unwrap() else {
+            panic!("expected a wrapped entry");
+        };
+        let v2 = serde_json::json!({ "kdf": current.kdf, "ciphertext": current.ciphertext });
+        backend.put(BARRIER_INIT_PATH, &serde_json::to_vec(&v2).unwrap()).await.unwrap();
+        assert_eq!(barrier.stored_init().await.unwrap().version(), 2);
+
+        barrier.unseal(&kek).await.unwrap();
+        assert_eq!(barrier.get("secret/a").await.unwrap().unwrap(), b"value");
+        assert_eq!(barrier.stored_init().await.unwrap().version(), BARRIER_INIT_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_newer_format_is_refused() {
+        let backend: Arc<dyn StorageBackend> = Arc::new(InMemoryBackend::new());
+        let barrier = AESGCMBarrier::with_kdf(backend.clone(), cheap_pbkdf2());
+        let kek = barrier.generate_key().unwrap();
+        barrier.init(&kek).await.unwrap();
+
+        let mut entry: serde_json::Value =
+            serde_json::from_slice(&backend.get(BARRIER_INIT_PATH).await.unwrap().unwrap()).unwrap();
+        entry["version"] = serde_json::json!(BARRIER_INIT_VERSION + 1);
+        backend.put(BARRIER_INIT_PATH, &serde_json::to_vec(&entry).unwrap()).await.unwrap();
+
+        let err = barrier.unseal(&kek).await.unwrap_err();
+        assert!(err.to_string().contains("Upgrade required"), "{}", err);
+        assert!(barrier.sealed().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tampered_kdf_record_is_rejected() {
+        let backend: Arc<dyn StorageBackend> = Arc::new(InMemoryBackend::new());
+        let barrier = AESGCMBarrier::with_kdf(backend.clone(), cheap_pbkdf2());
+        let kek = barrier.generate_key().unwrap();
+        barrier.init(&kek).await.unwrap();
+
+        let mut wrapped: BarrierInit =
+            serde_json::from_slice(&backend.get(BARRIER_INIT_PATH).await.unwrap().unwrap()).unwrap();
+        wrapped.kdf.params = KdfParams::Pbkdf2Sha256 { iterations: 11 };
+        backend.put(BARRIER_INIT_PATH, &serde_json::to_vec(&wrapped).unwrap()).await.unwrap();
+
+        assert!(barrier.unseal(&kek).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_seal_wrap_key_is_only_held_while_unsealed() {
+        let backend: Arc<dyn StorageBackend> = Arc::new(InMemoryBackend::new());
+        let barrier = AESGCMBarrier::with_kdf(backend, cheap_pbkdf2());
+        let kek = barrier.generate_key().unwrap();
+        barrier.init(&kek).await.unwrap();
+        assert!(matches!(barrier.seal_wrap("secret/data/a", b"value"), Err(VaultError::Sealed)));
+
+        barrier.unseal(&kek).await.unwrap();
+        let wrapped = barrier.seal_wrap("secret/data/a", b"value").unwrap();
+        barrier.seal().unwrap();
+        assert!(matches!(barrier.seal_unwrap("secret/data/a", &wrapped), Err(VaultError::Sealed)));
+
+        // Derived from the unseal key, so it is the same after every unseal
+        barrier.unseal(&kek).await.unwrap();
+        assert_eq!(barrier.seal_unwrap("secret/data/a", &wrapped).unwrap(), b"value");
+    }
+}
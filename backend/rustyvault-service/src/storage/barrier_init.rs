@@ -0,0 +1,168 @@
+//! On-disk format of the barrier-init entry
+//!
+//! The entry at `BARRIER_INIT_PATH` holds the barrier's encryption key,
+//! wrapped with a key derived from the unseal key. Its plaintext envelope is a
+//! [`BarrierInit`] naming the layout version, the seal type and the KDF, so a
+//! later layout can read and migrate older entries instead of failing to
+//! unseal. The layouts so far:
+//!
+//! 1. Raw barrier ciphertext of the key, encrypted with the unseal key itself
+//! 2. JSON `{"kdf", "ciphertext"}` with no version field
+//! 3. [`BarrierInit`], the current layout
+//!
+//! [`StoredBarrierInit::decode`] reads all of them and refuses versions newer
+//! than this build understands. Entries are only ever written in the current
+//! layout; unsealing rewrites older ones.
+
+use serde::{Deserialize, Serialize};
+use shared::infrastructure::encryption::KdfRecord;
+
+use crate::errors::{VaultError, VaultResult};
+
+/// Layout version written by this build, and the newest it can read
+pub const BARRIER_INIT_VERSION: u32 = 3;
+
+/// How the unseal key is held
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SealType {
+    /// Operators hold it, split into Shamir shares (or as one key)
+    Shamir,
+}
+
+/// The barrier-init entry as stored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BarrierInit {
+    pub version: u32,
+    pub seal_type: SealType,
+    /// KDF, parameters and salt deriving the wrapping key from the unseal key
+    pub kdf: KdfRecord,
+    /// Hex-encoded barrier ciphertext of the wrapped barrier key
+    pub ciphertext: String,
+}
+
+/// Version 2 layout, which predates the version field
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BarrierInitV2 {
+    kdf: KdfRecord,
+    ciphertext: String,
+}
+
+impl BarrierInit {
+    /// A current-layout entry for `ciphertext` wrapped under `kdf`
+    pub fn new(seal_type: SealType, kdf: KdfRecord, ciphertext: &[u8]) -> Self {
+        BarrierInit {
+            version: BARRIER_INIT_VERSION,
+            seal_type,
+            kdf,
+            ciphertext: hex::encode(ciphertext),
+        }
+    }
+
+    pub fn encode(&self) -> VaultResult<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    pub fn ciphertext(&self) -> VaultResult<Vec<u8>> {
+        hex::decode(&self.ciphertext).map_err(|e| VaultError::Vault(format!("Invalid barrier init: {}", e)))
+    }
+}
+
+/// A barrier-init entry as read, in whichever layout it was written
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoredBarrierInit {
+    /// Version 1: ciphertext under the unseal key itself, with no KDF
+    Legacy(Vec<u8>),
+    /// Version 2 or later, read into the current struct
+    Wrapped(BarrierInit),
+}
+
+impl StoredBarrierInit {
+    pub fn decode(raw: &[u8]) -> VaultResult<Self> {
+        // Barrier ciphertext starts with a zero epoch byte, so it never parses
+        let Ok(value) = serde_json::from_slice::<serde_json::Value>(raw) else {
+            return Ok(StoredBarrierInit::Legacy(raw.to_vec()));
+        };
+
+        let Some(version) = value.get("version") else {
+            let v2: BarrierInitV2 = serde_json::from_value(value)?;
+            return Ok(StoredBarrierInit::Wrapped(BarrierInit {
+                version: 2,
+                seal_type: SealType::Shamir,
+                kdf: v2.kdf,
+                ciphertext: v2.ciphertext,
+            }));
+        };
+        let version = version
+            .as_u64()
+            .ok_or_else(|| VaultError::Vault("Invalid barrier init: version is not a number".to_string()))?;
+        if version > u64::from(BARRIER_INIT_VERSION) {
+            return Err(VaultError::Vault(format!(
+                "Upgrade required: barrier init data is format version {}, but this build reads up to version {}; \
+                 run a newer rustyvault-service to unseal",
+                version, BARRIER_INIT_VERSION
+            )));
+        }
+        Ok(StoredBarrierInit::Wrapped(serde_json::from_value(value)?))
+    }
+
+    pub fn version(&self) -> u32 {
+        match self {
+            StoredBarrierInit::Legacy(_) => 1,
+            StoredBarrierInit::Wrapped(init) => init.version,
+        }
+    }
+
+    /// KDF the entry is wrapped with; `None` for version 1
+    pub fn kdf(&self) -> Option<&KdfRecord> {
+        match self {
+            StoredBarrierInit::Legacy(_) => None,
+            StoredBarrierInit::Wrapped(init) => Some(&init.kdf),
+        }
+    }
+
+    /// Whether the entry should be rewritten in the current layout
+    pub fn outdated(&self) -> bool {
+        self.version() < BARRIER_INIT_VERSION
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::infrastructure::encryption::KdfParams;
+
+    fn record() -> KdfRecord {
+        KdfRecord::generate(KdfParams::Pbkdf2Sha256 { iterations: 10 }).unwrap()
+    }
+
+    #[test]
+    fn test_reads_every_layout() {
+        let legacy = vec![0, 0, 0, 1, 2, 9, 9, 9];
+        assert_eq!(StoredBarrierInit::decode(&legacy).unwrap(), StoredBarrierInit::Legacy(legacy.clone()));
+
+        let kdf = record();
+        let v2 = serde_json::to_vec(&serde_json::json!({ "kdf": kdf, "ciphertext": "00ff" })).unwrap();
+        let stored = StoredBarrierInit::decode(&v2).unwrap();
+        assert_eq!(stored.version(), 2);
+        assert!(stored.outdated());
+        assert_eq!(stored.kdf(), Some(&kdf));
+
+        let current = BarrierInit::new(SealType::Shamir, kdf, &[0, 255]);
+        let stored = StoredBarrierInit::decode(&current.encode().unwrap()).unwrap();
+        assert!(!stored.outdated());
+        assert_eq!(stored, StoredBarrierInit::Wrapped(current.clone()));
+        assert_eq!(current.ciphertext().unwrap(), vec![0, 255]);
+    }
+
+    #[test]
+    fn test_future_versions_require_an_upgrade() {
+        let mut future = serde_json::to_value(BarrierInit::new(SealType::Shamir, record(), &[1])).unwrap();
+        future["version"] = serde_json::json!(BARRIER_INIT_VERSION + 1);
+        future["seal_type"] = serde_json::json!("transit");
+
+        let err = StoredBarrierInit::decode(&serde_json::to_vec(&future).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("Upgrade required"), "{}", err);
+    }
+}
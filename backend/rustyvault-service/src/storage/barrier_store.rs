@@ -26,6 +26,10 @@ impl BarrierStore {
 
 #[async_trait]
 impl StorageBackend for BarrierStore {
+    fn storage_type(&self) -> String {
+        self.barrier.storage_type()
+    }
+
     async fn get(&self, key: &str) -> VaultResult<Option<Vec<u8>>> {
         self.barrier.get(key).await
     }
@@ -41,5 +45,14 @@ impl StorageBackend for BarrierStore {
     async fn list(&self, prefix: &str) -> VaultResult<Vec<String>> {
         self.barrier.list(prefix).await
     }
+
+    async fn list_page(
+        &self,
+        prefix: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> VaultResult<Vec<String>> {
+        self.barrier.list_page(prefix, after, limit).await
+    }
 }
 
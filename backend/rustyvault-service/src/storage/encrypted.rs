@@ -0,0 +1,240 @@
+//! Optional encryption at rest for any physical backend
+//!
+//! [`EncryptedBackend`] wraps the physical backend under the barrier and
+//! seals every value with AES-256-GCM under a storage key, so a copied data
+//! directory reveals nothing, not even barrier metadata, while the vault is
+//! sealed. The storage key is independent of the unseal keys.
+//!
+//! # Key management
+//!
+//! The key is 32 random bytes, base64-encoded (`openssl rand -base64 32`),
+//! given either in `VAULT_STORAGE_ENCRYPTION_KEY` or in a file named by
+//! `VAULT_STORAGE_ENCRYPTION_KEY_FILE`. Prefer the file, provisioned by the
+//! OS keyring or secret manager (e.g. a systemd credential or a mounted
+//! Kubernetes secret) and readable only by the vault user. Keep it off the
+//! disk that holds the data, or the second layer protects nothing.
+//!
+//! Once enabled, storage carries a marker entry at [`MARKER_PATH`] and the
+//! service refuses to start without the right key. Losing the key loses the
+//! data; back it up alongside, not instead of, the unseal keys. Values
+//! written before encryption was enabled stay readable and are encrypted the
+//! next time they are written. There is no rotation yet: changing the key
+//! means restoring a snapshot into fresh storage.
+//!
+//! # Format
+//!
+//! ```text
+//! "HVFE" | version 0x01 | nonce (12 bytes) | ciphertext and tag
+//! ```
+//!
+//! The storage key of the entry is the associated data, so a value cannot be
+//! moved to another key.
+
+use std::sync::Arc;
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Nonce,
+};
+use async_trait::async_trait;
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use zeroize::Zeroizing;
+use crate::errors::{VaultError, VaultResult};
+use crate::storage::StorageBackend;
+
+/// Prefix of every encrypted value; barrier ciphertext and plaintext JSON
+/// never start with it
+const MAGIC: &[u8; 4] = b"HVFE";
+const FORMAT_VERSION: u8 = 1;
+const NONCE_SIZE: usize = 12;
+const HEADER_SIZE: usize = MAGIC.len() + 1 + NONCE_SIZE;
+
+/// Marks storage as encrypted and holds a check value for the key
+pub const MARKER_PATH: &str = "core/storage-encryption";
+const MARKER_PLAINTEXT: &[u8] = b"health-v1 storage encryption";
+
+const MISSING_KEY: &str = "Storage is encrypted at rest; set VAULT_STORAGE_ENCRYPTION_KEY or VAULT_STORAGE_ENCRYPTION_KEY_FILE";
+
+/// AES-256-GCM under the storage key
+pub struct StorageCipher {
+    cipher: Aes256Gcm,
+}
+
+impl StorageCipher {
+    pub fn new(key: &[u8]) -> VaultResult<Self> {
+        if key.len() != 32 {
+            return Err(VaultError::Config(format!(
+                "Storage encryption key must be 32 bytes, got {}",
+                key.len()
+            )));
+        }
+        let cipher = Aes256Gcm::new_from_slice(key)
+            .map_err(|e| VaultError::Config(format!("Invalid storage encryption key: {}", e)))?;
+        Ok(Self { cipher })
+    }
+
+    /// `None` unless `VAULT_STORAGE_ENCRYPTION_KEY` or
+    /// `VAULT_STORAGE_ENCRYPTION_KEY_FILE` is set
+    pub fn from_env() -> VaultResult<Option<Self>> {
+        let encoded = match std::env::var("VAULT_STORAGE_ENCRYPTION_KEY").ok().filter(|k| !k.is_empty()) {
+            Some(key) => Zeroizing::new(key),
+            None => match std::env::var("VAULT_STORAGE_ENCRYPTION_KEY_FILE").ok().filter(|p| !p.is_empty()) {
+                Some(path) => Zeroizing::new(std::fs::read_to_string(&path).map_err(|e| {
+                    VaultError::Config(format!("Cannot read storage encryption key file {}: {}", path, e))
+                })?),
+                None => return Ok(None),
+            },
+        };
+        let key = Zeroizing::new(
+            STANDARD
+                .decode(encoded.trim())
+                .map_err(|_| VaultError::Config("Storage encryption key is not valid base64".to_string()))?,
+        );
+        Self::new(&key).map(Some)
+    }
+
+    /// Whether `data` was written by [`encrypt`](Self::encrypt)
+    pub fn is_encrypted(data: &[u8]) -> bool {
+        data.len() >= HEADER_SIZE && data.starts_with(MAGIC)
+    }
+
+    pub fn encrypt(&self, key: &str, plaintext: &[u8]) -> VaultResult<Vec<u8>> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, Payload { msg: plaintext, aad: key.as_bytes() })
+            .map_err(|_| VaultError::Encryption("Storage encryption failed".to_string()))?;
+        let mut out = Vec::with_capacity(HEADER_SIZE + ciphertext.len());
+        out.extend_from_slice(MAGIC);
+        out.push(FORMAT_VERSION);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    pub fn decrypt(&self, key: &str, data: &[u8]) -> VaultResult<Vec<u8>> {
+        if !Self::is_encrypted(data) || data[MAGIC.len()] != FORMAT_VERSION {
+            return Err(VaultError::Storage(format!("{} is not a supported encrypted value", key)));
+        }
+        let nonce = Nonce::from_slice(&data[MAGIC.len() + 1..HEADER_SIZE]);
+        self.cipher
+            .decrypt(nonce, Payload { msg: &data[HEADER_SIZE..], aad: key.as_bytes() })
+            .map_err(|_| VaultError::Encryption(format!("{} failed storage decryption (wrong key or corrupt value)", key)))
+    }
+}
+
+/// Physical storage whose values are encrypted at rest
+pub struct EncryptedBackend {
+    inner: Arc<dyn StorageBackend>,
+    cipher: StorageCipher,
+}
+
+impl EncryptedBackend {
+    /// Put `inner` behind `cipher`, if there is one
+    ///
+    /// Checks the marker first: encrypted storage without a key, or with a
+    /// different key than it was written with, is a configuration error
+    /// rather than a barrier that later fails to decrypt. The first start
+    /// with a key writes the marker.
+    pub async fn open(
+        inner: Arc<dyn StorageBackend>,
+        cipher: Option<StorageCipher>,
+    ) -> VaultResult<Arc<dyn StorageBackend>> {
+        let marker = inner.get(MARKER_PATH).await?;
+        let Some(cipher) = cipher else {
+            return match marker {
+                Some(_) => Err(VaultError::Config(MISSING_KEY.to_string())),
+                None => Ok(inner),
+            };
+        };
+        match marker {
+            Some(marker) => {
+                let verified = cipher
+                    .decrypt(MARKER_PATH, &marker)
+                    .is_ok_and(|plaintext| plaintext == MARKER_PLAINTEXT);
+                if !verified {
+                    return Err(VaultError::Config(
+                        "Storage encryption key does not match the one the data was written with".to_string(),
+                    ));
+                }
+            }
+            None => inner.put(MARKER_PATH, &cipher.encrypt(MARKER_PATH, MARKER_PLAINTEXT)?).await?,
+        }
+        Ok(Arc::new(Self { inner, cipher }))
+    }
+}
+
+#[async_trait]
+impl StorageBackend for EncryptedBackend {
+    fn storage_type(&self) -> String {
+        self.inner.storage_type()
+    }
+
+    async fn get(&self, key: &str) -> VaultResult<Option<Vec<u8>>> {
+        match self.inner.get(key).await? {
+            // Written before encryption was enabled
+            Some(value) if !StorageCipher::is_encrypted(&value) => Ok(Some(value)),
+            Some(value) => self.cipher.decrypt(key, &value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn put(&self, key: &str, value: &[u8]) -> VaultResult<()> {
+        self.inner.put(key, &self.cipher.encrypt(key, value)?).await
+    }
+
+    async fn delete(&self, key: &str) -> VaultResult<()> {
+        self.inner.delete(key).await
+    }
+
+    async fn list(&self, prefix: &str) -> VaultResult<Vec<String>> {
+        self.inner.list(prefix).await
+    }
+
+    async fn list_page(
+        &self,
+        prefix: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> VaultResult<Vec<String>> {
+        self.inner.list_page(prefix, after, limit).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::physical_inmem::InMemoryBackend;
+
+    #[test]
+    fn test_round_trip_is_bound_to_the_storage_key() {
+        let cipher = StorageCipher::new(&[7u8; 32]).unwrap();
+        let sealed = cipher.encrypt("logical/abc/data", b"barrier ciphertext").unwrap();
+        assert!(StorageCipher::is_encrypted(&sealed));
+        assert_eq!(cipher.decrypt("logical/abc/data", &sealed).unwrap(), b"barrier ciphertext");
+        assert!(cipher.decrypt("logical/abc/other", &sealed).is_err());
+        assert!(!StorageCipher::is_encrypted(b"{\"version\":1}"));
+        assert!(StorageCipher::new(&[0u8; 16]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_open_requires_the_key_the_storage_was_written_with() {
+        let physical: Arc<dyn StorageBackend> = Arc::new(InMemoryBackend::new());
+        physical.put("core/legacy", b"plain").await.unwrap();
+
+        let key = [1u8; 32];
+        let encrypted = EncryptedBackend::open(physical.clone(), Some(StorageCipher::new(&key).unwrap()))
+            .await
+            .unwrap();
+        encrypted.put("core/keyring", b"secret").await.unwrap();
+        assert!(StorageCipher::is_encrypted(&physical.get("core/keyring").await.unwrap().unwrap()));
+        assert_eq!(encrypted.get("core/keyring").await.unwrap().unwrap(), b"secret");
+        assert_eq!(encrypted.get("core/legacy").await.unwrap().unwrap(), b"plain");
+
+        let missing = EncryptedBackend::open(physical.clone(), None).await.err().unwrap();
+        assert!(missing.to_string().contains("VAULT_STORAGE_ENCRYPTION_KEY"));
+        assert!(EncryptedBackend::open(physical.clone(), Some(StorageCipher::new(&[2u8; 32]).unwrap()))
+            .await
+            .is_err());
+        assert!(EncryptedBackend::open(physical, Some(StorageCipher::new(&key).unwrap())).await.is_ok());
+    }
+}
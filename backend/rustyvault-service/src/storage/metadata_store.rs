@@ -17,8 +17,25 @@ impl MetadataStore {
     }
 }
 
+/// `LIKE` pattern matching every key under `prefix`, taken literally
+fn prefix_pattern(prefix: &str) -> String {
+    let mut pattern = String::with_capacity(prefix.len() + 1);
+    for c in prefix.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
 #[async_trait]
 impl StorageBackend for MetadataStore {
+    fn storage_type(&self) -> String {
+        "postgresql".to_string()
+    }
+
     async fn get(&self, key: &str) -> VaultResult<Option<Vec<u8>>> {
         let result = sqlx::query_scalar::<_, Option<Vec<u8>>>(
             "SELECT value FROM vault_metadata WHERE key = $1"
@@ -53,9 +70,9 @@ impl StorageBackend for MetadataStore {
     }
 
     async fn list(&self, prefix: &str) -> VaultResult<Vec<String>> {
-        let pattern = format!("{}%", prefix);
+        let pattern = prefix_pattern(prefix);
         let keys = sqlx::query_scalar::<_, String>(
-            "SELECT key FROM vault_metadata WHERE key LIKE $1 ORDER BY key"
+            "SELECT key FROM vault_metadata WHERE key LIKE $1 ESCAPE '\\' ORDER BY key"
         )
         .bind(pattern)
         .fetch_all(self.pool.as_ref())
@@ -63,5 +80,37 @@ impl StorageBackend for MetadataStore {
 
         Ok(keys)
     }
+
+    async fn list_page(
+        &self,
+        prefix: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> VaultResult<Vec<String>> {
+        let pattern = prefix_pattern(prefix);
+        let keys = sqlx::query_scalar::<_, String>(
+            "SELECT key FROM vault_metadata
+             WHERE key LIKE $1 ESCAPE '\\' AND ($2::TEXT IS NULL OR key > $2)
+             ORDER BY key LIMIT $3"
+        )
+        .bind(pattern)
+        .bind(after)
+        .bind(limit as i64)
+        .fetch_all(self.pool.as_ref())
+        .await?;
+
+        Ok(keys)
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_pattern_escapes_wildcards() {
+        assert_eq!(prefix_pattern("secret/"), "secret/%");
+        assert_eq!(prefix_pattern("a_b%c\\d/"), "a\\_b\\%c\\\\d/%");
+        assert_eq!(prefix_pattern(""), "%");
+    }
+}
@@ -8,13 +8,22 @@ pub mod barrier_store;
 pub mod adapter;
 pub mod barrier;
 pub mod barrier_aes_gcm;
+pub mod barrier_init;
+pub mod seal_wrap;
 pub mod physical_file;
+#[cfg(test)]
+pub mod physical_inmem;
+pub mod snapshot;
+pub mod encrypted;
 
 pub use storage_backend::StorageBackend;
 pub use metadata_store::MetadataStore;
 pub use barrier_store::BarrierStore;
 pub use adapter::StorageAdapter;
 pub use barrier::SecurityBarrier;
+pub use snapshot::SnapshotBackend;
+pub use encrypted::{EncryptedBackend, StorageCipher};
+pub use seal_wrap::SealWrapper;
 
 /// Path for barrier initialization data
 pub const BARRIER_INIT_PATH: &str = "core/barrier-init";
@@ -0,0 +1,78 @@
+//! In-memory physical storage backend
+//!
+//! Mirrors the listing semantics of the file backend (one level at a time,
+//! sub-directories suffixed with `/`). Only built for tests.
+
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use async_trait::async_trait;
+use crate::errors::{VaultError, VaultResult};
+use crate::storage::StorageBackend;
+
+#[derive(Default)]
+pub struct InMemoryBackend {
+    entries: RwLock<BTreeMap<String, Vec<u8>>>,
+}
+
+impl InMemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl StorageBackend for InMemoryBackend {
+    fn storage_type(&self) -> String {
+        "inmem".to_string()
+    }
+
+    async fn get(&self, key: &str) -> VaultResult<Option<Vec<u8>>> {
+        if key.starts_with('/') {
+            return Err(VaultError::Storage("Key cannot start with /".to_string()));
+        }
+        Ok(self.entries.read().unwrap().get(key).cloned())
+    }
+
+    async fn put(&self, key: &str, value: &[u8]) -> VaultResult<()> {
+        if key.starts_with('/') {
+            return Err(VaultError::Storage("Key cannot start with /".to_string()));
+        }
+        self.entries.write().unwrap().insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> VaultResult<()> {
+        if key.starts_with('/') {
+            return Err(VaultError::Storage("Key cannot start with /".to_string()));
+        }
+        self.entries.write().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> VaultResult<Vec<String>> {
+        if prefix.starts_with('/') {
+            return Err(VaultError::Storage("Prefix cannot start with /".to_string()));
+        }
+
+        let dir = match prefix.trim_end_matches('/') {
+            "" => String::new(),
+            p => format!("{}/", p),
+        };
+
+        let entries = self.entries.read().unwrap();
+        let mut names: Vec<String> = Vec::new();
+        for key in entries.range(dir.clone()..).map(|(k, _)| k) {
+            let Some(rest) = key.strip_prefix(&dir) else {
+                break;
+            };
+            let name = match rest.find('/') {
+                Some(idx) => format!("{}{}", dir, &rest[..=idx]),
+                None => key.clone(),
+            };
+            if names.last() != Some(&name) {
+                names.push(name);
+            }
+        }
+        Ok(names)
+    }
+}
@@ -0,0 +1,97 @@
+//! Seal wrapping of individual entries
+//!
+//! Barrier encryption protects everything in storage with the barrier key,
+//! which lives in memory for as long as the vault is unsealed. Seal wrapping
+//! adds a second AES-256-GCM layer, applied before the barrier's, under a key
+//! derived from the unseal key instead. A copy of storage plus the barrier
+//! key (a memory dump, a leaked backup of the barrier-init entry's contents)
+//! is then not enough to read a wrapped entry: that takes the unseal key,
+//! i.e. the seal mechanism itself.
+//!
+//! Only callers that ask for it are wrapped; today that is KV mounts
+//! configured with `seal_wrap`.
+//!
+//! # Format
+//!
+//! ```text
+//! version 0x01 | nonce (12 bytes) | ciphertext and tag
+//! ```
+//!
+//! The storage path of the entry is the associated data, so a wrapped value
+//! cannot be moved to another path.
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Nonce,
+};
+use ring::hmac;
+use zeroize::Zeroizing;
+
+use crate::errors::{VaultError, VaultResult};
+
+const FORMAT_VERSION: u8 = 1;
+const NONCE_SIZE: usize = 12;
+const HEADER_SIZE: usize = 1 + NONCE_SIZE;
+
+/// Domain separation for the derived key, so it never equals another key
+/// taken from the unseal key
+const KEY_LABEL: &[u8] = b"health-v1 seal wrap";
+
+/// Wraps and unwraps entries with the seal's key
+pub trait SealWrapper: Send + Sync {
+    /// Encrypt `plaintext` for storage at `path`
+    fn seal_wrap(&self, path: &str, plaintext: &[u8]) -> VaultResult<Vec<u8>>;
+
+    /// Decrypt a value produced by [`SealWrapper::seal_wrap`] for `path`
+    fn seal_unwrap(&self, path: &str, wrapped: &[u8]) -> VaultResult<Vec<u8>>;
+}
+
+/// The seal-wrap key for an unseal key
+pub fn derive_key(unseal_key: &[u8]) -> Zeroizing<Vec<u8>> {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, unseal_key);
+    Zeroizing::new(hmac::sign(&key, KEY_LABEL).as_ref().to_vec())
+}
+
+pub fn wrap(key: &[u8], path: &str, plaintext: &[u8]) -> VaultResult<Vec<u8>> {
+    let cipher = cipher(key)?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: plaintext, aad: path.as_bytes() })
+        .map_err(|_| VaultError::Vault("Seal wrap failed".to_string()))?;
+
+    let mut out = Vec::with_capacity(HEADER_SIZE + ciphertext.len());
+    out.push(FORMAT_VERSION);
+    out.extend_from_slice(nonce.as_slice());
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+pub fn unwrap(key: &[u8], path: &str, wrapped: &[u8]) -> VaultResult<Vec<u8>> {
+    if wrapped.len() < HEADER_SIZE || wrapped[0] != FORMAT_VERSION {
+        return Err(VaultError::Vault(format!("Invalid seal-wrapped value at {}", path)));
+    }
+    let nonce = Nonce::from_slice(&wrapped[1..HEADER_SIZE]);
+    cipher(key)?
+        .decrypt(nonce, Payload { msg: &wrapped[HEADER_SIZE..], aad: path.as_bytes() })
+        .map_err(|_| VaultError::Vault(format!("Seal unwrap failed for {}", path)))
+}
+
+fn cipher(key: &[u8]) -> VaultResult<Aes256Gcm> {
+    Aes256Gcm::new_from_slice(key).map_err(|e| VaultError::Vault(format!("Failed to create cipher: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrapped_values_need_the_key_and_path() {
+        let key = derive_key(b"unseal-key");
+        let wrapped = wrap(&key, "secret/data/db", b"{\"password\":\"s3cr3t\"}").unwrap();
+        assert_eq!(unwrap(&key, "secret/data/db", &wrapped).unwrap(), b"{\"password\":\"s3cr3t\"}");
+
+        assert!(unwrap(&derive_key(b"another-key"), "secret/data/db", &wrapped).is_err());
+        assert!(unwrap(&key, "secret/data/other", &wrapped).is_err());
+        assert!(unwrap(&key, "secret/data/db", &wrapped[..HEADER_SIZE]).is_err());
+    }
+}
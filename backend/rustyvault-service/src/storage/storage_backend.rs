@@ -16,6 +16,36 @@ pub trait StorageBackend: Send + Sync {
     async fn delete(&self, key: &str) -> VaultResult<()>;
 
     /// List keys with prefix
+    ///
+    /// Keys are returned in full, prefix included. Backends with a
+    /// hierarchy list only the level under `prefix` and return each
+    /// directory once with a trailing `/`, so callers that want every key
+    /// list those again; flat backends may return nested keys directly.
     async fn list(&self, prefix: &str) -> VaultResult<Vec<String>>;
+
+    /// Physical backend the data ends up in, reported in the seal status
+    fn storage_type(&self) -> String {
+        "unknown".to_string()
+    }
+
+    /// List one page of keys with prefix, in lexical order
+    ///
+    /// Returns at most `limit` keys that sort strictly after `after` (a full
+    /// key as returned by a previous page). The default implementation
+    /// materializes the full listing; backends that can seek should override it.
+    async fn list_page(
+        &self,
+        prefix: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> VaultResult<Vec<String>> {
+        let mut keys = self.list(prefix).await?;
+        keys.sort();
+        Ok(keys
+            .into_iter()
+            .filter(|k| after.is_none_or(|a| k.as_str() > a))
+            .take(limit)
+            .collect())
+    }
 }
 
@@ -0,0 +1,34 @@
+//! Sealing and opening a 64 KiB payload with each supported AEAD
+//!
+//! AES-256-GCM wins on CPUs with AES-NI; ChaCha20-Poly1305 is the choice
+//! where that is missing.
+//!
+//! Run with `cargo bench -p shared --bench aead`.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use shared::infrastructure::encryption::aead::open;
+use shared::infrastructure::encryption::AeadAlgorithm;
+
+const PAYLOAD_LEN: usize = 64 * 1024;
+
+fn bench_seal_open(c: &mut Criterion) {
+    let key = [9u8; 32];
+    let payload = vec![0x5au8; PAYLOAD_LEN];
+
+    let mut group = c.benchmark_group("aead_seal_open_64k");
+    group.throughput(Throughput::Bytes(PAYLOAD_LEN as u64));
+    for algorithm in [AeadAlgorithm::Aes256Gcm, AeadAlgorithm::ChaCha20Poly1305] {
+        group.bench_function(algorithm.to_string(), |b| {
+            b.iter(|| {
+                let blob = algorithm.seal(&key, black_box(&payload)).unwrap();
+                black_box(open(&key, &blob).unwrap())
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_seal_open);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+//! Keeping the authorization graph current after one tuple changes
+//!
+//! Compares rebuilding a graph of 100k edges from every relationship, which
+//! is what a cache invalidation costs, with applying the single changed
+//! edge in place as `GraphCache` now does.
+//!
+//! Run with `cargo bench -p shared --bench graph_update`.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use shared::domain::entities::Relationship;
+use shared::infrastructure::zanzibar::AuthorizationGraph;
+
+const EDGES: usize = 100_000;
+
+fn relationships() -> Vec<Relationship> {
+    (0..EDGES)
+        .map(|i| {
+            let (user, relation, object) = match i % 4 {
+                0 => (format!("user:{}", i % 20_000), "member", format!("group:{}", i % 500)),
+                1 => (format!("group:{}", i % 500), "has_role", format!("role:{}", i % 50)),
+                2 => (format!("role:{}", i % 50), "view", format!("document:{}", i)),
+                _ => (format!("user:{}", i % 20_000), "edit", format!("document:{}", i)),
+            };
+            Relationship::new(user, relation.to_string(), object)
+        })
+        .collect()
+}
+
+fn bench_graph_update(c: &mut Criterion) {
+    let relationships = relationships();
+    let graph = AuthorizationGraph::build_from_relationships(relationships.clone());
+    let added = Relationship::new("user:42".to_string(), "member".to_string(), "group:7".to_string());
+    let mut revoked = relationships[EDGES / 2].clone();
+    revoked.soft_delete(None);
+
+    let mut group = c.benchmark_group("graph_single_edge_change");
+    group.sample_size(10);
+    group.bench_function("full_rebuild", |b| {
+        b.iter_batched(
+            || relationships.clone(),
+            |relationships| black_box(AuthorizationGraph::build_from_relationships(relationships)),
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("incremental_add", |b| {
+        b.iter_batched_ref(
+            || graph.clone(),
+            |graph| graph.upsert_relationship(black_box(added.clone())),
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("incremental_remove", |b| {
+        b.iter_batched_ref(
+            || graph.clone(),
+            |graph| graph.upsert_relationship(black_box(revoked.clone())),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_graph_update);
+criterion_main!(benches);
@@ -0,0 +1,188 @@
+//! Feature flags for shipping endpoints dark
+//!
+//! Flags are set through `FEATURE_FLAGS` (the reloadable `runtime.features`
+//! section) and looked up by name. Anything not configured is off, so a
+//! misspelt lookup fails closed; configured names missing from
+//! [`KNOWN_FEATURES`] are logged at startup. Admins can override flags for a
+//! single request with the `X-Feature-Flags` header, e.g.
+//! `X-Feature-Flags: graph_debug_endpoints=on`, to test a dark endpoint
+//! without enabling it for everyone.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::HeaderMap;
+use crate::config::settings::RuntimeSettings;
+use crate::shared::{AppState, RequestContext};
+
+/// Request header carrying per-request overrides (admins only)
+pub const FEATURE_OVERRIDE_HEADER: &str = "x-feature-flags";
+
+/// Role allowed to override flags per request
+const OVERRIDE_ROLE: &str = "admin";
+
+/// Graph cache stats, refresh/invalidate and permission path debugging
+pub const GRAPH_DEBUG_ENDPOINTS: &str = "graph_debug_endpoints";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeatureDefinition {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// Every flag the code consults
+pub const KNOWN_FEATURES: [FeatureDefinition; 1] = [FeatureDefinition {
+    name: GRAPH_DEBUG_ENDPOINTS,
+    description: "Graph cache stats, refresh/invalidate and permission path debugging under /v1/admin/graph",
+}];
+
+/// Effective flags for one request (or for background work, without overrides)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureFlags {
+    values: BTreeMap<String, bool>,
+}
+
+impl FeatureFlags {
+    pub fn new(values: BTreeMap<String, bool>) -> Self {
+        Self { values }
+    }
+
+    pub fn from_runtime(runtime: &RuntimeSettings) -> Self {
+        Self::new(runtime.features.clone())
+    }
+
+    /// Flags from config, with the override header applied for admins
+    pub fn for_request(runtime: &RuntimeSettings, headers: &HeaderMap, context: Option<&RequestContext>) -> Self {
+        let flags = Self::from_runtime(runtime);
+        let overrides = headers
+            .get(FEATURE_OVERRIDE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(parse_overrides)
+            .unwrap_or_default();
+        if overrides.is_empty() {
+            return flags;
+        }
+        match context {
+            Some(context) if context.has_role(OVERRIDE_ROLE) => flags.with_overrides(overrides),
+            _ => {
+                tracing::debug!("Ignoring {} header from a non-admin request", FEATURE_OVERRIDE_HEADER);
+                flags
+            }
+        }
+    }
+
+    /// Whether `name` is on; unknown and unconfigured flags are off
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.values.get(name).copied().unwrap_or(false)
+    }
+
+    pub fn with_overrides(mut self, overrides: BTreeMap<String, bool>) -> Self {
+        self.values.extend(overrides);
+        self
+    }
+
+    /// Configured names that no code consults (likely typos)
+    pub fn unknown_names(&self) -> Vec<&str> {
+        self.values
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !KNOWN_FEATURES.iter().any(|f| f.name == *name))
+            .collect()
+    }
+}
+
+/// Parse `name=on,name=off` (also true/false, 1/0); malformed entries are skipped
+fn parse_overrides(raw: &str) -> BTreeMap<String, bool> {
+    raw.split(',')
+        .filter_map(|entry| {
+            let (name, value) = entry.split_once('=')?;
+            let enabled = match value.trim().to_lowercase().as_str() {
+                "on" | "true" | "1" => true,
+                "off" | "false" | "0" => false,
+                _ => return None,
+            };
+            let name = name.trim();
+            (!name.is_empty()).then(|| (name.to_string(), enabled))
+        })
+        .collect()
+}
+
+/// Extract the request's effective flags in a handler
+impl<L, R, O, U, SO, CS> FromRequestParts<Arc<AppState<L, R, O, U, SO, CS>>> for FeatureFlags
+where
+    L: Send + Sync,
+    R: Send + Sync,
+    O: Send + Sync,
+    U: Send + Sync,
+    SO: Send + Sync,
+    CS: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState<L, R, O, U, SO, CS>>,
+    ) -> Result<Self, Self::Rejection> {
+        let settings = state.settings.current();
+        Ok(Self::for_request(
+            &settings.runtime,
+            &parts.headers,
+            parts.extensions.get::<RequestContext>(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::{LoggingConfig, RateLimitConfig};
+    use axum::http::HeaderValue;
+    use uuid::Uuid;
+
+    fn runtime(features: &[(&str, bool)]) -> RuntimeSettings {
+        RuntimeSettings {
+            logging: LoggingConfig { level: "info".to_string(), rust_log: "info".to_string() },
+            rate_limits: RateLimitConfig { api_keys_per_hour: 5 },
+            features: features.iter().map(|(name, on)| (name.to_string(), *on)).collect(),
+        }
+    }
+
+    fn context(role: &str) -> RequestContext {
+        RequestContext::new(
+            "req-1".to_string(),
+            Uuid::new_v4(),
+            "someone@example.com".to_string(),
+            Some(role.to_string()),
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_unknown_and_unset_flags_are_off() {
+        let flags = FeatureFlags::from_runtime(&runtime(&[(GRAPH_DEBUG_ENDPOINTS, true), ("typo_flag", true)]));
+        assert!(flags.is_enabled(GRAPH_DEBUG_ENDPOINTS));
+        assert!(!flags.is_enabled("never_heard_of_it"));
+        assert_eq!(flags.unknown_names(), vec!["typo_flag"]);
+    }
+
+    #[test]
+    fn test_override_header_only_applies_to_admins() {
+        let runtime = runtime(&[("bulk_export", true)]);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            FEATURE_OVERRIDE_HEADER,
+            HeaderValue::from_static("graph_debug_endpoints=on, bulk_export=off, bogus"),
+        );
+
+        let admin = FeatureFlags::for_request(&runtime, &headers, Some(&context("admin")));
+        assert!(admin.is_enabled(GRAPH_DEBUG_ENDPOINTS));
+        assert!(!admin.is_enabled("bulk_export"));
+
+        for context in [Some(context("user")), None] {
+            let flags = FeatureFlags::for_request(&runtime, &headers, context.as_ref());
+            assert!(!flags.is_enabled(GRAPH_DEBUG_ENDPOINTS));
+            assert!(flags.is_enabled("bulk_export"));
+        }
+    }
+}
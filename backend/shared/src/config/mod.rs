@@ -1,9 +1,19 @@
 pub mod settings;
 pub mod providers;
 pub mod deployment;
+pub mod validation;
+pub mod reload;
+pub mod features;
 
 pub use settings::Settings;
 pub use settings::DatabaseConfig;
-pub use providers::ProviderConfig;
+pub use settings::TlsConfig;
+pub use settings::{CookieConfig, SameSite};
+pub use settings::RedactionConfig;
+pub use settings::ConcurrencyConfig;
+pub use providers::{ProviderConfig, VaultClientConfig};
 pub use deployment::DeploymentConfig;
+pub use validation::{validate_env, ConfigErrors, ConfigIssue};
+pub use reload::{ReloadError, ReloadReport, SettingsHandle};
+pub use features::FeatureFlags;
 
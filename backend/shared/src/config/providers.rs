@@ -20,6 +20,7 @@ pub struct ProviderConfig {
     pub storage: StorageProviderConfig,
     pub database: DatabaseProviderConfig,
     pub messaging: MessagingProviderConfig,
+    pub counters: CounterStoreConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +47,64 @@ pub struct HashiCorpConfig {
     pub mount_path: String,
 }
 
+/// HTTP settings for clients talking to the vault service
+///
+/// Connections are pooled and reused across calls, which spares the DEK
+/// lookups on the request path a TCP and TLS handshake each. The pool must
+/// give up idle connections before the vault server, or any proxy or load
+/// balancer in front of it, closes them: keep `pool_idle_timeout_ms` below
+/// their idle timeout (commonly 60s), or calls will now and then be sent on
+/// a connection that is already closed. Likewise keep
+/// `pool_max_idle_per_host` within the connections the server accepts per
+/// client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultClientConfig {
+    /// How long establishing a connection (TCP and TLS) may take
+    pub connect_timeout_ms: u64,
+    /// How long a whole request may take, from sending it to reading the body
+    pub request_timeout_ms: u64,
+    /// Idle connections kept open to the vault; enough for a full `get_deks`
+    /// batch plus concurrent single reads
+    pub pool_max_idle_per_host: usize,
+    /// How long an unused connection stays in the pool; 0 keeps it forever
+    pub pool_idle_timeout_ms: u64,
+    /// Interval of HTTP/2 pings that keep idle connections alive and detect
+    /// dead ones; 0 disables them. Only HTTP/2 connections (negotiated over
+    /// TLS) are pinged.
+    pub http2_keep_alive_interval_ms: u64,
+}
+
+impl Default for VaultClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_ms: 2_000,
+            request_timeout_ms: 5_000,
+            pool_max_idle_per_host: 32,
+            pool_idle_timeout_ms: 50_000,
+            http2_keep_alive_interval_ms: 30_000,
+        }
+    }
+}
+
+impl VaultClientConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        fn var<T: std::str::FromStr>(key: &str, default: T) -> T {
+            env::var(key).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+        }
+        Self {
+            connect_timeout_ms: var("VAULT_CONNECT_TIMEOUT_MS", defaults.connect_timeout_ms),
+            request_timeout_ms: var("VAULT_REQUEST_TIMEOUT_MS", defaults.request_timeout_ms),
+            pool_max_idle_per_host: var("VAULT_POOL_MAX_IDLE_PER_HOST", defaults.pool_max_idle_per_host),
+            pool_idle_timeout_ms: var("VAULT_POOL_IDLE_TIMEOUT_MS", defaults.pool_idle_timeout_ms),
+            http2_keep_alive_interval_ms: var(
+                "VAULT_HTTP2_KEEP_ALIVE_INTERVAL_MS",
+                defaults.http2_keep_alive_interval_ms,
+            ),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AwsKmsConfig {
     pub region: String,
@@ -154,6 +213,22 @@ pub struct PostgresConfig {
     pub url: String,
 }
 
+/// Where rate-limit and lockout counters live
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CounterStoreConfig {
+    pub provider: CounterStoreProvider,
+    pub redis_url: Option<String>,
+    /// Prepended to every Redis key
+    pub key_prefix: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum CounterStoreProvider {
+    /// Per process; only consistent with a single replica
+    Memory,
+    Redis,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessagingProviderConfig {
     pub nats: Option<NatsConfig>,
@@ -387,12 +462,51 @@ impl ProviderConfig {
             kafka,
         };
 
+        let counters = CounterStoreConfig::from_env()?;
+
         Ok(ProviderConfig {
             kms,
             storage,
             database,
             messaging,
+            counters,
         })
     }
 }
 
+impl CounterStoreConfig {
+    /// Read `COUNTER_STORE`, `REDIS_URL` and `COUNTER_KEY_PREFIX`
+    pub fn from_env() -> Result<Self, config::ConfigError> {
+        Ok(CounterStoreConfig {
+            provider: parse_counter_store(&env::var("COUNTER_STORE").unwrap_or_default())?,
+            redis_url: env::var("REDIS_URL").ok(),
+            key_prefix: env::var("COUNTER_KEY_PREFIX").unwrap_or_else(|_| "health-v1:counter:".to_string()),
+        })
+    }
+}
+
+/// `COUNTER_STORE`; unset means memory, and a typo fails startup rather
+/// than quietly keeping counts per replica
+fn parse_counter_store(raw: &str) -> Result<CounterStoreProvider, config::ConfigError> {
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "" | "memory" => Ok(CounterStoreProvider::Memory),
+        "redis" => Ok(CounterStoreProvider::Redis),
+        other => Err(config::ConfigError::Message(format!(
+            "Unknown COUNTER_STORE '{}'; expected memory or redis",
+            other
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_store_provider_parsing() {
+        assert!(matches!(parse_counter_store(""), Ok(CounterStoreProvider::Memory)));
+        assert!(matches!(parse_counter_store("memory"), Ok(CounterStoreProvider::Memory)));
+        assert!(matches!(parse_counter_store("Redis"), Ok(CounterStoreProvider::Redis)));
+        assert!(parse_counter_store("redsi").is_err());
+    }
+}
@@ -0,0 +1,164 @@
+//! Hot reload of the runtime section of `Settings`
+//!
+//! Handlers read settings through a [`SettingsHandle`], which swaps in a new
+//! `Arc<Settings>` atomically. A reload re-reads the `.env` file over the
+//! process environment, validates it, and applies it only if every fixed
+//! section is unchanged; otherwise nothing is applied and the changed
+//! sections are reported so the operator knows a restart is needed.
+
+use std::sync::{Arc, RwLock};
+use serde::Serialize;
+use thiserror::Error;
+use crate::config::settings::Settings;
+use crate::config::validation::{validate_env, ConfigErrors};
+
+/// Sections of `Settings` that can be reloaded; everything else is fixed
+pub const RELOADABLE_SECTIONS: [&str; 1] = ["runtime"];
+
+#[derive(Debug, Error)]
+pub enum ReloadError {
+    #[error("{0}")]
+    Invalid(ConfigErrors),
+    #[error("restart required to change: {}", .0.join(", "))]
+    RestartRequired(Vec<String>),
+    #[error("failed to load configuration: {0}")]
+    Load(String),
+}
+
+/// What a successful reload changed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReloadReport {
+    /// Changed runtime subsections, e.g. `logging`, `rate_limits`
+    pub changed: Vec<String>,
+}
+
+/// Shared, swappable view of the current settings
+pub struct SettingsHandle {
+    current: RwLock<Arc<Settings>>,
+}
+
+impl SettingsHandle {
+    pub fn new(settings: Settings) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(settings)),
+        }
+    }
+
+    /// Settings in effect right now; hold the `Arc` only for one operation
+    pub fn current(&self) -> Arc<Settings> {
+        self.current.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Re-read `.env` and the environment and apply the runtime section
+    pub fn reload(&self) -> Result<ReloadReport, ReloadError> {
+        // Values in .env override the environment so edits to it are picked up
+        let _ = dotenvy::dotenv_override();
+        validate_env().map_err(ReloadError::Invalid)?;
+        let next = Settings::from_env().map_err(|e| ReloadError::Load(e.to_string()))?;
+        self.apply(next)
+    }
+
+    /// Swap in `next` if it differs from the current settings only in reloadable sections
+    pub fn apply(&self, next: Settings) -> Result<ReloadReport, ReloadError> {
+        let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+
+        let fixed_changes = changed_sections(current.as_ref(), &next)?
+            .into_iter()
+            .filter(|section| !RELOADABLE_SECTIONS.contains(&section.as_str()))
+            .collect::<Vec<_>>();
+        if !fixed_changes.is_empty() {
+            return Err(ReloadError::RestartRequired(fixed_changes));
+        }
+
+        let changed = changed_sections(&current.runtime, &next.runtime)?;
+        if changed.iter().any(|s| s == "logging") {
+            crate::infrastructure::logging::set_filter(&next.runtime.logging.rust_log).map_err(ReloadError::Load)?;
+        }
+
+        *current = Arc::new(next);
+        Ok(ReloadReport { changed })
+    }
+}
+
+/// Names of top-level fields whose serialized values differ
+fn changed_sections<T: Serialize>(current: &T, next: &T) -> Result<Vec<String>, ReloadError> {
+    let to_object = |value: &T| match serde_json::to_value(value) {
+        Ok(serde_json::Value::Object(map)) => Ok(map),
+        Ok(_) => Err(ReloadError::Load("settings did not serialize to an object".to_string())),
+        Err(e) => Err(ReloadError::Load(e.to_string())),
+    };
+    let current = to_object(current)?;
+    let next = to_object(next)?;
+    let mut changed: Vec<String> = current
+        .iter()
+        .filter(|(key, value)| next.get(key.as_str()) != Some(value))
+        .map(|(key, _)| key.clone())
+        .collect();
+    changed.sort();
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use crate::config::settings::{LoggingConfig, RateLimitConfig, RuntimeSettings};
+
+    fn settings() -> Settings {
+        serde_json::from_value(serde_json::json!({
+            "server": { "host": "0.0.0.0", "port": 8080, "grpc_port": null, "cors_allowed_origins": [] },
+            "database": { "url": "postgresql://localhost/db", "local_db_path": "./data/local.db", "max_connections": 5, "min_connections": 1 },
+            "encryption": { "master_key_path": null, "kms_provider": "hashicorp", "kms_config_path": null, "algorithm": "aes-256-gcm", "vault_breaker_failure_threshold": 5, "vault_breaker_cooldown_seconds": 30, "dek_cache_ttl_seconds": 30, "dek_cache_max_entries": 10000 },
+            "oidc": { "issuer": "http://localhost:8080", "client_id": "c", "client_secret": "s", "jwt_secret": "j", "jwt_expiration": 3600 },
+            "storage": { "provider": "local", "config_path": null },
+            "runtime": {
+                "logging": { "level": "info", "rust_log": "info" },
+                "rate_limits": { "api_keys_per_hour": 5 },
+                "features": {}
+            },
+            "deployment": { "environment": "Development", "cloud_provider": "None" },
+            "session": { "admin_ui_ttl_hours": 8, "client_ui_ttl_hours": 24, "api_ttl_hours": 1, "admin_ui_cors_origins": [], "client_ui_cors_origins": [], "cache_max_entries": 1000 },
+            "graph_cache": { "enabled": true, "ttl_seconds": 60 },
+            "webhook": { "url": null, "secret": "", "event_types": [], "max_attempts": 8, "timeout_seconds": 10 },
+            "authorization": { "schema_path": null },
+            "key_rotation": { "enabled": true, "schedule": {}, "check_interval_seconds": 3600, "batch_size": 100 }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_applies_runtime_changes() {
+        let handle = SettingsHandle::new(settings());
+        let before = handle.current();
+
+        let mut next = settings();
+        next.runtime = RuntimeSettings {
+            logging: LoggingConfig { level: "info".to_string(), rust_log: "info".to_string() },
+            rate_limits: RateLimitConfig { api_keys_per_hour: 20 },
+            features: BTreeMap::from([("new_graph_checker".to_string(), true)]),
+        };
+        let report = handle.apply(next).unwrap();
+
+        assert_eq!(report.changed, vec!["features".to_string(), "rate_limits".to_string()]);
+        assert_eq!(handle.current().runtime.rate_limits.api_keys_per_hour, 20);
+        // Readers holding the old Arc keep a consistent snapshot
+        assert_eq!(before.runtime.rate_limits.api_keys_per_hour, 5);
+    }
+
+    #[test]
+    fn test_rejects_fixed_changes() {
+        let handle = SettingsHandle::new(settings());
+
+        let mut next = settings();
+        next.database.url = "postgresql://elsewhere/db".to_string();
+        next.oidc.jwt_secret = "rotated".to_string();
+        next.runtime.rate_limits.api_keys_per_hour = 20;
+
+        match handle.apply(next) {
+            Err(ReloadError::RestartRequired(sections)) => assert_eq!(sections, vec!["database", "oidc"]),
+            other => panic!("expected RestartRequired, got {:?}", other),
+        }
+        // Nothing was applied, not even the runtime change
+        assert_eq!(handle.current().runtime.rate_limits.api_keys_per_hour, 5);
+    }
+}
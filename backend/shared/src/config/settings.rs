@@ -1,7 +1,14 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use crate::config::deployment::DeploymentConfig;
+use crate::infrastructure::encryption::AeadAlgorithm;
 
+/// Service configuration
+///
+/// Every section except `runtime` is fixed: it is read once at startup and a
+/// changed value only takes effect after a restart. A hot reload that would
+/// change a fixed section is rejected (see `config::reload`).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
     pub server: ServerConfig,
@@ -9,17 +16,121 @@ pub struct Settings {
     pub encryption: EncryptionConfig,
     pub oidc: OidcConfig,
     pub storage: StorageConfig,
-    pub logging: LoggingConfig,
+    /// Reloadable at runtime via SIGHUP or `POST /v1/admin/config/reload`
+    pub runtime: RuntimeSettings,
     pub deployment: DeploymentConfig,
     pub session: SessionConfig,
     pub graph_cache: GraphCacheConfig,
+    pub webhook: WebhookConfig,
+    pub authorization: AuthorizationConfig,
+    pub key_rotation: KeyRotationConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// Port for the internal gRPC API; disabled when unset
+    pub grpc_port: Option<u16>,
+    /// Address the gRPC API binds to; loopback unless a mesh needs it wider
+    #[serde(default = "default_grpc_host")]
+    pub grpc_host: String,
+    /// Bearer token gRPC callers must present; required off loopback
+    #[serde(default)]
+    pub grpc_auth_token: Option<String>,
     pub cors_allowed_origins: Vec<String>,
+    #[serde(default)]
+    pub tls: TlsConfig,
+    #[serde(default)]
+    pub concurrency: ConcurrencyConfig,
+}
+
+fn default_grpc_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+/// Bounds on requests handled at once, across all clients
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConcurrencyConfig {
+    /// Most requests in flight at once; 0 leaves it unbounded
+    pub max_in_flight: usize,
+    /// Limits for requests under a path prefix, on top of the global one
+    pub routes: Vec<(String, usize)>,
+    /// `Retry-After` seconds sent with the 503 once a limit is reached
+    pub retry_after_secs: u64,
+}
+
+impl Default for ConcurrencyConfig {
+    fn default() -> Self {
+        ConcurrencyConfig {
+            max_in_flight: 0,
+            routes: Vec::new(),
+            retry_after_secs: 1,
+        }
+    }
+}
+
+impl ConcurrencyConfig {
+    /// Read `MAX_CONCURRENT_REQUESTS`, `MAX_CONCURRENT_REQUESTS_BY_ROUTE`
+    /// (`/v1/reports=10,/v1/exports=2`) and `CONCURRENCY_RETRY_AFTER_SECS`
+    pub fn from_env() -> Self {
+        let defaults = ConcurrencyConfig::default();
+        ConcurrencyConfig {
+            max_in_flight: env::var("MAX_CONCURRENT_REQUESTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_in_flight),
+            routes: env::var("MAX_CONCURRENT_REQUESTS_BY_ROUTE")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|entry| {
+                    let (prefix, max) = entry.split_once('=')?;
+                    Some((prefix.trim().to_string(), max.trim().parse().ok()?))
+                })
+                .collect(),
+            retry_after_secs: env::var("CONCURRENCY_RETRY_AFTER_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.retry_after_secs),
+        }
+    }
+}
+
+/// HTTPS served by the service itself, for deployments without a
+/// TLS-terminating proxy
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TlsConfig {
+    pub enabled: bool,
+    /// PEM certificate chain, leaf first
+    pub cert_path: Option<String>,
+    /// PEM private key (PKCS#8, PKCS#1 or SEC1)
+    pub key_path: Option<String>,
+    /// PEM CA bundle client certificates are verified against
+    pub client_ca_path: Option<String>,
+    /// Refuse clients without a certificate signed by `client_ca_path`
+    pub require_client_cert: bool,
+    /// Seconds between checks for changed certificate files; 0 disables reloading
+    pub reload_interval_secs: u64,
+}
+
+impl TlsConfig {
+    /// Read the `{prefix}TLS_*` variables, e.g. `TLS_ENABLED` or `VAULT_TLS_ENABLED`
+    pub fn from_env(prefix: &str) -> Self {
+        let var = |name: &str| {
+            env::var(format!("{}TLS_{}", prefix, name))
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        TlsConfig {
+            enabled: var("ENABLED").is_some_and(|v| v == "true"),
+            cert_path: var("CERT_PATH"),
+            key_path: var("KEY_PATH"),
+            client_ca_path: var("CLIENT_CA_PATH"),
+            require_client_cert: var("REQUIRE_CLIENT_CERT").is_some_and(|v| v == "true"),
+            reload_interval_secs: var("RELOAD_INTERVAL_SECS").and_then(|v| v.parse().ok()).unwrap_or(60),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +146,17 @@ pub struct EncryptionConfig {
     pub master_key_path: Option<String>,
     pub kms_provider: String,
     pub kms_config_path: Option<String>,
+    /// AEAD for newly encrypted fields and service data; existing data
+    /// decrypts with whatever algorithm its header names
+    pub algorithm: AeadAlgorithm,
+    /// Consecutive vault failures that open the circuit breaker
+    pub vault_breaker_failure_threshold: u32,
+    /// How long an open breaker fails fast before letting a trial call through
+    pub vault_breaker_cooldown_seconds: u64,
+    /// How long decrypted DEKs stay cached in memory; 0 disables the cache
+    pub dek_cache_ttl_seconds: u64,
+    /// Upper bound on cached DEKs, least recently used evicted first
+    pub dek_cache_max_entries: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,12 +174,55 @@ pub struct StorageConfig {
     pub config_path: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LoggingConfig {
     pub level: String,
     pub rust_log: String,
 }
 
+/// Fields to redact from audit entries, on top of the built-in sensitive set
+/// in `shared::masking`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    /// Field names (`patient_mrn`), wildcard patterns (`*_mrn`) or JSON
+    /// pointers (`/patient/dob`, `/visits/*/notes`)
+    pub fields: Vec<String>,
+}
+
+impl RedactionConfig {
+    /// Read the comma-separated `REDACT_FIELDS`
+    pub fn from_env() -> Self {
+        RedactionConfig {
+            fields: env::var("REDACT_FIELDS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|f| !f.is_empty())
+                .map(String::from)
+                .collect(),
+        }
+    }
+}
+
+/// Settings that can change while the service runs
+///
+/// Readers must go through `SettingsHandle::current()` on each use rather
+/// than copying values at startup, or a reload will not reach them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeSettings {
+    /// `rust_log` is re-applied as the tracing filter on reload
+    pub logging: LoggingConfig,
+    pub rate_limits: RateLimitConfig,
+    /// Feature flags by name; unknown names are off
+    pub features: BTreeMap<String, bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// API keys a service account may create per hour
+    pub api_keys_per_hour: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionConfig {
     pub admin_ui_ttl_hours: u64,
@@ -66,6 +231,128 @@ pub struct SessionConfig {
     pub admin_ui_cors_origins: Vec<String>,
     pub client_ui_cors_origins: Vec<String>,
     pub cache_max_entries: usize,
+    #[serde(default)]
+    pub cookie: CookieConfig,
+}
+
+/// `SameSite` attribute of the session cookies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SameSite {
+    Strict,
+    #[default]
+    Lax,
+    /// Sent on cross-site requests too; browsers require `Secure` with it
+    None,
+}
+
+impl std::str::FromStr for SameSite {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "strict" => Ok(SameSite::Strict),
+            "lax" => Ok(SameSite::Lax),
+            "none" => Ok(SameSite::None),
+            other => Err(format!("unknown SameSite '{}' (expected Strict, Lax or None)", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for SameSite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::None => "None",
+        })
+    }
+}
+
+/// Attributes of the cookies the API sets for a session
+///
+/// Clearing a cookie only works with the domain and path it was set with,
+/// so both setting and clearing go through [`CookieConfig::set_cookie`] and
+/// [`CookieConfig::clear_cookie`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CookieConfig {
+    pub secure: bool,
+    pub http_only: bool,
+    pub same_site: SameSite,
+    /// Share the cookie with subdomains, e.g. `example.com` for SSO across
+    /// `admin.example.com` and `app.example.com`; host-only when unset
+    pub domain: Option<String>,
+    pub path: String,
+    pub max_age_seconds: u64,
+}
+
+impl Default for CookieConfig {
+    fn default() -> Self {
+        CookieConfig {
+            secure: true,
+            http_only: true,
+            same_site: SameSite::Lax,
+            domain: None,
+            path: "/".to_string(),
+            max_age_seconds: 3600 * 24 * 7, // 7 days
+        }
+    }
+}
+
+impl CookieConfig {
+    /// Read the `SESSION_COOKIE_*` variables
+    pub fn from_env() -> Self {
+        let defaults = CookieConfig::default();
+        let var = |name: &str| {
+            env::var(format!("SESSION_COOKIE_{}", name))
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        CookieConfig {
+            secure: var("SECURE").and_then(|v| v.parse().ok()).unwrap_or(defaults.secure),
+            http_only: var("HTTP_ONLY").and_then(|v| v.parse().ok()).unwrap_or(defaults.http_only),
+            same_site: var("SAME_SITE").and_then(|v| v.parse().ok()).unwrap_or(defaults.same_site),
+            domain: var("DOMAIN"),
+            path: var("PATH").unwrap_or(defaults.path),
+            max_age_seconds: var("MAX_AGE_SECONDS").and_then(|v| v.parse().ok()).unwrap_or(defaults.max_age_seconds),
+        }
+    }
+
+    /// The same attributes for a cookie scripts must read, e.g. the CSRF token
+    pub fn script_readable(&self) -> Self {
+        CookieConfig { http_only: false, ..self.clone() }
+    }
+
+    /// `Set-Cookie` value storing `name=value`
+    pub fn set_cookie(&self, name: &str, value: &str) -> String {
+        self.format(name, value, self.max_age_seconds)
+    }
+
+    /// `Set-Cookie` value deleting `name`; the attributes match [`Self::set_cookie`]
+    pub fn clear_cookie(&self, name: &str) -> String {
+        self.format(name, "", 0)
+    }
+
+    fn format(&self, name: &str, value: &str, max_age: u64) -> String {
+        let mut cookie = format!("{}={}; Path={}; Max-Age={}; SameSite={}", name, value, self.path, max_age, self.same_site);
+        if let Some(domain) = &self.domain {
+            cookie.push_str("; Domain=");
+            cookie.push_str(domain);
+        }
+        if self.http_only {
+            cookie.push_str("; HttpOnly");
+        }
+        if self.secure {
+            cookie.push_str("; Secure");
+        }
+        cookie
+    }
+}
+
+/// Whether browsers reach the service over HTTPS: it terminates TLS itself,
+/// or a UI origin it serves is `https://`
+pub(crate) fn is_https_deployment<'a>(tls_enabled: bool, origins: impl IntoIterator<Item = &'a str>) -> bool {
+    tls_enabled || origins.into_iter().any(|origin| origin.trim().starts_with("https://"))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,6 +361,85 @@ pub struct GraphCacheConfig {
     pub ttl_seconds: i64,
 }
 
+/// Authorization model settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorizationConfig {
+    /// JSON schema of object types and relations; relations are free-form when unset
+    pub schema_path: Option<String>,
+}
+
+/// Outbound webhook notifications (e.g. to a SIEM)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Delivery target; webhooks are disabled when unset
+    pub url: Option<String>,
+    /// Key for the HMAC signature header
+    pub secret: String,
+    /// Domain event types to forward (see `DomainEvent::ALL_TYPES`)
+    pub event_types: Vec<String>,
+    /// Attempts before a delivery is moved to the dead letter
+    pub max_attempts: i32,
+    pub timeout_seconds: u64,
+}
+
+/// Scheduled key rotation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRotationConfig {
+    pub enabled: bool,
+    /// Maximum key age in days per key type, e.g. `master=90,group=30`
+    pub schedule: BTreeMap<String, u32>,
+    /// How often the job looks for due rotations
+    pub check_interval_seconds: u64,
+    /// Keys rotated per batch; progress is saved after each batch
+    pub batch_size: i64,
+}
+
+/// Parse `type=days,type=days` into a rotation schedule
+pub(crate) fn parse_rotation_schedule(raw: &str) -> Result<BTreeMap<String, u32>, config::ConfigError> {
+    let mut schedule = BTreeMap::new();
+    for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parsed = entry
+            .split_once('=')
+            .and_then(|(key_type, days)| Some((key_type.trim(), days.trim().parse::<u32>().ok()?)))
+            .filter(|(key_type, days)| !key_type.is_empty() && *days > 0);
+        match parsed {
+            Some((key_type, days)) => {
+                schedule.insert(key_type.to_string(), days);
+            }
+            None => {
+                return Err(config::ConfigError::Message(format!(
+                    "KEY_ROTATION_SCHEDULE entry '{}' must be <key_type>=<days> with days > 0",
+                    entry
+                )))
+            }
+        }
+    }
+    Ok(schedule)
+}
+
+/// Parse `name=bool,name=bool` into feature flags
+pub(crate) fn parse_feature_flags(raw: &str) -> Result<BTreeMap<String, bool>, config::ConfigError> {
+    let mut flags = BTreeMap::new();
+    for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parsed = entry
+            .split_once('=')
+            .and_then(|(name, enabled)| Some((name.trim(), enabled.trim().parse::<bool>().ok()?)))
+            .filter(|(name, _)| !name.is_empty());
+        match parsed {
+            Some((name, enabled)) => {
+                flags.insert(name.to_string(), enabled);
+            }
+            None => {
+                return Err(config::ConfigError::Message(format!(
+                    "FEATURE_FLAGS entry '{}' must be <name>=true or <name>=false",
+                    entry
+                )))
+            }
+        }
+    }
+    Ok(flags)
+}
+
 impl Settings {
     pub fn from_env() -> Result<Self, config::ConfigError> {
         let server = ServerConfig {
@@ -82,12 +448,17 @@ impl Settings {
                 .unwrap_or_else(|_| "8080".to_string())
                 .parse()
                 .unwrap_or(8080),
+            grpc_port: env::var("GRPC_PORT").ok().and_then(|p| p.parse().ok()),
+            grpc_host: env::var("GRPC_HOST").unwrap_or_else(|_| default_grpc_host()),
+            grpc_auth_token: env::var("GRPC_AUTH_TOKEN").ok().filter(|t| !t.is_empty()),
             cors_allowed_origins: env::var("CORS_ALLOWED_ORIGINS")
                 .unwrap_or_else(|_| "http://localhost:5174,http://localhost:5173,http://localhost:5175".to_string())
                 .split(',')
                 .map(|s| s.trim().to_string())
                 .filter(|s| !s.is_empty())
                 .collect(),
+            tls: TlsConfig::from_env(""),
+            concurrency: ConcurrencyConfig::from_env(),
         };
 
         let database = DatabaseConfig {
@@ -108,6 +479,28 @@ impl Settings {
             master_key_path: env::var("MASTER_KEY_PATH").ok(),
             kms_provider: env::var("KMS_PROVIDER").unwrap_or_else(|_| "hashicorp".to_string()),
             kms_config_path: env::var("KMS_CONFIG_PATH").ok(),
+            algorithm: env::var("ENCRYPTION_ALGORITHM")
+                .ok()
+                .map(|raw| raw.parse())
+                .transpose()
+                .map_err(|e: String| config::ConfigError::Message(format!("ENCRYPTION_ALGORITHM: {}", e)))?
+                .unwrap_or_default(),
+            vault_breaker_failure_threshold: env::var("VAULT_BREAKER_FAILURE_THRESHOLD")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            vault_breaker_cooldown_seconds: env::var("VAULT_BREAKER_COOLDOWN_SECONDS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            dek_cache_ttl_seconds: env::var("DEK_CACHE_TTL_SECONDS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            dek_cache_max_entries: env::var("DEK_CACHE_MAX_ENTRIES")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .unwrap_or(10000),
         };
 
         let oidc = OidcConfig {
@@ -128,9 +521,18 @@ impl Settings {
             config_path: env::var("STORAGE_CONFIG_PATH").ok(),
         };
 
-        let logging = LoggingConfig {
-            level: env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string()),
-            rust_log: env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()),
+        let runtime = RuntimeSettings {
+            logging: LoggingConfig {
+                level: env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string()),
+                rust_log: env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()),
+            },
+            rate_limits: RateLimitConfig {
+                api_keys_per_hour: env::var("RATE_LIMIT_API_KEYS_PER_HOUR")
+                    .unwrap_or_else(|_| "5".to_string())
+                    .parse()
+                    .unwrap_or(5),
+            },
+            features: parse_feature_flags(&env::var("FEATURE_FLAGS").unwrap_or_default())?,
         };
 
         let deployment = DeploymentConfig::from_env()?;
@@ -164,6 +566,7 @@ impl Settings {
                 .unwrap_or_else(|_| "1000".to_string())
                 .parse()
                 .unwrap_or(1000),
+            cookie: CookieConfig::from_env(),
         };
 
         let graph_cache = GraphCacheConfig {
@@ -177,17 +580,109 @@ impl Settings {
                 .unwrap_or(60),
         };
 
+        let webhook = WebhookConfig {
+            url: env::var("WEBHOOK_URL").ok().filter(|s| !s.trim().is_empty()),
+            secret: env::var("WEBHOOK_SECRET").unwrap_or_default(),
+            event_types: env::var("WEBHOOK_EVENT_TYPES")
+                .unwrap_or_else(|_| "LoginFailureBurst,PolicyChanged,TokenRevoked".to_string())
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            max_attempts: env::var("WEBHOOK_MAX_ATTEMPTS")
+                .unwrap_or_else(|_| "8".to_string())
+                .parse()
+                .unwrap_or(8),
+            timeout_seconds: env::var("WEBHOOK_TIMEOUT_SECONDS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+        };
+
+        let authorization = AuthorizationConfig {
+            schema_path: env::var("AUTHZ_SCHEMA_PATH").ok().filter(|s| !s.trim().is_empty()),
+        };
+
+        let key_rotation = KeyRotationConfig {
+            enabled: env::var("KEY_ROTATION_ENABLED")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            schedule: parse_rotation_schedule(
+                &env::var("KEY_ROTATION_SCHEDULE").unwrap_or_else(|_| "master=90,group=90".to_string()),
+            )?,
+            check_interval_seconds: env::var("KEY_ROTATION_CHECK_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
+            batch_size: env::var("KEY_ROTATION_BATCH_SIZE")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .unwrap_or(100),
+        };
+
         Ok(Settings {
             server,
             database,
             encryption,
             oidc,
             storage,
-            logging,
+            runtime,
             deployment,
             session,
             graph_cache,
+            webhook,
+            authorization,
+            key_rotation,
         })
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rotation_schedule() {
+        let schedule = parse_rotation_schedule(" master=90, group=30 ,").unwrap();
+        assert_eq!(schedule.get("master"), Some(&90));
+        assert_eq!(schedule.get("group"), Some(&30));
+        assert!(parse_rotation_schedule("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_rotation_schedule_rejects_bad_entries() {
+        assert!(parse_rotation_schedule("master").is_err());
+        assert!(parse_rotation_schedule("master=0").is_err());
+        assert!(parse_rotation_schedule("=90").is_err());
+        assert!(parse_rotation_schedule("group=soon").is_err());
+    }
+
+    #[test]
+    fn test_cookie_set_and_clear_share_attributes() {
+        let cookie = CookieConfig {
+            same_site: SameSite::Strict,
+            domain: Some("example.com".to_string()),
+            ..CookieConfig::default()
+        };
+        assert_eq!(
+            cookie.set_cookie("session_token", "abc"),
+            "session_token=abc; Path=/; Max-Age=604800; SameSite=Strict; Domain=example.com; HttpOnly; Secure"
+        );
+        assert_eq!(
+            cookie.clear_cookie("session_token"),
+            "session_token=; Path=/; Max-Age=0; SameSite=Strict; Domain=example.com; HttpOnly; Secure"
+        );
+        assert!(!cookie.script_readable().set_cookie("csrf_token", "x").contains("HttpOnly"));
+    }
+
+    #[test]
+    fn test_parse_feature_flags() {
+        let flags = parse_feature_flags("new_graph_checker=true, bulk_export = false,").unwrap();
+        assert_eq!(flags.get("new_graph_checker"), Some(&true));
+        assert_eq!(flags.get("bulk_export"), Some(&false));
+        assert!(parse_feature_flags("").unwrap().is_empty());
+        assert!(parse_feature_flags("new_graph_checker").is_err());
+        assert!(parse_feature_flags("new_graph_checker=on").is_err());
+    }
+}
@@ -0,0 +1,401 @@
+//! Startup validation of environment configuration
+//!
+//! `Settings::from_env` falls back to a default whenever a value does not
+//! parse, so a typo like `SERVER_PORT=80800` silently starts on 8080.
+//! [`validate_env`] checks every variable up front and reports all problems
+//! at once, each tagged with the variable name, so the service can refuse to
+//! start with the full list.
+
+use std::collections::BTreeSet;
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use crate::config::settings::{is_https_deployment, parse_feature_flags, parse_rotation_schedule, SameSite};
+use crate::domain::events::DomainEvent;
+use crate::infrastructure::encryption::AeadAlgorithm;
+
+/// One invalid or missing setting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    pub var: &'static str,
+    pub message: String,
+}
+
+/// Every problem found in the environment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigErrors(pub Vec<ConfigIssue>);
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid configuration ({} problem", self.0.len())?;
+        if self.0.len() != 1 {
+            f.write_str("s")?;
+        }
+        f.write_str("):")?;
+        for issue in &self.0 {
+            write!(f, "\n  - {}: {}", issue.var, issue.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
+/// Validate the process environment
+pub fn validate_env() -> Result<(), ConfigErrors> {
+    validate_with(|name| env::var(name).ok())
+}
+
+/// Validate settings read through `lookup` (an env var reader in production)
+pub fn validate_with<F: Fn(&str) -> Option<String>>(lookup: F) -> Result<(), ConfigErrors> {
+    let mut c = Checker { lookup, issues: Vec::new() };
+
+    // Server
+    c.port("SERVER_PORT");
+    c.port("GRPC_PORT");
+    c.origins("CORS_ALLOWED_ORIGINS");
+    if c.parse::<bool>("TLS_ENABLED") == Some(true) {
+        c.required("TLS_CERT_PATH");
+        c.required("TLS_KEY_PATH");
+        if c.parse::<bool>("TLS_REQUIRE_CLIENT_CERT") == Some(true) {
+            c.required("TLS_CLIENT_CA_PATH");
+        }
+    }
+    c.parse::<u64>("TLS_RELOAD_INTERVAL_SECS");
+
+    // Database
+    c.url("DATABASE_URL", &["postgres", "postgresql"]);
+    let max = c.positive::<u32>("DATABASE_MAX_CONNECTIONS");
+    let min = c.parse::<u32>("DATABASE_MIN_CONNECTIONS");
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            c.issue(
+                "DATABASE_MIN_CONNECTIONS",
+                format!("must not exceed DATABASE_MAX_CONNECTIONS ({} > {})", min, max),
+            );
+        }
+    }
+
+    // Encryption
+    c.parse::<AeadAlgorithm>("ENCRYPTION_ALGORITHM");
+    c.positive::<u32>("VAULT_BREAKER_FAILURE_THRESHOLD");
+    c.positive::<u64>("VAULT_BREAKER_COOLDOWN_SECONDS");
+    c.parse::<u64>("DEK_CACHE_TTL_SECONDS");
+    c.positive::<usize>("DEK_CACHE_MAX_ENTRIES");
+
+    // OIDC / JWT
+    c.required("JWT_SECRET");
+    c.url("OIDC_ISSUER", &["http", "https"]);
+    c.positive::<u64>("JWT_EXPIRATION");
+
+    // Logging and deployment
+    c.one_of("LOG_LEVEL", &["trace", "debug", "info", "warn", "error"]);
+    c.one_of("RUST_ENV", &["development", "staging", "production"]);
+    c.one_of("CLOUD_PROVIDER", &["none", "aws", "gcp", "azure"]);
+
+    // Runtime (reloadable)
+    c.positive::<i64>("RATE_LIMIT_API_KEYS_PER_HOUR");
+    if let Some(raw) = c.value("FEATURE_FLAGS") {
+        if let Err(e) = parse_feature_flags(&raw) {
+            c.issue("FEATURE_FLAGS", e.to_string());
+        }
+    }
+
+    // Sessions
+    c.positive::<u64>("SESSION_ADMIN_UI_TTL_HOURS");
+    c.positive::<u64>("SESSION_CLIENT_UI_TTL_HOURS");
+    c.positive::<u64>("SESSION_API_TTL_HOURS");
+    c.origins("CORS_ADMIN_UI_ORIGINS");
+    c.origins("CORS_CLIENT_UI_ORIGINS");
+    c.positive::<usize>("SESSION_CACHE_MAX_ENTRIES");
+    let secure = c.parse::<bool>("SESSION_COOKIE_SECURE").unwrap_or(true);
+    c.parse::<bool>("SESSION_COOKIE_HTTP_ONLY");
+    let same_site = c.parse::<SameSite>("SESSION_COOKIE_SAME_SITE");
+    c.positive::<u64>("SESSION_COOKIE_MAX_AGE_SECONDS");
+    if let Some(domain) = c.value("SESSION_COOKIE_DOMAIN") {
+        if domain.contains(|ch: char| ch == ';' || ch == ',' || ch.is_whitespace()) {
+            c.issue("SESSION_COOKIE_DOMAIN", format!("'{}' is not a domain", domain));
+        }
+    }
+    if let Some(path) = c.value("SESSION_COOKIE_PATH") {
+        if !path.starts_with('/') || path.contains(';') {
+            c.issue("SESSION_COOKIE_PATH", format!("'{}' must be a path starting with /", path));
+        }
+    }
+    if !secure {
+        let origins: Vec<String> = ["CORS_ADMIN_UI_ORIGINS", "CORS_CLIENT_UI_ORIGINS"]
+            .into_iter()
+            .filter_map(|var| c.value(var))
+            .collect();
+        let tls_enabled = c.value("TLS_ENABLED").is_some_and(|v| v == "true");
+        if is_https_deployment(tls_enabled, origins.iter().flat_map(|o| o.split(','))) {
+            c.issue("SESSION_COOKIE_SECURE", "must be true when the service or its UIs are served over HTTPS");
+        } else if same_site == Some(SameSite::None) {
+            c.issue("SESSION_COOKIE_SECURE", "must be true with SESSION_COOKIE_SAME_SITE=None");
+        }
+    }
+
+    // Graph cache
+    c.parse::<bool>("GRAPH_CACHE_ENABLED");
+    c.positive::<i64>("GRAPH_CACHE_TTL_SECONDS");
+
+    // Webhooks
+    c.url("WEBHOOK_URL", &["http", "https"]);
+    c.event_types("WEBHOOK_EVENT_TYPES");
+    c.positive::<i32>("WEBHOOK_MAX_ATTEMPTS");
+    c.positive::<u64>("WEBHOOK_TIMEOUT_SECONDS");
+
+    // Key rotation
+    c.parse::<bool>("KEY_ROTATION_ENABLED");
+    if let Some(raw) = c.value("KEY_ROTATION_SCHEDULE") {
+        if let Err(e) = parse_rotation_schedule(&raw) {
+            c.issue("KEY_ROTATION_SCHEDULE", e.to_string());
+        }
+    }
+    c.positive::<u64>("KEY_ROTATION_CHECK_INTERVAL_SECONDS");
+    c.positive::<i64>("KEY_ROTATION_BATCH_SIZE");
+
+    // Legacy MUMPS gateway (optional)
+    c.port("MUMPS_PORT");
+    c.positive::<usize>("MUMPS_POOL_SIZE");
+    c.positive::<u64>("MUMPS_CONNECT_TIMEOUT_MS");
+    c.positive::<u64>("MUMPS_REQUEST_TIMEOUT_MS");
+    c.positive::<u64>("MUMPS_ACQUIRE_TIMEOUT_MS");
+
+    // SAML service provider (optional)
+    c.url("SAML_ACS_URL", &["https", "http"]);
+    c.url("SAML_IDP_METADATA_URL", &["https"]);
+    c.positive::<u64>("SAML_REQUEST_TTL_SECS");
+
+    if c.issues.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigErrors(c.issues))
+    }
+}
+
+struct Checker<F> {
+    lookup: F,
+    issues: Vec<ConfigIssue>,
+}
+
+impl<F: Fn(&str) -> Option<String>> Checker<F> {
+    fn issue(&mut self, var: &'static str, message: impl Into<String>) {
+        self.issues.push(ConfigIssue { var, message: message.into() });
+    }
+
+    /// Trimmed value, `None` when unset or blank (the default then applies)
+    fn value(&self, var: &str) -> Option<String> {
+        (self.lookup)(var)
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    }
+
+    fn required(&mut self, var: &'static str) -> Option<String> {
+        let value = self.value(var);
+        if value.is_none() {
+            self.issue(var, "is required but not set");
+        }
+        value
+    }
+
+    fn parse<T: FromStr>(&mut self, var: &'static str) -> Option<T>
+    where
+        T::Err: fmt::Display,
+    {
+        let raw = self.value(var)?;
+        match raw.parse::<T>() {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.issue(var, format!("'{}' is not a valid {}: {}", raw, type_name::<T>(), e));
+                None
+            }
+        }
+    }
+
+    fn positive<T: FromStr + PartialOrd + Default>(&mut self, var: &'static str) -> Option<T>
+    where
+        T::Err: fmt::Display,
+    {
+        let value = self.parse::<T>(var)?;
+        if value <= T::default() {
+            self.issue(var, "must be greater than 0");
+            return None;
+        }
+        Some(value)
+    }
+
+    fn port(&mut self, var: &'static str) {
+        let Some(raw) = self.value(var) else { return };
+        match raw.parse::<u32>() {
+            Ok(port) if (1..=65535).contains(&port) => {}
+            _ => self.issue(var, format!("'{}' is not a port number (1-65535)", raw)),
+        }
+    }
+
+    fn url(&mut self, var: &'static str, schemes: &[&str]) {
+        let Some(raw) = self.value(var) else { return };
+        match reqwest::Url::parse(&raw) {
+            Ok(url) if schemes.contains(&url.scheme()) => {}
+            Ok(url) => self.issue(
+                var,
+                format!("scheme '{}' is not supported (expected {})", url.scheme(), schemes.join(" or ")),
+            ),
+            // Avoid echoing the value: URLs may carry credentials
+            Err(e) => self.issue(var, format!("is not a valid URL: {}", e)),
+        }
+    }
+
+    /// Comma-separated list of browser origins
+    fn origins(&mut self, var: &'static str) {
+        let Some(raw) = self.value(var) else { return };
+        let invalid: Vec<&str> = raw
+            .split(',')
+            .map(str::trim)
+            .filter(|o| !o.is_empty() && *o != "*")
+            .filter(|o| {
+                !reqwest::Url::parse(o).is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host())
+            })
+            .collect();
+        if !invalid.is_empty() {
+            self.issue(var, format!("invalid origin(s): {}", invalid.join(", ")));
+        }
+    }
+
+    fn one_of(&mut self, var: &'static str, allowed: &[&str]) {
+        let Some(raw) = self.value(var) else { return };
+        if !allowed.contains(&raw.to_lowercase().as_str()) {
+            self.issue(var, format!("'{}' is not one of {}", raw, allowed.join(", ")));
+        }
+    }
+
+    fn event_types(&mut self, var: &'static str) {
+        let Some(raw) = self.value(var) else { return };
+        let known: BTreeSet<&str> = DomainEvent::ALL_TYPES.iter().copied().collect();
+        let unknown: Vec<&str> = raw
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty() && !known.contains(t))
+            .collect();
+        if !unknown.is_empty() {
+            self.issue(
+                var,
+                format!(
+                    "unknown event type(s) {} (known: {})",
+                    unknown.join(", "),
+                    DomainEvent::ALL_TYPES.join(", ")
+                ),
+            );
+        }
+    }
+}
+
+/// Short human-readable name of the expected type
+fn type_name<T>() -> &'static str {
+    let full = std::any::type_name::<T>();
+    match full.rsplit("::").next().unwrap_or(full) {
+        "bool" => "boolean (true or false)",
+        "AeadAlgorithm" => "encryption algorithm",
+        "SameSite" => "SameSite value",
+        "u32" | "u64" | "usize" | "i32" | "i64" => "integer",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn validate(vars: &[(&str, &str)]) -> Result<(), ConfigErrors> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        validate_with(|name| vars.get(name).cloned())
+    }
+
+    fn failing_vars(result: Result<(), ConfigErrors>) -> Vec<&'static str> {
+        result.unwrap_err().0.into_iter().map(|i| i.var).collect()
+    }
+
+    #[test]
+    fn test_defaults_only_need_jwt_secret() {
+        assert!(validate(&[("JWT_SECRET", "s3cret")]).is_ok());
+        assert_eq!(failing_vars(validate(&[])), vec!["JWT_SECRET"]);
+    }
+
+    #[test]
+    fn test_reports_every_problem() {
+        let result = validate(&[
+            ("SERVER_PORT", "80800"),
+            ("DATABASE_URL", "mysql://localhost/db"),
+            ("DATABASE_MAX_CONNECTIONS", "five"),
+            ("JWT_EXPIRATION", "0"),
+            ("OIDC_ISSUER", "not a url"),
+            ("RUST_ENV", "prod"),
+            ("GRAPH_CACHE_ENABLED", "yes"),
+            ("WEBHOOK_EVENT_TYPES", "PolicyChanged,PolicyChnaged"),
+            ("KEY_ROTATION_SCHEDULE", "master"),
+        ]);
+        assert_eq!(
+            failing_vars(result),
+            vec![
+                "SERVER_PORT",
+                "DATABASE_URL",
+                "DATABASE_MAX_CONNECTIONS",
+                "JWT_SECRET",
+                "OIDC_ISSUER",
+                "JWT_EXPIRATION",
+                "RUST_ENV",
+                "GRAPH_CACHE_ENABLED",
+                "WEBHOOK_EVENT_TYPES",
+                "KEY_ROTATION_SCHEDULE",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_connection_bounds_and_origins() {
+        let result = validate(&[
+            ("JWT_SECRET", "s3cret"),
+            ("DATABASE_MIN_CONNECTIONS", "10"),
+            ("DATABASE_MAX_CONNECTIONS", "5"),
+            ("CORS_ALLOWED_ORIGINS", "http://localhost:5173, localhost:5174"),
+        ]);
+        assert_eq!(failing_vars(result), vec!["CORS_ALLOWED_ORIGINS", "DATABASE_MIN_CONNECTIONS"]);
+    }
+
+    #[test]
+    fn test_tls_needs_certificate_and_key() {
+        let result = validate(&[
+            ("JWT_SECRET", "s3cret"),
+            ("TLS_ENABLED", "true"),
+            ("TLS_KEY_PATH", "/etc/tls/key.pem"),
+            ("TLS_REQUIRE_CLIENT_CERT", "true"),
+        ]);
+        assert_eq!(failing_vars(result), vec!["TLS_CERT_PATH", "TLS_CLIENT_CA_PATH"]);
+        assert!(validate(&[("JWT_SECRET", "s3cret"), ("TLS_KEY_PATH", "/etc/tls/key.pem")]).is_ok());
+    }
+
+    #[test]
+    fn test_insecure_cookie_rejected_for_https() {
+        let insecure = [("JWT_SECRET", "s3cret"), ("SESSION_COOKIE_SECURE", "false")];
+        assert!(validate(&insecure).is_ok());
+        let https = [insecure.as_slice(), &[("CORS_ADMIN_UI_ORIGINS", "https://admin.example.com")]].concat();
+        assert_eq!(failing_vars(validate(&https)), vec!["SESSION_COOKIE_SECURE"]);
+        let tls = [insecure.as_slice(), &[("TLS_ENABLED", "true"), ("TLS_CERT_PATH", "c"), ("TLS_KEY_PATH", "k")]].concat();
+        assert_eq!(failing_vars(validate(&tls)), vec!["SESSION_COOKIE_SECURE"]);
+        let cross_site = [insecure.as_slice(), &[("SESSION_COOKIE_SAME_SITE", "none")]].concat();
+        assert_eq!(failing_vars(validate(&cross_site)), vec!["SESSION_COOKIE_SECURE"]);
+        assert_eq!(
+            failing_vars(validate(&[("JWT_SECRET", "s3cret"), ("SESSION_COOKIE_SAME_SITE", "loose")])),
+            vec!["SESSION_COOKIE_SAME_SITE"]
+        );
+    }
+
+    #[test]
+    fn test_display_lists_variables() {
+        let message = validate(&[("SERVER_PORT", "0")]).unwrap_err().to_string();
+        assert!(message.starts_with("Invalid configuration (2 problems):"));
+        assert!(message.contains("\n  - SERVER_PORT: '0' is not a port number"));
+        assert!(message.contains("\n  - JWT_SECRET: is required but not set"));
+    }
+}
@@ -0,0 +1,104 @@
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+use crate::shared::{AppError, AppResult};
+
+/// Marks a string as an API key issued by this service
+pub const API_KEY_PREFIX: &str = "hvk_";
+
+/// Characters of the key kept in clear for identification
+const DISPLAY_PREFIX_LEN: usize = 12;
+
+/// Long-lived credential for a service account, scoped to one role
+///
+/// Only the SHA-256 of the key is stored; the key itself is returned once
+/// by `generate` and cannot be recovered afterwards.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub role_id: Uuid,
+    pub name: String,
+    pub key_prefix: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ApiKey {
+    /// Create a key record and the plaintext key to hand to the caller
+    pub fn generate(
+        user_id: Uuid,
+        role_id: Uuid,
+        name: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> AppResult<(Self, String)> {
+        let mut secret = [0u8; 32];
+        SystemRandom::new()
+            .fill(&mut secret)
+            .map_err(|_| AppError::Internal("Failed to generate API key".to_string()))?;
+        let key = format!("{}{}", API_KEY_PREFIX, URL_SAFE_NO_PAD.encode(secret));
+
+        let api_key = Self {
+            id: Uuid::new_v4(),
+            user_id,
+            role_id,
+            name,
+            key_prefix: key[..DISPLAY_PREFIX_LEN].to_string(),
+            key_hash: Self::hash_key(&key),
+            expires_at,
+            last_used_at: None,
+            revoked_at: None,
+            created_at: Utc::now(),
+        };
+        Ok((api_key, key))
+    }
+
+    /// Hash under which a key is stored and looked up
+    pub fn hash_key(key: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(key.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Usable for authentication: not revoked and not expired
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_generated_key_matches_stored_hash_only() {
+        let (api_key, key) = ApiKey::generate(Uuid::new_v4(), Uuid::new_v4(), "ci".to_string(), None).unwrap();
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert!(key.starts_with(&api_key.key_prefix));
+        assert_eq!(api_key.key_hash, ApiKey::hash_key(&key));
+        assert_ne!(api_key.key_hash, key);
+
+        let (_, other) = ApiKey::generate(Uuid::new_v4(), Uuid::new_v4(), "ci".to_string(), None).unwrap();
+        assert_ne!(key, other);
+    }
+
+    #[test]
+    fn test_revoked_or_expired_keys_are_inactive() {
+        let now = Utc::now();
+        let (mut api_key, _) =
+            ApiKey::generate(Uuid::new_v4(), Uuid::new_v4(), "ci".to_string(), Some(now + Duration::days(1))).unwrap();
+        assert!(api_key.is_active_at(now));
+        assert!(!api_key.is_active_at(now + Duration::days(2)));
+
+        api_key.revoked_at = Some(now);
+        assert!(!api_key.is_active_at(now));
+    }
+}
@@ -5,7 +5,7 @@ use chrono::{DateTime, Utc};
 use serde_json::Value;
 
 /// Group entity - metadata only, permissions managed via Zanzibar relationships
-/// Groups don't have DEKs, they're just organizational units
+/// Group DEKs shared with members are managed by `GroupKeyring`, keyed by `id`
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Group {
     pub id: Uuid,
@@ -0,0 +1,27 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Administrative sex as recorded in the legacy system
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AdministrativeSex {
+    Male,
+    Female,
+    Unknown,
+}
+
+/// Patient demographics read from the legacy MUMPS system (read-only)
+///
+/// Identified by the legacy internal entry number (DFN), not a UUID; these
+/// records are not stored in Postgres.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LegacyPatient {
+    pub dfn: String,
+    pub family_name: String,
+    pub given_names: Vec<String>,
+    pub sex: AdministrativeSex,
+    /// `None` when the legacy date is missing or imprecise (month or day unknown)
+    pub date_of_birth: Option<NaiveDate>,
+    /// Only the last four digits are kept
+    pub ssn_last4: Option<String>,
+}
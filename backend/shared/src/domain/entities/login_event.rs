@@ -0,0 +1,144 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+/// Why a login attempt failed
+///
+/// Recorded for security review only; the client always sees the same
+/// "invalid credentials" error whatever the reason.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoginFailureReason {
+    NoSuchUser,
+    BadPassword,
+    Inactive,
+    Locked,
+}
+
+impl LoginFailureReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NoSuchUser => "no_such_user",
+            Self::BadPassword => "bad_password",
+            Self::Inactive => "inactive",
+            Self::Locked => "locked",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "no_such_user" => Some(Self::NoSuchUser),
+            "bad_password" => Some(Self::BadPassword),
+            "inactive" => Some(Self::Inactive),
+            "locked" => Some(Self::Locked),
+            _ => None,
+        }
+    }
+}
+
+/// Coarse location of an IP address
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GeoLocation {
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+/// Client details of a login attempt, taken from the HTTP request
+#[derive(Debug, Clone, Default)]
+pub struct LoginAttempt {
+    pub ip_address: Option<IpAddr>,
+    pub user_agent: Option<String>,
+    pub app_type: Option<String>,
+    pub app_device: Option<String>,
+    pub request_id: Option<String>,
+}
+
+/// One password login attempt and its outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginEvent {
+    pub id: Uuid,
+    /// `None` when the email matched no user
+    pub user_id: Option<Uuid>,
+    pub email: String,
+    pub succeeded: bool,
+    pub failure_reason: Option<LoginFailureReason>,
+    pub ip_address: Option<IpAddr>,
+    pub user_agent: Option<String>,
+    pub app_type: Option<String>,
+    pub app_device: Option<String>,
+    pub request_id: Option<String>,
+    pub geo: Option<GeoLocation>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl LoginEvent {
+    pub fn success(user_id: Uuid, email: &str, attempt: LoginAttempt) -> Self {
+        Self::new(Some(user_id), email, None, attempt)
+    }
+
+    pub fn failure(
+        user_id: Option<Uuid>,
+        email: &str,
+        reason: LoginFailureReason,
+        attempt: LoginAttempt,
+    ) -> Self {
+        Self::new(user_id, email, Some(reason), attempt)
+    }
+
+    fn new(
+        user_id: Option<Uuid>,
+        email: &str,
+        failure_reason: Option<LoginFailureReason>,
+        attempt: LoginAttempt,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            email: email.trim().to_lowercase(),
+            succeeded: failure_reason.is_none(),
+            failure_reason,
+            ip_address: attempt.ip_address,
+            user_agent: attempt.user_agent,
+            app_type: attempt.app_type,
+            app_device: attempt.app_device,
+            request_id: attempt.request_id,
+            geo: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn with_geo(mut self, geo: Option<GeoLocation>) -> Self {
+        self.geo = geo;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failure_reasons_round_trip() {
+        for reason in [
+            LoginFailureReason::NoSuchUser,
+            LoginFailureReason::BadPassword,
+            LoginFailureReason::Inactive,
+            LoginFailureReason::Locked,
+        ] {
+            assert_eq!(LoginFailureReason::parse(reason.as_str()), Some(reason));
+        }
+        assert_eq!(LoginFailureReason::parse("other"), None);
+    }
+
+    #[test]
+    fn test_events_normalize_email_and_outcome() {
+        let event = LoginEvent::failure(None, " Alice@Example.COM ", LoginFailureReason::NoSuchUser, LoginAttempt::default());
+        assert_eq!(event.email, "alice@example.com");
+        assert!(!event.succeeded);
+        assert!(LoginEvent::success(Uuid::new_v4(), "a@b.c", LoginAttempt::default()).succeeded);
+    }
+}
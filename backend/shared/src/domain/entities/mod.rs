@@ -9,11 +9,15 @@ pub mod ui_page;
 pub mod ui_button;
 pub mod ui_field;
 pub mod ui_api_endpoint;
+pub mod ui_entity_version;
 pub mod module;
 pub mod policy_template;
 pub mod policy_assignment;
 pub mod session;
 pub mod request_log;
+pub mod login_event;
+pub mod api_key;
+pub mod legacy_patient;
 
 pub use user::User;
 pub use role::Role;
@@ -26,9 +30,13 @@ pub use ui_page::UiPage;
 pub use ui_button::UiButton;
 pub use ui_field::UiField;
 pub use ui_api_endpoint::UiApiEndpoint;
+pub use ui_entity_version::{UiEntityKind, UiEntityVersion};
 pub use module::Module;
 pub use policy_template::PolicyTemplate;
 pub use policy_assignment::PolicyAssignment;
 pub use session::Session;
 pub use request_log::RequestLog;
+pub use login_event::{GeoLocation, LoginAttempt, LoginEvent, LoginFailureReason};
+pub use api_key::ApiKey;
+pub use legacy_patient::{AdministrativeSex, LegacyPatient};
 
@@ -118,6 +118,11 @@ impl Relationship {
     
     /// Check if relationship is currently valid
     pub fn is_valid(&self) -> bool {
+        self.is_valid_at(Utc::now())
+    }
+
+    /// Check if relationship was in effect at `at`, judged by its current state
+    pub fn is_valid_at(&self, at: DateTime<Utc>) -> bool {
         // Check soft delete
         if self.deleted_at.is_some() {
             return false;
@@ -128,18 +133,16 @@ impl Relationship {
             return false;
         }
         
-        let now = Utc::now();
-        
         // Check valid_from
         if let Some(valid_from) = self.valid_from {
-            if now < valid_from {
+            if at < valid_from {
                 return false;
             }
         }
         
         // Check expires_at
         if let Some(expires_at) = self.expires_at {
-            if now >= expires_at {
+            if at >= expires_at {
                 return false;
             }
         }
@@ -180,6 +183,40 @@ impl Relationship {
         self.updated_at = Utc::now();
         self.version += 1;
     }
+
+    /// Check that a validity window is non-empty
+    pub fn validate_window(
+        valid_from: Option<DateTime<Utc>>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(), String> {
+        match (valid_from, expires_at) {
+            (Some(from), Some(until)) if from >= until => Err(format!(
+                "valid_from ({}) must be before valid_until ({})",
+                from, until
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Move the validity window; `None` for `valid_from` keeps the current start
+    pub fn reschedule(
+        &mut self,
+        valid_from: Option<DateTime<Utc>>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(), String> {
+        let valid_from = valid_from.or(self.valid_from);
+        Self::validate_window(valid_from, expires_at)?;
+        self.valid_from = valid_from;
+        self.expires_at = expires_at;
+        self.updated_at = Utc::now();
+        self.version += 1;
+        Ok(())
+    }
+
+    /// Whether the window has not started yet
+    pub fn is_pending(&self) -> bool {
+        self.valid_from.is_some_and(|from| Utc::now() < from)
+    }
     
     /// Encrypt sensitive metadata using user's DEK
     /// Note: This requires DekManager and user_id, so it's async
@@ -258,3 +295,58 @@ impl Relationship {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn relationship() -> Relationship {
+        Relationship::new("user:1".to_string(), "on_call".to_string(), "ward:icu".to_string())
+    }
+
+    #[test]
+    fn test_future_grant_is_not_valid_yet() {
+        let now = Utc::now();
+        let rel = Relationship::new_with_validity(
+            "user:1".to_string(),
+            "on_call".to_string(),
+            "ward:icu".to_string(),
+            now + Duration::hours(1),
+            Some(now + Duration::hours(9)),
+        );
+        assert!(rel.is_pending());
+        assert!(!rel.is_valid());
+    }
+
+    #[test]
+    fn test_expired_grant_is_not_valid() {
+        let mut rel = relationship();
+        rel.valid_from = Some(Utc::now() - Duration::hours(2));
+        rel.expires_at = Some(Utc::now() - Duration::hours(1));
+        assert!(!rel.is_pending());
+        assert!(!rel.is_valid());
+    }
+
+    #[test]
+    fn test_reschedule_shifts_window() {
+        let mut rel = relationship();
+        let from = Utc::now() + Duration::days(1);
+        let until = from + Duration::hours(12);
+        rel.reschedule(Some(from), Some(until)).unwrap();
+        assert_eq!(rel.valid_from, Some(from));
+        assert_eq!(rel.expires_at, Some(until));
+        assert_eq!(rel.version, 2);
+        assert!(!rel.is_valid());
+    }
+
+    #[test]
+    fn test_reschedule_rejects_empty_window() {
+        let mut rel = relationship();
+        let from = Utc::now() + Duration::days(1);
+        assert!(rel.reschedule(Some(from), Some(from)).is_err());
+        // Keeping the current start still has to end after it
+        let start = rel.valid_from.unwrap();
+        assert!(rel.reschedule(None, Some(start - Duration::hours(1))).is_err());
+        assert_eq!(rel.version, 1);
+    }
+}
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+/// Kind of UI entity a version belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UiEntityKind {
+    Page,
+    Button,
+    Field,
+    Api,
+}
+
+impl UiEntityKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Page => "page",
+            Self::Button => "button",
+            Self::Field => "field",
+            Self::Api => "api",
+        }
+    }
+}
+
+impl fmt::Display for UiEntityKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Accepts the singular or the plural used in route paths (`pages`)
+impl FromStr for UiEntityKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim_end_matches('s') {
+            "page" => Ok(Self::Page),
+            "button" => Ok(Self::Button),
+            "field" => Ok(Self::Field),
+            "api" => Ok(Self::Api),
+            _ => Err(format!("Unknown UI entity type: {}", s)),
+        }
+    }
+}
+
+/// A superseded version of a UI entity, recorded on every update
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UiEntityVersion {
+    pub id: i64,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub version: i64,
+    /// The entity row as it was at this version
+    pub snapshot: Value,
+    /// Who wrote this version
+    pub changed_by: Option<Uuid>,
+    pub changed_at: DateTime<Utc>,
+    /// When the next version replaced it
+    pub superseded_at: DateTime<Utc>,
+}
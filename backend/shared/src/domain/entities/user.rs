@@ -12,6 +12,11 @@ pub struct User {
     pub is_active: bool,
     pub is_verified: bool,
     pub is_super_user: bool,
+    /// Machine client that authenticates with API keys
+    pub is_service_account: bool,
+    /// Identity in the provisioning source (SCIM, LDAP) that manages the user
+    #[serde(default)]
+    pub external_id: Option<String>,
     pub organization_id: Option<Uuid>,
     pub last_login: Option<DateTime<Utc>>,
     // Audit fields
@@ -35,6 +40,8 @@ impl User {
             is_active: true,
             is_verified: false,
             is_super_user: false,
+            is_service_account: false,
+            external_id: None,
             organization_id: None,
             last_login: None,
             request_id: audit.request_id,
@@ -57,6 +64,8 @@ impl User {
             is_active: true,
             is_verified: true,
             is_super_user: true,
+            is_service_account: false,
+            external_id: None,
             organization_id: None,
             last_login: None,
             request_id: audit.request_id,
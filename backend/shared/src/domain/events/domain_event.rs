@@ -0,0 +1,167 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Domain events other services may react to
+///
+/// Serialized with an `event_type` tag; the tag is also stored in its own
+/// outbox column so consumers can be selected without parsing the payload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event_type")]
+pub enum DomainEvent {
+    UserDeactivated {
+        user_id: Uuid,
+        deactivated_by: Option<Uuid>,
+    },
+    PolicyChanged {
+        policy_name: String,
+        changed_by: Option<Uuid>,
+    },
+    SecretRotated {
+        path: String,
+        version: u64,
+    },
+    TokenRevoked {
+        token_id: Uuid,
+    },
+    /// Repeated failed logins for one username within a short window
+    LoginFailureBurst {
+        username: String,
+        failures: u32,
+        window_secs: u64,
+    },
+    /// A bulk grant or revoke changed a subject's permissions; one event
+    /// covers the whole batch
+    PermissionsBatchApplied {
+        /// Zanzibar subject, e.g. `user:{id}` or `group:{id}`
+        subject: String,
+        /// `grant` or `revoke`
+        action: String,
+        /// `relation@object` for every tuple the batch changed
+        changed: Vec<String>,
+        /// Tuples that were already in the requested state
+        unchanged: usize,
+        applied_by: Option<Uuid>,
+    },
+    /// A scheduled rotation run finished for one key type
+    KeysRotated {
+        /// Key type from the rotation schedule, e.g. `group`
+        key_type: String,
+        /// Keys rotated across all batches of the run
+        rotated: u64,
+        next_due_at: DateTime<Utc>,
+    },
+    /// A successful login looks like an account takeover
+    LoginAnomalyDetected {
+        user_id: Uuid,
+        /// The `login_events` row that was flagged
+        login_event_id: Uuid,
+        login_at: DateTime<Utc>,
+        /// Detector that flagged it, e.g. `impossible_travel`
+        kind: String,
+        /// `low`, `medium` or `high`
+        severity: String,
+        /// Detector-specific evidence
+        details: serde_json::Value,
+    },
+    /// A password reset was requested
+    ///
+    /// The token itself is handed to the mailer in-process and is not part
+    /// of the event, which is persisted in the outbox.
+    PasswordResetRequested {
+        user_id: Uuid,
+        /// The `password_reset_tokens` row
+        reset_id: Uuid,
+        expires_at: DateTime<Utc>,
+    },
+    /// A user asked to change their email; the current one stays in effect
+    ///
+    /// As with password resets, the token goes to the mailer in-process and
+    /// is not part of the event.
+    EmailChangeRequested {
+        user_id: Uuid,
+        /// The `email_change_requests` row
+        change_id: Uuid,
+        expires_at: DateTime<Utc>,
+    },
+}
+
+impl DomainEvent {
+    pub const USER_DEACTIVATED: &'static str = "UserDeactivated";
+    pub const POLICY_CHANGED: &'static str = "PolicyChanged";
+    pub const SECRET_ROTATED: &'static str = "SecretRotated";
+    pub const TOKEN_REVOKED: &'static str = "TokenRevoked";
+    pub const LOGIN_FAILURE_BURST: &'static str = "LoginFailureBurst";
+    pub const PERMISSIONS_BATCH_APPLIED: &'static str = "PermissionsBatchApplied";
+    pub const KEYS_ROTATED: &'static str = "KeysRotated";
+    pub const LOGIN_ANOMALY_DETECTED: &'static str = "LoginAnomalyDetected";
+    pub const PASSWORD_RESET_REQUESTED: &'static str = "PasswordResetRequested";
+    pub const EMAIL_CHANGE_REQUESTED: &'static str = "EmailChangeRequested";
+
+    /// Every event type, e.g. to validate configured type filters
+    pub const ALL_TYPES: [&'static str; 10] = [
+        Self::USER_DEACTIVATED,
+        Self::POLICY_CHANGED,
+        Self::SECRET_ROTATED,
+        Self::TOKEN_REVOKED,
+        Self::LOGIN_FAILURE_BURST,
+        Self::PERMISSIONS_BATCH_APPLIED,
+        Self::KEYS_ROTATED,
+        Self::LOGIN_ANOMALY_DETECTED,
+        Self::PASSWORD_RESET_REQUESTED,
+        Self::EMAIL_CHANGE_REQUESTED,
+    ];
+
+    /// Type name used to route the event to its handlers
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            DomainEvent::UserDeactivated { .. } => Self::USER_DEACTIVATED,
+            DomainEvent::PolicyChanged { .. } => Self::POLICY_CHANGED,
+            DomainEvent::SecretRotated { .. } => Self::SECRET_ROTATED,
+            DomainEvent::TokenRevoked { .. } => Self::TOKEN_REVOKED,
+            DomainEvent::LoginFailureBurst { .. } => Self::LOGIN_FAILURE_BURST,
+            DomainEvent::PermissionsBatchApplied { .. } => Self::PERMISSIONS_BATCH_APPLIED,
+            DomainEvent::KeysRotated { .. } => Self::KEYS_ROTATED,
+            DomainEvent::LoginAnomalyDetected { .. } => Self::LOGIN_ANOMALY_DETECTED,
+            DomainEvent::PasswordResetRequested { .. } => Self::PASSWORD_RESET_REQUESTED,
+            DomainEvent::EmailChangeRequested { .. } => Self::EMAIL_CHANGE_REQUESTED,
+        }
+    }
+
+    /// Identifier of the entity the event is about
+    pub fn aggregate_id(&self) -> String {
+        match self {
+            DomainEvent::UserDeactivated { user_id, .. } => user_id.to_string(),
+            DomainEvent::PolicyChanged { policy_name, .. } => policy_name.clone(),
+            DomainEvent::SecretRotated { path, .. } => path.clone(),
+            DomainEvent::TokenRevoked { token_id } => token_id.to_string(),
+            DomainEvent::LoginFailureBurst { username, .. } => username.clone(),
+            DomainEvent::PermissionsBatchApplied { subject, .. } => subject.clone(),
+            DomainEvent::KeysRotated { key_type, .. } => key_type.clone(),
+            DomainEvent::LoginAnomalyDetected { user_id, .. } => user_id.to_string(),
+            DomainEvent::PasswordResetRequested { user_id, .. } => user_id.to_string(),
+            DomainEvent::EmailChangeRequested { user_id, .. } => user_id.to_string(),
+        }
+    }
+}
+
+/// A domain event with its delivery identity
+///
+/// `id` stays the same across redeliveries, so handlers can use it to
+/// detect duplicates.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    pub id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    pub event: DomainEvent,
+}
+
+impl EventEnvelope {
+    pub fn new(event: DomainEvent) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            occurred_at: Utc::now(),
+            event,
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod domain_event;
+
+pub use domain_event::{DomainEvent, EventEnvelope};
@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+use crate::domain::entities::ApiKey;
+use crate::shared::AppResult;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    async fn create(&self, api_key: ApiKey) -> AppResult<ApiKey>;
+    /// Look up a key by hash, including revoked and expired keys
+    async fn find_by_hash(&self, key_hash: &str) -> AppResult<Option<ApiKey>>;
+    async fn list_by_user(&self, user_id: Uuid) -> AppResult<Vec<ApiKey>>;
+    /// Revoke one of `user_id`'s keys; false when no active key matched
+    async fn revoke(&self, id: Uuid, user_id: Uuid) -> AppResult<bool>;
+    async fn touch_last_used(&self, id: Uuid) -> AppResult<()>;
+}
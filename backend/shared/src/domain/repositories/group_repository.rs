@@ -1,16 +1,34 @@
 use async_trait::async_trait;
 use crate::domain::entities::Group;
-use crate::shared::AppResult;
+use crate::shared::{AppResult, ListQuery, ListSpec, Page, SortDirection};
 use uuid::Uuid;
 
+/// Sort and filter fields accepted by [`GroupRepository::list_page`]
+pub const GROUP_LIST_SPEC: ListSpec = ListSpec {
+    sortable: &[
+        ("name", "name"),
+        ("created_at", "created_at"),
+        ("updated_at", "updated_at"),
+    ],
+    filterable: &[
+        ("name", "name"),
+        ("organization_id", "organization_id"),
+    ],
+    default_sort: "name",
+    default_direction: SortDirection::Asc,
+};
+
 #[async_trait]
 pub trait GroupRepository: Send + Sync {
     async fn create(&self, group: Group) -> AppResult<Group>;
+    /// Update, failing with `VersionConflict` unless `group.version` is
+    /// still current
     async fn update(&self, group: Group) -> AppResult<Group>;
     async fn find_by_id(&self, id: Uuid) -> AppResult<Option<Group>>;
     async fn find_by_name(&self, name: &str, organization_id: Option<Uuid>) -> AppResult<Option<Group>>;
     async fn find_by_organization(&self, organization_id: Uuid) -> AppResult<Vec<Group>>;
     async fn find_all(&self) -> AppResult<Vec<Group>>;
+    async fn list_page(&self, query: &ListQuery) -> AppResult<Page<Group>>;
     async fn soft_delete(&self, id: Uuid, deleted_by: Option<Uuid>) -> AppResult<()>;
     async fn restore(&self, id: Uuid) -> AppResult<()>;
 }
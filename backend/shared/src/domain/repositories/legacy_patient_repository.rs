@@ -0,0 +1,11 @@
+use async_trait::async_trait;
+use crate::domain::entities::LegacyPatient;
+use crate::shared::AppResult;
+
+/// Read-only access to patients held in the legacy MUMPS system
+#[async_trait]
+pub trait LegacyPatientRepository: Send + Sync {
+    async fn find_by_dfn(&self, dfn: &str) -> AppResult<Option<LegacyPatient>>;
+    /// Up to `limit` patient DFNs in collation order, starting after `after`
+    async fn list_dfns(&self, after: Option<&str>, limit: usize) -> AppResult<Vec<String>>;
+}
@@ -0,0 +1,29 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use crate::domain::entities::LoginEvent;
+use crate::shared::{AppResult, ListQuery, ListSpec, Page, SortDirection};
+use uuid::Uuid;
+
+/// Sort and filter fields accepted by [`LoginEventRepository::list_page`]
+pub const LOGIN_EVENT_LIST_SPEC: ListSpec = ListSpec {
+    sortable: &[("created_at", "created_at"), ("email", "email")],
+    filterable: &[
+        ("user_id", "user_id"),
+        ("email", "email"),
+        ("succeeded", "succeeded"),
+        ("failure_reason", "failure_reason"),
+        ("ip_address", "host(ip_address)"),
+        ("app_type", "app_type"),
+        ("country", "country"),
+    ],
+    default_sort: "created_at",
+    default_direction: SortDirection::Desc,
+};
+
+#[async_trait]
+pub trait LoginEventRepository: Send + Sync {
+    async fn create(&self, event: LoginEvent) -> AppResult<LoginEvent>;
+    async fn list_page(&self, query: &ListQuery) -> AppResult<Page<LoginEvent>>;
+    /// A user's successful logins since `since`, newest first
+    async fn recent_successes(&self, user_id: Uuid, since: DateTime<Utc>, limit: u32) -> AppResult<Vec<LoginEvent>>;
+}
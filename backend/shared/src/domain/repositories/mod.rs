@@ -9,16 +9,22 @@ pub mod group_repository;
 pub mod ui_entity_repository;
 pub mod session_repository;
 pub mod request_log_repository;
+pub mod login_event_repository;
+pub mod api_key_repository;
+pub mod legacy_patient_repository;
 
-pub use user_repository::UserRepository;
+pub use user_repository::{UserRepository, USER_LIST_SPEC};
 pub use key_repository::KeyRepository;
-pub use relationship_repository::RelationshipRepository;
-pub use role_repository::RoleRepository;
-pub use permission_repository::PermissionRepository;
+pub use relationship_repository::{RelationshipFilter, RelationshipRepository, SnapshotPoint};
+pub use role_repository::{RoleRepository, ROLE_LIST_SPEC};
+pub use permission_repository::{PermissionRepository, PERMISSION_LIST_SPEC};
 pub use refresh_token_repository::RefreshTokenRepository;
 pub use setup_repository::SetupRepository;
-pub use group_repository::GroupRepository;
+pub use group_repository::{GroupRepository, GROUP_LIST_SPEC};
 pub use ui_entity_repository::UiEntityRepository;
 pub use session_repository::SessionRepository;
-pub use request_log_repository::RequestLogRepository;
+pub use request_log_repository::{RequestLogRepository, REQUEST_LOG_LIST_SPEC};
+pub use login_event_repository::{LoginEventRepository, LOGIN_EVENT_LIST_SPEC};
+pub use api_key_repository::ApiKeyRepository;
+pub use legacy_patient_repository::LegacyPatientRepository;
 
@@ -1,15 +1,38 @@
 use async_trait::async_trait;
 use crate::domain::entities::Permission;
-use crate::shared::AppResult;
+use crate::shared::{AppResult, ListQuery, ListSpec, Page, SortDirection};
 use uuid::Uuid;
 
+/// Sort and filter fields accepted by [`PermissionRepository::list_page`]
+pub const PERMISSION_LIST_SPEC: ListSpec = ListSpec {
+    sortable: &[
+        ("name", "name"),
+        ("resource", "resource"),
+        ("action", "action"),
+        ("created_at", "created_at"),
+    ],
+    filterable: &[
+        ("name", "name"),
+        ("resource", "resource"),
+        ("action", "action"),
+    ],
+    default_sort: "resource",
+    default_direction: SortDirection::Asc,
+};
+
 #[async_trait]
 pub trait PermissionRepository: Send + Sync {
     async fn create(&self, permission: Permission) -> AppResult<Permission>;
+    /// Update, failing with `VersionConflict` unless `permission.version`
+    /// is still current
+    async fn update(&self, permission: Permission) -> AppResult<Permission>;
     async fn find_by_id(&self, id: Uuid) -> AppResult<Option<Permission>>;
+    /// Every permission among `ids` in one query; missing ids are skipped
+    async fn find_by_ids(&self, ids: &[Uuid]) -> AppResult<Vec<Permission>>;
     async fn find_by_name(&self, name: &str) -> AppResult<Option<Permission>>;
     async fn find_by_resource_and_action(&self, resource: &str, action: &str) -> AppResult<Option<Permission>>;
     async fn list(&self) -> AppResult<Vec<Permission>>;
+    async fn list_page(&self, query: &ListQuery) -> AppResult<Page<Permission>>;
     async fn list_by_resource(&self, resource: &str) -> AppResult<Vec<Permission>>;
 }
 
@@ -1,8 +1,85 @@
 use async_trait::async_trait;
 use crate::domain::entities::Relationship;
+use crate::domain::value_objects::ConsistencyToken;
 use crate::shared::AppResult;
+use chrono::{DateTime, Utc};
+use std::fmt;
 use uuid::Uuid;
 
+/// Default and maximum page size for [`RelationshipFilter`]
+pub const RELATIONSHIP_PAGE_DEFAULT: u32 = 100;
+pub const RELATIONSHIP_PAGE_MAX: u32 = 1000;
+
+/// Query for relationships by subject and/or object
+///
+/// At least one of `subject` or `object` must be set. Results are ordered by
+/// relation, then by the other side of the tuple, so pages are stable.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RelationshipFilter {
+    pub subject: Option<String>,
+    pub object: Option<String>,
+    pub relation: Option<String>,
+    /// Also return inactive, expired and not-yet-valid relationships
+    pub include_invalid: bool,
+    pub limit: Option<u32>,
+    pub offset: u32,
+}
+
+impl RelationshipFilter {
+    /// All relationships a subject has, e.g. `user:{id}`
+    pub fn for_subject(subject: impl Into<String>) -> Self {
+        Self { subject: Some(subject.into()), ..Default::default() }
+    }
+
+    /// All relationships on an object
+    pub fn for_object(object: impl Into<String>) -> Self {
+        Self { object: Some(object.into()), ..Default::default() }
+    }
+
+    pub fn with_relation(mut self, relation: impl Into<String>) -> Self {
+        self.relation = Some(relation.into());
+        self
+    }
+
+    pub fn page(mut self, limit: u32, offset: u32) -> Self {
+        self.limit = Some(limit);
+        self.offset = offset;
+        self
+    }
+
+    /// Page size after applying the default and the cap
+    pub fn effective_limit(&self) -> u32 {
+        self.limit.unwrap_or(RELATIONSHIP_PAGE_DEFAULT).clamp(1, RELATIONSHIP_PAGE_MAX)
+    }
+}
+
+/// A point in relationship history
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotPoint {
+    At(DateTime<Utc>),
+    /// Everything written by the transaction behind the token and before it
+    Token(ConsistencyToken),
+}
+
+impl SnapshotPoint {
+    /// An RFC 3339 time, or otherwise a consistency token
+    pub fn parse(value: &str) -> Self {
+        match DateTime::parse_from_rfc3339(value) {
+            Ok(at) => Self::At(at.with_timezone(&Utc)),
+            Err(_) => Self::Token(ConsistencyToken::new(value.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for SnapshotPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::At(at) => write!(f, "{}", at.to_rfc3339()),
+            Self::Token(token) => write!(f, "revision {}", token),
+        }
+    }
+}
+
 #[async_trait]
 pub trait RelationshipRepository: Send + Sync {
     async fn create(&self, relationship: Relationship) -> AppResult<Relationship>;
@@ -16,6 +93,33 @@ pub trait RelationshipRepository: Send + Sync {
     async fn delete_by_tuple(&self, user: &str, relation: &str, object: &str) -> AppResult<()>;
     async fn soft_delete(&self, id: Uuid, deleted_by: Option<Uuid>) -> AppResult<()>;
     async fn list_all(&self) -> AppResult<Vec<Relationship>>;
+    /// Non-deleted relationships matching `filter`; subject or object must be set
+    async fn find_by_filter(&self, filter: &RelationshipFilter) -> AppResult<Vec<Relationship>>;
+    /// Non-deleted relationships as they stood at `point`, rebuilt from
+    /// history; metadata and audit fields are not kept there and come back empty
+    async fn list_as_of(&self, point: &SnapshotPoint) -> AppResult<Vec<Relationship>>;
+    /// Which of the `(user, relation, object)` tuples exist and are not
+    /// deleted, in any organization
+    async fn find_existing_tuples(&self, keys: &[(String, String, String)]) -> AppResult<Vec<(String, String, String)>>;
+
+    /// Live grants whose window starts after now and no later than `until`,
+    /// soonest first
+    async fn find_upcoming(&self, until: DateTime<Utc>, limit: u32) -> AppResult<Vec<Relationship>>;
+    /// Live grants whose window ends after now and no later than `until`,
+    /// soonest first
+    async fn find_expiring(&self, until: DateTime<Utc>, limit: u32) -> AppResult<Vec<Relationship>>;
+    /// Relationships with encrypted metadata, including deleted ones, in id
+    /// order after `after`
+    async fn find_with_encrypted_metadata(&self, after: Option<Uuid>, limit: u32) -> AppResult<Vec<Relationship>>;
+
+    /// Create `writes` and soft-delete the `(user, relation, object)` tuples in
+    /// `deletes` in one transaction; nothing is applied if any statement fails
+    async fn apply_batch(
+        &self,
+        writes: Vec<Relationship>,
+        deletes: Vec<(String, String, String)>,
+        deleted_by: Option<Uuid>,
+    ) -> AppResult<ConsistencyToken>;
     
     // Organization-scoped methods
     async fn find_by_user_and_org(&self, user: &str, organization_id: Uuid) -> AppResult<Vec<Relationship>>;
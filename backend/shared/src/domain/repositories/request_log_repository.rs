@@ -1,12 +1,33 @@
 use async_trait::async_trait;
 use crate::domain::entities::RequestLog;
-use crate::shared::AppResult;
+use crate::shared::{AppResult, ListQuery, ListSpec, Page, SortDirection};
 use uuid::Uuid;
 
+/// Sort and filter fields accepted by [`RequestLogRepository::list_page`]
+pub const REQUEST_LOG_LIST_SPEC: ListSpec = ListSpec {
+    sortable: &[
+        ("created_at", "created_at"),
+        ("status_code", "status_code"),
+        ("response_time_ms", "response_time_ms"),
+        ("path", "path"),
+    ],
+    filterable: &[
+        ("session_id", "session_id"),
+        ("user_id", "user_id"),
+        ("request_id", "request_id"),
+        ("method", "method"),
+        ("path", "path"),
+        ("status_code", "status_code"),
+    ],
+    default_sort: "created_at",
+    default_direction: SortDirection::Desc,
+};
+
 #[async_trait]
 pub trait RequestLogRepository: Send + Sync {
     async fn create(&self, log: RequestLog) -> AppResult<RequestLog>;
     async fn find_by_session(&self, session_id: Uuid, limit: u32) -> AppResult<Vec<RequestLog>>;
     async fn find_by_request_id(&self, request_id: &str) -> AppResult<Option<RequestLog>>;
+    async fn list_page(&self, query: &ListQuery) -> AppResult<Page<RequestLog>>;
 }
 
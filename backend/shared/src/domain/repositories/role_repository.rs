@@ -1,14 +1,35 @@
 use async_trait::async_trait;
 use crate::domain::entities::Role;
-use crate::shared::AppResult;
+use crate::shared::{AppResult, ListQuery, ListSpec, Page, SortDirection};
 use uuid::Uuid;
 
+/// Sort and filter fields accepted by [`RoleRepository::list_page`]
+pub const ROLE_LIST_SPEC: ListSpec = ListSpec {
+    sortable: &[
+        ("name", "name"),
+        ("created_at", "created_at"),
+        ("updated_at", "updated_at"),
+    ],
+    filterable: &[
+        ("name", "name"),
+    ],
+    default_sort: "name",
+    default_direction: SortDirection::Asc,
+};
+
 #[async_trait]
 pub trait RoleRepository: Send + Sync {
     async fn create(&self, role: Role) -> AppResult<Role>;
+    /// Update name and description, failing with `VersionConflict` unless
+    /// `role.version` is still current
+    async fn update(&self, role: Role) -> AppResult<Role>;
+    /// Delete the role and every relationship naming it, both its grants
+    /// and its holders; `user_roles` rows go with it
+    async fn delete(&self, id: Uuid) -> AppResult<()>;
     async fn find_by_id(&self, id: Uuid) -> AppResult<Option<Role>>;
     async fn find_by_name(&self, name: &str) -> AppResult<Option<Role>>;
     async fn list(&self) -> AppResult<Vec<Role>>;
+    async fn list_page(&self, query: &ListQuery) -> AppResult<Page<Role>>;
     async fn add_permission_to_role(&self, role_id: Uuid, permission_id: Uuid) -> AppResult<()>;
     async fn remove_permission_from_role(&self, role_id: Uuid, permission_id: Uuid) -> AppResult<()>;
     async fn get_role_permissions(&self, role_id: Uuid) -> AppResult<Vec<Uuid>>;
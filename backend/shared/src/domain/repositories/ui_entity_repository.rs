@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use crate::domain::entities::{UiPage, UiButton, UiField, UiApiEndpoint};
+use crate::domain::entities::{UiPage, UiButton, UiField, UiApiEndpoint, UiEntityKind, UiEntityVersion};
 use crate::shared::AppResult;
 use uuid::Uuid;
 
@@ -37,5 +37,13 @@ pub trait UiEntityRepository: Send + Sync {
     async fn list_apis(&self) -> AppResult<Vec<UiApiEndpoint>>;
     async fn update_api(&self, api: UiApiEndpoint) -> AppResult<UiApiEndpoint>;
     async fn soft_delete_api(&self, id: Uuid, deleted_by: Option<Uuid>) -> AppResult<()>;
+
+    // Version history, recorded by the database on every update
+    /// Superseded versions of an entity, newest first
+    async fn list_versions(&self, kind: UiEntityKind, entity_id: Uuid) -> AppResult<Vec<UiEntityVersion>>;
+    async fn find_version(&self, kind: UiEntityKind, entity_id: Uuid, version: i64) -> AppResult<Option<UiEntityVersion>>;
+
+    /// Changes whenever any relationship or UI entity does
+    async fn visibility_stamp(&self) -> AppResult<String>;
 }
 
@@ -1,16 +1,43 @@
 use async_trait::async_trait;
 use crate::domain::entities::User;
-use crate::shared::AppResult;
+use crate::shared::{AppResult, ListQuery, ListSpec, Page, SortDirection};
 use uuid::Uuid;
 
+/// Sort and filter fields accepted by [`UserRepository::list_page`]
+pub const USER_LIST_SPEC: ListSpec = ListSpec {
+    sortable: &[
+        ("email", "email"),
+        ("username", "username"),
+        ("created_at", "created_at"),
+        ("last_login", "last_login"),
+    ],
+    filterable: &[
+        ("email", "email"),
+        ("username", "username"),
+        ("is_active", "is_active"),
+        ("is_verified", "is_verified"),
+        ("is_super_user", "is_super_user"),
+        ("is_service_account", "is_service_account"),
+        ("external_id", "external_id"),
+        ("organization_id", "organization_id"),
+    ],
+    default_sort: "created_at",
+    default_direction: SortDirection::Desc,
+};
+
 #[async_trait]
 pub trait UserRepository: Send + Sync {
     async fn create(&self, user: User) -> AppResult<User>;
     async fn find_by_id(&self, id: Uuid) -> AppResult<Option<User>>;
     async fn find_by_email(&self, email: &str) -> AppResult<Option<User>>;
     async fn find_by_username(&self, username: &str) -> AppResult<Option<User>>;
+    /// The user a provisioning source knows as `external_id`
+    async fn find_by_external_id(&self, external_id: &str) -> AppResult<Option<User>>;
+    /// Update, failing with `VersionConflict` unless `user.version` is
+    /// still current
     async fn update(&self, user: User) -> AppResult<User>;
     async fn delete(&self, id: Uuid) -> AppResult<()>;
     async fn list(&self, limit: u32, offset: u32) -> AppResult<Vec<User>>;
+    async fn list_page(&self, query: &ListQuery) -> AppResult<Page<User>>;
 }
 
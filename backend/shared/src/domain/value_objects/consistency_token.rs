@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Opaque marker of the write that produced it
+///
+/// Backed by the id of the database transaction that applied the write, so
+/// tokens of later transactions compare greater. Callers should treat the
+/// value as opaque and only hand it back to this service.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct ConsistencyToken {
+    value: String,
+}
+
+impl ConsistencyToken {
+    pub fn new(value: String) -> Self {
+        Self { value }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+}
+
+impl fmt::Display for ConsistencyToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
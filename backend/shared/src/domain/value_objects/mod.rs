@@ -1,6 +1,8 @@
+pub mod consistency_token;
 pub mod email;
 pub mod encrypted_value;
 
+pub use consistency_token::ConsistencyToken;
 pub use email::Email;
 pub use encrypted_value::EncryptedValue;
 
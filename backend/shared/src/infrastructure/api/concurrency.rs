@@ -0,0 +1,260 @@
+//! Bounds on requests handled at once
+//!
+//! [`ConcurrencyLimitLayer`] caps in-flight requests across all clients, and
+//! optionally under individual path prefixes, answering 503 with
+//! `Retry-After` once a cap is reached. It complements the per-client rate
+//! limits: those stop one caller from hogging the service, this stops a spike
+//! across all callers from exhausting database connections and memory. Shed
+//! requests never reach the handler, so they cost next to nothing.
+//!
+//! A slot is held until the handler has produced its response; streaming
+//! the body afterwards does not count.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::{
+    extract::Request,
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use tower::{Layer, Service};
+
+use crate::config::settings::ConcurrencyConfig;
+
+/// Requests in flight against an optional maximum
+struct Slots {
+    /// 0 leaves the count unbounded
+    max: usize,
+    in_flight: AtomicUsize,
+}
+
+impl Slots {
+    fn new(max: usize) -> Self {
+        Slots { max, in_flight: AtomicUsize::new(0) }
+    }
+
+    fn try_acquire(&self) -> bool {
+        if self.max == 0 {
+            self.in_flight.fetch_add(1, Ordering::AcqRel);
+            return true;
+        }
+        self.in_flight
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| (n < self.max).then_some(n + 1))
+            .is_ok()
+    }
+
+    fn release(&self) {
+        self.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Point-in-time view of a limit, for metrics and status pages
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LimitSnapshot {
+    pub in_flight: usize,
+    /// 0 when unbounded
+    pub max_in_flight: usize,
+}
+
+/// Point-in-time view of a limiter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConcurrencySnapshot {
+    #[serde(flatten)]
+    pub global: LimitSnapshot,
+    /// Requests shed since startup
+    pub rejected: u64,
+    /// Per-route limits by path prefix
+    pub routes: BTreeMap<String, LimitSnapshot>,
+}
+
+/// Global and per-route in-flight request counts
+pub struct ConcurrencyLimiter {
+    global: Slots,
+    routes: Vec<(String, Slots)>,
+    retry_after_secs: u64,
+    rejected: AtomicU64,
+}
+
+/// A request's slots, released when dropped
+pub struct Permit {
+    limiter: Arc<ConcurrencyLimiter>,
+    route: Option<usize>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if let Some(route) = self.route {
+            self.limiter.routes[route].1.release();
+        }
+        self.limiter.global.release();
+    }
+}
+
+impl ConcurrencyLimiter {
+    pub fn new(config: &ConcurrencyConfig) -> Self {
+        ConcurrencyLimiter {
+            global: Slots::new(config.max_in_flight),
+            routes: config.routes.iter().map(|(prefix, max)| (prefix.clone(), Slots::new(*max))).collect(),
+            retry_after_secs: config.retry_after_secs,
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// The longest configured prefix of `path` decides its route limit
+    fn route_of(&self, path: &str) -> Option<usize> {
+        self.routes
+            .iter()
+            .enumerate()
+            .filter(|(_, (prefix, _))| path.starts_with(prefix.as_str()))
+            .max_by_key(|(_, (prefix, _))| prefix.len())
+            .map(|(index, _)| index)
+    }
+
+    /// Slots for a request to `path`, or `None` when a limit is reached
+    pub fn try_acquire(self: &Arc<Self>, path: &str) -> Option<Permit> {
+        let route = self.route_of(path);
+        if !self.global.try_acquire() {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        if let Some(route) = route {
+            if !self.routes[route].1.try_acquire() {
+                self.global.release();
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+        }
+        Some(Permit { limiter: self.clone(), route })
+    }
+
+    pub fn snapshot(&self) -> ConcurrencySnapshot {
+        let limit = |slots: &Slots| LimitSnapshot {
+            in_flight: slots.in_flight.load(Ordering::Acquire),
+            max_in_flight: slots.max,
+        };
+        ConcurrencySnapshot {
+            global: limit(&self.global),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            routes: self.routes.iter().map(|(prefix, slots)| (prefix.clone(), limit(slots))).collect(),
+        }
+    }
+
+    /// Response to a request shed because a limit was reached
+    fn overloaded(&self) -> Response {
+        let mut response = (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Server is busy, retry later" })),
+        )
+            .into_response();
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(self.retry_after_secs));
+        response
+    }
+}
+
+/// Tower layer enforcing a [`ConcurrencyLimiter`]
+#[derive(Clone)]
+pub struct ConcurrencyLimitLayer {
+    limiter: Arc<ConcurrencyLimiter>,
+}
+
+impl ConcurrencyLimitLayer {
+    pub fn new(limiter: Arc<ConcurrencyLimiter>) -> Self {
+        ConcurrencyLimitLayer { limiter }
+    }
+}
+
+impl<S> Layer<S> for ConcurrencyLimitLayer {
+    type Service = ConcurrencyLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConcurrencyLimitService { inner, limiter: self.limiter.clone() }
+    }
+}
+
+#[derive(Clone)]
+pub struct ConcurrencyLimitService<S> {
+    inner: S,
+    limiter: Arc<ConcurrencyLimiter>,
+}
+
+impl<S> Service<Request> for ConcurrencyLimitService<S>
+where
+    S: Service<Request, Response = Response> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let Some(permit) = self.limiter.try_acquire(request.uri().path()) else {
+            let response = self.limiter.overloaded();
+            return Box::pin(async move { Ok(response) });
+        };
+        let future = self.inner.call(request);
+        Box::pin(async move {
+            let response = future.await;
+            drop(permit);
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(max_in_flight: usize, routes: &[(&str, usize)]) -> Arc<ConcurrencyLimiter> {
+        Arc::new(ConcurrencyLimiter::new(&ConcurrencyConfig {
+            max_in_flight,
+            routes: routes.iter().map(|(prefix, max)| (prefix.to_string(), *max)).collect(),
+            retry_after_secs: 2,
+        }))
+    }
+
+    #[test]
+    fn test_global_limit_sheds_and_recovers() {
+        let limiter = limiter(2, &[]);
+        let first = limiter.try_acquire("/v1/users").unwrap();
+        let _second = limiter.try_acquire("/v1/users").unwrap();
+        assert!(limiter.try_acquire("/v1/users").is_none());
+        assert_eq!(limiter.snapshot().global, LimitSnapshot { in_flight: 2, max_in_flight: 2 });
+        assert_eq!(limiter.snapshot().rejected, 1);
+
+        drop(first);
+        assert!(limiter.try_acquire("/v1/users").is_some());
+    }
+
+    #[test]
+    fn test_route_limits_use_the_longest_prefix() {
+        let limiter = limiter(0, &[("/v1/reports", 1), ("/v1/reports/export", 2)]);
+        let _report = limiter.try_acquire("/v1/reports/daily").unwrap();
+        assert!(limiter.try_acquire("/v1/reports/weekly").is_none());
+
+        // A full route neither holds a global slot nor blocks other routes
+        let _exports = (limiter.try_acquire("/v1/reports/export/a"), limiter.try_acquire("/v1/reports/export/b"));
+        assert!(limiter.try_acquire("/v1/reports/export/c").is_none());
+        assert!(limiter.try_acquire("/v1/users").is_some());
+        assert_eq!(limiter.snapshot().global.in_flight, 3);
+    }
+
+    #[test]
+    fn test_overloaded_response_asks_to_retry() {
+        let response = limiter(1, &[]).overloaded();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "2");
+    }
+}
@@ -0,0 +1,129 @@
+//! Conditional GET support for read-only endpoints
+//! Weak ETags are derived from row versions (or the response body) and
+//! matched against `If-None-Match` so unchanged resources return 304
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Weak ETag over the identity and version of every row in a response
+///
+/// Any insert, delete or update (which bumps `version` and `updated_at`)
+/// changes the tag.
+pub fn weak_etag_for_rows<I>(rows: I) -> String
+where
+    I: IntoIterator<Item = (Uuid, i64, DateTime<Utc>)>,
+{
+    let mut hasher = Sha256::new();
+    for (id, version, updated_at) in rows {
+        hasher.update(id.as_bytes());
+        hasher.update(version.to_be_bytes());
+        hasher.update(updated_at.timestamp_micros().to_be_bytes());
+    }
+    format!("W/\"{}\"", hex::encode(&hasher.finalize()[..16]))
+}
+
+/// Weak ETag over a serialized response body, for computed responses
+/// that have no row version
+pub fn weak_etag_for_body(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    format!("W/\"{}\"", hex::encode(&digest[..16]))
+}
+
+/// Whether `If-None-Match` matches `etag` (weak comparison)
+pub fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    let Some(value) = headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let wanted = opaque(etag);
+    value.split(',').any(|candidate| {
+        let candidate = candidate.trim();
+        candidate == "*" || opaque(candidate) == wanted
+    })
+}
+
+/// 304 if the client already has `etag`, otherwise 200 with the JSON body
+///
+/// Responses are per-user, so caches must keep them private and
+/// revalidate on every use.
+pub fn conditional_json<T: Serialize>(headers: &HeaderMap, etag: String, body: T) -> Response {
+    let status = if if_none_match(headers, &etag) {
+        StatusCode::NOT_MODIFIED
+    } else {
+        StatusCode::OK
+    };
+    let mut response = if status == StatusCode::NOT_MODIFIED {
+        status.into_response()
+    } else {
+        (status, Json(body)).into_response()
+    };
+
+    let response_headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        response_headers.insert(header::ETAG, value);
+    }
+    response_headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("private, no-cache"));
+    response
+}
+
+/// Like [`conditional_json`], with the ETag computed from the body itself
+pub fn conditional_json_by_body<T: Serialize>(headers: &HeaderMap, body: T) -> Response {
+    match serde_json::to_vec(&body) {
+        Ok(bytes) => conditional_json(headers, weak_etag_for_body(&bytes), body),
+        // Not cacheable, but still a valid response
+        Err(_) => (StatusCode::OK, Json(body)).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers_with(if_none_match: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(if_none_match).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_row_etag_changes_with_version() {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        let v1 = weak_etag_for_rows([(id, 1, now)]);
+        assert_eq!(v1, weak_etag_for_rows([(id, 1, now)]));
+        assert_ne!(v1, weak_etag_for_rows([(id, 2, now)]));
+        assert_ne!(v1, weak_etag_for_rows([]));
+        assert!(v1.starts_with("W/\""));
+    }
+
+    #[test]
+    fn test_if_none_match_uses_weak_comparison() {
+        let etag = weak_etag_for_body(b"{}");
+        let opaque = etag.trim_start_matches("W/");
+        assert!(if_none_match(&headers_with(&etag), &etag));
+        assert!(if_none_match(&headers_with(opaque), &etag));
+        assert!(if_none_match(&headers_with(&format!("\"other\", {}", etag)), &etag));
+        assert!(if_none_match(&headers_with("*"), &etag));
+        assert!(!if_none_match(&headers_with("W/\"other\""), &etag));
+        assert!(!if_none_match(&HeaderMap::new(), &etag));
+    }
+
+    #[test]
+    fn test_conditional_json_returns_not_modified() {
+        let etag = weak_etag_for_body(b"x");
+        let fresh = conditional_json(&HeaderMap::new(), etag.clone(), serde_json::json!({"a": 1}));
+        assert_eq!(fresh.status(), StatusCode::OK);
+        assert_eq!(fresh.headers()[header::ETAG], etag.as_str());
+
+        let cached = conditional_json(&headers_with(&etag), etag.clone(), serde_json::json!({"a": 1}));
+        assert_eq!(cached.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(cached.headers()[header::ETAG], etag.as_str());
+    }
+}
@@ -1,4 +1,7 @@
 //! API infrastructure utilities
-//! Provides shared functionality for API versioning and negotiation
+//! Provides shared functionality for API versioning, negotiation, conditional
+//! GETs and load shedding
 
 pub mod version;
+pub mod etag;
+pub mod concurrency;
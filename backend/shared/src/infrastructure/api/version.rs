@@ -1,102 +1,170 @@
-//! API versioning middleware and utilities
-//! Supports URL-based versioning (/v1/, /v2/) and header-based version negotiation
+//! API versioning and negotiation
+//!
+//! A request's version comes from its path prefix (`/v1/...`) or, on paths
+//! without one, from the `Accept-Version` header (`1` or `v1`). Requests
+//! naming neither get [`DEFAULT_VERSION`]. A version this build does not
+//! serve, or a header contradicting the path, is refused with 406 before
+//! routing. Every response carries the version it was served with in
+//! `API-Version`, and handlers can take [`ApiVersion`] as an extractor.
+//!
+//! # Adding a version
+//!
+//! Add a variant, list it in [`SUPPORTED_VERSIONS`] and mount its handlers
+//! under the new prefix (`/v2/...`). The v1 routes stay as they are, so v1
+//! clients are unaffected; handlers shared by both can branch on the
+//! extracted version. Move [`DEFAULT_VERSION`] only once clients that send
+//! no version have been told to pin one.
+//!
+//! # Deprecation
+//!
+//! A version is deprecated by giving it a [`ApiVersion::sunset`] date.
+//! Responses served with it then carry `Deprecation: true` and `Sunset`
+//! (RFC 8594) so clients can see the removal coming. After that date the
+//! version is dropped from [`SUPPORTED_VERSIONS`] and requests for it get
+//! 406.
 
 use axum::{
-    extract::Request,
-    http::{HeaderMap, HeaderValue, StatusCode},
+    extract::{FromRequestParts, Request},
+    http::{request::Parts, HeaderMap, HeaderName, HeaderValue, StatusCode},
     middleware::Next,
-    response::{Response, IntoResponse},
+    response::{IntoResponse, Response},
+    Json,
 };
-use std::str::FromStr;
 
-/// API version extracted from request
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Header a client pins its version with on unversioned paths
+pub const ACCEPT_VERSION: HeaderName = HeaderName::from_static("accept-version");
+
+/// Header naming the version a response was served with
+pub const API_VERSION: HeaderName = HeaderName::from_static("api-version");
+
+/// A version of the API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ApiVersion {
     V1,
-    V2,
-    Unversioned,
 }
 
+/// Version of requests that do not ask for one
+pub const DEFAULT_VERSION: ApiVersion = ApiVersion::V1;
+
+/// Versions this build serves
+pub const SUPPORTED_VERSIONS: &[ApiVersion] = &[ApiVersion::V1];
+
 impl ApiVersion {
-    /// Extract version from URL path
-    pub fn from_path(path: &str) -> Self {
-        if path.starts_with("/v2/") {
-            ApiVersion::V2
-        } else if path.starts_with("/v1/") {
-            ApiVersion::V1
-        } else {
-            ApiVersion::Unversioned
+    pub fn number(&self) -> u32 {
+        match self {
+            ApiVersion::V1 => 1,
         }
     }
 
-    /// Extract version from Accept header
-    /// Supports: `Accept: application/vnd.api+json;version=1`
-    pub fn from_accept_header(headers: &HeaderMap) -> Option<Self> {
-        if let Some(accept) = headers.get("Accept") {
-            if let Ok(accept_str) = accept.to_str() {
-                // Parse version from Accept header
-                if accept_str.contains("version=2") {
-                    return Some(ApiVersion::V2);
-                } else if accept_str.contains("version=1") {
-                    return Some(ApiVersion::V1);
-                }
-            }
-        }
-        None
+    /// The supported version numbered `number`
+    pub fn from_number(number: u32) -> Option<Self> {
+        SUPPORTED_VERSIONS.iter().copied().find(|v| v.number() == number)
     }
 
-    /// Get version string for response headers
-    pub fn to_header_value(&self) -> &'static str {
+    /// HTTP-date after which the version is removed; `None` while current
+    pub fn sunset(&self) -> Option<&'static str> {
         match self {
-            ApiVersion::V1 => "1",
-            ApiVersion::V2 => "2",
-            ApiVersion::Unversioned => "1", // Default to v1 for unversioned
+            ApiVersion::V1 => None,
         }
     }
 }
 
-/// Middleware to add API version information to response headers
-/// Adds `X-API-Version` header to all responses
-pub async fn version_middleware(
-    request: Request,
-    next: Next,
-) -> Response {
-    let version = ApiVersion::from_path(request.uri().path());
-    
-    // If no version in URL, check Accept header
-    let version = if version == ApiVersion::Unversioned {
-        ApiVersion::from_accept_header(request.headers())
-            .unwrap_or(ApiVersion::V1)
-    } else {
-        version
+/// Version number of a `/v{n}/` path prefix
+fn path_version(path: &str) -> Option<u32> {
+    let rest = path.strip_prefix("/v")?;
+    let end = rest.find('/').unwrap_or(rest.len());
+    let digits = &rest[..end];
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Version number of the `Accept-Version` header, if sent
+fn header_version(headers: &HeaderMap) -> Result<Option<u32>, String> {
+    let Some(value) = headers.get(ACCEPT_VERSION) else {
+        return Ok(None);
     };
+    let raw = value.to_str().unwrap_or("").trim();
+    raw.strip_prefix(['v', 'V'])
+        .unwrap_or(raw)
+        .parse()
+        .map(Some)
+        .map_err(|_| format!("Invalid Accept-Version '{}'; expected a version number such as 1", raw))
+}
 
-    let mut response = next.run(request).await;
-    
-    // Add version header to response
-    if let Ok(header_value) = HeaderValue::from_str(version.to_header_value()) {
-        response.headers_mut().insert("X-API-Version", header_value);
-    }
+/// The version a request is served with, or why it cannot be
+pub fn negotiate(path: &str, headers: &HeaderMap) -> Result<ApiVersion, String> {
+    let requested = match (path_version(path), header_version(headers)?) {
+        (Some(in_path), Some(in_header)) if in_path != in_header => {
+            return Err(format!("Accept-Version {} contradicts the /v{} path", in_header, in_path));
+        }
+        (Some(number), _) | (None, Some(number)) => number,
+        (None, None) => return Ok(DEFAULT_VERSION),
+    };
+    ApiVersion::from_number(requested).ok_or_else(|| {
+        let supported: Vec<String> = SUPPORTED_VERSIONS.iter().map(|v| v.number().to_string()).collect();
+        format!("API version {} is not supported (supported: {})", requested, supported.join(", "))
+    })
+}
 
-    // Add deprecation warning for unversioned routes
-    if version == ApiVersion::Unversioned {
-        if let Ok(warning) = HeaderValue::from_str(
-            r#"299 - "Deprecated: This endpoint will be removed in a future version. Please use /v1/ prefix."#
-        ) {
-            response.headers_mut().insert("Warning", warning);
+/// Negotiate the version of every request and label every response with it
+pub async fn version_middleware(mut request: Request, next: Next) -> Response {
+    let version = match negotiate(request.uri().path(), request.headers()) {
+        Ok(version) => version,
+        Err(message) => {
+            return (StatusCode::NOT_ACCEPTABLE, Json(serde_json::json!({ "error": message }))).into_response();
         }
-    }
+    };
+    request.extensions_mut().insert(version);
 
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(API_VERSION, HeaderValue::from(version.number()));
+    if let Some(sunset) = version.sunset() {
+        headers.insert(HeaderName::from_static("deprecation"), HeaderValue::from_static("true"));
+        headers.insert(HeaderName::from_static("sunset"), HeaderValue::from_static(sunset));
+    }
     response
 }
 
-/// Get current API version from request
-pub fn get_api_version(request: &Request) -> ApiVersion {
-    let version = ApiVersion::from_path(request.uri().path());
-    if version == ApiVersion::Unversioned {
-        ApiVersion::from_accept_header(request.headers())
-            .unwrap_or(ApiVersion::V1)
-    } else {
-        version
+/// The negotiated version, or the default outside `version_middleware`
+impl<S: Send + Sync> FromRequestParts<S> for ApiVersion {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts.extensions.get::<ApiVersion>().copied().unwrap_or(DEFAULT_VERSION))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accept_version(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT_VERSION, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_path_and_header_negotiation() {
+        let none = HeaderMap::new();
+        assert_eq!(negotiate("/v1/users", &none), Ok(ApiVersion::V1));
+        assert_eq!(negotiate("/health", &none), Ok(DEFAULT_VERSION));
+        assert_eq!(negotiate("/vault/x", &none), Ok(DEFAULT_VERSION));
+        // SCIM's own protocol version is not ours
+        assert_eq!(negotiate("/scim/v2/Users", &none), Ok(DEFAULT_VERSION));
+        assert_eq!(negotiate("/health", &accept_version("v1")), Ok(ApiVersion::V1));
+        assert_eq!(negotiate("/v1/users", &accept_version("1")), Ok(ApiVersion::V1));
+    }
+
+    #[test]
+    fn test_unsupported_or_conflicting_versions_are_refused() {
+        let none = HeaderMap::new();
+        assert!(negotiate("/v2/users", &none).unwrap_err().contains("not supported"));
+        assert!(negotiate("/health", &accept_version("2")).is_err());
+        assert!(negotiate("/health", &accept_version("latest")).is_err());
+        assert!(negotiate("/v1/users", &accept_version("2")).unwrap_err().contains("contradicts"));
     }
 }
@@ -0,0 +1,84 @@
+//! Counters in process memory, for a single node and tests
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use super::CounterStore;
+use crate::shared::AppResult;
+
+/// Counters at which an increment first drops every ended window
+const SWEEP_THRESHOLD: usize = 1024;
+
+#[derive(Default)]
+pub struct InMemoryCounterStore {
+    /// Count and end of the window of each key
+    counters: Mutex<HashMap<String, (u64, Instant)>>,
+}
+
+impl InMemoryCounterStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CounterStore for InMemoryCounterStore {
+    async fn increment(&self, key: &str, window: Duration) -> AppResult<u64> {
+        let now = Instant::now();
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        if counters.len() >= SWEEP_THRESHOLD {
+            counters.retain(|_, (_, ends)| *ends > now);
+        }
+        let counter = counters.entry(key.to_string()).or_insert((0, now + window));
+        if counter.1 <= now {
+            *counter = (0, now + window);
+        }
+        counter.0 += 1;
+        Ok(counter.0)
+    }
+
+    async fn get(&self, key: &str) -> AppResult<u64> {
+        let counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(counters
+            .get(key)
+            .filter(|(_, ends)| *ends > Instant::now())
+            .map_or(0, |(count, _)| *count))
+    }
+
+    async fn reset(&self, key: &str) -> AppResult<()> {
+        self.counters.lock().unwrap_or_else(|e| e.into_inner()).remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_counts_within_a_window() {
+        let store = InMemoryCounterStore::new();
+        let window = Duration::from_secs(60);
+        assert_eq!(store.get("login:alice").await.unwrap(), 0);
+        assert_eq!(store.increment("login:alice", window).await.unwrap(), 1);
+        assert_eq!(store.increment("login:alice", window).await.unwrap(), 2);
+        assert_eq!(store.increment("login:bob", window).await.unwrap(), 1);
+        assert_eq!(store.get("login:alice").await.unwrap(), 2);
+
+        store.reset("login:alice").await.unwrap();
+        assert_eq!(store.get("login:alice").await.unwrap(), 0);
+        assert_eq!(store.get("login:bob").await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_counters_expire_with_their_window() {
+        let store = InMemoryCounterStore::new();
+        store.increment("login:alice", Duration::from_millis(20)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(store.get("login:alice").await.unwrap(), 0);
+        assert_eq!(store.increment("login:alice", Duration::from_millis(20)).await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,35 @@
+//! Expiring counters for rate limits and lockouts
+//!
+//! A limit enforced by one replica's memory lets a client through N times
+//! per replica behind a load balancer. [`CounterStore`] puts the counts
+//! somewhere every replica shares: Redis for multi-node deployments, or
+//! process memory for a single node and tests. `COUNTER_STORE` picks one
+//! (see `create_counter_store`).
+//!
+//! Counts are fixed windows: the increment that creates a counter starts
+//! its window, and the counter is gone once the window ends.
+
+pub mod memory;
+pub mod redis;
+
+pub use memory::InMemoryCounterStore;
+pub use self::redis::RedisCounterStore;
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::shared::AppResult;
+
+#[async_trait]
+pub trait CounterStore: Send + Sync {
+    /// Add one to `key` and return the new count, starting a `window`-long
+    /// window if the counter did not exist
+    async fn increment(&self, key: &str, window: Duration) -> AppResult<u64>;
+
+    /// Current count of `key`; 0 once its window has ended
+    async fn get(&self, key: &str) -> AppResult<u64>;
+
+    /// Drop `key`'s counter, e.g. after a successful login
+    async fn reset(&self, key: &str) -> AppResult<()>;
+}
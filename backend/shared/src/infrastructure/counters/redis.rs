@@ -0,0 +1,72 @@
+//! Counters in Redis, shared by every replica
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use redis::{AsyncCommands, Script};
+
+use super::CounterStore;
+use crate::shared::{AppError, AppResult};
+
+/// INCR, and PEXPIRE on the increment that created the key, as one atomic
+/// step: a separate EXPIRE could be lost to a crash or a racing replica and
+/// leave a counter that never resets
+const INCREMENT_SCRIPT: &str = r#"
+local count = redis.call('INCR', KEYS[1])
+if count == 1 then
+    redis.call('PEXPIRE', KEYS[1], ARGV[1])
+end
+return count
+"#;
+
+pub struct RedisCounterStore {
+    connection: ConnectionManager,
+    /// Prepended to every key, so services can share one Redis
+    key_prefix: String,
+    increment: Script,
+}
+
+impl RedisCounterStore {
+    pub async fn connect(url: &str, key_prefix: &str) -> AppResult<Self> {
+        let client = redis::Client::open(url)
+            .map_err(|e| AppError::Configuration(format!("Invalid Redis URL: {}", e)))?;
+        let connection = ConnectionManager::new(client).await.map_err(unavailable)?;
+        Ok(RedisCounterStore {
+            connection,
+            key_prefix: key_prefix.to_string(),
+            increment: Script::new(INCREMENT_SCRIPT),
+        })
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}{}", self.key_prefix, key)
+    }
+}
+
+fn unavailable(e: redis::RedisError) -> AppError {
+    AppError::Unavailable(format!("Redis: {}", e))
+}
+
+#[async_trait]
+impl CounterStore for RedisCounterStore {
+    async fn increment(&self, key: &str, window: Duration) -> AppResult<u64> {
+        // At least 1ms, since PEXPIRE 0 deletes the key it just counted
+        let window_ms = window.as_millis().max(1) as u64;
+        self.increment
+            .key(self.key(key))
+            .arg(window_ms)
+            .invoke_async(&mut self.connection.clone())
+            .await
+            .map_err(unavailable)
+    }
+
+    async fn get(&self, key: &str) -> AppResult<u64> {
+        let count: Option<u64> = self.connection.clone().get(self.key(key)).await.map_err(unavailable)?;
+        Ok(count.unwrap_or(0))
+    }
+
+    async fn reset(&self, key: &str) -> AppResult<()> {
+        self.connection.clone().del::<_, ()>(self.key(key)).await.map_err(unavailable)
+    }
+}
@@ -0,0 +1,143 @@
+//! Distributed locking with PostgreSQL advisory locks
+//!
+//! Used to make background jobs (token reaping, key rotation, CRL rotation)
+//! run on exactly one replica at a time.
+//!
+//! Advisory locks are session-scoped: the lock belongs to the database
+//! connection that acquired it. This is what makes crashed holders safe —
+//! when a replica dies its connection drops and PostgreSQL releases the lock
+//! automatically. The flip side is that the lock must be released on the
+//! same connection, so a dedicated pooled connection is held for the whole
+//! critical section, and it is closed rather than returned to the pool if
+//! the section is abandoned without unlocking.
+
+use std::future::Future;
+use sha2::{Digest, Sha256};
+use sqlx::pool::PoolConnection;
+use sqlx::{PgPool, Postgres};
+use crate::shared::{AppError, AppResult};
+
+/// Distributed lock helper backed by `pg_advisory_lock`
+#[derive(Clone)]
+pub struct DistributedLock {
+    pool: PgPool,
+}
+
+impl DistributedLock {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Map a lock name to the 64-bit key used by PostgreSQL
+    pub fn lock_id(key: &str) -> i64 {
+        let digest = Sha256::digest(key.as_bytes());
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        i64::from_be_bytes(bytes)
+    }
+
+    /// Run `f` while holding the lock, waiting until it becomes available
+    pub async fn with_lock<F, Fut, T>(&self, key: &str, f: F) -> AppResult<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let lock_id = Self::lock_id(key);
+        let mut conn = self.pool.acquire().await.map_err(AppError::Database)?;
+        sqlx::query("SELECT pg_advisory_lock($1)")
+            .bind(lock_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(AppError::Database)?;
+
+        let guard = LockGuard { conn: Some(conn), lock_id };
+        let result = f().await;
+        guard.release().await?;
+        Ok(result)
+    }
+
+    /// Run `f` only if the lock is free, returning `None` when another holder has it
+    ///
+    /// This is the usual mode for periodic singleton jobs: replicas that lose
+    /// the race simply skip the run.
+    pub async fn try_with_lock<F, Fut, T>(&self, key: &str, f: F) -> AppResult<Option<T>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let lock_id = Self::lock_id(key);
+        let mut conn = self.pool.acquire().await.map_err(AppError::Database)?;
+        let acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
+            .bind(lock_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(AppError::Database)?;
+        if !acquired {
+            return Ok(None);
+        }
+
+        let guard = LockGuard { conn: Some(conn), lock_id };
+        let result = f().await;
+        guard.release().await?;
+        Ok(Some(result))
+    }
+}
+
+/// Holds the connection owning an advisory lock
+///
+/// If dropped without [`LockGuard::release`] (panic or cancelled future) the
+/// connection is detached from the pool and closed, which ends the session
+/// and releases the lock instead of leaking it to the next pool user.
+struct LockGuard {
+    conn: Option<PoolConnection<Postgres>>,
+    lock_id: i64,
+}
+
+impl LockGuard {
+    async fn release(mut self) -> AppResult<()> {
+        let Some(mut conn) = self.conn.take() else {
+            return Ok(());
+        };
+        let unlocked: Result<bool, sqlx::Error> = sqlx::query_scalar("SELECT pg_advisory_unlock($1)")
+            .bind(self.lock_id)
+            .fetch_one(&mut *conn)
+            .await;
+        match unlocked {
+            Ok(true) => Ok(()),
+            Ok(false) => {
+                tracing::warn!("Advisory lock {} was not held at release", self.lock_id);
+                Ok(())
+            }
+            Err(e) => {
+                // Close the session so the lock cannot outlive this holder
+                drop(conn.detach());
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            drop(conn.detach());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_id_is_stable_and_distinct() {
+        assert_eq!(
+            DistributedLock::lock_id("rustyvault:expiration-reaper"),
+            DistributedLock::lock_id("rustyvault:expiration-reaper")
+        );
+        assert_ne!(
+            DistributedLock::lock_id("rustyvault:expiration-reaper"),
+            DistributedLock::lock_id("rustyvault:key-rotation")
+        );
+    }
+}
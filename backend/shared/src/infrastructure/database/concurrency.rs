@@ -0,0 +1,38 @@
+//! Optimistic concurrency for entity updates
+//!
+//! Updates run `... WHERE id = $1 AND version = <expected>` and bump the
+//! version. When no row comes back, [`stale_update_error`] tells a concurrent
+//! edit apart from a missing row.
+
+use crate::shared::AppError;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Error for an update that matched no row
+///
+/// `table` must be a trusted, static table name. Rows are considered missing
+/// when `live_filter` (e.g. `deleted_at IS NULL`) excludes them.
+pub async fn stale_update_error(
+    pool: &PgPool,
+    table: &str,
+    live_filter: Option<&str>,
+    entity: &str,
+    id: Uuid,
+    expected_version: i64,
+) -> AppError {
+    let mut sql = format!("SELECT version FROM {} WHERE id = $1", table);
+    if let Some(filter) = live_filter {
+        sql.push_str(" AND ");
+        sql.push_str(filter);
+    }
+    match sqlx::query_scalar::<_, i64>(&sql).bind(id).fetch_optional(pool).await {
+        Ok(Some(current_version)) => AppError::VersionConflict {
+            entity: entity.to_string(),
+            id,
+            expected_version,
+            current_version,
+        },
+        Ok(None) => AppError::NotFound(format!("{} {} not found", entity, id)),
+        Err(e) => AppError::Database(e),
+    }
+}
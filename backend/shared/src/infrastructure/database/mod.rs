@@ -6,8 +6,14 @@ pub mod rls;
 pub mod migrations;
 pub mod db_service;
 pub mod queries;
+pub mod advisory_lock;
+pub mod pagination;
+pub mod concurrency;
 
 pub use local_db::LocalDb;
 pub use live_db::LiveDb;
 pub use db_service::{DatabaseService, create_pool, create_pool_with_options};
+pub use advisory_lock::DistributedLock;
+pub use pagination::fetch_page;
+pub use concurrency::stale_update_error;
 
@@ -0,0 +1,534 @@
+//! Read-only, pooled client for a MUMPS gateway
+//!
+//! Legacy data (YottaDB/GT.M or Caché) is reached through a small gateway
+//! routine listening on TCP inside the M environment. This client is
+//! entirely separate from the Postgres `DatabaseService` and can only read:
+//! the protocol has no verb that sets or kills a node.
+//!
+//! # Protocol
+//!
+//! One request line, one reply, lines terminated by `\r\n`:
+//!
+//! ```text
+//! NS <namespace>     ->  +OK              switch namespace (sent on connect)
+//! PING               ->  +PONG
+//! GET <ref>          ->  $<len>\r\n<bytes> | $-1 (undefined)    $GET(ref)
+//! DATA <ref>         ->  :<0|1|10|11>                           $DATA(ref)
+//! ORDER <ref>        ->  $<len>\r\n<bytes>, $0 at the end      $ORDER(ref)
+//! any                ->  -<code> <message>                      M error
+//! ```
+//!
+//! `<ref>` is a global reference such as `^DPT("42","0")`, with embedded
+//! quotes doubled. Values are decoded as UTF-8, falling back to Latin-1
+//! (the usual encoding of older M systems).
+//!
+//! Reads are idempotent, so a request that fails on a pooled connection the
+//! gateway has since closed is retried once on a fresh connection. Values
+//! may contain PHI and are never logged.
+
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::Semaphore;
+use tokio::time::timeout;
+use crate::infrastructure::database::mumps::{Global, MumpsError};
+use crate::shared::AppResult;
+
+/// Longest reply line accepted (excluding values)
+const MAX_LINE_LEN: u64 = 1024;
+/// Largest value accepted; well above the M string limit
+const MAX_VALUE_LEN: usize = 4 * 1024 * 1024;
+/// Longest global name M allows
+const MAX_NAME_LEN: usize = 31;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MumpsConfig {
+    pub host: String,
+    pub port: u16,
+    /// Caché namespace / YottaDB environment to switch to on connect
+    pub namespace: Option<String>,
+    /// Maximum open connections
+    pub pool_size: usize,
+    pub connect_timeout: Duration,
+    pub request_timeout: Duration,
+    /// How long a request waits for a free connection
+    pub acquire_timeout: Duration,
+}
+
+impl MumpsConfig {
+    /// Config from `MUMPS_*` variables; `None` unless `MUMPS_HOST` is set
+    pub fn from_env() -> Option<Self> {
+        let host = std::env::var("MUMPS_HOST").ok().filter(|h| !h.trim().is_empty())?;
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let millis = |name: &str, default: u64| {
+            Duration::from_millis(var(name).and_then(|v| v.parse().ok()).unwrap_or(default))
+        };
+        Some(Self {
+            host,
+            port: var("MUMPS_PORT").and_then(|v| v.parse().ok()).unwrap_or(9080),
+            namespace: var("MUMPS_NAMESPACE"),
+            pool_size: var("MUMPS_POOL_SIZE").and_then(|v| v.parse().ok()).unwrap_or(4),
+            connect_timeout: millis("MUMPS_CONNECT_TIMEOUT_MS", 2000),
+            request_timeout: millis("MUMPS_REQUEST_TIMEOUT_MS", 5000),
+            acquire_timeout: millis("MUMPS_ACQUIRE_TIMEOUT_MS", 5000),
+        })
+    }
+
+    fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+/// Result of `$DATA` on a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeState {
+    pub has_value: bool,
+    pub has_children: bool,
+}
+
+impl NodeState {
+    pub fn exists(&self) -> bool {
+        self.has_value || self.has_children
+    }
+}
+
+/// Pooled, read-only MUMPS client
+pub struct MumpsClient {
+    config: MumpsConfig,
+    idle: Mutex<Vec<Connection>>,
+    permits: Semaphore,
+}
+
+impl MumpsClient {
+    pub fn new(config: MumpsConfig) -> Self {
+        let permits = Semaphore::new(config.pool_size.max(1));
+        Self {
+            config,
+            idle: Mutex::new(Vec::new()),
+            permits,
+        }
+    }
+
+    /// `$GET`: the node's value, `None` if it has none
+    pub async fn get(&self, global: &Global) -> AppResult<Option<String>> {
+        let line = format!("GET {}", reference(global, false)?);
+        match self.request(line, "GET").await? {
+            Reply::Value(value) => Ok(value),
+            other => Err(unexpected_reply("GET", other).into()),
+        }
+    }
+
+    /// `$DATA`: whether the node has a value and/or descendants
+    pub async fn data(&self, global: &Global) -> AppResult<NodeState> {
+        let line = format!("DATA {}", reference(global, false)?);
+        match self.request(line, "DATA").await? {
+            Reply::Integer(n @ (0 | 1 | 10 | 11)) => Ok(NodeState {
+                has_value: n % 10 == 1,
+                has_children: n >= 10,
+            }),
+            other => Err(unexpected_reply("DATA", other).into()),
+        }
+    }
+
+    /// `$ORDER`: the subscript following the last one of `global`, `None` at
+    /// the end. An empty last subscript starts from the beginning.
+    pub async fn next_subscript(&self, global: &Global) -> AppResult<Option<String>> {
+        if global.subscripts.is_empty() {
+            return Err(MumpsError::InvalidReference("$ORDER needs at least one subscript".to_string()).into());
+        }
+        let line = format!("ORDER {}", reference(global, true)?);
+        match self.request(line, "ORDER").await? {
+            Reply::Value(Some(next)) if !next.is_empty() => Ok(Some(next)),
+            Reply::Value(_) => Ok(None),
+            other => Err(unexpected_reply("ORDER", other).into()),
+        }
+    }
+
+    /// Check the gateway is reachable
+    pub async fn ping(&self) -> AppResult<()> {
+        match self.request("PING".to_string(), "PING").await? {
+            Reply::Status(_) => Ok(()),
+            other => Err(unexpected_reply("PING", other).into()),
+        }
+    }
+
+    async fn request(&self, line: String, operation: &'static str) -> Result<Reply, MumpsError> {
+        let _permit = timeout(self.config.acquire_timeout, self.permits.acquire())
+            .await
+            .map_err(|_| MumpsError::PoolExhausted(self.config.acquire_timeout))?
+            .map_err(|_| MumpsError::Protocol("connection pool is closed".to_string()))?;
+
+        let reply = match self.take_idle() {
+            Some(mut connection) => match self.call(&mut connection, &line, operation).await {
+                Ok(reply) => {
+                    self.put_idle(connection);
+                    reply
+                }
+                // The gateway may have closed an idle connection; reads are safe to retry
+                Err(MumpsError::Io(e)) => {
+                    tracing::debug!("Pooled MUMPS connection failed ({}), reconnecting", e);
+                    self.call_fresh(&line, operation).await?
+                }
+                Err(e) => return Err(e),
+            },
+            None => self.call_fresh(&line, operation).await?,
+        };
+
+        match reply {
+            Reply::Error { code, message } => Err(MumpsError::Remote { code, message }),
+            reply => Ok(reply),
+        }
+    }
+
+    async fn call_fresh(&self, line: &str, operation: &'static str) -> Result<Reply, MumpsError> {
+        let mut connection = timeout(self.config.connect_timeout, Connection::open(&self.config))
+            .await
+            .map_err(|_| MumpsError::Timeout {
+                operation: "connect",
+                after: self.config.connect_timeout,
+            })??;
+        let reply = self.call(&mut connection, line, operation).await?;
+        self.put_idle(connection);
+        Ok(reply)
+    }
+
+    /// Send one request; the connection is dropped by the caller on error
+    async fn call(&self, connection: &mut Connection, line: &str, operation: &'static str) -> Result<Reply, MumpsError> {
+        timeout(self.config.request_timeout, connection.call(line))
+            .await
+            .map_err(|_| MumpsError::Timeout {
+                operation,
+                after: self.config.request_timeout,
+            })?
+    }
+
+    fn take_idle(&self) -> Option<Connection> {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner()).pop()
+    }
+
+    fn put_idle(&self, connection: Connection) {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner()).push(connection);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Reply {
+    Status(String),
+    Value(Option<String>),
+    Integer(i64),
+    Error { code: String, message: String },
+}
+
+fn unexpected_reply(operation: &str, reply: Reply) -> MumpsError {
+    let kind = match reply {
+        Reply::Status(_) => "status",
+        Reply::Value(_) => "value",
+        Reply::Integer(_) => "integer",
+        Reply::Error { .. } => "error",
+    };
+    MumpsError::Protocol(format!("unexpected {} reply to {}", kind, operation))
+}
+
+struct Connection {
+    stream: BufReader<TcpStream>,
+}
+
+impl Connection {
+    async fn open(config: &MumpsConfig) -> Result<Self, MumpsError> {
+        let address = config.address();
+        let stream = TcpStream::connect(&address)
+            .await
+            .map_err(|source| MumpsError::Connect { address, source })?;
+        stream.set_nodelay(true)?;
+        let mut connection = Self {
+            stream: BufReader::new(stream),
+        };
+        if let Some(namespace) = &config.namespace {
+            if !is_valid_name(namespace) {
+                return Err(MumpsError::InvalidReference(format!("invalid namespace '{}'", namespace)));
+            }
+            match connection.call(&format!("NS {}", namespace)).await? {
+                Reply::Status(_) => {}
+                Reply::Error { code, message } => return Err(MumpsError::Remote { code, message }),
+                other => return Err(unexpected_reply("NS", other)),
+            }
+        }
+        Ok(connection)
+    }
+
+    async fn call(&mut self, line: &str) -> Result<Reply, MumpsError> {
+        let stream = self.stream.get_mut();
+        stream.write_all(line.as_bytes()).await?;
+        stream.write_all(b"\r\n").await?;
+        stream.flush().await?;
+        self.read_reply().await
+    }
+
+    async fn read_reply(&mut self) -> Result<Reply, MumpsError> {
+        let line = self.read_line().await?;
+        let mut chars = line.chars();
+        let kind = chars.next();
+        let rest = chars.as_str();
+        match kind {
+            Some('+') => Ok(Reply::Status(rest.to_string())),
+            Some(':') => rest
+                .parse()
+                .map(Reply::Integer)
+                .map_err(|_| MumpsError::Protocol(format!("invalid integer reply '{}'", rest))),
+            Some('-') => {
+                let (code, message) = rest.split_once(' ').unwrap_or((rest, ""));
+                Ok(Reply::Error {
+                    code: code.to_string(),
+                    message: message.to_string(),
+                })
+            }
+            Some('$') if rest == "-1" => Ok(Reply::Value(None)),
+            Some('$') => {
+                let len: usize = rest
+                    .parse()
+                    .map_err(|_| MumpsError::Protocol(format!("invalid value length '{}'", rest)))?;
+                if len > MAX_VALUE_LEN {
+                    return Err(MumpsError::Protocol(format!("value of {} bytes exceeds the limit", len)));
+                }
+                let mut bytes = vec![0u8; len + 2];
+                self.stream.read_exact(&mut bytes).await?;
+                if !bytes.ends_with(b"\r\n") {
+                    return Err(MumpsError::Protocol("value is not terminated by CRLF".to_string()));
+                }
+                bytes.truncate(len);
+                Ok(Reply::Value(Some(decode(bytes))))
+            }
+            _ => Err(MumpsError::Protocol("unrecognised reply".to_string())),
+        }
+    }
+
+    async fn read_line(&mut self) -> Result<String, MumpsError> {
+        let mut bytes = Vec::new();
+        let read = (&mut self.stream).take(MAX_LINE_LEN).read_until(b'\n', &mut bytes).await?;
+        if read == 0 {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        if !bytes.ends_with(b"\r\n") {
+            return Err(MumpsError::Protocol("reply line is too long or not terminated by CRLF".to_string()));
+        }
+        bytes.truncate(bytes.len() - 2);
+        Ok(decode(bytes))
+    }
+}
+
+/// UTF-8, or Latin-1 when the bytes are not valid UTF-8
+fn decode(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes).unwrap_or_else(|e| e.into_bytes().into_iter().map(char::from).collect())
+}
+
+/// `%` or a letter, then letters and digits
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c == '%' || c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric())
+        && name.len() <= MAX_NAME_LEN
+}
+
+/// Encode `global` as a quoted M reference; an empty subscript is only
+/// allowed in last position, and only for `$ORDER`
+fn reference(global: &Global, allow_trailing_empty: bool) -> Result<String, MumpsError> {
+    if !is_valid_name(&global.name) {
+        return Err(MumpsError::InvalidReference(format!("invalid global name '{}'", global.name)));
+    }
+    let last = global.subscripts.len().saturating_sub(1);
+    let mut subscripts = Vec::with_capacity(global.subscripts.len());
+    for (index, subscript) in global.subscripts.iter().enumerate() {
+        if subscript.chars().any(char::is_control) {
+            return Err(MumpsError::InvalidReference(format!(
+                "subscript {} of ^{} contains control characters",
+                index + 1,
+                global.name
+            )));
+        }
+        if subscript.is_empty() && !(allow_trailing_empty && index == last) {
+            return Err(MumpsError::InvalidReference(format!(
+                "subscript {} of ^{} is empty",
+                index + 1,
+                global.name
+            )));
+        }
+        subscripts.push(format!("\"{}\"", subscript.replace('"', "\"\"")));
+    }
+    if subscripts.is_empty() {
+        Ok(format!("^{}", global.name))
+    } else {
+        Ok(format!("^{}({})", global.name, subscripts.join(",")))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+    use crate::shared::AppError;
+
+    /// In-process gateway serving `nodes` (reference -> value) with M's
+    /// `$GET`/`$DATA`/`$ORDER` semantics; counts accepted connections
+    pub(crate) async fn fake_gateway(nodes: &[(&str, &str)]) -> (MumpsConfig, Arc<AtomicUsize>) {
+        let nodes: Arc<BTreeMap<Vec<String>, String>> = Arc::new(
+            nodes
+                .iter()
+                .map(|(reference, value)| (parse(reference), value.to_string()))
+                .collect(),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let accepted = connections.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                let nodes = nodes.clone();
+                tokio::spawn(async move {
+                    let (read, mut write) = socket.into_split();
+                    let mut lines = BufReader::new(read).lines();
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        let line = line.trim_end_matches('\r');
+                        let (verb, arg) = line.split_once(' ').unwrap_or((line, ""));
+                        let key = parse(arg);
+                        let reply = match verb {
+                            "PING" | "NS" => "+OK\r\n".to_string(),
+                            "SLEEP" => {
+                                tokio::time::sleep(Duration::from_secs(5)).await;
+                                "+OK\r\n".to_string()
+                            }
+                            "GET" if key[0] == "^BAD" => "-M7 <UNDEFINED>\r\n".to_string(),
+                            "GET" => match nodes.get(&key) {
+                                Some(value) => format!("${}\r\n{}\r\n", value.len(), value),
+                                None => "$-1\r\n".to_string(),
+                            },
+                            "DATA" => {
+                                let value = nodes.contains_key(&key) as i64;
+                                let children = nodes.keys().any(|k| k.len() > key.len() && k.starts_with(&key));
+                                format!(":{}\r\n", value + 10 * children as i64)
+                            }
+                            "ORDER" => {
+                                let (parent, from) = key.split_at(key.len() - 1);
+                                let next = nodes
+                                    .keys()
+                                    .filter(|k| k.len() > parent.len() && k.starts_with(parent))
+                                    .map(|k| k[parent.len()].clone())
+                                    .filter(|s| from[0].is_empty() || collates_after(s, &from[0]))
+                                    .min_by(|a, b| collation(a, b))
+                                    .unwrap_or_default();
+                                format!("${}\r\n{}\r\n", next.len(), next)
+                            }
+                            _ => "-M1 <SYNTAX>\r\n".to_string(),
+                        };
+                        if write.write_all(reply.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        let config = MumpsConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            namespace: Some("VAH".to_string()),
+            pool_size: 2,
+            connect_timeout: Duration::from_secs(1),
+            request_timeout: Duration::from_millis(200),
+            acquire_timeout: Duration::from_millis(200),
+        };
+        (config, connections)
+    }
+
+    /// `^DPT("1","0")` -> `["^DPT", "1", "0"]`
+    fn parse(reference: &str) -> Vec<String> {
+        let (name, rest) = reference.split_once('(').unwrap_or((reference, ""));
+        let mut key = vec![name.to_string()];
+        let rest = rest.trim_end_matches(')');
+        if !rest.is_empty() {
+            key.extend(rest.split("\",\"").map(|s| s.trim_matches('"').replace("\"\"", "\"")));
+        }
+        key
+    }
+
+    /// M collation: canonic numbers first, numerically, then strings
+    fn collation(a: &String, b: &String) -> std::cmp::Ordering {
+        match (a.parse::<f64>(), b.parse::<f64>()) {
+            (Ok(x), Ok(y)) => x.partial_cmp(&y).unwrap(),
+            (Ok(_), Err(_)) => std::cmp::Ordering::Less,
+            (Err(_), Ok(_)) => std::cmp::Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        }
+    }
+
+    fn collates_after(a: &String, b: &String) -> bool {
+        collation(a, b) == std::cmp::Ordering::Greater
+    }
+
+    fn dpt(subscripts: &[&str]) -> Global {
+        Global::new("DPT".to_string()).with_subscripts(subscripts.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn test_reference_encoding() {
+        assert_eq!(reference(&dpt(&["1", "0"]), false).unwrap(), "^DPT(\"1\",\"0\")");
+        assert_eq!(reference(&dpt(&["B", "O\"BRIEN"]), false).unwrap(), "^DPT(\"B\",\"O\"\"BRIEN\")");
+        assert_eq!(reference(&dpt(&[""]), true).unwrap(), "^DPT(\"\")");
+        assert!(reference(&dpt(&[""]), false).is_err());
+        assert!(reference(&dpt(&["1\r\nKILL ^DPT"]), false).is_err());
+        assert!(reference(&Global::new("DPT;X".to_string()), false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reads_and_pools_connections() {
+        let (config, connections) =
+            fake_gateway(&[("^DPT(\"1\",\"0\")", "DOE,JANE^F"), ("^DPT(\"2\",\"0\")", "ROE,RICHARD^M")]).await;
+        let client = MumpsClient::new(config);
+
+        client.ping().await.unwrap();
+        assert_eq!(client.get(&dpt(&["1", "0"])).await.unwrap(), Some("DOE,JANE^F".to_string()));
+        assert_eq!(client.get(&dpt(&["3", "0"])).await.unwrap(), None);
+        assert_eq!(
+            client.data(&dpt(&["1"])).await.unwrap(),
+            NodeState { has_value: false, has_children: true }
+        );
+        assert!(!client.data(&dpt(&["3"])).await.unwrap().exists());
+        assert_eq!(client.next_subscript(&dpt(&[""])).await.unwrap(), Some("1".to_string()));
+        assert_eq!(client.next_subscript(&dpt(&["1"])).await.unwrap(), Some("2".to_string()));
+        assert_eq!(client.next_subscript(&dpt(&["2"])).await.unwrap(), None);
+
+        // Sequential requests reuse one connection
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_typed() {
+        let (config, _) = fake_gateway(&[]).await;
+        let client = MumpsClient::new(config);
+
+        match client.get(&Global::new("BAD".to_string())).await {
+            Err(AppError::Mumps(MumpsError::Remote { code, message })) => {
+                assert_eq!(code, "M7");
+                assert_eq!(message, "<UNDEFINED>");
+            }
+            other => panic!("expected remote error, got {:?}", other),
+        }
+        // The connection survives an M error
+        client.ping().await.unwrap();
+
+        let slow = client.request("SLEEP".to_string(), "SLEEP").await;
+        assert!(matches!(slow, Err(MumpsError::Timeout { operation: "SLEEP", .. })));
+
+        let unreachable = MumpsClient::new(MumpsConfig {
+            port: 1,
+            ..client.config.clone()
+        });
+        assert!(matches!(
+            unreachable.ping().await,
+            Err(AppError::Mumps(MumpsError::Connect { .. } | MumpsError::Timeout { .. }))
+        ));
+    }
+}
@@ -0,0 +1,37 @@
+use std::time::Duration;
+use thiserror::Error;
+
+/// Failures talking to, or reading data from, the legacy MUMPS database
+#[derive(Debug, Error)]
+pub enum MumpsError {
+    #[error("cannot connect to MUMPS gateway at {address}: {source}")]
+    Connect {
+        address: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("MUMPS connection failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("MUMPS {operation} timed out after {after:?}")]
+    Timeout { operation: &'static str, after: Duration },
+
+    #[error("no MUMPS connection available within {0:?}")]
+    PoolExhausted(Duration),
+
+    /// The gateway sent something that is not a valid reply
+    #[error("MUMPS protocol error: {0}")]
+    Protocol(String),
+
+    /// An M error raised while evaluating the request, e.g. `M7 <UNDEFINED>`
+    #[error("MUMPS error {code}: {message}")]
+    Remote { code: String, message: String },
+
+    #[error("invalid global reference: {0}")]
+    InvalidReference(String),
+
+    /// A node exists but its contents do not have the expected layout
+    #[error("unexpected structure at {reference}: {reason}")]
+    UnexpectedStructure { reference: String, reason: String },
+}
@@ -1,8 +1,13 @@
 pub mod globals;
 pub mod hierarchical;
 pub mod query;
+pub mod error;
+pub mod client;
+pub mod patient;
 
 pub use globals::Global;
 pub use hierarchical::HierarchicalAccess;
 pub use query::MumpsQuery;
-
+pub use error::MumpsError;
+pub use client::{MumpsClient, MumpsConfig, NodeState};
+pub use patient::MumpsLegacyPatientRepository;
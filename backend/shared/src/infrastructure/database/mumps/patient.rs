@@ -0,0 +1,219 @@
+//! Legacy patient demographics from the VistA PATIENT file (#2)
+//!
+//! Patients live under `^DPT(DFN)`. The zero node `^DPT(DFN,0)` holds the
+//! demographics as `^`-delimited pieces: 1 NAME (`FAMILY,GIVEN MIDDLE`),
+//! 2 SEX, 3 DATE OF BIRTH (FileMan date) and 9 SSN. Patients are the
+//! positive numeric subscripts; `^DPT(0)` is the file header and the string
+//! subscripts that collate after them (`"B"`, `"SSN"`, ...) are indexes.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use crate::domain::entities::{AdministrativeSex, LegacyPatient};
+use crate::domain::repositories::LegacyPatientRepository;
+use crate::infrastructure::database::mumps::{Global, MumpsClient, MumpsError};
+use crate::shared::{AppError, AppResult};
+
+/// Global holding the PATIENT file
+pub const PATIENT_GLOBAL: &str = "DPT";
+
+/// Most DFNs returned by one `list_dfns` call
+pub const MAX_LIST_LIMIT: usize = 500;
+
+pub struct MumpsLegacyPatientRepository {
+    client: Arc<MumpsClient>,
+}
+
+impl MumpsLegacyPatientRepository {
+    pub fn new(client: Arc<MumpsClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl LegacyPatientRepository for MumpsLegacyPatientRepository {
+    async fn find_by_dfn(&self, dfn: &str) -> AppResult<Option<LegacyPatient>> {
+        if !is_dfn(dfn) {
+            return Err(AppError::Validation(format!("'{}' is not a patient DFN", dfn)));
+        }
+        let zero_node = patient_global(&[dfn, "0"]);
+        match self.client.get(&zero_node).await? {
+            Some(node) => Ok(Some(parse_zero_node(dfn, &node).map_err(|reason| {
+                MumpsError::UnexpectedStructure {
+                    reference: format!("^{}({},0)", PATIENT_GLOBAL, dfn),
+                    reason,
+                }
+            })?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn list_dfns(&self, after: Option<&str>, limit: usize) -> AppResult<Vec<String>> {
+        if let Some(after) = after {
+            if !is_dfn(after) {
+                return Err(AppError::Validation(format!("'{}' is not a patient DFN", after)));
+            }
+        }
+        let limit = limit.min(MAX_LIST_LIMIT);
+        let mut dfns = Vec::with_capacity(limit);
+        // Start after the file header at ^DPT(0)
+        let mut cursor = after.unwrap_or("0").to_string();
+        while dfns.len() < limit {
+            match self.client.next_subscript(&patient_global(&[&cursor])).await? {
+                // Numeric subscripts collate first, so the first string one ends the patients
+                Some(next) if is_dfn(&next) => {
+                    dfns.push(next.clone());
+                    cursor = next;
+                }
+                _ => break,
+            }
+        }
+        Ok(dfns)
+    }
+}
+
+fn patient_global(subscripts: &[&str]) -> Global {
+    Global::new(PATIENT_GLOBAL.to_string()).with_subscripts(subscripts.iter().map(|s| s.to_string()).collect())
+}
+
+/// DFNs are positive canonic numbers; VistA allows a fractional part
+fn is_dfn(value: &str) -> bool {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    !whole.is_empty()
+        && whole.bytes().all(|b| b.is_ascii_digit())
+        && !whole.starts_with('0')
+        && fraction.bytes().all(|b| b.is_ascii_digit())
+        && !fraction.ends_with('0')
+        && !value.ends_with('.')
+}
+
+/// Map a `^DPT(DFN,0)` node to a patient; the error describes what is wrong
+pub fn parse_zero_node(dfn: &str, node: &str) -> Result<LegacyPatient, String> {
+    let pieces: Vec<&str> = node.split('^').collect();
+    let piece = |n: usize| pieces.get(n - 1).map(|p| p.trim()).unwrap_or("");
+
+    let name = piece(1);
+    if name.is_empty() {
+        return Err("NAME (piece 1) is empty".to_string());
+    }
+    let (family_name, given) = name.split_once(',').unwrap_or((name, ""));
+    if family_name.trim().is_empty() {
+        return Err("NAME (piece 1) has no family name".to_string());
+    }
+    let given_names = given.split_whitespace().map(str::to_string).collect();
+
+    let sex = match piece(2) {
+        "M" => AdministrativeSex::Male,
+        "F" => AdministrativeSex::Female,
+        "" => AdministrativeSex::Unknown,
+        other => return Err(format!("SEX (piece 2) '{}' is not M or F", other)),
+    };
+
+    let date_of_birth = parse_fileman_date(piece(3)).map_err(|e| format!("DATE OF BIRTH (piece 3) {}", e))?;
+
+    let digits: String = piece(9).chars().filter(char::is_ascii_digit).collect();
+    let ssn_last4 = match digits.len() {
+        0 => None,
+        // A trailing "P" marks a pseudo SSN, which carries no real digits
+        9 if !piece(9).ends_with('P') => Some(digits[5..].to_string()),
+        9 => None,
+        _ => return Err("SSN (piece 9) is not nine digits".to_string()),
+    };
+
+    Ok(LegacyPatient {
+        dfn: dfn.to_string(),
+        family_name: family_name.trim().to_string(),
+        given_names,
+        sex,
+        date_of_birth,
+        ssn_last4,
+    })
+}
+
+/// FileMan dates are `YYYMMDD[.HHMMSS]` with YYY = year - 1700. Imprecise
+/// dates (month or day `00`) have no calendar date and map to `None`.
+fn parse_fileman_date(raw: &str) -> Result<Option<NaiveDate>, String> {
+    if raw.is_empty() {
+        return Ok(None);
+    }
+    let date = raw.split('.').next().unwrap_or("");
+    if date.len() != 7 || !date.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("'{}' is not a FileMan date", raw));
+    }
+    let number = |range: std::ops::Range<usize>| date[range].parse::<u32>().unwrap_or(0);
+    let (year, month, day) = (number(0..3) as i32 + 1700, number(3..5), number(5..7));
+    if month == 0 || day == 0 {
+        return Ok(None);
+    }
+    NaiveDate::from_ymd_opt(year, month, day)
+        .map(Some)
+        .ok_or_else(|| format!("'{}' is not a valid date", raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::database::mumps::client::tests::fake_gateway;
+
+    #[test]
+    fn test_parse_zero_node() {
+        let patient = parse_zero_node("7", "DOE,JANE ANN^F^2450312^^^^^^123456789").unwrap();
+        assert_eq!(patient.family_name, "DOE");
+        assert_eq!(patient.given_names, vec!["JANE", "ANN"]);
+        assert_eq!(patient.sex, AdministrativeSex::Female);
+        assert_eq!(patient.date_of_birth, NaiveDate::from_ymd_opt(1945, 3, 12));
+        assert_eq!(patient.ssn_last4.as_deref(), Some("6789"));
+
+        // Sparse nodes and imprecise dates are fine
+        let patient = parse_zero_node("8", "ROE,RICHARD^^2450300").unwrap();
+        assert_eq!(patient.sex, AdministrativeSex::Unknown);
+        assert_eq!(patient.date_of_birth, None);
+        assert_eq!(patient.ssn_last4, None);
+
+        assert_eq!(parse_zero_node("9", "SMITH^M^^^^^^^123456789P").unwrap().ssn_last4, None);
+    }
+
+    #[test]
+    fn test_rejects_unexpected_structure() {
+        assert!(parse_zero_node("1", "").is_err());
+        assert!(parse_zero_node("1", ",JANE^F").is_err());
+        assert!(parse_zero_node("1", "DOE,JANE^X").is_err());
+        assert!(parse_zero_node("1", "DOE,JANE^F^19450312").is_err());
+        assert!(parse_zero_node("1", "DOE,JANE^F^2451332").is_err());
+        assert!(parse_zero_node("1", "DOE,JANE^F^2450312^^^^^^1234").is_err());
+    }
+
+    #[test]
+    fn test_is_dfn() {
+        for dfn in ["1", "42", "100.5"] {
+            assert!(is_dfn(dfn), "{}", dfn);
+        }
+        for value in ["", "0", "007", "B", "1.", "1.50", "-1", "1E3"] {
+            assert!(!is_dfn(value), "{}", value);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reads_patients_through_gateway() {
+        let (config, _) = fake_gateway(&[
+            ("^DPT(\"0\")", "PATIENT^2I^3^3"),
+            ("^DPT(\"1\",\"0\")", "DOE,JANE^F^2450312"),
+            ("^DPT(\"2\",\"0\")", "ROE,RICHARD^Q"),
+            ("^DPT(\"10\",\"0\")", "POE,EDGAR^M"),
+            ("^DPT(\"B\",\"DOE,JANE\",\"1\")", ""),
+        ])
+        .await;
+        let repository = MumpsLegacyPatientRepository::new(Arc::new(MumpsClient::new(config)));
+
+        assert_eq!(repository.find_by_dfn("1").await.unwrap().unwrap().family_name, "DOE");
+        assert_eq!(repository.find_by_dfn("3").await.unwrap(), None);
+        assert!(matches!(
+            repository.find_by_dfn("2").await,
+            Err(AppError::Mumps(MumpsError::UnexpectedStructure { .. }))
+        ));
+        assert!(matches!(repository.find_by_dfn("B").await, Err(AppError::Validation(_))));
+
+        assert_eq!(repository.list_dfns(None, 10).await.unwrap(), vec!["1", "2", "10"]);
+        assert_eq!(repository.list_dfns(Some("1"), 1).await.unwrap(), vec!["2"]);
+    }
+}
@@ -0,0 +1,77 @@
+//! SQL for [`ListQuery`] listings
+//!
+//! Column names only ever come from the listing's [`ListSpec`]; filter values
+//! are bound. Filters compare the column's text form so one code path serves
+//! text, uuid, integer and boolean columns.
+
+use crate::shared::{AppError, AppResult, ListQuery, ListSpec, Page};
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder};
+
+/// Fetch one page of `columns` from `table`
+///
+/// `base_filter` is a fixed SQL condition (e.g. `deleted_at IS NULL`) applied
+/// in addition to the client's filters.
+pub async fn fetch_page<T>(
+    pool: &PgPool,
+    table: &str,
+    columns: &str,
+    base_filter: Option<&str>,
+    query: &ListQuery,
+    spec: &ListSpec,
+) -> AppResult<Page<T>>
+where
+    T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+{
+    query.validate(spec)?;
+    let offset = query.offset()?;
+
+    let mut count = QueryBuilder::<Postgres>::new(format!("SELECT COUNT(*) FROM {}", table));
+    push_filters(&mut count, base_filter, query, spec);
+    let total: i64 = count
+        .build_query_scalar()
+        .fetch_one(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+    let mut select = QueryBuilder::<Postgres>::new(format!("SELECT {} FROM {}", columns, table));
+    push_filters(&mut select, base_filter, query, spec);
+    let (column, direction) = query.order_by(spec);
+    // id as tie-breaker keeps pages stable when the sort column repeats
+    select.push(format!(" ORDER BY {} {}, id {}", column, direction.as_sql(), direction.as_sql()));
+    select
+        .push(" LIMIT ")
+        .push_bind(query.limit as i64)
+        .push(" OFFSET ")
+        .push_bind(offset as i64);
+
+    let items = select
+        .build_query_as::<T>()
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+    Ok(Page::new(items, offset, total))
+}
+
+fn push_filters(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    base_filter: Option<&str>,
+    query: &ListQuery,
+    spec: &ListSpec,
+) {
+    let mut keyword = " WHERE ";
+    if let Some(condition) = base_filter {
+        builder.push(keyword).push(condition);
+        keyword = " AND ";
+    }
+    for (field, value) in &query.filters {
+        if let Some(column) = spec.filter_column(field) {
+            builder
+                .push(keyword)
+                .push(format!("{}::text = ", column))
+                .push_bind(value.clone());
+            keyword = " AND ";
+        }
+    }
+}
@@ -43,11 +43,12 @@ pub const UI_PAGE_LIST: &str = r#"
 "#;
 
 /// Update UI page
+/// `version` always advances so read ETags change on every update
 pub const UI_PAGE_UPDATE: &str = r#"
     UPDATE ui_pages
     SET name = $2, path = $3, description = $4, metadata = $5, updated_at = $6,
         deleted_at = $7, deleted_by = $8, request_id = $9, updated_by = $10,
-        system_id = $11, version = $12
+        system_id = $11, version = GREATEST(version + 1, $12)
     WHERE id = $1
     RETURNING id, name, path, description, metadata, created_at, updated_at,
               deleted_at, deleted_by, request_id, created_by, updated_by, system_id, version
@@ -99,7 +100,7 @@ pub const UI_BUTTON_UPDATE: &str = r#"
     UPDATE ui_buttons
     SET page_id = $2, button_id = $3, label = $4, action = $5, metadata = $6, updated_at = $7,
         deleted_at = $8, deleted_by = $9, request_id = $10, updated_by = $11,
-        system_id = $12, version = $13
+        system_id = $12, version = GREATEST(version + 1, $13)
     WHERE id = $1
     RETURNING id, page_id, button_id, label, action, metadata, created_at, updated_at,
               deleted_at, deleted_by, request_id, created_by, updated_by, system_id, version
@@ -151,7 +152,7 @@ pub const UI_FIELD_UPDATE: &str = r#"
     UPDATE ui_fields
     SET page_id = $2, field_id = $3, label = $4, field_type = $5, metadata = $6, updated_at = $7,
         deleted_at = $8, deleted_by = $9, request_id = $10, updated_by = $11,
-        system_id = $12, version = $13
+        system_id = $12, version = GREATEST(version + 1, $13)
     WHERE id = $1
     RETURNING id, page_id, field_id, label, field_type, metadata, created_at, updated_at,
               deleted_at, deleted_by, request_id, created_by, updated_by, system_id, version
@@ -203,7 +204,7 @@ pub const UI_API_UPDATE: &str = r#"
     UPDATE ui_api_endpoints
     SET endpoint = $2, method = $3, description = $4, metadata = $5, updated_at = $6,
         deleted_at = $7, deleted_by = $8, request_id = $9, updated_by = $10,
-        system_id = $11, version = $12
+        system_id = $11, version = GREATEST(version + 1, $12)
     WHERE id = $1
     RETURNING id, endpoint, method, description, metadata, created_at, updated_at,
               deleted_at, deleted_by, request_id, created_by, updated_by, system_id, version
@@ -216,3 +217,30 @@ pub const UI_API_SOFT_DELETE: &str = r#"
     WHERE id = $1 AND deleted_at IS NULL
 "#;
 
+
+/// Superseded versions of a UI entity, newest first
+pub const UI_ENTITY_VERSION_LIST: &str = r#"
+    SELECT id, entity_type, entity_id, version, snapshot, changed_by, changed_at, superseded_at
+    FROM ui_entity_versions
+    WHERE entity_type = $1 AND entity_id = $2
+    ORDER BY version DESC
+"#;
+
+/// One superseded version of a UI entity
+pub const UI_ENTITY_VERSION_FIND: &str = r#"
+    SELECT id, entity_type, entity_id, version, snapshot, changed_by, changed_at, superseded_at
+    FROM ui_entity_versions
+    WHERE entity_type = $1 AND entity_id = $2 AND version = $3
+"#;
+
+/// Latest relationship change and latest UI entity write; relationship
+/// history ids only grow, and every UI insert, update and soft delete sets
+/// updated_at
+pub const UI_VISIBILITY_STAMP: &str = r#"
+    SELECT (SELECT COALESCE(MAX(id), 0) FROM relationship_history)::text || ':' ||
+           COALESCE((SELECT MAX(ts) FROM (
+               SELECT MAX(updated_at) AS ts FROM ui_pages
+               UNION ALL SELECT MAX(updated_at) FROM ui_buttons
+               UNION ALL SELECT MAX(updated_at) FROM ui_fields
+           ) latest)::text, '')
+"#;
@@ -0,0 +1,253 @@
+//! Self-service email change with verification
+//!
+//! A request stores the new address with the SHA-256 of a random token,
+//! publishes `EmailChangeRequested` and, once committed, hands the token to
+//! the [`TokenSender`] to email to the new address. The event carries only
+//! the request id, never the token. The current email stays in effect until the token is confirmed,
+//! which swaps the address and, since the email is the login identifier,
+//! signs the user out everywhere.
+//!
+//! Requests and confirmations for one address are serialized with an
+//! advisory lock, so two accounts can neither hold a live pending request for
+//! the same address nor both end up with it.
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::sync::Arc;
+use uuid::Uuid;
+use crate::domain::events::{DomainEvent, EventEnvelope};
+use crate::infrastructure::events::{OutboxRelay, OutboxStore};
+use crate::infrastructure::one_time_token::{generate_token, hash_token};
+use crate::infrastructure::session::SessionService;
+use crate::infrastructure::token_delivery::{TokenMessage, TokenPurpose, TokenSender};
+use crate::shared::{AppError, AppResult};
+
+const DEFAULT_TTL_MINUTES: i64 = 60;
+
+/// The same message for unknown, used and expired tokens
+const INVALID_TOKEN: &str = "Invalid or expired email verification token";
+
+pub struct EmailChangeService {
+    pool: PgPool,
+    sessions: Arc<SessionService>,
+    relay: Option<Arc<OutboxRelay>>,
+    sender: Option<Arc<dyn TokenSender>>,
+    ttl: Duration,
+}
+
+impl EmailChangeService {
+    pub fn new(
+        pool: PgPool,
+        sessions: Arc<SessionService>,
+        relay: Option<Arc<OutboxRelay>>,
+        sender: Option<Arc<dyn TokenSender>>,
+    ) -> Self {
+        Self {
+            pool,
+            sessions,
+            relay,
+            sender,
+            ttl: Duration::minutes(DEFAULT_TTL_MINUTES),
+        }
+    }
+
+    /// Token lifetime from `EMAIL_CHANGE_TTL_MINUTES`
+    pub fn with_ttl_from_env(mut self) -> Self {
+        if let Some(minutes) = std::env::var("EMAIL_CHANGE_TTL_MINUTES")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|minutes| *minutes > 0)
+        {
+            self.ttl = Duration::minutes(minutes);
+        }
+        self
+    }
+
+    /// Ask to move `user_id` to `new_email`, returning when the link expires
+    ///
+    /// Supersedes the user's earlier pending request.
+    pub async fn request_change(&self, user_id: Uuid, new_email: &str) -> AppResult<DateTime<Utc>> {
+        let Some(sender) = &self.sender else {
+            return Err(AppError::Configuration(
+                "Email verification tokens cannot be delivered: TOKEN_DELIVERY_URL is not set".to_string(),
+            ));
+        };
+        let new_email = new_email.trim().to_string();
+        let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
+        lock_email(&mut tx, &new_email).await?;
+
+        let current_email: Option<String> =
+            sqlx::query_scalar("SELECT email FROM users WHERE id = $1 AND is_active = true AND deleted_at IS NULL")
+                .bind(user_id)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(AppError::Database)?;
+        let current_email = current_email.ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        if current_email.eq_ignore_ascii_case(&new_email) {
+            return Err(AppError::Validation("New email is the current email".to_string()));
+        }
+        ensure_available(&mut tx, user_id, &new_email).await?;
+
+        let pending_elsewhere: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM email_change_requests
+                WHERE LOWER(new_email) = LOWER($1) AND user_id <> $2
+                  AND consumed_at IS NULL AND expires_at > NOW()
+            )
+            "#,
+        )
+        .bind(&new_email)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+        if pending_elsewhere {
+            return Err(AppError::Validation(
+                "Email is awaiting verification for another account".to_string(),
+            ));
+        }
+
+        sqlx::query("UPDATE email_change_requests SET consumed_at = NOW() WHERE user_id = $1 AND consumed_at IS NULL")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+
+        let token = generate_token();
+        let expires_at = Utc::now() + self.ttl;
+        let change_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO email_change_requests (user_id, new_email, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .bind(&new_email)
+        .bind(hash_token(&token))
+        .bind(expires_at)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        let event = EventEnvelope::new(DomainEvent::EmailChangeRequested { user_id, change_id, expires_at });
+        OutboxStore::append(&mut *tx, &event).await?;
+        tx.commit().await.map_err(AppError::Database)?;
+
+        if let Some(relay) = &self.relay {
+            relay.notify();
+        }
+        // A failed send leaves the request unusable; asking again supersedes it
+        let message = TokenMessage {
+            purpose: TokenPurpose::EmailChange,
+            user_id,
+            recipient: new_email,
+            token,
+            expires_at,
+            notify: Some(current_email),
+        };
+        sender.send(&message).await?;
+        tracing::info!(target: "security", user_id = %user_id, "Email change requested");
+        Ok(expires_at)
+    }
+
+    /// Apply the change a token from [`Self::request_change`] was sent for
+    ///
+    /// The address is checked again here: it may have been taken since the
+    /// request. Other accounts' pending requests for it are consumed.
+    pub async fn confirm_change(&self, token: &str) -> AppResult<Uuid> {
+        let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
+        let row: Option<(Uuid, Uuid, String, DateTime<Utc>)> = sqlx::query_as(
+            r#"
+            SELECT r.id, r.user_id, r.new_email, r.expires_at
+            FROM email_change_requests r
+            JOIN users u ON u.id = r.user_id
+            WHERE r.token_hash = $1 AND r.consumed_at IS NULL AND u.is_active = true
+            FOR UPDATE OF r
+            "#,
+        )
+        .bind(hash_token(token))
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+        let Some((request_id, user_id, new_email, expires_at)) = row else {
+            return Err(AppError::Validation(INVALID_TOKEN.to_string()));
+        };
+
+        if expires_at <= Utc::now() {
+            consume(&mut tx, request_id).await?;
+            tx.commit().await.map_err(AppError::Database)?;
+            return Err(AppError::Validation(INVALID_TOKEN.to_string()));
+        }
+
+        lock_email(&mut tx, &new_email).await?;
+        if let Err(e) = ensure_available(&mut tx, user_id, &new_email).await {
+            consume(&mut tx, request_id).await?;
+            tx.commit().await.map_err(AppError::Database)?;
+            return Err(e);
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET email = $2, updated_at = NOW(), updated_by = $1, version = version + 1
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(&new_email)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+        sqlx::query(
+            "UPDATE email_change_requests SET consumed_at = NOW() WHERE LOWER(new_email) = LOWER($1) AND consumed_at IS NULL",
+        )
+        .bind(&new_email)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+        sqlx::query("UPDATE refresh_tokens SET is_revoked = true, revoked_at = NOW() WHERE user_id = $1 AND is_revoked = false")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+        tx.commit().await.map_err(AppError::Database)?;
+
+        let ended = self.sessions.end_user_sessions(user_id).await?;
+        tracing::warn!(target: "security", user_id = %user_id, sessions_ended = ended, "Email change confirmed");
+        Ok(user_id)
+    }
+}
+
+/// Serialize work on one address until the transaction ends
+async fn lock_email(tx: &mut Transaction<'_, Postgres>, email: &str) -> AppResult<()> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('email_change:' || LOWER($1)))")
+        .bind(email)
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::Database)?;
+    Ok(())
+}
+
+async fn ensure_available(tx: &mut Transaction<'_, Postgres>, user_id: Uuid, email: &str) -> AppResult<()> {
+    let taken: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE LOWER(email) = LOWER($1) AND id <> $2)")
+        .bind(email)
+        .bind(user_id)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(AppError::Database)?;
+    if taken {
+        return Err(AppError::Validation("Email already in use".to_string()));
+    }
+    Ok(())
+}
+
+async fn consume(tx: &mut Transaction<'_, Postgres>, request_id: Uuid) -> AppResult<()> {
+    sqlx::query("UPDATE email_change_requests SET consumed_at = NOW() WHERE id = $1")
+        .bind(request_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::Database)?;
+    Ok(())
+}
@@ -0,0 +1,345 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::Serialize;
+
+use crate::config::settings::EncryptionConfig;
+use crate::infrastructure::encryption::Vault;
+use crate::shared::{AppError, AppResult};
+
+/// When the breaker opens and how long it stays open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the breaker
+    pub failure_threshold: u32,
+    /// How long an open breaker fails fast before a trial call
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+impl From<&EncryptionConfig> for CircuitBreakerConfig {
+    fn from(config: &EncryptionConfig) -> Self {
+        Self {
+            failure_threshold: config.vault_breaker_failure_threshold.max(1),
+            cooldown: Duration::from_secs(config.vault_breaker_cooldown_seconds),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Calls go through
+    Closed,
+    /// Calls fail fast with `AppError::Unavailable`
+    Open,
+    /// The cooldown is over; one trial call decides whether to close again
+    HalfOpen,
+}
+
+/// Point-in-time view of a breaker, for metrics and status pages
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CircuitBreakerSnapshot {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    /// Calls failed fast since startup
+    pub rejected_calls: u64,
+    /// Seconds until an open breaker lets a trial call through
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_in_seconds: Option<u64>,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// When the current half-open trial was let through
+    trial_started: Option<Instant>,
+}
+
+/// Consecutive-failure circuit breaker
+///
+/// Closed until `failure_threshold` calls fail in a row, then open for
+/// `cooldown`. After that a single trial call is let through: success closes
+/// the breaker, failure reopens it for another cooldown. Errors that show the
+/// dependency answered (validation, not found, auth) do not count as failures.
+pub struct CircuitBreaker {
+    name: String,
+    config: CircuitBreakerConfig,
+    inner: Mutex<BreakerState>,
+    rejected_calls: AtomicU64,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: impl Into<String>, config: CircuitBreakerConfig) -> Self {
+        Self {
+            name: name.into(),
+            config,
+            inner: Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                trial_started: None,
+            }),
+            rejected_calls: AtomicU64::new(0),
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.snapshot().state
+    }
+
+    pub fn snapshot(&self) -> CircuitBreakerSnapshot {
+        let inner = self.inner.lock().unwrap();
+        let remaining = inner
+            .opened_at
+            .filter(|_| inner.state == CircuitState::Open)
+            .map(|opened_at| self.config.cooldown.saturating_sub(opened_at.elapsed()));
+        // An open breaker whose cooldown is over admits the next call
+        let state = match remaining {
+            Some(remaining) if remaining.is_zero() => CircuitState::HalfOpen,
+            _ => inner.state,
+        };
+        CircuitBreakerSnapshot {
+            state,
+            consecutive_failures: inner.consecutive_failures,
+            rejected_calls: self.rejected_calls.load(Ordering::Relaxed),
+            retry_in_seconds: remaining.filter(|r| !r.is_zero()).map(|r| r.as_secs().max(1)),
+        }
+    }
+
+    /// Run `call` through the breaker
+    pub async fn call<T, F>(&self, call: F) -> AppResult<T>
+    where
+        F: Future<Output = AppResult<T>>,
+    {
+        self.admit()?;
+        let result = call.await;
+        match &result {
+            Err(e) if is_outage(e) => self.record_failure(),
+            _ => self.record_success(),
+        }
+        result
+    }
+
+    fn admit(&self) -> AppResult<()> {
+        let mut inner = self.inner.lock().unwrap();
+        let now = Instant::now();
+        let admitted = match inner.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => {
+                let cooled_down = inner
+                    .opened_at
+                    .is_none_or(|opened_at| now.duration_since(opened_at) >= self.config.cooldown);
+                if cooled_down {
+                    inner.state = CircuitState::HalfOpen;
+                    inner.trial_started = Some(now);
+                }
+                cooled_down
+            }
+            // One trial at a time; a trial outliving the cooldown was likely
+            // cancelled, so another may go
+            CircuitState::HalfOpen => {
+                let free = inner
+                    .trial_started
+                    .is_none_or(|started| now.duration_since(started) >= self.config.cooldown);
+                if free {
+                    inner.trial_started = Some(now);
+                }
+                free
+            }
+        };
+        if admitted {
+            return Ok(());
+        }
+
+        self.rejected_calls.fetch_add(1, Ordering::Relaxed);
+        let retry_in = inner
+            .opened_at
+            .map(|opened_at| self.config.cooldown.saturating_sub(now.duration_since(opened_at)))
+            .unwrap_or_default();
+        Err(AppError::Unavailable(format!(
+            "{} circuit is open after {} consecutive failures; retry in {}s",
+            self.name,
+            inner.consecutive_failures,
+            retry_in.as_secs().max(1)
+        )))
+    }
+
+    fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state != CircuitState::Closed {
+            tracing::info!("{} circuit closed; calls resumed", self.name);
+        }
+        inner.state = CircuitState::Closed;
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+        inner.trial_started = None;
+    }
+
+    fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+        let trip = match inner.state {
+            CircuitState::HalfOpen => true,
+            CircuitState::Closed => inner.consecutive_failures >= self.config.failure_threshold,
+            CircuitState::Open => false,
+        };
+        if trip {
+            tracing::warn!(
+                "{} circuit opened after {} consecutive failures; failing fast for {}s",
+                self.name,
+                inner.consecutive_failures,
+                self.config.cooldown.as_secs()
+            );
+            inner.state = CircuitState::Open;
+            inner.opened_at = Some(Instant::now());
+            inner.trial_started = None;
+        }
+    }
+}
+
+/// Whether an error means the dependency is unhealthy, as opposed to it
+/// answering with a refusal
+fn is_outage(error: &AppError) -> bool {
+    !matches!(
+        error,
+        AppError::Validation(_)
+            | AppError::NotFound(_)
+            | AppError::Authentication(_)
+            | AppError::Authorization(_)
+            | AppError::VersionConflict { .. }
+    )
+}
+
+/// [`Vault`] wrapper that routes every call through a [`CircuitBreaker`]
+///
+/// While the breaker is open calls return `AppError::Unavailable` without
+/// touching the vault, so a vault outage costs callers nothing but the error.
+pub struct CircuitBreakerVault {
+    inner: Box<dyn Vault>,
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl CircuitBreakerVault {
+    pub fn new(inner: Box<dyn Vault>, breaker: Arc<CircuitBreaker>) -> Self {
+        Self { inner, breaker }
+    }
+}
+
+#[async_trait]
+impl Vault for CircuitBreakerVault {
+    async fn store_dek(&self, entity_id: &str, entity_type: &str, encrypted_dek: &[u8]) -> AppResult<()> {
+        self.breaker.call(self.inner.store_dek(entity_id, entity_type, encrypted_dek)).await
+    }
+
+    async fn get_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<Option<Vec<u8>>> {
+        self.breaker.call(self.inner.get_dek(entity_id, entity_type)).await
+    }
+
+    async fn get_deks(&self, entity_ids: &[String], entity_type: &str) -> AppResult<Vec<Option<Vec<u8>>>> {
+        self.breaker.call(self.inner.get_deks(entity_ids, entity_type)).await
+    }
+
+    async fn delete_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<()> {
+        self.breaker.call(self.inner.delete_dek(entity_id, entity_type)).await
+    }
+
+    async fn rotate_master_key(&self, new_master_key: &[u8]) -> AppResult<()> {
+        self.breaker.call(self.inner.rotate_master_key(new_master_key)).await
+    }
+
+    async fn store_master_key(&self, master_key: &[u8]) -> AppResult<()> {
+        self.breaker.call(self.inner.store_master_key(master_key)).await
+    }
+
+    async fn get_master_key(&self) -> AppResult<Option<Vec<u8>>> {
+        self.breaker.call(self.inner.get_master_key()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(cooldown: Duration) -> CircuitBreaker {
+        CircuitBreaker::new(
+            "vault",
+            CircuitBreakerConfig {
+                failure_threshold: 2,
+                cooldown,
+            },
+        )
+    }
+
+    async fn fail(breaker: &CircuitBreaker) -> AppResult<()> {
+        breaker.call(async { Err(AppError::Encryption("connection refused".to_string())) }).await
+    }
+
+    async fn succeed(breaker: &CircuitBreaker) -> AppResult<()> {
+        breaker.call(async { Ok(()) }).await
+    }
+
+    #[tokio::test]
+    async fn test_opens_after_consecutive_failures() {
+        let breaker = breaker(Duration::from_secs(60));
+
+        assert!(fail(&breaker).await.is_err());
+        assert!(succeed(&breaker).await.is_ok());
+        assert!(fail(&breaker).await.is_err());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(fail(&breaker).await.is_err());
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // Open: the call is never made
+        let called = std::sync::atomic::AtomicBool::new(false);
+        let result = breaker
+            .call(async {
+                called.store(true, Ordering::SeqCst);
+                Ok(())
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::Unavailable(_))));
+        assert!(!called.load(Ordering::SeqCst));
+        let snapshot = breaker.snapshot();
+        assert_eq!(snapshot.rejected_calls, 1);
+        assert!(snapshot.retry_in_seconds.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_half_open_trial_decides() {
+        let breaker = breaker(Duration::ZERO);
+        let _ = fail(&breaker).await;
+        let _ = fail(&breaker).await;
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        // A failed trial reopens, a successful one closes
+        assert!(matches!(fail(&breaker).await, Err(AppError::Encryption(_))));
+        assert_eq!(breaker.snapshot().consecutive_failures, 3);
+        assert!(succeed(&breaker).await.is_ok());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.snapshot().consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_refusals_are_not_outages() {
+        let breaker = breaker(Duration::from_secs(60));
+        for _ in 0..3 {
+            let result: AppResult<()> = breaker.call(async { Err(AppError::NotFound("dek".to_string())) }).await;
+            assert!(result.is_err());
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
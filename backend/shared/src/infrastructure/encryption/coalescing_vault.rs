@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use tokio::sync::broadcast;
+
+use crate::infrastructure::encryption::Vault;
+use crate::shared::{AppError, AppResult};
+
+/// Outcome of a read, as handed to every caller waiting on it
+type SharedRead = Result<Option<Vec<u8>>, Arc<AppError>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ReadKey {
+    Dek { entity_id: String, entity_type: String },
+    MasterKey,
+}
+
+/// Single-flight wrapper around a [`Vault`]
+///
+/// Concurrent `get_dek` calls for the same entity, and concurrent
+/// `get_master_key` calls, share one round-trip to the inner vault. Every
+/// waiter gets the result, including an error. Nothing is cached: once the
+/// read finishes the next call goes to the vault again. Writes and bulk
+/// reads pass straight through.
+pub struct CoalescingVault {
+    inner: Box<dyn Vault>,
+    in_flight: Mutex<HashMap<ReadKey, broadcast::Sender<SharedRead>>>,
+}
+
+impl CoalescingVault {
+    pub fn new(inner: Box<dyn Vault>) -> Self {
+        Self {
+            inner,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `read` unless an identical read is already in flight, in which
+    /// case wait for and share its result
+    async fn coalesce<F, Fut>(&self, key: ReadKey, read: F) -> AppResult<Option<Vec<u8>>>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = AppResult<Option<Vec<u8>>>>,
+    {
+        loop {
+            let waiting = {
+                let mut in_flight = self.in_flight.lock().unwrap();
+                match in_flight.get(&key) {
+                    Some(sender) => Some(sender.subscribe()),
+                    None => {
+                        in_flight.insert(key.clone(), broadcast::channel(1).0);
+                        None
+                    }
+                }
+            };
+
+            if let Some(mut receiver) = waiting {
+                match receiver.recv().await {
+                    Ok(result) => return result.map_err(unshare_error),
+                    // The leading call was cancelled before it finished; take over
+                    Err(_) => continue,
+                }
+            }
+
+            let guard = InFlightGuard {
+                in_flight: &self.in_flight,
+                key: &key,
+                armed: true,
+            };
+            let result: SharedRead = read().await.map_err(Arc::new);
+            if let Some(sender) = guard.finish() {
+                // No receivers just means nobody else asked
+                let _ = sender.send(result.clone());
+            }
+            return result.map_err(unshare_error);
+        }
+    }
+}
+
+/// Clears an in-flight entry, so a cancelled leader does not strand the key
+struct InFlightGuard<'a> {
+    in_flight: &'a Mutex<HashMap<ReadKey, broadcast::Sender<SharedRead>>>,
+    key: &'a ReadKey,
+    armed: bool,
+}
+
+impl InFlightGuard<'_> {
+    fn finish(mut self) -> Option<broadcast::Sender<SharedRead>> {
+        self.armed = false;
+        self.in_flight.lock().unwrap().remove(self.key)
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            // Dropping the sender wakes the waiters, which retry the read
+            self.in_flight.lock().unwrap_or_else(|e| e.into_inner()).remove(self.key);
+        }
+    }
+}
+
+/// Take back an error shared between callers
+///
+/// The last holder gets the original; other callers get a copy. Database and
+/// MUMPS errors cannot be cloned, so copies of those keep only the message.
+fn unshare_error(err: Arc<AppError>) -> AppError {
+    Arc::try_unwrap(err).unwrap_or_else(|err| match err.as_ref() {
+        AppError::Database(e) => AppError::Storage(e.to_string()),
+        AppError::Encryption(m) => AppError::Encryption(m.clone()),
+        AppError::Authentication(m) => AppError::Authentication(m.clone()),
+        AppError::Authorization(m) => AppError::Authorization(m.clone()),
+        AppError::Configuration(m) => AppError::Configuration(m.clone()),
+        AppError::Storage(m) => AppError::Storage(m.clone()),
+        AppError::Validation(m) => AppError::Validation(m.clone()),
+        AppError::NotFound(m) => AppError::NotFound(m.clone()),
+        AppError::VersionConflict {
+            entity,
+            id,
+            expected_version,
+            current_version,
+        } => AppError::VersionConflict {
+            entity: entity.clone(),
+            id: *id,
+            expected_version: *expected_version,
+            current_version: *current_version,
+        },
+        AppError::Mumps(e) => AppError::Internal(e.to_string()),
+        AppError::Unavailable(m) => AppError::Unavailable(m.clone()),
+        AppError::Timeout(m) => AppError::Timeout(m.clone()),
+        AppError::Internal(m) => AppError::Internal(m.clone()),
+    })
+}
+
+#[async_trait]
+impl Vault for CoalescingVault {
+    async fn store_dek(&self, entity_id: &str, entity_type: &str, encrypted_dek: &[u8]) -> AppResult<()> {
+        self.inner.store_dek(entity_id, entity_type, encrypted_dek).await
+    }
+
+    async fn get_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<Option<Vec<u8>>> {
+        let key = ReadKey::Dek {
+            entity_id: entity_id.to_string(),
+            entity_type: entity_type.to_string(),
+        };
+        self.coalesce(key, || self.inner.get_dek(entity_id, entity_type)).await
+    }
+
+    /// Batches go straight to the inner vault's own bulk read
+    async fn get_deks(&self, entity_ids: &[String], entity_type: &str) -> AppResult<Vec<Option<Vec<u8>>>> {
+        self.inner.get_deks(entity_ids, entity_type).await
+    }
+
+    async fn delete_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<()> {
+        self.inner.delete_dek(entity_id, entity_type).await
+    }
+
+    async fn rotate_master_key(&self, new_master_key: &[u8]) -> AppResult<()> {
+        self.inner.rotate_master_key(new_master_key).await
+    }
+
+    async fn store_master_key(&self, master_key: &[u8]) -> AppResult<()> {
+        self.inner.store_master_key(master_key).await
+    }
+
+    async fn get_master_key(&self) -> AppResult<Option<Vec<u8>>> {
+        self.coalesce(ReadKey::MasterKey, || self.inner.get_master_key()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::Notify;
+
+    /// Holds every `get_dek` until released, counting the calls
+    #[derive(Default)]
+    struct GatedVault {
+        calls: AtomicUsize,
+        gate: Notify,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl Vault for Arc<GatedVault> {
+        async fn store_dek(&self, _entity_id: &str, _entity_type: &str, _encrypted_dek: &[u8]) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn get_dek(&self, entity_id: &str, _entity_type: &str) -> AppResult<Option<Vec<u8>>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.gate.notified().await;
+            if self.fail {
+                return Err(AppError::Storage("vault unavailable".to_string()));
+            }
+            Ok(Some(entity_id.as_bytes().to_vec()))
+        }
+
+        async fn delete_dek(&self, _entity_id: &str, _entity_type: &str) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn rotate_master_key(&self, _new_master_key: &[u8]) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn store_master_key(&self, _master_key: &[u8]) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn get_master_key(&self) -> AppResult<Option<Vec<u8>>> {
+            Ok(None)
+        }
+    }
+
+    /// Start `n` concurrent reads of one DEK and release them once all wait
+    async fn concurrent_reads(inner: Arc<GatedVault>, n: usize) -> (Arc<CoalescingVault>, Vec<AppResult<Option<Vec<u8>>>>) {
+        let vault = Arc::new(CoalescingVault::new(Box::new(inner.clone())));
+        let tasks: Vec<_> = (0..n)
+            .map(|_| {
+                let vault = vault.clone();
+                tokio::spawn(async move { vault.get_dek("e1", "user").await })
+            })
+            .collect();
+        while vault.in_flight.lock().unwrap().get(&ReadKey::Dek {
+            entity_id: "e1".to_string(),
+            entity_type: "user".to_string(),
+        })
+        .map_or(0, |sender| sender.receiver_count())
+            < n - 1
+        {
+            tokio::task::yield_now().await;
+        }
+        inner.gate.notify_waiters();
+
+        let mut results = Vec::new();
+        for task in tasks {
+            results.push(task.await.unwrap());
+        }
+        (vault, results)
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_reads_share_one_call() {
+        let inner = Arc::new(GatedVault::default());
+        let (vault, results) = concurrent_reads(inner.clone(), 5).await;
+
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+        for result in results {
+            assert_eq!(result.unwrap(), Some(b"e1".to_vec()));
+        }
+        assert!(vault.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_errors_reach_every_waiter() {
+        let inner = Arc::new(GatedVault {
+            fail: true,
+            ..Default::default()
+        });
+        let (vault, results) = concurrent_reads(inner.clone(), 3).await;
+
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+        for result in results {
+            assert!(matches!(result, Err(AppError::Storage(m)) if m == "vault unavailable"));
+        }
+        assert!(vault.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_leader_hands_over() {
+        let inner = Arc::new(GatedVault::default());
+        let vault = CoalescingVault::new(Box::new(inner.clone()));
+
+        // The leader gives up while the read is in flight
+        let cancelled = tokio::time::timeout(std::time::Duration::from_millis(10), vault.get_dek("e1", "user")).await;
+        assert!(cancelled.is_err());
+        assert!(vault.in_flight.lock().unwrap().is_empty());
+
+        let read = vault.get_dek("e1", "user");
+        tokio::pin!(read);
+        tokio::select! {
+            _ = &mut read => panic!("read finished before the vault answered"),
+            _ = tokio::task::yield_now() => {}
+        }
+        inner.gate.notify_waiters();
+        assert_eq!(read.await.unwrap(), Some(b"e1".to_vec()));
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+    }
+}
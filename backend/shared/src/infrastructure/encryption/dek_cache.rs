@@ -0,0 +1,100 @@
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+struct CachedDek {
+    /// Wiped when the entry is evicted, expires or is invalidated
+    dek: Zeroizing<Vec<u8>>,
+    expires_at: Instant,
+}
+
+/// In-memory cache of decrypted DEKs, keyed by (entity_id, entity_type)
+///
+/// Bounded by LRU eviction and a TTL. The TTL is the window in which another
+/// instance's rotation can go unnoticed here, so keep it short.
+pub struct DekCache {
+    entries: Mutex<LruCache<(Uuid, String), CachedDek>>,
+    ttl: Duration,
+}
+
+impl DekCache {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        let capacity = NonZeroUsize::new(max_entries.max(1)).unwrap();
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            ttl,
+        }
+    }
+
+    /// A live entry, promoted to most recently used; expired entries are dropped
+    pub fn get(&self, entity_id: Uuid, entity_type: &str) -> Option<Vec<u8>> {
+        let key = (entity_id, entity_type.to_string());
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(&key) {
+            Some(cached) if cached.expires_at > Instant::now() => Some(cached.dek.to_vec()),
+            Some(_) => {
+                entries.pop(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, entity_id: Uuid, entity_type: &str, dek: &[u8]) {
+        let cached = CachedDek {
+            dek: Zeroizing::new(dek.to_vec()),
+            expires_at: Instant::now() + self.ttl,
+        };
+        self.entries.lock().unwrap().put((entity_id, entity_type.to_string()), cached);
+    }
+
+    pub fn invalidate(&self, entity_id: Uuid, entity_type: &str) {
+        self.entries.lock().unwrap().pop(&(entity_id, entity_type.to_string()));
+    }
+
+    /// Drop every entry, e.g. after a rotation touched many keys
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_expire_and_evict() {
+        let cache = DekCache::new(Duration::from_secs(60), 2);
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        cache.insert(a, "user", b"dek-a");
+        cache.insert(b, "user", b"dek-b");
+        assert_eq!(cache.get(a, "user"), Some(b"dek-a".to_vec()));
+        assert_eq!(cache.get(a, "group"), None);
+
+        // `a` was used more recently, so `b` is evicted
+        cache.insert(c, "user", b"dek-c");
+        assert_eq!(cache.get(b, "user"), None);
+        assert_eq!(cache.len(), 2);
+
+        cache.invalidate(a, "user");
+        assert_eq!(cache.get(a, "user"), None);
+        cache.clear();
+        assert!(cache.is_empty());
+
+        let expired = DekCache::new(Duration::ZERO, 2);
+        expired.insert(a, "user", b"dek-a");
+        assert_eq!(expired.get(a, "user"), None);
+        assert!(expired.is_empty());
+    }
+}
@@ -1,19 +1,37 @@
-use crate::infrastructure::encryption::{MasterKey, Vault};
+use crate::infrastructure::encryption::{DekCache, MasterKey, Vault};
 use crate::shared::AppResult;
 use aes_gcm::{
     aead::{Aead, AeadCore, KeyInit, OsRng},
     Aes256Gcm, Nonce,
 };
+use std::time::Duration;
 use uuid::Uuid;
 
 pub struct DekManager {
     master_key: MasterKey,
     vault: Box<dyn Vault>,
+    cache: Option<DekCache>,
 }
 
 impl DekManager {
     pub fn new(master_key: MasterKey, vault: Box<dyn Vault>) -> Self {
-        Self { master_key, vault }
+        Self { master_key, vault, cache: None }
+    }
+
+    /// Cache decrypted DEKs for `ttl`, keeping at most `max_entries`
+    ///
+    /// A zero TTL leaves caching off. DEKs this manager writes replace their
+    /// cached copy; a rotation done elsewhere is picked up once the TTL lapses.
+    pub fn with_cache(mut self, ttl: Duration, max_entries: usize) -> Self {
+        self.cache = (!ttl.is_zero()).then(|| DekCache::new(ttl, max_entries));
+        self
+    }
+
+    /// Forget every cached DEK, e.g. after a rotation
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
     }
 
     /// Generate a new DEK for an entity
@@ -26,15 +44,25 @@ impl DekManager {
         let encrypted_dek = self.encrypt_dek(&dek_bytes)?;
 
         // Store encrypted DEK in vault
+        if let Some(cache) = &self.cache {
+            cache.invalidate(entity_id, entity_type);
+        }
         self.vault
             .store_dek(&entity_id.to_string(), entity_type, &encrypted_dek)
             .await?;
+        if let Some(cache) = &self.cache {
+            cache.insert(entity_id, entity_type, &dek_bytes);
+        }
 
         Ok(dek_bytes)
     }
 
     /// Get DEK for an entity (decrypts from vault)
     pub async fn get_dek(&self, entity_id: Uuid, entity_type: &str) -> AppResult<Option<Vec<u8>>> {
+        if let Some(dek) = self.cache.as_ref().and_then(|cache| cache.get(entity_id, entity_type)) {
+            return Ok(Some(dek));
+        }
+
         // Retrieve encrypted DEK from vault
         let encrypted_dek = self.vault
             .get_dek(&entity_id.to_string(), entity_type)
@@ -43,12 +71,44 @@ impl DekManager {
         if let Some(encrypted) = encrypted_dek {
             // Decrypt DEK with master key
             let dek = self.decrypt_dek(&encrypted)?;
+            if let Some(cache) = &self.cache {
+                cache.insert(entity_id, entity_type, &dek);
+            }
             Ok(Some(dek))
         } else {
             Ok(None)
         }
     }
 
+    /// Get the DEKs of a batch of entities, aligned with `entity_ids`
+    ///
+    /// Cached DEKs are served directly; the rest go through the vault's bulk
+    /// read, so hydrating a page of records costs as few round-trips as the
+    /// backend allows.
+    pub async fn get_deks(&self, entity_ids: &[Uuid], entity_type: &str) -> AppResult<Vec<Option<Vec<u8>>>> {
+        let mut deks: Vec<Option<Vec<u8>>> = entity_ids
+            .iter()
+            .map(|id| self.cache.as_ref().and_then(|cache| cache.get(*id, entity_type)))
+            .collect();
+        let misses: Vec<usize> = (0..entity_ids.len()).filter(|&i| deks[i].is_none()).collect();
+        if misses.is_empty() {
+            return Ok(deks);
+        }
+
+        let ids: Vec<String> = misses.iter().map(|&i| entity_ids[i].to_string()).collect();
+        let encrypted_deks = self.vault.get_deks(&ids, entity_type).await?;
+        for (i, encrypted) in misses.into_iter().zip(encrypted_deks) {
+            if let Some(encrypted) = encrypted {
+                let dek = self.decrypt_dek(&encrypted)?;
+                if let Some(cache) = &self.cache {
+                    cache.insert(entity_ids[i], entity_type, &dek);
+                }
+                deks[i] = Some(dek);
+            }
+        }
+        Ok(deks)
+    }
+
     // ==========================================
     // Realm and Service DEK Isolation Methods
     // ==========================================
@@ -130,6 +190,26 @@ impl DekManager {
         Ok(plaintext)
     }
 
+    /// Wrap another key (e.g. a group DEK) with an entity's DEK
+    ///
+    /// Returns nonce || ciphertext. The entity's DEK is created if missing,
+    /// so a key can be shared with a user who has not encrypted anything yet.
+    pub async fn wrap_key(&self, entity_id: Uuid, entity_type: &str, key: &[u8]) -> AppResult<Vec<u8>> {
+        self.get_or_create_dek(entity_id, entity_type).await?;
+        let (ciphertext, nonce) = self.encrypt(entity_id, entity_type, key).await?;
+        let mut wrapped = nonce;
+        wrapped.extend_from_slice(&ciphertext);
+        Ok(wrapped)
+    }
+
+    /// Unwrap a key produced by [`DekManager::wrap_key`]
+    pub async fn unwrap_key(&self, entity_id: Uuid, entity_type: &str, wrapped: &[u8]) -> AppResult<Vec<u8>> {
+        if wrapped.len() < 12 {
+            return Err(crate::shared::AppError::Encryption("Invalid wrapped key format".to_string()));
+        }
+        self.decrypt(entity_id, entity_type, &wrapped[12..], &wrapped[..12]).await
+    }
+
     /// Encrypt DEK with master key and return separately (for database storage)
     pub async fn encrypt_dek_for_storage(
         &self,
@@ -215,3 +295,78 @@ impl DekManager {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Relies on the trait's default `get_deks`
+    #[derive(Default)]
+    struct MemoryVault {
+        deks: Mutex<HashMap<String, Vec<u8>>>,
+    }
+
+    #[async_trait]
+    impl Vault for MemoryVault {
+        async fn store_dek(&self, entity_id: &str, entity_type: &str, encrypted_dek: &[u8]) -> AppResult<()> {
+            self.deks
+                .lock()
+                .unwrap()
+                .insert(format!("{}/{}", entity_type, entity_id), encrypted_dek.to_vec());
+            Ok(())
+        }
+
+        async fn get_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<Option<Vec<u8>>> {
+            Ok(self.deks.lock().unwrap().get(&format!("{}/{}", entity_type, entity_id)).cloned())
+        }
+
+        async fn delete_dek(&self, _entity_id: &str, _entity_type: &str) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn rotate_master_key(&self, _new_master_key: &[u8]) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn store_master_key(&self, _master_key: &[u8]) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn get_master_key(&self) -> AppResult<Option<Vec<u8>>> {
+            Ok(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_deks_keeps_input_order() {
+        let manager = DekManager::new(MasterKey::generate().unwrap(), Box::new(MemoryVault::default()));
+        let (first, missing, second) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let first_dek = manager.generate_dek(first, "user").await.unwrap();
+        let second_dek = manager.generate_dek(second, "user").await.unwrap();
+
+        let deks = manager.get_deks(&[second, missing, first], "user").await.unwrap();
+        assert_eq!(deks, vec![Some(second_dek), None, Some(first_dek)]);
+        assert!(manager.get_deks(&[], "user").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cache_follows_rewritten_deks() {
+        let manager = DekManager::new(MasterKey::generate().unwrap(), Box::new(MemoryVault::default()))
+            .with_cache(Duration::from_secs(60), 100);
+        let entity = Uuid::new_v4();
+        let first = manager.generate_dek(entity, "user").await.unwrap();
+        assert_eq!(manager.get_dek(entity, "user").await.unwrap(), Some(first.clone()));
+
+        // Regenerating replaces the cached copy rather than serving the old key
+        let second = manager.generate_dek(entity, "user").await.unwrap();
+        assert_ne!(first, second);
+        assert_eq!(manager.get_dek(entity, "user").await.unwrap(), Some(second.clone()));
+        assert_eq!(manager.get_deks(&[entity], "user").await.unwrap(), vec![Some(second)]);
+
+        manager.clear_cache();
+        assert!(manager.cache.as_ref().unwrap().is_empty());
+    }
+}
@@ -1,30 +1,69 @@
+//! Field-level encryption
+//!
+//! New values are written as `aead:` followed by the base64 of a sealed blob
+//! (see [`super::aead`]), so the algorithm travels with the value. Values
+//! without the prefix predate the header and are base64 of
+//! `nonce (12 bytes) || ciphertext` under AES-256-GCM; they still decrypt.
+//! The prefix cannot collide with them because `:` is not in the base64
+//! alphabet.
+
+use crate::infrastructure::encryption::aead::{self, AeadAlgorithm};
 use crate::infrastructure::encryption::DekManager;
 use crate::shared::AppResult;
 use base64::{Engine as _, engine::general_purpose::STANDARD};
 use uuid::Uuid;
 
+/// Prefix marking a field value as a base64 sealed blob
+const SEALED_PREFIX: &str = "aead:";
+
 pub struct FieldEncryption {
     dek_manager: DekManager,
+    algorithm: AeadAlgorithm,
 }
 
 impl FieldEncryption {
     pub fn new(dek_manager: DekManager) -> Self {
-        Self { dek_manager }
+        Self {
+            dek_manager,
+            algorithm: AeadAlgorithm::default(),
+        }
+    }
+
+    /// Use `algorithm` for values encrypted from now on
+    pub fn with_algorithm(mut self, algorithm: AeadAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn algorithm(&self) -> AeadAlgorithm {
+        self.algorithm
     }
 
     /// Encrypt a field value
     pub async fn encrypt_field(&self, entity_id: Uuid, entity_type: &str, field_value: &str) -> AppResult<String> {
-        let data = field_value.as_bytes();
-        let (ciphertext, nonce) = self.dek_manager.encrypt(entity_id, entity_type, data).await?;
-        
-        // Combine nonce and ciphertext, encode as base64
-        let mut combined = nonce;
-        combined.extend_from_slice(&ciphertext);
-        Ok(STANDARD.encode(&combined))
+        let dek = self.dek(entity_id, entity_type).await?;
+        let blob = self.algorithm.seal(&dek, field_value.as_bytes())?;
+        Ok(format!("{}{}", SEALED_PREFIX, STANDARD.encode(&blob)))
     }
 
-    /// Decrypt a field value
+    /// Decrypt a field value written with any supported algorithm
     pub async fn decrypt_field(&self, entity_id: Uuid, entity_type: &str, encrypted_value: &str) -> AppResult<String> {
+        let plaintext = match encrypted_value.strip_prefix(SEALED_PREFIX) {
+            Some(encoded) => {
+                let blob = STANDARD.decode(encoded)
+                    .map_err(|e| crate::shared::AppError::Encryption(format!("Base64 decode error: {}", e)))?;
+                let dek = self.dek(entity_id, entity_type).await?;
+                aead::open(&dek, &blob)?
+            }
+            None => self.decrypt_legacy(entity_id, entity_type, encrypted_value).await?,
+        };
+
+        String::from_utf8(plaintext)
+            .map_err(|e| crate::shared::AppError::Encryption(format!("UTF-8 decode error: {}", e)))
+    }
+
+    /// Decrypt a pre-header value (bare base64 of nonce || AES-256-GCM ciphertext)
+    async fn decrypt_legacy(&self, entity_id: Uuid, entity_type: &str, encrypted_value: &str) -> AppResult<Vec<u8>> {
         let combined = STANDARD.decode(encrypted_value)
             .map_err(|e| crate::shared::AppError::Encryption(format!("Base64 decode error: {}", e)))?;
         
@@ -35,9 +74,11 @@ impl FieldEncryption {
         let nonce = &combined[..12];
         let ciphertext = &combined[12..];
         
-        let plaintext = self.dek_manager.decrypt(entity_id, entity_type, ciphertext, nonce).await?;
-        String::from_utf8(plaintext)
-            .map_err(|e| crate::shared::AppError::Encryption(format!("UTF-8 decode error: {}", e)))
+        self.dek_manager.decrypt(entity_id, entity_type, ciphertext, nonce).await
     }
-}
 
+    async fn dek(&self, entity_id: Uuid, entity_type: &str) -> AppResult<Vec<u8>> {
+        self.dek_manager.get_dek(entity_id, entity_type).await?
+            .ok_or_else(|| crate::shared::AppError::Encryption("DEK not found".to_string()))
+    }
+}
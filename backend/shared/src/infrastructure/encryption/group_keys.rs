@@ -0,0 +1,322 @@
+//! Group DEKs shared with members
+//!
+//! A group owns a series of DEKs, one per key version. Each version is
+//! escrowed in the vault under the master key (entity type `group/v{n}`) and
+//! wrapped once per member with that member's own user DEK
+//! (`group_dek_shares`). Data shared with the group is encrypted once with the
+//! current version; a member decrypts by unwrapping their own share, so adding
+//! or removing a member never re-encrypts the data itself.
+//!
+//! Adding a member wraps every existing version for them, so they can read
+//! everything already shared with the group.
+//!
+//! Removing a member deletes their shares and rotates: a new version is
+//! generated, wrapped for the remaining members and used for all new data.
+//! Existing ciphertexts keep their version and stay readable by the remaining
+//! members through their shares of older versions, while the removed member
+//! can no longer obtain any version through the server. A key the removed
+//! member copied out earlier still opens data encrypted before the removal;
+//! data that must be protected against that can be moved to the current
+//! version with [`GroupKeyring::reencrypt`].
+
+use crate::infrastructure::encryption::DekManager;
+use crate::shared::{AppError, AppResult};
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::Serialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// DEK type members' shares are wrapped with
+const MEMBER_KEY_TYPE: &str = "user";
+
+fn version_key_type(version: i32) -> String {
+    format!("group/v{}", version)
+}
+
+/// Data encrypted with a group DEK
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupCiphertext {
+    pub key_version: i32,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+impl GroupCiphertext {
+    /// Encode as `gk{version}:{base64(nonce || ciphertext)}`
+    pub fn encode(&self) -> String {
+        let mut combined = self.nonce.clone();
+        combined.extend_from_slice(&self.ciphertext);
+        format!("gk{}:{}", self.key_version, STANDARD.encode(combined))
+    }
+
+    pub fn decode(encoded: &str) -> AppResult<Self> {
+        let invalid = || AppError::Encryption("Invalid group ciphertext format".to_string());
+        let (version, body) = encoded
+            .strip_prefix("gk")
+            .and_then(|rest| rest.split_once(':'))
+            .ok_or_else(invalid)?;
+        let key_version = version.parse().map_err(|_| invalid())?;
+        let combined = STANDARD.decode(body).map_err(|_| invalid())?;
+        if combined.len() < 12 {
+            return Err(invalid());
+        }
+        Ok(Self {
+            key_version,
+            nonce: combined[..12].to_vec(),
+            ciphertext: combined[12..].to_vec(),
+        })
+    }
+}
+
+/// Outcome of rotating a group DEK
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupKeyRotation {
+    pub group_id: Uuid,
+    pub new_version: i32,
+    /// Members the new version was wrapped for
+    pub rewrapped_for: usize,
+}
+
+pub struct GroupKeyring {
+    dek_manager: Arc<DekManager>,
+    pool: PgPool,
+}
+
+impl GroupKeyring {
+    pub fn new(dek_manager: Arc<DekManager>, pool: PgPool) -> Self {
+        Self { dek_manager, pool }
+    }
+
+    /// Version new data is encrypted with, `None` before the first member joins
+    pub async fn current_version(&self, group_id: Uuid) -> AppResult<Option<i32>> {
+        sqlx::query_scalar::<_, Option<i32>>(
+            "SELECT MAX(key_version) FROM group_dek_versions WHERE group_id = $1",
+        )
+        .bind(group_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Give a member access to every key version of the group
+    ///
+    /// Creates the group's first version when it has none. Re-granting is a
+    /// no-op.
+    pub async fn grant_member(&self, group_id: Uuid, member_id: Uuid) -> AppResult<()> {
+        let versions: Vec<i32> = sqlx::query_scalar(
+            "SELECT key_version FROM group_dek_versions WHERE group_id = $1 ORDER BY key_version",
+        )
+        .bind(group_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        if versions.is_empty() {
+            let (version, key) = self.create_version(group_id).await?;
+            return self.store_share(group_id, member_id, version, &key).await;
+        }
+        for version in versions {
+            let key = self.version_key(group_id, version).await?;
+            self.store_share(group_id, member_id, version, &key).await?;
+        }
+        Ok(())
+    }
+
+    /// Drop a member's shares and rotate to a new version for the others
+    ///
+    /// Returns `None` when the member held no shares, in which case nothing
+    /// is rotated.
+    pub async fn revoke_member(&self, group_id: Uuid, member_id: Uuid) -> AppResult<Option<GroupKeyRotation>> {
+        let removed = sqlx::query("DELETE FROM group_dek_shares WHERE group_id = $1 AND member_id = $2")
+            .bind(group_id)
+            .bind(member_id)
+            .execute(&self.pool)
+            .await
+            .map_err(AppError::Database)?
+            .rows_affected();
+        if removed == 0 {
+            return Ok(None);
+        }
+
+        let rotation = self.rotate(group_id).await?;
+        tracing::info!(
+            "Rotated group {} DEK to v{} after removing {}, re-wrapped for {} member(s)",
+            group_id,
+            rotation.new_version,
+            member_id,
+            rotation.rewrapped_for
+        );
+        Ok(Some(rotation))
+    }
+
+    /// Generate a new version and wrap it for every current member
+    ///
+    /// New data uses the new version; existing data stays readable through
+    /// members' shares of older versions.
+    pub async fn rotate(&self, group_id: Uuid) -> AppResult<GroupKeyRotation> {
+        let members: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT DISTINCT member_id FROM group_dek_shares WHERE group_id = $1",
+        )
+        .bind(group_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let (version, key) = self.create_version(group_id).await?;
+        for member in &members {
+            self.store_share(group_id, *member, version, &key).await?;
+        }
+        Ok(GroupKeyRotation {
+            group_id,
+            new_version: version,
+            rewrapped_for: members.len(),
+        })
+    }
+
+    /// Encrypt data shared with the group under its current version
+    pub async fn encrypt(&self, group_id: Uuid, plaintext: &[u8]) -> AppResult<GroupCiphertext> {
+        let version = self.current_version(group_id).await?.ok_or_else(|| {
+            AppError::Encryption(format!("Group {} has no DEK yet; add a member first", group_id))
+        })?;
+        let key = self.version_key(group_id, version).await?;
+        let (nonce, ciphertext) = seal(&key, plaintext)?;
+        Ok(GroupCiphertext { key_version: version, nonce, ciphertext })
+    }
+
+    /// Decrypt as `member_id`, using only that member's own share
+    pub async fn decrypt(&self, group_id: Uuid, member_id: Uuid, data: &GroupCiphertext) -> AppResult<Vec<u8>> {
+        let wrapped: Option<Vec<u8>> = sqlx::query_scalar(
+            "SELECT wrapped_dek FROM group_dek_shares \
+             WHERE group_id = $1 AND member_id = $2 AND key_version = $3",
+        )
+        .bind(group_id)
+        .bind(member_id)
+        .bind(data.key_version)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+        let wrapped = wrapped.ok_or_else(|| {
+            AppError::Authorization(format!(
+                "User {} has no access to group {} key v{}",
+                member_id, group_id, data.key_version
+            ))
+        })?;
+
+        let key = self.dek_manager.unwrap_key(member_id, MEMBER_KEY_TYPE, &wrapped).await?;
+        open(&key, &data.nonce, &data.ciphertext)
+    }
+
+    /// Move data to the current version, e.g. after a revocation
+    pub async fn reencrypt(&self, group_id: Uuid, data: &GroupCiphertext) -> AppResult<GroupCiphertext> {
+        if self.current_version(group_id).await? == Some(data.key_version) {
+            return Ok(data.clone());
+        }
+        let old_key = self.version_key(group_id, data.key_version).await?;
+        let plaintext = open(&old_key, &data.nonce, &data.ciphertext)?;
+        self.encrypt(group_id, &plaintext).await
+    }
+
+    /// Generate the next version and escrow it under the master key
+    async fn create_version(&self, group_id: Uuid) -> AppResult<(i32, Vec<u8>)> {
+        let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
+        // A concurrent rotation that picks the same version fails on the
+        // primary key instead of overwriting the escrowed key
+        let version: i32 = sqlx::query_scalar(
+            "INSERT INTO group_dek_versions (group_id, key_version) \
+             SELECT $1, COALESCE(MAX(key_version), 0) + 1 FROM group_dek_versions WHERE group_id = $1 \
+             RETURNING key_version",
+        )
+        .bind(group_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+        let key = self.dek_manager.generate_dek(group_id, &version_key_type(version)).await?;
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok((version, key))
+    }
+
+    async fn version_key(&self, group_id: Uuid, version: i32) -> AppResult<Vec<u8>> {
+        self.dek_manager
+            .get_dek(group_id, &version_key_type(version))
+            .await?
+            .ok_or_else(|| AppError::Encryption(format!("Group {} DEK v{} missing from vault", group_id, version)))
+    }
+
+    async fn store_share(&self, group_id: Uuid, member_id: Uuid, version: i32, key: &[u8]) -> AppResult<()> {
+        let wrapped = self.dek_manager.wrap_key(member_id, MEMBER_KEY_TYPE, key).await?;
+        sqlx::query(
+            "INSERT INTO group_dek_shares (group_id, member_id, key_version, wrapped_dek) \
+             VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (group_id, member_id, key_version) DO NOTHING",
+        )
+        .bind(group_id)
+        .bind(member_id)
+        .bind(version)
+        .bind(wrapped)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+        Ok(())
+    }
+}
+
+/// Encrypt with a raw group key, returning (nonce, ciphertext)
+fn seal(key: &[u8], plaintext: &[u8]) -> AppResult<(Vec<u8>, Vec<u8>)> {
+    let cipher = Aes256Gcm::new_from_slice(key)
+        .map_err(|e| AppError::Encryption(format!("Invalid group DEK: {}", e)))?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|e| AppError::Encryption(format!("Encryption failed: {}", e)))?;
+    Ok((nonce.to_vec(), ciphertext))
+}
+
+fn open(key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> AppResult<Vec<u8>> {
+    if nonce.len() != 12 {
+        return Err(AppError::Encryption("Invalid nonce length".to_string()));
+    }
+    let cipher = Aes256Gcm::new_from_slice(key)
+        .map_err(|e| AppError::Encryption(format!("Invalid group DEK: {}", e)))?;
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|e| AppError::Encryption(format!("Decryption failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_open_round_trip() {
+        let key = Aes256Gcm::generate_key(&mut OsRng).to_vec();
+        let (nonce, ciphertext) = seal(&key, b"discharge summary").unwrap();
+        assert_eq!(open(&key, &nonce, &ciphertext).unwrap(), b"discharge summary");
+
+        let other = Aes256Gcm::generate_key(&mut OsRng).to_vec();
+        assert!(open(&other, &nonce, &ciphertext).is_err());
+    }
+
+    #[test]
+    fn test_ciphertext_encoding_round_trip() {
+        let data = GroupCiphertext {
+            key_version: 3,
+            nonce: vec![7; 12],
+            ciphertext: vec![1, 2, 3, 4],
+        };
+        let encoded = data.encode();
+        assert!(encoded.starts_with("gk3:"));
+        assert_eq!(GroupCiphertext::decode(&encoded).unwrap(), data);
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed_input() {
+        assert!(GroupCiphertext::decode("not-a-ciphertext").is_err());
+        assert!(GroupCiphertext::decode("gkx:AAAA").is_err());
+        assert!(GroupCiphertext::decode("gk1:AAAA").is_err());
+    }
+}
@@ -0,0 +1,397 @@
+//! Scheduled key rotation
+//!
+//! Each key type in the schedule (e.g. `master`, `group`) has a maximum age.
+//! [`KeyRotationScheduler`] checks `key_rotation_state` periodically and,
+//! for every due key type with a registered [`KeyRotator`], rotates keys in
+//! batches. The cursor is saved after each batch, so a run over a large
+//! dataset is spread over many short transactions and resumes where it
+//! stopped after a restart. When a run completes, the next due time is
+//! recorded and a `KeysRotated` event is written to the outbox.
+//!
+//! A due key type without a rotator is only reported: it stays overdue in
+//! [`KeyRotationStore::statuses`] and is logged on every check.
+
+use crate::domain::events::{DomainEvent, EventEnvelope};
+use crate::infrastructure::encryption::GroupKeyring;
+use crate::infrastructure::events::{OutboxRelay, OutboxStore};
+use crate::shared::{AppError, AppResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Progress of one batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotationBatch {
+    pub rotated: usize,
+    /// Where the next batch starts; `None` once every key has been rotated
+    pub next_cursor: Option<String>,
+}
+
+/// Rotates all keys of one type, a batch at a time
+#[async_trait]
+pub trait KeyRotator: Send + Sync {
+    /// Key type as named in the rotation schedule
+    fn key_type(&self) -> &'static str;
+
+    /// Rotate up to `batch_size` keys after `cursor` (`None` = from the start)
+    async fn rotate_batch(&self, cursor: Option<&str>, batch_size: i64) -> AppResult<RotationBatch>;
+}
+
+/// Rotation state of one key type, as exposed for monitoring
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct KeyRotationStatus {
+    pub key_type: String,
+    pub max_age_days: i32,
+    pub last_rotated_at: Option<DateTime<Utc>>,
+    pub next_due_at: DateTime<Utc>,
+    /// Resume point of an unfinished run
+    pub cursor: Option<String>,
+    pub run_started_at: Option<DateTime<Utc>>,
+    pub rotated_in_run: i64,
+    pub last_error: Option<String>,
+}
+
+impl KeyRotationStatus {
+    pub fn in_progress(&self) -> bool {
+        self.run_started_at.is_some()
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.in_progress() || self.next_due_at <= now
+    }
+}
+
+const STATUS_COLUMNS: &str = "key_type, max_age_days, last_rotated_at, next_due_at, cursor, \
+     run_started_at, rotated_in_run, last_error";
+
+/// Persistence for [`KeyRotationStatus`]
+pub struct KeyRotationStore {
+    pool: PgPool,
+}
+
+impl KeyRotationStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Add or update the schedule entry of each key type
+    ///
+    /// A newly scheduled key type is first due one period from now; changing
+    /// a period recomputes the due time from the last rotation.
+    pub async fn register(&self, schedule: &BTreeMap<String, u32>) -> AppResult<()> {
+        for (key_type, days) in schedule {
+            sqlx::query(
+                r#"
+                INSERT INTO key_rotation_state (key_type, max_age_days, next_due_at)
+                VALUES ($1, $2, NOW() + make_interval(days => $2))
+                ON CONFLICT (key_type) DO UPDATE
+                SET max_age_days = EXCLUDED.max_age_days,
+                    next_due_at = COALESCE(key_rotation_state.last_rotated_at, key_rotation_state.created_at)
+                        + make_interval(days => EXCLUDED.max_age_days),
+                    updated_at = NOW()
+                WHERE key_rotation_state.max_age_days <> EXCLUDED.max_age_days
+                "#,
+            )
+            .bind(key_type)
+            .bind(*days as i32)
+            .execute(&self.pool)
+            .await
+            .map_err(AppError::Database)?;
+        }
+        Ok(())
+    }
+
+    /// Every scheduled key type, with its next due time
+    pub async fn statuses(&self) -> AppResult<Vec<KeyRotationStatus>> {
+        sqlx::query_as::<_, KeyRotationStatus>(&format!(
+            "SELECT {} FROM key_rotation_state ORDER BY key_type",
+            STATUS_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)
+    }
+}
+
+/// Background job that runs due rotations
+pub struct KeyRotationScheduler {
+    pool: PgPool,
+    store: KeyRotationStore,
+    schedule: BTreeMap<String, u32>,
+    rotators: HashMap<&'static str, Arc<dyn KeyRotator>>,
+    batch_size: i64,
+    check_interval: Duration,
+    relay: Option<Arc<OutboxRelay>>,
+}
+
+impl KeyRotationScheduler {
+    pub fn new(pool: PgPool, schedule: BTreeMap<String, u32>) -> Self {
+        Self {
+            store: KeyRotationStore::new(pool.clone()),
+            pool,
+            schedule,
+            rotators: HashMap::new(),
+            batch_size: 100,
+            check_interval: Duration::from_secs(3600),
+            relay: None,
+        }
+    }
+
+    pub fn with_rotator(mut self, rotator: Arc<dyn KeyRotator>) -> Self {
+        self.rotators.insert(rotator.key_type(), rotator);
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: i64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_check_interval(mut self, check_interval: Duration) -> Self {
+        self.check_interval = check_interval;
+        self
+    }
+
+    /// Wake the outbox relay when a run completes
+    pub fn with_relay(mut self, relay: Arc<OutboxRelay>) -> Self {
+        self.relay = Some(relay);
+        self
+    }
+
+    /// Run one batch for every due key type
+    ///
+    /// Returns whether any run is still unfinished, i.e. whether to continue
+    /// without waiting for the next check.
+    pub async fn run_once(&self) -> AppResult<bool> {
+        let now = Utc::now();
+        let mut pending = false;
+        for status in self.store.statuses().await? {
+            if !self.schedule.contains_key(&status.key_type) || !status.is_due(now) {
+                continue;
+            }
+            let Some(rotator) = self.rotators.get(status.key_type.as_str()) else {
+                tracing::warn!(
+                    "Key type '{}' was due for rotation at {} but has no automatic rotator",
+                    status.key_type,
+                    status.next_due_at
+                );
+                continue;
+            };
+            match self.advance(&status.key_type, rotator.as_ref()).await {
+                Ok(unfinished) => pending |= unfinished,
+                Err(e) => tracing::warn!("Rotation of '{}' keys failed: {}", status.key_type, e),
+            }
+        }
+        Ok(pending)
+    }
+
+    /// Rotate one batch of a key type and save the progress
+    ///
+    /// The state row stays locked for the batch, so several instances can
+    /// run the job without rotating the same keys twice.
+    async fn advance(&self, key_type: &str, rotator: &dyn KeyRotator) -> AppResult<bool> {
+        let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
+        let status = sqlx::query_as::<_, KeyRotationStatus>(&format!(
+            "SELECT {} FROM key_rotation_state WHERE key_type = $1 FOR UPDATE SKIP LOCKED",
+            STATUS_COLUMNS
+        ))
+        .bind(key_type)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+        let Some(status) = status else {
+            // Another instance holds this key type
+            return Ok(false);
+        };
+
+        let batch = match rotator.rotate_batch(status.cursor.as_deref(), self.batch_size).await {
+            Ok(batch) => batch,
+            Err(e) => {
+                sqlx::query(
+                    "UPDATE key_rotation_state SET last_error = $2, updated_at = NOW() WHERE key_type = $1",
+                )
+                .bind(key_type)
+                .bind(e.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(AppError::Database)?;
+                tx.commit().await.map_err(AppError::Database)?;
+                return Err(e);
+            }
+        };
+        let rotated = status.rotated_in_run + batch.rotated as i64;
+
+        if let Some(cursor) = batch.next_cursor {
+            sqlx::query(
+                r#"
+                UPDATE key_rotation_state
+                SET cursor = $2,
+                    rotated_in_run = $3,
+                    run_started_at = COALESCE(run_started_at, NOW()),
+                    last_error = NULL,
+                    updated_at = NOW()
+                WHERE key_type = $1
+                "#,
+            )
+            .bind(key_type)
+            .bind(cursor)
+            .bind(rotated)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+            tx.commit().await.map_err(AppError::Database)?;
+            return Ok(true);
+        }
+
+        let next_due_at: DateTime<Utc> = sqlx::query_scalar(
+            r#"
+            UPDATE key_rotation_state
+            SET last_rotated_at = NOW(),
+                next_due_at = NOW() + make_interval(days => max_age_days),
+                cursor = NULL,
+                run_started_at = NULL,
+                rotated_in_run = 0,
+                last_error = NULL,
+                updated_at = NOW()
+            WHERE key_type = $1
+            RETURNING next_due_at
+            "#,
+        )
+        .bind(key_type)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+        let event = EventEnvelope::new(DomainEvent::KeysRotated {
+            key_type: key_type.to_string(),
+            rotated: rotated as u64,
+            next_due_at,
+        });
+        OutboxStore::append(&mut *tx, &event).await?;
+        tx.commit().await.map_err(AppError::Database)?;
+
+        if let Some(relay) = &self.relay {
+            relay.notify();
+        }
+        tracing::info!(
+            "Rotated {} '{}' key(s); next rotation due {}",
+            rotated,
+            key_type,
+            next_due_at
+        );
+        Ok(false)
+    }
+
+    /// Register the schedule, then spawn the rotation loop
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            if let Err(e) = self.store.register(&self.schedule).await {
+                tracing::error!("Key rotation schedule could not be registered: {}", e);
+                return;
+            }
+            loop {
+                match self.run_once().await {
+                    // An unfinished run continues with its next batch right away
+                    Ok(true) => continue,
+                    Ok(false) => {}
+                    Err(e) => tracing::warn!("Key rotation check failed: {}", e),
+                }
+                tokio::time::sleep(self.check_interval).await;
+            }
+        })
+    }
+}
+
+/// Rotates every group DEK, re-wrapping the new version for current members
+pub struct GroupDekRotator {
+    keyring: Arc<GroupKeyring>,
+    pool: PgPool,
+}
+
+impl GroupDekRotator {
+    pub fn new(keyring: Arc<GroupKeyring>, pool: PgPool) -> Self {
+        Self { keyring, pool }
+    }
+}
+
+#[async_trait]
+impl KeyRotator for GroupDekRotator {
+    fn key_type(&self) -> &'static str {
+        "group"
+    }
+
+    async fn rotate_batch(&self, cursor: Option<&str>, batch_size: i64) -> AppResult<RotationBatch> {
+        let after = cursor
+            .map(|c| Uuid::parse_str(c).map_err(|_| AppError::Internal(format!("Invalid group rotation cursor '{}'", c))))
+            .transpose()?;
+        let groups: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT group_id FROM group_dek_versions
+            WHERE $1::uuid IS NULL OR group_id > $1
+            ORDER BY group_id
+            LIMIT $2
+            "#,
+        )
+        .bind(after)
+        .bind(batch_size)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        for group_id in &groups {
+            self.keyring.rotate(*group_id).await?;
+        }
+        Ok(RotationBatch {
+            rotated: groups.len(),
+            next_cursor: next_cursor(&groups, batch_size),
+        })
+    }
+}
+
+/// Cursor after a batch of ids; `None` when the batch was the last one
+fn next_cursor(ids: &[Uuid], batch_size: i64) -> Option<String> {
+    if (ids.len() as i64) < batch_size {
+        None
+    } else {
+        ids.last().map(Uuid::to_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(next_due_at: DateTime<Utc>, run_started_at: Option<DateTime<Utc>>) -> KeyRotationStatus {
+        KeyRotationStatus {
+            key_type: "group".to_string(),
+            max_age_days: 90,
+            last_rotated_at: None,
+            next_due_at,
+            cursor: None,
+            run_started_at,
+            rotated_in_run: 0,
+            last_error: None,
+        }
+    }
+
+    #[test]
+    fn test_status_is_due_when_overdue_or_unfinished() {
+        let now = Utc::now();
+        assert!(status(now - chrono::Duration::days(1), None).is_due(now));
+        assert!(!status(now + chrono::Duration::days(1), None).is_due(now));
+        // A run that stopped midway resumes even though the next due time is far away
+        assert!(status(now + chrono::Duration::days(1), Some(now)).is_due(now));
+    }
+
+    #[test]
+    fn test_next_cursor_stops_on_short_batch() {
+        let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        assert_eq!(next_cursor(&ids, 3), Some(ids[2].to_string()));
+        assert_eq!(next_cursor(&ids, 10), None);
+        assert_eq!(next_cursor(&[], 10), None);
+    }
+}
@@ -1,20 +1,38 @@
 pub mod vault;
+pub mod coalescing_vault;
+pub mod circuit_breaker;
+pub mod aead;
 pub mod vault_impl;
 pub mod dek_manager;
+pub mod dek_cache;
 pub mod master_key;
+pub mod kdf;
 pub mod field_encryption;
 pub mod master_key_rotation;
 pub mod dek_rotation;
 pub mod relationship_encryption;
 pub mod service_encryption;
+pub mod group_keys;
+pub mod key_rotation;
 
 pub use vault::Vault;
+pub use coalescing_vault::CoalescingVault;
+pub use circuit_breaker::{
+    CircuitBreaker, CircuitBreakerConfig, CircuitBreakerSnapshot, CircuitBreakerVault, CircuitState,
+};
 pub use vault_impl::{RustyVaultClient, CreateTokenRequest, TokenAuth, TokenEntry};
 pub use dek_manager::DekManager;
+pub use dek_cache::DekCache;
 pub use master_key::MasterKey;
+pub use kdf::{KdfAlgorithm, KdfParams, KdfRecord};
+pub use aead::AeadAlgorithm;
 pub use field_encryption::FieldEncryption;
 pub use master_key_rotation::MasterKeyRotation;
 pub use dek_rotation::DekRotation;
-pub use relationship_encryption::RelationshipEncryption;
+pub use relationship_encryption::{MetadataIntegrity, RelationshipEncryption};
 pub use service_encryption::{ServiceEncryption, ServiceEncryptionBuilder};
+pub use group_keys::{GroupCiphertext, GroupKeyRotation, GroupKeyring};
+pub use key_rotation::{
+    GroupDekRotator, KeyRotationScheduler, KeyRotationStatus, KeyRotationStore, KeyRotator, RotationBatch,
+};
 
@@ -2,11 +2,30 @@ use crate::domain::entities::Relationship;
 use crate::infrastructure::encryption::DekManager;
 use crate::shared::AppResult;
 use uuid::Uuid;
+use serde::Serialize;
 use serde_json::Value;
 use std::sync::Arc;
 
+/// Result of checking a relationship's encrypted metadata
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", content = "reason", rename_all = "snake_case")]
+pub enum MetadataIntegrity {
+    /// Metadata is stored in plaintext; nothing to verify
+    NotEncrypted,
+    /// Marked for encryption by `Relationship::set_metadata` but not yet encrypted
+    PendingEncryption,
+    /// Ciphertext authenticated and decrypted to valid JSON
+    Valid,
+    /// Tampered, corrupted, or encrypted with a key that no longer exists
+    Failed(String),
+}
+
 /// Relationship metadata encryption helper
 /// Encrypts sensitive metadata in relationships using user's DEK
+///
+/// Metadata is sealed with AES-256-GCM, so the stored value carries a
+/// 16-byte authentication tag; [`RelationshipEncryption::verify`] uses it to
+/// detect tampering. The tag covers the metadata only, not the tuple.
 pub struct RelationshipEncryption {
     dek_manager: Arc<DekManager>,
 }
@@ -73,6 +92,42 @@ impl RelationshipEncryption {
         Ok(())
     }
     
+    /// Check that encrypted metadata authenticates, without returning it
+    ///
+    /// The key owner is taken from the subject (`user:{id}`), matching
+    /// [`RelationshipEncryption::encrypt_metadata`]. The plaintext is
+    /// dropped as soon as it has been parsed.
+    pub async fn verify(&self, relationship: &Relationship) -> MetadataIntegrity {
+        let metadata = &relationship.metadata;
+        if metadata.get("_encrypted").and_then(|v| v.as_bool()) != Some(true) {
+            return MetadataIntegrity::NotEncrypted;
+        }
+        if metadata.get("_needs_encryption").and_then(|v| v.as_bool()) == Some(true) {
+            return MetadataIntegrity::PendingEncryption;
+        }
+        let Some(encrypted_data) = metadata.get("data").and_then(|v| v.as_str()) else {
+            return MetadataIntegrity::Failed("encrypted metadata has no ciphertext".to_string());
+        };
+        let Some(user_id) = relationship
+            .user
+            .strip_prefix("user:")
+            .and_then(|id| Uuid::parse_str(id).ok())
+        else {
+            return MetadataIntegrity::Failed(format!(
+                "cannot determine key owner from subject '{}'",
+                relationship.user
+            ));
+        };
+
+        match self.dek_manager.decrypt_field(user_id, "user", encrypted_data).await {
+            Ok(plaintext) => match serde_json::from_str::<Value>(&plaintext) {
+                Ok(_) => MetadataIntegrity::Valid,
+                Err(e) => MetadataIntegrity::Failed(format!("decrypted metadata is not JSON: {}", e)),
+            },
+            Err(e) => MetadataIntegrity::Failed(e.to_string()),
+        }
+    }
+
     /// Check if metadata contains sensitive data that should be encrypted
     pub fn should_encrypt_metadata(metadata: &Value) -> bool {
         // Check for sensitive keys
@@ -84,3 +139,93 @@ impl RelationshipEncryption {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::encryption::{MasterKey, Vault};
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryVault {
+        deks: Mutex<HashMap<(String, String), Vec<u8>>>,
+    }
+
+    #[async_trait]
+    impl Vault for MemoryVault {
+        async fn store_dek(&self, entity_id: &str, entity_type: &str, encrypted_dek: &[u8]) -> AppResult<()> {
+            self.deks
+                .lock()
+                .unwrap()
+                .insert((entity_id.to_string(), entity_type.to_string()), encrypted_dek.to_vec());
+            Ok(())
+        }
+
+        async fn get_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<Option<Vec<u8>>> {
+            Ok(self.deks.lock().unwrap().get(&(entity_id.to_string(), entity_type.to_string())).cloned())
+        }
+
+        async fn delete_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<()> {
+            self.deks.lock().unwrap().remove(&(entity_id.to_string(), entity_type.to_string()));
+            Ok(())
+        }
+
+        async fn rotate_master_key(&self, _new_master_key: &[u8]) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn store_master_key(&self, _master_key: &[u8]) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn get_master_key(&self) -> AppResult<Option<Vec<u8>>> {
+            Ok(None)
+        }
+    }
+
+    async fn encrypted_relationship() -> (RelationshipEncryption, Relationship) {
+        let dek_manager = Arc::new(DekManager::new(MasterKey::generate().unwrap(), Box::new(MemoryVault::default())));
+        let user_id = Uuid::new_v4();
+        dek_manager.generate_dek(user_id, "user").await.unwrap();
+        let encryption = RelationshipEncryption::new(dek_manager);
+
+        let mut relationship = Relationship::new(
+            format!("user:{}", user_id),
+            "viewer".to_string(),
+            "patient:42".to_string(),
+        );
+        relationship.metadata = serde_json::json!({"reason": "consult"});
+        encryption.encrypt_metadata(&mut relationship, user_id).await.unwrap();
+        (encryption, relationship)
+    }
+
+    #[tokio::test]
+    async fn test_verify_accepts_untouched_ciphertext() {
+        let (encryption, relationship) = encrypted_relationship().await;
+        assert_eq!(encryption.verify(&relationship).await, MetadataIntegrity::Valid);
+    }
+
+    #[tokio::test]
+    async fn test_verify_detects_tampering() {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+        let (encryption, mut relationship) = encrypted_relationship().await;
+        let mut bytes = STANDARD.decode(relationship.metadata["data"].as_str().unwrap()).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0x01;
+        relationship.metadata["data"] = Value::String(STANDARD.encode(bytes));
+
+        assert!(matches!(encryption.verify(&relationship).await, MetadataIntegrity::Failed(_)));
+    }
+
+    #[tokio::test]
+    async fn test_verify_classifies_unencrypted_metadata() {
+        let (encryption, mut relationship) = encrypted_relationship().await;
+        relationship.metadata = serde_json::json!({"reason": "consult"});
+        assert_eq!(encryption.verify(&relationship).await, MetadataIntegrity::NotEncrypted);
+
+        relationship.set_metadata(serde_json::json!({"reason": "consult"}), true);
+        assert_eq!(encryption.verify(&relationship).await, MetadataIntegrity::PendingEncryption);
+    }
+}
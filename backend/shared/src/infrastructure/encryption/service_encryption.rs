@@ -2,10 +2,16 @@
 //!
 //! Provides encryption/decryption operations scoped to a specific service,
 //! with controlled access to realm-specific DEKs based on service permissions.
+//!
+//! Data is sealed with the configured AEAD (see [`super::aead`] for the blob
+//! format). Blobs written before the header existed are age-encrypted with the
+//! hex DEK as passphrase; they start with `age-encryption.org/` and are still
+//! decrypted, but never written.
 
+use super::aead::{self, AeadAlgorithm};
 use super::DekManager;
 use crate::shared::{AppError, AppResult};
-use std::io::{Read, Write};
+use std::io::Read;
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -19,6 +25,8 @@ pub struct ServiceEncryption {
     service_uuid: Uuid,
     /// Realms this service is authorized to access
     allowed_realms: Vec<String>,
+    /// AEAD used for new ciphertexts
+    algorithm: AeadAlgorithm,
 }
 
 impl ServiceEncryption {
@@ -34,9 +42,20 @@ impl ServiceEncryption {
             service_id: service_id.into(),
             service_uuid,
             allowed_realms,
+            algorithm: AeadAlgorithm::default(),
         }
     }
 
+    /// Use `algorithm` for data encrypted from now on
+    pub fn with_algorithm(mut self, algorithm: AeadAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn algorithm(&self) -> AeadAlgorithm {
+        self.algorithm
+    }
+
     /// Check if service has access to a realm
     pub fn has_realm_access(&self, realm_id: &str) -> bool {
         self.allowed_realms.iter().any(|r| r == realm_id)
@@ -108,51 +127,50 @@ impl ServiceEncryption {
         self.decrypt_with_dek(&dek, encrypted)
     }
 
-    /// Encrypt data using age with the provided DEK
+    /// Seal data with the provided DEK
     fn encrypt_with_dek(&self, dek: &[u8], data: &[u8]) -> AppResult<Vec<u8>> {
-        let passphrase_str = hex::encode(dek);
-        let passphrase = age::secrecy::SecretString::from(passphrase_str);
-        let encryptor = age::Encryptor::with_user_passphrase(passphrase);
-
-        let mut encrypted = vec![];
-        let mut writer = encryptor
-            .wrap_output(&mut encrypted)
-            .map_err(|e| AppError::Encryption(format!("Failed to create age encryptor: {}", e)))?;
-
-        writer
-            .write_all(data)
-            .map_err(|e| AppError::Encryption(format!("Failed to encrypt data: {}", e)))?;
+        self.algorithm.seal(dek, data)
+    }
 
-        writer
-            .finish()
-            .map_err(|e| AppError::Encryption(format!("Failed to finalize encryption: {}", e)))?;
+    /// Decrypt data with the provided DEK, whichever format it was written in
+    fn decrypt_with_dek(&self, dek: &[u8], encrypted: &[u8]) -> AppResult<Vec<u8>> {
+        open_any(dek, encrypted)
+    }
+}
 
-        Ok(encrypted)
+/// Decrypt a sealed blob, falling back to age for pre-header data
+fn open_any(dek: &[u8], encrypted: &[u8]) -> AppResult<Vec<u8>> {
+    if encrypted.starts_with(AGE_MAGIC) {
+        return decrypt_legacy_age(dek, encrypted);
     }
+    aead::open(dek, encrypted)
+}
 
-    /// Decrypt data using age with the provided DEK
-    fn decrypt_with_dek(&self, dek: &[u8], encrypted: &[u8]) -> AppResult<Vec<u8>> {
-        let passphrase_str = hex::encode(dek);
-        let passphrase = age::secrecy::SecretString::from(passphrase_str);
-        
-        // Create identity from passphrase for decryption
-        let identity = age::scrypt::Identity::new(passphrase);
-        
-        // Create decryptor
-        let decryptor = age::Decryptor::new(encrypted)
-            .map_err(|e| AppError::Encryption(format!("Failed to create age decryptor: {}", e)))?;
+/// Leading bytes of an age-encrypted blob
+const AGE_MAGIC: &[u8] = b"age-encryption.org/";
 
-        let mut decrypted = vec![];
-        let mut reader = decryptor
-            .decrypt(std::iter::once(&identity as &dyn age::Identity))
-            .map_err(|e| AppError::Encryption(format!("Failed to decrypt: {}", e)))?;
+/// Decrypt data using age with the provided DEK
+fn decrypt_legacy_age(dek: &[u8], encrypted: &[u8]) -> AppResult<Vec<u8>> {
+    let passphrase_str = hex::encode(dek);
+    let passphrase = age::secrecy::SecretString::from(passphrase_str);
+    
+    // Create identity from passphrase for decryption
+    let identity = age::scrypt::Identity::new(passphrase);
+    
+    // Create decryptor
+    let decryptor = age::Decryptor::new(encrypted)
+        .map_err(|e| AppError::Encryption(format!("Failed to create age decryptor: {}", e)))?;
 
-        reader
-            .read_to_end(&mut decrypted)
-            .map_err(|e| AppError::Encryption(format!("Failed to read decrypted data: {}", e)))?;
+    let mut decrypted = vec![];
+    let mut reader = decryptor
+        .decrypt(std::iter::once(&identity as &dyn age::Identity))
+        .map_err(|e| AppError::Encryption(format!("Failed to decrypt: {}", e)))?;
 
-        Ok(decrypted)
-    }
+    reader
+        .read_to_end(&mut decrypted)
+        .map_err(|e| AppError::Encryption(format!("Failed to read decrypted data: {}", e)))?;
+
+    Ok(decrypted)
 }
 
 /// Builder for ServiceEncryption
@@ -161,6 +179,7 @@ pub struct ServiceEncryptionBuilder {
     service_id: String,
     service_uuid: Uuid,
     allowed_realms: Vec<String>,
+    algorithm: AeadAlgorithm,
 }
 
 impl ServiceEncryptionBuilder {
@@ -175,6 +194,7 @@ impl ServiceEncryptionBuilder {
             service_id: service_id.into(),
             service_uuid,
             allowed_realms: Vec::new(),
+            algorithm: AeadAlgorithm::default(),
         }
     }
 
@@ -190,6 +210,12 @@ impl ServiceEncryptionBuilder {
         self
     }
 
+    /// Select the AEAD for new ciphertexts
+    pub fn with_algorithm(mut self, algorithm: AeadAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
     /// Build the ServiceEncryption
     pub fn build(self) -> ServiceEncryption {
         ServiceEncryption::new(
@@ -198,6 +224,7 @@ impl ServiceEncryptionBuilder {
             self.service_uuid,
             self.allowed_realms,
         )
+        .with_algorithm(self.algorithm)
     }
 }
 
@@ -218,5 +245,36 @@ mod tests {
         assert!(allowed.iter().any(|r| r == "hospital-b"));
         assert!(!allowed.iter().any(|r| r == "hospital-c"));
     }
+
+    /// Encrypt the way ServiceEncryption did before sealed blobs, with a
+    /// cheap scrypt work factor
+    fn encrypt_legacy_age(dek: &[u8], data: &[u8]) -> Vec<u8> {
+        use std::io::Write;
+
+        let mut recipient = age::scrypt::Recipient::new(age::secrecy::SecretString::from(hex::encode(dek)));
+        recipient.set_work_factor(10);
+        let encryptor = age::Encryptor::with_recipients(std::iter::once(&recipient as &dyn age::Recipient)).unwrap();
+
+        let mut encrypted = vec![];
+        let mut writer = encryptor.wrap_output(&mut encrypted).unwrap();
+        writer.write_all(data).unwrap();
+        writer.finish().unwrap();
+        encrypted
+    }
+
+    #[test]
+    fn test_decrypts_legacy_age_and_sealed_blobs() {
+        let dek = [3u8; 32];
+
+        let legacy = encrypt_legacy_age(&dek, b"lab result");
+        assert!(legacy.starts_with(AGE_MAGIC));
+        assert_eq!(open_any(&dek, &legacy).unwrap(), b"lab result");
+
+        for algorithm in [AeadAlgorithm::Aes256Gcm, AeadAlgorithm::ChaCha20Poly1305] {
+            let sealed = algorithm.seal(&dek, b"lab result").unwrap();
+            assert_eq!(open_any(&dek, &sealed).unwrap(), b"lab result");
+        }
+        assert!(open_any(&[4u8; 32], &legacy).is_err());
+    }
 }
 
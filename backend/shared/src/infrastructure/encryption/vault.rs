@@ -10,6 +10,19 @@ pub trait Vault: Send + Sync {
     /// Retrieve encrypted DEK
     async fn get_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<Option<Vec<u8>>>;
     
+    /// Retrieve the encrypted DEKs of several entities of one type
+    ///
+    /// Results line up with `entity_ids`. The default fetches them one at a
+    /// time, which is what the HashiCorp, AWS, GCP and Azure vaults use;
+    /// `RustyVaultClient` overrides it to fetch concurrently.
+    async fn get_deks(&self, entity_ids: &[String], entity_type: &str) -> AppResult<Vec<Option<Vec<u8>>>> {
+        let mut deks = Vec::with_capacity(entity_ids.len());
+        for entity_id in entity_ids {
+            deks.push(self.get_dek(entity_id, entity_type).await?);
+        }
+        Ok(deks)
+    }
+    
     /// Delete DEK
     async fn delete_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<()>;
     
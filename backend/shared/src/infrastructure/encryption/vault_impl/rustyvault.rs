@@ -2,7 +2,15 @@
 //! 
 //! Extends the base Vault trait with policy and token management
 //! for integration with RustyVault service.
+//!
+//! Every call goes through one pooled HTTP client built from
+//! [`VaultClientConfig`], so connections are reused across calls and a hung
+//! vault costs a request at most the request timeout. Timeouts surface as `AppError::Timeout`, which
+//! callers can retry, rather than as a vault error.
 
+use std::time::Duration;
+
+use crate::config::providers::VaultClientConfig;
 use crate::infrastructure::encryption::vault::Vault;
 use crate::shared::{AppError, AppResult};
 use async_trait::async_trait;
@@ -64,14 +72,37 @@ pub struct TokenEntry {
 }
 
 impl RustyVaultClient {
-    /// Create a new RustyVault client
+    /// Create a new RustyVault client with the default timeouts
+    ///
+    /// # Panics
+    ///
+    /// If the HTTP client cannot be initialized, like `reqwest::Client::new`;
+    /// use [`RustyVaultClient::with_config`] to handle that instead.
     pub fn new(addr: &str, token: &str, mount_path: &str) -> Self {
-        Self {
-            client: Client::new(),
+        Self::with_config(addr, token, mount_path, &VaultClientConfig::default())
+            .expect("failed to build the vault HTTP client")
+    }
+
+    /// Create a RustyVault client with the given HTTP settings
+    pub fn with_config(addr: &str, token: &str, mount_path: &str, config: &VaultClientConfig) -> AppResult<Self> {
+        // Zero turns the optional settings off
+        let optional = |ms: u64| (ms > 0).then(|| Duration::from_millis(ms));
+        let client = Client::builder()
+            .connect_timeout(Duration::from_millis(config.connect_timeout_ms))
+            .timeout(Duration::from_millis(config.request_timeout_ms))
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .pool_idle_timeout(optional(config.pool_idle_timeout_ms))
+            .http2_keep_alive_interval(optional(config.http2_keep_alive_interval_ms))
+            .http2_keep_alive_while_idle(true)
+            .build()
+            .map_err(|e| AppError::Configuration(format!("Failed to build vault HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
             addr: addr.trim_end_matches('/').to_string(),
             token: token.to_string(),
             mount_path: mount_path.to_string(),
-        }
+        })
     }
 
     /// Create from environment variables
@@ -83,7 +114,7 @@ impl RustyVaultClient {
         let mount_path = std::env::var("VAULT_MOUNT_PATH")
             .unwrap_or_else(|_| "secret".to_string());
         
-        Ok(Self::new(&addr, &token, &mount_path))
+        Self::with_config(&addr, &token, &mount_path, &VaultClientConfig::from_env())
     }
 
     // ==========================================
@@ -103,7 +134,7 @@ impl RustyVaultClient {
             .json(&data)
             .send()
             .await
-            .map_err(|e| AppError::Encryption(format!("Vault policy write error: {}", e)))?;
+            .map_err(|e| request_error("Vault policy write error", e))?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -125,7 +156,7 @@ impl RustyVaultClient {
             .header("X-RustyVault-Token", &self.token)
             .send()
             .await
-            .map_err(|e| AppError::Encryption(format!("Vault policy delete error: {}", e)))?;
+            .map_err(|e| request_error("Vault policy delete error", e))?;
 
         if !response.status().is_success() && response.status() != 404 {
             let status = response.status();
@@ -147,11 +178,11 @@ impl RustyVaultClient {
             .header("X-RustyVault-Token", &self.token)
             .send()
             .await
-            .map_err(|e| AppError::Encryption(format!("Vault policy list error: {}", e)))?;
+            .map_err(|e| request_error("Vault policy list error", e))?;
 
         if response.status().is_success() {
             let json: serde_json::Value = response.json().await
-                .map_err(|e| AppError::Encryption(format!("Parse error: {}", e)))?;
+                .map_err(|e| request_error("Parse error", e))?;
             
             let policies = json
                 .get("data")
@@ -180,11 +211,11 @@ impl RustyVaultClient {
             .json(request)
             .send()
             .await
-            .map_err(|e| AppError::Encryption(format!("Vault token create error: {}", e)))?;
+            .map_err(|e| request_error("Vault token create error", e))?;
 
         if response.status().is_success() {
             let token_response: TokenResponse = response.json().await
-                .map_err(|e| AppError::Encryption(format!("Parse error: {}", e)))?;
+                .map_err(|e| request_error("Parse error", e))?;
             
             token_response.auth.ok_or_else(|| {
                 AppError::Encryption("No auth in token response".to_string())
@@ -209,11 +240,11 @@ impl RustyVaultClient {
             .json(&data)
             .send()
             .await
-            .map_err(|e| AppError::Encryption(format!("Vault token lookup error: {}", e)))?;
+            .map_err(|e| request_error("Vault token lookup error", e))?;
 
         if response.status().is_success() {
             let json: serde_json::Value = response.json().await
-                .map_err(|e| AppError::Encryption(format!("Parse error: {}", e)))?;
+                .map_err(|e| request_error("Parse error", e))?;
             
             if let Some(data) = json.get("data") {
                 let entry: TokenEntry = serde_json::from_value(data.clone())
@@ -244,7 +275,7 @@ impl RustyVaultClient {
             .json(&data)
             .send()
             .await
-            .map_err(|e| AppError::Encryption(format!("Vault token revoke error: {}", e)))?;
+            .map_err(|e| request_error("Vault token revoke error", e))?;
 
         if !response.status().is_success() && response.status() != 404 {
             let status = response.status();
@@ -326,6 +357,49 @@ path "sys/policies/*" {{
     }
 }
 
+/// Turn a failed vault round-trip into an `AppError`
+///
+/// Running out of time is reported as `Timeout` so it can be retried; any
+/// other failure keeps the `context` it happened in.
+fn request_error(context: &str, e: reqwest::Error) -> AppError {
+    if e.is_timeout() {
+        AppError::Timeout(format!("{}: vault did not answer in time: {}", context, e))
+    } else {
+        AppError::Encryption(format!("{}: {}", context, e))
+    }
+}
+
+/// Most DEK reads `get_deks` keeps in flight at once
+const DEK_FETCH_CONCURRENCY: usize = 16;
+
+/// Send a DEK read and decode the stored DEK; a non-success status is a miss
+async fn fetch_dek(request: reqwest::RequestBuilder) -> AppResult<Option<Vec<u8>>> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| request_error("Vault request error", e))?;
+
+    if response.status().is_success() {
+        let json: serde_json::Value = response.json().await
+            .map_err(|e| request_error("Vault response parse error", e))?;
+        
+        if let Some(encrypted_dek_str) = json
+            .get("data")
+            .and_then(|d| d.get("data"))
+            .and_then(|d| d.get("encrypted_dek"))
+            .and_then(|v| v.as_str())
+        {
+            let dek = STANDARD.decode(encrypted_dek_str)
+                .map_err(|e| AppError::Encryption(format!("Base64 decode error: {}", e)))?;
+            Ok(Some(dek))
+        } else {
+            Ok(None)
+        }
+    } else {
+        Ok(None)
+    }
+}
+
 // Implement base Vault trait for RustyVaultClient
 #[async_trait]
 impl Vault for RustyVaultClient {
@@ -343,7 +417,7 @@ impl Vault for RustyVaultClient {
             .json(&data)
             .send()
             .await
-            .map_err(|e| AppError::Encryption(format!("Vault request error: {}", e)))?;
+            .map_err(|e| request_error("Vault request error", e))?;
         
         if !response.status().is_success() {
             let status = response.status();
@@ -359,32 +433,31 @@ impl Vault for RustyVaultClient {
     async fn get_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<Option<Vec<u8>>> {
         let path = format!("{}/v1/{}/data/{}/{}", self.addr, self.mount_path, entity_type, entity_id);
         
-        let response = self.client
-            .get(&path)
-            .header("X-RustyVault-Token", &self.token)
-            .send()
-            .await
-            .map_err(|e| AppError::Encryption(format!("Vault request error: {}", e)))?;
+        fetch_dek(self.client.get(&path).header("X-RustyVault-Token", &self.token)).await
+    }
 
-        if response.status().is_success() {
-            let json: serde_json::Value = response.json().await
-                .map_err(|e| AppError::Encryption(format!("Vault response parse error: {}", e)))?;
-            
-            if let Some(encrypted_dek_str) = json
-                .get("data")
-                .and_then(|d| d.get("data"))
-                .and_then(|d| d.get("encrypted_dek"))
-                .and_then(|v| v.as_str())
-            {
-                let dek = STANDARD.decode(encrypted_dek_str)
-                    .map_err(|e| AppError::Encryption(format!("Base64 decode error: {}", e)))?;
-                Ok(Some(dek))
-            } else {
-                Ok(None)
+    /// RustyVault's KV engine has no multi-get, so the reads are issued
+    /// concurrently (up to `DEK_FETCH_CONCURRENCY` at a time) over the pooled
+    /// client: a batch costs about one round-trip per chunk instead of one per id.
+    async fn get_deks(&self, entity_ids: &[String], entity_type: &str) -> AppResult<Vec<Option<Vec<u8>>>> {
+        let mut deks = vec![None; entity_ids.len()];
+
+        for (chunk_index, chunk) in entity_ids.chunks(DEK_FETCH_CONCURRENCY).enumerate() {
+            let mut fetches = tokio::task::JoinSet::new();
+            for (offset, entity_id) in chunk.iter().enumerate() {
+                let index = chunk_index * DEK_FETCH_CONCURRENCY + offset;
+                let path = format!("{}/v1/{}/data/{}/{}", self.addr, self.mount_path, entity_type, entity_id);
+                let request = self.client.get(&path).header("X-RustyVault-Token", &self.token);
+                fetches.spawn(async move { (index, fetch_dek(request).await) });
+            }
+            while let Some(joined) = fetches.join_next().await {
+                let (index, dek) = joined
+                    .map_err(|e| AppError::Internal(format!("DEK fetch task failed: {}", e)))?;
+                deks[index] = dek?;
             }
-        } else {
-            Ok(None)
         }
+
+        Ok(deks)
     }
 
     async fn delete_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<()> {
@@ -395,7 +468,7 @@ impl Vault for RustyVaultClient {
             .header("X-RustyVault-Token", &self.token)
             .send()
             .await
-            .map_err(|e| AppError::Encryption(format!("Vault delete error: {}", e)))?;
+            .map_err(|e| request_error("Vault delete error", e))?;
         
         if !response.status().is_success() && response.status() != 404 {
             let status = response.status();
@@ -428,7 +501,7 @@ impl Vault for RustyVaultClient {
             .json(&data)
             .send()
             .await
-            .map_err(|e| AppError::Encryption(format!("Vault request error: {}", e)))?;
+            .map_err(|e| request_error("Vault request error", e))?;
         
         if !response.status().is_success() {
             let status = response.status();
@@ -449,11 +522,11 @@ impl Vault for RustyVaultClient {
             .header("X-RustyVault-Token", &self.token)
             .send()
             .await
-            .map_err(|e| AppError::Encryption(format!("Vault request error: {}", e)))?;
+            .map_err(|e| request_error("Vault request error", e))?;
 
         if response.status().is_success() {
             let json: serde_json::Value = response.json().await
-                .map_err(|e| AppError::Encryption(format!("Vault response parse error: {}", e)))?;
+                .map_err(|e| request_error("Vault response parse error", e))?;
             
             if let Some(master_key_str) = json
                 .get("data")
@@ -479,3 +552,36 @@ impl Vault for RustyVaultClient {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_hung_vault_times_out() {
+        // Accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+
+        let config = VaultClientConfig {
+            connect_timeout_ms: 200,
+            request_timeout_ms: 200,
+            ..VaultClientConfig::default()
+        };
+        let client = RustyVaultClient::with_config(&addr, "token", "secret", &config).unwrap();
+
+        let started = std::time::Instant::now();
+        let dek = client.get_dek("patient-1", "patient").await;
+        assert!(matches!(dek, Err(AppError::Timeout(_))), "{:?}", dek);
+        assert!(dek.unwrap_err().is_retryable());
+        assert!(matches!(client.list_policies().await, Err(AppError::Timeout(_))));
+        assert!(matches!(client.revoke_token("t").await, Err(AppError::Timeout(_))));
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,142 @@
+use crate::domain::events::EventEnvelope;
+use crate::shared::{AppError, AppResult};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Consumer of domain events
+///
+/// Handlers may see the same event more than once and must be idempotent.
+#[async_trait]
+pub trait EventHandler: Send + Sync {
+    /// Name used in logs
+    fn name(&self) -> &str;
+
+    async fn handle(&self, envelope: &EventEnvelope) -> AppResult<()>;
+}
+
+/// In-process registry of event handlers keyed by event type
+#[derive(Default)]
+pub struct EventBus {
+    handlers: RwLock<HashMap<&'static str, Vec<Arc<dyn EventHandler>>>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a handler for one event type (see `DomainEvent::event_type`)
+    pub fn subscribe(&self, event_type: &'static str, handler: Arc<dyn EventHandler>) {
+        self.handlers
+            .write()
+            .unwrap()
+            .entry(event_type)
+            .or_default()
+            .push(handler);
+    }
+
+    /// Deliver an event to every handler registered for its type
+    ///
+    /// All handlers run even if one fails; the event counts as delivered only
+    /// when every handler succeeded.
+    pub async fn dispatch(&self, envelope: &EventEnvelope) -> AppResult<()> {
+        let handlers = self
+            .handlers
+            .read()
+            .unwrap()
+            .get(envelope.event.event_type())
+            .cloned()
+            .unwrap_or_default();
+
+        let mut failures = Vec::new();
+        for handler in handlers {
+            if let Err(e) = handler.handle(envelope).await {
+                tracing::warn!(
+                    "Event handler {} failed for {} {}: {}",
+                    handler.name(),
+                    envelope.event.event_type(),
+                    envelope.id,
+                    e
+                );
+                failures.push(format!("{}: {}", handler.name(), e));
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::Internal(failures.join("; ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::events::DomainEvent;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use uuid::Uuid;
+
+    struct Counter {
+        calls: AtomicUsize,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl EventHandler for Counter {
+        fn name(&self) -> &str {
+            "counter"
+        }
+
+        async fn handle(&self, _envelope: &EventEnvelope) -> AppResult<()> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.fail {
+                return Err(AppError::Internal("boom".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    fn deactivated() -> EventEnvelope {
+        EventEnvelope::new(DomainEvent::UserDeactivated {
+            user_id: Uuid::new_v4(),
+            deactivated_by: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_routes_by_event_type() {
+        let bus = EventBus::new();
+        let on_deactivate = Arc::new(Counter { calls: AtomicUsize::new(0), fail: false });
+        let on_policy = Arc::new(Counter { calls: AtomicUsize::new(0), fail: false });
+        bus.subscribe(DomainEvent::USER_DEACTIVATED, on_deactivate.clone());
+        bus.subscribe(DomainEvent::POLICY_CHANGED, on_policy.clone());
+
+        bus.dispatch(&deactivated()).await.unwrap();
+
+        assert_eq!(on_deactivate.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(on_policy.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failing_handler_fails_dispatch_but_others_run() {
+        let bus = EventBus::new();
+        let failing = Arc::new(Counter { calls: AtomicUsize::new(0), fail: true });
+        let ok = Arc::new(Counter { calls: AtomicUsize::new(0), fail: false });
+        bus.subscribe(DomainEvent::USER_DEACTIVATED, failing);
+        bus.subscribe(DomainEvent::USER_DEACTIVATED, ok.clone());
+
+        assert!(bus.dispatch(&deactivated()).await.is_err());
+        assert_eq!(ok.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_envelope_round_trips() {
+        let envelope = deactivated();
+        let json = serde_json::to_value(&envelope.event).unwrap();
+        assert_eq!(json["event_type"], DomainEvent::USER_DEACTIVATED);
+        let parsed: DomainEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, envelope.event);
+    }
+}
@@ -0,0 +1,22 @@
+//! Domain event delivery
+//!
+//! Events are written to the `event_outbox` table in the same transaction as
+//! the state change that produced them ([`OutboxStore::append`]). The
+//! [`OutboxRelay`] then delivers pending events to the handlers registered on
+//! the in-process [`EventBus`], marking them published only after every
+//! handler succeeded. Delivery is therefore at-least-once: handlers must be
+//! idempotent, and can use the envelope id to detect redeliveries.
+//!
+//! Events selected in `WebhookConfig` are additionally forwarded to an
+//! external receiver through a [`WebhookSink`], which queues them for the
+//! [`WebhookDispatcher`] so slow receivers never hold up the relay.
+
+pub mod event_bus;
+pub mod outbox;
+pub mod session_revocation;
+pub mod webhook;
+
+pub use event_bus::{EventBus, EventHandler};
+pub use outbox::{OutboxRelay, OutboxStore};
+pub use session_revocation::{RevokeSessionsOnDeactivate, RevokeSessionsOnLoginAnomaly};
+pub use webhook::{WebhookDispatcher, WebhookSink};
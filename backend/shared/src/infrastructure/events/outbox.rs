@@ -0,0 +1,195 @@
+use crate::domain::events::{DomainEvent, EventEnvelope};
+use crate::infrastructure::events::EventBus;
+use crate::shared::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+/// Longest delay between redelivery attempts
+const MAX_RETRY_DELAY_SECS: i64 = 3600;
+
+#[derive(Debug, FromRow)]
+struct OutboxRow {
+    id: Uuid,
+    payload: serde_json::Value,
+    occurred_at: DateTime<Utc>,
+    attempts: i32,
+}
+
+/// Transactional outbox of domain events
+pub struct OutboxStore {
+    pool: PgPool,
+}
+
+impl OutboxStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record an event as part of the caller's transaction
+    ///
+    /// Pass the transaction that performs the state change so the event is
+    /// committed (or rolled back) together with it.
+    pub async fn append<'c, E>(executor: E, envelope: &EventEnvelope) -> AppResult<()>
+    where
+        E: sqlx::PgExecutor<'c>,
+    {
+        let payload = serde_json::to_value(&envelope.event)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize event: {}", e)))?;
+        sqlx::query(
+            r#"
+            INSERT INTO event_outbox (id, event_type, aggregate_id, payload, occurred_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(envelope.id)
+        .bind(envelope.event.event_type())
+        .bind(envelope.event.aggregate_id())
+        .bind(payload)
+        .bind(envelope.occurred_at)
+        .execute(executor)
+        .await
+        .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// Claim up to `limit` pending events for delivery
+    ///
+    /// Claimed events are hidden from other relays for `lease`; if this relay
+    /// dies before reporting back they become due again afterwards.
+    async fn claim_batch(&self, limit: i64, lease: Duration) -> AppResult<Vec<(EventEnvelope, i32)>> {
+        let rows: Vec<OutboxRow> = sqlx::query_as(
+            r#"
+            UPDATE event_outbox
+            SET next_attempt_at = NOW() + make_interval(secs => $2), attempts = attempts + 1
+            WHERE id IN (
+                SELECT id FROM event_outbox
+                WHERE published_at IS NULL AND next_attempt_at <= NOW()
+                ORDER BY occurred_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, payload, occurred_at, attempts
+            "#,
+        )
+        .bind(limit)
+        .bind(lease.as_secs_f64())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let mut claimed = Vec::with_capacity(rows.len());
+        for row in rows {
+            match serde_json::from_value::<DomainEvent>(row.payload) {
+                Ok(event) => claimed.push((
+                    EventEnvelope { id: row.id, occurred_at: row.occurred_at, event },
+                    row.attempts,
+                )),
+                // Likely written by a newer service version; retried later
+                Err(e) => self.mark_failed(row.id, row.attempts, &format!("undecodable payload: {}", e)).await?,
+            }
+        }
+        claimed.sort_by_key(|(envelope, _)| envelope.occurred_at);
+        Ok(claimed)
+    }
+
+    async fn mark_published(&self, id: Uuid) -> AppResult<()> {
+        sqlx::query("UPDATE event_outbox SET published_at = NOW(), last_error = NULL WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// Schedule a retry with exponential backoff
+    async fn mark_failed(&self, id: Uuid, attempts: i32, error: &str) -> AppResult<()> {
+        let delay = 2i64.saturating_pow(attempts.clamp(0, 30) as u32).min(MAX_RETRY_DELAY_SECS);
+        sqlx::query(
+            r#"
+            UPDATE event_outbox
+            SET next_attempt_at = NOW() + make_interval(secs => $2), last_error = $3
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(delay as f64)
+        .bind(error)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+        Ok(())
+    }
+}
+
+/// Delivers pending outbox events to the event bus
+pub struct OutboxRelay {
+    store: Arc<OutboxStore>,
+    bus: Arc<EventBus>,
+    batch_size: i64,
+    poll_interval: Duration,
+    /// How long a claimed event stays hidden from other relays
+    lease: Duration,
+    wake: Notify,
+}
+
+impl OutboxRelay {
+    pub fn new(store: Arc<OutboxStore>, bus: Arc<EventBus>) -> Self {
+        Self {
+            store,
+            bus,
+            batch_size: 100,
+            poll_interval: Duration::from_secs(5),
+            lease: Duration::from_secs(60),
+            wake: Notify::new(),
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Deliver promptly instead of waiting for the next poll
+    ///
+    /// Call after committing a transaction that appended events.
+    pub fn notify(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Deliver one batch of due events, returning how many were published
+    pub async fn run_once(&self) -> AppResult<usize> {
+        let mut published = 0;
+        for (envelope, attempts) in self.store.claim_batch(self.batch_size, self.lease).await? {
+            match self.bus.dispatch(&envelope).await {
+                Ok(()) => {
+                    self.store.mark_published(envelope.id).await?;
+                    published += 1;
+                }
+                Err(e) => self.store.mark_failed(envelope.id, attempts, &e.to_string()).await?,
+            }
+        }
+        Ok(published)
+    }
+
+    /// Spawn the delivery loop on the current runtime
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match self.run_once().await {
+                    // A full batch suggests more is pending; continue immediately
+                    Ok(n) if n as i64 >= self.batch_size => continue,
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Outbox relay run failed: {}", e),
+                }
+                tokio::select! {
+                    _ = self.wake.notified() => {}
+                    _ = tokio::time::sleep(self.poll_interval) => {}
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,68 @@
+use crate::domain::events::{DomainEvent, EventEnvelope};
+use crate::infrastructure::events::EventHandler;
+use crate::infrastructure::session::SessionService;
+use crate::shared::AppResult;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Ends every active session of a user once the user is deactivated
+///
+/// Idempotent: redelivery finds no active sessions left and does nothing.
+pub struct RevokeSessionsOnDeactivate {
+    sessions: Arc<SessionService>,
+}
+
+impl RevokeSessionsOnDeactivate {
+    pub fn new(sessions: Arc<SessionService>) -> Self {
+        Self { sessions }
+    }
+}
+
+#[async_trait]
+impl EventHandler for RevokeSessionsOnDeactivate {
+    fn name(&self) -> &str {
+        "revoke-sessions-on-deactivate"
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> AppResult<()> {
+        if let DomainEvent::UserDeactivated { user_id, .. } = &envelope.event {
+            let ended = self.sessions.end_user_sessions(*user_id).await?;
+            if ended > 0 {
+                tracing::info!("Ended {} session(s) of deactivated user {}", ended, user_id);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Ends a user's sessions from before a login flagged as anomalous
+///
+/// The flagged login's own session is authenticated after the login is
+/// recorded, so it survives; whoever held the earlier sessions has to log in
+/// again. Idempotent like [`RevokeSessionsOnDeactivate`].
+pub struct RevokeSessionsOnLoginAnomaly {
+    sessions: Arc<SessionService>,
+}
+
+impl RevokeSessionsOnLoginAnomaly {
+    pub fn new(sessions: Arc<SessionService>) -> Self {
+        Self { sessions }
+    }
+}
+
+#[async_trait]
+impl EventHandler for RevokeSessionsOnLoginAnomaly {
+    fn name(&self) -> &str {
+        "revoke-sessions-on-login-anomaly"
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> AppResult<()> {
+        if let DomainEvent::LoginAnomalyDetected { user_id, login_at, kind, .. } = &envelope.event {
+            let ended = self.sessions.end_user_sessions_before(*user_id, *login_at).await?;
+            if ended > 0 {
+                tracing::warn!("Ended {} earlier session(s) of user {} after {} login", ended, user_id, kind);
+            }
+        }
+        Ok(())
+    }
+}
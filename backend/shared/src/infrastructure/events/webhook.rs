@@ -0,0 +1,336 @@
+use crate::config::settings::WebhookConfig;
+use crate::domain::events::{DomainEvent, EventEnvelope};
+use crate::infrastructure::events::{EventBus, EventHandler};
+use crate::shared::{AppError, AppResult};
+use async_trait::async_trait;
+use sqlx::{FromRow, PgPool};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+pub const ID_HEADER: &str = "X-Webhook-Id";
+
+/// Longest delay between delivery attempts
+const MAX_RETRY_DELAY_SECS: i64 = 3600;
+
+/// Signature of a webhook body: `sha256=` + hex HMAC-SHA256 of `"{timestamp}.{body}"`
+///
+/// The timestamp is signed too so receivers can reject replayed requests.
+pub fn sign(secret: &[u8], timestamp: i64, body: &[u8]) -> String {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret);
+    let mut ctx = ring::hmac::Context::with_key(&key);
+    ctx.update(timestamp.to_string().as_bytes());
+    ctx.update(b".");
+    ctx.update(body);
+    format!("sha256={}", hex::encode(ctx.sign().as_ref()))
+}
+
+/// Resolve configured event type names, skipping (and logging) unknown ones
+pub fn forwarded_event_types(config: &WebhookConfig) -> Vec<&'static str> {
+    config
+        .event_types
+        .iter()
+        .filter_map(|name| {
+            let found = DomainEvent::ALL_TYPES.iter().copied().find(|t| t == name);
+            if found.is_none() {
+                tracing::warn!("Ignoring unknown webhook event type '{}'", name);
+            }
+            found
+        })
+        .collect()
+}
+
+/// What to do with a delivery after a failed attempt
+#[derive(Debug, PartialEq)]
+enum RetryDecision {
+    RetryIn(i64),
+    DeadLetter,
+}
+
+fn retry_decision(attempts: i32, max_attempts: i32) -> RetryDecision {
+    if attempts >= max_attempts {
+        RetryDecision::DeadLetter
+    } else {
+        RetryDecision::RetryIn(2i64.saturating_pow(attempts.clamp(0, 30) as u32).min(MAX_RETRY_DELAY_SECS))
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct DeliveryRow {
+    id: Uuid,
+    payload: serde_json::Value,
+    attempts: i32,
+}
+
+/// Delivers queued events to the configured webhook URL
+///
+/// Deliveries live in `webhook_deliveries`; failed ones are retried with
+/// exponential backoff and marked `dead_letter` after `max_attempts`.
+pub struct WebhookDispatcher {
+    pool: PgPool,
+    client: reqwest::Client,
+    url: String,
+    secret: Vec<u8>,
+    max_attempts: i32,
+    batch_size: i64,
+    poll_interval: Duration,
+    /// How long a claimed delivery stays hidden from other dispatchers
+    lease: Duration,
+    wake: Notify,
+}
+
+impl WebhookDispatcher {
+    /// Build a dispatcher, or `None` when no webhook URL is configured
+    pub fn from_config(pool: PgPool, config: &WebhookConfig) -> AppResult<Option<Self>> {
+        let Some(url) = config.url.clone() else {
+            return Ok(None);
+        };
+        if config.secret.is_empty() {
+            return Err(AppError::Configuration(
+                "WEBHOOK_SECRET must be set when WEBHOOK_URL is configured".to_string(),
+            ));
+        }
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+            .map_err(|e| AppError::Configuration(format!("Failed to build webhook client: {}", e)))?;
+
+        Ok(Some(Self {
+            pool,
+            client,
+            url,
+            secret: config.secret.as_bytes().to_vec(),
+            max_attempts: config.max_attempts.max(1),
+            batch_size: 50,
+            poll_interval: Duration::from_secs(5),
+            lease: Duration::from_secs(60),
+            wake: Notify::new(),
+        }))
+    }
+
+    /// Queue an event for delivery; enqueuing the same event twice is a no-op
+    pub async fn enqueue(&self, envelope: &EventEnvelope) -> AppResult<()> {
+        let payload = serde_json::to_value(envelope)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize event: {}", e)))?;
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries (id, event_type, payload)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(envelope.id)
+        .bind(envelope.event.event_type())
+        .bind(payload)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+        self.wake.notify_one();
+        Ok(())
+    }
+
+    async fn claim_batch(&self) -> AppResult<Vec<DeliveryRow>> {
+        sqlx::query_as(
+            r#"
+            UPDATE webhook_deliveries
+            SET next_attempt_at = NOW() + make_interval(secs => $2), attempts = attempts + 1
+            WHERE id IN (
+                SELECT id FROM webhook_deliveries
+                WHERE status = 'pending' AND next_attempt_at <= NOW()
+                ORDER BY created_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, payload, attempts
+            "#,
+        )
+        .bind(self.batch_size)
+        .bind(self.lease.as_secs_f64())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    async fn post(&self, id: Uuid, payload: &serde_json::Value) -> Result<(), String> {
+        let body = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+        let timestamp = chrono::Utc::now().timestamp();
+        let response = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(ID_HEADER, id.to_string())
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, sign(&self.secret, timestamp, &body))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("webhook responded with {}", response.status()))
+        }
+    }
+
+    async fn mark_delivered(&self, id: Uuid) -> AppResult<()> {
+        sqlx::query(
+            "UPDATE webhook_deliveries SET status = 'delivered', delivered_at = NOW(), last_error = NULL WHERE id = $1",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    async fn mark_failed(&self, id: Uuid, attempts: i32, error: &str) -> AppResult<()> {
+        let query = match retry_decision(attempts, self.max_attempts) {
+            RetryDecision::RetryIn(delay) => sqlx::query(
+                r#"
+                UPDATE webhook_deliveries
+                SET next_attempt_at = NOW() + make_interval(secs => $2), last_error = $3
+                WHERE id = $1
+                "#,
+            )
+            .bind(id)
+            .bind(delay as f64)
+            .bind(error),
+            RetryDecision::DeadLetter => {
+                tracing::error!("Webhook delivery {} dead-lettered after {} attempts: {}", id, attempts, error);
+                sqlx::query("UPDATE webhook_deliveries SET status = 'dead_letter', last_error = $2 WHERE id = $1")
+                    .bind(id)
+                    .bind(error)
+            }
+        };
+        query.execute(&self.pool).await.map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// Attempt one batch of due deliveries, returning how many were claimed
+    pub async fn run_once(&self) -> AppResult<usize> {
+        let batch = self.claim_batch().await?;
+        for row in &batch {
+            match self.post(row.id, &row.payload).await {
+                Ok(()) => self.mark_delivered(row.id).await?,
+                Err(e) => {
+                    tracing::warn!("Webhook delivery {} failed (attempt {}): {}", row.id, row.attempts, e);
+                    self.mark_failed(row.id, row.attempts, &e).await?
+                }
+            }
+        }
+        Ok(batch.len())
+    }
+
+    /// Spawn the delivery loop on the current runtime
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match self.run_once().await {
+                    Ok(n) if n as i64 >= self.batch_size => continue,
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Webhook dispatcher run failed: {}", e),
+                }
+                tokio::select! {
+                    _ = self.wake.notified() => {}
+                    _ = tokio::time::sleep(self.poll_interval) => {}
+                }
+            }
+        })
+    }
+}
+
+/// Event handler that queues events for webhook delivery
+///
+/// Only enqueues, so a slow or unavailable receiver never holds up the
+/// outbox relay or the request that produced the event.
+pub struct WebhookSink {
+    dispatcher: Arc<WebhookDispatcher>,
+}
+
+impl WebhookSink {
+    pub fn new(dispatcher: Arc<WebhookDispatcher>) -> Self {
+        Self { dispatcher }
+    }
+
+    /// Subscribe a sink on `bus` for each event type selected in `config`
+    pub fn subscribe(bus: &EventBus, dispatcher: Arc<WebhookDispatcher>, config: &WebhookConfig) {
+        let sink: Arc<dyn EventHandler> = Arc::new(Self::new(dispatcher));
+        for event_type in forwarded_event_types(config) {
+            bus.subscribe(event_type, sink.clone());
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for WebhookSink {
+    fn name(&self) -> &str {
+        "webhook-sink"
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> AppResult<()> {
+        self.dispatcher.enqueue(envelope).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(event_types: &[&str]) -> WebhookConfig {
+        WebhookConfig {
+            url: Some("https://siem.example.com/hook".to_string()),
+            secret: "secret".to_string(),
+            event_types: event_types.iter().map(|s| s.to_string()).collect(),
+            max_attempts: 3,
+            timeout_seconds: 5,
+        }
+    }
+
+    #[test]
+    fn test_signature_is_verifiable_by_receiver() {
+        let body = br#"{"id":"1"}"#;
+        let signature = sign(b"secret", 1_700_000_000, body);
+
+        let hex = signature.strip_prefix("sha256=").unwrap();
+        let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, b"secret");
+        let tag = hex::decode(hex).unwrap();
+        assert!(ring::hmac::verify(&key, br#"1700000000.{"id":"1"}"#, &tag).is_ok());
+    }
+
+    #[test]
+    fn test_signature_covers_timestamp() {
+        let body = b"{}";
+        assert_ne!(sign(b"secret", 1, body), sign(b"secret", 2, body));
+        assert_ne!(sign(b"secret", 1, body), sign(b"other", 1, body));
+    }
+
+    #[test]
+    fn test_unknown_event_types_are_ignored() {
+        let types = forwarded_event_types(&config(&["PolicyChanged", "NoSuchEvent", "TokenRevoked"]));
+        assert_eq!(types, vec![DomainEvent::POLICY_CHANGED, DomainEvent::TOKEN_REVOKED]);
+    }
+
+    #[test]
+    fn test_retry_backs_off_then_dead_letters() {
+        assert_eq!(retry_decision(1, 3), RetryDecision::RetryIn(2));
+        assert_eq!(retry_decision(2, 3), RetryDecision::RetryIn(4));
+        assert_eq!(retry_decision(3, 3), RetryDecision::DeadLetter);
+        assert_eq!(retry_decision(20, 100), RetryDecision::RetryIn(MAX_RETRY_DELAY_SECS));
+    }
+
+    #[tokio::test]
+    async fn test_dispatcher_disabled_without_url() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let mut cfg = config(&[]);
+        cfg.url = None;
+        assert!(WebhookDispatcher::from_config(pool.clone(), &cfg).unwrap().is_none());
+
+        cfg.url = Some("https://siem.example.com/hook".to_string());
+        cfg.secret.clear();
+        assert!(WebhookDispatcher::from_config(pool, &cfg).is_err());
+    }
+}
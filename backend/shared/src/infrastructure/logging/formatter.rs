@@ -1,5 +1,12 @@
 use super::config::{LogFormat, LoggerConfig};
 use std::env;
+use std::sync::OnceLock;
+use tracing_subscriber::EnvFilter;
+
+type FilterReloader = Box<dyn Fn(EnvFilter) -> Result<(), String> + Send + Sync>;
+
+/// Swaps the active filter of the global subscriber, set by `init_logger`
+static FILTER_RELOADER: OnceLock<FilterReloader> = OnceLock::new();
 
 /// Initialize the logger with the given configuration
 pub fn init_logger(config: &LoggerConfig) {
@@ -13,27 +20,45 @@ pub fn init_logger(config: &LoggerConfig) {
     match config.format {
         LogFormat::Json => {
             // JSON format for production
-            tracing_subscriber::fmt()
-                .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+            let builder = tracing_subscriber::fmt()
+                .with_env_filter(EnvFilter::from_default_env())
                 .json()
                 .with_target(true)
                 .with_file(config.include_location)
                 .with_line_number(config.include_location)
-                .init();
+                .with_filter_reloading();
+            let handle = builder.reload_handle();
+            let _ = FILTER_RELOADER.set(Box::new(move |filter| handle.reload(filter).map_err(|e| e.to_string())));
+            builder.init();
         }
         LogFormat::Pretty => {
             // Pretty format for development
-            tracing_subscriber::fmt()
-                .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+            let builder = tracing_subscriber::fmt()
+                .with_env_filter(EnvFilter::from_default_env())
                 .pretty()
                 .with_target(true)
                 .with_file(config.include_location)
                 .with_line_number(config.include_location)
-                .init();
+                .with_filter_reloading();
+            let handle = builder.reload_handle();
+            let _ = FILTER_RELOADER.set(Box::new(move |filter| handle.reload(filter).map_err(|e| e.to_string())));
+            builder.init();
         }
     }
 }
 
+/// Replace the log filter of the running subscriber
+///
+/// `directives` uses `RUST_LOG` syntax (e.g. `info,shared=debug`).
+pub fn set_filter(directives: &str) -> Result<(), String> {
+    let filter = EnvFilter::try_new(directives)
+        .map_err(|e| format!("invalid log filter '{}': {}", directives, e))?;
+    let reload = FILTER_RELOADER
+        .get()
+        .ok_or_else(|| "logger has not been initialized".to_string())?;
+    reload(filter)
+}
+
 /// Initialize logger with default configuration
 pub fn init_default() {
     let config = LoggerConfig::default();
@@ -4,7 +4,7 @@ pub mod formatter;
 
 pub use config::{LogFormat, LoggerConfig};
 pub use context::{LogContext, span_with_context, span_from_request_context};
-pub use formatter::{init_logger, init_default};
+pub use formatter::{init_logger, init_default, set_filter};
 
 use crate::config::settings::LoggingConfig;
 use crate::config::deployment::DeploymentConfig;
@@ -0,0 +1,203 @@
+//! Account-takeover detection on successful logins
+//!
+//! Detectors look at a login next to the user's recent successful logins.
+//! What they flag is published as a `LoginAnomalyDetected` domain event;
+//! responses subscribe to it. Ending the user's earlier sessions is the one
+//! provided, as there is no MFA yet to challenge the login with.
+
+use async_trait::async_trait;
+use chrono::Duration;
+use serde_json::json;
+use std::env;
+use crate::domain::entities::LoginEvent;
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnomalySeverity {
+    Low,
+    Medium,
+    High,
+}
+
+impl AnomalySeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+/// What a detector found suspicious about a login
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoginAnomaly {
+    /// Detector name, e.g. `impossible_travel`
+    pub kind: &'static str,
+    pub severity: AnomalySeverity,
+    pub details: serde_json::Value,
+}
+
+/// A rule applied to every successful login
+#[async_trait]
+pub trait LoginAnomalyDetector: Send + Sync {
+    /// How far back `recent` reaches
+    fn lookback(&self) -> Duration;
+
+    /// `recent` holds the user's earlier successful logins within
+    /// [`lookback`](Self::lookback), newest first
+    async fn inspect(&self, login: &LoginEvent, recent: &[LoginEvent]) -> Option<LoginAnomaly>;
+}
+
+/// Flags two logins further apart than anyone could have travelled in the
+/// time between them
+///
+/// Only logins with coordinates are compared. `min_distance_km` absorbs the
+/// imprecision of IP geolocation, so nearby cities never count.
+#[derive(Debug, Clone)]
+pub struct ImpossibleTravelDetector {
+    pub max_speed_kmh: f64,
+    pub min_distance_km: f64,
+    pub window: Duration,
+}
+
+impl Default for ImpossibleTravelDetector {
+    /// Faster than an airliner, over more than 500 km, within a day
+    fn default() -> Self {
+        Self {
+            max_speed_kmh: 900.0,
+            min_distance_km: 500.0,
+            window: Duration::hours(24),
+        }
+    }
+}
+
+impl ImpossibleTravelDetector {
+    /// `None` when `LOGIN_IMPOSSIBLE_TRAVEL_ENABLED` is `false`; thresholds
+    /// from `LOGIN_IMPOSSIBLE_TRAVEL_MAX_SPEED_KMH`, `..._MIN_DISTANCE_KM`
+    /// and `..._WINDOW_HOURS`
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| env::var(format!("LOGIN_IMPOSSIBLE_TRAVEL_{}", name)).ok();
+        if var("ENABLED").is_some_and(|v| v.eq_ignore_ascii_case("false")) {
+            return None;
+        }
+        let defaults = Self::default();
+        Some(Self {
+            max_speed_kmh: var("MAX_SPEED_KMH")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_speed_kmh),
+            min_distance_km: var("MIN_DISTANCE_KM")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.min_distance_km),
+            window: var("WINDOW_HOURS")
+                .and_then(|v| v.parse().ok())
+                .map(Duration::hours)
+                .unwrap_or(defaults.window),
+        })
+    }
+}
+
+#[async_trait]
+impl LoginAnomalyDetector for ImpossibleTravelDetector {
+    fn lookback(&self) -> Duration {
+        self.window
+    }
+
+    async fn inspect(&self, login: &LoginEvent, recent: &[LoginEvent]) -> Option<LoginAnomaly> {
+        let here = coordinates(login)?;
+        recent
+            .iter()
+            .filter(|previous| previous.id != login.id && login.created_at - previous.created_at <= self.window)
+            .filter_map(|previous| {
+                let distance_km = haversine_km(coordinates(previous)?, here);
+                if distance_km < self.min_distance_km {
+                    return None;
+                }
+                // Floor at a minute: same-instant logins are not infinitely fast
+                let hours = (login.created_at - previous.created_at).num_seconds().max(60) as f64 / 3600.0;
+                let speed_kmh = distance_km / hours;
+                (speed_kmh > self.max_speed_kmh).then_some((previous, distance_km, speed_kmh))
+            })
+            .max_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(previous, distance_km, speed_kmh)| LoginAnomaly {
+                kind: "impossible_travel",
+                severity: AnomalySeverity::High,
+                details: json!({
+                    "previous_login_event_id": previous.id,
+                    "previous_login_at": previous.created_at,
+                    "previous_ip_address": previous.ip_address,
+                    "previous_location": previous.geo,
+                    "ip_address": login.ip_address,
+                    "location": login.geo,
+                    "distance_km": distance_km.round(),
+                    "speed_kmh": speed_kmh.round(),
+                }),
+            })
+    }
+}
+
+fn coordinates(event: &LoginEvent) -> Option<(f64, f64)> {
+    let geo = event.geo.as_ref()?;
+    Some((geo.latitude?, geo.longitude?))
+}
+
+/// Great-circle distance between two (latitude, longitude) points in degrees
+pub fn haversine_km((lat1, lon1): (f64, f64), (lat2, lon2): (f64, f64)) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (lon2 - lon1).to_radians();
+    let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::{GeoLocation, LoginAttempt};
+    use uuid::Uuid;
+
+    fn login_at(user_id: Uuid, minutes_ago: i64, latitude: f64, longitude: f64) -> LoginEvent {
+        let mut event = LoginEvent::success(user_id, "a@example.com", LoginAttempt::default()).with_geo(Some(GeoLocation {
+            latitude: Some(latitude),
+            longitude: Some(longitude),
+            ..Default::default()
+        }));
+        event.created_at -= Duration::minutes(minutes_ago);
+        event
+    }
+
+    #[test]
+    fn test_haversine_distance() {
+        // London to New York is about 5570 km
+        let distance = haversine_km((51.5074, -0.1278), (40.7128, -74.0060));
+        assert!((distance - 5570.0).abs() < 20.0, "{}", distance);
+        assert_eq!(haversine_km((10.0, 10.0), (10.0, 10.0)), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_distant_logins_in_quick_succession_are_flagged() {
+        let detector = ImpossibleTravelDetector::default();
+        let user = Uuid::new_v4();
+        let new_york = login_at(user, 0, 40.7128, -74.0060);
+
+        // London an hour ago: ~5570 km/h
+        let anomaly = detector.inspect(&new_york, &[login_at(user, 60, 51.5074, -0.1278)]).await.unwrap();
+        assert_eq!(anomaly.kind, "impossible_travel");
+        assert_eq!(anomaly.severity, AnomalySeverity::High);
+
+        // London ten hours ago is a plausible flight
+        assert!(detector.inspect(&new_york, &[login_at(user, 600, 51.5074, -0.1278)]).await.is_none());
+        // Boston a minute ago is within geolocation noise
+        assert!(detector.inspect(&new_york, &[login_at(user, 1, 42.3601, -71.0589)]).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_logins_without_coordinates_are_ignored() {
+        let detector = ImpossibleTravelDetector::default();
+        let user = Uuid::new_v4();
+        let unlocated = LoginEvent::success(user, "a@example.com", LoginAttempt::default());
+        assert!(detector.inspect(&unlocated, &[login_at(user, 5, 51.5074, -0.1278)]).await.is_none());
+        assert!(detector.inspect(&login_at(user, 0, 40.7128, -74.0060), &[unlocated]).await.is_none());
+    }
+}
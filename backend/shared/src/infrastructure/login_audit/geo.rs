@@ -0,0 +1,135 @@
+//! IP geolocation for login events
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::net::IpAddr;
+use std::time::Duration;
+use crate::domain::entities::GeoLocation;
+
+/// Env var holding the lookup URL, with `{ip}` where the address goes
+pub const GEO_LOOKUP_URL_ENV: &str = "LOGIN_GEO_LOOKUP_URL";
+
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Resolves an IP address to a coarse location
+#[async_trait]
+pub trait GeoLocator: Send + Sync {
+    /// `None` when the address cannot be located; lookups never fail a login
+    async fn locate(&self, ip: IpAddr) -> Option<GeoLocation>;
+}
+
+/// Looks addresses up with an HTTP GET returning JSON with any of
+/// `country`, `region`, `city`, `latitude` and `longitude`
+/// (e.g. `https://ipapi.co/{ip}/json/` or a self-hosted equivalent)
+pub struct HttpGeoLocator {
+    client: reqwest::Client,
+    url_template: String,
+}
+
+#[derive(Deserialize)]
+struct LookupResponse {
+    country: Option<String>,
+    region: Option<String>,
+    city: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+}
+
+impl HttpGeoLocator {
+    pub fn new(url_template: impl Into<String>) -> Result<Self, reqwest::Error> {
+        Ok(Self {
+            client: reqwest::Client::builder().timeout(LOOKUP_TIMEOUT).build()?,
+            url_template: url_template.into(),
+        })
+    }
+
+    /// `None` unless a lookup URL is configured
+    pub fn from_env() -> Option<Self> {
+        let url_template = std::env::var(GEO_LOOKUP_URL_ENV).ok().filter(|url| !url.is_empty())?;
+        match Self::new(url_template) {
+            Ok(locator) => Some(locator),
+            Err(e) => {
+                tracing::error!("Login geolocation disabled: {}", e);
+                None
+            }
+        }
+    }
+
+    fn url_for(&self, ip: IpAddr) -> String {
+        self.url_template.replace("{ip}", &ip.to_string())
+    }
+}
+
+#[async_trait]
+impl GeoLocator for HttpGeoLocator {
+    async fn locate(&self, ip: IpAddr) -> Option<GeoLocation> {
+        // Private and loopback addresses say nothing about where a user is
+        if !is_public(ip) {
+            return None;
+        }
+        let response = self.client.get(self.url_for(ip)).send().await.and_then(|r| r.error_for_status());
+        let body: LookupResponse = match response {
+            Ok(response) => response.json().await.ok()?,
+            Err(e) => {
+                tracing::warn!("Geolocation lookup failed: {}", e);
+                return None;
+            }
+        };
+        Some(GeoLocation {
+            country: body.country,
+            region: body.region,
+            city: body.city,
+            latitude: body.latitude,
+            longitude: body.longitude,
+        })
+    }
+}
+
+/// Whether `ip` is routable on the public internet
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            // 100.64.0.0/10 is carrier-grade NAT
+            let shared = a == 100 && (64..128).contains(&b);
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || shared)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public(IpAddr::V4(v4)),
+            None => {
+                let first = ip.segments()[0];
+                // fc00::/7 unique local, fe80::/10 link-local
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_public_addresses_are_looked_up() {
+        assert!(is_public("8.8.8.8".parse().unwrap()));
+        assert!(is_public("2001:4860:4860::8888".parse().unwrap()));
+        for ip in ["10.1.2.3", "192.168.0.1", "127.0.0.1", "100.64.0.1", "::1", "fd00::1", "fe80::1", "::ffff:10.0.0.1"] {
+            assert!(!is_public(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn test_lookup_url_substitutes_the_address() {
+        let locator = HttpGeoLocator::new("https://geo.internal/{ip}/json").unwrap();
+        assert_eq!(locator.url_for("203.0.113.9".parse().unwrap()), "https://geo.internal/203.0.113.9/json");
+    }
+}
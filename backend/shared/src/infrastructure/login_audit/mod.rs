@@ -0,0 +1,133 @@
+//! Login audit trail
+//!
+//! Every password login attempt is stored as a [`LoginEvent`], enriched with
+//! a coarse location when a [`GeoLocator`] is configured. Successful logins
+//! then go through the configured [`LoginAnomalyDetector`]s. Recording
+//! happens off the request path: a slow lookup or a failed insert never
+//! delays or fails the login itself.
+
+pub mod anomaly;
+pub mod geo;
+
+pub use anomaly::{AnomalySeverity, ImpossibleTravelDetector, LoginAnomaly, LoginAnomalyDetector};
+pub use geo::{GeoLocator, HttpGeoLocator};
+
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+use crate::domain::entities::LoginEvent;
+use crate::domain::events::{DomainEvent, EventEnvelope};
+use crate::domain::repositories::LoginEventRepository;
+use crate::infrastructure::events::{OutboxRelay, OutboxStore};
+use crate::shared::AppResult;
+
+/// Earlier logins handed to each detector, at most
+const DETECTION_HISTORY: u32 = 50;
+
+pub struct LoginAuditor {
+    repository: Arc<dyn LoginEventRepository>,
+    geo: Option<Arc<dyn GeoLocator>>,
+    detectors: Vec<Arc<dyn LoginAnomalyDetector>>,
+    /// Where anomalies are published; without it they are only logged
+    events: Option<(PgPool, Arc<OutboxRelay>)>,
+}
+
+impl LoginAuditor {
+    pub fn new(repository: Arc<dyn LoginEventRepository>, geo: Option<Arc<dyn GeoLocator>>) -> Self {
+        Self {
+            repository,
+            geo,
+            detectors: Vec::new(),
+            events: None,
+        }
+    }
+
+    pub fn with_detector(mut self, detector: Arc<dyn LoginAnomalyDetector>) -> Self {
+        self.detectors.push(detector);
+        self
+    }
+
+    /// Publish anomalies as `LoginAnomalyDetected` through the outbox
+    pub fn with_events(mut self, pool: PgPool, relay: Arc<OutboxRelay>) -> Self {
+        self.events = Some((pool, relay));
+        self
+    }
+
+    /// Store `event` in the background
+    pub fn record(self: &Arc<Self>, event: LoginEvent) {
+        let auditor = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = auditor.store(event).await {
+                tracing::error!("Failed to record login event: {}", e);
+            }
+        });
+    }
+
+    /// Enrich and store `event`, returning it as stored
+    pub async fn store(&self, event: LoginEvent) -> AppResult<LoginEvent> {
+        let geo = match (&self.geo, event.ip_address) {
+            (Some(geo), Some(ip)) => geo.locate(ip).await,
+            _ => None,
+        };
+        let event = self.repository.create(event.with_geo(geo)).await?;
+        tracing::info!(
+            target: "login_audit",
+            email = %event.email,
+            succeeded = event.succeeded,
+            failure_reason = event.failure_reason.map(|reason| reason.as_str()),
+            ip = ?event.ip_address,
+            country = event.geo.as_ref().and_then(|geo| geo.country.as_deref()),
+            "login attempt"
+        );
+        if let (true, Some(user_id)) = (event.succeeded, event.user_id) {
+            for anomaly in self.detect(user_id, &event).await? {
+                self.report(user_id, &event, anomaly).await?;
+            }
+        }
+        Ok(event)
+    }
+
+    async fn detect(&self, user_id: Uuid, event: &LoginEvent) -> AppResult<Vec<LoginAnomaly>> {
+        let Some(lookback) = self.detectors.iter().map(|d| d.lookback()).max() else {
+            return Ok(Vec::new());
+        };
+        let recent = self
+            .repository
+            .recent_successes(user_id, event.created_at - lookback, DETECTION_HISTORY)
+            .await?;
+        let recent: Vec<LoginEvent> = recent.into_iter().filter(|previous| previous.id != event.id).collect();
+        let mut anomalies = Vec::new();
+        for detector in &self.detectors {
+            let since = event.created_at - detector.lookback();
+            let window: Vec<LoginEvent> = recent.iter().filter(|previous| previous.created_at >= since).cloned().collect();
+            anomalies.extend(detector.inspect(event, &window).await);
+        }
+        Ok(anomalies)
+    }
+
+    async fn report(&self, user_id: Uuid, event: &LoginEvent, anomaly: LoginAnomaly) -> AppResult<()> {
+        tracing::error!(
+            target: "security",
+            user_id = %user_id,
+            login_event_id = %event.id,
+            kind = anomaly.kind,
+            severity = anomaly.severity.as_str(),
+            details = %anomaly.details,
+            "suspicious login"
+        );
+        let Some((pool, relay)) = &self.events else {
+            return Ok(());
+        };
+        let envelope = EventEnvelope::new(DomainEvent::LoginAnomalyDetected {
+            user_id,
+            login_event_id: event.id,
+            login_at: event.created_at,
+            kind: anomaly.kind.to_string(),
+            severity: anomaly.severity.as_str().to_string(),
+            details: anomaly.details,
+        });
+        OutboxStore::append(pool, &envelope).await?;
+        relay.notify();
+        Ok(())
+    }
+}
@@ -3,10 +3,19 @@ pub mod encryption;
 pub mod storage;
 pub mod providers;
 pub mod oidc;
+pub mod saml;
+pub mod login_audit;
+pub mod one_time_token;
+pub mod token_delivery;
+pub mod password_reset;
+pub mod email_change;
 pub mod zanzibar;
 pub mod repositories;
 pub mod logging;
 pub mod session;
+pub mod counters;
+pub mod events;
 pub mod runtime;
 pub mod api;
+pub mod tls;
 
@@ -0,0 +1,33 @@
+//! Single-use tokens sent to users by email (password reset, email change)
+//!
+//! Only the hash is stored, so a database read does not yield usable tokens.
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use sha2::{Digest, Sha256};
+
+/// 256 random bits, hex encoded
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// What is stored and looked up; whitespace picked up by copy and paste is ignored
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.trim().as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_are_unique_and_stored_hashed() {
+        let token = generate_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, generate_token());
+        assert_ne!(hash_token(&token), token);
+        assert_eq!(hash_token(&format!(" {}\n", token)), hash_token(&token));
+    }
+}
@@ -0,0 +1,204 @@
+//! Self-service password reset
+//!
+//! Requesting a reset stores the SHA-256 of a random token, publishes
+//! `PasswordResetRequested` through the outbox and, once committed, hands the
+//! token to the [`TokenSender`] to email. The event carries only the reset
+//! id, never the token. Confirming with the token and a new
+//! password sets the password, consumes the token and signs the user out
+//! everywhere: refresh tokens are revoked and sessions ended.
+
+use bcrypt::{hash, DEFAULT_COST};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use std::net::IpAddr;
+use std::sync::Arc;
+use uuid::Uuid;
+use crate::domain::events::{DomainEvent, EventEnvelope};
+use crate::infrastructure::events::{OutboxRelay, OutboxStore};
+use crate::infrastructure::one_time_token::{generate_token, hash_token};
+use crate::infrastructure::session::SessionService;
+use crate::infrastructure::token_delivery::{TokenMessage, TokenPurpose, TokenSender};
+use crate::shared::{AppError, AppResult, PasswordPolicy};
+
+const DEFAULT_TTL_MINUTES: i64 = 30;
+
+/// The same message for unknown, used and expired tokens
+const INVALID_TOKEN: &str = "Invalid or expired password reset token";
+
+pub struct PasswordResetService {
+    pool: PgPool,
+    sessions: Arc<SessionService>,
+    relay: Option<Arc<OutboxRelay>>,
+    sender: Option<Arc<dyn TokenSender>>,
+    policy: PasswordPolicy,
+    ttl: Duration,
+}
+
+impl PasswordResetService {
+    pub fn new(
+        pool: PgPool,
+        sessions: Arc<SessionService>,
+        relay: Option<Arc<OutboxRelay>>,
+        sender: Option<Arc<dyn TokenSender>>,
+    ) -> Self {
+        Self {
+            pool,
+            sessions,
+            relay,
+            sender,
+            policy: PasswordPolicy::default(),
+            ttl: Duration::minutes(DEFAULT_TTL_MINUTES),
+        }
+    }
+
+    /// Token lifetime from `PASSWORD_RESET_TTL_MINUTES`
+    pub fn with_ttl_from_env(mut self) -> Self {
+        if let Some(minutes) = std::env::var("PASSWORD_RESET_TTL_MINUTES")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|minutes| *minutes > 0)
+        {
+            self.ttl = Duration::minutes(minutes);
+        }
+        self
+    }
+
+    /// Start a reset for `email`
+    ///
+    /// Succeeds whether or not an active account has that email, so callers
+    /// learn nothing about which emails exist. Earlier unused tokens of the
+    /// user are consumed: only the newest link works.
+    pub async fn request_reset(&self, email: &str, requested_ip: Option<IpAddr>) -> AppResult<()> {
+        let Some(sender) = &self.sender else {
+            return Err(AppError::Configuration(
+                "Password reset tokens cannot be delivered: TOKEN_DELIVERY_URL is not set".to_string(),
+            ));
+        };
+        let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
+
+        let user: Option<(Uuid, String)> = sqlx::query_as(
+            "SELECT id, email FROM users WHERE LOWER(email) = LOWER($1) AND is_active = true AND deleted_at IS NULL",
+        )
+        .bind(email.trim())
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+        let Some((user_id, email)) = user else {
+            tracing::info!(target: "security", "Password reset requested for an unknown or inactive email");
+            return Ok(());
+        };
+
+        sqlx::query("UPDATE password_reset_tokens SET consumed_at = NOW() WHERE user_id = $1 AND consumed_at IS NULL")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+
+        let token = generate_token();
+        let expires_at = Utc::now() + self.ttl;
+        let reset_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO password_reset_tokens (user_id, token_hash, expires_at, requested_ip)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .bind(hash_token(&token))
+        .bind(expires_at)
+        .bind(requested_ip.map(|ip| ip.to_string()))
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        let event = EventEnvelope::new(DomainEvent::PasswordResetRequested { user_id, reset_id, expires_at });
+        OutboxStore::append(&mut *tx, &event).await?;
+        tx.commit().await.map_err(AppError::Database)?;
+
+        if let Some(relay) = &self.relay {
+            relay.notify();
+        }
+        let message = TokenMessage {
+            purpose: TokenPurpose::PasswordReset,
+            user_id,
+            recipient: email,
+            token,
+            expires_at,
+            notify: None,
+        };
+        sender.send(&message).await?;
+        tracing::info!(target: "security", user_id = %user_id, "Password reset requested");
+        Ok(())
+    }
+
+    /// Set a new password with a token from [`Self::request_reset`]
+    ///
+    /// A token presented after expiry is consumed and rejected. One rejected
+    /// for the password policy stays usable, so the user can pick another
+    /// password.
+    pub async fn confirm_reset(&self, token: &str, new_password: &str) -> AppResult<Uuid> {
+        self.policy.check(new_password, None)?;
+
+        let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
+        let row: Option<(Uuid, Uuid, DateTime<Utc>, String)> = sqlx::query_as(
+            r#"
+            SELECT t.id, t.user_id, t.expires_at, u.email
+            FROM password_reset_tokens t
+            JOIN users u ON u.id = t.user_id
+            WHERE t.token_hash = $1 AND t.consumed_at IS NULL AND u.is_active = true
+            FOR UPDATE OF t
+            "#,
+        )
+        .bind(hash_token(token))
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+        let Some((token_id, user_id, expires_at, email)) = row else {
+            return Err(AppError::Validation(INVALID_TOKEN.to_string()));
+        };
+
+        if expires_at <= Utc::now() {
+            consume(&mut tx, token_id).await?;
+            tx.commit().await.map_err(AppError::Database)?;
+            return Err(AppError::Validation(INVALID_TOKEN.to_string()));
+        }
+        self.policy.check(new_password, Some(&email))?;
+
+        let password_hash = hash(new_password, DEFAULT_COST)
+            .map_err(|e| AppError::Internal(format!("Password hashing failed: {}", e)))?;
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET password_hash = $2, updated_at = NOW(), updated_by = $1, version = version + 1
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(password_hash)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+        consume(&mut tx, token_id).await?;
+        sqlx::query("UPDATE refresh_tokens SET is_revoked = true, revoked_at = NOW() WHERE user_id = $1 AND is_revoked = false")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+        tx.commit().await.map_err(AppError::Database)?;
+
+        // After commit: the password has changed even if this fails, and
+        // the sessions can still be ended by an admin
+        let ended = self.sessions.end_user_sessions(user_id).await?;
+        tracing::warn!(target: "security", user_id = %user_id, sessions_ended = ended, "Password reset completed");
+        Ok(user_id)
+    }
+}
+
+async fn consume(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, token_id: Uuid) -> AppResult<()> {
+    sqlx::query("UPDATE password_reset_tokens SET consumed_at = NOW() WHERE id = $1")
+        .bind(token_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::Database)?;
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use crate::config::providers::{CounterStoreConfig, CounterStoreProvider};
+use crate::infrastructure::counters::{CounterStore, InMemoryCounterStore, RedisCounterStore};
+use crate::shared::{AppError, AppResult};
+use std::sync::Arc;
+
+/// Create the counter store for rate limits and lockouts
+///
+/// Redis is connected to up front, so a bad `REDIS_URL` fails startup rather
+/// than the first login.
+pub async fn create_counter_store(config: &CounterStoreConfig) -> AppResult<Arc<dyn CounterStore>> {
+    match config.provider {
+        CounterStoreProvider::Memory => Ok(Arc::new(InMemoryCounterStore::new())),
+        CounterStoreProvider::Redis => {
+            let url = config.redis_url.as_deref().ok_or_else(|| {
+                AppError::Configuration("COUNTER_STORE=redis requires REDIS_URL".to_string())
+            })?;
+            Ok(Arc::new(RedisCounterStore::connect(url, &config.key_prefix).await?))
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::domain::entities::ApiKey;
+use crate::domain::repositories::ApiKeyRepository;
+use crate::shared::AppResult;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct ApiKeyRepositoryImpl {
+    pool: PgPool,
+}
+
+impl ApiKeyRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for ApiKeyRepositoryImpl {
+    async fn create(&self, api_key: ApiKey) -> AppResult<ApiKey> {
+        sqlx::query!(
+            r#"
+            INSERT INTO api_keys (id, user_id, role_id, name, key_prefix, key_hash, expires_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            api_key.id,
+            api_key.user_id,
+            api_key.role_id,
+            api_key.name,
+            api_key.key_prefix,
+            api_key.key_hash,
+            api_key.expires_at,
+            api_key.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| crate::shared::AppError::Database(e))?;
+
+        Ok(api_key)
+    }
+
+    async fn find_by_hash(&self, key_hash: &str) -> AppResult<Option<ApiKey>> {
+        let api_key = sqlx::query_as!(
+            ApiKey,
+            r#"
+            SELECT id, user_id, role_id, name, key_prefix, key_hash, expires_at, last_used_at, revoked_at, created_at
+            FROM api_keys
+            WHERE key_hash = $1
+            "#,
+            key_hash
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| crate::shared::AppError::Database(e))?;
+
+        Ok(api_key)
+    }
+
+    async fn list_by_user(&self, user_id: Uuid) -> AppResult<Vec<ApiKey>> {
+        let api_keys = sqlx::query_as!(
+            ApiKey,
+            r#"
+            SELECT id, user_id, role_id, name, key_prefix, key_hash, expires_at, last_used_at, revoked_at, created_at
+            FROM api_keys
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| crate::shared::AppError::Database(e))?;
+
+        Ok(api_keys)
+    }
+
+    async fn revoke(&self, id: Uuid, user_id: Uuid) -> AppResult<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE api_keys
+            SET revoked_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            "#,
+            id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| crate::shared::AppError::Database(e))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn touch_last_used(&self, id: Uuid) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE api_keys
+            SET last_used_at = NOW()
+            WHERE id = $1
+            "#,
+            id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| crate::shared::AppError::Database(e))?;
+
+        Ok(())
+    }
+}
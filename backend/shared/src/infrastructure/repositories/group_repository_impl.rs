@@ -1,6 +1,7 @@
 use crate::domain::entities::Group;
-use crate::domain::repositories::GroupRepository;
-use crate::shared::AppResult;
+use crate::domain::repositories::{GroupRepository, GROUP_LIST_SPEC};
+use crate::infrastructure::database::{fetch_page, stale_update_error};
+use crate::shared::{AppResult, ListQuery, Page};
 use async_trait::async_trait;
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -49,8 +50,12 @@ impl GroupRepository for GroupRepositoryImpl {
         .map_err(|e| crate::shared::AppError::Database(e))
     }
 
-    async fn update(&self, group: Group) -> AppResult<Group> {
-        sqlx::query_as!(
+    async fn update(&self, mut group: Group) -> AppResult<Group> {
+        // Store current version for optimistic locking
+        let current_version = group.version;
+        group.version += 1;
+
+        let row = sqlx::query_as!(
             Group,
             r#"
             UPDATE groups
@@ -63,7 +68,7 @@ impl GroupRepository for GroupRepositoryImpl {
                 updated_by = $8,
                 system_id = $9,
                 version = $10
-            WHERE id = $1
+            WHERE id = $1 AND version = $11 AND deleted_at IS NULL
             RETURNING id, name, description, organization_id, metadata, created_at, updated_at,
                       deleted_at, deleted_by, request_id, created_by, updated_by, system_id, version
             "#,
@@ -76,11 +81,25 @@ impl GroupRepository for GroupRepositoryImpl {
             group.request_id,
             group.updated_by,
             group.system_id,
-            group.version
+            group.version,
+            current_version
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
         .await
-        .map_err(|e| crate::shared::AppError::Database(e))
+        .map_err(|e| crate::shared::AppError::Database(e))?;
+
+        match row {
+            Some(group) => Ok(group),
+            None => Err(stale_update_error(
+                &self.pool,
+                "groups",
+                Some("deleted_at IS NULL"),
+                "Group",
+                group.id,
+                current_version,
+            )
+            .await),
+        }
     }
 
     async fn find_by_id(&self, id: Uuid) -> AppResult<Option<Group>> {
@@ -151,6 +170,19 @@ impl GroupRepository for GroupRepositoryImpl {
         .map_err(|e| crate::shared::AppError::Database(e))
     }
 
+    async fn list_page(&self, query: &ListQuery) -> AppResult<Page<Group>> {
+        fetch_page(
+            &self.pool,
+            "groups",
+            "id, name, description, organization_id, metadata, created_at, updated_at, \
+             deleted_at, deleted_by, request_id, created_by, updated_by, system_id, version",
+            Some("deleted_at IS NULL"),
+            query,
+            &GROUP_LIST_SPEC,
+        )
+        .await
+    }
+
     async fn soft_delete(&self, id: Uuid, deleted_by: Option<Uuid>) -> AppResult<()> {
         sqlx::query!(
             r#"
@@ -0,0 +1,154 @@
+use crate::domain::entities::{GeoLocation, LoginEvent, LoginFailureReason};
+use crate::domain::repositories::{LoginEventRepository, LOGIN_EVENT_LIST_SPEC};
+use crate::infrastructure::database::{fetch_page, DatabaseService};
+use crate::shared::{AppResult, ListQuery, Page};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+const LOGIN_EVENT_COLUMNS: &str = "id, user_id, email, succeeded, failure_reason, \
+     host(ip_address) as ip_address_str, user_agent, app_type, app_device, request_id, \
+     country, region, city, latitude, longitude, created_at";
+
+/// Database row, with the INET column read back as text
+#[derive(Debug, sqlx::FromRow)]
+struct LoginEventRow {
+    id: Uuid,
+    user_id: Option<Uuid>,
+    email: String,
+    succeeded: bool,
+    failure_reason: Option<String>,
+    ip_address_str: Option<String>,
+    user_agent: Option<String>,
+    app_type: Option<String>,
+    app_device: Option<String>,
+    request_id: Option<String>,
+    country: Option<String>,
+    region: Option<String>,
+    city: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<LoginEventRow> for LoginEvent {
+    fn from(row: LoginEventRow) -> Self {
+        let located = row.country.is_some()
+            || row.region.is_some()
+            || row.city.is_some()
+            || row.latitude.is_some()
+            || row.longitude.is_some();
+        LoginEvent {
+            id: row.id,
+            user_id: row.user_id,
+            email: row.email,
+            succeeded: row.succeeded,
+            failure_reason: row.failure_reason.as_deref().and_then(LoginFailureReason::parse),
+            ip_address: row.ip_address_str.and_then(|ip| ip.parse().ok()),
+            user_agent: row.user_agent,
+            app_type: row.app_type,
+            app_device: row.app_device,
+            request_id: row.request_id,
+            geo: located.then_some(GeoLocation {
+                country: row.country,
+                region: row.region,
+                city: row.city,
+                latitude: row.latitude,
+                longitude: row.longitude,
+            }),
+            created_at: row.created_at,
+        }
+    }
+}
+
+pub struct LoginEventRepositoryImpl {
+    database_service: Arc<DatabaseService>,
+}
+
+impl LoginEventRepositoryImpl {
+    pub fn new(database_service: Arc<DatabaseService>) -> Self {
+        Self { database_service }
+    }
+}
+
+#[async_trait]
+impl LoginEventRepository for LoginEventRepositoryImpl {
+    async fn create(&self, event: LoginEvent) -> AppResult<LoginEvent> {
+        let location = concat!(file!(), ":", line!());
+        let geo = event.geo.clone().unwrap_or_default();
+        let row: LoginEventRow = sqlx::query_as(&format!(
+            "INSERT INTO login_events (
+                id, user_id, email, succeeded, failure_reason, ip_address, user_agent,
+                app_type, app_device, request_id, country, region, city, latitude, longitude, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6::text::inet, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            RETURNING {}",
+            LOGIN_EVENT_COLUMNS
+        ))
+        .bind(event.id)
+        .bind(event.user_id)
+        .bind(&event.email)
+        .bind(event.succeeded)
+        .bind(event.failure_reason.map(|reason| reason.as_str()))
+        .bind(event.ip_address.map(|ip| ip.to_string()))
+        .bind(&event.user_agent)
+        .bind(&event.app_type)
+        .bind(&event.app_device)
+        .bind(&event.request_id)
+        .bind(geo.country)
+        .bind(geo.region)
+        .bind(geo.city)
+        .bind(geo.latitude)
+        .bind(geo.longitude)
+        .bind(event.created_at)
+        .fetch_one(self.database_service.pool())
+        .await
+        .map_err(|e| {
+            let err = crate::shared::AppError::Database(e);
+            err.log_with_operation(location, "login_event_repository.create");
+            err
+        })?;
+        Ok(row.into())
+    }
+
+    async fn list_page(&self, query: &ListQuery) -> AppResult<Page<LoginEvent>> {
+        let location = concat!(file!(), ":", line!());
+        let page: Page<LoginEventRow> = fetch_page(
+            self.database_service.pool(),
+            "login_events",
+            LOGIN_EVENT_COLUMNS,
+            None,
+            query,
+            &LOGIN_EVENT_LIST_SPEC,
+        )
+        .await
+        .map_err(|e| {
+            e.log_with_operation(location, "login_event_repository.list_page");
+            e
+        })?;
+        Ok(page.map(LoginEvent::from))
+    }
+
+    async fn recent_successes(&self, user_id: Uuid, since: DateTime<Utc>, limit: u32) -> AppResult<Vec<LoginEvent>> {
+        let location = concat!(file!(), ":", line!());
+        let rows: Vec<LoginEventRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM login_events
+            WHERE user_id = $1 AND succeeded AND created_at >= $2
+            ORDER BY created_at DESC
+            LIMIT $3",
+            LOGIN_EVENT_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(since)
+        .bind(limit as i64)
+        .fetch_all(self.database_service.pool())
+        .await
+        .map_err(|e| {
+            let err = crate::shared::AppError::Database(e);
+            err.log_with_operation(location, "login_event_repository.recent_successes");
+            err
+        })?;
+        Ok(rows.into_iter().map(LoginEvent::from).collect())
+    }
+}
@@ -9,6 +9,8 @@ pub mod group_repository_impl;
 pub mod ui_entity_repository_impl;
 pub mod session_repository_impl;
 pub mod request_log_repository_impl;
+pub mod login_event_repository_impl;
+pub mod api_key_repository_impl;
 
 pub use user_repository_impl::UserRepositoryImpl;
 pub use key_repository_impl::KeyRepositoryImpl;
@@ -21,4 +23,6 @@ pub use group_repository_impl::GroupRepositoryImpl;
 pub use ui_entity_repository_impl::UiEntityRepositoryImpl;
 pub use session_repository_impl::SessionRepositoryImpl;
 pub use request_log_repository_impl::RequestLogRepositoryImpl;
+pub use login_event_repository_impl::LoginEventRepositoryImpl;
+pub use api_key_repository_impl::ApiKeyRepositoryImpl;
 
@@ -1,6 +1,7 @@
 use crate::domain::entities::Permission;
-use crate::domain::repositories::PermissionRepository;
-use crate::shared::AppResult;
+use crate::domain::repositories::{PermissionRepository, PERMISSION_LIST_SPEC};
+use crate::infrastructure::database::{fetch_page, stale_update_error};
+use crate::shared::{AppResult, ListQuery, Page};
 use async_trait::async_trait;
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -37,6 +38,49 @@ impl PermissionRepository for PermissionRepositoryImpl {
         .map_err(|e| crate::shared::AppError::Database(e))
     }
 
+    async fn update(&self, mut permission: Permission) -> AppResult<Permission> {
+        // Store current version for optimistic locking
+        let current_version = permission.version;
+        permission.version += 1;
+
+        let row = sqlx::query_as!(
+            Permission,
+            r#"
+            UPDATE permissions
+            SET name = $2, resource = $3, action = $4, description = $5,
+                updated_at = $6, request_id = $7, updated_by = $8, version = $9
+            WHERE id = $1 AND version = $10
+            RETURNING id, name, resource, action, description, request_id, created_at, updated_at, created_by, updated_by, system_id, version
+            "#,
+            permission.id,
+            permission.name,
+            permission.resource,
+            permission.action,
+            permission.description,
+            permission.updated_at,
+            permission.request_id,
+            permission.updated_by,
+            permission.version,
+            current_version
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| crate::shared::AppError::Database(e))?;
+
+        match row {
+            Some(permission) => Ok(permission),
+            None => Err(stale_update_error(
+                &self.pool,
+                "permissions",
+                None,
+                "Permission",
+                permission.id,
+                current_version,
+            )
+            .await),
+        }
+    }
+
     async fn find_by_id(&self, id: Uuid) -> AppResult<Option<Permission>> {
         sqlx::query_as!(
             Permission,
@@ -52,6 +96,22 @@ impl PermissionRepository for PermissionRepositoryImpl {
         .map_err(|e| crate::shared::AppError::Database(e))
     }
 
+    async fn find_by_ids(&self, ids: &[Uuid]) -> AppResult<Vec<Permission>> {
+        sqlx::query_as!(
+            Permission,
+            r#"
+            SELECT id, name, resource, action, description, request_id, created_at, updated_at, created_by, updated_by, system_id, version
+            FROM permissions
+            WHERE id = ANY($1)
+            ORDER BY resource, action
+            "#,
+            ids
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| crate::shared::AppError::Database(e))
+    }
+
     async fn find_by_name(&self, name: &str) -> AppResult<Option<Permission>> {
         sqlx::query_as!(
             Permission,
@@ -97,6 +157,19 @@ impl PermissionRepository for PermissionRepositoryImpl {
         .map_err(|e| crate::shared::AppError::Database(e))
     }
 
+    async fn list_page(&self, query: &ListQuery) -> AppResult<Page<Permission>> {
+        fetch_page(
+            &self.pool,
+            "permissions",
+            "id, name, resource, action, description, request_id, created_at, updated_at, \
+             created_by, updated_by, system_id, version",
+            None,
+            query,
+            &PERMISSION_LIST_SPEC,
+        )
+        .await
+    }
+
     async fn list_by_resource(&self, resource: &str) -> AppResult<Vec<Permission>> {
         sqlx::query_as!(
             Permission,
@@ -1,7 +1,9 @@
 use crate::domain::entities::Relationship;
-use crate::domain::repositories::RelationshipRepository;
+use crate::domain::repositories::{RelationshipFilter, RelationshipRepository, SnapshotPoint};
+use crate::domain::value_objects::ConsistencyToken;
 use crate::shared::AppResult;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -266,6 +268,258 @@ impl RelationshipRepository for RelationshipRepositoryImpl {
         .await
         .map_err(|e| crate::shared::AppError::Database(e))
     }
+
+    async fn list_as_of(&self, point: &SnapshotPoint) -> AppResult<Vec<Relationship>> {
+        if let SnapshotPoint::Token(token) = point {
+            if token.as_str().parse::<u64>().is_err() {
+                return Err(crate::shared::AppError::Validation(format!("Invalid consistency token: {}", token)));
+            }
+        }
+
+        // Latest change per tuple at the point; see 0051_create_relationship_history
+        let mut query = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+            r#"
+            SELECT relationship_id AS id, "user", relation, object, organization_id, created_at,
+                   valid_from, expires_at, is_active, '{}'::jsonb AS metadata, deleted_at,
+                   NULL::uuid AS deleted_by, NULL::text AS request_id, changed_at AS updated_at,
+                   NULL::uuid AS created_by, NULL::uuid AS updated_by, NULL::text AS system_id,
+                   1::bigint AS version
+            FROM (
+                SELECT DISTINCT ON ("user", relation, object, organization_id) *
+                FROM relationship_history
+                WHERE "#,
+        );
+        match point {
+            SnapshotPoint::At(at) => query.push("changed_at <= ").push_bind(*at),
+            SnapshotPoint::Token(token) => query.push("txid <= ").push_bind(token.as_str()).push("::xid8"),
+        };
+        query.push(
+            r#"
+                ORDER BY "user", relation, object, organization_id, changed_at DESC, id DESC
+            ) latest
+            WHERE operation <> 'delete' AND deleted_at IS NULL"#,
+        );
+
+        query
+            .build_query_as::<Relationship>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| crate::shared::AppError::Database(e))
+    }
+
+    async fn find_existing_tuples(&self, keys: &[(String, String, String)]) -> AppResult<Vec<(String, String, String)>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let users: Vec<&str> = keys.iter().map(|(user, _, _)| user.as_str()).collect();
+        let relations: Vec<&str> = keys.iter().map(|(_, relation, _)| relation.as_str()).collect();
+        let objects: Vec<&str> = keys.iter().map(|(_, _, object)| object.as_str()).collect();
+        sqlx::query_as(
+            r#"
+            SELECT DISTINCT r."user", r.relation, r.object
+            FROM relationships r
+            JOIN UNNEST($1::text[], $2::text[], $3::text[]) AS k("user", relation, object)
+              ON r."user" = k."user" AND r.relation = k.relation AND r.object = k.object
+            WHERE r.deleted_at IS NULL
+            "#,
+        )
+        .bind(users)
+        .bind(relations)
+        .bind(objects)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| crate::shared::AppError::Database(e))
+    }
+
+    async fn find_by_filter(&self, filter: &RelationshipFilter) -> AppResult<Vec<Relationship>> {
+        if filter.subject.is_none() && filter.object.is_none() {
+            return Err(crate::shared::AppError::Validation(
+                "Relationship filter needs a subject or an object".to_string(),
+            ));
+        }
+
+        let mut query = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+            r#"
+            SELECT id, "user", relation, object, organization_id, created_at, valid_from, expires_at, 
+                   is_active, metadata, deleted_at, deleted_by, request_id, updated_at, 
+                   created_by, updated_by, system_id, version
+            FROM relationships
+            WHERE deleted_at IS NULL"#,
+        );
+        if let Some(subject) = &filter.subject {
+            query.push(r#" AND "user" = "#).push_bind(subject);
+        }
+        if let Some(object) = &filter.object {
+            query.push(" AND object = ").push_bind(object);
+        }
+        if let Some(relation) = &filter.relation {
+            query.push(" AND relation = ").push_bind(relation);
+        }
+        if !filter.include_invalid {
+            query.push(
+                " AND is_active = true \
+                 AND (valid_from IS NULL OR valid_from <= NOW()) \
+                 AND (expires_at IS NULL OR expires_at > NOW())",
+            );
+        }
+        // Order matches idx_relationships_subject_page / idx_relationships_object_page
+        if filter.subject.is_some() {
+            query.push(" ORDER BY relation, object, id");
+        } else {
+            query.push(r#" ORDER BY relation, "user", id"#);
+        }
+        query
+            .push(" LIMIT ")
+            .push_bind(filter.effective_limit() as i64)
+            .push(" OFFSET ")
+            .push_bind(filter.offset as i64);
+
+        query
+            .build_query_as::<Relationship>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::shared::AppError::Database)
+    }
+
+    async fn find_upcoming(&self, until: DateTime<Utc>, limit: u32) -> AppResult<Vec<Relationship>> {
+        sqlx::query_as::<_, Relationship>(
+            r#"
+            SELECT id, "user", relation, object, organization_id, created_at, valid_from, expires_at, 
+                   is_active, metadata, deleted_at, deleted_by, request_id, updated_at, 
+                   created_by, updated_by, system_id, version
+            FROM relationships
+            WHERE deleted_at IS NULL AND is_active = true
+            AND valid_from > NOW() AND valid_from <= $1
+            ORDER BY valid_from, id
+            LIMIT $2
+            "#,
+        )
+        .bind(until)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::shared::AppError::Database)
+    }
+
+    async fn find_expiring(&self, until: DateTime<Utc>, limit: u32) -> AppResult<Vec<Relationship>> {
+        sqlx::query_as::<_, Relationship>(
+            r#"
+            SELECT id, "user", relation, object, organization_id, created_at, valid_from, expires_at, 
+                   is_active, metadata, deleted_at, deleted_by, request_id, updated_at, 
+                   created_by, updated_by, system_id, version
+            FROM relationships
+            WHERE deleted_at IS NULL AND is_active = true
+            AND expires_at > NOW() AND expires_at <= $1
+            ORDER BY expires_at, id
+            LIMIT $2
+            "#,
+        )
+        .bind(until)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::shared::AppError::Database)
+    }
+
+    async fn find_with_encrypted_metadata(&self, after: Option<Uuid>, limit: u32) -> AppResult<Vec<Relationship>> {
+        sqlx::query_as::<_, Relationship>(
+            r#"
+            SELECT id, "user", relation, object, organization_id, created_at, valid_from, expires_at, 
+                   is_active, metadata, deleted_at, deleted_by, request_id, updated_at, 
+                   created_by, updated_by, system_id, version
+            FROM relationships
+            WHERE metadata->>'_encrypted' = 'true'
+            AND ($1::uuid IS NULL OR id > $1)
+            ORDER BY id
+            LIMIT $2
+            "#,
+        )
+        .bind(after)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::shared::AppError::Database)
+    }
+
+    async fn apply_batch(
+        &self,
+        writes: Vec<Relationship>,
+        deletes: Vec<(String, String, String)>,
+        deleted_by: Option<Uuid>,
+    ) -> AppResult<ConsistencyToken> {
+        let mut tx = self.pool.begin().await.map_err(crate::shared::AppError::Database)?;
+
+        // Deletes first, so a batch that removes and re-adds a tuple keeps it
+        for (user, relation, object) in &deletes {
+            sqlx::query!(
+                r#"
+                UPDATE relationships
+                SET deleted_at = NOW(),
+                    deleted_by = $4,
+                    is_active = false,
+                    updated_at = NOW(),
+                    version = version + 1
+                WHERE "user" = $1 AND relation = $2 AND object = $3
+                AND deleted_at IS NULL
+                "#,
+                user,
+                relation,
+                object,
+                deleted_by
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(crate::shared::AppError::Database)?;
+        }
+
+        for relationship in writes {
+            sqlx::query!(
+                r#"
+                INSERT INTO relationships (
+                    id, "user", relation, object, organization_id, created_at, valid_from, expires_at, 
+                    is_active, metadata, deleted_at, deleted_by, request_id, updated_at, 
+                    created_by, updated_by, system_id, version
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+                ON CONFLICT ("user", relation, object, organization_id) 
+                WHERE deleted_at IS NULL
+                DO UPDATE SET 
+                    id = EXCLUDED.id,
+                    updated_at = EXCLUDED.updated_at,
+                    version = relationships.version + 1
+                "#,
+                relationship.id,
+                relationship.user,
+                relationship.relation,
+                relationship.object,
+                relationship.organization_id,
+                relationship.created_at,
+                relationship.valid_from,
+                relationship.expires_at,
+                relationship.is_active,
+                relationship.metadata,
+                relationship.deleted_at,
+                relationship.deleted_by,
+                relationship.request_id,
+                relationship.updated_at,
+                relationship.created_by,
+                relationship.updated_by,
+                relationship.system_id,
+                relationship.version
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(crate::shared::AppError::Database)?;
+        }
+
+        let token = sqlx::query_scalar!(r#"SELECT pg_current_xact_id()::text AS "token!""#)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(crate::shared::AppError::Database)?;
+        tx.commit().await.map_err(crate::shared::AppError::Database)?;
+
+        Ok(ConsistencyToken::new(token))
+    }
     
     async fn find_by_user_and_org(&self, user: &str, organization_id: Uuid) -> AppResult<Vec<Relationship>> {
         sqlx::query_as!(
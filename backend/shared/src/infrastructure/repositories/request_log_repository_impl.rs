@@ -1,7 +1,7 @@
 use crate::domain::entities::RequestLog;
-use crate::domain::repositories::RequestLogRepository;
-use crate::infrastructure::database::DatabaseService;
-use crate::shared::AppResult;
+use crate::domain::repositories::{RequestLogRepository, REQUEST_LOG_LIST_SPEC};
+use crate::infrastructure::database::{fetch_page, DatabaseService};
+use crate::shared::{AppResult, ListQuery, Page};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use std::sync::Arc;
@@ -9,7 +9,7 @@ use uuid::Uuid;
 
 /// Temporary struct for database deserialization (with ip_address as String)
 /// Note: Field names must match SQL aliases exactly for compile-time macros
-#[derive(Debug)]
+#[derive(Debug, sqlx::FromRow)]
 struct RequestLogRow {
     id: Uuid,
     session_id: Uuid,
@@ -164,5 +164,25 @@ impl RequestLogRepository for RequestLogRepositoryImpl {
         })?;
         Ok(row.map(|r| r.into()))
     }
+
+    async fn list_page(&self, query: &ListQuery) -> AppResult<Page<RequestLog>> {
+        let location = concat!(file!(), ":", line!());
+        let page: Page<RequestLogRow> = fetch_page(
+            self.database_service.pool(),
+            "request_logs",
+            "id, session_id, request_id, user_id, method, path, query_string, \
+             ip_address::text as ip_address_str, user_agent, status_code, response_time_ms, \
+             request_size_bytes, response_size_bytes, created_at, metadata",
+            None,
+            query,
+            &REQUEST_LOG_LIST_SPEC,
+        )
+        .await
+        .map_err(|e| {
+            e.log_with_operation(location, "request_log_repository.list_page");
+            e
+        })?;
+        Ok(page.map(RequestLog::from))
+    }
 }
 
@@ -1,12 +1,29 @@
 use crate::domain::entities::Role;
-use crate::domain::repositories::{RoleRepository, PermissionRepository};
-use crate::infrastructure::database::DatabaseService;
-use crate::infrastructure::zanzibar::RelationshipStore;
-use crate::shared::AppResult;
+use crate::domain::repositories::{RelationshipFilter, RoleRepository, PermissionRepository, ROLE_LIST_SPEC};
+use crate::domain::repositories::relationship_repository::RELATIONSHIP_PAGE_MAX;
+use crate::infrastructure::database::{fetch_page, stale_update_error, DatabaseService};
+use crate::infrastructure::zanzibar::{RelationshipStore, RelationshipTuple};
+use crate::shared::{AppResult, ListQuery, Page};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// Role columns; permissions are loaded separately from relationships
+#[derive(Debug, sqlx::FromRow)]
+struct RoleRow {
+    id: Uuid,
+    name: String,
+    description: Option<String>,
+    request_id: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    created_by: Option<Uuid>,
+    updated_by: Option<Uuid>,
+    system_id: Option<String>,
+    version: i64,
+}
+
 pub struct RoleRepositoryImpl {
     database_service: Arc<DatabaseService>,
     relationship_store: Arc<RelationshipStore>,
@@ -73,6 +90,87 @@ impl RoleRepository for RoleRepositoryImpl {
         self.find_by_id(role_id).await.map(|r| r.unwrap())
     }
 
+    async fn update(&self, mut role: Role) -> AppResult<Role> {
+        // Store current version for optimistic locking
+        let current_version = role.version;
+        role.version += 1;
+
+        let row = sqlx::query!(
+            r#"
+            UPDATE roles
+            SET name = $2, description = $3, updated_at = $4, request_id = $5,
+                updated_by = $6, version = $7
+            WHERE id = $1 AND version = $8
+            RETURNING updated_at, version
+            "#,
+            role.id,
+            role.name,
+            role.description,
+            role.updated_at,
+            role.request_id,
+            role.updated_by,
+            role.version,
+            current_version
+        )
+        .fetch_optional(self.database_service.pool())
+        .await
+        .map_err(|e| crate::shared::AppError::Database(e))?;
+
+        match row {
+            Some(row) => {
+                role.updated_at = row.updated_at;
+                role.version = row.version;
+                Ok(role)
+            }
+            None => Err(stale_update_error(
+                self.database_service.pool(),
+                "roles",
+                None,
+                "Role",
+                role.id,
+                current_version,
+            )
+            .await),
+        }
+    }
+
+    async fn delete(&self, id: Uuid) -> AppResult<()> {
+        let location = concat!(file!(), ":", line!());
+        let name: Option<String> = sqlx::query_scalar("DELETE FROM roles WHERE id = $1 RETURNING name")
+            .bind(id)
+            .fetch_optional(self.database_service.pool())
+            .await
+            .map_err(|e| {
+                let err = crate::shared::AppError::Database(e);
+                err.log_with_operation(location, "role_repository.delete");
+                err
+            })?;
+        let name = name.ok_or_else(|| crate::shared::AppError::NotFound(format!("Role {} not found", id)))?;
+
+        // Grants (role:{name}#{action}@resource:..) and holders (..@role:{name})
+        let role_str = format!("role:{}", name);
+        let mut tuples = Vec::new();
+        for filter in [RelationshipFilter::for_subject(&role_str), RelationshipFilter::for_object(&role_str)] {
+            let mut offset = 0;
+            loop {
+                let page = self
+                    .relationship_store
+                    .read_relationships(&filter.clone().page(RELATIONSHIP_PAGE_MAX, offset))
+                    .await?;
+                let count = page.len() as u32;
+                tuples.extend(page.into_iter().map(|r| RelationshipTuple::new(r.user, r.relation, r.object)));
+                if count < RELATIONSHIP_PAGE_MAX {
+                    break;
+                }
+                offset += count;
+            }
+        }
+        if !tuples.is_empty() {
+            self.relationship_store.delete_tuples(&tuples).await?;
+        }
+        Ok(())
+    }
+
     async fn find_by_id(&self, id: Uuid) -> AppResult<Option<Role>> {
         // Use query_as with FromRow - but we need to handle permissions separately
         // Since permissions are stored in a separate table, we'll fetch role first then permissions
@@ -177,6 +275,38 @@ impl RoleRepository for RoleRepositoryImpl {
         Ok(roles)
     }
 
+    async fn list_page(&self, query: &ListQuery) -> AppResult<Page<Role>> {
+        let page: Page<RoleRow> = fetch_page(
+            self.database_service.pool(),
+            "roles",
+            "id, name, description, request_id, created_at, updated_at, \
+             created_by, updated_by, system_id, version",
+            None,
+            query,
+            &ROLE_LIST_SPEC,
+        )
+        .await?;
+
+        let mut roles = Vec::with_capacity(page.items.len());
+        for row in page.items {
+            let permissions = self.get_role_permissions(row.id).await?;
+            roles.push(Role {
+                id: row.id,
+                name: row.name,
+                description: row.description,
+                permissions,
+                request_id: row.request_id,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+                created_by: row.created_by,
+                updated_by: row.updated_by,
+                system_id: row.system_id,
+                version: row.version,
+            });
+        }
+        Ok(Page { items: roles, next_cursor: page.next_cursor, total: page.total })
+    }
+
     async fn add_permission_to_role(&self, role_id: Uuid, permission_id: Uuid) -> AppResult<()> {
         // Get role and permission details
         let role = self.find_by_id(role_id).await?
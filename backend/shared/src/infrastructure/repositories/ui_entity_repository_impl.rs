@@ -1,4 +1,4 @@
-use crate::domain::entities::{UiPage, UiButton, UiField, UiApiEndpoint};
+use crate::domain::entities::{UiPage, UiButton, UiField, UiApiEndpoint, UiEntityKind, UiEntityVersion};
 use crate::domain::repositories::UiEntityRepository;
 use crate::infrastructure::database::queries::ui_entities::*;
 use crate::shared::AppResult;
@@ -330,5 +330,32 @@ impl UiEntityRepository for UiEntityRepositoryImpl {
             .map_err(|e| crate::shared::AppError::Database(e))?;
         Ok(())
     }
+
+    // Version history
+    async fn list_versions(&self, kind: UiEntityKind, entity_id: Uuid) -> AppResult<Vec<UiEntityVersion>> {
+        sqlx::query_as::<_, UiEntityVersion>(UI_ENTITY_VERSION_LIST)
+            .bind(kind.as_str())
+            .bind(entity_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| crate::shared::AppError::Database(e))
+    }
+
+    async fn visibility_stamp(&self) -> AppResult<String> {
+        sqlx::query_scalar::<_, String>(UI_VISIBILITY_STAMP)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| crate::shared::AppError::Database(e))
+    }
+
+    async fn find_version(&self, kind: UiEntityKind, entity_id: Uuid, version: i64) -> AppResult<Option<UiEntityVersion>> {
+        sqlx::query_as::<_, UiEntityVersion>(UI_ENTITY_VERSION_FIND)
+            .bind(kind.as_str())
+            .bind(entity_id)
+            .bind(version)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| crate::shared::AppError::Database(e))
+    }
 }
 
@@ -1,7 +1,7 @@
 use crate::domain::entities::User;
-use crate::domain::repositories::UserRepository;
-use crate::infrastructure::database::DatabaseService;
-use crate::shared::AppResult;
+use crate::domain::repositories::{UserRepository, USER_LIST_SPEC};
+use crate::infrastructure::database::{fetch_page, stale_update_error, DatabaseService};
+use crate::shared::{AppResult, ListQuery, Page};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use sqlx::FromRow;
@@ -18,6 +18,8 @@ struct UserRow {
     is_active: bool,
     is_verified: bool,
     is_super_user: bool,
+    is_service_account: bool,
+    external_id: Option<String>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
     last_login: Option<DateTime<Utc>>,
@@ -39,6 +41,8 @@ impl From<UserRow> for User {
             is_active: row.is_active,
             is_verified: row.is_verified,
             is_super_user: row.is_super_user,
+            is_service_account: row.is_service_account,
+            external_id: row.external_id,
             organization_id: row.organization_id,
             last_login: row.last_login,
             request_id: row.request_id,
@@ -72,11 +76,11 @@ impl UserRepository for UserRepositoryImpl {
             INSERT INTO users (
                 id, email, username, password_hash, is_active, is_verified, is_super_user, 
                 organization_id, created_at, updated_at, last_login,
-                request_id, created_by, updated_by, system_id, version
+                request_id, created_by, updated_by, system_id, version, is_service_account, external_id
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
             RETURNING 
-                id, email, username, password_hash, is_active, is_verified, is_super_user, 
+                id, email, username, password_hash, is_active, is_verified, is_super_user, is_service_account, external_id,
                 created_at, updated_at, last_login, organization_id, request_id,
                 created_by, updated_by, system_id, version
             "#,
@@ -95,7 +99,9 @@ impl UserRepository for UserRepositoryImpl {
             user.created_by,
             user.updated_by,
             user.system_id,
-            user.version
+            user.version,
+            user.is_service_account,
+            user.external_id
         )
         .fetch_one(self.database_service.pool())
         .await
@@ -112,7 +118,7 @@ impl UserRepository for UserRepositoryImpl {
         let row = sqlx::query_as!(
             UserRow,
             r#"
-            SELECT id, email, username, password_hash, is_active, is_verified, is_super_user, 
+            SELECT id, email, username, password_hash, is_active, is_verified, is_super_user, is_service_account, external_id,
                    created_at, updated_at, last_login, organization_id, request_id,
                    created_by, updated_by, system_id, version
             FROM users
@@ -136,7 +142,7 @@ impl UserRepository for UserRepositoryImpl {
             UserRow,
             r#"
             SELECT 
-                id, email, username, password_hash, is_active, is_verified, is_super_user, 
+                id, email, username, password_hash, is_active, is_verified, is_super_user, is_service_account, external_id,
                 created_at, updated_at, last_login, organization_id, request_id,
                 created_by, updated_by, system_id, version
             FROM users
@@ -159,7 +165,7 @@ impl UserRepository for UserRepositoryImpl {
             UserRow,
             r#"
             SELECT 
-                id, email, username, password_hash, is_active, is_verified, is_super_user, 
+                id, email, username, password_hash, is_active, is_verified, is_super_user, is_service_account, external_id,
                 created_at, updated_at, last_login, organization_id, request_id,
                 created_by, updated_by, system_id, version
             FROM users
@@ -173,22 +179,42 @@ impl UserRepository for UserRepositoryImpl {
         Ok(row.map(|r| r.into()))
     }
 
+    async fn find_by_external_id(&self, external_id: &str) -> AppResult<Option<User>> {
+        let row = sqlx::query_as!(
+            UserRow,
+            r#"
+            SELECT 
+                id, email, username, password_hash, is_active, is_verified, is_super_user, is_service_account, external_id,
+                created_at, updated_at, last_login, organization_id, request_id,
+                created_by, updated_by, system_id, version
+            FROM users
+            WHERE external_id = $1
+            "#,
+            external_id
+        )
+        .fetch_optional(self.database_service.pool())
+        .await
+        .map_err(|e| crate::shared::AppError::Database(e))?;
+        Ok(row.map(|r| r.into()))
+    }
+
     async fn update(&self, mut user: User) -> AppResult<User> {
         // Store current version for optimistic locking
         let current_version = user.version;
         // Increment version for update
         user.version += 1;
         
-        let row: UserRow = sqlx::query_as!(
+        let row: Option<UserRow> = sqlx::query_as!(
             UserRow,
             r#"
             UPDATE users
             SET email = $2, username = $3, password_hash = $4, is_active = $5, is_verified = $6, 
                 is_super_user = $7, organization_id = $8, updated_at = $9, last_login = $10,
-                request_id = $11, updated_by = $12, version = $13
+                request_id = $11, updated_by = $12, version = $13, is_service_account = $15,
+                external_id = $16
             WHERE id = $1 AND version = $14
             RETURNING 
-                id, email, username, password_hash, is_active, is_verified, is_super_user, 
+                id, email, username, password_hash, is_active, is_verified, is_super_user, is_service_account, external_id,
                 created_at, updated_at, last_login, organization_id, request_id,
                 created_by, updated_by, system_id, version
             "#,
@@ -205,12 +231,26 @@ impl UserRepository for UserRepositoryImpl {
             user.request_id,
             user.updated_by,
             user.version, // New incremented version
-            current_version // Current version for WHERE clause (optimistic locking)
+            current_version, // Current version for WHERE clause (optimistic locking)
+            user.is_service_account,
+            user.external_id
         )
-        .fetch_one(self.database_service.pool())
+        .fetch_optional(self.database_service.pool())
         .await
         .map_err(|e| crate::shared::AppError::Database(e))?;
-        Ok(row.into())
+
+        match row {
+            Some(row) => Ok(row.into()),
+            None => Err(stale_update_error(
+                self.database_service.pool(),
+                "users",
+                None,
+                "User",
+                user.id,
+                current_version,
+            )
+            .await),
+        }
     }
 
     async fn delete(&self, id: Uuid) -> AppResult<()> {
@@ -235,7 +275,7 @@ impl UserRepository for UserRepositoryImpl {
             UserRow,
             r#"
             SELECT 
-                id, email, username, password_hash, is_active, is_verified, is_super_user, 
+                id, email, username, password_hash, is_active, is_verified, is_super_user, is_service_account, external_id,
                 created_at, updated_at, last_login, organization_id, request_id,
                 created_by, updated_by, system_id, version
             FROM users
@@ -250,5 +290,20 @@ impl UserRepository for UserRepositoryImpl {
         .map_err(|e| crate::shared::AppError::Database(e))?;
         Ok(rows.into_iter().map(|r| r.into()).collect())
     }
+
+    async fn list_page(&self, query: &ListQuery) -> AppResult<Page<User>> {
+        let page: Page<UserRow> = fetch_page(
+            self.database_service.pool(),
+            "users",
+            "id, email, username, password_hash, is_active, is_verified, is_super_user, is_service_account, external_id, \
+             created_at, updated_at, last_login, organization_id, request_id, \
+             created_by, updated_by, system_id, version",
+            None,
+            query,
+            &USER_LIST_SPEC,
+        )
+        .await?;
+        Ok(page.map(User::from))
+    }
 }
 
@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Where the IdP metadata is read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataSource {
+    Url(String),
+    File(PathBuf),
+}
+
+/// Service provider settings, read from `SAML_*` variables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SamlConfig {
+    /// Our entity id, the audience IdP assertions must be addressed to
+    pub entity_id: String,
+    /// Assertion Consumer Service URL the IdP posts responses to
+    pub acs_url: String,
+    pub idp_metadata: MetadataSource,
+    /// Attribute holding the user's email; the NameID is used when unset
+    pub email_attribute: Option<String>,
+    /// How long an AuthnRequest may be answered
+    pub request_ttl: Duration,
+    /// Where the browser goes after login when no relay state was given
+    pub default_redirect: String,
+}
+
+impl SamlConfig {
+    /// `None` unless SAML is configured (`SAML_SP_ENTITY_ID` is set)
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let entity_id = var("SAML_SP_ENTITY_ID")?;
+        let idp_metadata = match (var("SAML_IDP_METADATA_URL"), var("SAML_IDP_METADATA_FILE")) {
+            (Some(url), _) => MetadataSource::Url(url),
+            (None, Some(path)) => MetadataSource::File(PathBuf::from(path)),
+            (None, None) => {
+                tracing::warn!("SAML_SP_ENTITY_ID is set but no IdP metadata is; SAML login is disabled");
+                return None;
+            }
+        };
+        let Some(acs_url) = var("SAML_ACS_URL") else {
+            tracing::warn!("SAML_SP_ENTITY_ID is set but SAML_ACS_URL is not; SAML login is disabled");
+            return None;
+        };
+        Some(Self {
+            entity_id,
+            acs_url,
+            idp_metadata,
+            email_attribute: var("SAML_EMAIL_ATTRIBUTE"),
+            request_ttl: Duration::from_secs(
+                var("SAML_REQUEST_TTL_SECS").and_then(|v| v.parse().ok()).unwrap_or(300),
+            ),
+            default_redirect: var("SAML_DEFAULT_REDIRECT")
+                .and_then(|v| local_redirect(&v))
+                .unwrap_or_else(|| "/".to_string()),
+        })
+    }
+}
+
+/// `path` if it is safe to redirect to: a local absolute path, never another host
+pub fn local_redirect(path: &str) -> Option<String> {
+    let safe = path.starts_with('/')
+        && !path.starts_with("//")
+        && !path.starts_with("/\\")
+        && path.len() <= 2048
+        && !path.chars().any(|c| c.is_control());
+    safe.then(|| path.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_redirect() {
+        assert_eq!(local_redirect("/dashboard?tab=1").as_deref(), Some("/dashboard?tab=1"));
+        for unsafe_path in ["https://evil.example", "//evil.example", "/\\evil.example", "dashboard", "/a\r\nb", ""] {
+            assert_eq!(local_redirect(unsafe_path), None, "{:?}", unsafe_path);
+        }
+    }
+}
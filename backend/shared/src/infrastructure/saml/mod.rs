@@ -0,0 +1,15 @@
+//! SAML 2.0 service provider (SP-initiated, HTTP-Redirect AuthnRequest and
+//! HTTP-POST response)
+//!
+//! Responses must be signed by a key in the IdP metadata, answer a request
+//! we issued (`InResponseTo`) and carry an assertion id not seen before.
+
+pub mod config;
+pub mod replay;
+#[cfg(feature = "saml")]
+pub mod service_provider;
+
+pub use config::{MetadataSource, SamlConfig};
+pub use replay::{PendingRequest, SamlReplayStore};
+#[cfg(feature = "saml")]
+pub use service_provider::{SamlIdentity, SamlLogin, SamlServiceProvider};
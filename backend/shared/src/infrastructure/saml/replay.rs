@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use crate::shared::AppResult;
+
+/// An AuthnRequest awaiting its response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingRequest {
+    pub relay_state: Option<String>,
+    pub app_type: Option<String>,
+}
+
+/// Outstanding request ids and consumed assertion ids, shared by all instances
+pub struct SamlReplayStore {
+    pool: PgPool,
+}
+
+impl SamlReplayStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn record_request(
+        &self,
+        id: &str,
+        pending: &PendingRequest,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            "INSERT INTO saml_requests (id, relay_state, app_type, expires_at) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(id)
+        .bind(&pending.relay_state)
+        .bind(&pending.app_type)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Whether `id` is an unexpired request we issued
+    pub async fn is_pending(&self, id: &str) -> AppResult<bool> {
+        let row: Option<(String,)> =
+            sqlx::query_as("SELECT id FROM saml_requests WHERE id = $1 AND expires_at > NOW()")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(row.is_some())
+    }
+
+    /// Consume a request; `None` if it expired or was already answered
+    pub async fn take_request(&self, id: &str) -> AppResult<Option<PendingRequest>> {
+        let row: Option<(Option<String>, Option<String>)> = sqlx::query_as(
+            "DELETE FROM saml_requests WHERE id = $1 AND expires_at > NOW() RETURNING relay_state, app_type",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|(relay_state, app_type)| PendingRequest { relay_state, app_type }))
+    }
+
+    /// Record an assertion as used; `false` if it already was (a replay)
+    pub async fn record_assertion(&self, id: &str, expires_at: DateTime<Utc>) -> AppResult<bool> {
+        let result = sqlx::query(
+            "INSERT INTO saml_assertions (id, expires_at) VALUES ($1, $2) ON CONFLICT (id) DO NOTHING",
+        )
+        .bind(id)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Drop expired requests and assertions that can no longer be replayed
+    pub async fn prune(&self) -> AppResult<u64> {
+        let requests = sqlx::query("DELETE FROM saml_requests WHERE expires_at <= NOW()")
+            .execute(&self.pool)
+            .await?;
+        let assertions = sqlx::query("DELETE FROM saml_assertions WHERE expires_at <= NOW()")
+            .execute(&self.pool)
+            .await?;
+        Ok(requests.rows_affected() + assertions.rows_affected())
+    }
+}
@@ -0,0 +1,261 @@
+//! Service provider built on samael
+//!
+//! Issues AuthnRequests for the HTTP-Redirect binding and validates the
+//! IdP's POSTed responses: signature against the IdP metadata, the request
+//! it answers, and a first use of its assertion id. Only compiled with the
+//! `saml` feature, since xmlsec needs native libraries to build.
+
+use std::collections::HashMap;
+use chrono::{DateTime, Duration, Utc};
+use samael::metadata::{EntityDescriptor, HTTP_REDIRECT_BINDING};
+use samael::schema::Assertion;
+use samael::service_provider::{ServiceProvider, ServiceProviderBuilder};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use crate::infrastructure::saml::config::local_redirect;
+use crate::infrastructure::saml::{MetadataSource, PendingRequest, SamlConfig, SamlReplayStore};
+use crate::shared::{AppError, AppResult};
+
+/// Who the IdP vouched for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SamlIdentity {
+    pub name_id: String,
+    /// From the configured email attribute, else the NameID
+    pub email: String,
+    /// Attribute values keyed by name (and by friendly name, where given)
+    pub attributes: HashMap<String, Vec<String>>,
+}
+
+/// A validated, first-use response to one of our requests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SamlLogin {
+    pub identity: SamlIdentity,
+    /// Local path to send the browser to
+    pub redirect_to: String,
+    pub app_type: Option<String>,
+}
+
+pub struct SamlServiceProvider {
+    config: SamlConfig,
+    sp: ServiceProvider,
+    sso_url: String,
+    replay: SamlReplayStore,
+}
+
+impl SamlServiceProvider {
+    /// Load the IdP metadata and build the service provider
+    pub async fn load(config: SamlConfig, replay: SamlReplayStore) -> AppResult<Self> {
+        let xml = match &config.idp_metadata {
+            MetadataSource::Url(url) => reqwest::get(url)
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| AppError::Configuration(format!("Failed to fetch SAML IdP metadata: {}", e)))?
+                .text()
+                .await
+                .map_err(|e| AppError::Configuration(format!("Failed to read SAML IdP metadata: {}", e)))?,
+            MetadataSource::File(path) => tokio::fs::read_to_string(path).await.map_err(|e| {
+                AppError::Configuration(format!("Failed to read SAML IdP metadata {}: {}", path.display(), e))
+            })?,
+        };
+        let idp_metadata: EntityDescriptor = xml
+            .parse()
+            .map_err(|e| AppError::Configuration(format!("Invalid SAML IdP metadata: {}", e)))?;
+
+        let sp = ServiceProviderBuilder::default()
+            .entity_id(config.entity_id.clone())
+            .acs_url(config.acs_url.clone())
+            .idp_metadata(idp_metadata)
+            .allow_idp_initiated(false)
+            .build()
+            .map_err(|e| AppError::Configuration(format!("Invalid SAML service provider settings: {}", e)))?;
+
+        // Without a signing certificate no response could be verified
+        let has_signing_certs = sp
+            .idp_signing_certs()
+            .map_err(|e| AppError::Configuration(format!("Invalid SAML IdP certificates: {}", e)))?
+            .is_some_and(|certs| !certs.is_empty());
+        if !has_signing_certs {
+            return Err(AppError::Configuration("SAML IdP metadata has no signing certificate".to_string()));
+        }
+        let sso_url = sp.sso_binding_location(HTTP_REDIRECT_BINDING).ok_or_else(|| {
+            AppError::Configuration("SAML IdP metadata has no HTTP-Redirect SingleSignOnService".to_string())
+        })?;
+
+        Ok(Self { config, sp, sso_url, replay })
+    }
+
+    /// Our SP metadata, for registering with the IdP
+    pub fn metadata_xml(&self) -> AppResult<String> {
+        self.sp
+            .metadata()
+            .and_then(|metadata| metadata.to_xml())
+            .map_err(|e| AppError::Internal(format!("Failed to build SAML SP metadata: {}", e)))
+    }
+
+    /// Issue an AuthnRequest; returns the IdP URL to redirect the browser to
+    ///
+    /// `relay_state` is kept server-side and only honored if it is a local path.
+    pub async fn start_login(&self, relay_state: Option<&str>, app_type: Option<&str>) -> AppResult<String> {
+        if let Err(e) = self.replay.prune().await {
+            tracing::warn!("Failed to prune SAML replay cache: {}", e);
+        }
+
+        let request = self
+            .sp
+            .make_authentication_request(&self.sso_url)
+            .map_err(|e| AppError::Internal(format!("Failed to build SAML AuthnRequest: {}", e)))?;
+        let pending = PendingRequest {
+            relay_state: relay_state.and_then(local_redirect),
+            app_type: app_type.map(str::to_string),
+        };
+        let expires_at = Utc::now() + Duration::from_std(self.config.request_ttl).unwrap_or(Duration::minutes(5));
+        self.replay.record_request(&request.id, &pending, expires_at).await?;
+
+        // The relay state names the request; the destination stays on our side
+        request
+            .redirect(&request.id)
+            .map_err(|e| AppError::Internal(format!("Failed to encode SAML AuthnRequest: {}", e)))?
+            .map(|url| url.to_string())
+            .ok_or_else(|| AppError::Internal("SAML AuthnRequest has no destination".to_string()))
+    }
+
+    /// Validate a base64 `SAMLResponse` posted to the ACS
+    ///
+    /// Checks the signature against the IdP metadata, the audience, the
+    /// validity window, that it answers an outstanding request and that its
+    /// assertion has not been used before.
+    pub async fn consume(&self, saml_response: &str) -> AppResult<SamlLogin> {
+        let request_id = in_response_to(saml_response)?;
+        if !self.replay.is_pending(&request_id).await? {
+            return Err(AppError::Authentication("SAML response does not answer a pending request".to_string()));
+        }
+
+        let assertion = self
+            .sp
+            .parse_base64_response(saml_response, Some(&[request_id.as_str()]))
+            .map_err(|e| AppError::Authentication(format!("SAML response rejected: {}", e)))?;
+
+        // Consume the request only once the response is known to be genuine
+        let Some(pending) = self.replay.take_request(&request_id).await? else {
+            return Err(AppError::Authentication("SAML request was already answered".to_string()));
+        };
+        if !self.replay.record_assertion(&assertion.id, assertion_expiry(&assertion)).await? {
+            return Err(AppError::Authentication(format!("SAML assertion {} was replayed", assertion.id)));
+        }
+
+        let identity = identity_from_assertion(&assertion, self.config.email_attribute.as_deref())?;
+        Ok(SamlLogin {
+            identity,
+            redirect_to: pending.relay_state.unwrap_or_else(|| self.config.default_redirect.clone()),
+            app_type: pending.app_type,
+        })
+    }
+}
+
+/// `InResponseTo` of an as yet unverified response
+fn in_response_to(saml_response: &str) -> AppResult<String> {
+    let compact: String = saml_response.split_whitespace().collect();
+    let xml = STANDARD
+        .decode(compact)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| AppError::Authentication("SAMLResponse is not base64 XML".to_string()))?;
+    let response: samael::schema::Response = xml
+        .parse()
+        .map_err(|e| AppError::Authentication(format!("SAMLResponse is malformed: {}", e)))?;
+    response
+        .in_response_to
+        .ok_or_else(|| AppError::Authentication("Unsolicited SAML responses are not accepted".to_string()))
+}
+
+/// How long an assertion id must be remembered
+///
+/// Assertions without `NotOnOrAfter` are still bounded by the issue-instant
+/// check, so an hour comfortably outlives them.
+fn assertion_expiry(assertion: &Assertion) -> DateTime<Utc> {
+    assertion
+        .conditions
+        .as_ref()
+        .and_then(|conditions| conditions.not_on_or_after)
+        .unwrap_or_else(|| Utc::now() + Duration::hours(1))
+}
+
+pub fn identity_from_assertion(assertion: &Assertion, email_attribute: Option<&str>) -> AppResult<SamlIdentity> {
+    let name_id = assertion
+        .subject
+        .as_ref()
+        .and_then(|subject| subject.name_id.as_ref())
+        .map(|name_id| name_id.value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| AppError::Authentication("SAML assertion has no NameID".to_string()))?;
+
+    let mut attributes: HashMap<String, Vec<String>> = HashMap::new();
+    for statement in assertion.attribute_statements.iter().flatten() {
+        for attribute in &statement.attributes {
+            let values: Vec<String> = attribute.values.iter().filter_map(|v| v.value.clone()).collect();
+            for key in [&attribute.name, &attribute.friendly_name].into_iter().flatten() {
+                attributes.entry(key.clone()).or_default().extend(values.iter().cloned());
+            }
+        }
+    }
+
+    let email = match email_attribute {
+        Some(name) => attributes
+            .get(name)
+            .and_then(|values| values.first())
+            .cloned()
+            .ok_or_else(|| AppError::Authentication(format!("SAML assertion has no '{}' attribute", name)))?,
+        None => name_id.clone(),
+    };
+    Ok(SamlIdentity { name_id, email, attributes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASSERTION: &str = r#"<saml:Assertion xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion" ID="_a1" Version="2.0" IssueInstant="2026-01-01T00:00:00Z">
+        <saml:Issuer>https://idp.example.com</saml:Issuer>
+        <saml:Subject>
+            <saml:NameID Format="urn:oasis:names:tc:SAML:1.1:nameid-format:unspecified">jdoe</saml:NameID>
+        </saml:Subject>
+        <saml:Conditions NotBefore="2026-01-01T00:00:00Z" NotOnOrAfter="2026-01-01T00:05:00Z"/>
+        <saml:AttributeStatement>
+            <saml:Attribute Name="urn:oid:0.9.2342.19200300.100.1.3" FriendlyName="mail">
+                <saml:AttributeValue>jane@example.com</saml:AttributeValue>
+            </saml:Attribute>
+            <saml:Attribute Name="groups">
+                <saml:AttributeValue>clinicians</saml:AttributeValue>
+                <saml:AttributeValue>admins</saml:AttributeValue>
+            </saml:Attribute>
+        </saml:AttributeStatement>
+    </saml:Assertion>"#;
+
+    #[test]
+    fn test_identity_from_assertion() {
+        let assertion: Assertion = ASSERTION.parse().unwrap();
+
+        let identity = identity_from_assertion(&assertion, None).unwrap();
+        assert_eq!(identity.name_id, "jdoe");
+        assert_eq!(identity.email, "jdoe");
+        assert_eq!(identity.attributes["groups"], vec!["clinicians", "admins"]);
+        // Reachable by name and by friendly name
+        assert_eq!(identity.attributes["mail"], identity.attributes["urn:oid:0.9.2342.19200300.100.1.3"]);
+
+        let identity = identity_from_assertion(&assertion, Some("mail")).unwrap();
+        assert_eq!(identity.email, "jane@example.com");
+        assert!(identity_from_assertion(&assertion, Some("email")).is_err());
+
+        assert_eq!(assertion_expiry(&assertion).to_rfc3339(), "2026-01-01T00:05:00+00:00");
+    }
+
+    #[test]
+    fn test_unsolicited_responses_are_rejected() {
+        let response = r#"<samlp:Response xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" ID="_r1" Version="2.0" IssueInstant="2026-01-01T00:00:00Z"><samlp:Status><samlp:StatusCode Value="urn:oasis:names:tc:SAML:2.0:status:Success"/></samlp:Status></samlp:Response>"#;
+        assert!(matches!(in_response_to(&STANDARD.encode(response)), Err(AppError::Authentication(_))));
+
+        let answered = response.replace("ID=\"_r1\"", "ID=\"_r1\" InResponseTo=\"_req1\"");
+        assert_eq!(in_response_to(&STANDARD.encode(answered)).unwrap(), "_req1");
+        assert!(in_response_to("not base64!").is_err());
+    }
+}
@@ -3,7 +3,7 @@ use crate::domain::repositories::SessionRepository;
 use crate::infrastructure::session::SessionCache;
 use crate::shared::AppResult;
 use crate::config::settings::SessionConfig;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use std::net::IpAddr;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -225,6 +225,31 @@ impl SessionService {
         Ok(())
     }
 
+    /// End all active sessions of a user, returning how many were ended
+    pub async fn end_user_sessions(&self, user_id: Uuid) -> AppResult<usize> {
+        let sessions = self.repository.find_active_by_user(user_id).await?;
+        let now = Utc::now();
+        for session in &sessions {
+            self.repository.end_session(session.id, now).await?;
+            self.cache.remove(&session.session_token);
+        }
+        Ok(sessions.len())
+    }
+
+    /// End a user's active sessions authenticated before `before`, returning
+    /// how many were ended
+    pub async fn end_user_sessions_before(&self, user_id: Uuid, before: DateTime<Utc>) -> AppResult<usize> {
+        let sessions = self.repository.find_active_by_user(user_id).await?;
+        let now = Utc::now();
+        let mut ended = 0;
+        for session in sessions.iter().filter(|s| s.authenticated_at.is_some_and(|at| at < before)) {
+            self.repository.end_session(session.id, now).await?;
+            self.cache.remove(&session.session_token);
+            ended += 1;
+        }
+        Ok(ended)
+    }
+
     /// Get active session by token
     pub async fn get_active_session(&self, token: &str) -> AppResult<Option<Session>> {
         // Try cache first
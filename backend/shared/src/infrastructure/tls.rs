@@ -0,0 +1,296 @@
+//! HTTPS for deployments without a TLS-terminating proxy
+//!
+//! Only TLS 1.2 and 1.3 are offered. With a client CA configured, client
+//! certificates are verified against it, and required when
+//! `require_client_cert` is set. Certificate files are polled for changes so
+//! a renewed certificate is served without a restart; one that fails to load
+//! leaves the previous certificate in service.
+//!
+//! A verified client certificate is attached to every request on its
+//! connection as a [`ClientCertificate`] extension; requests on connections
+//! without one carry no extension at all.
+
+use std::fs::File;
+use std::future::Future;
+use std::io::BufReader;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+use axum::Router;
+use axum_server::accept::Accept;
+use axum_server::tls_rustls::{RustlsAcceptor, RustlsConfig};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_rustls::server::TlsStream;
+use x509_parser::extensions::GeneralName;
+use crate::config::settings::TlsConfig;
+
+#[derive(Debug, Error)]
+pub enum TlsError {
+    #[error("TLS is enabled but no {0} is configured")]
+    Missing(&'static str),
+    #[error("failed to read {path}: {source}")]
+    Read {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{path} contains no {what}")]
+    Empty { path: String, what: &'static str },
+    #[error("invalid TLS configuration: {0}")]
+    Config(String),
+}
+
+/// Load the certificate, key and client CA into a server configuration
+///
+/// Fails on anything missing, unreadable or mismatched, so a misconfigured
+/// server refuses to start rather than serving plain HTTP.
+pub fn server_config(tls: &TlsConfig) -> Result<ServerConfig, TlsError> {
+    let cert_path = tls.cert_path.as_deref().ok_or(TlsError::Missing("certificate path"))?;
+    let key_path = tls.key_path.as_deref().ok_or(TlsError::Missing("private key path"))?;
+    let certs = load_certs(cert_path)?;
+    let key = load_key(key_path)?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_protocol_versions(&[&rustls::version::TLS13, &rustls::version::TLS12])
+        .map_err(|e| TlsError::Config(e.to_string()))?;
+    let builder = match tls.client_ca_path.as_deref() {
+        Some(ca_path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in load_certs(ca_path)? {
+                roots.add(cert).map_err(|e| TlsError::Config(format!("{}: {}", ca_path, e)))?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+            let verifier = if tls.require_client_cert {
+                verifier
+            } else {
+                verifier.allow_unauthenticated()
+            };
+            builder.with_client_cert_verifier(verifier.build().map_err(|e| TlsError::Config(e.to_string()))?)
+        }
+        None if tls.require_client_cert => return Err(TlsError::Missing("client CA path")),
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut config = builder
+        .with_single_cert(certs, key)
+        .map_err(|e| TlsError::Config(format!("certificate and key do not match: {}", e)))?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(config)
+}
+
+/// Serve `app` over HTTPS on `addr`
+///
+/// `config` comes from [`server_config`], called beforehand so that startup
+/// fails before anything is bound.
+pub async fn serve(addr: SocketAddr, app: Router, config: ServerConfig, tls: &TlsConfig) -> std::io::Result<()> {
+    let rustls = RustlsConfig::from_config(Arc::new(config));
+    if tls.reload_interval_secs > 0 {
+        tokio::spawn(reload_on_change(rustls.clone(), tls.clone()));
+    }
+    axum_server::bind(addr)
+        .acceptor(ClientCertAcceptor {
+            inner: RustlsAcceptor::new(rustls),
+        })
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+        .await
+}
+
+/// The verified certificate a client presented during the TLS handshake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClientCertificate {
+    /// First common name of the subject
+    pub subject_cn: Option<String>,
+    /// DNS names, email addresses, URIs and IP addresses of the subject
+    /// alternative name extension
+    pub sans: Vec<String>,
+    /// Lowercase hex SHA-256 of the DER encoding
+    pub fingerprint: String,
+}
+
+impl ClientCertificate {
+    /// `None` when `der` is not an X.509 certificate
+    pub fn from_der(der: &[u8]) -> Option<Self> {
+        let (_, cert) = x509_parser::parse_x509_certificate(der).ok()?;
+        let subject_cn = cert
+            .subject()
+            .iter_common_name()
+            .next()
+            .and_then(|cn| cn.as_str().ok())
+            .map(str::to_string);
+        let sans = match cert.subject_alternative_name() {
+            Ok(Some(san)) => san.value.general_names.iter().filter_map(general_name).collect(),
+            _ => Vec::new(),
+        };
+        Some(Self {
+            subject_cn,
+            sans,
+            fingerprint: hex::encode(Sha256::digest(der)),
+        })
+    }
+}
+
+fn general_name(name: &GeneralName) -> Option<String> {
+    match name {
+        GeneralName::DNSName(dns) => Some(dns.to_string()),
+        GeneralName::RFC822Name(email) => Some(email.to_string()),
+        GeneralName::URI(uri) => Some(uri.to_string()),
+        GeneralName::IPAddress(bytes) => match bytes.len() {
+            4 => Some(IpAddr::from(<[u8; 4]>::try_from(*bytes).ok()?).to_string()),
+            16 => Some(IpAddr::from(<[u8; 16]>::try_from(*bytes).ok()?).to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Completes the handshake, then hands the connection's client certificate
+/// to every request served on it
+#[derive(Clone)]
+struct ClientCertAcceptor {
+    inner: RustlsAcceptor,
+}
+
+impl<I, S> Accept<I, S> for ClientCertAcceptor
+where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: Send + 'static,
+{
+    type Stream = TlsStream<I>;
+    type Service = WithClientCert<S>;
+    type Future = Pin<Box<dyn Future<Output = std::io::Result<(Self::Stream, Self::Service)>> + Send>>;
+
+    fn accept(&self, stream: I, service: S) -> Self::Future {
+        let handshake = self.inner.accept(stream, service);
+        Box::pin(async move {
+            let (stream, service) = handshake.await?;
+            // rustls has verified the chain; the leaf comes first
+            let cert = stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|chain| chain.first())
+                .and_then(|der| ClientCertificate::from_der(der));
+            Ok((stream, WithClientCert { inner: service, cert }))
+        })
+    }
+}
+
+/// Per-connection service inserting the client certificate, when there is one
+#[derive(Clone)]
+struct WithClientCert<S> {
+    inner: S,
+    cert: Option<ClientCertificate>,
+}
+
+impl<S, B> tower::Service<axum::http::Request<B>> for WithClientCert<S>
+where
+    S: tower::Service<axum::http::Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: axum::http::Request<B>) -> Self::Future {
+        if let Some(cert) = &self.cert {
+            req.extensions_mut().insert(cert.clone());
+        }
+        self.inner.call(req)
+    }
+}
+
+/// Swap in the certificate files whenever their modification times change
+async fn reload_on_change(rustls: RustlsConfig, tls: TlsConfig) {
+    let mut interval = tokio::time::interval(Duration::from_secs(tls.reload_interval_secs));
+    interval.tick().await;
+    let mut seen = modified_times(&tls);
+    loop {
+        interval.tick().await;
+        let current = modified_times(&tls);
+        if current == seen {
+            continue;
+        }
+        // Remembered even on failure: the next write to the files retries
+        seen = current;
+        match server_config(&tls) {
+            Ok(config) => {
+                rustls.reload_from_config(Arc::new(config));
+                tracing::info!("Reloaded TLS certificate from {}", tls.cert_path.as_deref().unwrap_or_default());
+            }
+            Err(e) => tracing::error!("Keeping the current TLS certificate, reload failed: {}", e),
+        }
+    }
+}
+
+fn modified_times(tls: &TlsConfig) -> Vec<Option<SystemTime>> {
+    [&tls.cert_path, &tls.key_path, &tls.client_ca_path]
+        .into_iter()
+        .map(|path| std::fs::metadata(path.as_deref()?).ok()?.modified().ok())
+        .collect()
+}
+
+fn open(path: &str) -> Result<BufReader<File>, TlsError> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|source| TlsError::Read { path: path.to_string(), source })
+}
+
+fn load_certs(path: &str) -> Result<Vec<CertificateDer<'static>>, TlsError> {
+    let certs = rustls_pemfile::certs(&mut open(path)?)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|source| TlsError::Read { path: path.to_string(), source })?;
+    if certs.is_empty() {
+        return Err(TlsError::Empty { path: path.to_string(), what: "PEM certificates" });
+    }
+    Ok(certs)
+}
+
+fn load_key(path: &str) -> Result<PrivateKeyDer<'static>, TlsError> {
+    rustls_pemfile::private_key(&mut open(path)?)
+        .map_err(|source| TlsError::Read { path: path.to_string(), source })?
+        .ok_or_else(|| TlsError::Empty { path: path.to_string(), what: "PEM private key" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unloadable_certificates_fail_clearly() {
+        let tls = TlsConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(matches!(server_config(&tls), Err(TlsError::Missing("certificate path"))));
+
+        let not_pem = std::env::temp_dir().join(format!("tls-test-{}.pem", uuid::Uuid::new_v4()));
+        std::fs::write(&not_pem, "not a certificate").unwrap();
+        let tls = TlsConfig {
+            enabled: true,
+            cert_path: Some(not_pem.display().to_string()),
+            key_path: Some("/nonexistent/key.pem".to_string()),
+            ..Default::default()
+        };
+        let err = server_config(&tls).unwrap_err();
+        std::fs::remove_file(&not_pem).unwrap();
+        assert!(matches!(err, TlsError::Empty { what: "PEM certificates", .. }));
+    }
+
+    #[test]
+    fn test_unparsable_client_certificate_is_absent() {
+        assert_eq!(ClientCertificate::from_der(b"not a certificate"), None);
+        assert_eq!(ClientCertificate::from_der(&[]), None);
+    }
+}
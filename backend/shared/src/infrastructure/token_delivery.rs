@@ -0,0 +1,158 @@
+//! Delivery of one-time tokens to users
+//!
+//! A token goes from the service that issued it straight to the mail relay
+//! and is never persisted. The event published alongside carries only IDs,
+//! so neither `event_outbox` nor `webhook_deliveries` holds a usable token.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::Duration;
+use uuid::Uuid;
+use crate::infrastructure::events::webhook::{sign, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+use crate::shared::{AppError, AppResult};
+
+/// What a token lets its holder do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenPurpose {
+    PasswordReset,
+    EmailChange,
+}
+
+/// A token on its way to a user
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenMessage {
+    pub purpose: TokenPurpose,
+    pub user_id: Uuid,
+    /// Address the token is sent to
+    pub recipient: String,
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    /// Address to tell about the request, without the token; the current
+    /// email on an email change
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify: Option<String>,
+}
+
+/// Hands tokens to whatever emails them
+#[async_trait]
+pub trait TokenSender: Send + Sync {
+    async fn send(&self, message: &TokenMessage) -> AppResult<()>;
+}
+
+/// Mail relay endpoint tokens are posted to
+#[derive(Debug, Clone)]
+pub struct TokenDeliveryConfig {
+    pub url: String,
+    /// Key for the HMAC signature header, as for webhooks
+    pub secret: String,
+    pub timeout: Duration,
+}
+
+impl TokenDeliveryConfig {
+    /// Read `TOKEN_DELIVERY_*`; `None` when no URL is set
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        Some(Self {
+            url: var("TOKEN_DELIVERY_URL")?,
+            secret: var("TOKEN_DELIVERY_SECRET").unwrap_or_default(),
+            timeout: Duration::from_secs(var("TOKEN_DELIVERY_TIMEOUT_SECONDS").and_then(|v| v.parse().ok()).unwrap_or(10)),
+        })
+    }
+}
+
+/// Posts each token to a mail relay, signed the way webhooks are
+///
+/// There is no retry queue, since queuing would mean storing the token; a
+/// failed delivery is reported and the user asks again.
+pub struct HttpTokenSender {
+    client: reqwest::Client,
+    url: String,
+    secret: Vec<u8>,
+}
+
+impl HttpTokenSender {
+    pub fn new(config: TokenDeliveryConfig) -> AppResult<Self> {
+        if config.secret.is_empty() {
+            return Err(AppError::Configuration(
+                "TOKEN_DELIVERY_SECRET must be set when TOKEN_DELIVERY_URL is configured".to_string(),
+            ));
+        }
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .map_err(|e| AppError::Configuration(format!("Failed to build token delivery client: {}", e)))?;
+        Ok(Self {
+            client,
+            url: config.url,
+            secret: config.secret.into_bytes(),
+        })
+    }
+}
+
+#[async_trait]
+impl TokenSender for HttpTokenSender {
+    async fn send(&self, message: &TokenMessage) -> AppResult<()> {
+        let body = serde_json::to_vec(message)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize token message: {}", e)))?;
+        let timestamp = Utc::now().timestamp();
+        let response = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, sign(&self.secret, timestamp, &body))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AppError::Unavailable(format!("Token delivery failed: {}", e)))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(AppError::Unavailable(format!("Token delivery responded with {}", response.status())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_shape() {
+        let message = TokenMessage {
+            purpose: TokenPurpose::PasswordReset,
+            user_id: Uuid::nil(),
+            recipient: "jane@example.com".to_string(),
+            token: "abc".to_string(),
+            expires_at: DateTime::from_timestamp(0, 0).unwrap(),
+            notify: None,
+        };
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(json["purpose"], "password_reset");
+        assert_eq!(json["recipient"], "jane@example.com");
+        assert_eq!(json["token"], "abc");
+        assert!(json.get("notify").is_none());
+
+        let change = TokenMessage {
+            purpose: TokenPurpose::EmailChange,
+            notify: Some("old@example.com".to_string()),
+            ..message
+        };
+        let json = serde_json::to_value(&change).unwrap();
+        assert_eq!(json["purpose"], "email_change");
+        assert_eq!(json["notify"], "old@example.com");
+    }
+
+    #[test]
+    fn test_sender_requires_a_secret() {
+        let config = TokenDeliveryConfig {
+            url: "https://mail.internal/tokens".to_string(),
+            secret: String::new(),
+            timeout: Duration::from_secs(1),
+        };
+        assert!(matches!(HttpTokenSender::new(config), Err(AppError::Configuration(_))));
+    }
+}
@@ -0,0 +1,202 @@
+//! Seed relationship tuples from a file
+//!
+//! A bootstrap file lists `(object, relation, subject)` rows, either as a
+//! JSON array of `{"object", "relation", "subject"}` objects or as CSV with
+//! an `object,relation,subject` header (columns in any order, no quoting;
+//! blank lines and `#` comments are skipped). Rows are checked as a write
+//! would check them, tuples that already exist are skipped, and the rest are
+//! written in batches, so loading the same file twice changes nothing.
+
+use crate::infrastructure::zanzibar::{RelationshipStore, RelationshipTuple};
+use crate::shared::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+pub const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// One row of a bootstrap file
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TupleRow {
+    /// CSV line number, or 1-based position in a JSON array
+    #[serde(skip)]
+    pub row: usize,
+    pub object: String,
+    pub relation: String,
+    pub subject: String,
+}
+
+impl TupleRow {
+    fn tuple(&self) -> RelationshipTuple {
+        RelationshipTuple::new(self.subject.clone(), self.relation.clone(), self.object.clone())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RowError {
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BootstrapReport {
+    pub rows: usize,
+    pub written: usize,
+    /// Already stored, or repeated earlier in the file
+    pub skipped: usize,
+    pub errors: Vec<RowError>,
+}
+
+/// Rows of a file, by extension: `.json`, otherwise CSV
+///
+/// Rows that cannot be read are returned as errors alongside the rest.
+pub fn read_file(path: impl AsRef<Path>) -> AppResult<(Vec<TupleRow>, Vec<RowError>)> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| AppError::Configuration(format!("Failed to read {}: {}", path.display(), e)))?;
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    if is_json {
+        parse_json(&content)
+    } else {
+        parse_csv(&content)
+    }
+}
+
+pub fn parse_json(content: &str) -> AppResult<(Vec<TupleRow>, Vec<RowError>)> {
+    let values: Vec<serde_json::Value> = serde_json::from_str(content)
+        .map_err(|e| AppError::Validation(format!("Expected a JSON array of tuples: {}", e)))?;
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for (index, value) in values.into_iter().enumerate() {
+        match serde_json::from_value::<TupleRow>(value) {
+            Ok(mut row) => {
+                row.row = index + 1;
+                rows.push(row);
+            }
+            Err(e) => errors.push(RowError { row: index + 1, message: e.to_string() }),
+        }
+    }
+    Ok((rows, errors))
+}
+
+pub fn parse_csv(content: &str) -> AppResult<(Vec<TupleRow>, Vec<RowError>)> {
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+    let (_, header) = lines
+        .next()
+        .ok_or_else(|| AppError::Validation("CSV file has no header".to_string()))?;
+    let columns: Vec<&str> = header.split(',').map(str::trim).collect();
+    let position = |name: &str| {
+        columns
+            .iter()
+            .position(|column| column.eq_ignore_ascii_case(name))
+            .ok_or_else(|| AppError::Validation(format!("CSV header is missing the '{}' column", name)))
+    };
+    let (object, relation, subject) = (position("object")?, position("relation")?, position("subject")?);
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for (line_number, line) in lines {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() != columns.len() {
+            errors.push(RowError {
+                row: line_number,
+                message: format!("expected {} fields, found {}", columns.len(), fields.len()),
+            });
+            continue;
+        }
+        rows.push(TupleRow {
+            row: line_number,
+            object: fields[object].to_string(),
+            relation: fields[relation].to_string(),
+            subject: fields[subject].to_string(),
+        });
+    }
+    Ok((rows, errors))
+}
+
+/// Writes bootstrap rows through a [`RelationshipStore`]
+pub struct TupleBootstrap<'a> {
+    store: &'a RelationshipStore,
+    batch_size: usize,
+}
+
+impl<'a> TupleBootstrap<'a> {
+    pub fn new(store: &'a RelationshipStore) -> Self {
+        Self { store, batch_size: DEFAULT_BATCH_SIZE }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Validate every row, then write the valid new ones batch by batch
+    ///
+    /// Invalid rows are reported and left out; a failed batch stops the load
+    /// with an error, leaving earlier batches written.
+    pub async fn load(&self, rows: Vec<TupleRow>) -> AppResult<BootstrapReport> {
+        let mut report = BootstrapReport { rows: rows.len(), ..Default::default() };
+        let mut seen = HashSet::new();
+        let mut pending = Vec::new();
+        for row in rows {
+            let tuple = row.tuple();
+            if let Err(e) = self.store.validate_tuple(&tuple) {
+                report.errors.push(RowError { row: row.row, message: e.to_string() });
+            } else if seen.insert(tuple.clone()) {
+                pending.push(tuple);
+            } else {
+                report.skipped += 1;
+            }
+        }
+
+        for batch in pending.chunks(self.batch_size) {
+            let existing = self.store.existing_tuples(batch).await?;
+            let new: Vec<RelationshipTuple> = batch.iter().filter(|t| !existing.contains(*t)).cloned().collect();
+            report.skipped += batch.len() - new.len();
+            if !new.is_empty() {
+                let token = self.store.write_tuples(&new).await?;
+                tracing::info!("Bootstrapped {} relationship tuple(s) at {}", new.len(), token);
+                report.written += new.len();
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_columns_in_any_order_with_row_errors() {
+        let csv = "# fixtures\nsubject,relation,object\nuser:alice,member,group:eng\n\nuser:bob,viewer\n";
+        let (rows, errors) = parse_csv(csv).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].row, 3);
+        assert_eq!(rows[0].tuple().to_string(), "user:alice#member@group:eng");
+        assert_eq!(errors, vec![RowError { row: 5, message: "expected 3 fields, found 2".to_string() }]);
+
+        assert!(parse_csv("object,relation\ndocument:1,viewer\n").is_err());
+    }
+
+    #[test]
+    fn test_json_rows_report_their_position() {
+        let json = r#"[
+            {"object": "document:1", "relation": "viewer", "subject": "group:eng"},
+            {"object": "document:2", "relation": "viewer"}
+        ]"#;
+        let (rows, errors) = parse_json(json).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].row, 1);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].row, 2);
+        assert!(errors[0].message.contains("subject"));
+    }
+}
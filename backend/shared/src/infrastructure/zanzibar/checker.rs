@@ -1,4 +1,4 @@
-use crate::infrastructure::zanzibar::{RelationshipStore, GraphPermissionChecker, GraphCache};
+use crate::infrastructure::zanzibar::{AuthorizationSchema, RelationshipStore, GraphPermissionChecker, GraphCache};
 use crate::domain::repositories::RelationshipRepository;
 use crate::shared::AppResult;
 use std::collections::HashSet;
@@ -38,6 +38,32 @@ impl PermissionChecker {
         self.use_graph_for_deep_queries && self.graph_cache.is_some()
     }
 
+    /// Schema the underlying store validates writes against, if any
+    pub fn schema(&self) -> Option<Arc<AuthorizationSchema>> {
+        self.store.schema().cloned()
+    }
+
+    /// Check a single tuple; with a schema, tuples of implying relations
+    /// (e.g. editor for viewer) also grant. Tuple-to-userset rules are only
+    /// evaluated by the graph checker.
+    async fn check_direct(
+        &self,
+        subject: &str,
+        relation: &str,
+        object: &str,
+        organization_id: Option<Uuid>,
+    ) -> AppResult<bool> {
+        let Some(schema) = self.store.schema() else {
+            return self.store.check_with_organization(subject, relation, object, organization_id).await;
+        };
+        for implied in schema.implying_relations(object, relation) {
+            if self.store.check_with_organization(subject, &implied, object, organization_id).await? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     /// Check if user has relation on object
     /// Supports multiple inheritance paths and UNION of permissions:
     /// 1. Wildcard permission: user#*@* (grants all permissions - checked first)
@@ -72,7 +98,7 @@ impl PermissionChecker {
             if let Some(cache) = &self.graph_cache {
                 // Try to get cached graph
                 if let Some(graph) = cache.get_cached() {
-                    let graph_checker = GraphPermissionChecker::new(graph);
+                    let graph_checker = GraphPermissionChecker::new(graph).with_schema(self.store.schema().cloned());
                     if let Ok(result) = graph_checker.check(user, relation, object) {
                         // TODO: Add organization filtering to graph checker
                         return Ok(result);
@@ -84,7 +110,7 @@ impl PermissionChecker {
         
         // Fallback to database-based check (original implementation)
         // 1. Direct user permission check (with organization filtering)
-        if self.store.check_with_organization(user, relation, object, organization_id).await? {
+        if self.check_direct(user, relation, object, organization_id).await? {
             return Ok(true);
         }
 
@@ -101,7 +127,7 @@ impl PermissionChecker {
                 // User has a role, check if role has the relation
                 let role_str = &rel.object; // e.g., "role:admin"
                 // Check role permission with same organization context
-                if self.store.check_with_organization(role_str, relation, object, organization_id).await? {
+                if self.check_direct(role_str, relation, object, organization_id).await? {
                     return Ok(true);
                 }
             }
@@ -113,7 +139,7 @@ impl PermissionChecker {
                 let group_str = &rel.object; // e.g., "group:doctors"
                 
                 // 4a. Direct group permission (with organization context)
-                if self.store.check_with_organization(group_str, relation, object, organization_id).await? {
+                if self.check_direct(group_str, relation, object, organization_id).await? {
                     return Ok(true);
                 }
                 
@@ -133,7 +159,7 @@ impl PermissionChecker {
                 for group_rel in &group_relationships {
                     if group_rel.relation == "has_role" {
                         let role_str = &group_rel.object;
-                        if self.store.check_with_organization(role_str, relation, object, organization_id).await? {
+                        if self.check_direct(role_str, relation, object, organization_id).await? {
                             return Ok(true);
                         }
                     }
@@ -154,7 +180,7 @@ impl PermissionChecker {
     ) -> AppResult<bool> {
         if let Some(cache) = &self.graph_cache {
             let graph = cache.get_or_build(repository).await?;
-            let graph_checker = GraphPermissionChecker::new(graph);
+            let graph_checker = GraphPermissionChecker::new(graph).with_schema(self.store.schema().cloned());
             graph_checker.check(user, relation, object)
         } else {
             // Fallback to regular check
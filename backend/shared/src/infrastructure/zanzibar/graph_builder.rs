@@ -2,14 +2,22 @@ use crate::domain::entities::Relationship;
 use crate::domain::repositories::RelationshipRepository;
 use crate::infrastructure::zanzibar::graph_types::{GraphNode, RelationshipEdge, EntityType};
 use crate::shared::AppResult;
-use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::graph::{DiGraph, EdgeIndex, NodeIndex};
 use std::collections::HashMap;
+use uuid::Uuid;
 
 /// Authorization graph built from relationships
+///
+/// Besides being built in one go, the graph can follow individual writes:
+/// [`Self::upsert_relationship`] and [`Self::remove_tuple`] change one edge
+/// each. Nodes are never removed, so an entity whose last edge goes away
+/// stays behind without edges, which no check can tell apart from absent.
+#[derive(Clone)]
 pub struct AuthorizationGraph {
     pub graph: DiGraph<GraphNode, RelationshipEdge>,
     pub node_index: HashMap<String, NodeIndex>, // Entity string -> NodeIndex
     pub reverse_index: HashMap<NodeIndex, String>, // NodeIndex -> Entity string
+    edge_index: HashMap<Uuid, EdgeIndex>, // Relationship ID -> EdgeIndex
 }
 
 impl AuthorizationGraph {
@@ -18,6 +26,7 @@ impl AuthorizationGraph {
             graph: DiGraph::new(),
             node_index: HashMap::new(),
             reverse_index: HashMap::new(),
+            edge_index: HashMap::new(),
         }
     }
     
@@ -51,18 +60,105 @@ impl AuthorizationGraph {
         // Get or create target node
         let target_node = self.get_or_create_node(&relationship.object);
         
-        // Create edge
-        let edge = RelationshipEdge {
+        // Add edge to graph
+        let edge = self.graph.add_edge(source_node, target_node, Self::edge(&relationship));
+        self.edge_index.insert(relationship.id, edge);
+    }
+
+    fn edge(relationship: &Relationship) -> RelationshipEdge {
+        RelationshipEdge {
             relation: relationship.relation.clone(),
             expires_at: relationship.expires_at,
             valid_from: relationship.valid_from,
             is_active: relationship.is_active,
             metadata: relationship.metadata.clone(),
             relationship_id: relationship.id,
+            organization_id: relationship.organization_id,
+        }
+    }
+
+    /// Bring the graph in line with one relationship as stored
+    ///
+    /// Replaces the edge of the same relationship ID, or of the same live
+    /// tuple in the same organization (a create that hit an existing tuple
+    /// gives it a new ID), adds one if there is none, and removes it when
+    /// the relationship is soft-deleted.
+    pub fn upsert_relationship(&mut self, relationship: Relationship) {
+        if relationship.deleted_at.is_some() {
+            self.remove_relationship(relationship.id);
+            return;
+        }
+        let existing = self.edge_index.get(&relationship.id).copied().or_else(|| self.live_edge(&relationship));
+        match existing {
+            Some(edge) => {
+                let previous = std::mem::replace(&mut self.graph[edge], Self::edge(&relationship));
+                self.edge_index.remove(&previous.relationship_id);
+                self.edge_index.insert(relationship.id, edge);
+            }
+            None => self.add_relationship(relationship),
+        }
+    }
+
+    /// Follow a batch write of `relationship`
+    ///
+    /// A batch write of a tuple that is already live in the same
+    /// organization only gives the stored row the new ID, keeping its
+    /// window and metadata; otherwise it is added as is.
+    pub fn merge_relationship(&mut self, relationship: Relationship) {
+        match self.live_edge(&relationship) {
+            Some(edge) => {
+                let previous = std::mem::replace(&mut self.graph[edge].relationship_id, relationship.id);
+                self.edge_index.remove(&previous);
+                self.edge_index.insert(relationship.id, edge);
+            }
+            None => self.add_relationship(relationship),
+        }
+    }
+
+    /// The edge of the tuple of `relationship` in its organization
+    fn live_edge(&self, relationship: &Relationship) -> Option<EdgeIndex> {
+        self.tuple_edges(&relationship.user, &relationship.relation, &relationship.object)
+            .into_iter()
+            .find(|edge| self.graph[*edge].organization_id == relationship.organization_id)
+    }
+
+    /// Remove the edge of one relationship, returning whether there was one
+    pub fn remove_relationship(&mut self, relationship_id: Uuid) -> bool {
+        let Some(edge) = self.edge_index.remove(&relationship_id) else {
+            return false;
         };
-        
-        // Add edge to graph
-        self.graph.add_edge(source_node, target_node, edge);
+        self.graph.remove_edge(edge);
+        // The last edge was moved into the freed index
+        if let Some(moved) = self.graph.edge_weight(edge) {
+            self.edge_index.insert(moved.relationship_id, edge);
+        }
+        true
+    }
+
+    /// Remove every edge of a tuple, whatever its organization, returning
+    /// how many there were
+    pub fn remove_tuple(&mut self, user: &str, relation: &str, object: &str) -> usize {
+        let ids: Vec<Uuid> = self
+            .tuple_edges(user, relation, object)
+            .into_iter()
+            .map(|edge| self.graph[edge].relationship_id)
+            .collect();
+        for id in &ids {
+            self.remove_relationship(*id);
+        }
+        ids.len()
+    }
+
+    fn tuple_edges(&self, user: &str, relation: &str, object: &str) -> Vec<EdgeIndex> {
+        use petgraph::visit::EdgeRef;
+        let (Some(source), Some(target)) = (self.get_node(user), self.get_node(object)) else {
+            return Vec::new();
+        };
+        self.graph
+            .edges_connecting(source, target)
+            .filter(|edge| edge.weight().relation == relation)
+            .map(|edge| edge.id())
+            .collect()
     }
     
     /// Get or create a node for an entity
@@ -165,3 +261,71 @@ pub struct GraphStats {
     pub edge_count: usize,
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn edges(graph: &AuthorizationGraph) -> BTreeSet<(String, String, String, Uuid)> {
+        graph
+            .graph
+            .edge_indices()
+            .map(|edge| {
+                let (source, target) = graph.graph.edge_endpoints(edge).unwrap();
+                let weight = &graph.graph[edge];
+                assert_eq!(graph.edge_index[&weight.relationship_id], edge);
+                (
+                    graph.get_entity(source).unwrap().to_string(),
+                    weight.relation.clone(),
+                    graph.get_entity(target).unwrap().to_string(),
+                    weight.relationship_id,
+                )
+            })
+            .collect()
+    }
+
+    fn relationship(user: &str, relation: &str, object: &str) -> Relationship {
+        Relationship::new(user.to_string(), relation.to_string(), object.to_string())
+    }
+
+    #[test]
+    fn test_incremental_updates_match_a_rebuild() {
+        let mut stored = vec![
+            relationship("user:alice", "member", "group:eng"),
+            relationship("group:eng", "view", "document:1"),
+            relationship("user:bob", "edit", "document:1"),
+            relationship("user:carol", "view", "document:2"),
+        ];
+        let mut graph = AuthorizationGraph::build_from_relationships(stored.clone());
+
+        // Removing an early edge moves the last one into its index
+        let removed = stored.remove(0);
+        assert!(graph.remove_relationship(removed.id));
+        assert!(!graph.remove_relationship(removed.id));
+
+        let added = relationship("user:dave", "member", "group:eng");
+        graph.upsert_relationship(added.clone());
+        stored.push(added);
+
+        stored[0].revoke(None);
+        graph.upsert_relationship(stored[0].clone());
+        stored.remove(0);
+
+        // A batch rewrite of a live tuple keeps the row under a new ID
+        let rewrite = relationship("user:bob", "edit", "document:1");
+        graph.merge_relationship(rewrite.clone());
+        stored.retain(|r| r.user != "user:bob");
+        stored.push(rewrite);
+
+        // So does a create that hits a live tuple, taking the new row whole
+        let recreated = relationship("user:dave", "member", "group:eng");
+        graph.upsert_relationship(recreated.clone());
+        stored.retain(|r| r.user != "user:dave");
+        stored.push(recreated);
+
+        assert_eq!(graph.remove_tuple("user:carol", "view", "document:2"), 1);
+        stored.retain(|r| r.user != "user:carol");
+
+        assert_eq!(edges(&graph), edges(&AuthorizationGraph::build_from_relationships(stored)));
+    }
+}
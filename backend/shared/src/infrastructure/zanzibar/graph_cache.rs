@@ -1,6 +1,8 @@
 use crate::infrastructure::zanzibar::graph_builder::AuthorizationGraph;
+use crate::domain::entities::Relationship;
 use crate::domain::repositories::RelationshipRepository;
 use crate::shared::AppResult;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 use chrono::{DateTime, Utc, Duration};
 
@@ -13,10 +15,17 @@ struct CacheEntry {
 }
 
 /// Graph cache manager
+///
+/// Writes made through a `RelationshipStore` holding the cache are applied
+/// to the cached graph in place (see [`Self::relationship_stored`]), so only
+/// a cold or expired cache is rebuilt from every tuple. The TTL still bounds
+/// how long writes made elsewhere, e.g. by another instance, go unseen.
 pub struct GraphCache {
     cache: Arc<RwLock<Option<CacheEntry>>>,
     ttl: Duration,
     enabled: bool,
+    /// Bumped on every change, so a graph built meanwhile is not cached
+    generation: AtomicU64,
 }
 
 impl GraphCache {
@@ -25,6 +34,7 @@ impl GraphCache {
             cache: Arc::new(RwLock::new(None)),
             ttl: Duration::seconds(ttl_seconds),
             enabled,
+            generation: AtomicU64::new(0),
         }
     }
     
@@ -57,16 +67,20 @@ impl GraphCache {
         }
         
         // Cache miss or expired, build new graph
+        let generation = self.generation.load(Ordering::SeqCst);
         let graph = Arc::new(self.build_graph(repository).await?);
         
-        // Update cache
+        // Update cache, unless a write landed while building: the graph may
+        // predate it, and the write found no cached graph to apply to
         {
             let mut cache = self.cache.write().unwrap();
-            *cache = Some(CacheEntry {
-                graph: Arc::clone(&graph),
-                created_at: Utc::now(),
-                expires_at: Utc::now() + self.ttl,
-            });
+            if self.generation.load(Ordering::SeqCst) == generation {
+                *cache = Some(CacheEntry {
+                    graph: Arc::clone(&graph),
+                    created_at: Utc::now(),
+                    expires_at: Utc::now() + self.ttl,
+                });
+            }
         }
         
         Ok(graph)
@@ -89,8 +103,44 @@ impl GraphCache {
     /// Invalidate cache
     pub fn invalidate(&self) {
         let mut cache = self.cache.write().unwrap();
+        self.generation.fetch_add(1, Ordering::SeqCst);
         *cache = None;
     }
+
+    /// Apply a relationship as stored after a single write
+    pub fn relationship_stored(&self, relationship: &Relationship) {
+        self.update(|graph| graph.upsert_relationship(relationship.clone()));
+    }
+
+    /// Apply a batch from `RelationshipStore::apply_tuples`, deletes first
+    /// as in the database
+    pub fn batch_applied(&self, writes: &[Relationship], deletes: &[(String, String, String)]) {
+        self.update(|graph| {
+            for (user, relation, object) in deletes {
+                graph.remove_tuple(user, relation, object);
+            }
+            for relationship in writes {
+                graph.merge_relationship(relationship.clone());
+            }
+        });
+    }
+
+    /// Change the cached graph in place, if one is cached
+    ///
+    /// A cold cache is left alone; the next read builds it with the change.
+    /// The graph is copied first only while a check still holds it.
+    fn update(&self, change: impl FnOnce(&mut AuthorizationGraph)) {
+        if !self.enabled {
+            return;
+        }
+        let mut cache = self.cache.write().unwrap();
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        if let Some(entry) = cache.as_mut() {
+            if Utc::now() < entry.expires_at {
+                change(Arc::make_mut(&mut entry.graph));
+            }
+        }
+    }
     
     /// Force refresh cache
     pub async fn refresh(
@@ -1,13 +1,80 @@
 use crate::infrastructure::zanzibar::graph_builder::AuthorizationGraph;
-use crate::infrastructure::zanzibar::graph_types::RelationshipEdge;
+use crate::infrastructure::zanzibar::schema::AuthorizationSchema;
 use crate::shared::AppResult;
 use petgraph::graph::NodeIndex;
+use serde::Serialize;
 use std::collections::{HashSet, VecDeque, HashMap};
 
+/// Relations through which a subject passes its grants on to other subjects
+pub(crate) const INHERITING_RELATIONS: [&str; 2] = ["member", "has_role"];
+
+/// A subject in an expanded userset
+///
+/// `relation` is how the subject reaches its parent (or the expanded object,
+/// at the top level); `children` are subjects that inherit through it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsersetNode {
+    pub subject: String,
+    pub relation: String,
+    pub children: Vec<UsersetNode>,
+}
+
+/// Outcome of [`GraphPermissionChecker::check_explain`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CheckExplanation {
+    pub allowed: bool,
+    /// The granting path, or where the search dead-ended
+    pub summary: String,
+    /// Traversal steps, indented by nesting
+    pub steps: Vec<String>,
+}
+
+/// Steps of one check; only recorded when `enabled`, so plain checks pay
+/// nothing for it
+#[derive(Default)]
+struct Trace {
+    enabled: bool,
+    steps: Vec<String>,
+    hints: Vec<String>,
+    granted_by: Option<String>,
+    /// The DFS revisits edges; each step is kept once
+    seen: HashSet<String>,
+}
+
+impl Trace {
+    fn note(&mut self, indent: usize, step: impl FnOnce() -> String) {
+        if self.enabled {
+            let step = format!("{}{}", "  ".repeat(indent), step());
+            if self.seen.insert(step.clone()) {
+                self.steps.push(step);
+            }
+        }
+    }
+
+    /// A reason the check may have failed, for the summary
+    fn hint(&mut self, hint: impl FnOnce() -> String) {
+        if self.enabled {
+            let hint = hint();
+            if !self.hints.contains(&hint) {
+                self.hints.push(hint);
+            }
+        }
+    }
+
+    fn grant(&mut self, indent: usize, step: impl FnOnce() -> String) {
+        if self.enabled {
+            let step = step();
+            self.note(indent, || step.clone());
+            self.granted_by = Some(step);
+        }
+    }
+}
+
 /// Graph-based permission checker
 pub struct GraphPermissionChecker {
     graph: std::sync::Arc<AuthorizationGraph>,
     max_depth: usize,
+    schema: Option<std::sync::Arc<AuthorizationSchema>>,
 }
 
 impl GraphPermissionChecker {
@@ -15,6 +82,7 @@ impl GraphPermissionChecker {
         Self {
             graph,
             max_depth: 10, // Default max depth to prevent infinite loops
+            schema: None,
         }
     }
     
@@ -22,9 +90,86 @@ impl GraphPermissionChecker {
         self.max_depth = max_depth;
         self
     }
+
+    /// Honor the schema's relation rewrites (implied relations and
+    /// tuple-to-userset inheritance) in checks and expansions
+    pub fn with_schema(mut self, schema: Option<std::sync::Arc<AuthorizationSchema>>) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    /// Relations whose edges into `object` grant `relation`
+    fn granting_relations(&self, object: &str, relation: &str) -> HashSet<String> {
+        match &self.schema {
+            Some(schema) => schema.implying_relations(object, relation),
+            None => HashSet::from([relation.to_string()]),
+        }
+    }
+
+    fn entity(&self, idx: NodeIndex) -> &str {
+        self.graph.get_entity(idx).unwrap_or("unknown")
+    }
+
+    /// Whether any valid edge from `source` to `target` has one of `relations`
+    fn edge_grants(
+        &self,
+        source: NodeIndex,
+        target: NodeIndex,
+        relations: &HashSet<String>,
+        trace: &mut Trace,
+        indent: usize,
+    ) -> bool {
+        self.graph
+            .graph
+            .edges_connecting(source, target)
+            .any(|edge_ref| {
+                let edge = edge_ref.weight();
+                if !relations.contains(&edge.relation) {
+                    return false;
+                }
+                match edge.invalid_reason() {
+                    None => true,
+                    Some(reason) => {
+                        trace.note(indent, || {
+                            format!("{}#{}@{} is {}", self.entity(source), edge.relation, self.entity(target), reason)
+                        });
+                        false
+                    }
+                }
+            })
+    }
     
     /// Check if user has relation on object using graph traversal
     pub fn check(&self, user: &str, relation: &str, object: &str) -> AppResult<bool> {
+        self.check_traced(user, relation, object, &mut Trace::default())
+    }
+
+    /// Run [`check`](Self::check) and report the traversal it attempted
+    ///
+    /// On a denial the summary names where the search dead-ended, e.g. a
+    /// group that has the relation but that the user is not a member of.
+    pub fn check_explain(&self, user: &str, relation: &str, object: &str) -> AppResult<CheckExplanation> {
+        let mut trace = Trace { enabled: true, ..Default::default() };
+        let allowed = self.check_traced(user, relation, object, &mut trace)?;
+        let summary = match (&trace.granted_by, allowed) {
+            (Some(granted_by), true) => granted_by.clone(),
+            _ => {
+                let mut summary = format!("no path from {} to {} via {}", user, object, relation);
+                for hint in &trace.hints {
+                    summary.push_str("; ");
+                    summary.push_str(hint);
+                }
+                summary
+            }
+        };
+        Ok(CheckExplanation {
+            allowed,
+            summary,
+            steps: trace.steps,
+        })
+    }
+
+    fn check_traced(&self, user: &str, relation: &str, object: &str, trace: &mut Trace) -> AppResult<bool> {
         // First, check for wildcard permission (super admin bypass)
         // Check if user has wildcard: user#*@*
         if let Some(user_node) = self.graph.get_node(user) {
@@ -33,9 +178,15 @@ impl GraphPermissionChecker {
                 if edge.relation == "*" {
                     // Check if target is "*" by looking at the entity
                     if let Some(target_entity) = self.graph.get_entity(target_node) {
-                        if target_entity == "*" && edge.is_valid() {
-                            // Wildcard exists, user has all permissions
-                            return Ok(true);
+                        if target_entity == "*" {
+                            match edge.invalid_reason() {
+                                // Wildcard exists, user has all permissions
+                                None => {
+                                    trace.grant(0, || format!("{} holds the wildcard grant {}#*@*", user, user));
+                                    return Ok(true);
+                                }
+                                Some(reason) => trace.note(0, || format!("wildcard grant {}#*@* is {}", user, reason)),
+                            }
                         }
                     }
                 }
@@ -46,32 +197,90 @@ impl GraphPermissionChecker {
         let object_node = self.graph.get_node(object);
         
         if user_node.is_none() || object_node.is_none() {
+            for (entity, node) in [(user, user_node), (object, object_node)] {
+                if node.is_none() {
+                    trace.hint(|| format!("{} has no relationships", entity));
+                }
+            }
             return Ok(false);
         }
         
-        let user_idx = user_node.unwrap();
-        let object_idx = object_node.unwrap();
-        
+        self.check_nodes(user_node.unwrap(), relation, object_node.unwrap(), object, 0, trace)
+    }
+
+    fn check_nodes(
+        &self,
+        user_idx: NodeIndex,
+        relation: &str,
+        object_idx: NodeIndex,
+        object: &str,
+        depth: usize,
+        trace: &mut Trace,
+    ) -> AppResult<bool> {
+        // With a schema, an edge of any implying relation (e.g. editor for viewer) grants
+        let relations = self.granting_relations(object, relation);
+        trace.note(depth, || {
+            let mut names: Vec<&str> = relations.iter().map(String::as_str).collect();
+            names.sort();
+            format!("looking for {} on {}", names.join(" or "), object)
+        });
+
         // Check direct edge first
-        if let Some(edge) = self.get_edge(user_idx, object_idx) {
-            if edge.matches_relation(relation) && edge.is_valid() {
-                return Ok(true);
-            }
+        if self.edge_grants(user_idx, object_idx, &relations, trace, depth + 1) {
+            trace.grant(depth + 1, || format!("{} has {} on {} directly", self.entity(user_idx), relation, object));
+            return Ok(true);
         }
         
         // Find all paths from user to object
         // We need to check if any path exists AND any edge leading to object has the relation
-        let paths = self.find_paths(user_idx, object_idx, relation)?;
+        let paths = self.find_paths_traced(user_idx, object_idx, relation, trace, depth + 1)?;
         
         if !paths.is_empty() {
             // Check if any path ends with an edge that has the relation
-            for path in paths {
+            for path in &paths {
                 if path.len() >= 2 {
                     let last_node = path[path.len() - 2];
-                    if let Some(edge) = self.get_edge(last_node, object_idx) {
-                        if edge.matches_relation(relation) && edge.is_valid() {
-                            return Ok(true);
-                        }
+                    if self.edge_grants(last_node, object_idx, &relations, trace, depth + 1) {
+                        trace.grant(depth + 1, || {
+                            let hops: Vec<&str> = path.iter().map(|idx| self.entity(*idx)).collect();
+                            format!("{} has {} on {} via {}", self.entity(user_idx), relation, object, hops.join(" -> "))
+                        });
+                        return Ok(true);
+                    }
+                }
+            }
+            trace.note(depth + 1, || {
+                format!("{} path(s) reach {} but none ends in a granting edge", paths.len(), object)
+            });
+        }
+        if trace.enabled {
+            self.trace_unreached_grantors(user_idx, object_idx, object, &relations, trace);
+        }
+
+        // Tuple-to-userset: the relation may be inherited from linked objects
+        // (e.g. viewer of a document's parent folder)
+        if let Some(schema) = &self.schema {
+            if depth >= self.max_depth {
+                trace.note(depth + 1, || format!("stopped at max depth {}", self.max_depth));
+                return Ok(false);
+            }
+            for rule in schema.tuple_to_usersets(object, relation) {
+                for (linked_idx, edge) in self.graph.get_incoming_edges(object_idx) {
+                    if !edge.matches_relation(&rule.tupleset) {
+                        continue;
+                    }
+                    let Some(linked) = self.graph.get_entity(linked_idx) else {
+                        continue;
+                    };
+                    if let Some(reason) = edge.invalid_reason() {
+                        trace.note(depth + 1, || format!("{}#{}@{} is {}", linked, edge.relation, object, reason));
+                        continue;
+                    }
+                    trace.note(depth + 1, || {
+                        format!("{} inherits {} from {} ({})", object, relation, linked, rule.tupleset)
+                    });
+                    if self.check_nodes(user_idx, &rule.computed_userset, linked_idx, linked, depth + 1, trace)? {
+                        return Ok(true);
                     }
                 }
             }
@@ -79,6 +288,33 @@ impl GraphPermissionChecker {
         
         Ok(false)
     }
+
+    /// Name the subjects that do hold a granting relation on the object but
+    /// that `user_idx` never reached
+    fn trace_unreached_grantors(
+        &self,
+        user_idx: NodeIndex,
+        object_idx: NodeIndex,
+        object: &str,
+        relations: &HashSet<String>,
+        trace: &mut Trace,
+    ) {
+        let user = self.entity(user_idx);
+        for (source, edge) in self.graph.get_incoming_edges(object_idx) {
+            if source == user_idx || !relations.contains(&edge.relation) || !edge.is_valid() {
+                continue;
+            }
+            let grantor = self.entity(source);
+            let missing = if grantor.starts_with("group:") {
+                format!("{} is not a member", user)
+            } else if grantor.starts_with("role:") {
+                format!("{} does not hold it", user)
+            } else {
+                format!("{} does not reach it", user)
+            };
+            trace.hint(|| format!("{} has {} on {} but {}", grantor, edge.relation, object, missing));
+        }
+    }
     
     /// Find all paths from source to target matching a relation
     /// In Zanzibar, we find paths to the target and check if any edge leading to it has the relation
@@ -87,6 +323,17 @@ impl GraphPermissionChecker {
         source: NodeIndex,
         target: NodeIndex,
         target_relation: &str,
+    ) -> AppResult<Vec<Vec<NodeIndex>>> {
+        self.find_paths_traced(source, target, target_relation, &mut Trace::default(), 0)
+    }
+
+    fn find_paths_traced(
+        &self,
+        source: NodeIndex,
+        target: NodeIndex,
+        target_relation: &str,
+        trace: &mut Trace,
+        indent: usize,
     ) -> AppResult<Vec<Vec<NodeIndex>>> {
         let mut paths = Vec::new();
         let mut current_path = Vec::new();
@@ -98,12 +345,15 @@ impl GraphPermissionChecker {
             &mut paths,
             &mut current_path,
             0,
+            trace,
+            indent,
         )?;
         
         Ok(paths)
     }
     
     /// DFS to find all paths
+    #[allow(clippy::too_many_arguments)]
     fn dfs_paths(
         &self,
         current: NodeIndex,
@@ -112,9 +362,12 @@ impl GraphPermissionChecker {
         paths: &mut Vec<Vec<NodeIndex>>,
         current_path: &mut Vec<NodeIndex>,
         depth: usize,
+        trace: &mut Trace,
+        indent: usize,
     ) -> AppResult<()> {
         // Check max depth
         if depth > self.max_depth {
+            trace.note(indent, || format!("stopped at {} after {} hops", self.entity(current), self.max_depth));
             return Ok(());
         }
         
@@ -139,7 +392,10 @@ impl GraphPermissionChecker {
         // Explore neighbors
         for (neighbor, edge) in self.graph.get_outgoing_edges(current) {
             // Only traverse valid edges
-            if !edge.is_valid() {
+            if let Some(reason) = edge.invalid_reason() {
+                trace.note(indent, || {
+                    format!("{}#{}@{} is {}", self.entity(current), edge.relation, self.entity(neighbor), reason)
+                });
                 continue;
             }
             
@@ -159,6 +415,8 @@ impl GraphPermissionChecker {
                 paths,
                 current_path,
                 depth + 1,
+                trace,
+                indent,
             )?;
         }
         
@@ -168,15 +426,6 @@ impl GraphPermissionChecker {
         Ok(())
     }
     
-    /// Get edge between two nodes
-    fn get_edge(&self, source: NodeIndex, target: NodeIndex) -> Option<&RelationshipEdge> {
-        self.graph
-            .graph
-            .edges_connecting(source, target)
-            .next()
-            .map(|edge_ref| edge_ref.weight())
-    }
-    
     /// Find shortest path using BFS
     #[allow(unused_variables)]
     pub fn shortest_path(
@@ -341,6 +590,62 @@ impl GraphPermissionChecker {
         Ok(results)
     }
     
+    /// Expand the subjects that have `relation` on `object`
+    ///
+    /// The root stands for the object itself. Its children are subjects with
+    /// a direct `relation` edge (or an implying one, given a schema); below those are group members and role
+    /// holders that inherit the grant, down to `max_depth`.
+    pub fn expand(&self, relation: &str, object: &str) -> AppResult<UsersetNode> {
+        let mut root = UsersetNode {
+            subject: object.to_string(),
+            relation: relation.to_string(),
+            children: Vec::new(),
+        };
+        if let Some(object_idx) = self.graph.get_node(object) {
+            let relations = self.granting_relations(object, relation);
+            let relations: Vec<&str> = relations.iter().map(String::as_str).collect();
+            let mut visited = HashSet::from([object_idx]);
+            root.children = self.expand_subjects(object_idx, &relations, &mut visited, 0);
+        }
+        Ok(root)
+    }
+
+    fn expand_subjects(
+        &self,
+        target: NodeIndex,
+        relations: &[&str],
+        visited: &mut HashSet<NodeIndex>,
+        depth: usize,
+    ) -> Vec<UsersetNode> {
+        if depth >= self.max_depth {
+            return Vec::new();
+        }
+
+        // Claim this level's subjects before descending, so each subject is
+        // listed once, at the shallowest level it appears (also breaks cycles)
+        let mut level = Vec::new();
+        for (source, edge) in self.graph.get_incoming_edges(target) {
+            if !edge.is_valid() || !relations.iter().any(|r| edge.matches_relation(r)) {
+                continue;
+            }
+            if let Some(subject) = self.graph.get_entity(source) {
+                if visited.insert(source) {
+                    level.push((source, subject.to_string(), edge.relation.clone()));
+                }
+            }
+        }
+        level.sort_by(|a, b| a.1.cmp(&b.1));
+
+        level
+            .into_iter()
+            .map(|(source, subject, relation)| UsersetNode {
+                subject,
+                relation,
+                children: self.expand_subjects(source, &INHERITING_RELATIONS, visited, depth + 1),
+            })
+            .collect()
+    }
+
     /// Find all accessible entities for a user with a specific relation
     /// Useful for "find all resources user can view"
     pub fn find_accessible_entities_by_relation(
@@ -353,3 +658,140 @@ impl GraphPermissionChecker {
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::Relationship;
+    use std::sync::Arc;
+
+    fn rel(user: &str, relation: &str, object: &str) -> Relationship {
+        Relationship::new(user.to_string(), relation.to_string(), object.to_string())
+    }
+
+    #[test]
+    fn test_expand_includes_inherited_subjects() {
+        let graph = AuthorizationGraph::build_from_relationships(vec![
+            rel("user:alice", "view", "document:1"),
+            rel("group:doctors", "view", "document:1"),
+            rel("user:bob", "member", "group:doctors"),
+            rel("role:auditor", "view", "document:1"),
+            rel("group:doctors", "has_role", "role:auditor"),
+            rel("user:carol", "edit", "document:1"),
+        ]);
+        let checker = GraphPermissionChecker::new(Arc::new(graph));
+
+        let root = checker.expand("view", "document:1").unwrap();
+        assert_eq!(root.subject, "document:1");
+        let direct: Vec<&str> = root.children.iter().map(|n| n.subject.as_str()).collect();
+        assert_eq!(direct, vec!["group:doctors", "role:auditor", "user:alice"]);
+
+        let doctors = &root.children[0];
+        assert_eq!(doctors.children.len(), 1);
+        assert_eq!(doctors.children[0].subject, "user:bob");
+        assert_eq!(doctors.children[0].relation, "member");
+
+        // group:doctors was already listed directly, so the role does not repeat it
+        assert!(root.children[1].children.is_empty());
+    }
+
+    #[test]
+    fn test_expand_unknown_object_is_empty() {
+        let checker = GraphPermissionChecker::new(Arc::new(AuthorizationGraph::new()));
+        let root = checker.expand("view", "document:missing").unwrap();
+        assert!(root.children.is_empty());
+    }
+
+    const SCHEMA: &str = r#"{
+        "types": {
+            "folder": { "relations": { "viewer": {} } },
+            "document": {
+                "relations": {
+                    "parent": {},
+                    "editor": {},
+                    "viewer": {
+                        "computed_usersets": ["editor"],
+                        "tuple_to_usersets": [{ "tupleset": "parent", "computed_userset": "viewer" }]
+                    }
+                }
+            }
+        }
+    }"#;
+
+    fn schema_checker(relationships: Vec<Relationship>) -> GraphPermissionChecker {
+        let schema = Arc::new(AuthorizationSchema::from_json(SCHEMA).unwrap());
+        GraphPermissionChecker::new(Arc::new(AuthorizationGraph::build_from_relationships(relationships)))
+            .with_schema(Some(schema))
+    }
+
+    #[test]
+    fn test_editor_passes_viewer_check_with_schema() {
+        let relationships = vec![rel("user:alice", "editor", "document:1")];
+        let plain = GraphPermissionChecker::new(Arc::new(AuthorizationGraph::build_from_relationships(relationships.clone())));
+        assert!(!plain.check("user:alice", "viewer", "document:1").unwrap());
+
+        let checker = schema_checker(relationships);
+        assert!(checker.check("user:alice", "viewer", "document:1").unwrap());
+        assert!(checker.check("user:alice", "editor", "document:1").unwrap());
+    }
+
+    #[test]
+    fn test_viewer_inherited_from_parent_folder() {
+        let checker = schema_checker(vec![
+            rel("folder:a", "parent", "document:1"),
+            rel("user:bob", "viewer", "folder:a"),
+            rel("user:carol", "editor", "document:2"),
+        ]);
+        assert!(checker.check("user:bob", "viewer", "document:1").unwrap());
+        assert!(!checker.check("user:bob", "editor", "document:1").unwrap());
+        assert!(!checker.check("user:carol", "viewer", "document:1").unwrap());
+    }
+
+    #[test]
+    fn test_expand_includes_implying_relations() {
+        let checker = schema_checker(vec![
+            rel("user:alice", "editor", "document:1"),
+            rel("user:bob", "viewer", "document:1"),
+        ]);
+        let root = checker.expand("viewer", "document:1").unwrap();
+        let subjects: Vec<&str> = root.children.iter().map(|n| n.subject.as_str()).collect();
+        assert_eq!(subjects, vec!["user:alice", "user:bob"]);
+    }
+
+    #[test]
+    fn test_explain_names_the_group_the_user_is_missing_from() {
+        let checker = GraphPermissionChecker::new(Arc::new(AuthorizationGraph::build_from_relationships(vec![
+            rel("group:eng", "viewer", "document:1"),
+            rel("user:bob", "member", "group:eng"),
+            rel("user:alice", "member", "group:ops"),
+        ])));
+
+        let denied = checker.check_explain("user:alice", "viewer", "document:1").unwrap();
+        assert!(!denied.allowed);
+        assert_eq!(
+            denied.summary,
+            "no path from user:alice to document:1 via viewer; group:eng has viewer on document:1 but user:alice is not a member"
+        );
+
+        let allowed = checker.check_explain("user:bob", "viewer", "document:1").unwrap();
+        assert!(allowed.allowed);
+        assert_eq!(allowed.summary, "user:bob has viewer on document:1 via user:bob -> group:eng -> document:1");
+        assert_eq!(allowed.allowed, checker.check("user:bob", "viewer", "document:1").unwrap());
+    }
+
+    #[test]
+    fn test_explain_reports_expired_edges() {
+        let mut expired = rel("user:alice", "member", "group:eng");
+        expired.valid_from = Some(chrono::Utc::now() - chrono::Duration::days(2));
+        expired.expires_at = Some(chrono::Utc::now() - chrono::Duration::days(1));
+        let checker = GraphPermissionChecker::new(Arc::new(AuthorizationGraph::build_from_relationships(vec![
+            expired,
+            rel("group:eng", "viewer", "document:1"),
+        ])));
+
+        let explanation = checker.check_explain("user:alice", "viewer", "document:1").unwrap();
+        assert!(!explanation.allowed);
+        assert!(explanation.steps.iter().any(|s| s.contains("user:alice#member@group:eng is expired at")));
+        assert!(explanation.summary.contains("group:eng has viewer on document:1 but user:alice is not a member"));
+    }
+}
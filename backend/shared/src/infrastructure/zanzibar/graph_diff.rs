@@ -0,0 +1,205 @@
+use crate::infrastructure::zanzibar::graph_builder::AuthorizationGraph;
+use crate::infrastructure::zanzibar::graph_checker::INHERITING_RELATIONS;
+use crate::infrastructure::zanzibar::RelationshipTuple;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::fmt::Write;
+
+/// A relation a subject holds on an object, directly or through a group or
+/// role
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct EffectivePermission {
+    pub relation: String,
+    pub object: String,
+    /// Group or role the grant comes through; `None` when held directly
+    pub via: Option<String>,
+}
+
+/// The part of the graph that decides what one subject can access
+///
+/// `tuples` are the subject's edges and those of every group and role it
+/// reaches through `member` and `has_role`. Schema rewrites are not applied,
+/// so `permissions` lists grants as stored.
+#[derive(Debug, Clone, Default)]
+pub struct SubjectSnapshot {
+    pub subject: String,
+    pub tuples: BTreeSet<RelationshipTuple>,
+    /// Keyed by (relation, object); the value is the shallowest grant
+    pub permissions: BTreeMap<(String, String), EffectivePermission>,
+}
+
+impl SubjectSnapshot {
+    /// Walk the valid edges reachable from `subject`, up to `max_depth` hops
+    pub fn from_graph(graph: &AuthorizationGraph, subject: &str, max_depth: usize) -> Self {
+        let mut snapshot = Self { subject: subject.to_string(), ..Default::default() };
+        let Some(start) = graph.get_node(subject) else {
+            return snapshot;
+        };
+
+        let mut visited = HashSet::from([start]);
+        let mut queue = VecDeque::from([(start, 0)]);
+        while let Some((node, depth)) = queue.pop_front() {
+            let Some(holder) = graph.get_entity(node) else {
+                continue;
+            };
+            let via = (node != start).then(|| holder.to_string());
+            for (target, edge) in graph.get_outgoing_edges(node) {
+                if !edge.is_valid() {
+                    continue;
+                }
+                let Some(object) = graph.get_entity(target) else {
+                    continue;
+                };
+                snapshot
+                    .tuples
+                    .insert(RelationshipTuple::new(holder.to_string(), edge.relation.clone(), object.to_string()));
+
+                if INHERITING_RELATIONS.contains(&edge.relation.as_str()) {
+                    if depth < max_depth && visited.insert(target) {
+                        queue.push_back((target, depth + 1));
+                    }
+                } else {
+                    // Breadth-first, so the first grant seen is the shallowest
+                    snapshot
+                        .permissions
+                        .entry((edge.relation.clone(), object.to_string()))
+                        .or_insert_with(|| EffectivePermission {
+                            relation: edge.relation.clone(),
+                            object: object.to_string(),
+                            via: via.clone(),
+                        });
+                }
+            }
+        }
+        snapshot
+    }
+}
+
+/// What changed for one subject between two snapshots
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphDiff {
+    pub subject: String,
+    pub from: String,
+    pub to: String,
+    /// Tuples as `user#relation@object`
+    pub added_edges: Vec<String>,
+    pub removed_edges: Vec<String>,
+    pub granted: Vec<EffectivePermission>,
+    /// `via` is how the access was held before it was lost
+    pub revoked: Vec<EffectivePermission>,
+}
+
+impl GraphDiff {
+    /// `from` and `to` label the two snapshots in the report
+    pub fn between(before: &SubjectSnapshot, after: &SubjectSnapshot, from: &str, to: &str) -> Self {
+        let granted = after
+            .permissions
+            .iter()
+            .filter(|(key, _)| !before.permissions.contains_key(*key))
+            .map(|(_, permission)| permission.clone())
+            .collect();
+        let revoked = before
+            .permissions
+            .iter()
+            .filter(|(key, _)| !after.permissions.contains_key(*key))
+            .map(|(_, permission)| permission.clone())
+            .collect();
+        Self {
+            subject: after.subject.clone(),
+            from: from.to_string(),
+            to: to.to_string(),
+            added_edges: after.tuples.difference(&before.tuples).map(|t| t.to_string()).collect(),
+            removed_edges: before.tuples.difference(&after.tuples).map(|t| t.to_string()).collect(),
+            granted,
+            revoked,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added_edges.is_empty() && self.removed_edges.is_empty() && self.granted.is_empty() && self.revoked.is_empty()
+    }
+
+    /// Plain-text summary for compliance reports
+    pub fn report(&self) -> String {
+        let mut out = format!("Authorization changes for {}\nFrom {} to {}\n", self.subject, self.from, self.to);
+        if self.is_empty() {
+            out.push_str("\nNo changes.\n");
+            return out;
+        }
+        section(&mut out, "Access granted", &self.granted, permission_line);
+        section(&mut out, "Access revoked", &self.revoked, permission_line);
+        section(&mut out, "Relationships added", &self.added_edges, |t| t.clone());
+        section(&mut out, "Relationships removed", &self.removed_edges, |t| t.clone());
+        out
+    }
+}
+
+fn permission_line(permission: &EffectivePermission) -> String {
+    match &permission.via {
+        Some(via) => format!("{} on {} (via {})", permission.relation, permission.object, via),
+        None => format!("{} on {} (direct)", permission.relation, permission.object),
+    }
+}
+
+fn section<T>(out: &mut String, title: &str, items: &[T], line: impl Fn(&T) -> String) {
+    let _ = write!(out, "\n{} ({}):\n", title, items.len());
+    if items.is_empty() {
+        out.push_str("  none\n");
+    }
+    for item in items {
+        let _ = writeln!(out, "  - {}", line(item));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::Relationship;
+
+    fn snapshot(tuples: &[(&str, &str, &str)]) -> SubjectSnapshot {
+        let graph = AuthorizationGraph::build_from_relationships(
+            tuples
+                .iter()
+                .map(|(u, r, o)| Relationship::new(u.to_string(), r.to_string(), o.to_string()))
+                .collect(),
+        );
+        SubjectSnapshot::from_graph(&graph, "user:alice", 10)
+    }
+
+    #[test]
+    fn test_snapshot_follows_groups_and_roles_only() {
+        let snapshot = snapshot(&[
+            ("user:alice", "member", "group:eng"),
+            ("group:eng", "view", "document:1"),
+            ("group:eng", "has_role", "role:auditor"),
+            ("role:auditor", "read", "resource:audit_log"),
+            ("user:alice", "edit", "document:2"),
+            ("user:bob", "view", "document:3"),
+        ]);
+        assert_eq!(snapshot.tuples.len(), 5);
+        let via = |relation: &str, object: &str| {
+            snapshot.permissions[&(relation.to_string(), object.to_string())].via.clone()
+        };
+        assert_eq!(via("edit", "document:2"), None);
+        assert_eq!(via("view", "document:1"), Some("group:eng".to_string()));
+        assert_eq!(via("read", "resource:audit_log"), Some("role:auditor".to_string()));
+        assert!(!snapshot.permissions.contains_key(&("view".to_string(), "document:3".to_string())));
+    }
+
+    #[test]
+    fn test_diff_reports_edges_and_access_changes() {
+        let before = snapshot(&[("user:alice", "member", "group:eng"), ("group:eng", "view", "document:1")]);
+        let after = snapshot(&[("user:alice", "member", "group:ops"), ("group:ops", "edit", "document:1")]);
+        let diff = GraphDiff::between(&before, &after, "t0", "t1");
+
+        assert_eq!(diff.added_edges, vec!["group:ops#edit@document:1", "user:alice#member@group:ops"]);
+        assert_eq!(diff.removed_edges, vec!["group:eng#view@document:1", "user:alice#member@group:eng"]);
+        assert_eq!(diff.granted.len(), 1);
+        assert_eq!(diff.revoked[0].via.as_deref(), Some("group:eng"));
+
+        let report = diff.report();
+        assert!(report.contains("Access granted (1):\n  - edit on document:1 (via group:ops)"));
+        assert!(report.contains("Access revoked (1):\n  - view on document:1 (via group:eng)"));
+        assert!(GraphDiff::between(&after, &after, "t1", "t1").report().contains("No changes."));
+    }
+}
@@ -192,13 +192,19 @@ pub struct RelationshipEdge {
     pub is_active: bool,
     pub metadata: Value,
     pub relationship_id: Uuid,
+    pub organization_id: Option<Uuid>,
 }
 
 impl RelationshipEdge {
     /// Check if edge is currently valid (not expired, active, within validity window)
     pub fn is_valid(&self) -> bool {
+        self.invalid_reason().is_none()
+    }
+
+    /// Why the edge is not currently valid, if it is not
+    pub fn invalid_reason(&self) -> Option<String> {
         if !self.is_active {
-            return false;
+            return Some("revoked".to_string());
         }
         
         let now = Utc::now();
@@ -206,18 +212,18 @@ impl RelationshipEdge {
         // Check valid_from
         if let Some(valid_from) = self.valid_from {
             if now < valid_from {
-                return false;
+                return Some(format!("not valid until {}", valid_from.to_rfc3339()));
             }
         }
         
         // Check expires_at
         if let Some(expires_at) = self.expires_at {
             if now >= expires_at {
-                return false;
+                return Some(format!("expired at {}", expires_at.to_rfc3339()));
             }
         }
         
-        true
+        None
     }
     
     /// Check if edge matches a specific relation
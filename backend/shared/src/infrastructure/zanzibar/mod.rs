@@ -1,3 +1,4 @@
+pub mod bootstrap;
 pub mod checker;
 pub mod relationship_store;
 pub mod tuple;
@@ -5,12 +6,18 @@ pub mod graph_types;
 pub mod graph_builder;
 pub mod graph_checker;
 pub mod graph_cache;
+pub mod graph_diff;
+pub mod schema;
+pub mod ui_visibility;
 
 pub use checker::PermissionChecker;
 pub use relationship_store::RelationshipStore;
 pub use tuple::RelationshipTuple;
 pub use graph_types::{EntityType, GraphNode, RelationshipEdge};
 pub use graph_builder::AuthorizationGraph;
-pub use graph_checker::GraphPermissionChecker;
+pub use graph_checker::{CheckExplanation, GraphPermissionChecker, UsersetNode};
 pub use graph_cache::GraphCache;
+pub use graph_diff::{EffectivePermission, GraphDiff, SubjectSnapshot};
+pub use schema::AuthorizationSchema;
+pub use ui_visibility::{UiPermissions, UiVisibilityCache, VisibleUi};
 
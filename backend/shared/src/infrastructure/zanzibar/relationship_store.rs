@@ -1,18 +1,65 @@
 use crate::domain::entities::Relationship;
-use crate::domain::repositories::RelationshipRepository;
-use crate::shared::AppResult;
+use crate::domain::repositories::{RelationshipFilter, RelationshipRepository, SnapshotPoint};
+use crate::domain::value_objects::ConsistencyToken;
+use crate::infrastructure::zanzibar::{AuthorizationGraph, AuthorizationSchema, GraphCache, GraphDiff, RelationshipTuple, SubjectSnapshot};
+use crate::shared::{AppError, AppResult};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::Arc;
 use tracing;
 
+/// Group and role nesting followed by [`RelationshipStore::snapshot_subject`],
+/// as for graph checks
+const SNAPSHOT_MAX_DEPTH: usize = 10;
+
 pub struct RelationshipStore {
     repository: Box<dyn RelationshipRepository>,
+    schema: Option<Arc<AuthorizationSchema>>,
+    graph_cache: Option<Arc<GraphCache>>,
 }
 
 impl RelationshipStore {
     pub fn new(repository: Box<dyn RelationshipRepository>) -> Self {
-        Self { repository }
+        Self { repository, schema: None, graph_cache: None }
+    }
+
+    /// Apply this store's writes to `graph_cache` as they are made
+    pub fn with_graph_cache(mut self, graph_cache: Arc<GraphCache>) -> Self {
+        self.graph_cache = Some(graph_cache);
+        self
+    }
+
+    /// Pass a written row on to the graph cache
+    fn stored(&self, relationship: Relationship) -> Relationship {
+        if let Some(cache) = &self.graph_cache {
+            cache.relationship_stored(&relationship);
+        }
+        relationship
+    }
+
+    /// Reject writes of object types and relations the schema does not declare
+    pub fn with_schema(mut self, schema: Option<Arc<AuthorizationSchema>>) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    pub fn schema(&self) -> Option<&Arc<AuthorizationSchema>> {
+        self.schema.as_ref()
+    }
+
+    /// Check a tuple as a write would, without writing it
+    pub fn validate_tuple(&self, tuple: &RelationshipTuple) -> AppResult<()> {
+        tuple.validate()?;
+        self.validate_against_schema(&tuple.user, &tuple.relation, &tuple.object)
+    }
+
+    fn validate_against_schema(&self, user: &str, relation: &str, object: &str) -> AppResult<()> {
+        match &self.schema {
+            Some(schema) => schema.validate_tuple(user, relation, object),
+            None => Ok(()),
+        }
     }
 
     pub async fn add(&self, user: &str, relation: &str, object: &str) -> AppResult<()> {
@@ -27,6 +74,7 @@ impl RelationshipStore {
         object: &str,
         organization_id: Option<Uuid>,
     ) -> AppResult<()> {
+        self.validate_against_schema(user, relation, object)?;
         tracing::debug!("Creating relationship: {} → {} → {} [org: {:?}]", user, relation, object, organization_id);
         let relationship = Relationship::new_with_organization(
             user.to_string(),
@@ -34,7 +82,7 @@ impl RelationshipStore {
             object.to_string(),
             organization_id,
         );
-        match self.repository.create(relationship).await {
+        match self.repository.create(relationship).await.map(|created| self.stored(created)) {
             Ok(created) => {
                 tracing::debug!(
                     "Successfully created relationship: {} → {} → {} [org: {:?}] (id: {})",
@@ -68,6 +116,7 @@ impl RelationshipStore {
         object: &str,
         expires_at: Option<DateTime<Utc>>,
     ) -> AppResult<()> {
+        self.validate_against_schema(user, relation, object)?;
         let relationship = if let Some(exp) = expires_at {
             Relationship::new_with_expiration(
                 user.to_string(),
@@ -83,7 +132,7 @@ impl RelationshipStore {
             )
         };
         
-        self.repository.create(relationship).await?;
+        self.stored(self.repository.create(relationship).await?);
         Ok(())
     }
     
@@ -96,6 +145,8 @@ impl RelationshipStore {
         valid_from: DateTime<Utc>,
         expires_at: Option<DateTime<Utc>>,
     ) -> AppResult<()> {
+        self.validate_against_schema(user, relation, object)?;
+        Relationship::validate_window(Some(valid_from), expires_at).map_err(AppError::Validation)?;
         let relationship = Relationship::new_with_validity(
             user.to_string(),
             relation.to_string(),
@@ -104,20 +155,34 @@ impl RelationshipStore {
             expires_at,
         );
         
-        self.repository.create(relationship).await?;
+        self.stored(self.repository.create(relationship).await?);
         Ok(())
     }
     
     /// Add relationship with metadata (optionally encrypted)
+    ///
+    /// A future `valid_from` schedules the grant; it is ignored by checks
+    /// until then.
     pub async fn add_with_metadata(
         &self,
         user: &str,
         relation: &str,
         object: &str,
         metadata: Option<Value>,
+        valid_from: Option<DateTime<Utc>>,
         expires_at: Option<DateTime<Utc>>,
     ) -> AppResult<()> {
-        let mut relationship = if let Some(exp) = expires_at {
+        self.validate_against_schema(user, relation, object)?;
+        Relationship::validate_window(valid_from, expires_at).map_err(AppError::Validation)?;
+        let mut relationship = if let Some(from) = valid_from {
+            Relationship::new_with_validity(
+                user.to_string(),
+                relation.to_string(),
+                object.to_string(),
+                from,
+                expires_at,
+            )
+        } else if let Some(exp) = expires_at {
             Relationship::new_with_expiration(
                 user.to_string(),
                 relation.to_string(),
@@ -136,7 +201,7 @@ impl RelationshipStore {
             relationship.set_metadata(meta, false); // Encryption handled in service layer
         }
         
-        self.repository.create(relationship).await?;
+        self.stored(self.repository.create(relationship).await?);
         Ok(())
     }
     
@@ -153,11 +218,31 @@ impl RelationshipStore {
             .await?
         {
             rel.extend_expiration(new_expires_at);
-            self.repository.update(rel).await?;
+            self.stored(self.repository.update(rel).await?);
         }
         Ok(())
     }
     
+    /// Move a grant's validity window
+    ///
+    /// `None` for `valid_from` keeps the current start. Fails if the grant
+    /// does not exist or the new window is empty.
+    pub async fn reschedule(
+        &self,
+        user: &str,
+        relation: &str,
+        object: &str,
+        valid_from: Option<DateTime<Utc>>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> AppResult<Relationship> {
+        let mut rel = self.repository
+            .find_by_user_object_relation(user, object, relation)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Relationship {}#{}@{} not found", user, relation, object)))?;
+        rel.reschedule(valid_from, expires_at).map_err(AppError::Validation)?;
+        Ok(self.stored(self.repository.update(rel).await?))
+    }
+
     /// Revoke relationship (soft delete)
     pub async fn revoke(
         &self,
@@ -171,7 +256,7 @@ impl RelationshipStore {
             .await?
         {
             rel.revoke(revoked_by);
-            self.repository.update(rel).await?;
+            self.stored(self.repository.update(rel).await?);
         }
         Ok(())
     }
@@ -189,7 +274,7 @@ impl RelationshipStore {
             .await?
         {
             rel.soft_delete(deleted_by);
-            self.repository.update(rel).await?;
+            self.stored(self.repository.update(rel).await?);
         }
         Ok(())
     }
@@ -199,6 +284,81 @@ impl RelationshipStore {
         self.soft_delete(user, relation, object, None).await
     }
 
+    /// Write many tuples in one transaction
+    ///
+    /// Fails without writing anything if any tuple is malformed or, with a
+    /// schema, uses an undeclared relation.
+    pub async fn write_tuples(&self, tuples: &[RelationshipTuple]) -> AppResult<ConsistencyToken> {
+        self.apply_tuples(tuples, &[], None).await
+    }
+
+    /// Which of `tuples` already exist
+    pub async fn existing_tuples(&self, tuples: &[RelationshipTuple]) -> AppResult<HashSet<RelationshipTuple>> {
+        let keys: Vec<(String, String, String)> = tuples
+            .iter()
+            .map(|t| (t.user.clone(), t.relation.clone(), t.object.clone()))
+            .collect();
+        let existing = self.repository.find_existing_tuples(&keys).await?;
+        Ok(existing
+            .into_iter()
+            .map(|(user, relation, object)| RelationshipTuple::new(user, relation, object))
+            .collect())
+    }
+
+    /// Soft-delete many tuples in one transaction
+    pub async fn delete_tuples(&self, tuples: &[RelationshipTuple]) -> AppResult<ConsistencyToken> {
+        self.apply_tuples(&[], tuples, None).await
+    }
+
+    /// Apply writes and deletes together, e.g. a diff from [`RelationshipTuple::diff`]
+    ///
+    /// All tuples are validated before anything is written; the batch is
+    /// applied atomically and its consistency token returned.
+    pub async fn apply_tuples(
+        &self,
+        writes: &[RelationshipTuple],
+        deletes: &[RelationshipTuple],
+        deleted_by: Option<Uuid>,
+    ) -> AppResult<ConsistencyToken> {
+        for tuple in writes.iter().chain(deletes) {
+            tuple.validate().map_err(|e| {
+                AppError::Validation(format!("Invalid tuple {}: {}", tuple.to_string(), e))
+            })?;
+        }
+        for tuple in writes {
+            self.validate_against_schema(&tuple.user, &tuple.relation, &tuple.object)
+                .map_err(|e| AppError::Validation(format!("Invalid tuple {}: {}", tuple.to_string(), e)))?;
+        }
+
+        let relationships: Vec<Relationship> = writes
+            .iter()
+            .map(|t| Relationship::new(t.user.clone(), t.relation.clone(), t.object.clone()))
+            .collect();
+        let delete_keys: Vec<(String, String, String)> = deletes
+            .iter()
+            .map(|t| (t.user.clone(), t.relation.clone(), t.object.clone()))
+            .collect();
+
+        let token = match &self.graph_cache {
+            Some(cache) => {
+                let token = self
+                    .repository
+                    .apply_batch(relationships.clone(), delete_keys.clone(), deleted_by)
+                    .await?;
+                cache.batch_applied(&relationships, &delete_keys);
+                token
+            }
+            None => self.repository.apply_batch(relationships, delete_keys, deleted_by).await?,
+        };
+        tracing::debug!(
+            "Applied relationship batch: {} write(s), {} delete(s) at {}",
+            writes.len(),
+            deletes.len(),
+            token
+        );
+        Ok(token)
+    }
+
     /// Check relationship (only returns true if valid and not expired/deleted)
     pub async fn check(&self, user: &str, relation: &str, object: &str) -> AppResult<bool> {
         self.check_with_organization(user, relation, object, None).await
@@ -250,6 +410,45 @@ impl RelationshipStore {
         Ok(all.into_iter().filter(|r| r.is_valid()).collect())
     }
     
+    /// Read relationships by subject ("what can this user access?") or by
+    /// object ("who has access to this?"), optionally narrowed to one relation
+    pub async fn read_relationships(&self, filter: &RelationshipFilter) -> AppResult<Vec<Relationship>> {
+        self.repository.find_by_filter(filter).await
+    }
+
+    /// What `subject` could reach at `point`
+    ///
+    /// Grants are judged by their window at that time; a token point is
+    /// judged at the current time, as tokens carry no timestamp.
+    pub async fn snapshot_subject(&self, subject: &str, point: &SnapshotPoint) -> AppResult<SubjectSnapshot> {
+        let at = match point {
+            SnapshotPoint::At(at) => *at,
+            SnapshotPoint::Token(_) => Utc::now(),
+        };
+        let relationships = self
+            .repository
+            .list_as_of(point)
+            .await?
+            .into_iter()
+            .filter(|r| r.is_valid_at(at))
+            .map(|mut r| {
+                // Already judged at `at`; the graph would judge the window at now
+                r.valid_from = None;
+                r.expires_at = None;
+                r
+            })
+            .collect();
+        let graph = AuthorizationGraph::build_from_relationships(relationships);
+        Ok(SubjectSnapshot::from_graph(&graph, subject, SNAPSHOT_MAX_DEPTH))
+    }
+
+    /// Relationships and access `subject` gained or lost between two points
+    pub async fn diff_subject(&self, subject: &str, from: &SnapshotPoint, to: &SnapshotPoint) -> AppResult<GraphDiff> {
+        let before = self.snapshot_subject(subject, from).await?;
+        let after = self.snapshot_subject(subject, to).await?;
+        Ok(GraphDiff::between(&before, &after, &from.to_string(), &to.to_string()))
+    }
+
     /// Get repository (for graph building)
     pub fn repository(&self) -> &dyn RelationshipRepository {
         self.repository.as_ref()
@@ -0,0 +1,217 @@
+use crate::shared::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// Declared object types, their relations and how relations compose
+///
+/// Loaded from a JSON file, for example:
+///
+/// ```json
+/// {
+///   "types": {
+///     "document": {
+///       "relations": {
+///         "parent": {},
+///         "owner": {},
+///         "editor": { "computed_usersets": ["owner"] },
+///         "viewer": {
+///           "computed_usersets": ["editor"],
+///           "tuple_to_usersets": [{ "tupleset": "parent", "computed_userset": "viewer" }]
+///         }
+///       }
+///     }
+///   }
+/// }
+/// ```
+///
+/// Here an owner is also an editor and a viewer, and viewers of a
+/// document's parent folder are viewers of the document.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuthorizationSchema {
+    pub types: BTreeMap<String, ObjectTypeDefinition>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ObjectTypeDefinition {
+    pub relations: BTreeMap<String, RelationDefinition>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RelationDefinition {
+    /// Relations on the same object that imply this one
+    #[serde(default)]
+    pub computed_usersets: Vec<String>,
+    /// Relations inherited from objects linked through another relation
+    #[serde(default)]
+    pub tuple_to_usersets: Vec<TupleToUserset>,
+}
+
+/// Grants `relation` on an object to whoever has `computed_userset` on any
+/// object linked to it via `tupleset` (stored as `linked#tupleset@object`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TupleToUserset {
+    pub tupleset: String,
+    pub computed_userset: String,
+}
+
+/// Type of an entity string: `document:1` → `document`; for hierarchical
+/// objects the last segment counts (`organization:1/app:admin-ui` → `app`)
+pub fn object_type(entity: &str) -> Option<&str> {
+    let last = entity.rsplit('/').next()?;
+    let (object_type, id) = last.split_once(':')?;
+    (!object_type.is_empty() && !id.is_empty()).then_some(object_type)
+}
+
+impl AuthorizationSchema {
+    pub fn from_json(json: &str) -> AppResult<Self> {
+        let schema: Self = serde_json::from_str(json)
+            .map_err(|e| AppError::Configuration(format!("Invalid authorization schema: {}", e)))?;
+        schema.validate()?;
+        Ok(schema)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> AppResult<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            AppError::Configuration(format!("Failed to read authorization schema {}: {}", path.display(), e))
+        })?;
+        Self::from_json(&json)
+    }
+
+    /// Check that every rewrite refers to a relation declared on its type
+    fn validate(&self) -> AppResult<()> {
+        for (type_name, definition) in &self.types {
+            for (relation, rules) in &definition.relations {
+                let referenced = rules
+                    .computed_usersets
+                    .iter()
+                    .chain(rules.tuple_to_usersets.iter().map(|ttu| &ttu.tupleset));
+                for name in referenced {
+                    if !definition.relations.contains_key(name) {
+                        return Err(AppError::Configuration(format!(
+                            "Relation {}#{} refers to undeclared relation '{}'",
+                            type_name, relation, name
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn relation(&self, object: &str, relation: &str) -> Option<&RelationDefinition> {
+        self.types.get(object_type(object)?)?.relations.get(relation)
+    }
+
+    /// Reject a tuple whose object type or relation is not declared
+    ///
+    /// The super-admin wildcard `*#*@*` is always allowed.
+    pub fn validate_tuple(&self, user: &str, relation: &str, object: &str) -> AppResult<()> {
+        if relation == "*" && object == "*" {
+            return Ok(());
+        }
+        if user.is_empty() {
+            return Err(AppError::Validation("Relationship subject cannot be empty".to_string()));
+        }
+        let type_name = object_type(object)
+            .ok_or_else(|| AppError::Validation(format!("Object '{}' has no type", object)))?;
+        let definition = self
+            .types
+            .get(type_name)
+            .ok_or_else(|| AppError::Validation(format!("Object type '{}' is not in the schema", type_name)))?;
+        if !definition.relations.contains_key(relation) {
+            return Err(AppError::Validation(format!(
+                "Relation '{}' is not defined for object type '{}'",
+                relation, type_name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Relations whose tuples grant `relation` on `object` directly: the
+    /// relation itself plus everything implied through computed usersets
+    pub fn implying_relations(&self, object: &str, relation: &str) -> HashSet<String> {
+        let mut found = HashSet::from([relation.to_string()]);
+        let mut pending = vec![relation.to_string()];
+        while let Some(current) = pending.pop() {
+            if let Some(rules) = self.relation(object, &current) {
+                for implied_by in &rules.computed_usersets {
+                    if found.insert(implied_by.clone()) {
+                        pending.push(implied_by.clone());
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    /// Tuple-to-userset rules for `relation` and every relation implying it
+    pub fn tuple_to_usersets(&self, object: &str, relation: &str) -> Vec<&TupleToUserset> {
+        self.implying_relations(object, relation)
+            .iter()
+            .filter_map(|r| self.relation(object, r))
+            .flat_map(|rules| rules.tuple_to_usersets.iter())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"{
+        "types": {
+            "folder": { "relations": { "viewer": {} } },
+            "document": {
+                "relations": {
+                    "parent": {},
+                    "owner": {},
+                    "editor": { "computed_usersets": ["owner"] },
+                    "viewer": {
+                        "computed_usersets": ["editor"],
+                        "tuple_to_usersets": [{ "tupleset": "parent", "computed_userset": "viewer" }]
+                    }
+                }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_object_type_of_simple_and_hierarchical_entities() {
+        assert_eq!(object_type("document:1"), Some("document"));
+        assert_eq!(object_type("organization:1/app:admin-ui"), Some("app"));
+        assert_eq!(object_type("*"), None);
+        assert_eq!(object_type("document:"), None);
+    }
+
+    #[test]
+    fn test_implications_are_transitive() {
+        let schema = AuthorizationSchema::from_json(SCHEMA).unwrap();
+        let relations = schema.implying_relations("document:1", "viewer");
+        assert_eq!(
+            relations,
+            HashSet::from(["viewer".to_string(), "editor".to_string(), "owner".to_string()])
+        );
+        assert_eq!(schema.implying_relations("document:1", "owner").len(), 1);
+        assert_eq!(schema.tuple_to_usersets("document:1", "viewer").len(), 1);
+        assert!(schema.tuple_to_usersets("document:1", "editor").is_empty());
+    }
+
+    #[test]
+    fn test_validate_tuple_rejects_undeclared_relations() {
+        let schema = AuthorizationSchema::from_json(SCHEMA).unwrap();
+        assert!(schema.validate_tuple("user:1", "editor", "document:1").is_ok());
+        assert!(schema.validate_tuple("user:1", "admin", "document:1").is_err());
+        assert!(schema.validate_tuple("user:1", "viewer", "spreadsheet:1").is_err());
+        assert!(schema.validate_tuple("user:1", "*", "*").is_ok());
+    }
+
+    #[test]
+    fn test_schema_with_dangling_reference_is_rejected() {
+        let json = r#"{ "types": { "document": { "relations": {
+            "viewer": { "computed_usersets": ["editor"] }
+        } } } }"#;
+        assert!(AuthorizationSchema::from_json(json).is_err());
+    }
+}
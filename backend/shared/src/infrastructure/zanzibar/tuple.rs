@@ -1,7 +1,8 @@
 use crate::domain::entities::Relationship;
 use crate::shared::AppResult;
+use std::collections::HashSet;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct RelationshipTuple {
     pub user: String,
     pub relation: String,
@@ -37,5 +38,57 @@ impl RelationshipTuple {
         }
         Ok(())
     }
+
+    /// Tuples to write and to delete to turn `current` into `desired`
+    pub fn diff(current: &[RelationshipTuple], desired: &[RelationshipTuple]) -> (Vec<RelationshipTuple>, Vec<RelationshipTuple>) {
+        let current_set: HashSet<&RelationshipTuple> = current.iter().collect();
+        let desired_set: HashSet<&RelationshipTuple> = desired.iter().collect();
+
+        let mut seen = HashSet::new();
+        let writes = desired
+            .iter()
+            .filter(|t| !current_set.contains(t) && seen.insert(*t))
+            .cloned()
+            .collect();
+        let mut seen = HashSet::new();
+        let deletes = current
+            .iter()
+            .filter(|t| !desired_set.contains(t) && seen.insert(*t))
+            .cloned()
+            .collect();
+        (writes, deletes)
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tuple(user: &str, relation: &str, object: &str) -> RelationshipTuple {
+        RelationshipTuple::new(user.to_string(), relation.to_string(), object.to_string())
+    }
+
+    #[test]
+    fn test_diff_computes_writes_and_deletes() {
+        let current = vec![
+            tuple("user:alice", "member", "group:team"),
+            tuple("user:bob", "member", "group:team"),
+        ];
+        let desired = vec![
+            tuple("user:bob", "member", "group:team"),
+            tuple("user:carol", "member", "group:team"),
+            tuple("user:carol", "member", "group:team"),
+        ];
+
+        let (writes, deletes) = RelationshipTuple::diff(&current, &desired);
+        assert_eq!(writes, vec![tuple("user:carol", "member", "group:team")]);
+        assert_eq!(deletes, vec![tuple("user:alice", "member", "group:team")]);
+    }
+
+    #[test]
+    fn test_diff_of_equal_sets_is_empty() {
+        let tuples = vec![tuple("user:alice", "member", "group:team")];
+        let (writes, deletes) = RelationshipTuple::diff(&tuples, &tuples);
+        assert!(writes.is_empty() && deletes.is_empty());
+    }
+}
@@ -0,0 +1,239 @@
+//! Which UI pages, buttons and fields a user may see
+//!
+//! A page is visible with `can_view` on `page:{name}`. Within a visible
+//! page, a button needs `can_click` on `button:{button_id}`, plus
+//! `can_call` on the API it triggers when its metadata names one as
+//! `"api": {"method": "POST", "endpoint": "/v1/..."}`. A field is visible
+//! with `can_view` or `can_edit` on `field:{field_id}`, editable with the
+//! latter.
+
+use crate::domain::entities::{UiButton, UiField, UiPage};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+pub const PAGE_VIEW: &str = "can_view";
+pub const BUTTON_CLICK: &str = "can_click";
+pub const API_CALL: &str = "can_call";
+pub const FIELD_VIEW: &str = "can_view";
+pub const FIELD_EDIT: &str = "can_edit";
+
+const DEFAULT_TTL_SECONDS: i64 = 60;
+const DEFAULT_MAX_USERS: usize = 10_000;
+
+/// Everything one user may see, as the frontend renders it
+#[derive(Debug, Clone, Serialize)]
+pub struct VisibleUi {
+    pub user_id: Uuid,
+    pub pages: Vec<VisiblePage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VisiblePage {
+    pub id: Uuid,
+    pub name: String,
+    pub path: String,
+    pub description: Option<String>,
+    pub buttons: Vec<VisibleButton>,
+    pub fields: Vec<VisibleField>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VisibleButton {
+    pub id: Uuid,
+    pub button_id: String,
+    pub label: String,
+    pub action: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VisibleField {
+    pub id: Uuid,
+    pub field_id: String,
+    pub label: String,
+    pub field_type: String,
+    pub editable: bool,
+}
+
+/// A user's effective `(relation, object)` permissions
+pub struct UiPermissions<'a>(pub &'a HashSet<(String, String)>);
+
+impl UiPermissions<'_> {
+    fn has(&self, relation: &str, object: String) -> bool {
+        self.0.contains(&(relation.to_string(), object))
+    }
+
+    pub fn page_visible(&self, page: &UiPage) -> bool {
+        self.has(PAGE_VIEW, page.to_zanzibar_resource())
+    }
+
+    pub fn button(&self, button: &UiButton) -> Option<VisibleButton> {
+        if !self.has(BUTTON_CLICK, button.to_zanzibar_resource()) {
+            return None;
+        }
+        if let Some(api) = button.metadata.get("api") {
+            let method = api.get("method").and_then(|m| m.as_str()).unwrap_or_default();
+            let endpoint = api.get("endpoint").and_then(|e| e.as_str()).unwrap_or_default();
+            if !self.has(API_CALL, format!("api:{}:{}", method.to_uppercase(), endpoint)) {
+                return None;
+            }
+        }
+        Some(VisibleButton {
+            id: button.id,
+            button_id: button.button_id.clone(),
+            label: button.label.clone(),
+            action: button.action.clone(),
+        })
+    }
+
+    pub fn field(&self, field: &UiField) -> Option<VisibleField> {
+        let editable = self.has(FIELD_EDIT, field.to_zanzibar_resource());
+        (editable || self.has(FIELD_VIEW, field.to_zanzibar_resource())).then(|| VisibleField {
+            id: field.id,
+            field_id: field.field_id.clone(),
+            label: field.label.clone(),
+            field_type: field.field_type.clone(),
+            editable,
+        })
+    }
+
+    pub fn page(&self, page: &UiPage, buttons: &[UiButton], fields: &[UiField]) -> VisiblePage {
+        VisiblePage {
+            id: page.id,
+            name: page.name.clone(),
+            path: page.path.clone(),
+            description: page.description.clone(),
+            buttons: buttons.iter().filter_map(|b| self.button(b)).collect(),
+            fields: fields.iter().filter_map(|f| self.field(f)).collect(),
+        }
+    }
+}
+
+struct CacheEntry {
+    stamp: String,
+    expires_at: DateTime<Utc>,
+    ui: Arc<VisibleUi>,
+}
+
+/// Resolved [`VisibleUi`] per user
+///
+/// Entries are stored with a stamp that changes whenever a relationship or
+/// UI entity does, and are only returned for the same stamp, so a change
+/// made through any instance invalidates them. The TTL catches what no
+/// write marks: relationships that start or expire with time.
+pub struct UiVisibilityCache {
+    entries: RwLock<HashMap<Uuid, CacheEntry>>,
+    ttl: Duration,
+    max_users: usize,
+}
+
+impl Default for UiVisibilityCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_TTL_SECONDS, DEFAULT_MAX_USERS)
+    }
+}
+
+impl UiVisibilityCache {
+    /// A TTL of 0 disables caching
+    pub fn new(ttl_seconds: i64, max_users: usize) -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+            ttl: Duration::seconds(ttl_seconds),
+            max_users,
+        }
+    }
+
+    /// TTL from `UI_VISIBILITY_CACHE_TTL_SECONDS`
+    pub fn from_env() -> Self {
+        let ttl = std::env::var("UI_VISIBILITY_CACHE_TTL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_TTL_SECONDS);
+        Self::new(ttl, DEFAULT_MAX_USERS)
+    }
+
+    pub fn get(&self, user_id: Uuid, stamp: &str) -> Option<Arc<VisibleUi>> {
+        let entries = self.entries.read().unwrap();
+        entries
+            .get(&user_id)
+            .filter(|entry| entry.stamp == stamp && Utc::now() < entry.expires_at)
+            .map(|entry| Arc::clone(&entry.ui))
+    }
+
+    pub fn insert(&self, user_id: Uuid, stamp: String, ui: Arc<VisibleUi>) {
+        if self.ttl <= Duration::zero() {
+            return;
+        }
+        let now = Utc::now();
+        let mut entries = self.entries.write().unwrap();
+        if entries.len() >= self.max_users && !entries.contains_key(&user_id) {
+            entries.retain(|_, entry| entry.stamp == stamp && now < entry.expires_at);
+            if entries.len() >= self.max_users {
+                entries.clear();
+            }
+        }
+        entries.insert(user_id, CacheEntry { stamp, expires_at: now + self.ttl, ui });
+    }
+
+    pub fn invalidate_user(&self, user_id: Uuid) {
+        self.entries.write().unwrap().remove(&user_id);
+    }
+
+    pub fn clear(&self) {
+        self.entries.write().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn permissions(pairs: &[(&str, &str)]) -> HashSet<(String, String)> {
+        pairs.iter().map(|(r, o)| (r.to_string(), o.to_string())).collect()
+    }
+
+    #[test]
+    fn test_buttons_need_their_api_and_fields_report_editability() {
+        let page = UiPage::new("users".to_string(), "/users".to_string(), None);
+        let create = UiButton::new(page.id, "create-user".to_string(), "Create".to_string(), None);
+        let mut delete = UiButton::new(page.id, "delete-user".to_string(), "Delete".to_string(), None);
+        delete.metadata = json!({"api": {"method": "delete", "endpoint": "/v1/admin/users/:id"}});
+        let email = UiField::new(page.id, "email".to_string(), "Email".to_string(), "text".to_string());
+        let ssn = UiField::new(page.id, "ssn".to_string(), "SSN".to_string(), "text".to_string());
+
+        let granted = permissions(&[
+            ("can_view", "page:users"),
+            ("can_click", "button:create-user"),
+            ("can_click", "button:delete-user"),
+            ("can_edit", "field:email"),
+        ]);
+        let checker = UiPermissions(&granted);
+        assert!(checker.page_visible(&page));
+        let visible = checker.page(&page, &[create, delete.clone()], &[email, ssn]);
+        assert_eq!(visible.buttons.iter().map(|b| b.button_id.as_str()).collect::<Vec<_>>(), vec!["create-user"]);
+        assert_eq!(visible.fields.len(), 1);
+        assert!(visible.fields[0].editable);
+
+        let mut with_api = granted.clone();
+        with_api.insert(("can_call".to_string(), "api:DELETE:/v1/admin/users/:id".to_string()));
+        assert!(UiPermissions(&with_api).button(&delete).is_some());
+    }
+
+    #[test]
+    fn test_cache_entries_only_match_their_stamp() {
+        let cache = UiVisibilityCache::default();
+        let user = Uuid::new_v4();
+        cache.insert(user, "7:t1".to_string(), Arc::new(VisibleUi { user_id: user, pages: Vec::new() }));
+        assert!(cache.get(user, "7:t1").is_some());
+        assert!(cache.get(user, "8:t1").is_none());
+        cache.invalidate_user(user);
+        assert!(cache.get(user, "7:t1").is_none());
+
+        let disabled = UiVisibilityCache::new(0, 10);
+        disabled.insert(user, "7:t1".to_string(), Arc::new(VisibleUi { user_id: user, pages: Vec::new() }));
+        assert!(disabled.get(user, "7:t1").is_none());
+    }
+}
@@ -1,11 +1,18 @@
 use std::sync::Arc;
 use sqlx::PgPool;
-use crate::domain::repositories::{SetupRepository, RoleRepository};
+use crate::domain::repositories::{LegacyPatientRepository, SetupRepository, RoleRepository};
 use crate::infrastructure::database::DatabaseService;
 use crate::infrastructure::oidc::TokenManager;
-use crate::infrastructure::zanzibar::{PermissionChecker, RelationshipStore, GraphCache};
-use crate::infrastructure::encryption::DekManager;
+#[cfg(feature = "saml")]
+use crate::infrastructure::saml::SamlServiceProvider;
+use crate::infrastructure::zanzibar::{PermissionChecker, RelationshipStore, GraphCache, UiVisibilityCache};
+use crate::infrastructure::encryption::{CircuitBreaker, DekManager};
 use crate::infrastructure::session::SessionService;
+use crate::infrastructure::events::OutboxRelay;
+use crate::infrastructure::api::concurrency::ConcurrencyLimiter;
+use crate::infrastructure::counters::CounterStore;
+use crate::infrastructure::token_delivery::TokenSender;
+use crate::config::SettingsHandle;
 
 /// Application state that holds shared services and use cases.
 /// Note: Use case types are provided by the consuming crate (e.g., api-service)
@@ -26,6 +33,27 @@ pub struct AppState<LoginUseCase, RefreshTokenUseCase, LogoutUseCase, UserInfoUs
     pub dek_manager: Arc<DekManager>,
     pub role_repository: Arc<dyn RoleRepository>,
     pub graph_cache: Option<Arc<GraphCache>>,
+    /// Resolved UI visibility per user, for `permissions/user/{id}/ui`
+    pub ui_visibility_cache: Arc<UiVisibilityCache>,
     pub session_service: Arc<SessionService>,
+    /// Woken after a use case commits outbox events, for prompt delivery
+    pub outbox_relay: Option<Arc<OutboxRelay>>,
+    /// Current settings; read the runtime section through this on each use
+    pub settings: Arc<SettingsHandle>,
+    /// Read-only legacy patient data; `None` unless a MUMPS gateway is configured
+    pub legacy_patients: Option<Arc<dyn LegacyPatientRepository>>,
+    /// SAML login; `None` unless an IdP is configured
+    #[cfg(feature = "saml")]
+    pub saml: Option<Arc<SamlServiceProvider>>,
+    /// Breaker in front of the key vault; its state is reported on the status page
+    pub vault_breaker: Arc<CircuitBreaker>,
+    /// In-flight request limits; their counts are reported on the status page
+    pub concurrency: Arc<ConcurrencyLimiter>,
+    /// Rate-limit and lockout counters, shared across replicas when in Redis;
+    /// API key issuance is limited through these
+    pub counters: Arc<dyn CounterStore>,
+    /// Emails password reset and verification tokens; `None` unless a mail
+    /// relay is configured
+    pub token_sender: Option<Arc<dyn TokenSender>>,
 }
 
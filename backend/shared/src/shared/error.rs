@@ -1,4 +1,6 @@
 use thiserror::Error;
+use uuid::Uuid;
+use crate::infrastructure::database::mumps::MumpsError;
 use crate::infrastructure::logging::context::LogContext;
 
 #[derive(Error, Debug)]
@@ -27,6 +29,30 @@ pub enum AppError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    /// An update was based on a stale copy of the row; the client should
+    /// refetch and reapply its change
+    #[error("Version conflict: {entity} {id} is at version {current_version}, update was based on {expected_version}")]
+    VersionConflict {
+        entity: String,
+        id: Uuid,
+        expected_version: i64,
+        current_version: i64,
+    },
+
+    /// The legacy MUMPS database could not be reached or returned bad data
+    #[error("Legacy database error: {0}")]
+    Mumps(#[from] MumpsError),
+
+    /// A dependency is failing and calls to it are being short-circuited;
+    /// callers can degrade or answer 503 right away
+    #[error("Service unavailable: {0}")]
+    Unavailable(String),
+
+    /// A dependency did not answer in time; unlike a refusal or a bad
+    /// response, the same call may well succeed if retried
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -41,6 +67,10 @@ pub enum ErrorKind {
     Storage,
     Validation,
     NotFound,
+    Conflict,
+    Mumps,
+    Unavailable,
+    Timeout,
     Internal,
 }
 
@@ -55,6 +85,10 @@ impl From<AppError> for ErrorKind {
             AppError::Storage(_) => ErrorKind::Storage,
             AppError::Validation(_) => ErrorKind::Validation,
             AppError::NotFound(_) => ErrorKind::NotFound,
+            AppError::VersionConflict { .. } => ErrorKind::Conflict,
+            AppError::Mumps(_) => ErrorKind::Mumps,
+            AppError::Unavailable(_) => ErrorKind::Unavailable,
+            AppError::Timeout(_) => ErrorKind::Timeout,
             AppError::Internal(_) => ErrorKind::Internal,
         }
     }
@@ -71,12 +105,35 @@ impl From<&AppError> for ErrorKind {
             AppError::Storage(_) => ErrorKind::Storage,
             AppError::Validation(_) => ErrorKind::Validation,
             AppError::NotFound(_) => ErrorKind::NotFound,
+            AppError::VersionConflict { .. } => ErrorKind::Conflict,
+            AppError::Mumps(_) => ErrorKind::Mumps,
+            AppError::Unavailable(_) => ErrorKind::Unavailable,
+            AppError::Timeout(_) => ErrorKind::Timeout,
             AppError::Internal(_) => ErrorKind::Internal,
         }
     }
 }
 
 impl AppError {
+    /// Reject an update based on `expected_version` when the row is at
+    /// `current_version`
+    pub fn check_version(entity: &str, id: Uuid, expected_version: i64, current_version: i64) -> Result<(), Self> {
+        if expected_version == current_version {
+            return Ok(());
+        }
+        Err(AppError::VersionConflict {
+            entity: entity.to_string(),
+            id,
+            expected_version,
+            current_version,
+        })
+    }
+
+    /// Whether the same call may succeed if tried again later
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, AppError::Unavailable(_) | AppError::Timeout(_))
+    }
+
     /// Log this error with structured fields
     /// 
     /// # Arguments
@@ -133,3 +190,21 @@ impl AppError {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_version() {
+        let id = Uuid::new_v4();
+        assert!(AppError::check_version("User", id, 3, 3).is_ok());
+
+        let err = AppError::check_version("User", id, 2, 3).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Conflict);
+        assert!(matches!(
+            err,
+            AppError::VersionConflict { expected_version: 2, current_version: 3, .. }
+        ));
+    }
+}
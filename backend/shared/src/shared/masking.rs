@@ -43,3 +43,164 @@ pub fn mask_ssn(ssn: &str) -> String {
     }
 }
 
+
+/// Prefix identifying HMAC'd values
+pub const HMAC_PREFIX: &str = "hmac-sha256:";
+
+/// Replace a sensitive value with a keyed hash
+///
+/// Unlike the display masks above nothing of the value is revealed, but equal
+/// inputs hash to equal outputs under the same key, so occurrences can be
+/// correlated (e.g. by HMAC'ing a known value with the same key).
+pub fn hmac_field(key: &[u8], value: &str) -> String {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, key);
+    let tag = ring::hmac::sign(&key, value.as_bytes());
+    format!("{}{}", HMAC_PREFIX, hex::encode(tag.as_ref()))
+}
+
+/// HMAC every scalar leaf of a JSON value, keeping its structure
+///
+/// Object keys are preserved so the shape of the data stays visible; strings
+/// and numbers are replaced by [`hmac_field`]. Booleans and nulls are kept.
+pub fn hmac_json(key: &[u8], value: &serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        Value::String(s) => Value::String(hmac_field(key, s)),
+        Value::Number(n) => Value::String(hmac_field(key, &n.to_string())),
+        Value::Array(items) => Value::Array(items.iter().map(|v| hmac_json(key, v)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter().map(|(k, v)| (k.clone(), hmac_json(key, v))).collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Replacement for redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Field patterns redacted whatever the configuration
+pub const DEFAULT_SENSITIVE_FIELDS: &[&str] = &[
+    "password",
+    "*_password",
+    "secret",
+    "*_secret",
+    "token",
+    "*_token",
+    "api_key",
+    "private_key",
+    "authorization",
+    "ssn",
+];
+
+/// Which fields of a JSON value to redact
+///
+/// A rule is either a field name pattern, matched against the key of every
+/// field at any depth, or a JSON pointer naming one field by its path.
+/// Patterns and pointer segments are compared case-insensitively, and `*`
+/// in them matches any run of characters, so `*_mrn` covers `patient_mrn`
+/// and `/visits/*/notes` covers the notes of every visit.
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    names: Vec<String>,
+    pointers: Vec<Vec<String>>,
+}
+
+impl Default for Redactor {
+    /// The built-in sensitive fields only
+    fn default() -> Self {
+        Self::new(&crate::config::RedactionConfig::default())
+    }
+}
+
+impl Redactor {
+    /// The built-in sensitive fields plus the configured ones
+    pub fn new(config: &crate::config::RedactionConfig) -> Self {
+        let mut redactor = Redactor { names: Vec::new(), pointers: Vec::new() };
+        let rules = DEFAULT_SENSITIVE_FIELDS.iter().copied().chain(config.fields.iter().map(String::as_str));
+        for rule in rules {
+            match rule.strip_prefix('/') {
+                Some(pointer) => redactor.pointers.push(
+                    pointer
+                        .split('/')
+                        .map(|segment| segment.replace("~1", "/").replace("~0", "~").to_lowercase())
+                        .collect(),
+                ),
+                None => redactor.names.push(rule.to_lowercase()),
+            }
+        }
+        redactor
+    }
+
+    /// Whether a field with this key is redacted wherever it appears
+    pub fn is_sensitive(&self, field: &str) -> bool {
+        let field = field.to_lowercase();
+        self.names.iter().any(|pattern| wildcard_match(pattern, &field))
+    }
+
+    /// Copy of `value` with every matching field's value replaced by [`REDACTED`]
+    pub fn redact_json(&self, value: &serde_json::Value) -> serde_json::Value {
+        self.redact_at(value, &mut Vec::new())
+    }
+
+    fn redact_at(&self, value: &serde_json::Value, path: &mut Vec<String>) -> serde_json::Value {
+        use serde_json::Value;
+        match value {
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), self.redact_child(k.to_lowercase(), self.is_sensitive(k), v, path)))
+                    .collect(),
+            ),
+            // Array elements have no key, so only pointers can name them
+            Value::Array(items) => Value::Array(
+                items.iter()
+                    .enumerate()
+                    .map(|(i, v)| self.redact_child(i.to_string(), false, v, path))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    fn redact_child(
+        &self,
+        segment: String,
+        named: bool,
+        value: &serde_json::Value,
+        path: &mut Vec<String>,
+    ) -> serde_json::Value {
+        path.push(segment);
+        let redacted = if named || self.pointer_matches(path) {
+            serde_json::Value::String(REDACTED.to_string())
+        } else {
+            self.redact_at(value, path)
+        };
+        path.pop();
+        redacted
+    }
+
+    fn pointer_matches(&self, path: &[String]) -> bool {
+        self.pointers.iter().any(|pointer| {
+            pointer.len() == path.len()
+                && pointer.iter().zip(path).all(|(pattern, segment)| wildcard_match(pattern, segment))
+        })
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any run of characters
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut remaining) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let last = parts.pop().unwrap_or("");
+    for part in parts {
+        match remaining.find(part) {
+            Some(at) => remaining = &remaining[at + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.len() >= last.len() && remaining.ends_with(last)
+}
@@ -4,10 +4,16 @@ pub mod masking;
 pub mod app_state;
 pub mod request_context;
 pub mod audit;
+pub mod pagination;
+pub mod validated_json;
+pub mod password_policy;
 
 pub use error::{AppError, ErrorKind};
 pub use result::AppResult;
 pub use app_state::AppState;
 pub use request_context::RequestContext;
 pub use audit::{AuditFields, HasAuditFields, AuditContext};
+pub use pagination::{ListQuery, ListSpec, Page, SortDirection};
+pub use validated_json::ValidatedJson;
+pub use password_policy::PasswordPolicy;
 
@@ -0,0 +1,274 @@
+//! Shared list query and page envelope for list endpoints
+//!
+//! Query parameters are parsed once into a [`ListQuery`]:
+//! `?limit=50&cursor=...&sort=name&order=desc&filter[email]=a@b.c`.
+//! Each listing declares a [`ListSpec`] naming the fields it can sort and
+//! filter on, so unknown fields are rejected instead of reaching SQL.
+
+use crate::shared::{AppError, AppResult};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Page size used when `limit` is not given
+pub const LIST_LIMIT_DEFAULT: u32 = 50;
+/// Largest page size a client may request
+pub const LIST_LIMIT_MAX: u32 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        }
+    }
+}
+
+/// Sortable and filterable fields of a listing, as (API name, SQL column)
+#[derive(Debug, Clone, Copy)]
+pub struct ListSpec {
+    pub sortable: &'static [(&'static str, &'static str)],
+    pub filterable: &'static [(&'static str, &'static str)],
+    /// API name of the sort field used when `sort` is not given
+    pub default_sort: &'static str,
+    pub default_direction: SortDirection,
+}
+
+impl ListSpec {
+    pub fn sort_column(&self, field: &str) -> Option<&'static str> {
+        lookup(self.sortable, field)
+    }
+
+    pub fn filter_column(&self, field: &str) -> Option<&'static str> {
+        lookup(self.filterable, field)
+    }
+}
+
+fn lookup(fields: &[(&'static str, &'static str)], name: &str) -> Option<&'static str> {
+    fields.iter().find(|(api, _)| *api == name).map(|(_, column)| *column)
+}
+
+/// Pagination, sorting and filtering requested by a client
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListQuery {
+    pub limit: u32,
+    /// Opaque cursor from a previous page's `next_cursor`
+    pub cursor: Option<String>,
+    pub sort: Option<String>,
+    pub direction: Option<SortDirection>,
+    /// Exact-match filters keyed by API field name
+    pub filters: BTreeMap<String, String>,
+}
+
+impl Default for ListQuery {
+    fn default() -> Self {
+        Self {
+            limit: LIST_LIMIT_DEFAULT,
+            cursor: None,
+            sort: None,
+            direction: None,
+            filters: BTreeMap::new(),
+        }
+    }
+}
+
+impl ListQuery {
+    /// Parse raw query parameters
+    ///
+    /// Unknown parameters are ignored so endpoints can accept extra ones.
+    pub fn from_params(params: &HashMap<String, String>) -> AppResult<Self> {
+        let mut query = Self::default();
+        for (key, value) in params {
+            match key.as_str() {
+                "limit" => {
+                    let limit: u32 = value.parse().map_err(|_| {
+                        AppError::Validation(format!("limit must be a positive integer, got '{}'", value))
+                    })?;
+                    if limit == 0 || limit > LIST_LIMIT_MAX {
+                        return Err(AppError::Validation(format!(
+                            "limit must be between 1 and {}",
+                            LIST_LIMIT_MAX
+                        )));
+                    }
+                    query.limit = limit;
+                }
+                "cursor" if !value.is_empty() => query.cursor = Some(value.clone()),
+                "sort" if !value.is_empty() => query.sort = Some(value.clone()),
+                "order" => {
+                    query.direction = Some(match value.to_ascii_lowercase().as_str() {
+                        "asc" => SortDirection::Asc,
+                        "desc" => SortDirection::Desc,
+                        _ => {
+                            return Err(AppError::Validation(format!(
+                                "order must be 'asc' or 'desc', got '{}'",
+                                value
+                            )))
+                        }
+                    });
+                }
+                _ => {
+                    if let Some(field) = key.strip_prefix("filter[").and_then(|k| k.strip_suffix(']')) {
+                        query.filters.insert(field.to_string(), value.clone());
+                    }
+                }
+            }
+        }
+        // Catch a malformed cursor before any query runs
+        query.offset()?;
+        Ok(query)
+    }
+
+    /// Reject sort and filter fields the listing does not support
+    pub fn validate(&self, spec: &ListSpec) -> AppResult<()> {
+        if let Some(sort) = &self.sort {
+            if spec.sort_column(sort).is_none() {
+                return Err(AppError::Validation(format!("cannot sort by '{}'", sort)));
+            }
+        }
+        for field in self.filters.keys() {
+            if spec.filter_column(field).is_none() {
+                return Err(AppError::Validation(format!("cannot filter by '{}'", field)));
+            }
+        }
+        Ok(())
+    }
+
+    /// SQL column and direction to order by
+    pub fn order_by(&self, spec: &ListSpec) -> (&'static str, SortDirection) {
+        let column = self
+            .sort
+            .as_deref()
+            .and_then(|s| spec.sort_column(s))
+            .or_else(|| spec.sort_column(spec.default_sort))
+            .unwrap_or("id");
+        (column, self.direction.unwrap_or(spec.default_direction))
+    }
+
+    /// Start at `offset` rows, for callers that page by position
+    pub fn with_offset(mut self, offset: u64) -> Self {
+        self.cursor = (offset > 0).then(|| encode_cursor(offset));
+        self
+    }
+
+    /// Number of rows to skip, decoded from the cursor
+    pub fn offset(&self) -> AppResult<u64> {
+        match &self.cursor {
+            None => Ok(0),
+            Some(cursor) => decode_cursor(cursor)
+                .ok_or_else(|| AppError::Validation("invalid cursor".to_string())),
+        }
+    }
+}
+
+fn encode_cursor(offset: u64) -> String {
+    URL_SAFE_NO_PAD.encode(format!("o:{}", offset))
+}
+
+fn decode_cursor(cursor: &str) -> Option<u64> {
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    let text = String::from_utf8(bytes).ok()?;
+    text.strip_prefix("o:")?.parse().ok()
+}
+
+/// Response envelope for list endpoints
+#[derive(Debug, Clone, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Cursor for the next page, absent on the last page
+    pub next_cursor: Option<String>,
+    /// Rows matching the filters across all pages
+    pub total: i64,
+}
+
+impl<T> Page<T> {
+    /// Build a page of `items` fetched at `offset`
+    pub fn new(items: Vec<T>, offset: u64, total: i64) -> Self {
+        let end = offset + items.len() as u64;
+        let next_cursor = if !items.is_empty() && (end as i64) < total {
+            Some(encode_cursor(end))
+        } else {
+            None
+        };
+        Self { items, next_cursor, total }
+    }
+
+    pub fn map<U, F: FnMut(T) -> U>(self, f: F) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+            total: self.total,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: ListSpec = ListSpec {
+        sortable: &[("name", "name"), ("created_at", "created_at")],
+        filterable: &[("organization_id", "organization_id")],
+        default_sort: "name",
+        default_direction: SortDirection::Asc,
+    };
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_from_params_parses_all_fields() {
+        let query = ListQuery::from_params(&params(&[
+            ("limit", "20"),
+            ("sort", "created_at"),
+            ("order", "DESC"),
+            ("filter[organization_id]", "abc"),
+            ("unrelated", "x"),
+        ]))
+        .unwrap();
+        assert_eq!(query.limit, 20);
+        assert_eq!(query.order_by(&SPEC), ("created_at", SortDirection::Desc));
+        assert_eq!(query.filters.get("organization_id").map(String::as_str), Some("abc"));
+        assert!(query.validate(&SPEC).is_ok());
+
+        let default = ListQuery::from_params(&HashMap::new()).unwrap();
+        assert_eq!(default.limit, LIST_LIMIT_DEFAULT);
+        assert_eq!(default.order_by(&SPEC), ("name", SortDirection::Asc));
+    }
+
+    #[test]
+    fn test_limit_is_bounded() {
+        assert!(ListQuery::from_params(&params(&[("limit", "0")])).is_err());
+        assert!(ListQuery::from_params(&params(&[("limit", "-1")])).is_err());
+        let too_many = (LIST_LIMIT_MAX + 1).to_string();
+        assert!(ListQuery::from_params(&params(&[("limit", &too_many)])).is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_fields() {
+        let sort = ListQuery::from_params(&params(&[("sort", "password_hash")])).unwrap();
+        assert!(sort.validate(&SPEC).is_err());
+        let filter = ListQuery::from_params(&params(&[("filter[name); --]", "x")])).unwrap();
+        assert!(filter.validate(&SPEC).is_err());
+        assert!(ListQuery::from_params(&params(&[("order", "sideways")])).is_err());
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let first: Page<u32> = Page::new(vec![1, 2], 0, 5);
+        let cursor = first.next_cursor.clone().unwrap();
+        let query = ListQuery::from_params(&params(&[("cursor", &cursor)])).unwrap();
+        assert_eq!(query.offset().unwrap(), 2);
+
+        let last: Page<u32> = Page::new(vec![5], 4, 5);
+        assert!(last.next_cursor.is_none());
+        assert!(ListQuery::from_params(&params(&[("cursor", "not-a-cursor")])).is_err());
+    }
+}
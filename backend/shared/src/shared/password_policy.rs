@@ -0,0 +1,56 @@
+use crate::shared::{AppError, AppResult};
+
+/// bcrypt ignores everything past 72 bytes
+const BCRYPT_MAX_BYTES: usize = 72;
+
+/// Rules a new password must meet
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self { min_length: 8 }
+    }
+}
+
+impl PasswordPolicy {
+    /// `email` is the account's, which the password may not repeat
+    pub fn check(&self, password: &str, email: Option<&str>) -> AppResult<()> {
+        if password.chars().count() < self.min_length {
+            return Err(AppError::Validation(format!(
+                "Password must be at least {} characters",
+                self.min_length
+            )));
+        }
+        if password.len() > BCRYPT_MAX_BYTES {
+            return Err(AppError::Validation(format!(
+                "Password must be at most {} bytes",
+                BCRYPT_MAX_BYTES
+            )));
+        }
+        if password.trim().is_empty() {
+            return Err(AppError::Validation("Password must not be blank".to_string()));
+        }
+        if email.is_some_and(|email| password.eq_ignore_ascii_case(email)) {
+            return Err(AppError::Validation("Password must not be the email address".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_policy() {
+        let policy = PasswordPolicy::default();
+        assert!(policy.check("correct horse", Some("a@example.com")).is_ok());
+        assert!(policy.check("short", None).is_err());
+        assert!(policy.check("        ", None).is_err());
+        assert!(policy.check(&"x".repeat(73), None).is_err());
+        assert!(policy.check("A@Example.com", Some("a@example.com")).is_err());
+    }
+}
@@ -17,6 +17,8 @@ pub struct RequestContext {
     pub organization_id: Option<Uuid>,
     pub app_type: Option<String>,
     pub app_device: Option<String>,
+    /// API key the request authenticated with, if any
+    pub api_key_id: Option<Uuid>,
 }
 
 impl RequestContext {
@@ -38,6 +40,7 @@ impl RequestContext {
             organization_id: None,
             app_type: None,
             app_device: None,
+            api_key_id: None,
         }
     }
 
@@ -65,6 +68,11 @@ impl RequestContext {
         self.app_device = Some(app_device);
         self
     }
+
+    pub fn with_api_key(mut self, api_key_id: Uuid) -> Self {
+        self.api_key_id = Some(api_key_id);
+        self
+    }
     
     /// Create audit context from request context
     pub fn to_audit_context(&self, system_id: Option<String>) -> crate::shared::AuditContext {
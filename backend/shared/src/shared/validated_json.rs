@@ -0,0 +1,207 @@
+//! JSON body extractor that enforces DTO validation rules
+//!
+//! DTOs declare their rules with `#[derive(Validate)]` attributes and
+//! handlers take `ValidatedJson<T>` instead of `Json<T>`. Rule violations are
+//! answered with 422 before the handler runs:
+//!
+//! ```json
+//! {
+//!   "error": "Validation failed",
+//!   "code": "validation_failed",
+//!   "fields": { "email": ["must be a valid email address"] }
+//! }
+//! ```
+//!
+//! Field keys are the DTO's Rust field names; nested and list fields are
+//! joined with `.` and `[i]`. Bodies that are not valid JSON for `T` keep
+//! axum's status and get the usual `{"error": ...}` body.
+
+use std::collections::BTreeMap;
+use axum::extract::{FromRequest, Request};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::de::DeserializeOwned;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+use crate::shared::AppError;
+
+/// `Json<T>` that has passed `T::validate()`
+#[derive(Debug, Clone)]
+pub struct ValidatedJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(request, state).await.map_err(|rejection| {
+            (rejection.status(), Json(serde_json::json!({ "error": rejection.body_text() }))).into_response()
+        })?;
+        value.validate().map_err(|errors| validation_error_response(&errors))?;
+        Ok(Self(value))
+    }
+}
+
+/// 422 response listing every failed rule by field
+pub fn validation_error_response(errors: &ValidationErrors) -> Response {
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        Json(serde_json::json!({
+            "error": "Validation failed",
+            "code": "validation_failed",
+            "fields": field_errors(errors),
+        })),
+    )
+        .into_response()
+}
+
+/// Flatten validation errors into `field -> messages`
+pub fn field_errors(errors: &ValidationErrors) -> BTreeMap<String, Vec<String>> {
+    let mut fields = BTreeMap::new();
+    collect(errors, "", &mut fields);
+    fields
+}
+
+fn collect(errors: &ValidationErrors, prefix: &str, fields: &mut BTreeMap<String, Vec<String>>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
+        };
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                fields.entry(path).or_default().extend(errors.iter().map(describe));
+            }
+            ValidationErrorsKind::Struct(nested) => collect(nested, &path, fields),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect(nested, &format!("{}[{}]", path, index), fields);
+                }
+            }
+        }
+    }
+}
+
+/// The rule's message, or a generic one derived from its code
+fn describe(error: &ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+    match error.code.as_ref() {
+        "email" => "must be a valid email address".to_string(),
+        "url" => "must be a valid URL".to_string(),
+        "required" => "is required".to_string(),
+        code => format!("failed the '{}' rule", code),
+    }
+}
+
+/// Rejects values that are empty once trimmed
+pub fn not_blank(value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        let mut error = ValidationError::new("not_blank");
+        error.message = Some("must not be blank".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// Rejects values that are not a hyphenated or simple UUID
+pub fn uuid(value: &str) -> Result<(), ValidationError> {
+    if uuid::Uuid::parse_str(value).is_err() {
+        let mut error = ValidationError::new("uuid");
+        error.message = Some("must be a UUID".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        let summary = field_errors(&errors)
+            .into_iter()
+            .map(|(field, messages)| format!("{} {}", field, messages.join(", ")))
+            .collect::<Vec<_>>()
+            .join("; ");
+        AppError::Validation(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, serde::Deserialize, Validate)]
+    struct Signup {
+        #[validate(email)]
+        email: String,
+        #[validate(length(min = 8, message = "must be at least 8 characters"))]
+        password: String,
+        #[validate(custom(function = "not_blank"))]
+        name: String,
+        #[validate(custom(function = "uuid"))]
+        organization_id: String,
+    }
+
+    fn signup(email: &str, password: &str, name: &str, organization_id: &str) -> Signup {
+        Signup {
+            email: email.to_string(),
+            password: password.to_string(),
+            name: name.to_string(),
+            organization_id: organization_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_field_errors_are_keyed_by_field() {
+        let errors = signup("not-an-email", "short", "   ", "42").validate().unwrap_err();
+        let fields = field_errors(&errors);
+
+        assert_eq!(fields["email"], vec!["must be a valid email address"]);
+        assert_eq!(fields["password"], vec!["must be at least 8 characters"]);
+        assert_eq!(fields["name"], vec!["must not be blank"]);
+        assert_eq!(fields["organization_id"], vec!["must be a UUID"]);
+    }
+
+    #[test]
+    fn test_valid_input_passes() {
+        let valid = signup("a@example.com", "long enough", "Ada", "8c1f6a8e-6f0e-4d3c-9a39-3b1a4b9a2f10");
+        assert!(valid.validate().is_ok());
+    }
+
+    #[test]
+    fn test_converts_to_validation_app_error() {
+        let errors = signup("a@example.com", "short", "Ada", "8c1f6a8e-6f0e-4d3c-9a39-3b1a4b9a2f10")
+            .validate()
+            .unwrap_err();
+        match AppError::from(errors) {
+            AppError::Validation(message) => assert_eq!(message, "password must be at least 8 characters"),
+            other => panic!("expected validation error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extractor_rejects_with_422() {
+        let body = serde_json::json!({
+            "email": "nope",
+            "password": "long enough",
+            "name": "Ada",
+            "organization_id": "8c1f6a8e-6f0e-4d3c-9a39-3b1a4b9a2f10",
+        });
+        let request = Request::builder()
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap();
+
+        let response = ValidatedJson::<Signup>::from_request(request, &()).await.unwrap_err();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["code"], "validation_failed");
+        assert_eq!(json["fields"]["email"][0], "must be a valid email address");
+    }
+}
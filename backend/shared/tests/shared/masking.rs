@@ -21,3 +21,50 @@ fn test_mask_ssn() {
     assert_eq!(mask_ssn("123-45-6789"), "***-**-6789");
 }
 
+
+#[test]
+fn test_hmac_field() {
+    let hashed = hmac_field(b"key", "s3cr3t");
+    assert!(hashed.starts_with(HMAC_PREFIX));
+    assert!(!hashed.contains("s3cr3t"));
+    assert_eq!(hashed, hmac_field(b"key", "s3cr3t"));
+    assert_ne!(hashed, hmac_field(b"other", "s3cr3t"));
+}
+
+#[test]
+fn test_hmac_json_keeps_structure() {
+    let value = serde_json::json!({"password": "p", "nested": {"n": 1, "ok": true}});
+    let hashed = hmac_json(b"key", &value);
+    assert_eq!(hashed["password"], serde_json::json!(hmac_field(b"key", "p")));
+    assert_eq!(hashed["nested"]["n"], serde_json::json!(hmac_field(b"key", "1")));
+    assert_eq!(hashed["nested"]["ok"], serde_json::json!(true));
+}
+
+#[test]
+fn test_redactor_applies_built_in_and_configured_rules() {
+    let config = shared::config::RedactionConfig {
+        fields: vec!["*_MRN".to_string(), "dob".to_string(), "/visits/*/notes".to_string()],
+    };
+    let redactor = Redactor::new(&config);
+    let value = serde_json::json!({
+        "name": "Ada",
+        "patient_mrn": "MRN-1",
+        "contact": {"DOB": "1815-12-10", "db_password": "p"},
+        "visits": [{"notes": "private", "ward": "B"}],
+        "notes": "public",
+    });
+
+    let redacted = redactor.redact_json(&value);
+    assert_eq!(redacted["name"], "Ada");
+    assert_eq!(redacted["patient_mrn"], REDACTED);
+    assert_eq!(redacted["contact"]["DOB"], REDACTED);
+    assert_eq!(redacted["contact"]["db_password"], REDACTED);
+    assert_eq!(redacted["visits"][0]["notes"], REDACTED);
+    assert_eq!(redacted["visits"][0]["ward"], "B");
+    assert_eq!(redacted["notes"], "public");
+
+    // Without configuration only the built-in set applies
+    let built_in = Redactor::default().redact_json(&value);
+    assert_eq!(built_in["patient_mrn"], "MRN-1");
+    assert_eq!(built_in["contact"]["db_password"], REDACTED);
+}
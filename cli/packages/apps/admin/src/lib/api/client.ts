@@ -3,7 +3,13 @@
  * Re-export from shared package with admin-specific overrides if needed
  */
 
-import { API_ROUTES, API_CONFIG as SHARED_API_CONFIG, getApiUrl } from "@health-v1/shared/api";
+import {
+  API_ROUTES,
+  API_CONFIG as SHARED_API_CONFIG,
+  csrfHeaders,
+  getApiUrl,
+  rememberCsrfToken,
+} from "@health-v1/shared/api";
 import { env } from "../env";
 
 // Use shared config but allow admin-specific env overrides
@@ -29,6 +35,7 @@ export async function apiRequest<T>(path: string, options: RequestInit = {}): Pr
     "Content-Type": "application/json",
     "X-App-Type": "admin-ui",
     "X-App-Device": "web",
+    ...csrfHeaders(options.method),
     ...options.headers,
   };
 
@@ -42,6 +49,7 @@ export async function apiRequest<T>(path: string, options: RequestInit = {}): Pr
     });
 
     clearTimeout(timeoutId);
+    rememberCsrfToken(response);
 
     if (!response.ok) {
       const error = await response.json().catch(() => ({
@@ -3,13 +3,14 @@
  */
 
 import { API_ROUTES, apiRequest } from "./client";
-import type { ApiResponse } from "./types";
+import type { ApiResponse, Page } from "./types";
 
 export interface Group {
   id: string;
   name: string;
   description?: string;
   organization_id?: string;
+  version: number;
 }
 
 export interface CreateGroupRequest {
@@ -21,13 +22,14 @@ export interface CreateGroupRequest {
 export interface UpdateGroupRequest {
   name?: string;
   description?: string;
-  organization_id?: string;
+  /** Version the edit is based on; a stale version is rejected with 409 */
+  version: number;
 }
 
 /**
  * List all groups
  */
-export async function listGroups(): Promise<ApiResponse<{ groups: Group[] }>> {
+export async function listGroups(): Promise<ApiResponse<Page<Group>>> {
   return apiRequest(API_ROUTES.ADMIN.GROUPS.LIST);
 }
 
@@ -3,13 +3,14 @@
  */
 
 import { API_ROUTES, apiRequest } from "./client";
-import type { ApiResponse } from "./types";
+import type { ApiResponse, Page } from "./types";
 
 export interface Role {
   id: string;
   name: string;
   description?: string;
   permissions?: string[];
+  version: number;
 }
 
 export interface CreateRoleRequest {
@@ -18,8 +19,9 @@ export interface CreateRoleRequest {
 }
 
 export interface UpdateRoleRequest {
-  name?: string;
   description?: string;
+  /** Version the edit is based on; a stale version is rejected with 409 */
+  version: number;
 }
 
 export interface AssignRoleRequest {
@@ -30,7 +32,7 @@ export interface AssignRoleRequest {
 /**
  * List all roles
  */
-export async function listRoles(): Promise<ApiResponse<{ roles: Role[] }> | ApiResponse<Role[]>> {
+export async function listRoles(): Promise<ApiResponse<Page<Role>>> {
   return apiRequest(API_ROUTES.ADMIN.ROLES.LIST);
 }
 
@@ -8,6 +8,7 @@ export type {
   ApiResponse,
   LoginRequest,
   LoginResponse,
+  Page,
   RefreshTokenRequest,
   RefreshTokenResponse,
   ServiceInfo,
@@ -33,10 +33,8 @@ export function GroupsPage() {
   });
 
   // Normalize groups to always be an array
-  const groups: Group[] = Array.isArray(groupsResponse?.data?.groups)
-    ? groupsResponse.data.groups
-    : Array.isArray(groupsResponse?.data)
-    ? groupsResponse.data
+  const groups: Group[] = Array.isArray(groupsResponse?.data?.items)
+    ? groupsResponse.data.items
     : [];
 
   const deleteMutation = useMutation({
@@ -32,10 +32,7 @@ export function RolesPage() {
     queryFn: listRoles,
   });
 
-  // Handle both { roles: [] } and [] response formats
-  const roles = Array.isArray(rolesResponse?.data) 
-    ? rolesResponse.data 
-    : rolesResponse?.data?.roles || [];
+  const roles = rolesResponse?.data?.items || [];
 
 
   const deleteMutation = useMutation({
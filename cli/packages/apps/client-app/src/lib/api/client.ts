@@ -4,6 +4,7 @@
  */
 
 import { API_CONFIG } from "@health-v1/shared/api/config";
+import { rememberCsrfToken } from "@health-v1/shared/api/csrf";
 import { API_ROUTES } from "@health-v1/shared/api/routes";
 const API_BASE_URL = API_CONFIG.BASE_URL;
 const API_TIMEOUT = API_CONFIG.TIMEOUT;
@@ -61,6 +62,7 @@ export class ApiClient {
       });
 
       clearTimeout(timeoutId);
+      rememberCsrfToken(response);
 
       // Handle non-OK responses
       if (!response.ok) {
@@ -3,6 +3,7 @@
  * Request and response interceptors for security, audit logging, and error handling
  */
 
+import { csrfHeaders } from "@health-v1/shared/api/csrf";
 import { SECURITY_CONFIG } from "@health-v1/shared/constants/security";
 import { maskObject } from "./masking";
 import type { ApiResponse, RequestConfig } from "./types";
@@ -87,6 +88,9 @@ export async function requestInterceptor(
   headers["X-App-Type"] = "client-ui";
   headers["X-App-Device"] = detectDeviceType();
 
+  // Echo the CSRF token on state-changing requests
+  Object.assign(headers, csrfHeaders(config.method));
+
   return {
     ...config,
     headers,
@@ -10,9 +10,13 @@ export const VAULT_ROUTES = {
   // System routes
   SYS: {
     HEALTH: '/sys/health',
+    CRYPTO_SELFTEST: '/sys/health/crypto-selftest',
     SEAL_STATUS: '/sys/seal-status',
     SEAL: '/sys/seal',
     UNSEAL: '/sys/unseal',
+    READ_ONLY: '/sys/read-only',
+    GENERATE_ROOT_ATTEMPT: '/sys/generate-root/attempt',
+    GENERATE_ROOT_UPDATE: '/sys/generate-root/update',
     INIT: '/sys/init',
     MOUNTS: '/sys/mounts',
     MOUNT: (path: string) => `/sys/mounts/${path}`,
@@ -29,6 +33,7 @@ export const VAULT_ROUTES = {
     TOKEN_CREATE: '/auth/token/create',
     TOKEN_LOOKUP: '/auth/token/lookup',
     TOKEN_LOOKUP_SELF: '/auth/token/lookup-self',
+    TOKEN_LIST: '/auth/token/list',
     TOKEN_RENEW: '/auth/token/renew',
     TOKEN_RENEW_SELF: '/auth/token/renew-self',
     TOKEN_REVOKE: '/auth/token/revoke',
@@ -43,6 +48,7 @@ export const VAULT_ROUTES = {
     UPDATE: (name: string) => `/sys/policies/acl/${name}`,
     DELETE: (name: string) => `/sys/policies/acl/${name}`,
     CAPABILITIES: '/sys/capabilities',
+    LINT: '/sys/policy/lint',
   },
 
   // Secret routes (KV v1)
@@ -51,6 +57,7 @@ export const VAULT_ROUTES = {
     WRITE: (path: string) => `/secret/${path}`,
     DELETE: (path: string) => `/secret/${path}`,
     LIST: (path: string = '') => path ? `/secret/${path}/` : '/secret/',
+    BATCH_READ: '/sys/secrets/batch-read',
   },
 
   // Realm routes
@@ -0,0 +1,32 @@
+/**
+ * CSRF token handling for cookie-authenticated requests
+ * The API hands out a token in the X-CSRF-Token response header (its cookie
+ * lives on the API origin, out of reach of the UI); state-changing requests
+ * must echo it back in the same header.
+ */
+
+export const CSRF_HEADER = "X-CSRF-Token";
+
+const SAFE_METHODS = ["GET", "HEAD", "OPTIONS"];
+
+let csrfToken: string | null = null;
+
+/**
+ * Keep the token from a response, if it carries one
+ */
+export function rememberCsrfToken(response: Response): void {
+  const token = response.headers.get(CSRF_HEADER);
+  if (token) {
+    csrfToken = token;
+  }
+}
+
+/**
+ * Headers to add for a request with the given method
+ */
+export function csrfHeaders(method: string | undefined): Record<string, string> {
+  if (!csrfToken || SAFE_METHODS.includes((method || "GET").toUpperCase())) {
+    return {};
+  }
+  return { [CSRF_HEADER]: csrfToken };
+}
@@ -5,3 +5,4 @@
 export * from "./types";
 export * from "./routes";
 export * from "./config";
+export * from "./csrf";
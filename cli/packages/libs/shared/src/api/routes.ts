@@ -17,6 +17,10 @@ export const API_ROUTES = {
     LOGOUT: "/v1/auth/logout",
     REFRESH: "/v1/auth/token",
     USERINFO: "/v1/auth/userinfo",
+    PASSWORD_RESET: "/v1/auth/password-reset",
+    PASSWORD_RESET_CONFIRM: "/v1/auth/password-reset/confirm",
+    EMAIL_CHANGE: "/v1/auth/email-change",
+    EMAIL_CHANGE_CONFIRM: "/v1/auth/email-change/confirm",
   },
 
   // Setup (versioned, gets /api prefix)
@@ -32,6 +36,7 @@ export const API_ROUTES = {
     CREATE: "/v1/users",
     UPDATE: (id: string) => `/v1/users/${id}`,
     DELETE: (id: string) => `/v1/users/${id}`,
+    DEACTIVATE: (id: string) => `/v1/users/${id}/deactivate`,
   },
 
   // Organizations (versioned, gets /api prefix)
@@ -78,10 +83,14 @@ export const API_ROUTES = {
       CHECK_BATCH: "/v1/admin/permissions/check-batch",
       USER: (id: string) => `/v1/admin/permissions/user/${id}`,
       USER_PAGES: (id: string) => `/v1/admin/permissions/user/${id}/pages`,
+      USER_UI: (id: string) => `/v1/admin/permissions/user/${id}/ui`,
       USER_BUTTONS: (id: string, page: string) => `/v1/admin/permissions/user/${id}/buttons/${page}`,
       USER_FIELDS: (id: string, page: string) => `/v1/admin/permissions/user/${id}/fields/${page}`,
       ASSIGN: "/v1/admin/permissions/assign",
       ASSIGN_BATCH: "/v1/admin/permissions/assign-batch",
+      BULK: "/v1/admin/permissions/bulk",
+      SCHEDULED: "/v1/admin/permissions/scheduled",
+      WINDOW: (id: string) => `/v1/admin/permissions/${id}/window`,
       REVOKE: "/v1/admin/permissions/revoke",
     },
     UI: {
@@ -91,6 +100,10 @@ export const API_ROUTES = {
       BUTTONS: "/v1/admin/ui/buttons",
       FIELDS: "/v1/admin/ui/fields",
       APIS: "/v1/admin/ui/apis",
+      HISTORY: (kind: "pages" | "buttons" | "fields" | "apis", id: string) =>
+        `/v1/admin/ui/${kind}/${id}/history`,
+      RESTORE: (kind: "pages" | "buttons" | "fields" | "apis", id: string, version: number) =>
+        `/v1/admin/ui/${kind}/${id}/versions/${version}/restore`,
     },
     GROUPS: {
       LIST: "/v1/admin/groups",
@@ -105,6 +118,25 @@ export const API_ROUTES = {
     DASHBOARD: {
       STATS: "/v1/admin/dashboard/stats",
     },
+    CONFIG: {
+      RELOAD: "/v1/admin/config/reload",
+    },
+    GRAPH: {
+      STATS: "/v1/admin/graph/stats",
+      REFRESH: "/v1/admin/graph/refresh",
+      INVALIDATE: "/v1/admin/graph/invalidate",
+      PATHS: "/v1/admin/graph/paths",
+      EXPLAIN: "/v1/admin/graph/explain",
+    },
+    AUTHORIZATION: {
+      DIFF: "/v1/admin/authorization/diff",
+    },
+    REQUEST_LOGS: {
+      LIST: "/v1/admin/request-logs",
+    },
+    LOGIN_EVENTS: {
+      LIST: "/v1/admin/login-events",
+    },
     ROLES: {
       LIST: "/v1/admin/roles",
       GET: (id: string) => `/v1/admin/roles/${id}`,
@@ -17,6 +17,16 @@ export interface ApiResponse<T> {
   message?: string;
 }
 
+/**
+ * Envelope returned by list endpoints.
+ * Pass `next_cursor` back as `cursor` to fetch the following page.
+ */
+export interface Page<T> {
+  items: T[];
+  next_cursor?: string | null;
+  total: number;
+}
+
 // Authentication types
 export interface LoginRequest {
   email: string;
@@ -73,4 +83,15 @@ export interface ServiceStatusResponse {
   services: ServiceInfo[];
   overallStatus: string; // "operational", "degraded", "down", "unknown"
   checkedAt: string;
+  concurrency?: ConcurrencySnapshot;
+}
+
+export interface ConcurrencyLimit {
+  inFlight: number;
+  maxInFlight: number; // 0 when unbounded
+}
+
+export interface ConcurrencySnapshot extends ConcurrencyLimit {
+  rejected: number;
+  routes: Record<string, ConcurrencyLimit>;
 }